@@ -229,6 +229,7 @@ impl NodeArgs {
             .with_init_state(self.load_state.or_else(|| self.state.and_then(|s| s.state)))
             .with_transaction_block_keeper(self.transaction_block_keeper)
             .with_optimism(self.evm_opts.optimism)
+            .with_zksync_l1_chain_id(self.evm_opts.zksync_l1_chain_id)
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -264,6 +265,11 @@ impl NodeArgs {
     ///
     /// See also [crate::spawn()]
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.evm_opts.zksync {
+            let (_, handle) = crate::zksync::spawn(self.into_node_config()).await;
+            return Ok(handle.await??)
+        }
+
         let dump_state = self.dump_state_path();
         let dump_interval =
             self.state_interval.map(Duration::from_secs).unwrap_or(DEFAULT_DUMP_INTERVAL);
@@ -503,6 +509,58 @@ pub struct AnvilEvmArgs {
     /// Run an Optimism chain
     #[clap(long, visible_alias = "optimism")]
     pub optimism: bool,
+
+    /// Run a zkSync Era chain
+    ///
+    /// The node boots the system contracts and the bootloader, executes transactions in EraVM,
+    /// accepts EIP-712 transactions and serves the `zks_` namespace. It has the chain id of
+    /// era_test_node and mines a block per transaction, so the flags of the chain id, of the
+    /// mining mode and of the fork can't be set.
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "fork_url",
+            "fork_block_number",
+            "fork_chain_id",
+            "fork_headers",
+            "timeout",
+            "retries",
+            "fork_retry_backoff",
+            "compute_units_per_second",
+            "no_rate_limit",
+            "no_storage_caching",
+            "chain_id",
+            "block-time",
+            "no_mining",
+            "order",
+            "hardfork",
+            "timestamp",
+            "gas_limit",
+            "disable_block_gas_limit",
+            "code_size_limit",
+            "gas_price",
+            "block_base_fee_per_gas",
+            "steps_tracing",
+            "auto_impersonate",
+            "optimism",
+            "init",
+            "state",
+            "state_interval",
+            "load_state",
+            "dump_state",
+            "prune_history",
+            "transaction_block_keeper",
+            "ipc"
+        ]
+    )]
+    pub zksync: bool,
+
+    /// The chain id of the L1 of the zkSync Era chain, returned by `zks_L1ChainId`
+    ///
+    /// The node isn't connected to an L1. This defaults to the chain id of a default Ethereum
+    /// node, so that an `anvil` running next to it can stand in for the L1.
+    #[clap(long, value_name = "CHAIN_ID", requires = "zksync")]
+    pub zksync_l1_chain_id: Option<u64>,
 }
 
 /// Resolves an alias passed as fork-url to the matching url defined in the rpc_endpoints section
@@ -754,6 +812,31 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn can_parse_zksync() {
+        let args: NodeArgs = NodeArgs::parse_from(["anvil", "--zksync"]);
+        assert!(args.evm_opts.zksync);
+
+        let args =
+            NodeArgs::try_parse_from(["anvil", "--zksync", "--fork-url", "http://localhost:8545"]);
+        assert!(args.is_err());
+
+        for flags in [
+            &["--chain-id", "1"][..],
+            &["--block-time", "1"],
+            &["--no-mining"],
+            &["--fork-block-number", "1"],
+            &["--hardfork", "shanghai"],
+        ] {
+            let args = NodeArgs::try_parse_from(["anvil", "--zksync"].iter().chain(flags));
+            assert!(args.is_err(), "{flags:?}");
+        }
+
+        let args = NodeArgs::parse_from(["anvil", "--zksync", "--zksync-l1-chain-id", "1"]);
+        assert_eq!(args.into_node_config().zksync_l1_chain_id, 1);
+        assert!(NodeArgs::try_parse_from(["anvil", "--zksync-l1-chain-id", "1"]).is_err());
+    }
+
     #[test]
     fn can_parse_host() {
         let args = NodeArgs::parse_from(["anvil"]);
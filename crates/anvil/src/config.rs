@@ -76,7 +76,7 @@ pub const VERSION_MESSAGE: &str = concat!(
     ")"
 );
 
-const BANNER: &str = r"
+pub(crate) const BANNER: &str = r"
                              _   _
                             (_) | |
       __ _   _ __   __   __  _  | |
@@ -170,6 +170,8 @@ pub struct NodeConfig {
     pub disable_default_create2_deployer: bool,
     /// Enable Optimism deposit transaction
     pub enable_optimism: bool,
+    /// The chain id returned by `zks_L1ChainId` by the zkSync Era node
+    pub zksync_l1_chain_id: u64,
 }
 
 impl NodeConfig {
@@ -404,6 +406,7 @@ impl Default for NodeConfig {
             transaction_block_keeper: None,
             disable_default_create2_deployer: false,
             enable_optimism: false,
+            zksync_l1_chain_id: CHAIN_ID,
         }
     }
 }
@@ -796,6 +799,13 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the chain id returned by `zks_L1ChainId` by the zkSync Era node
+    #[must_use]
+    pub fn with_zksync_l1_chain_id(mut self, l1_chain_id: Option<u64>) -> Self {
+        self.zksync_l1_chain_id = l1_chain_id.unwrap_or(CHAIN_ID);
+        self
+    }
+
     /// Configures everything related to env, backend and database and returns the
     /// [Backend](mem::Backend)
     ///
@@ -62,6 +62,8 @@ pub mod server;
 mod shutdown;
 /// additional task management
 mod tasks;
+/// local zkSync Era node
+pub mod zksync;
 
 /// contains cli command
 #[cfg(feature = "cmd")]
@@ -0,0 +1,267 @@
+//! A local zkSync Era node, launched with `anvil --zksync`.
+//!
+//! The node is the in-memory node of era_test_node: it boots the system contracts and the
+//! bootloader, executes the transactions in EraVM, including EIP-712 transactions, and serves the
+//! `eth_` and `zks_` namespaces of an Era chain. Its JSON-RPC API is served by the same axum
+//! server as the one of the Ethereum node.
+
+use crate::{
+    config::BANNER,
+    eth::api::CLIENT_VERSION,
+    init_tracing,
+    server::error::{NodeError, NodeResult},
+    NodeConfig, VERSION_MESSAGE,
+};
+use alloy_primitives::{hex, utils::format_ether, Address, U256, U64};
+use alloy_signer::Signer;
+use anvil_rpc::{error::RpcError, response::ResponseResult};
+use anvil_server::RpcHandler;
+use era_test_node::{
+    http_fork_source::HttpForkSource,
+    namespaces::{
+        ConfigurationApiNamespaceT, DebugNamespaceT, EthNamespaceT, EvmNamespaceT,
+        HardhatNamespaceT, NetNamespaceT, ZksNamespaceT,
+    },
+    node::InMemoryNode,
+};
+use futures::{FutureExt, TryFutureExt};
+use jsonrpc_core::IoHandler;
+use serde_json::{json, Value};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::task::{JoinError, JoinHandle};
+use yansi::Paint;
+
+/// A `RpcHandler` that executes the rpc calls with the namespaces of the Era node
+#[derive(Clone)]
+pub struct ZkRpcHandler {
+    io: Arc<IoHandler>,
+}
+
+// === impl ZkRpcHandler ===
+
+impl ZkRpcHandler {
+    /// Creates a new instance of the handler serving the namespaces of the given node
+    ///
+    /// The node is not connected to an L1, `zks_L1ChainId` returns the given `l1_chain_id`, by
+    /// default the chain id of a default Ethereum node so that an `anvil` running next to it can
+    /// stand in for the L1.
+    pub fn new(node: InMemoryNode<HttpForkSource>, l1_chain_id: u64) -> Self {
+        let mut io = IoHandler::new();
+        io.extend_with(EthNamespaceT::to_delegate(node.clone()));
+        io.extend_with(NetNamespaceT::to_delegate(node.clone()));
+        io.extend_with(ConfigurationApiNamespaceT::to_delegate(node.clone()));
+        io.extend_with(DebugNamespaceT::to_delegate(node.clone()));
+        io.extend_with(EvmNamespaceT::to_delegate(node.clone()));
+        io.extend_with(HardhatNamespaceT::to_delegate(node.clone()));
+        io.extend_with(ZksNamespaceT::to_delegate(node));
+        io.add_sync_method("web3_clientVersion", |_| Ok(Value::from(CLIENT_VERSION)));
+        io.add_sync_method("zks_L1ChainId", move |_| {
+            Ok(Value::String(format!("{l1_chain_id:#x}")))
+        });
+        Self { io: Arc::new(io) }
+    }
+
+    /// Executes a `{ "method" : "<name>", "params": "<params>" }` call
+    pub async fn execute(&self, mut call: Value) -> ResponseResult {
+        call["jsonrpc"] = "2.0".into();
+        call["id"] = 1.into();
+        if call["params"].is_null() {
+            call["params"] = json!([]);
+        }
+
+        let Some(response) = self.io.handle_request(&call.to_string()).await else {
+            return RpcError::invalid_request().into()
+        };
+        let mut response = match serde_json::from_str::<Value>(&response) {
+            Ok(response) => response,
+            Err(err) => return RpcError::internal_error_with(err.to_string()).into(),
+        };
+        match response.get_mut("error") {
+            Some(error) => match serde_json::from_value::<RpcError>(error.take()) {
+                Ok(error) => error.into(),
+                Err(err) => RpcError::internal_error_with(err.to_string()).into(),
+            },
+            None => ResponseResult::Success(response["result"].take()),
+        }
+    }
+
+    /// Returns the chain id of the node
+    async fn chain_id(&self) -> Result<u64, RpcError> {
+        match self.execute(json!({ "method": "eth_chainId" })).await {
+            ResponseResult::Success(chain_id) => serde_json::from_value::<U64>(chain_id)
+                .map(|chain_id| chain_id.to::<u64>())
+                .map_err(|err| RpcError::internal_error_with(err.to_string())),
+            ResponseResult::Error(err) => Err(err),
+        }
+    }
+
+    /// Sets the balance of the given account
+    async fn set_balance(&self, address: Address, balance: U256) -> ResponseResult {
+        self.execute(json!({
+            "method": "hardhat_setBalance",
+            "params": [address, format!("{balance:#x}")],
+        }))
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcHandler for ZkRpcHandler {
+    type Request = Value;
+
+    async fn on_request(&self, request: Self::Request) -> ResponseResult {
+        self.execute(request).await
+    }
+}
+
+/// Creates the Era node and runs the server
+///
+/// The genesis accounts of the config are funded with its `genesis_balance`. The node has the
+/// chain id of era_test_node and the L1 chain id of the config, and mines a block per
+/// transaction: the chain id, the mining mode, the fork and the other Ethereum-specific options
+/// of the config are ignored, `anvil --zksync` rejects their flags.
+///
+/// # Panics
+///
+/// Panics if the chain id of the node can't be read or a genesis account can't be funded
+pub async fn spawn(config: NodeConfig) -> (ZkRpcHandler, ZkNodeHandle) {
+    let logger = if config.enable_tracing { init_tracing() } else { Default::default() };
+    logger.set_enabled(!config.silent);
+
+    let handler = ZkRpcHandler::new(InMemoryNode::default(), config.zksync_l1_chain_id);
+    let chain_id = match handler.chain_id().await {
+        Ok(chain_id) => chain_id,
+        Err(err) => panic!("failed to get the chain id of the node: {}", err.message),
+    };
+
+    for wallet in &config.genesis_accounts {
+        if let ResponseResult::Error(err) =
+            handler.set_balance(wallet.address(), config.genesis_balance).await
+        {
+            panic!("failed to fund genesis account {}: {}", wallet.address(), err.message);
+        }
+    }
+
+    let mut servers = Vec::new();
+    let mut addresses = Vec::new();
+
+    for addr in config.host.iter() {
+        let sock_addr = SocketAddr::new(addr.to_owned(), config.port);
+        let srv =
+            anvil_server::serve_http(sock_addr, config.server_config.clone(), handler.clone());
+
+        addresses.push(srv.local_addr());
+
+        // spawn the server on a new task
+        let srv = tokio::task::spawn(srv.map_err(NodeError::from));
+        servers.push(srv);
+    }
+
+    let handle = ZkNodeHandle { config, chain_id, addresses, servers };
+    handle.print();
+
+    (handler, handle)
+}
+
+/// A handle to the spawned Era node and server tasks
+///
+/// This future will resolve if any of the server tasks resolve/fail.
+pub struct ZkNodeHandle {
+    config: NodeConfig,
+    /// The chain id of the node
+    chain_id: u64,
+    /// The address of the running rpc server
+    addresses: Vec<SocketAddr>,
+    /// Join handles (one per socket) for the Anvil server.
+    pub servers: Vec<JoinHandle<NodeResult<()>>>,
+}
+
+impl ZkNodeHandle {
+    /// The [NodeConfig] the node was launched with
+    pub fn config(&self) -> &NodeConfig {
+        &self.config
+    }
+
+    /// The chain id of the node, the one of era_test_node rather than the one of the config
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// The address of the launched server
+    ///
+    /// See [crate::NodeHandle::socket_address()]
+    pub fn socket_address(&self) -> &SocketAddr {
+        &self.addresses[0]
+    }
+
+    /// Returns the http endpoint
+    pub fn http_endpoint(&self) -> String {
+        format!("http://{}", self.socket_address())
+    }
+
+    /// Accounts that are funded with `genesis_balance` on launch
+    pub fn genesis_accounts(&self) -> impl Iterator<Item = Address> + '_ {
+        self.config.genesis_accounts.iter().map(|w| w.address())
+    }
+
+    /// Native token balance of every genesis account on launch
+    pub fn genesis_balance(&self) -> U256 {
+        self.config.genesis_balance
+    }
+
+    /// Prints the launch info
+    fn print(&self) {
+        if self.config.silent {
+            return
+        }
+
+        println!("\n{}", Paint::green(BANNER));
+        println!("    {VERSION_MESSAGE}");
+        println!("    {}", Paint::green("https://github.com/foundry-rs/foundry"));
+
+        println!("\nAvailable Accounts\n==================\n");
+        let balance = format_ether(self.config.genesis_balance);
+        for (idx, wallet) in self.config.genesis_accounts.iter().enumerate() {
+            println!("({idx}) {} ({balance} ETH)", wallet.address());
+        }
+
+        println!("\nPrivate Keys\n==================\n");
+        for (idx, wallet) in self.config.genesis_accounts.iter().enumerate() {
+            println!("({idx}) 0x{}", hex::encode(wallet.signer().to_bytes()));
+        }
+
+        println!("\nChain ID\n==================\n\n{}", Paint::green(self.chain_id));
+        println!(
+            "\nL1 Chain ID\n==================\n\n{}\n",
+            Paint::green(self.config.zksync_l1_chain_id)
+        );
+
+        println!(
+            "Listening on {}",
+            self.addresses.iter().map(|addr| addr.to_string()).collect::<Vec<_>>().join(", ")
+        );
+    }
+}
+
+impl Future for ZkNodeHandle {
+    type Output = Result<NodeResult<()>, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pin = self.get_mut();
+
+        // poll the axum server handles
+        for server in pin.servers.iter_mut() {
+            if let Poll::Ready(res) = server.poll_unpin(cx) {
+                return Poll::Ready(res)
+            }
+        }
+
+        Poll::Pending
+    }
+}
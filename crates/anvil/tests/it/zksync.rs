@@ -0,0 +1,186 @@
+//! tests for `anvil --zksync`
+
+use crate::utils::ethers_http_provider;
+use alloy_primitives::{hex, U256 as rU256};
+use anvil::{zksync, NodeConfig, CHAIN_ID};
+use anvil_rpc::response::ResponseResult;
+use ethers::{
+    abi::{self, Token},
+    prelude::{LocalWallet, Middleware, Signer},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, TransactionRequest, H256, U64,
+    },
+};
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_zksync_core::ZkTransactionMetadata;
+use serde_json::json;
+
+/// The zksolc bytecode of the `ConstantNumber` of testdata/zk/Cheatcodes.t.sol, whose `ten()`
+/// returns 10
+const CONSTANT_NUMBER_CODE: &str = concat!(
+    "0000008003000039000000400030043f0000000102200190000000120000c13d000000000201001900000009",
+    "022001980000001a0000613d000000000101043b0000000a011001970000000b0110009c0000001a0000c13d",
+    "0000000001000416000000000101004b0000001a0000c13d0000000a01000039000000800010043f0000000c",
+    "010000410000001d0001042e0000000001000416000000000101004b0000001a0000c13d0000002001000039",
+    "0000010000100443000001200000044300000008010000410000001d0001042e00000000010000190000001e",
+    "000104300000001c000004320000001d0001042e0000001e0001043000000000000000000000000200000000",
+    "00000000000000000000004000000100000000000000000000000000000000000000000000000000fffffffc",
+    "000000000000000000000000ffffffff00000000000000000000000000000000000000000000000000000000",
+    "643ceff900000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "0000000000000020000000800000000000000000000000000000000000000000000000000000000000000000",
+    "000000000000000075b6ac057b6098db0e2fae836aa00e54c6eec4973fc9e5e2b4c8baee23515b65",
+);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_launch_zksync_node() {
+    let (_handler, handle) = zksync::spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let chain_id = provider.get_chainid().await.unwrap();
+    assert_eq!(chain_id.as_u64(), handle.chain_id());
+
+    let l1_chain_id: U64 = provider.request("zks_L1ChainId", ()).await.unwrap();
+    assert_eq!(l1_chain_id.as_u64(), CHAIN_ID);
+
+    for account in handle.genesis_accounts() {
+        let balance = provider.get_balance(account.to_ethers(), None).await.unwrap();
+        assert_eq!(balance.to_alloy(), handle.genesis_balance());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_unknown_zksync_methods() {
+    let (handler, _handle) = zksync::spawn(NodeConfig::test()).await;
+
+    let res = handler.execute(json!({ "method": "foundry_unknown", "params": [] })).await;
+    assert!(matches!(res, ResponseResult::Error(_)));
+
+    let res = handler.execute(json!({ "method": "zks_L1ChainId" })).await;
+    assert_eq!(res, ResponseResult::Success(json!(format!("{CHAIN_ID:#x}"))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_send_eip712_transaction() {
+    let (_handler, handle) = zksync::spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let accounts = &handle.config().genesis_accounts;
+    let wallet = LocalWallet::from_bytes(&accounts[0].signer().to_bytes())
+        .unwrap()
+        .with_chain_id(handle.chain_id());
+    let from = wallet.address();
+    let to = handle.genesis_accounts().nth(1).unwrap().to_ethers();
+
+    let nonce = provider.get_transaction_count(from, None).await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(from)
+        .to(to)
+        .value(1000)
+        .chain_id(handle.chain_id())
+        .nonce(nonce)
+        .gas(1_000_000)
+        .gas_price(gas_price)
+        .data(Bytes::new())
+        .into();
+    let raw = foundry_zksync_core::new_eip712_transaction(
+        tx,
+        &ZkTransactionMetadata::default(),
+        provider.clone(),
+        wallet,
+    )
+    .await
+    .unwrap();
+
+    let receipt =
+        provider.send_raw_transaction(raw.to_ethers()).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+
+    let tx = provider.get_transaction(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(tx.transaction_type, Some(U64::from(0x71)));
+
+    let balance = provider.get_balance(to, None).await.unwrap();
+    assert_eq!(balance.to_alloy(), handle.genesis_balance() + rU256::from(1000));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_estimate_zksync_transfer_fee() {
+    let (_handler, handle) = zksync::spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let from = handle.genesis_accounts().next().unwrap().to_ethers();
+    let to = handle.genesis_accounts().nth(1).unwrap().to_ethers();
+    let nonce = provider.get_transaction_count(from, None).await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(from)
+        .to(to)
+        .value(1000)
+        .chain_id(handle.chain_id())
+        .nonce(nonce)
+        .gas(1_000_000)
+        .gas_price(gas_price)
+        .data(Bytes::new())
+        .into();
+    let request =
+        foundry_zksync_core::new_eip712_request(&tx, &ZkTransactionMetadata::default()).unwrap();
+    let fee = foundry_zksync_core::estimate_eip712_fee(&request, provider.clone()).await.unwrap();
+
+    // EraVM charges the validation of the account and the pubdata of the transfer on top of the
+    // intrinsic gas of an Ethereum transfer
+    assert!(fee.gas_limit > 21_000.into(), "{}", fee.gas_limit);
+    assert!(!fee.max_fee_per_gas.is_zero());
+    assert!(!fee.gas_per_pubdata_limit.is_zero());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_bytecode_of_deployed_contract() {
+    let (_handler, handle) = zksync::spawn(NodeConfig::test()).await;
+    let provider = ethers_http_provider(&handle.http_endpoint());
+
+    let accounts = &handle.config().genesis_accounts;
+    let wallet = LocalWallet::from_bytes(&accounts[0].signer().to_bytes())
+        .unwrap()
+        .with_chain_id(handle.chain_id());
+    let from = wallet.address();
+
+    let code = hex::decode(CONSTANT_NUMBER_CODE).unwrap();
+    let code_hash = H256(foundry_zksync_core::hash_bytecode(&code).0);
+    assert_eq!(
+        code_hash,
+        "0x0100000f6d092b2cd44547a312320ad99c9587b40e0d03b0c17f09afd286d660".parse().unwrap()
+    );
+
+    // `create(bytes32 salt, bytes32 bytecodeHash, bytes input)` of the `ContractDeployer`
+    let create = [
+        &hex::decode("9c4d535b").unwrap()[..],
+        &abi::encode(&[
+            Token::FixedBytes(vec![0; 32]),
+            Token::FixedBytes(code_hash.0.to_vec()),
+            Token::Bytes(Vec::new()),
+        ]),
+    ]
+    .concat();
+    let nonce = provider.get_transaction_count(from, None).await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(from)
+        .to(Address::from(foundry_zksync_core::CONTRACT_DEPLOYER_ADDRESS.0))
+        .chain_id(handle.chain_id())
+        .nonce(nonce)
+        .gas(10_000_000)
+        .gas_price(gas_price)
+        .data(create)
+        .into();
+    let zk_tx = ZkTransactionMetadata { factory_deps: vec![code.clone()], ..Default::default() };
+    let raw = foundry_zksync_core::new_eip712_transaction(tx, &zk_tx, provider.clone(), wallet)
+        .await
+        .unwrap();
+    let receipt =
+        provider.send_raw_transaction(raw.to_ethers()).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+
+    let bytecode: Option<Vec<u8>> =
+        provider.request("zks_getBytecodeByHash", [code_hash]).await.unwrap();
+    assert_eq!(bytecode, Some(code));
+}
@@ -135,7 +135,9 @@ impl CallArgs {
                     let figment = Config::figment_with_root(find_project_root_path(None).unwrap())
                         .merge(eth.rpc);
 
-                    let evm_opts = figment.extract::<EvmOpts>()?;
+                    let mut evm_opts = figment.extract::<EvmOpts>()?;
+                    utils::pin_config_fork_block(&config, &mut evm_opts, false).await?;
+                    utils::ensure_evm_fork(&config).await?;
 
                     let (env, fork, chain) =
                         TracingExecutor::get_fork_material(&config, evm_opts).await?;
@@ -170,7 +172,9 @@ impl CallArgs {
                     let figment = Config::figment_with_root(find_project_root_path(None).unwrap())
                         .merge(eth.rpc);
 
-                    let evm_opts = figment.extract::<EvmOpts>()?;
+                    let mut evm_opts = figment.extract::<EvmOpts>()?;
+                    utils::pin_config_fork_block(&config, &mut evm_opts, false).await?;
+                    utils::ensure_evm_fork(&config).await?;
 
                     let (env, fork, chain) =
                         TracingExecutor::get_fork_material(&config, evm_opts).await?;
@@ -18,6 +18,31 @@ const DEPLOYER: &str = "0x4e59b44847b379578588920ca78fbf26c0b4956c";
 /// CLI arguments for `cast create2`.
 #[derive(Clone, Debug, Parser)]
 pub struct Create2Args {
+    /// Address of the contract deployer.
+    #[clap(
+        short,
+        long,
+        default_value = DEPLOYER,
+        value_name = "ADDRESS"
+    )]
+    deployer: Address,
+
+    /// Init code of the contract to be deployed.
+    #[clap(short, long, value_name = "HEX")]
+    init_code: Option<String>,
+
+    /// Init code hash of the contract to be deployed.
+    #[clap(alias = "ch", long, value_name = "HASH", required_unless_present = "init_code")]
+    init_code_hash: Option<String>,
+
+    #[clap(flatten)]
+    search: SaltSearchArgs,
+}
+
+/// The vanity patterns of the address and the options of the salt search, shared with
+/// `cast zk find-salt`.
+#[derive(Clone, Debug, Parser)]
+pub struct SaltSearchArgs {
     /// Prefix for the contract address.
     #[clap(
         long,
@@ -39,23 +64,6 @@ pub struct Create2Args {
     #[clap(short, long)]
     case_sensitive: bool,
 
-    /// Address of the contract deployer.
-    #[clap(
-        short,
-        long,
-        default_value = DEPLOYER,
-        value_name = "ADDRESS"
-    )]
-    deployer: Address,
-
-    /// Init code of the contract to be deployed.
-    #[clap(short, long, value_name = "HEX")]
-    init_code: Option<String>,
-
-    /// Init code hash of the contract to be deployed.
-    #[clap(alias = "ch", long, value_name = "HASH", required_unless_present = "init_code")]
-    init_code_hash: Option<String>,
-
     /// Number of threads to use. Defaults to and caps at the number of logical cores.
     #[clap(short, long)]
     jobs: Option<NonZeroUsize>,
@@ -81,14 +89,34 @@ pub struct Create2Output {
 
 impl Create2Args {
     pub fn run(self) -> Result<Create2Output> {
-        let Create2Args {
+        let Create2Args { deployer, init_code, init_code_hash, search } = self;
+
+        let init_code_hash = if let Some(init_code_hash) = init_code_hash {
+            let mut hash: [u8; 32] = [0; 32];
+            hex::decode_to_slice(init_code_hash, &mut hash)?;
+            hash.into()
+        } else if let Some(init_code) = init_code {
+            keccak256(hex::decode(init_code)?)
+        } else {
+            unreachable!();
+        };
+
+        search.run(move |salt| deployer.create2(salt, init_code_hash))
+    }
+}
+
+impl SaltSearchArgs {
+    /// Searches a salt for which `create2` derives an address matching the vanity patterns, in
+    /// parallel, and prints the results.
+    pub fn run<F>(self, create2: F) -> Result<Create2Output>
+    where
+        F: Fn(&B256) -> Address + Clone + Send + 'static,
+    {
+        let SaltSearchArgs {
             starts_with,
             ends_with,
             matching,
             case_sensitive,
-            deployer,
-            init_code,
-            init_code_hash,
             jobs,
             caller,
             seed,
@@ -134,16 +162,6 @@ impl Create2Args {
 
         let regex = RegexSetBuilder::new(regexs).case_insensitive(!case_sensitive).build()?;
 
-        let init_code_hash = if let Some(init_code_hash) = init_code_hash {
-            let mut hash: [u8; 32] = [0; 32];
-            hex::decode_to_slice(init_code_hash, &mut hash)?;
-            hash.into()
-        } else if let Some(init_code) = init_code {
-            keccak256(hex::decode(init_code)?)
-        } else {
-            unreachable!();
-        };
-
         let mut n_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
         if let Some(jobs) = jobs {
             n_threads = n_threads.min(jobs.get());
@@ -181,6 +199,7 @@ impl Create2Args {
             let regex = regex.clone();
             let regex_len = regex.patterns().len();
             let found = Arc::clone(&found);
+            let create2 = create2.clone();
             handles.push(std::thread::spawn(move || {
                 // Read the first bytes of the salt as a usize to be able to increment it.
                 struct B256Aligned(B256, [usize; 0]);
@@ -200,8 +219,7 @@ impl Create2Args {
                     }
 
                     // Calculate the `CREATE2` address.
-                    #[allow(clippy::needless_borrows_for_generic_args)]
-                    let addr = deployer.create2(&salt.0, init_code_hash);
+                    let addr = create2(&salt.0);
 
                     // Check if the the regex matches the calculated address' checksum.
                     let _ = addr.to_checksum_raw(&mut checksum, None);
@@ -0,0 +1,168 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::{Error, JsonAbi};
+use alloy_primitives::Selector;
+use clap::{Parser, ValueHint};
+use eyre::{Result, WrapErr};
+use foundry_cli::stdin;
+use foundry_common::fmt::format_tokens;
+use foundry_config::{find_project_root_path, Config};
+use foundry_zksync_core::errors::SYSTEM_CONTRACT_ERRORS;
+use itertools::Itertools;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// The errors emitted by the Solidity compiler itself.
+const BUILTIN_ERRORS: &[&str] = &["Error(string)", "Panic(uint256)"];
+
+/// CLI arguments for `cast decode-error`.
+#[derive(Clone, Debug, Parser)]
+pub struct DecodeErrorArgs {
+    /// The ABI-encoded revert data, including the error selector.
+    data: Option<String>,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    root: Option<PathBuf>,
+}
+
+impl DecodeErrorArgs {
+    pub async fn run(self) -> Result<()> {
+        let DecodeErrorArgs { data, root } = self;
+
+        let data = stdin::unwrap_line(data)?;
+        let data = hex::decode(data.trim()).wrap_err("invalid revert data")?;
+        if data.len() < 4 {
+            eyre::bail!("revert data must contain at least a 4 byte selector");
+        }
+
+        let root = match root {
+            Some(root) => root,
+            None => find_project_root_path(None)?,
+        };
+        let config = Config::load_with_root(root);
+
+        let mut catalog = ErrorCatalog::default();
+        catalog.extend_from_artifacts(&config.project_paths().artifacts, "EVM");
+        if let Ok(project) = config.zk_project() {
+            catalog.extend_from_artifacts(&project.paths.artifacts, "zkSync");
+        }
+        catalog.extend_from_signatures(SYSTEM_CONTRACT_ERRORS, "zkSync system contracts");
+        catalog.extend_from_signatures(BUILTIN_ERRORS, "Solidity");
+
+        let decoded = catalog.decode(&data);
+        if decoded.is_empty() {
+            eyre::bail!(
+                "no error with selector {} found in the project artifacts",
+                hex::encode_prefixed(&data[..4])
+            );
+        }
+
+        for (i, (error, origins, tokens)) in decoded.into_iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("{} // {}", error.signature(), origins.iter().format(", "));
+            format_tokens(&tokens).for_each(|t| println!("{t}"));
+        }
+
+        Ok(())
+    }
+}
+
+/// All known errors, grouped by selector.
+#[derive(Debug, Default)]
+struct ErrorCatalog {
+    /// The errors along with the contracts or artifact sets they were found in.
+    errors: BTreeMap<Selector, BTreeMap<String, (Error, BTreeSet<String>)>>,
+}
+
+impl ErrorCatalog {
+    /// Adds the errors of all the contract artifacts found under `artifacts`.
+    fn extend_from_artifacts(&mut self, artifacts: &Path, kind: &str) {
+        for path in foundry_common::fs::json_files(artifacts) {
+            // build info files don't contain the ABI, and can be large
+            if path.components().any(|c| c.as_os_str() == "build-info") {
+                continue
+            }
+            let Some(abi) = read_abi(&path) else { continue };
+            let name = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+            let origin = format!("{} ({kind})", name.unwrap_or_default());
+            for error in abi.errors() {
+                self.push(error.clone(), origin.clone());
+            }
+        }
+    }
+
+    /// Adds errors from their human-readable signatures.
+    fn extend_from_signatures(&mut self, signatures: &[&str], origin: &str) {
+        for signature in signatures {
+            if let Ok(error) = Error::parse(signature) {
+                self.push(error, origin.to_string());
+            }
+        }
+    }
+
+    fn push(&mut self, error: Error, origin: String) {
+        let (_, origins) = self
+            .errors
+            .entry(error.selector())
+            .or_default()
+            .entry(error.signature())
+            .or_insert_with(|| (error, BTreeSet::new()));
+        origins.insert(origin);
+    }
+
+    /// Returns all the errors that can decode the given revert data, along with their arguments.
+    fn decode(&self, data: &[u8]) -> Vec<(&Error, &BTreeSet<String>, Vec<DynSolValue>)> {
+        let (selector, args) = data.split_at(4);
+        let Some(errors) = self.errors.get(&Selector::from_slice(selector)) else { return vec![] };
+        errors
+            .values()
+            .filter_map(|(error, origins)| {
+                let tokens = error.abi_decode_input(args, false).ok()?;
+                Some((error, origins, tokens))
+            })
+            .collect()
+    }
+}
+
+/// Reads the ABI of a contract artifact, if any.
+fn read_abi(path: &Path) -> Option<JsonAbi> {
+    let mut artifact: serde_json::Value = foundry_common::fs::read_json_file(path).ok()?;
+    serde_json::from_value(artifact.get_mut("abi")?.take()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, U256};
+
+    #[test]
+    fn decodes_system_contract_errors() {
+        let mut catalog = ErrorCatalog::default();
+        catalog.extend_from_signatures(SYSTEM_CONTRACT_ERRORS, "zkSync system contracts");
+        catalog.extend_from_signatures(BUILTIN_ERRORS, "Solidity");
+
+        let error = Error::parse("Unauthorized(address)").unwrap();
+        let caller = address!("000000000000000000000000000000000000dead");
+        let mut data = error.selector().to_vec();
+        data.extend(DynSolValue::Address(caller).abi_encode());
+
+        let decoded = catalog.decode(&data);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].0.name, "Unauthorized");
+        assert_eq!(decoded[0].2, vec![DynSolValue::Address(caller)]);
+
+        let error = Error::parse("Panic(uint256)").unwrap();
+        let mut data = error.selector().to_vec();
+        data.extend(DynSolValue::Uint(U256::from(0x11), 256).abi_encode());
+        assert_eq!(catalog.decode(&data)[0].0.name, "Panic");
+
+        assert!(catalog.decode(&[0xde, 0xad, 0xbe, 0xef]).is_empty());
+    }
+}
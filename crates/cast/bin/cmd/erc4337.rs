@@ -0,0 +1,198 @@
+use alloy_primitives::{hex, Address, Bytes, B256, U256};
+use cast::SimpleCast;
+use clap::{Parser, Subcommand};
+use eyre::{Result, WrapErr};
+use foundry_cli::{
+    opts::{EthereumOpts, RpcOpts},
+    utils::{self, parse_ether_value},
+};
+use foundry_common::{
+    erc4337::{
+        build_user_operation, encode_execute, Bundler, UserOperationReceipt, UserOperationRequest,
+        DEFAULT_ENTRY_POINT,
+    },
+    types::ToAlloy,
+};
+use foundry_config::Config;
+use std::time::Duration;
+
+/// CLI arguments for `cast 4337`.
+#[derive(Debug, Subcommand)]
+pub enum Erc4337Subcommand {
+    /// Build, sign and submit a user operation through a bundler.
+    ///
+    /// The account is called with `execute(to, value, calldata)`, unless `--call-data` is passed.
+    Send(SendUserOpArgs),
+
+    /// Get the receipt of a user operation.
+    Receipt {
+        /// The hash of the user operation.
+        hash: B256,
+
+        #[clap(flatten)]
+        bundler: BundlerOpts,
+
+        /// Print the receipt as JSON.
+        #[clap(long, short)]
+        json: bool,
+
+        #[clap(flatten)]
+        rpc: RpcOpts,
+    },
+}
+
+/// Options for connecting to a bundler.
+#[derive(Clone, Debug, Parser)]
+#[clap(next_help_heading = "Bundler options")]
+pub struct BundlerOpts {
+    /// The bundler RPC endpoint.
+    ///
+    /// Defaults to `bundler_url` in the config.
+    #[clap(long, env = "BUNDLER_RPC_URL", value_name = "URL")]
+    pub bundler_url: Option<String>,
+
+    /// The `EntryPoint` the user operations are sent to.
+    #[clap(long, value_name = "ADDRESS", default_value_t = DEFAULT_ENTRY_POINT)]
+    pub entry_point: Address,
+}
+
+impl BundlerOpts {
+    fn bundler(&self, config: &Config) -> Result<Bundler> {
+        let url = self.bundler_url.as_ref().or(config.bundler_url.as_ref()).ok_or_else(|| {
+            eyre::eyre!("no bundler configured, pass `--bundler-url` or set `bundler_url`")
+        })?;
+        Bundler::new(url, self.entry_point)
+    }
+}
+
+/// CLI arguments for `cast 4337 send`.
+#[derive(Debug, Parser)]
+pub struct SendUserOpArgs {
+    /// The smart account sending the user operation.
+    #[clap(long, value_name = "ADDRESS")]
+    account: Address,
+
+    /// The destination of the call made by the account.
+    #[clap(required_unless_present = "call_data")]
+    to: Option<Address>,
+
+    /// The signature of the function to call.
+    sig: Option<String>,
+
+    /// The arguments of the function to call.
+    args: Vec<String>,
+
+    /// The value sent with the call made by the account, in wei or with a unit (e.g. `1ether`).
+    #[clap(long, value_parser = parse_ether_value, conflicts_with = "call_data")]
+    value: Option<U256>,
+
+    /// The raw calldata the account is called with, instead of `execute(to, value, calldata)`.
+    #[clap(long, value_name = "HEX", conflicts_with = "to")]
+    call_data: Option<Bytes>,
+
+    /// The factory address and calldata, if the account has yet to be deployed.
+    #[clap(long, value_name = "HEX")]
+    init_code: Option<Bytes>,
+
+    /// The paymaster address and data, if the user operation is sponsored.
+    #[clap(long, value_name = "HEX")]
+    paymaster_and_data: Option<Bytes>,
+
+    /// Only print the user operation hash and exit immediately.
+    #[clap(name = "async", long = "async", alias = "cast-async", env = "CAST_ASYNC")]
+    cast_async: bool,
+
+    /// How long to wait for the user operation to be included, in seconds.
+    #[clap(long, default_value = "120")]
+    timeout: u64,
+
+    /// Print the receipt as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    bundler: BundlerOpts,
+
+    #[clap(flatten)]
+    eth: EthereumOpts,
+}
+
+impl Erc4337Subcommand {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Self::Send(args) => args.run().await,
+            Self::Receipt { hash, bundler, json, rpc } => {
+                let config = Config::from(&rpc);
+                let bundler = bundler.bundler(&config)?;
+                let receipt = bundler
+                    .receipt(hash)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("user operation {hash} not found"))?;
+                print_receipt(&receipt, json)
+            }
+        }
+    }
+}
+
+impl SendUserOpArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self.eth);
+        let provider = utils::get_provider(&config)?;
+        let bundler = self.bundler.bundler(&config)?;
+
+        let call_data = match self.call_data {
+            Some(call_data) => call_data,
+            None => {
+                let to = self.to.expect("required by clap");
+                let func = match self.sig.as_deref() {
+                    Some(sig) if !sig.is_empty() => {
+                        hex::decode(SimpleCast::calldata_encode(sig, &self.args)?)?.into()
+                    }
+                    _ => Bytes::new(),
+                };
+                encode_execute(to, self.value.unwrap_or_default(), func)
+            }
+        };
+
+        let signer = self.eth.wallet.signer().await?;
+        let request = UserOperationRequest {
+            sender: self.account,
+            call_data,
+            init_code: self.init_code.unwrap_or_default(),
+            paymaster_and_data: self.paymaster_and_data.unwrap_or_default(),
+        };
+        let op = build_user_operation(&provider, &bundler, &signer, request)
+            .await
+            .wrap_err("failed to build user operation")?;
+        let hash = bundler.send(&op).await?;
+
+        if self.cast_async {
+            println!("{hash}");
+            return Ok(())
+        }
+
+        let receipt = bundler.wait_for_receipt(hash, Duration::from_secs(self.timeout)).await?;
+        print_receipt(&receipt, self.json)
+    }
+}
+
+fn print_receipt(receipt: &UserOperationReceipt, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(receipt)?);
+        return Ok(())
+    }
+
+    let status = if receipt.success { "success" } else { "failed" };
+    println!("userOpHash           {}", receipt.user_op_hash);
+    println!("status               {status}");
+    if let Some(reason) = &receipt.reason {
+        println!("reason               {reason}");
+    }
+    println!("actualGasUsed        {}", receipt.actual_gas_used);
+    println!("actualGasCost        {}", receipt.actual_gas_cost);
+    println!("transactionHash      {}", receipt.receipt.transaction_hash.to_alloy());
+    if let Some(block) = receipt.receipt.block_number {
+        println!("blockNumber          {block}");
+    }
+    Ok(())
+}
@@ -9,6 +9,8 @@ pub mod access_list;
 pub mod bind;
 pub mod call;
 pub mod create2;
+pub mod decode_error;
+pub mod erc4337;
 pub mod estimate;
 pub mod find_block;
 pub mod interface;
@@ -18,3 +20,4 @@ pub mod run;
 pub mod send;
 pub mod storage;
 pub mod wallet;
+pub mod zk;
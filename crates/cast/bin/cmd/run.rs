@@ -7,7 +7,7 @@ use foundry_cli::{
     init_progress,
     opts::RpcOpts,
     update_progress,
-    utils::{handle_traces, TraceResult},
+    utils::{ensure_evm_fork, handle_traces, TraceResult},
 };
 use foundry_common::{is_known_system_sender, SYSTEM_TRANSACTION_TYPE};
 use foundry_compilers::EvmVersion;
@@ -117,8 +117,9 @@ impl RunArgs {
             .to::<u64>();
 
         // we need to fork off the parent block
-        config.fork_block_number = Some(tx_block_number - 1);
+        config.fork_block_number = Some((tx_block_number - 1).into());
 
+        ensure_evm_fork(&config).await?;
         let (mut env, fork, chain) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
 
         let mut executor =
@@ -0,0 +1,88 @@
+//! The zkSync Era addresses of `cast compute-address --zksync`.
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+
+/// Computes the address of a contract `deployer` deploys on an Era chain.
+///
+/// With a `salt`, the address of its `CREATE2` deployment, derived from the `bytecode_hash` and
+/// the `constructor_args` of the contract. Otherwise the address of its `CREATE` deployment,
+/// derived from the deployment `nonce` of the deployer, which is fetched from the NonceHolder
+/// system contract if not given.
+pub async fn compute_address<M: Middleware>(
+    provider: &M,
+    deployer: Address,
+    nonce: Option<u64>,
+    salt: Option<B256>,
+    bytecode_hash: Option<B256>,
+    constructor_args: Option<String>,
+) -> Result<Address> {
+    if let Some(salt) = salt {
+        let Some(bytecode_hash) = bytecode_hash else {
+            eyre::bail!(
+                "the bytecode hash of a zkSync CREATE2 deployment is required: set --bytecode-hash"
+            )
+        };
+        return create2_address(deployer, salt, bytecode_hash, constructor_args)
+    }
+
+    let nonce = match nonce {
+        Some(nonce) => U256::from(nonce),
+        None => foundry_zksync_core::get_deployment_nonce(deployer, provider).await?,
+    };
+    Ok(foundry_zksync_core::compute_create_address(deployer, nonce))
+}
+
+/// Computes the address of the `CREATE2` deployment of the contract of `bytecode_hash` by
+/// `deployer` on an Era chain.
+fn create2_address(
+    deployer: Address,
+    salt: B256,
+    bytecode_hash: B256,
+    constructor_args: Option<String>,
+) -> Result<Address> {
+    let input = match constructor_args {
+        Some(args) => hex::decode(args).wrap_err("invalid constructor args provided")?,
+        None => Vec::new(),
+    };
+    Ok(foundry_zksync_core::compute_create2_address(
+        deployer,
+        bytecode_hash,
+        salt,
+        keccak256(input),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn computes_create2_addresses() {
+        // the test vectors of zksync-ethers' `utils.create2Address`
+        let deployer = address!("36615Cf349d7F6344891B1e7CA7C72883F5dc049");
+        let bytecode_hash =
+            b256!("010001cb6a6e8d5f6829522f19fa9568660e0a9cd53b2e8be4deb0a679452e41");
+
+        assert_eq!(
+            create2_address(deployer, B256::ZERO, bytecode_hash, None).unwrap(),
+            address!("3cfd6d969361fd475d81ba3e3306263ec88e1c61")
+        );
+        assert_eq!(
+            create2_address(
+                deployer,
+                B256::with_last_byte(1),
+                bytecode_hash,
+                Some("0x01".to_string())
+            )
+            .unwrap(),
+            address!("78ee9dea03a39f5cc04c80a575517ff5de02ec4c")
+        );
+
+        assert!(
+            create2_address(deployer, B256::ZERO, bytecode_hash, Some("0xzz".to_string())).is_err()
+        );
+    }
+}
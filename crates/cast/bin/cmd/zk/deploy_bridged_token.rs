@@ -0,0 +1,380 @@
+use super::call;
+use alloy_primitives::{address, Address, B256, U256};
+use alloy_sol_types::{sol, SolCall, SolEvent, SolValue};
+use clap::Parser;
+use ethers_core::types::{TransactionReceipt, TransactionRequest};
+use ethers_middleware::SignerMiddleware;
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::EthereumOpts, utils};
+use foundry_common::{
+    provider::ethers::try_get_http_provider,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_config::Config;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+/// The address the shared bridge uses for ETH, when it's the base token of a chain.
+const ETH_TOKEN_ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+
+/// The gas per pubdata byte limit required for L1 to L2 transactions.
+const L1_TO_L2_GAS_PER_PUBDATA_LIMIT: u64 = 800;
+
+sol! {
+    struct L2TransactionRequestTwoBridgesOuter {
+        uint256 chainId;
+        uint256 mintValue;
+        uint256 l2Value;
+        uint256 l2GasLimit;
+        uint256 l2GasPerPubdataByteLimit;
+        address refundRecipient;
+        address secondBridgeAddress;
+        uint256 secondBridgeValue;
+        bytes secondBridgeCalldata;
+    }
+
+    struct L2CanonicalTransaction {
+        uint256 txType;
+        uint256 from;
+        uint256 to;
+        uint256 gasLimit;
+        uint256 gasPerPubdataByteLimit;
+        uint256 maxFeePerGas;
+        uint256 maxPriorityFeePerGas;
+        uint256 paymaster;
+        uint256 nonce;
+        uint256 value;
+        uint256[4] reserved;
+        bytes data;
+        bytes signature;
+        uint256[] factoryDeps;
+        bytes paymasterInput;
+        bytes reservedDynamic;
+    }
+
+    interface IBridgehub {
+        function baseToken(uint256 chainId) external view returns (address);
+
+        function l2TransactionBaseCost(
+            uint256 chainId,
+            uint256 gasPrice,
+            uint256 l2GasLimit,
+            uint256 l2GasPerPubdataByteLimit
+        ) external view returns (uint256);
+
+        function requestL2TransactionTwoBridges(
+            L2TransactionRequestTwoBridgesOuter calldata request
+        ) external payable returns (bytes32 canonicalTxHash);
+    }
+
+    interface IMailbox {
+        event NewPriorityRequest(
+            uint256 txId,
+            bytes32 txHash,
+            uint64 expirationTimestamp,
+            L2CanonicalTransaction transaction,
+            bytes[] factoryDeps
+        );
+    }
+
+    interface IL2SharedBridge {
+        function l2TokenAddress(address l1Token) external view returns (address);
+    }
+
+    interface IERC20 {
+        function allowance(address owner, address spender) external view returns (uint256);
+
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+}
+
+/// The bridges of an Era chain, as returned by `zks_getBridgeContracts`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeContracts {
+    l1_shared_default_bridge: Option<Address>,
+    l2_shared_default_bridge: Option<Address>,
+    l2_erc20_default_bridge: Option<Address>,
+}
+
+/// CLI arguments for `cast zk deploy-bridged-token`.
+#[derive(Debug, Parser)]
+pub struct DeployBridgedTokenArgs {
+    /// The L1 token to deploy the bridged representation of.
+    #[clap(value_name = "L1_TOKEN")]
+    token: Address,
+
+    /// The amount deposited to trigger the deployment, in the smallest unit of the token.
+    ///
+    /// The shared bridge doesn't accept empty deposits.
+    #[clap(long, default_value = "1")]
+    amount: U256,
+
+    /// The receiver of the deposit on L2. Defaults to the sender.
+    #[clap(long, value_name = "ADDRESS")]
+    receiver: Option<Address>,
+
+    /// The RPC endpoint of the Era chain.
+    #[clap(long, env = "ZKSYNC_RPC_URL", value_name = "URL")]
+    l2_rpc_url: String,
+
+    /// The gas limit of the deposit on L2, which includes the deployment of the token.
+    #[clap(long, default_value = "3000000")]
+    l2_gas_limit: u64,
+
+    /// Only print the L1 transaction hash and the L2 token address, without waiting for the
+    /// deposit to be processed on L2.
+    #[clap(name = "async", long = "async", alias = "cast-async", env = "CAST_ASYNC")]
+    cast_async: bool,
+
+    /// How long to wait for the deposit to be processed on L2, in seconds.
+    #[clap(long, default_value = "900")]
+    timeout: u64,
+
+    #[clap(flatten)]
+    eth: EthereumOpts,
+}
+
+impl DeployBridgedTokenArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self.eth);
+        let l1 = utils::get_provider(&config)?;
+        let l2 = try_get_http_provider(&self.l2_rpc_url)?;
+
+        let signer = self.eth.wallet.signer().await?;
+        let sender = signer.address().to_alloy();
+        let receiver = self.receiver.unwrap_or(sender);
+
+        let chain_id = l2.get_chainid().await?.to_alloy();
+        let bridgehub: Address = l2
+            .request("zks_getBridgehubContract", ())
+            .await
+            .wrap_err("failed to get the bridgehub, is the L2 RPC an Era node?")?;
+        let bridges: BridgeContracts = l2.request("zks_getBridgeContracts", ()).await?;
+        let (Some(l1_bridge), Some(l2_bridge)) = (
+            bridges.l1_shared_default_bridge,
+            bridges.l2_shared_default_bridge.or(bridges.l2_erc20_default_bridge),
+        ) else {
+            eyre::bail!("chain {chain_id} doesn't use the shared bridge")
+        };
+
+        // The address of the bridged token is deterministic, so it's known before the deposit.
+        let l2_token =
+            call(&l2, l2_bridge, IL2SharedBridge::l2TokenAddressCall { l1Token: self.token })
+                .await?
+                ._0;
+        if !l2.get_code(l2_token.to_ethers(), None).await?.is_empty() {
+            println!("{} is already deployed at {l2_token}", self.token);
+            return Ok(())
+        }
+
+        let base_token =
+            call(&l1, bridgehub, IBridgehub::baseTokenCall { chainId: chain_id }).await?._0;
+        if base_token != ETH_TOKEN_ADDRESS {
+            eyre::bail!(
+                "chain {chain_id} uses the custom base token {base_token}, which is not supported"
+            )
+        }
+
+        let gas_price = l1.get_gas_price().await?.to_alloy();
+        let base_cost = call(
+            &l1,
+            bridgehub,
+            IBridgehub::l2TransactionBaseCostCall {
+                chainId: chain_id,
+                gasPrice: gas_price,
+                l2GasLimit: U256::from(self.l2_gas_limit),
+                l2GasPerPubdataByteLimit: U256::from(L1_TO_L2_GAS_PER_PUBDATA_LIMIT),
+            },
+        )
+        .await?
+        ._0;
+
+        let client = SignerMiddleware::new_with_provider_chain(l1, signer).await?;
+
+        let allowance =
+            call(&client, self.token, IERC20::allowanceCall { owner: sender, spender: l1_bridge })
+                .await?
+                ._0;
+        if allowance < self.amount {
+            let approve = IERC20::approveCall { spender: l1_bridge, amount: self.amount };
+            let tx =
+                TransactionRequest::new().to(self.token.to_ethers()).data(approve.abi_encode());
+            let receipt = send(&client, tx).await.wrap_err("failed to approve the deposit")?;
+            println!("Approved the shared bridge in {:?}", receipt.transaction_hash);
+        }
+
+        let deposit = IBridgehub::requestL2TransactionTwoBridgesCall {
+            request: self.deposit_request(chain_id, base_cost, sender, receiver, l1_bridge),
+        };
+        let tx = TransactionRequest::new()
+            .to(bridgehub.to_ethers())
+            .value(base_cost.to_ethers())
+            .data(deposit.abi_encode());
+        let receipt = send(&client, tx).await.wrap_err("failed to deposit the token")?;
+
+        println!("L1 transaction: {:?}", receipt.transaction_hash);
+        println!("L2 token: {l2_token}");
+        if self.cast_async {
+            return Ok(())
+        }
+
+        let l2_hash = priority_tx_hash(&receipt)
+            .ok_or_else(|| eyre::eyre!("the deposit didn't request an L2 transaction"))?;
+        println!("Waiting for L2 transaction {l2_hash} to be processed...");
+
+        let start = Instant::now();
+        let l2_receipt = loop {
+            if let Some(receipt) = l2.get_transaction_receipt(l2_hash.to_ethers()).await? {
+                break receipt
+            }
+            if start.elapsed() >= Duration::from_secs(self.timeout) {
+                eyre::bail!(
+                    "L2 transaction {l2_hash} was not processed after {}s, its progress can be \
+                     checked with `cast receipt {l2_hash} --rpc-url <L2_RPC_URL>`",
+                    self.timeout
+                )
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        };
+        if l2_receipt.status.is_some_and(|status| status.is_zero()) {
+            eyre::bail!("L2 transaction {l2_hash} failed, the token was not deployed")
+        }
+
+        println!("Deployed the bridged token at {l2_token}");
+        Ok(())
+    }
+}
+
+impl DeployBridgedTokenArgs {
+    /// Returns the request depositing `amount` of the token to `receiver` through the
+    /// `l1_bridge` shared bridge, paying the `base_cost` of the L2 transaction in ETH.
+    fn deposit_request(
+        &self,
+        chain_id: U256,
+        base_cost: U256,
+        sender: Address,
+        receiver: Address,
+        l1_bridge: Address,
+    ) -> L2TransactionRequestTwoBridgesOuter {
+        L2TransactionRequestTwoBridgesOuter {
+            chainId: chain_id,
+            mintValue: base_cost,
+            l2Value: U256::ZERO,
+            l2GasLimit: U256::from(self.l2_gas_limit),
+            l2GasPerPubdataByteLimit: U256::from(L1_TO_L2_GAS_PER_PUBDATA_LIMIT),
+            refundRecipient: sender,
+            secondBridgeAddress: l1_bridge,
+            secondBridgeValue: U256::ZERO,
+            secondBridgeCalldata: (self.token, self.amount, receiver).abi_encode(),
+        }
+    }
+}
+
+/// Sends a transaction and waits for it to succeed.
+async fn send<M: Middleware>(client: &M, tx: TransactionRequest) -> Result<TransactionReceipt>
+where
+    M::Error: 'static,
+{
+    let pending = client.send_transaction(tx, None).await?;
+    let hash = *pending;
+    let receipt = pending.await?.ok_or_else(|| eyre::eyre!("transaction {hash:?} was dropped"))?;
+    if receipt.status.is_some_and(|status| status.is_zero()) {
+        eyre::bail!("transaction {hash:?} reverted")
+    }
+    Ok(receipt)
+}
+
+/// Returns the hash of the L2 transaction requested in an L1 transaction, from the
+/// `NewPriorityRequest` event of the chain.
+fn priority_tx_hash(receipt: &TransactionReceipt) -> Option<B256> {
+    receipt.logs.iter().find_map(|log| {
+        let topics = log.topics.iter().map(|topic| topic.to_alloy());
+        IMailbox::NewPriorityRequest::decode_raw_log(topics, &log.data, true)
+            .ok()
+            .map(|event| event.txHash)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::Log;
+
+    #[test]
+    fn encodes_deposit_request() {
+        let token = Address::with_last_byte(1);
+        let token_arg = token.to_string();
+        let args = DeployBridgedTokenArgs::parse_from([
+            "foundry-cli",
+            token_arg.as_str(),
+            "--l2-rpc-url=http://localhost:3050",
+            "--amount=5",
+        ]);
+        assert_eq!(args.amount, U256::from(5));
+
+        let sender = Address::with_last_byte(2);
+        let receiver = Address::with_last_byte(3);
+        let l1_bridge = Address::with_last_byte(4);
+        let request =
+            args.deposit_request(U256::from(270), U256::from(1000), sender, receiver, l1_bridge);
+        assert_eq!(request.chainId, U256::from(270));
+        assert_eq!(request.mintValue, U256::from(1000));
+        assert_eq!(request.l2Value, U256::ZERO);
+        assert_eq!(request.l2GasLimit, U256::from(3_000_000));
+        assert_eq!(request.l2GasPerPubdataByteLimit, U256::from(L1_TO_L2_GAS_PER_PUBDATA_LIMIT));
+        assert_eq!(request.refundRecipient, sender);
+        assert_eq!(request.secondBridgeAddress, l1_bridge);
+
+        // the shared bridge decodes the deposit as `(address, uint256, address)`
+        let deposit =
+            <(Address, U256, Address)>::abi_decode(&request.secondBridgeCalldata, true).unwrap();
+        assert_eq!(deposit, (token, U256::from(5), receiver));
+    }
+
+    #[test]
+    fn finds_priority_tx_hash() {
+        let tx_hash = B256::with_last_byte(7);
+        let event = IMailbox::NewPriorityRequest {
+            txId: U256::from(1),
+            txHash: tx_hash,
+            expirationTimestamp: 0,
+            transaction: L2CanonicalTransaction {
+                txType: U256::ZERO,
+                from: U256::ZERO,
+                to: U256::ZERO,
+                gasLimit: U256::ZERO,
+                gasPerPubdataByteLimit: U256::ZERO,
+                maxFeePerGas: U256::ZERO,
+                maxPriorityFeePerGas: U256::ZERO,
+                paymaster: U256::ZERO,
+                nonce: U256::ZERO,
+                value: U256::ZERO,
+                reserved: [U256::ZERO; 4],
+                data: vec![],
+                signature: vec![],
+                factoryDeps: vec![],
+                paymasterInput: vec![],
+                reservedDynamic: vec![],
+            },
+            factoryDeps: vec![],
+        };
+        let other = Log {
+            topics: vec![B256::with_last_byte(1).to_ethers()],
+            data: vec![0; 32].into(),
+            ..Default::default()
+        };
+        let log = Log {
+            topics: vec![IMailbox::NewPriorityRequest::SIGNATURE_HASH.to_ethers()],
+            data: event.encode_data().into(),
+            ..Default::default()
+        };
+
+        let mut receipt = TransactionReceipt { logs: vec![other], ..Default::default() };
+        assert_eq!(priority_tx_hash(&receipt), None);
+        receipt.logs.push(log);
+        assert_eq!(priority_tx_hash(&receipt), Some(tx_hash));
+    }
+}
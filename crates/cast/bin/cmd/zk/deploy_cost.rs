@@ -0,0 +1,398 @@
+use super::call;
+use alloy_primitives::{address, Address, Bytes, B256, U256};
+use alloy_sol_types::sol;
+use clap::Parser;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    fs,
+    provider::ethers::{try_get_http_provider, RetryProvider},
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::ConfigurableContractArtifact;
+use foundry_evm::revm::primitives::CreateScheme;
+use foundry_zksync_compiler::PackedEraBytecode;
+use foundry_zksync_core::{
+    convert::ConvertH256,
+    pubdata::{self, DEFAULT_GAS_PER_PUBDATA},
+};
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+/// The system contract storing the markers of the bytecodes published on the chain.
+const KNOWN_CODES_STORAGE: Address = address!("0000000000000000000000000000000000008004");
+
+/// The sender of the deployment estimated when `--from` isn't given.
+const DEFAULT_SENDER: Address = address!("000000000000000000000000000000000000dEaD");
+
+/// The L1 gas paid per byte of pubdata, which Era nodes derive the gas per pubdata from.
+const L1_GAS_PER_PUBDATA_BYTE: u64 = 17;
+
+sol! {
+    interface IKnownCodesStorage {
+        function getMarker(bytes32 hash) external view returns (uint256);
+    }
+}
+
+/// CLI arguments for `cast zk code-deploy-cost`.
+#[derive(Debug, Parser)]
+pub struct CodeDeployCostArgs {
+    /// The zksolc artifact of the contract, e.g. `zkout/Counter.sol/Counter.json`.
+    artifact: PathBuf,
+
+    /// The solc artifact of the contract, to compare with the deployment cost on the EVM.
+    ///
+    /// Defaults to the artifact at the same path in the solc output directory, if any.
+    #[clap(long, value_name = "PATH")]
+    evm_artifact: Option<PathBuf>,
+
+    /// The gas per pubdata byte of the chain.
+    ///
+    /// Defaults to the current value of the chain, or 800 without an RPC endpoint.
+    #[clap(long, value_name = "GAS")]
+    gas_per_pubdata: Option<u64>,
+
+    /// The RPC endpoint of the Era chain, to estimate the deployment with `zks_estimateFee` and
+    /// skip the bytecodes already published on it.
+    ///
+    /// Without it, only the gas of the pubdata is estimated, assuming all the bytecodes are
+    /// unknown to the chain.
+    #[clap(long, env = "ZKSYNC_RPC_URL", value_name = "URL")]
+    l2_rpc_url: Option<String>,
+
+    /// The ABI-encoded constructor arguments of the contract.
+    #[clap(long, value_name = "HEX")]
+    constructor_args: Option<Bytes>,
+
+    /// The sender of the estimated deployment.
+    #[clap(long, value_name = "ADDRESS", default_value_t = DEFAULT_SENDER)]
+    from: Address,
+
+    /// Print the costs as JSON.
+    #[clap(long, short)]
+    json: bool,
+}
+
+/// A bytecode published when deploying the contract.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishedBytecode {
+    hash: B256,
+    size: usize,
+    words: usize,
+    /// The pubdata publishing the bytecode, compressed if it's shorter.
+    pubdata: usize,
+    /// Whether the bytecode is the one of the deployed contract, not a factory dependency.
+    is_main: bool,
+    /// Whether the bytecode is already published on the chain, `None` if it wasn't checked.
+    known: Option<bool>,
+}
+
+/// The costs of deploying the contract on Era.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EraDeployCost {
+    bytecodes: Vec<PublishedBytecode>,
+    pubdata: u64,
+    gas_per_pubdata: u64,
+    /// The gas of publishing the pubdata.
+    pubdata_gas: u64,
+    /// The gas of the whole deployment estimated by the node, including the execution of the
+    /// constructor, if an RPC endpoint is given.
+    gas: Option<u64>,
+    gas_price: Option<U256>,
+    fee: Option<U256>,
+}
+
+/// The costs of deploying the contract on the EVM.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EvmDeployCost {
+    initcode_size: usize,
+    deployed_size: usize,
+    gas: u64,
+    gas_price: Option<U256>,
+    fee: Option<U256>,
+}
+
+impl CodeDeployCostArgs {
+    pub async fn run(self) -> Result<()> {
+        let artifact = read_artifact(&self.artifact)?;
+        let code = artifact
+            .bytecode
+            .as_ref()
+            .and_then(|bytecode| bytecode.object.as_bytes())
+            .filter(|code| !code.is_empty())
+            .ok_or_else(|| eyre::eyre!("{} has no bytecode", self.artifact.display()))?;
+        let packed: PackedEraBytecode = serde_json::from_slice(code)
+            .wrap_err_with(|| format!("{} is not a zksolc artifact", self.artifact.display()))?;
+
+        // the factory dependencies come first, the bytecode of the contract last
+        let deps = packed.factory_deps();
+        let mut bytecodes = deps
+            .iter()
+            .enumerate()
+            .map(|(i, code)| PublishedBytecode {
+                hash: foundry_zksync_core::hash_bytecode(code).to_b256(),
+                size: code.len(),
+                words: code.len() / 32,
+                pubdata: pubdata::bytecode_pubdata(code),
+                is_main: i == deps.len() - 1,
+                known: None,
+            })
+            .collect::<Vec<_>>();
+        bytecodes.dedup_by_key(|bytecode| bytecode.hash);
+
+        let provider = self.l2_rpc_url.as_deref().map(try_get_http_provider).transpose()?;
+        let (gas_per_pubdata, l2_gas_price, l1_gas_price) = match &provider {
+            Some(provider) => {
+                for bytecode in &mut bytecodes {
+                    let marker = call(
+                        provider,
+                        KNOWN_CODES_STORAGE,
+                        IKnownCodesStorage::getMarkerCall { hash: bytecode.hash },
+                    )
+                    .await
+                    .wrap_err("failed to query the known codes, is the RPC an Era node?")?
+                    ._0;
+                    bytecode.known = Some(!marker.is_zero());
+                }
+                let l2_gas_price = provider.get_gas_price().await?.to_alloy();
+                let l1_gas_price: U256 = provider.request("zks_getL1GasPrice", ()).await?;
+                let gas_per_pubdata = self
+                    .gas_per_pubdata
+                    .unwrap_or_else(|| gas_per_pubdata(l1_gas_price, l2_gas_price));
+                (gas_per_pubdata, Some(l2_gas_price), Some(l1_gas_price))
+            }
+            None => (self.gas_per_pubdata.unwrap_or(DEFAULT_GAS_PER_PUBDATA), None, None),
+        };
+
+        // the bytecodes unknown to the chain, and the initial write of the code hash of the
+        // deployed contract
+        let code_hash =
+            bytecodes.last().map_or(U256::ZERO, |bytecode| U256::from_be_bytes(bytecode.hash.0));
+        let pubdata = bytecodes
+            .iter()
+            .filter(|bytecode| bytecode.known != Some(true))
+            .map(|bytecode| bytecode.pubdata as u64)
+            .sum::<u64>() +
+            pubdata::state_diff_pubdata(true, U256::ZERO, code_hash) as u64;
+        let gas = match &provider {
+            Some(provider) => Some(self.estimate_deployment(provider, &deps, code_hash).await?),
+            None => None,
+        };
+        let era = EraDeployCost {
+            bytecodes,
+            pubdata,
+            gas_per_pubdata,
+            pubdata_gas: pubdata.saturating_mul(gas_per_pubdata),
+            gas,
+            gas_price: l2_gas_price,
+            fee: l2_gas_price.zip(gas).map(|(price, gas)| price * U256::from(gas)),
+        };
+
+        let evm_artifact = self.evm_artifact.clone().or_else(|| evm_artifact_path(&self.artifact));
+        let evm = evm_artifact
+            .map(|path| -> Result<_> {
+                let artifact = read_artifact(&path)?;
+                let initcode = artifact
+                    .bytecode
+                    .as_ref()
+                    .and_then(|bytecode| bytecode.object.as_bytes())
+                    .filter(|code| !code.is_empty())
+                    .ok_or_else(|| eyre::eyre!("{} has no bytecode", path.display()))?;
+                let deployed_size = artifact
+                    .deployed_bytecode
+                    .as_ref()
+                    .and_then(|deployed| deployed.bytecode.as_ref())
+                    .and_then(|bytecode| bytecode.object.as_bytes())
+                    .map_or(0, |code| code.len());
+                let gas = evm_deploy_gas(initcode, deployed_size);
+                Ok(EvmDeployCost {
+                    initcode_size: initcode.len(),
+                    deployed_size,
+                    gas,
+                    gas_price: l1_gas_price,
+                    fee: l1_gas_price.map(|price| price * U256::from(gas)),
+                })
+            })
+            .transpose()?;
+
+        if self.json {
+            let json = serde_json::json!({ "era": era, "evm": evm });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            return Ok(())
+        }
+
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(["Bytecode", "Hash", "Size", "Words", "Pubdata", "Published"]);
+        for bytecode in &era.bytecodes {
+            table.add_row([
+                if bytecode.is_main { "contract" } else { "factory dependency" }.to_string(),
+                bytecode.hash.to_string(),
+                bytecode.size.to_string(),
+                bytecode.words.to_string(),
+                bytecode.pubdata.to_string(),
+                match bytecode.known {
+                    Some(true) => "already known",
+                    Some(false) => "yes",
+                    None => "yes (unchecked)",
+                }
+                .to_string(),
+            ]);
+        }
+        println!("{table}\n");
+
+        match era.gas {
+            Some(gas) => println!(
+                "Era: {gas} gas estimated by the node{}, {} bytes of pubdata at {} gas per byte",
+                fee_suffix(era.fee),
+                era.pubdata,
+                era.gas_per_pubdata,
+            ),
+            None => println!(
+                "Era: {} bytes of pubdata at {} gas per byte, {} gas",
+                era.pubdata, era.gas_per_pubdata, era.pubdata_gas,
+            ),
+        }
+        match &evm {
+            Some(evm) => {
+                println!(
+                    "EVM: {} bytes of initcode deploying {} bytes, {} gas{}",
+                    evm.initcode_size,
+                    evm.deployed_size,
+                    evm.gas,
+                    fee_suffix(evm.fee)
+                );
+            }
+            None => println!("EVM: no solc artifact found, pass one with --evm-artifact"),
+        }
+        if era.gas.is_none() {
+            println!(
+                "\nThe Era gas only covers publishing the compressed bytecodes and the code hash of \
+                 the contract, pass --l2-rpc-url to estimate the whole deployment."
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the gas of deploying the contract with bytecode hash `code_hash` and its factory
+    /// dependencies `deps`, estimated by the node with `zks_estimateFee`.
+    async fn estimate_deployment(
+        &self,
+        provider: &RetryProvider,
+        deps: &[Vec<u8>],
+        code_hash: U256,
+    ) -> Result<u64> {
+        let constructor_args = self.constructor_args.clone().unwrap_or_default();
+        let from = self.from.to_ethers();
+        let tx: TypedTransaction = deployment_request(self.from, code_hash, &constructor_args)
+            .chain_id(provider.get_chainid().await?.as_u64())
+            .nonce(provider.get_transaction_count(from, None).await?)
+            .gas_price(provider.get_gas_price().await?)
+            .into();
+        let estimated = foundry_zksync_core::estimate_gas(&tx, deps.to_vec(), provider)
+            .await
+            .wrap_err("failed to estimate the deployment, are the constructor arguments set?")?;
+        Ok(estimated.limit.saturating_to())
+    }
+}
+
+fn read_artifact(path: &Path) -> Result<ConfigurableContractArtifact> {
+    fs::read_json_file(path).wrap_err_with(|| format!("failed to read {}", path.display()))
+}
+
+/// Returns the path of the solc artifact matching the zksolc artifact at `path`, by replacing its
+/// `zkout` directory with `out`, if it exists.
+fn evm_artifact_path(path: &Path) -> Option<PathBuf> {
+    let mut replaced = false;
+    let evm_path = path
+        .components()
+        .map(|component| match component {
+            Component::Normal(name) if !replaced && name == "zkout" => {
+                replaced = true;
+                Component::Normal("out".as_ref())
+            }
+            component => component,
+        })
+        .collect::<PathBuf>();
+    (replaced && evm_path.is_file()).then_some(evm_path)
+}
+
+/// Returns the call of the ContractDeployer deploying the contract with bytecode hash `code_hash`.
+fn deployment_request(
+    from: Address,
+    code_hash: U256,
+    constructor_args: &[u8],
+) -> TransactionRequest {
+    let data = foundry_zksync_core::encode_create_params(
+        &CreateScheme::Create,
+        B256::from(code_hash).to_h256(),
+        constructor_args.to_vec(),
+    );
+    TransactionRequest::new()
+        .from(from.to_ethers())
+        .to(foundry_zksync_core::CONTRACT_DEPLOYER_ADDRESS)
+        .data(data)
+}
+
+/// Returns the gas per pubdata byte of a chain with the given gas prices.
+fn gas_per_pubdata(l1_gas_price: U256, l2_gas_price: U256) -> u64 {
+    if l2_gas_price.is_zero() {
+        return DEFAULT_GAS_PER_PUBDATA
+    }
+    let pubdata_price = l1_gas_price.saturating_mul(U256::from(L1_GAS_PER_PUBDATA_BYTE));
+    pubdata_price.div_ceil(l2_gas_price).saturating_to()
+}
+
+/// Returns the gas of a transaction deploying a contract with `initcode` on the EVM, excluding
+/// the execution of the constructor.
+fn evm_deploy_gas(initcode: &[u8], deployed_size: usize) -> u64 {
+    let calldata = initcode.iter().map(|&byte| if byte == 0 { 4 } else { 16 }).sum::<u64>();
+    let initcode_words = initcode.len().div_ceil(32) as u64;
+    // the base cost of a transaction, of contract creations, and of the initcode words (EIP-3860)
+    21_000 + 32_000 + calldata + 2 * initcode_words + 200 * deployed_size as u64
+}
+
+fn fee_suffix(fee: Option<U256>) -> String {
+    fee.map(|fee| format!(" ({} ETH)", alloy_primitives::utils::format_ether(fee)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_evm_deploy_gas() {
+        assert_eq!(evm_deploy_gas(&[], 0), 53_000);
+        assert_eq!(evm_deploy_gas(&[0x60, 0x00, 0x60, 0x00], 2), 53_000 + 40 + 2 + 400);
+    }
+
+    #[test]
+    fn computes_gas_per_pubdata() {
+        let gwei = U256::from(1_000_000_000u64);
+        assert_eq!(gas_per_pubdata(gwei * U256::from(20), gwei / U256::from(4)), 1360);
+        assert_eq!(gas_per_pubdata(gwei, U256::ZERO), DEFAULT_GAS_PER_PUBDATA);
+    }
+
+    #[test]
+    fn calls_contract_deployer() {
+        let code_hash = U256::from(0xc0de);
+        let tx = deployment_request(DEFAULT_SENDER, code_hash, &[0xaa; 32]);
+        assert_eq!(
+            tx.to.and_then(|to| to.as_address().copied()),
+            Some(foundry_zksync_core::CONTRACT_DEPLOYER_ADDRESS)
+        );
+        assert_eq!(tx.from, Some(DEFAULT_SENDER.to_ethers()));
+
+        // create(bytes32 salt, bytes32 bytecodeHash, bytes input)
+        let data = tx.data.unwrap();
+        assert_eq!(data[..4], alloy_primitives::hex!("9c4d535b"));
+        assert_eq!(data[36..68], B256::from(code_hash)[..]);
+        assert!(data.ends_with(&[0xaa; 32]));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::cmd::create2::{Create2Output, SaltSearchArgs};
+use alloy_primitives::{keccak256, Address, B256};
+use clap::Parser;
+use eyre::{Result, WrapErr};
+use foundry_zksync_core::convert::ConvertH256;
+
+/// CLI arguments for `cast zk find-salt`.
+#[derive(Clone, Debug, Parser)]
+pub struct FindSaltArgs {
+    /// Address of the contract deployer, calling the `ContractDeployer` system contract.
+    #[clap(short, long, value_name = "ADDRESS")]
+    deployer: Address,
+
+    /// zksolc bytecode of the contract to be deployed.
+    #[clap(short, long, value_name = "HEX")]
+    bytecode: Option<String>,
+
+    /// Bytecode hash of the contract to be deployed, as versioned by EraVM.
+    #[clap(long, value_name = "HASH", required_unless_present = "bytecode")]
+    bytecode_hash: Option<B256>,
+
+    /// ABI-encoded constructor arguments of the contract.
+    #[clap(long, value_name = "HEX")]
+    constructor_args: Option<String>,
+
+    #[clap(flatten)]
+    search: SaltSearchArgs,
+}
+
+impl FindSaltArgs {
+    pub fn run(self) -> Result<Create2Output> {
+        let FindSaltArgs { deployer, bytecode, bytecode_hash, constructor_args, search } = self;
+
+        let bytecode_hash = match (bytecode_hash, bytecode) {
+            (Some(hash), _) => hash,
+            (None, Some(bytecode)) => {
+                let bytecode = hex::decode(bytecode).wrap_err("invalid bytecode provided")?;
+                if bytecode.is_empty() || bytecode.len() % 32 != 0 {
+                    eyre::bail!("an EraVM bytecode is made of 32 bytes words");
+                }
+                foundry_zksync_core::hash_bytecode(&bytecode).to_b256()
+            }
+            (None, None) => unreachable!(),
+        };
+        let input = match constructor_args {
+            Some(args) => hex::decode(args).wrap_err("invalid constructor args provided")?,
+            None => Vec::new(),
+        };
+        let input_hash = keccak256(input);
+
+        search.run(move |salt| {
+            foundry_zksync_core::compute_create2_address(deployer, bytecode_hash, *salt, input_hash)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn finds_era_salt() {
+        let deployer = address!("0000000000000000000000000000000000001234");
+        let bytecode_hash = B256::with_last_byte(1);
+        let deployer_arg = format!("--deployer={deployer}");
+        let bytecode_hash_arg = format!("--bytecode-hash={bytecode_hash}");
+        let args = FindSaltArgs::parse_from([
+            "foundry-cli",
+            "--starts-with=aa",
+            deployer_arg.as_str(),
+            bytecode_hash_arg.as_str(),
+            "--constructor-args=0x01",
+        ]);
+        let out = args.run().unwrap();
+        assert!(format!("{:x}", out.address).starts_with("aa"));
+        assert_eq!(
+            out.address,
+            foundry_zksync_core::compute_create2_address(
+                deployer,
+                bytecode_hash,
+                out.salt,
+                keccak256([1u8])
+            )
+        );
+
+        let err = FindSaltArgs::parse_from([
+            "foundry-cli",
+            "--starts-with=aa",
+            deployer_arg.as_str(),
+            "--bytecode=0x0102",
+        ])
+        .run()
+        .unwrap_err();
+        assert!(err.to_string().contains("32 bytes words"), "{err}");
+    }
+}
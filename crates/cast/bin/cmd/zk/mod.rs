@@ -0,0 +1,79 @@
+use alloy_primitives::Address;
+use alloy_sol_types::SolCall;
+use clap::Parser;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::types::ToEthers;
+
+pub mod address;
+
+pub mod deploy_bridged_token;
+use deploy_bridged_token::DeployBridgedTokenArgs;
+
+pub mod deploy_cost;
+use deploy_cost::CodeDeployCostArgs;
+
+pub mod find_salt;
+use find_salt::FindSaltArgs;
+
+pub mod proof;
+use proof::ProofSubcommand;
+
+pub mod tx;
+
+/// CLI arguments for `cast zk`.
+#[derive(Debug, Parser)]
+pub enum ZkSubcommand {
+    /// Deploy the bridged ERC-20 representation of an L1 token on a zkSync Era chain.
+    ///
+    /// The token is deployed on L2 by the shared bridge when it's first deposited, so this makes a
+    /// small deposit and waits for it to be processed on L2.
+    #[clap(visible_alias = "dbt")]
+    DeployBridgedToken(DeployBridgedTokenArgs),
+
+    /// Estimate the gas and pubdata of deploying a contract on a zkSync Era chain, compared to its
+    /// deployment on the EVM.
+    ///
+    /// The bytecodes already published on the chain are free to deploy again, which is checked in
+    /// the KnownCodesStorage system contract when an RPC endpoint is given.
+    #[clap(visible_alias = "cdc")]
+    CodeDeployCost(CodeDeployCostArgs),
+
+    /// Generate a deterministic contract address on a zkSync Era chain, by brute-forcing the
+    /// salt of its `CREATE2` deployment.
+    ///
+    /// Era derives `CREATE2` addresses from the bytecode hash and the constructor arguments of the
+    /// contract, so the addresses differ from the EVM ones of `cast create2`.
+    #[clap(visible_alias = "fs")]
+    FindSalt(FindSaltArgs),
+
+    /// Fetch the Merkle proofs of L2 to L1 logs and storage slots, and verify them against the
+    /// batch roots known to L1.
+    Proof {
+        #[clap(subcommand)]
+        command: ProofSubcommand,
+    },
+}
+
+impl ZkSubcommand {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Self::DeployBridgedToken(args) => args.run().await,
+            Self::CodeDeployCost(args) => args.run().await,
+            Self::FindSalt(args) => args.run().map(drop),
+            Self::Proof { command } => command.run().await,
+        }
+    }
+}
+
+/// Calls a view function of the contract at `to`.
+async fn call<M: Middleware, C: SolCall>(provider: &M, to: Address, call: C) -> Result<C::Return>
+where
+    M::Error: 'static,
+{
+    let tx: TypedTransaction =
+        TransactionRequest::new().to(to.to_ethers()).data(call.abi_encode()).into();
+    let ret = provider.call(&tx, None).await?;
+    Ok(C::abi_decode_returns(&ret, true)?)
+}
@@ -0,0 +1,545 @@
+use super::call;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256, U64};
+use alloy_sol_types::{sol, SolCall, SolValue};
+use clap::Parser;
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_common::{
+    provider::ethers::{try_get_http_provider, RetryProvider},
+    types::ToEthers,
+};
+use foundry_config::Config;
+use foundry_zksync_core::proof::{
+    l2_logs_root, padded_storage_proof, storage_root, storage_tree_key, L2ToL1Log,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+sol! {
+    struct L2Log {
+        uint8 l2ShardId;
+        bool isService;
+        uint16 txNumberInBatch;
+        address sender;
+        bytes32 key;
+        bytes32 value;
+    }
+
+    struct StoredBatchInfo {
+        uint64 batchNumber;
+        bytes32 batchHash;
+        uint64 indexRepeatedStorageChanges;
+        uint256 numberOfLayer1Txs;
+        bytes32 priorityOperationsHash;
+        bytes32 l2LogsTreeRoot;
+        uint256 timestamp;
+        bytes32 commitment;
+    }
+
+    struct PriorityOpsBatchInfo {
+        bytes32[] leftPath;
+        bytes32[] rightPath;
+        bytes32[] itemHashes;
+    }
+
+    interface IZkSyncChain {
+        function getTotalBatchesExecuted() external view returns (uint256);
+
+        function storedBatchHash(uint256 batchNumber) external view returns (bytes32);
+
+        function executeBatches(StoredBatchInfo[] calldata batchesData) external;
+
+        function executeBatchesSharedBridge(
+            uint256 chainId,
+            StoredBatchInfo[] calldata batchesData
+        ) external;
+
+        function l2LogsRootHash(uint256 batchNumber) external view returns (bytes32);
+
+        function proveL2LogInclusion(
+            uint256 batchNumber,
+            uint256 index,
+            L2Log calldata log,
+            bytes32[] calldata proof
+        ) external view returns (bool);
+    }
+
+    /// The executor of the chains settling through the gateway, whose batches are encoded in
+    /// `executeData`.
+    interface IGatewayExecutor {
+        function executeBatchesSharedBridge(
+            uint256 chainId,
+            uint256 processFrom,
+            uint256 processTo,
+            bytes calldata executeData
+        ) external;
+    }
+
+    /// The executor of the chains settling through the gateway, called by the address of the
+    /// chain.
+    interface IChainAddressExecutor {
+        function executeBatchesSharedBridge(
+            address chainAddress,
+            uint256 processFrom,
+            uint256 processTo,
+            bytes calldata executeData
+        ) external;
+    }
+}
+
+/// The version of the encoding of the `executeData` of the gateway executors.
+const EXECUTE_DATA_VERSION: u8 = 0;
+
+/// Returns the batches encoded in the `executeData` of a gateway `executeBatchesSharedBridge`,
+/// a version byte followed by the batches and their priority operations.
+fn decode_execute_data(data: &[u8]) -> Result<Vec<StoredBatchInfo>> {
+    match data.split_first() {
+        Some((&EXECUTE_DATA_VERSION, data)) => {
+            let (batches, _) =
+                <(Vec<StoredBatchInfo>, Vec<PriorityOpsBatchInfo>)>::abi_decode_params(data, true)?;
+            Ok(batches)
+        }
+        Some((version, _)) => eyre::bail!("unsupported version {version} of the execute data"),
+        None => eyre::bail!("the execute data is empty"),
+    }
+}
+
+/// The fields of a transaction receipt of an Era chain needed for log proofs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L2Receipt {
+    l1_batch_number: Option<U64>,
+    #[serde(default)]
+    l2_to_l1_logs: Vec<RpcL2ToL1Log>,
+}
+
+/// An L2 to L1 log, as returned in the receipts of an Era chain.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcL2ToL1Log {
+    shard_id: U64,
+    is_service: bool,
+    tx_index_in_l1_batch: U64,
+    sender: Address,
+    key: B256,
+    value: B256,
+}
+
+/// The response of `zks_getL2ToL1LogProof`.
+#[derive(Debug, Deserialize)]
+struct LogProof {
+    proof: Vec<B256>,
+    id: u32,
+    root: B256,
+}
+
+/// The response of `zks_getProof`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountProof {
+    storage_proof: Vec<StorageProof>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageProof {
+    key: B256,
+    proof: Vec<B256>,
+    value: B256,
+    index: u64,
+}
+
+/// The fields of the response of `zks_getL1BatchDetails` needed for storage proofs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchDetails {
+    root_hash: Option<B256>,
+    execute_tx_hash: Option<B256>,
+}
+
+/// Returns the stored info of `batch` among the batches executed by the calldata of an
+/// `executeBatches` or `executeBatchesSharedBridge` transaction.
+fn stored_batch_info(input: &[u8], batch: u64) -> Result<StoredBatchInfo> {
+    let selector = input.get(..4).unwrap_or_default();
+    let batches = if selector == IZkSyncChain::executeBatchesCall::SELECTOR {
+        IZkSyncChain::executeBatchesCall::abi_decode(input, true)?.batchesData
+    } else if selector == IZkSyncChain::executeBatchesSharedBridgeCall::SELECTOR {
+        IZkSyncChain::executeBatchesSharedBridgeCall::abi_decode(input, true)?.batchesData
+    } else if selector == IGatewayExecutor::executeBatchesSharedBridgeCall::SELECTOR {
+        let call = IGatewayExecutor::executeBatchesSharedBridgeCall::abi_decode(input, true)?;
+        decode_execute_data(&call.executeData)?
+    } else if selector == IChainAddressExecutor::executeBatchesSharedBridgeCall::SELECTOR {
+        let call = IChainAddressExecutor::executeBatchesSharedBridgeCall::abi_decode(input, true)?;
+        decode_execute_data(&call.executeData)?
+    } else {
+        eyre::bail!("the execute transaction of batch {batch} doesn't call executeBatches")
+    };
+    batches
+        .into_iter()
+        .find(|info| info.batchNumber == batch)
+        .ok_or_else(|| eyre::eyre!("batch {batch} is not executed by its execute transaction"))
+}
+
+/// The chain flags of the proof commands.
+#[derive(Debug, Parser)]
+pub struct ProofChainArgs {
+    /// The RPC endpoint of the Era chain.
+    #[clap(long, env = "ZKSYNC_RPC_URL", value_name = "URL")]
+    l2_rpc_url: String,
+
+    /// Print the proof as JSON, including the Merkle path and the calldata to verify it in
+    /// Solidity.
+    #[clap(long, short)]
+    json: bool,
+
+    /// The RPC endpoint of L1, where the batch roots are read from.
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl ProofChainArgs {
+    /// Returns the L1 and L2 providers and the address of the chain's L1 contract.
+    async fn providers(&self) -> Result<(RetryProvider, RetryProvider, Address)> {
+        let l1 = utils::get_provider(&Config::from(&self.rpc))?;
+        let l2 = try_get_http_provider(&self.l2_rpc_url)?;
+        let chain: Address = l2
+            .request("zks_getMainContract", ())
+            .await
+            .wrap_err("failed to get the L1 contract of the chain, is the L2 RPC an Era node?")?;
+        Ok((l1, l2, chain))
+    }
+}
+
+/// CLI arguments for `cast zk proof`.
+#[derive(Debug, Parser)]
+pub enum ProofSubcommand {
+    /// Fetch the proof that an L2 to L1 log is included in its batch, and verify it against the
+    /// L2 logs root stored on L1.
+    Log(LogProofArgs),
+
+    /// Fetch the proofs of storage slots of an account in a batch, and verify them against the
+    /// state root of the batch stored on L1.
+    ///
+    /// The batch must be executed on L1. Its state root is read from the calldata of the
+    /// transaction that executed it, which is checked against the batch hash stored on L1.
+    Storage(StorageProofArgs),
+}
+
+impl ProofSubcommand {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Self::Log(args) => args.run().await,
+            Self::Storage(args) => args.run().await,
+        }
+    }
+}
+
+/// CLI arguments for `cast zk proof log`.
+#[derive(Debug, Parser)]
+pub struct LogProofArgs {
+    /// The hash of the L2 transaction that sent the log.
+    tx_hash: B256,
+
+    /// The index of the log among the L2 to L1 logs of the transaction.
+    #[clap(long, default_value_t = 0)]
+    index: usize,
+
+    #[clap(flatten)]
+    chain: ProofChainArgs,
+}
+
+impl LogProofArgs {
+    pub async fn run(self) -> Result<()> {
+        let (l1, l2, chain) = self.chain.providers().await?;
+
+        let receipt: Option<L2Receipt> =
+            l2.request("eth_getTransactionReceipt", [self.tx_hash]).await?;
+        let receipt =
+            receipt.ok_or_else(|| eyre::eyre!("transaction {} not found", self.tx_hash))?;
+        let batch = receipt.l1_batch_number.ok_or_else(|| {
+            eyre::eyre!("transaction {} is not included in a batch yet", self.tx_hash)
+        })?;
+        let log = receipt.l2_to_l1_logs.get(self.index).ok_or_else(|| {
+            eyre::eyre!(
+                "transaction {} has {} L2 to L1 logs, there's no log {}",
+                self.tx_hash,
+                receipt.l2_to_l1_logs.len(),
+                self.index
+            )
+        })?;
+        let log = L2ToL1Log {
+            shard_id: log.shard_id.to::<u8>(),
+            is_service: log.is_service,
+            tx_number_in_batch: log.tx_index_in_l1_batch.to::<u16>(),
+            sender: log.sender,
+            key: log.key,
+            value: log.value,
+        };
+
+        let proof: Option<LogProof> =
+            l2.request("zks_getL2ToL1LogProof", (self.tx_hash, self.index)).await?;
+        let proof = proof.ok_or_else(|| {
+            eyre::eyre!("the proof of the log is not available yet, batch {batch} is not sealed")
+        })?;
+
+        let root = l2_logs_root(log.leaf_hash(), proof.id, &proof.proof);
+        if root != proof.root {
+            eyre::bail!("invalid proof: computed root {root}, the node returned {}", proof.root)
+        }
+        let batch = U256::from(batch.to::<u64>());
+        let l1_root =
+            call(&l1, chain, IZkSyncChain::l2LogsRootHashCall { batchNumber: batch }).await?._0;
+        if l1_root.is_zero() {
+            eyre::bail!("batch {batch} is not executed on L1 yet")
+        }
+        if l1_root != root {
+            eyre::bail!(
+                "invalid proof: computed root {root}, the L1 root of batch {batch} is {l1_root}"
+            )
+        }
+
+        let calldata = Bytes::from(
+            IZkSyncChain::proveL2LogInclusionCall {
+                batchNumber: batch,
+                index: U256::from(proof.id),
+                log: L2Log {
+                    l2ShardId: log.shard_id,
+                    isService: log.is_service,
+                    txNumberInBatch: log.tx_number_in_batch,
+                    sender: log.sender,
+                    key: log.key,
+                    value: log.value,
+                },
+                proof: proof.proof.clone(),
+            }
+            .abi_encode(),
+        );
+
+        if self.chain.json {
+            let json = json!({
+                "batchNumber": batch,
+                "index": proof.id,
+                "log": {
+                    "l2ShardId": log.shard_id,
+                    "isService": log.is_service,
+                    "txNumberInBatch": log.tx_number_in_batch,
+                    "sender": log.sender,
+                    "key": log.key,
+                    "value": log.value,
+                },
+                "leaf": log.leaf_hash(),
+                "proof": proof.proof,
+                "root": root,
+                "verifier": chain,
+                "calldata": calldata,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        } else {
+            println!("Verified log {} of {} in batch {batch}", self.index, self.tx_hash);
+            println!("Root: {root}");
+            println!("proveL2LogInclusion calldata for {chain}: {calldata}");
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `cast zk proof storage`.
+#[derive(Debug, Parser)]
+pub struct StorageProofArgs {
+    /// The account of the storage slots.
+    address: Address,
+
+    /// The storage slots to prove.
+    #[clap(required = true)]
+    slots: Vec<B256>,
+
+    /// The batch to prove the slots in. Defaults to the last batch executed on L1.
+    #[clap(long)]
+    batch: Option<u64>,
+
+    #[clap(flatten)]
+    chain: ProofChainArgs,
+}
+
+impl StorageProofArgs {
+    pub async fn run(self) -> Result<()> {
+        let (l1, l2, chain) = self.chain.providers().await?;
+
+        let executed = call(&l1, chain, IZkSyncChain::getTotalBatchesExecutedCall {}).await?._0;
+        let executed = executed.to::<u64>();
+        let batch = self.batch.unwrap_or(executed);
+        if batch > executed {
+            eyre::bail!("batch {batch} is not executed on L1 yet, the last executed is {executed}")
+        }
+
+        let details: Option<BatchDetails> = l2.request("zks_getL1BatchDetails", [batch]).await?;
+        let details =
+            details.ok_or_else(|| eyre::eyre!("the details of batch {batch} are not available"))?;
+        let execute_tx = details.execute_tx_hash.ok_or_else(|| {
+            eyre::eyre!("the execute transaction of batch {batch} is not available")
+        })?;
+        let execute_tx = l1
+            .get_transaction(execute_tx.to_ethers())
+            .await?
+            .ok_or_else(|| eyre::eyre!("execute transaction {execute_tx} not found on L1"))?;
+        let info = stored_batch_info(&execute_tx.input, batch)?;
+
+        let stored =
+            call(&l1, chain, IZkSyncChain::storedBatchHashCall { batchNumber: U256::from(batch) })
+                .await?
+                ._0;
+        let hash = keccak256(info.abi_encode());
+        if hash != stored {
+            eyre::bail!(
+                "the executed info of batch {batch} hashes to {hash}, the hash stored on L1 is \
+                 {stored}"
+            )
+        }
+        let root = info.batchHash;
+        if let Some(l2_root) = details.root_hash.filter(|l2_root| *l2_root != root) {
+            eyre::bail!(
+                "the L2 node returned state root {l2_root}, the L1 root of batch {batch} is {root}"
+            )
+        }
+
+        let proof: Option<AccountProof> =
+            l2.request("zks_getProof", (self.address, &self.slots, batch)).await?;
+        let proof =
+            proof.ok_or_else(|| eyre::eyre!("the node has no storage proofs for batch {batch}"))?;
+
+        let mut slots = Vec::with_capacity(proof.storage_proof.len());
+        for slot in &proof.storage_proof {
+            let computed =
+                storage_root(self.address, slot.key, slot.value, slot.index, &slot.proof);
+            if computed != root {
+                eyre::bail!(
+                    "invalid proof of slot {}: computed root {computed}, the root of batch {batch} \
+                     is {root}",
+                    slot.key
+                )
+            }
+            slots.push(json!({
+                "key": slot.key,
+                "treeKey": storage_tree_key(self.address, slot.key),
+                "value": slot.value,
+                "index": slot.index,
+                "proof": padded_storage_proof(&slot.proof),
+            }));
+        }
+
+        if self.chain.json {
+            let json = json!({
+                "address": self.address,
+                "batchNumber": batch,
+                "root": root,
+                "storageProof": slots,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        } else {
+            println!("Verified {} slots of {} in batch {batch}", slots.len(), self.address);
+            println!("Root: {root}");
+            for slot in &proof.storage_proof {
+                println!("  {}: {}", slot.key, slot.value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, hex};
+
+    #[test]
+    fn uses_the_selectors_of_the_executor() {
+        assert_eq!(IZkSyncChain::executeBatchesCall::SELECTOR, hex!("c3d93e7c"));
+        assert_eq!(IZkSyncChain::executeBatchesSharedBridgeCall::SELECTOR, hex!("6f497ac6"));
+        assert_eq!(IGatewayExecutor::executeBatchesSharedBridgeCall::SELECTOR, hex!("cf02827d"));
+        assert_eq!(
+            IChainAddressExecutor::executeBatchesSharedBridgeCall::SELECTOR,
+            hex!("a085344d")
+        );
+        assert_eq!(IZkSyncChain::storedBatchHashCall::SELECTOR, hex!("b22dd78e"));
+    }
+
+    #[test]
+    fn hashes_stored_batch_info() {
+        let info = StoredBatchInfo {
+            batchNumber: 500_000,
+            batchHash: B256::repeat_byte(0x11),
+            indexRepeatedStorageChanges: 123_456,
+            numberOfLayer1Txs: U256::from(2),
+            priorityOperationsHash: B256::repeat_byte(0x22),
+            l2LogsTreeRoot: B256::repeat_byte(0x33),
+            timestamp: U256::from(1_700_000_000),
+            commitment: B256::repeat_byte(0x44),
+        };
+        assert_eq!(
+            keccak256(info.abi_encode()),
+            b256!("88fba8e577bc470017e7d0ae25b03f5b52a0fa0ad02d1a7f9c5af83c265ca5a9")
+        );
+    }
+
+    #[test]
+    fn finds_stored_batch_info() {
+        let info = |batch: u64| StoredBatchInfo {
+            batchNumber: batch,
+            batchHash: B256::repeat_byte(batch as u8),
+            indexRepeatedStorageChanges: 100,
+            numberOfLayer1Txs: U256::from(1),
+            priorityOperationsHash: B256::repeat_byte(0xaa),
+            l2LogsTreeRoot: B256::repeat_byte(0xbb),
+            timestamp: U256::from(1_700_000_000),
+            commitment: B256::repeat_byte(0xcc),
+        };
+
+        let input =
+            IZkSyncChain::executeBatchesCall { batchesData: vec![info(7), info(8)] }.abi_encode();
+        let found = stored_batch_info(&input, 8).unwrap();
+        assert_eq!(found.batchHash, B256::repeat_byte(8));
+        assert_eq!(keccak256(found.abi_encode()), keccak256(info(8).abi_encode()));
+        assert!(stored_batch_info(&input, 9).is_err());
+
+        let input = IZkSyncChain::executeBatchesSharedBridgeCall {
+            chainId: U256::from(324),
+            batchesData: vec![info(7)],
+        }
+        .abi_encode();
+        assert_eq!(stored_batch_info(&input, 7).unwrap().batchHash, B256::repeat_byte(7));
+
+        let mut execute_data = vec![EXECUTE_DATA_VERSION];
+        execute_data.extend(
+            (vec![info(7), info(8)], Vec::<PriorityOpsBatchInfo>::new()).abi_encode_params(),
+        );
+        let input = IGatewayExecutor::executeBatchesSharedBridgeCall {
+            chainId: U256::from(324),
+            processFrom: U256::from(7),
+            processTo: U256::from(8),
+            executeData: execute_data.clone().into(),
+        }
+        .abi_encode();
+        assert_eq!(stored_batch_info(&input, 8).unwrap().batchHash, B256::repeat_byte(8));
+        let input = IChainAddressExecutor::executeBatchesSharedBridgeCall {
+            chainAddress: Address::repeat_byte(1),
+            processFrom: U256::from(7),
+            processTo: U256::from(8),
+            executeData: execute_data.clone().into(),
+        }
+        .abi_encode();
+        assert_eq!(stored_batch_info(&input, 7).unwrap().batchHash, B256::repeat_byte(7));
+
+        execute_data[0] = 1;
+        let input = IGatewayExecutor::executeBatchesSharedBridgeCall {
+            chainId: U256::from(324),
+            processFrom: U256::from(7),
+            processTo: U256::from(8),
+            executeData: execute_data.into(),
+        }
+        .abi_encode();
+        assert!(stored_batch_info(&input, 7).is_err());
+
+        let input = IZkSyncChain::getTotalBatchesExecutedCall {}.abi_encode();
+        assert!(stored_batch_info(&input, 7).is_err());
+    }
+}
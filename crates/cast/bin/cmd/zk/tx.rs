@@ -0,0 +1,344 @@
+//! The zkSync Era fields of `cast tx --zksync` and `cast receipt --zksync`.
+
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use alloy_sol_types::{sol, SolEvent};
+use cast::Cast;
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::provider::ethers::RetryProvider;
+use foundry_zksync_core::{
+    convert::{ConvertH160, ConvertH256},
+    CONTRACT_DEPLOYER_ADDRESS,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+sol! {
+    /// Emitted by the `ContractDeployer` for every deployed contract.
+    event ContractDeployed(
+        address indexed deployerAddress,
+        bytes32 indexed bytecodeHash,
+        address indexed contractAddress
+    );
+}
+
+/// The fields of a transaction of an Era chain not returned by other chains.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L2Transaction {
+    block_number: Option<U64>,
+    l1_batch_number: Option<U64>,
+    l1_batch_tx_index: Option<U64>,
+}
+
+/// The response of `zks_getTransactionDetails`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionDetails {
+    is_l1_originated: bool,
+    status: String,
+    gas_per_pubdata: Option<U256>,
+    eth_commit_tx_hash: Option<B256>,
+    eth_prove_tx_hash: Option<B256>,
+    eth_execute_tx_hash: Option<B256>,
+}
+
+/// A transaction of `zks_getRawBlockTransactions`, with the fields of type 0x71 transactions.
+#[derive(Debug, Deserialize)]
+struct RawTransaction {
+    common_data: RawCommonData,
+    execute: RawExecute,
+}
+
+/// The common data of a transaction, keyed by its kind, `L1`, `L2` or `ProtocolUpgrade`.
+#[derive(Debug, Deserialize)]
+struct RawCommonData {
+    #[serde(rename = "L2")]
+    l2: Option<RawL2CommonData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawL2CommonData {
+    input: Option<RawInput>,
+    paymaster_params: RawPaymasterParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawInput {
+    hash: B256,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPaymasterParams {
+    paymaster: Address,
+    paymaster_input: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawExecute {
+    #[serde(default)]
+    factory_deps: Option<Vec<Vec<u8>>>,
+}
+
+/// The fields of a receipt of an Era chain not returned by other chains.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L2Receipt {
+    l1_batch_number: Option<U64>,
+    l1_batch_tx_index: Option<U64>,
+    #[serde(default)]
+    logs: Vec<RpcLog>,
+    #[serde(default)]
+    l2_to_l1_logs: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcLog {
+    address: Address,
+    topics: Vec<B256>,
+}
+
+/// The Era fields of a transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ZkTransaction {
+    l1_batch_number: Option<U64>,
+    l1_batch_tx_index: Option<U64>,
+    status: String,
+    is_l1_originated: bool,
+    gas_per_pubdata: Option<U256>,
+    paymaster: Option<Address>,
+    paymaster_input: Option<Bytes>,
+    factory_deps: Vec<B256>,
+    eth_commit_tx_hash: Option<B256>,
+    eth_prove_tx_hash: Option<B256>,
+    eth_execute_tx_hash: Option<B256>,
+}
+
+/// A contract deployed by a transaction, from the events of the `ContractDeployer`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeployedContract {
+    address: Address,
+    deployer: Address,
+    bytecode_hash: B256,
+}
+
+/// The Era fields of a receipt.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ZkReceipt {
+    l1_batch_number: Option<U64>,
+    l1_batch_tx_index: Option<U64>,
+    l2_to_l1_logs: usize,
+    deployed_contracts: Vec<DeployedContract>,
+}
+
+/// Returns `cast tx` for the transaction `tx_hash` of an Era chain, with its Era fields.
+pub async fn transaction(
+    provider: &RetryProvider,
+    tx_hash: String,
+    field: Option<String>,
+    json: bool,
+) -> Result<String> {
+    let hash: B256 = tx_hash.parse().wrap_err("invalid tx hash")?;
+    let tx: Option<L2Transaction> = provider.request("eth_getTransactionByHash", [hash]).await?;
+    let tx = tx.ok_or_else(|| eyre::eyre!("tx not found: {hash}"))?;
+    let details: Option<TransactionDetails> = provider
+        .request("zks_getTransactionDetails", [hash])
+        .await
+        .wrap_err("failed to get the transaction details, is the RPC an Era node?")?;
+    let details = details.ok_or_else(|| eyre::eyre!("tx not found: {hash}"))?;
+
+    // the paymaster and factory dependencies are only returned with the raw transactions of the
+    // block
+    let mut raw = None;
+    if let Some(block) = tx.block_number {
+        let txs: Vec<RawTransaction> =
+            provider.request("zks_getRawBlockTransactions", [block]).await?;
+        raw = txs.into_iter().find_map(|raw| {
+            let data = raw.common_data.l2?;
+            data.input
+                .as_ref()
+                .is_some_and(|input| input.hash == hash)
+                .then_some((data, raw.execute))
+        });
+    }
+    let (paymaster, factory_deps) = match raw {
+        Some((data, execute)) => {
+            (Some(data.paymaster_params), execute.factory_deps.unwrap_or_default())
+        }
+        None => (None, vec![]),
+    };
+    let paymaster = paymaster.filter(|params| !params.paymaster.is_zero());
+
+    let era = ZkTransaction {
+        l1_batch_number: tx.l1_batch_number,
+        l1_batch_tx_index: tx.l1_batch_tx_index,
+        status: details.status,
+        is_l1_originated: details.is_l1_originated,
+        gas_per_pubdata: details.gas_per_pubdata,
+        paymaster: paymaster.as_ref().map(|params| params.paymaster),
+        paymaster_input: paymaster.map(|params| params.paymaster_input.into()),
+        factory_deps: factory_deps
+            .iter()
+            .map(|code| foundry_zksync_core::hash_bytecode(code).to_b256())
+            .collect(),
+        eth_commit_tx_hash: details.eth_commit_tx_hash,
+        eth_prove_tx_hash: details.eth_prove_tx_hash,
+        eth_execute_tx_hash: details.eth_execute_tx_hash,
+    };
+    let era = serde_json::to_value(era)?;
+    if let Some(value) = field.as_deref().and_then(|field| era.get(field)) {
+        return Ok(pretty_value(value))
+    }
+
+    let output = Cast::new(provider).transaction(tx_hash, field.clone(), false, json).await?;
+    with_era_fields(output, era, field.is_some(), json)
+}
+
+/// Returns `cast receipt` for the transaction `tx_hash` of an Era chain, with its Era fields.
+pub async fn receipt(
+    provider: &RetryProvider,
+    tx_hash: String,
+    field: Option<String>,
+    confirmations: usize,
+    cast_async: bool,
+    json: bool,
+) -> Result<String> {
+    // waits for the receipt if needed
+    let output = Cast::new(provider)
+        .receipt(tx_hash.clone(), field.clone(), confirmations, cast_async, json)
+        .await;
+
+    let hash: B256 = tx_hash.parse().wrap_err("invalid tx hash")?;
+    let receipt: Option<L2Receipt> = provider.request("eth_getTransactionReceipt", [hash]).await?;
+    let receipt = receipt.ok_or_else(|| eyre::eyre!("tx not found: {hash}"))?;
+
+    let era = serde_json::to_value(ZkReceipt {
+        l1_batch_number: receipt.l1_batch_number,
+        l1_batch_tx_index: receipt.l1_batch_tx_index,
+        l2_to_l1_logs: receipt.l2_to_l1_logs.len(),
+        deployed_contracts: deployed_contracts(&receipt.logs),
+    })?;
+    if let Some(value) = field.as_deref().and_then(|field| era.get(field)) {
+        return Ok(pretty_value(value))
+    }
+    with_era_fields(output?, era, field.is_some(), json)
+}
+
+/// Returns the contracts deployed according to the `ContractDeployed` events in `logs`.
+fn deployed_contracts(logs: &[RpcLog]) -> Vec<DeployedContract> {
+    logs.iter()
+        // the `ContractDeployer` emits an event for every deployed contract
+        .filter(|log| log.address == CONTRACT_DEPLOYER_ADDRESS.to_address())
+        .filter_map(|log| match log.topics[..] {
+            [topic, deployer, bytecode_hash, address]
+                if topic == ContractDeployed::SIGNATURE_HASH =>
+            {
+                Some(DeployedContract {
+                    address: Address::from_word(address),
+                    deployer: Address::from_word(deployer),
+                    bytecode_hash,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Appends the Era fields to the output of `cast`, under a `zksync` key with `--json`.
+fn with_era_fields(output: String, era: Value, is_field: bool, json: bool) -> Result<String> {
+    if is_field {
+        return Ok(output)
+    }
+    if json {
+        let mut output: Value = serde_json::from_str(&output)?;
+        if let Value::Object(fields) = &mut output {
+            fields.insert("zksync".to_string(), era);
+        }
+        return Ok(output.to_string())
+    }
+
+    let mut output = output;
+    if let Value::Object(fields) = era {
+        for (key, value) in fields {
+            output += &format!("\n{key:<21}{}", pretty_value(&value));
+        }
+    }
+    Ok(output)
+}
+
+fn pretty_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        Value::Array(values) if values.is_empty() => "[]".to_string(),
+        Value::Array(values) => match values[0] {
+            Value::Object(_) => {
+                values.iter().map(|value| format!("\n  {}", pretty_object(value))).collect()
+            }
+            _ => format!("[{}]", values.iter().map(pretty_value).collect::<Vec<_>>().join(", ")),
+        },
+        value => value.to_string(),
+    }
+}
+
+fn pretty_object(value: &Value) -> String {
+    match value {
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| format!("{key}: {}", pretty_value(value)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        value => pretty_value(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn decodes_deployed_contracts() {
+        let deployer = address!("0000000000000000000000000000000000001234");
+        let contract = address!("0000000000000000000000000000000000005678");
+        let logs = vec![
+            RpcLog {
+                address: CONTRACT_DEPLOYER_ADDRESS.to_address(),
+                topics: vec![
+                    ContractDeployed::SIGNATURE_HASH,
+                    deployer.into_word(),
+                    B256::with_last_byte(1),
+                    contract.into_word(),
+                ],
+            },
+            RpcLog { address: deployer, topics: vec![ContractDeployed::SIGNATURE_HASH] },
+        ];
+        let deployed = deployed_contracts(&logs);
+        assert_eq!(deployed.len(), 1);
+        assert_eq!(deployed[0].address, contract);
+        assert_eq!(deployed[0].deployer, deployer);
+        assert_eq!(deployed[0].bytecode_hash, B256::with_last_byte(1));
+    }
+
+    #[test]
+    fn appends_era_fields() {
+        let era = serde_json::json!({ "factoryDeps": [], "l1BatchNumber": "0x5" });
+        let output =
+            with_era_fields("\nhash                 0x01".to_string(), era.clone(), false, false)
+                .unwrap();
+        assert_eq!(
+            output,
+            "\nhash                 0x01\nfactoryDeps          []\nl1BatchNumber        0x5"
+        );
+
+        let output = with_era_fields(r#"{"hash":"0x01"}"#.to_string(), era, false, true).unwrap();
+        let output: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(output["zksync"]["l1BatchNumber"], "0x5");
+    }
+}
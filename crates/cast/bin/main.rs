@@ -2,7 +2,7 @@
 extern crate tracing;
 
 use alloy_primitives::{keccak256, Address, B256};
-use cast::{Cast, SimpleCast, TxBuilder};
+use cast::{address_book::AddressBook, Cast, SimpleCast, TxBuilder};
 use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use ethers_core::types::{BlockId, BlockNumber::Latest, NameOrAddress};
@@ -172,7 +172,15 @@ async fn main() -> Result<()> {
             let tokens = format_tokens(&tokens);
             tokens.for_each(|t| println!("{t}"));
         }
-        CastSubcommand::CalldataEncode { sig, args } => {
+        CastSubcommand::DecodeError(cmd) => cmd.run().await?,
+        CastSubcommand::CalldataEncode { sig, args, resolve_names, chain } => {
+            let args = if resolve_names {
+                let config = Config::load();
+                let chain_id = chain.or(config.chain).map(|chain| chain.id());
+                AddressBook::load(&config, chain_id)?.resolve_args(&args)?
+            } else {
+                args
+            };
             println!("{}", SimpleCast::calldata_encode(sig, &args)?);
         }
         CastSubcommand::Interface(cmd) => cmd.run().await?,
@@ -294,12 +302,41 @@ async fn main() -> Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).codesize(who, block).await?);
         }
-        CastSubcommand::ComputeAddress { address, nonce, rpc } => {
+        CastSubcommand::ComputeAddress {
+            address,
+            nonce,
+            salt,
+            init_code_hash,
+            bytecode_hash,
+            constructor_args,
+            zksync,
+            rpc,
+        } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
 
             let address: Address = stdin::unwrap_line(address)?.parse()?;
-            let computed = Cast::new(&provider).compute_address(address, nonce).await?;
+            let computed = if zksync {
+                cmd::zk::address::compute_address(
+                    &provider,
+                    address,
+                    nonce,
+                    salt,
+                    bytecode_hash,
+                    constructor_args,
+                )
+                .await?
+            } else if let Some(salt) = salt {
+                let Some(init_code_hash) = init_code_hash else {
+                    eyre::bail!(
+                        "the init code hash of a CREATE2 deployment is required: set \
+                         --init-code-hash"
+                    )
+                };
+                address.create2(salt, init_code_hash)
+            } else {
+                Cast::new(&provider).compute_address(address, nonce).await?
+            };
             println!("Computed Address: {}", computed.to_checksum(None));
         }
         CastSubcommand::Disassemble { bytecode } => {
@@ -381,26 +418,51 @@ async fn main() -> Result<()> {
                 println!("{}", serde_json::json!(receipt));
             }
         }
-        CastSubcommand::Receipt { tx_hash, field, json, cast_async, confirmations, rpc } => {
+        CastSubcommand::Receipt {
+            tx_hash,
+            field,
+            json,
+            cast_async,
+            confirmations,
+            zksync,
+            rpc,
+        } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
-            println!(
-                "{}",
-                Cast::new(provider)
-                    .receipt(tx_hash, field, confirmations, cast_async, json)
-                    .await?
-            );
+            if zksync {
+                let receipt = cmd::zk::tx::receipt(
+                    &provider,
+                    tx_hash,
+                    field,
+                    confirmations,
+                    cast_async,
+                    json,
+                )
+                .await?;
+                println!("{receipt}");
+            } else {
+                println!(
+                    "{}",
+                    Cast::new(provider)
+                        .receipt(tx_hash, field, confirmations, cast_async, json)
+                        .await?
+                );
+            }
         }
         CastSubcommand::Run(cmd) => cmd.run().await?,
         CastSubcommand::SendTx(cmd) => cmd.run().await?,
-        CastSubcommand::Tx { tx_hash, field, raw, json, rpc } => {
+        CastSubcommand::Tx { tx_hash, field, raw, json, zksync, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
 
             // Can use either --raw or specify raw as a field
             let raw = raw || field.as_ref().is_some_and(|f| f == "raw");
 
-            println!("{}", Cast::new(&provider).transaction(tx_hash, field, raw, json).await?)
+            if zksync && !raw {
+                println!("{}", cmd::zk::tx::transaction(&provider, tx_hash, field, json).await?)
+            } else {
+                println!("{}", Cast::new(&provider).transaction(tx_hash, field, raw, json).await?)
+            }
         }
 
         // 4Byte
@@ -537,6 +599,8 @@ async fn main() -> Result<()> {
             cmd.run()?;
         }
         CastSubcommand::Wallet { command } => command.run().await?,
+        CastSubcommand::Erc4337 { command } => command.run().await?,
+        CastSubcommand::Zk { command } => command.run().await?,
         CastSubcommand::Completions { shell } => {
             generate(shell, &mut Opts::command(), "cast", &mut std::io::stdout())
         }
@@ -1,13 +1,16 @@
 use crate::cmd::{
     access_list::AccessListArgs, bind::BindArgs, call::CallArgs, create2::Create2Args,
-    estimate::EstimateArgs, find_block::FindBlockArgs, interface::InterfaceArgs, logs::LogsArgs,
-    rpc::RpcArgs, run::RunArgs, send::SendTxArgs, storage::StorageArgs, wallet::WalletSubcommands,
+    decode_error::DecodeErrorArgs, erc4337::Erc4337Subcommand, estimate::EstimateArgs,
+    find_block::FindBlockArgs, interface::InterfaceArgs, logs::LogsArgs, rpc::RpcArgs,
+    run::RunArgs, send::SendTxArgs, storage::StorageArgs, wallet::WalletSubcommands,
+    zk::ZkSubcommand,
 };
 use alloy_primitives::{Address, B256, U256};
 use clap::{Parser, Subcommand, ValueHint};
 use ethers_core::types::{BlockId, NameOrAddress};
 use eyre::Result;
-use foundry_cli::opts::{EtherscanOpts, RpcOpts};
+use foundry_cli::opts::{ChainValueParser, EtherscanOpts, RpcOpts};
+use foundry_config::Chain;
 use std::{path::PathBuf, str::FromStr};
 
 const VERSION_MESSAGE: &str = concat!(
@@ -338,6 +341,22 @@ pub enum CastSubcommand {
         /// The arguments to encode.
         #[clap(allow_hyphen_values = true)]
         args: Vec<String>,
+
+        /// Resolve `@Name` placeholders in the arguments to addresses.
+        ///
+        /// Names are looked up in the project's `labels` and, if a chain is given, the contracts
+        /// deployed by its scripts on that chain. Use `@{Name}` for names containing spaces.
+        #[clap(long)]
+        resolve_names: bool,
+
+        /// The chain whose deployments names are resolved from.
+        #[clap(
+            long,
+            requires = "resolve_names",
+            value_parser = ChainValueParser::default(),
+            value_name = "CHAIN"
+        )]
+        chain: Option<Chain>,
     },
 
     /// Get the symbolic name of the current chain.
@@ -360,16 +379,44 @@ pub enum CastSubcommand {
         rpc: RpcOpts,
     },
 
-    /// Compute the contract address from a given nonce and deployer address.
+    /// Compute the contract address from a given nonce and deployer address, or from the salt of
+    /// a `CREATE2` deployment.
     #[clap(visible_alias = "ca")]
     ComputeAddress {
         /// The deployer address.
         address: Option<String>,
 
         /// The nonce of the deployer address.
+        ///
+        /// With `--zksync`, the deployment nonce of the deployer, the number of contracts it
+        /// deployed.
         #[clap(long)]
         nonce: Option<u64>,
 
+        /// The salt of a `CREATE2` deployment, to compute its address instead of a `CREATE` one.
+        #[clap(long, value_name = "HEX", conflicts_with = "nonce")]
+        salt: Option<B256>,
+
+        /// The init code hash of the `CREATE2` deployment.
+        #[clap(long, value_name = "HASH", requires = "salt", conflicts_with = "zksync")]
+        init_code_hash: Option<B256>,
+
+        /// The bytecode hash of the contract of the zkSync `CREATE2` deployment, as versioned by
+        /// EraVM.
+        #[clap(long, value_name = "HASH", requires = "zksync")]
+        bytecode_hash: Option<B256>,
+
+        /// The ABI-encoded constructor arguments of the zkSync `CREATE2` deployment.
+        #[clap(long, value_name = "HEX", requires = "bytecode_hash")]
+        constructor_args: Option<String>,
+
+        /// Compute the address of a deployment on a zkSync Era chain.
+        ///
+        /// Era derives `CREATE` addresses from the deployment nonce of the deployer, and `CREATE2`
+        /// addresses from the bytecode hash and the constructor arguments of the contract.
+        #[clap(long)]
+        zksync: bool,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -403,6 +450,11 @@ pub enum CastSubcommand {
         #[clap(long, short, help_heading = "Display options")]
         json: bool,
 
+        /// Also print the zkSync Era fields of the transaction: its L1 batch and status, gas per
+        /// pubdata, paymaster and factory dependency hashes.
+        #[clap(long, conflicts_with = "raw")]
+        zksync: bool,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -428,6 +480,11 @@ pub enum CastSubcommand {
         #[clap(long, short, help_heading = "Display options")]
         json: bool,
 
+        /// Also print the zkSync Era fields of the receipt: its L1 batch, its number of L2 to L1
+        /// logs and the contracts it deployed.
+        #[clap(long)]
+        zksync: bool,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -467,6 +524,13 @@ pub enum CastSubcommand {
         calldata: String,
     },
 
+    /// Decode ABI-encoded revert data using the errors of the project's artifacts.
+    ///
+    /// Both the EVM and the zkSync artifacts are searched, along with the errors of the zkSync
+    /// system contracts.
+    #[clap(visible_aliases = &["--decode-error", "de"])]
+    DecodeError(DecodeErrorArgs),
+
     /// Decode ABI-encoded input or output data.
     ///
     /// Defaults to decoding output data. To decode input data pass --input.
@@ -798,6 +862,19 @@ pub enum CastSubcommand {
         command: WalletSubcommands,
     },
 
+    /// ERC-4337 user operation utilities, e.g. sending through a bundler.
+    #[clap(name = "4337")]
+    Erc4337 {
+        #[clap(subcommand)]
+        command: Erc4337Subcommand,
+    },
+
+    /// zkSync Era utilities.
+    Zk {
+        #[clap(subcommand)]
+        command: ZkSubcommand,
+    },
+
     /// Generate a Solidity interface from a given ABI.
     ///
     /// Currently does not support ABI encoder v2.
@@ -0,0 +1,212 @@
+//! Named addresses in calldata arguments.
+//!
+//! Arguments may refer to addresses by name with `@Name` (or `@{Name with spaces}`) placeholders,
+//! which are resolved from the project's `labels` and the contracts deployed by its scripts on a
+//! given chain.
+
+use alloy_primitives::Address;
+use eyre::{Result, WrapErr};
+use foundry_common::fs;
+use foundry_config::Config;
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path};
+
+/// The addresses known by name in a project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AddressBook {
+    chain_id: Option<u64>,
+    entries: BTreeMap<String, Address>,
+}
+
+/// The parts of a `run-latest.json` broadcast file needed to find deployments.
+#[derive(Deserialize)]
+struct BroadcastRun {
+    #[serde(default)]
+    transactions: Vec<BroadcastTransaction>,
+    #[serde(default)]
+    timestamp: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastTransaction {
+    transaction_type: String,
+    contract_name: Option<String>,
+    contract_address: Option<Address>,
+}
+
+impl AddressBook {
+    /// Loads the address book of the project for `chain_id`.
+    ///
+    /// Contracts deployed by the project's scripts on the chain are named after their contract,
+    /// with the latest deployment winning. Labels from the config take precedence over
+    /// deployments.
+    pub fn load(config: &Config, chain_id: Option<u64>) -> Result<Self> {
+        let mut book = Self { chain_id, entries: BTreeMap::new() };
+        if let Some(chain_id) = chain_id {
+            book.add_deployments(&config.broadcast, chain_id)?;
+        }
+        for (address, label) in &config.labels {
+            book.insert(label.clone(), *address);
+        }
+        Ok(book)
+    }
+
+    fn add_deployments(&mut self, broadcast: &Path, chain_id: u64) -> Result<()> {
+        let Ok(scripts) = std::fs::read_dir(broadcast) else { return Ok(()) };
+
+        let mut runs = Vec::new();
+        for script in scripts {
+            let path = script?.path().join(chain_id.to_string()).join("run-latest.json");
+            if !path.is_file() {
+                continue
+            }
+            let run: BroadcastRun = fs::read_json_file(&path)
+                .wrap_err_with(|| format!("failed to read deployments from {}", path.display()))?;
+            runs.push(run);
+        }
+
+        // apply the oldest runs first, so the latest deployment of a contract wins
+        runs.sort_by_key(|run| run.timestamp);
+        for tx in runs.into_iter().flat_map(|run| run.transactions) {
+            if !tx.transaction_type.starts_with("CREATE") {
+                continue
+            }
+            if let (Some(name), Some(address)) = (tx.contract_name, tx.contract_address) {
+                self.insert(name, address);
+            }
+        }
+        Ok(())
+    }
+
+    /// Binds `name` to `address`, replacing any previous binding.
+    pub fn insert(&mut self, name: impl Into<String>, address: Address) {
+        self.entries.insert(name.into(), address);
+    }
+
+    /// Returns the address bound to `name`.
+    pub fn get(&self, name: &str) -> Option<Address> {
+        self.entries.get(name).copied()
+    }
+
+    /// Replaces every `@Name` placeholder in `arg` with the address bound to the name.
+    ///
+    /// Placeholders are only recognized at the start of a value, i.e. at the start of the
+    /// argument or after `[`, `(`, `,` or whitespace, so e.g. email addresses are left as is.
+    pub fn resolve(&self, arg: &str) -> Result<String> {
+        let mut out = String::with_capacity(arg.len());
+        let mut rest = arg;
+        let mut at_value_start = true;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '@' && at_value_start {
+                let (name, len) = parse_placeholder(&rest[1..])?;
+                let address = self.get(name).ok_or_else(|| self.unknown_name(name))?;
+                out.push_str(&address.to_checksum(None));
+                rest = &rest[1 + len..];
+                at_value_start = false;
+                continue
+            }
+            out.push(c);
+            at_value_start = matches!(c, '[' | '(' | ',') || c.is_whitespace();
+            rest = &rest[c.len_utf8()..];
+        }
+        Ok(out)
+    }
+
+    /// Resolves the placeholders of all `args`, see [AddressBook::resolve].
+    pub fn resolve_args(&self, args: &[String]) -> Result<Vec<String>> {
+        args.iter().map(|arg| self.resolve(arg)).collect()
+    }
+
+    fn unknown_name(&self, name: &str) -> eyre::Report {
+        let chain = self.chain_id.map(|id| format!(" on chain {id}")).unwrap_or_default();
+        if self.entries.is_empty() {
+            return eyre::eyre!("unknown name `@{name}`{chain}, no labels or deployments found")
+        }
+        let known = self.entries.keys().map(|name| format!("@{name}")).collect::<Vec<_>>();
+        eyre::eyre!("unknown name `@{name}`{chain}, known names: {}", known.join(", "))
+    }
+}
+
+/// Parses the name of a placeholder, after its `@`, returning it with its length in `s`.
+fn parse_placeholder(s: &str) -> Result<(&str, usize)> {
+    if let Some(braced) = s.strip_prefix('{') {
+        let end = braced.find('}').ok_or_else(|| eyre::eyre!("unterminated `@{{` in `@{s}`"))?;
+        return Ok((&braced[..end], end + 2))
+    }
+    let len = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.' | '-')))
+        .unwrap_or(s.len());
+    eyre::ensure!(len > 0, "expected a name after `@`");
+    Ok((&s[..len], len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> AddressBook {
+        let mut book = AddressBook::default();
+        book.insert("Treasury", Address::with_last_byte(1));
+        book.insert("Multisig Owner", Address::with_last_byte(2));
+        book
+    }
+
+    #[test]
+    fn resolves_placeholders() {
+        let book = book();
+        let treasury = Address::with_last_byte(1).to_checksum(None);
+        let owner = Address::with_last_byte(2).to_checksum(None);
+
+        assert_eq!(book.resolve("@Treasury").unwrap(), treasury);
+        assert_eq!(
+            book.resolve("[@Treasury, @{Multisig Owner}]").unwrap(),
+            format!("[{treasury}, {owner}]")
+        );
+        assert_eq!(book.resolve("(@Treasury,1)").unwrap(), format!("({treasury},1)"));
+        assert_eq!(book.resolve("dev@Treasury.xyz").unwrap(), "dev@Treasury.xyz");
+        assert_eq!(book.resolve("100").unwrap(), "100");
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        let book = book();
+        let err = book.resolve("@Vault").unwrap_err().to_string();
+        assert!(err.contains("unknown name `@Vault`"), "{err}");
+        assert!(err.contains("@Treasury"), "{err}");
+        assert!(book.resolve("@{Treasury").is_err());
+        assert!(book.resolve("@").is_err());
+    }
+
+    #[test]
+    fn loads_latest_deployments() {
+        let dir = tempfile::tempdir().unwrap();
+        let write_run = |script: &str, timestamp: u64, address: Address| {
+            let path = dir.path().join(script).join("324");
+            std::fs::create_dir_all(&path).unwrap();
+            let run = serde_json::json!({
+                "timestamp": timestamp,
+                "transactions": [
+                    {
+                        "transactionType": "CREATE",
+                        "contractName": "Treasury",
+                        "contractAddress": address,
+                    },
+                    { "transactionType": "CALL", "contractName": "Other", "contractAddress": null },
+                ],
+            });
+            std::fs::write(path.join("run-latest.json"), run.to_string()).unwrap();
+        };
+        write_run("Old.s.sol", 1, Address::with_last_byte(1));
+        write_run("New.s.sol", 2, Address::with_last_byte(2));
+
+        let config = Config { broadcast: dir.path().to_path_buf(), ..Default::default() };
+        let book = AddressBook::load(&config, Some(324)).unwrap();
+        assert_eq!(book.get("Treasury"), Some(Address::with_last_byte(2)));
+        assert_eq!(book.get("Other"), None);
+
+        let book = AddressBook::load(&config, Some(1)).unwrap();
+        assert_eq!(book.get("Treasury"), None);
+    }
+}
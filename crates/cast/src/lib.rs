@@ -45,6 +45,7 @@ pub use rusoto_core::{
 pub use rusoto_kms::KmsClient;
 pub use tx::TxBuilder;
 
+pub mod address_book;
 pub mod base;
 pub mod errors;
 mod rlp_converter;
@@ -108,6 +108,12 @@ pub enum Group {
     ///
     /// Safety: safe.
     Json,
+    /// Utility cheatcodes that deal with converting values to TOML.
+    ///
+    /// Examples: `writeToml`.
+    ///
+    /// Safety: safe.
+    Toml,
     /// Generic, uncategorized utilities.
     ///
     /// Examples: `toString`, `parse*`, `serialize*`.
@@ -130,6 +136,7 @@ impl Group {
             Self::Environment |
             Self::String |
             Self::Json |
+            Self::Toml |
             Self::Utilities => Some(Safety::Safe),
         }
     }
@@ -145,6 +152,7 @@ impl Group {
             Self::Environment => "environment",
             Self::String => "string",
             Self::Json => "json",
+            Self::Toml => "toml",
             Self::Utilities => "utilities",
         }
     }
@@ -81,8 +81,15 @@ impl Cheatcodes<'static> {
                 Vm::Wallet::STRUCT.clone(),
                 Vm::FfiResult::STRUCT.clone(),
                 Vm::ChainInfo::STRUCT.clone(),
+                Vm::BaseToken::STRUCT.clone(),
+                Vm::Chain::STRUCT.clone(),
                 Vm::AccountAccess::STRUCT.clone(),
                 Vm::StorageAccess::STRUCT.clone(),
+                Vm::L2ToL1Message::STRUCT.clone(),
+                Vm::L2ToL1Log::STRUCT.clone(),
+                Vm::PriorityRequestResult::STRUCT.clone(),
+                Vm::StaticCall::STRUCT.clone(),
+                Vm::StaticCallResult::STRUCT.clone(),
             ]),
             enums: Cow::Owned(vec![
                 Vm::CallerMode::ENUM.clone(),
@@ -96,6 +103,54 @@ impl Cheatcodes<'static> {
     }
 }
 
+impl Cheatcodes<'_> {
+    /// Returns all the items of the cheatcodes interface, one per line and indented by four
+    /// spaces, ready to be placed inside of an `interface` block.
+    pub fn interface_items(&self) -> String {
+        self.to_string().trim().replace('\n', "\n    ")
+    }
+
+    /// Generates a standalone Solidity file declaring all the cheatcodes in `interface {name}`.
+    ///
+    /// Errors are not included to keep the interface compatible with Solidity versions older
+    /// than 0.8.4.
+    pub fn sol_interface(&self, name: &str) -> String {
+        let mut cheats = self.clone();
+        cheats.errors = Default::default();
+        let items = cheats.interface_items();
+        format!(
+            "\
+// Automatically generated from `foundry-cheatcodes` Vm definitions. Do not modify manually.
+
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity >=0.6.2 <0.9.0;
+pragma experimental ABIEncoderV2;
+
+interface {name} {{
+    {items}
+}}
+"
+        )
+    }
+
+    /// Generates a Rust source file binding all the cheatcodes in `interface {name}` with
+    /// `alloy_sol_types::sol!`.
+    pub fn rust_bindings(&self, name: &str) -> String {
+        let items = self.interface_items().replace('\n', "\n    ");
+        format!(
+            "\
+// Automatically generated from `foundry-cheatcodes` Vm definitions. Do not modify manually.
+
+alloy_sol_types::sol! {{
+    interface {name} {{
+        {items}
+    }}
+}}
+"
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,7 +177,7 @@ mod tests {
     fn sol_iface() -> String {
         let mut cheats = Cheatcodes::new();
         cheats.errors = Default::default(); // Skip errors to allow <0.8.4.
-        let cheats = cheats.to_string().trim().replace('\n', "\n    ");
+        let cheats = cheats.interface_items();
         format!(
             "\
 // Automatically generated from `foundry-cheatcodes` Vm definitions. Do not modify manually.
@@ -155,6 +210,22 @@ interface Vm {{
         ensure_file_contents(Path::new(IFACE_PATH), &sol_iface());
     }
 
+    #[test]
+    fn generated_bindings_contain_all_cheatcodes() {
+        let cheats = Cheatcodes::new();
+        let sol = cheats.sol_interface("Vm");
+        let rust = cheats.rust_bindings("Vm");
+        assert!(sol.contains("interface Vm {"));
+        assert!(rust.contains("interface Vm {"));
+        for cheatcode in cheats.cheatcodes.iter() {
+            let decl = cheatcode.func.declaration;
+            assert!(sol.contains(decl), "missing {decl}");
+            assert!(rust.contains(decl), "missing {decl}");
+        }
+        assert!(!sol.contains("error CheatcodeError"));
+        assert!(rust.contains("error CheatcodeError"));
+    }
+
     /// Checks that the `file` has the specified `contents`. If that is not the
     /// case, updates the file and then fails the test.
     fn ensure_file_contents(file: &Path, contents: &str) {
@@ -169,6 +169,38 @@ interface Vm {
         uint256 chainId;
     }
 
+    /// The token a chain pays its fees with. Part of `Chain`.
+    struct BaseToken {
+        /// The name of the token.
+        string name;
+        /// The symbol of the token.
+        string symbol;
+        /// The decimals of the token.
+        uint8 decimals;
+    }
+
+    /// A chain of the built-in chains database. Returned by `getChain`.
+    struct Chain {
+        /// The name of the chain.
+        string name;
+        /// The chain ID.
+        uint256 chainId;
+        /// The alias of the chain.
+        string chainAlias;
+        /// The RPC URL of the chain.
+        string rpcUrl;
+        /// The API URL of the block explorer of the chain, empty if it has none.
+        string explorerApiUrl;
+        /// The token paying the fees of the chain.
+        BaseToken baseToken;
+        /// Whether the chain is a zkSync Era chain.
+        bool isEra;
+        /// Whether the chain is a validium, keeping its data off its settlement layer.
+        bool isValidium;
+        /// The chain ID of the settlement layer of the chain, zero if it's one itself.
+        uint256 settlementChainId;
+    }
+
     /// The result of a `stopAndReturnStateDiff` call.
     struct AccountAccess {
         /// The chain and fork the access occurred.
@@ -223,6 +255,64 @@ interface Vm {
         bool reverted;
     }
 
+    /// A message sent from L2 to L1 through the `L1Messenger` system contract.
+    struct L2ToL1Message {
+        /// The L2 address that sent the message.
+        address sender;
+        /// The message data.
+        bytes data;
+        /// The number of the batch the message was sent in.
+        uint256 batchNumber;
+        /// The index of the log of the message in the L2 logs tree of its batch.
+        uint256 index;
+        /// The index of the transaction sending the message in its batch.
+        uint16 txNumberInBatch;
+        /// The Merkle path of the log of the message, to prove it with `proveL2MessageInclusion`.
+        bytes32[] proof;
+    }
+
+    /// A log sent from L2 to L1 through the `L1Messenger` system contract.
+    struct L2ToL1Log {
+        /// The shard of the log, always 0.
+        uint8 l2ShardId;
+        /// Whether the log was sent by a system contract.
+        bool isService;
+        /// The index of the transaction sending the log in its batch.
+        uint16 txNumberInBatch;
+        /// The L2 address that sent the log, the `L1Messenger` for messages.
+        address sender;
+        /// The key of the log, the sender of the message for messages.
+        bytes32 key;
+        /// The value of the log, the hash of the message for messages.
+        bytes32 value;
+    }
+
+    /// The result of an L1 -> L2 priority request executed on the ZK-VM.
+    struct PriorityRequestResult {
+        /// The canonical hash of the transaction, as returned by `requestL2Transaction`.
+        bytes32 txHash;
+        /// If the transaction succeeded.
+        bool success;
+        /// The data returned by the transaction, or its revert data.
+        bytes returnData;
+    }
+
+    /// A static call made by `multicallStatic`.
+    struct StaticCall {
+        /// The called address.
+        address target;
+        /// The calldata.
+        bytes callData;
+    }
+
+    /// The result of a static call made by `multicallStatic`.
+    struct StaticCallResult {
+        /// Whether the call succeeded.
+        bool success;
+        /// The return data of the call, or its revert data if it reverted.
+        bytes returnData;
+    }
+
     // ======== EVM ========
 
     /// Gets the address for a given private key.
@@ -233,6 +323,12 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function dumpState(string calldata pathToStateJson) external;
 
+    /// Dump the storage and the deployed EraVM bytecodes of the accounts, including the ones
+    /// committed in `setUp`, to an era_test_node state file, which can be loaded by a local node
+    /// with `--load-state`.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function dumpEraState(string calldata pathToStateJson) external;
+
     /// Gets the nonce of an account.
     #[cheatcode(group = Evm, safety = Safe)]
     function getNonce(address account) external view returns (uint64 nonce);
@@ -245,6 +341,11 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function loadAllocs(string calldata pathToAllocsJson) external;
 
+    /// Load an era_test_node state file, as written with `--dump-state`, into the in-memory revm
+    /// state. This sets the raw storage of the accounts and the code of the deployed contracts.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function loadEraState(string calldata pathToStateJson) external;
+
     /// Signs `digest` with `privateKey` using the secp256k1 curve.
     #[cheatcode(group = Evm, safety = Safe)]
     function sign(uint256 privateKey, bytes32 digest) external pure returns (uint8 v, bytes32 r, bytes32 s);
@@ -375,6 +476,12 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function store(address target, bytes32 slot, bytes32 value) external;
 
+    /// Copies the storage of `from` to `to`, replacing the storage of `to`. Contract storage is
+    /// the same in the EVM and in EraVM, so this works with both. On a fork, it fails for the
+    /// contracts of the fork, whose full storage can't be read over RPC.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function copyStorage(address from, address to) external;
+
     /// Marks the slots of an account and the account address as cold.
     #[cheatcode(group = Evm, safety = Unsafe, status = Experimental)]
     function cool(address target) external;
@@ -526,6 +633,12 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function transact(uint256 forkId, bytes32 txHash) external;
 
+    /// Performs static calls against the current fork at its fork block in a single RPC round trip, with Multicall3
+    /// on EVM chains or a batched JSON-RPC request on zkSync chains and chains without Multicall3. Local changes
+    /// to the fork state are not seen by the calls, and calls that revert don't revert the cheatcode.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function multicallStatic(StaticCall[] calldata calls) external returns (StaticCallResult[] memory results);
+
     /// Performs an Ethereum JSON-RPC request to the current fork URL.
     #[cheatcode(group = Evm, safety = Safe)]
     function rpc(string calldata method, string calldata params) external returns (bytes memory data);
@@ -599,10 +712,111 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Safe)]
     function zkRegisterContract(string calldata name, bytes32 evmBytecodeHash, bytes calldata evmDeployedBytecode, bytes calldata evmBytecode, bytes32 zkBytecodeHash, bytes calldata zkDeployedBytecode) external pure;
 
+    /// Calls `target` on the ZK-VM with the system call flag set, like system contracts call each other,
+    /// and returns its output. Reverts with the revert data of the call if it reverts.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkSystemCall(address target, bytes calldata data) external returns (bytes memory returnData);
+
+    /// Enables/Disables the system call flag of the calls to the ZK-VM, for contracts compiled with
+    /// `is_system = true` that require system call semantics.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkSystemMode(bool enable) external;
+
+    /// Sets the paymaster of the subsequent calls and creates on the ZK-VM, which validates them
+    /// with `paymasterInput`, e.g. an encoded `IPaymasterFlow.general` call, and pays their fees.
+    /// `address(0)` stops using a paymaster.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkUsePaymaster(address paymaster, bytes calldata paymasterInput) external;
+
+    /// Registers the contract at `account` as an account, so the calls and creates on the ZK-VM it
+    /// makes, e.g. while pranked, are validated and executed by its `IAccount` implementation
+    /// instead of the default account's.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkRegisterAccount(address account) external;
+
+    /// Registers the contract at `account` as an account, whose `IAccount` implementation
+    /// validates the calls and creates on the ZK-VM it makes with `signature` as the signature of
+    /// their transactions.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkRegisterAccount(address account, bytes calldata signature) external;
+
+    /// Marks the EraVM bytecode hash `bytecodeHash` as known in the `KnownCodesStorage` system
+    /// contract, like a bytecode already published on the chain, so it's not published again as a
+    /// factory dependency.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkMarkBytecodeKnown(bytes32 bytecodeHash) external;
+
+    /// Marks the EraVM bytecode hash `bytecodeHash` as unknown in the `KnownCodesStorage` system
+    /// contract, so it's published again when it's used as a factory dependency.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkMarkBytecodeUnknown(bytes32 bytecodeHash) external;
+
+    /// Gets whether the EraVM bytecode hash `bytecodeHash` is known in the `KnownCodesStorage`
+    /// system contract.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkIsBytecodeKnown(bytes32 bytecodeHash) external view returns (bool known);
+
+    /// Sets the L2 block the subsequent calls and creates on the ZK-VM run in, as kept by the
+    /// `SystemContext` system contract, independently from the `block.number` and `block.timestamp`
+    /// set by `roll` and `warp`. Every call or create runs in a new batch with a single L2 block, so
+    /// the next one runs in block `number` at `timestamp`, and the following ones in the blocks after
+    /// it. The batch timestamp is lowered to `timestamp` if it's later.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkSetL2Block(uint256 number, uint256 timestamp) external;
+
+    /// Sets the hash the `SystemContext` system contract keeps for the L2 block `number`, one of
+    /// the 257 latest blocks. The hash of the block before the current one is computed when the
+    /// current one starts, replacing the one set.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkSetL2BlockHash(uint256 number, bytes32 hash) external;
+
+    /// Sets the batch the subsequent calls and creates on the ZK-VM run in, as kept by the
+    /// `SystemContext` system contract. The next one runs in batch `number` at `timestamp`, and the
+    /// following ones in the batches after it. Its L2 block starts at `timestamp` too.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkSetBatchInfo(uint256 number, uint256 timestamp) external;
+
+    /// Starts relaying messages between L1 (EVM) and L2 (ZK-VM).
+    ///
+    /// EVM calls to `mailbox` are handled like the zkSync Era `Mailbox`: `requestL2Transaction` queues
+    /// a priority request that is executed on the ZK-VM before its next call or create, and
+    /// `proveL2MessageInclusion` verifies the Merkle proof of a message against the root of the L2
+    /// logs tree of its batch. Every ZK-VM execution sending logs to L1 is a batch, numbered from 1.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkStartMessaging(address mailbox) external;
+
+    /// Stops relaying messages between L1 and L2, dropping all pending priority requests.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkStopMessaging() external;
+
+    /// Gets all messages sent from L2 to L1 since `zkStartMessaging` was called.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkGetL2ToL1Messages() external view returns (L2ToL1Message[] memory messages);
+
+    /// Gets all logs sent from L2 to L1 since `zkStartMessaging` was called, including the ones
+    /// of the messages.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkGetL2ToL1Logs() external view returns (L2ToL1Log[] memory logs);
+
+    /// Sends a message from `sender` on L2 to L1, as if `sender` called `sendToL1` on the
+    /// `L1Messenger` system contract.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkSendL1Message(address sender, bytes calldata data) external;
+
+    /// Executes all pending L1 -> L2 priority requests on the ZK-VM, in the order they were made,
+    /// instead of before the next ZK-VM call or create.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkRelayPriorityRequests() external returns (PriorityRequestResult[] memory results);
+
     /// If the condition is false, discard this run's fuzz inputs and generate new ones.
     #[cheatcode(group = Testing, safety = Safe)]
     function assume(bool condition) external pure;
 
+    /// Sets the verbosity the traces of the subsequent calls of the test are displayed with, like
+    /// `-vvv` for `3`, e.g. to only display the full traces of a failing operation.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function setTraceVerbosity(uint8 verbosity) external;
+
     /// Writes a breakpoint to jump to in the debugger.
     #[cheatcode(group = Testing, safety = Safe)]
     function breakpoint(string calldata char) external;
@@ -623,6 +837,16 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Safe)]
     function rpcUrlStructs() external view returns (Rpc[] memory urls);
 
+    /// Returns the chain with the given alias of the built-in chains database, e.g. `zksync`.
+    /// Its RPC URL is the one of the alias in the `rpc_endpoints` of the config, or of the
+    /// `<ALIAS>_RPC_URL` environment variable, if set.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function getChain(string calldata chainAlias) external view returns (Chain memory chain);
+
+    /// Returns the chain with the given chain ID of the built-in chains database.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function getChain(uint256 chainId) external view returns (Chain memory chain);
+
     /// Suspends execution of the main thread for `duration` milliseconds.
     #[cheatcode(group = Testing, safety = Safe)]
     function sleep(uint256 duration) external;
@@ -682,6 +906,12 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectEmit(address emitter) external;
 
+    /// Expects the next call to write `value` to the storage slot `slot` of `target`, in the EVM or in
+    /// EraVM. Call this function, then call a function: internally after the call, we check if the slot
+    /// was written with the value, and otherwise fail with the writes to `target` observed.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectStorageWrite(address target, bytes32 slot, bytes32 value) external;
+
     /// Expects an error on next call with any revert data.
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert() external;
@@ -1407,6 +1637,28 @@ interface Vm {
     #[cheatcode(group = Filesystem)]
     function tryFfi(string[] calldata commandInput) external returns (FfiResult memory result);
 
+    // -------- User Interaction --------
+
+    /// Prompts the user for a string value in the terminal.
+    /// In non-interactive sessions the answer is read from the prompt answers file or `FOUNDRY_PROMPT_*` env var.
+    #[cheatcode(group = Filesystem)]
+    function prompt(string calldata promptText) external returns (string memory input);
+
+    /// Prompts the user for a hidden string value in the terminal.
+    /// In non-interactive sessions the answer is read from the prompt answers file or `FOUNDRY_PROMPT_*` env var.
+    #[cheatcode(group = Filesystem)]
+    function promptSecret(string calldata promptText) external returns (string memory input);
+
+    /// Prompts the user for an address in the terminal.
+    /// In non-interactive sessions the answer is read from the prompt answers file or `FOUNDRY_PROMPT_*` env var.
+    #[cheatcode(group = Filesystem)]
+    function promptAddress(string calldata promptText) external returns (address);
+
+    /// Prompts the user for uint256 in the terminal.
+    /// In non-interactive sessions the answer is read from the prompt answers file or `FOUNDRY_PROMPT_*` env var.
+    #[cheatcode(group = Filesystem)]
+    function promptUint(string calldata promptText) external returns (uint256);
+
     // ======== Environment Variables ========
 
     /// Sets environment variables.
@@ -1595,6 +1847,43 @@ interface Vm {
     #[cheatcode(group = Scripting)]
     function stopBroadcast() external;
 
+    // -------- Broadcast Expectations --------
+
+    /// Expects the next broadcasted transaction to emit an event from `emitter` whose first topics
+    /// are `topics`.
+    ///
+    /// The expectation is checked against the simulated execution of the transaction, and against
+    /// its receipt once it's mined.
+    #[cheatcode(group = Scripting)]
+    function expectBroadcastEvent(address emitter, bytes32[] calldata topics) external;
+
+    /// Expects the next broadcasted transaction to emit an event from `emitter` whose first topics
+    /// are `topics` and whose data is `data`.
+    ///
+    /// The expectation is checked against the simulated execution of the transaction, and against
+    /// its receipt once it's mined.
+    #[cheatcode(group = Scripting)]
+    function expectBroadcastEvent(address emitter, bytes32[] calldata topics, bytes calldata data)
+        external;
+
+    // -------- User Operations --------
+
+    /// Records an ERC-4337 user operation from the smart `account`, calling it with `callData`, to
+    /// be submitted to `entryPoint` through the configured bundler.
+    ///
+    /// The operation is signed by the current broadcaster, or the script's sender, and is only
+    /// submitted when the script is broadcast. It isn't executed during simulation.
+    #[cheatcode(group = Scripting)]
+    function broadcastUserOp(address entryPoint, address account, bytes calldata callData) external;
+
+    /// Records an ERC-4337 user operation from the smart `account`, calling it with `callData`, to
+    /// be submitted to `entryPoint` through the configured bundler and signed by `signer`.
+    ///
+    /// The operation is only submitted when the script is broadcast. It isn't executed during
+    /// simulation.
+    #[cheatcode(group = Scripting)]
+    function broadcastUserOp(address entryPoint, address account, bytes calldata callData, address signer) external;
+
     // ======== Utilities ========
 
     // -------- Strings --------
@@ -1730,6 +2019,14 @@ interface Vm {
     #[cheatcode(group = Json)]
     function parseJsonKeys(string calldata json, string calldata key) external pure returns (string[] memory keys);
 
+    /// ABI-encodes a JSON object after validating it against `schemaId`, a struct-like type description,
+    /// e.g. `(address owner,uint256 amount,(string name,bool enabled)[] modules)`.
+    /// Every component must be named after the corresponding JSON key. Missing, unknown or mistyped keys revert
+    /// with the path of the offending value. Unlike `parseJson`, values are encoded in schema order,
+    /// not alphabetical key order, so the result can be decoded directly into the described struct.
+    #[cheatcode(group = Json)]
+    function parseJsonAs(bytes calldata schemaId, string calldata json) external pure returns (bytes memory abiEncodedData);
+
     // -------- Writing --------
 
     // NOTE: Please read https://book.getfoundry.sh/cheatcodes/serialize-json to understand how
@@ -1824,6 +2121,18 @@ interface Vm {
     #[cheatcode(group = Json)]
     function writeJson(string calldata json, string calldata path, string calldata valueKey) external;
 
+    // ======== TOML Manipulation ========
+
+    /// Converts a serialized JSON object to TOML and writes it to a file. If the file exists, it will be overwritten.
+    #[cheatcode(group = Toml)]
+    function writeToml(string calldata json, string calldata path) external;
+
+    /// Converts a serialized JSON value to TOML and writes it to an **existing** TOML file, replacing a value with
+    /// key = <value_key.>
+    /// This is useful to replace a specific value of a TOML file, without having to parse the entire thing.
+    #[cheatcode(group = Toml)]
+    function writeToml(string calldata json, string calldata path, string calldata valueKey) external;
+
     // -------- Key Management --------
 
     /// Derives a private key from the name, labels the account with that name, and returns the wallet.
@@ -1886,15 +2195,29 @@ interface Vm {
     #[cheatcode(group = Utilities)]
     function getLabel(address account) external view returns (string memory currentLabel);
 
+    /// Gets the address of a fixture contract the test suite depends on with `@custom:depends-on`,
+    /// by contract name or identifier.
+    #[cheatcode(group = Utilities)]
+    function getFixture(string calldata fixture) external view returns (address fixtureAddress);
+
     /// Compute the address a contract will be deployed at for a given deployer address and nonce.
+    ///
+    /// In zk mode, the address is derived as in EraVM, from the deployment nonce of the deployer:
+    /// the number of contracts it deployed, not the number of transactions it sent.
     #[cheatcode(group = Utilities)]
     function computeCreateAddress(address deployer, uint256 nonce) external pure returns (address);
 
     /// Compute the address of a contract created with CREATE2 using the given CREATE2 deployer.
+    ///
+    /// In zk mode, the address is derived as in EraVM, and `initCodeHash` is the hash of the
+    /// creation code of a contract without constructor arguments, or its zksolc bytecode hash.
     #[cheatcode(group = Utilities)]
     function computeCreate2Address(bytes32 salt, bytes32 initCodeHash, address deployer) external pure returns (address);
 
     /// Compute the address of a contract created with CREATE2 using the default CREATE2 deployer.
+    ///
+    /// In zk mode, the address is derived as in EraVM, and `initCodeHash` is the hash of the
+    /// creation code of a contract without constructor arguments, or its zksolc bytecode hash.
     #[cheatcode(group = Utilities)]
     function computeCreate2Address(bytes32 salt, bytes32 initCodeHash) external pure returns (address);
 
@@ -1,17 +1,26 @@
 use super::Result;
-use crate::{script::ScriptWallets, Vm::Rpc};
-use alloy_primitives::Address;
+use crate::{
+    script::ScriptWallets,
+    Vm::{BaseToken, Chain, Rpc},
+};
+use alloy_primitives::{Address, U256};
 use foundry_common::fs::normalize_path;
 use foundry_compilers::{utils::canonicalize, ProjectPathsConfig};
 use foundry_config::{
-    cache::StorageCachingConfig, fs_permissions::FsAccessKind, Config, FsPermissions,
-    ResolvedRpcEndpoints,
+    cache::StorageCachingConfig, fs_permissions::FsAccessKind, ChainInfo, Config,
+    FfiAllowedCommand, FsPermissions, ResolvedRpcEndpoints,
 };
 use foundry_evm_core::opts::EvmOpts;
-use foundry_zksync_compiler::DualCompiledContract;
+use foundry_zksync_compiler::DualCompiledContracts;
+use foundry_zksync_core::vm::{SizeLimitWarning, ZkResourceLimits};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 
 /// Additional, configurable context the `Cheatcodes` inspector has access to
@@ -21,12 +30,24 @@ use std::{
 pub struct CheatsConfig {
     /// Whether the FFI cheatcode is enabled.
     pub ffi: bool,
+    /// The commands the FFI cheatcodes can execute when `ffi` is enabled, only these if not empty
+    pub ffi_allowlist: Vec<FfiAllowedCommand>,
+    /// The audit log of the invocations of the FFI cheatcodes
+    ///
+    /// Shared between all `Cheatcodes` inspectors created from this config.
+    pub ffi_log: Arc<Mutex<Vec<FfiInvocation>>>,
+    /// How long the `prompt*` cheatcodes wait for terminal input.
+    pub prompt_timeout: Duration,
+    /// Answers file used by the `prompt*` cheatcodes in non-interactive sessions.
+    pub prompt_answers: Option<PathBuf>,
     /// Use the create 2 factory in all cases including tests and non-broadcasting scripts.
     pub always_use_create_2_factory: bool,
     /// RPC storage caching settings determines what chains and endpoints to cache
     pub rpc_storage_caching: StorageCachingConfig,
     /// All known endpoints and their aliases
     pub rpc_endpoints: ResolvedRpcEndpoints,
+    /// URLs that can be accessed in addition to `rpc_endpoints`, any URL if `None`
+    pub allowed_urls: Option<Vec<String>>,
     /// Project's paths as configured
     pub paths: ProjectPathsConfig,
     /// Filesystem permissions for cheatcodes like `writeFile`, `readFile`
@@ -42,9 +63,28 @@ pub struct CheatsConfig {
     /// Script wallets
     pub script_wallets: Option<ScriptWallets>,
     /// ZKSolc -> Solc Contract codes
-    pub dual_compiled_contracts: Vec<DualCompiledContract>,
+    ///
+    /// Shared between all `Cheatcodes` inspectors created from this config.
+    pub dual_compiled_contracts: Arc<DualCompiledContracts>,
     /// Use ZK-VM on startup
     pub use_zk: bool,
+    /// Resource limits of every ZK-VM execution
+    pub zk_resource_limits: ZkResourceLimits,
+    /// The bytes of return data the traces record of a successful call, all of it if `None`
+    pub trace_return_data_limit: Option<usize>,
+    /// Records the paths read by cheatcodes, if set
+    ///
+    /// Shared between all `Cheatcodes` inspectors created from this config.
+    pub read_paths: Option<Arc<Mutex<BTreeSet<PathBuf>>>>,
+    /// Records the calldata sizes of ZK-VM calls approaching the EraVM limit, if set
+    ///
+    /// Shared between all `Cheatcodes` inspectors created from this config.
+    pub zk_size_warnings: Option<Arc<Mutex<Vec<SizeLimitWarning>>>>,
+    /// The L1 address handled as the zkSync Era `Mailbox` from the start of every test, relaying
+    /// the messages between L1 and L2 like `zkStartMessaging`
+    pub zk_messaging: Option<Address>,
+    /// The addresses of the fixture contracts the test suite runs on, by name and identifier
+    pub fixtures: BTreeMap<String, Address>,
 }
 
 impl CheatsConfig {
@@ -53,7 +93,6 @@ impl CheatsConfig {
         config: &Config,
         evm_opts: EvmOpts,
         script_wallets: Option<ScriptWallets>,
-        dual_compiled_contracts: Vec<DualCompiledContract>,
         use_zk: bool,
     ) -> Self {
         let mut allowed_paths = vec![config.__root.0.clone()];
@@ -63,11 +102,20 @@ impl CheatsConfig {
         let rpc_endpoints = config.rpc_endpoints.clone().resolved();
         trace!(?rpc_endpoints, "using resolved rpc endpoints");
 
+        let allowed_urls = config.offline_tests.then(|| {
+            evm_opts.fork_url.iter().chain(&config.offline_tests_allow_urls).cloned().collect()
+        });
+
         Self {
             ffi: evm_opts.ffi,
+            ffi_allowlist: config.ffi_allowlist.clone(),
+            ffi_log: Default::default(),
+            prompt_timeout: Duration::from_secs(config.prompt_timeout),
+            prompt_answers: config.prompt_answers.as_ref().map(|path| config.__root.0.join(path)),
             always_use_create_2_factory: evm_opts.always_use_create_2_factory,
             rpc_storage_caching: config.rpc_storage_caching.clone(),
             rpc_endpoints,
+            allowed_urls,
             paths: config.project_paths(),
             fs_permissions: config.fs_permissions.clone().joined(&config.__root),
             root: config.__root.0.clone(),
@@ -75,11 +123,29 @@ impl CheatsConfig {
             evm_opts,
             labels: config.labels.clone(),
             script_wallets,
-            dual_compiled_contracts,
+            dual_compiled_contracts: Default::default(),
             use_zk,
+            zk_resource_limits: ZkResourceLimits {
+                max_cycles: config.zk_max_cycles,
+                max_memory: config.zk_max_memory,
+            },
+            trace_return_data_limit: config.trace_return_data_limit,
+            read_paths: None,
+            zk_size_warnings: None,
+            zk_messaging: config.zk_messaging,
+            fixtures: Default::default(),
         }
     }
 
+    /// Sets the contracts compiled with both solc and zksolc, which are migrated to the zkEVM.
+    pub fn with_dual_compiled_contracts(
+        mut self,
+        dual_compiled_contracts: DualCompiledContracts,
+    ) -> Self {
+        self.dual_compiled_contracts = Arc::new(dual_compiled_contracts);
+        self
+    }
+
     /// Attempts to canonicalize (see [std::fs::canonicalize]) the path.
     ///
     /// Canonicalization fails for non-existing paths, in which case we just normalize the path.
@@ -117,6 +183,11 @@ impl CheatsConfig {
             "the path {} is not allowed to be accessed for {kind} operations",
             normalized.strip_prefix(&self.root).unwrap_or(path).display()
         );
+        if kind == FsAccessKind::Read {
+            if let Some(read_paths) = &self.read_paths {
+                read_paths.lock().insert(normalized.clone());
+            }
+        }
         Ok(normalized)
     }
 
@@ -168,6 +239,33 @@ impl CheatsConfig {
         }
     }
 
+    /// Returns an error if tests are not allowed to access `url`, with `offline_tests` only the
+    /// fork URL, the `rpc_endpoints` and the `offline_tests_allow_urls` can be accessed.
+    pub fn ensure_url_allowed(&self, url: &str) -> Result<()> {
+        let Some(allowed_urls) = &self.allowed_urls else { return Ok(()) };
+        let same_url = |allowed: &str| allowed.trim_end_matches('/') == url.trim_end_matches('/');
+        let allowed = allowed_urls.iter().any(|allowed| same_url(allowed)) ||
+            self.rpc_endpoints
+                .keys()
+                .any(|alias| self.rpc_url(alias).is_ok_and(|u| same_url(&u)));
+        ensure!(
+            allowed,
+            "network access to `{url}` is forbidden with `offline_tests`, \
+             add it to `rpc_endpoints` or `offline_tests_allow_urls` to allow it"
+        );
+        Ok(())
+    }
+
+    /// Returns an error with `offline_tests` for the cheatcodes that may access the network
+    /// without going through an RPC URL, like `vm.ffi`.
+    pub fn ensure_offline_allowed(&self, cheatcode: &str) -> Result<()> {
+        ensure!(
+            self.allowed_urls.is_none(),
+            "`{cheatcode}` is forbidden with `offline_tests`, it may access the network"
+        );
+        Ok(())
+    }
+
     /// Returns all the RPC urls and their alias.
     pub fn rpc_urls(&self) -> Result<Vec<Rpc>> {
         let mut urls = Vec::with_capacity(self.rpc_endpoints.len());
@@ -177,15 +275,89 @@ impl CheatsConfig {
         }
         Ok(urls)
     }
+
+    /// Returns the chain `chain` of the built-in chains database, with the RPC URL of its alias in
+    /// the `rpc_endpoints` or of its environment variable, if set.
+    pub fn chain(&self, chain: &ChainInfo) -> Result<Chain> {
+        let rpc_url = self.chain_rpc_url(chain, |var| std::env::var(var).ok())?;
+        Ok(Chain {
+            name: chain.name.to_string(),
+            chainId: U256::from(chain.id),
+            chainAlias: chain.alias.to_string(),
+            rpcUrl: rpc_url,
+            explorerApiUrl: chain.explorer_api_url.unwrap_or_default().to_string(),
+            baseToken: BaseToken {
+                name: chain.base_token.name.to_string(),
+                symbol: chain.base_token.symbol.to_string(),
+                decimals: chain.base_token.decimals,
+            },
+            isEra: chain.is_era,
+            isValidium: chain.is_validium,
+            settlementChainId: U256::from(chain.settlement_chain_id.unwrap_or_default()),
+        })
+    }
+
+    /// Returns the RPC URL of `chain`: the one of its alias in the `rpc_endpoints`, else the one
+    /// of its environment variable, read with `env`, else its public one.
+    fn chain_rpc_url(
+        &self,
+        chain: &ChainInfo,
+        env: impl FnOnce(&str) -> Option<String>,
+    ) -> Result<String> {
+        if self.rpc_endpoints.contains_key(chain.alias) {
+            return self.rpc_url(chain.alias)
+        }
+        Ok(env(&chain.rpc_url_var())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| chain.rpc_url.to_string()))
+    }
+}
+
+/// An invocation of the FFI cheatcodes, recorded in the audit log of the run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FfiInvocation {
+    /// The command, the executable followed by its arguments.
+    pub command: Vec<String>,
+    /// Whether the command was allowed to execute.
+    pub allowed: bool,
+    /// The exit code of the command, if it was executed and exited.
+    pub exit_code: Option<i32>,
+}
+
+impl fmt::Display for FfiInvocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = self
+            .command
+            .iter()
+            .map(|arg| {
+                if arg.is_empty() || arg.contains(char::is_whitespace) {
+                    format!("{arg:?}")
+                } else {
+                    arg.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        match (self.allowed, self.exit_code) {
+            (false, _) => write!(f, "denied `{command}`"),
+            (true, Some(code)) => write!(f, "executed `{command}` (exit code {code})"),
+            (true, None) => write!(f, "executed `{command}` (failed to run)"),
+        }
+    }
 }
 
 impl Default for CheatsConfig {
     fn default() -> Self {
         Self {
             ffi: false,
+            ffi_allowlist: vec![],
+            ffi_log: Default::default(),
+            prompt_timeout: Duration::from_secs(120),
+            prompt_answers: None,
             always_use_create_2_factory: false,
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
+            allowed_urls: None,
             paths: ProjectPathsConfig::builder().build_with_root("./"),
             fs_permissions: Default::default(),
             root: Default::default(),
@@ -195,6 +367,12 @@ impl Default for CheatsConfig {
             script_wallets: None,
             dual_compiled_contracts: Default::default(),
             use_zk: false,
+            zk_resource_limits: Default::default(),
+            trace_return_data_limit: None,
+            read_paths: None,
+            zk_size_warnings: None,
+            zk_messaging: None,
+            fixtures: Default::default(),
         }
     }
 }
@@ -202,14 +380,13 @@ impl Default for CheatsConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use foundry_config::fs_permissions::PathPermission;
+    use foundry_config::{fs_permissions::PathPermission, RpcEndpoint, RpcEndpoints};
 
     fn config(root: &str, fs_permissions: FsPermissions) -> CheatsConfig {
         CheatsConfig::new(
             &Config { __root: PathBuf::from(root).into(), fs_permissions, ..Default::default() },
             Default::default(),
             None,
-            Default::default(),
             false,
         )
     }
@@ -227,6 +404,36 @@ mod tests {
         assert!(config.ensure_path_allowed("../../root/t.txt", FsAccessKind::Write).is_err());
     }
 
+    #[test]
+    fn test_chain_rpc_url() {
+        let chain = ChainInfo::by_alias("zksync_sepolia").unwrap();
+        let env = |url: &'static str| {
+            move |var: &str| {
+                assert_eq!(var, "ZKSYNC_SEPOLIA_RPC_URL");
+                Some(url.to_string())
+            }
+        };
+
+        let mut config = config("/my/project/root/", FsPermissions::default());
+        assert_eq!(config.chain_rpc_url(chain, |_| None).unwrap(), chain.rpc_url);
+        assert_eq!(config.chain_rpc_url(chain, env("")).unwrap(), chain.rpc_url);
+        assert_eq!(
+            config.chain_rpc_url(chain, env("http://127.0.0.1:8011")).unwrap(),
+            "http://127.0.0.1:8011"
+        );
+
+        // the `rpc_endpoints` take precedence over the environment variable
+        config.rpc_endpoints = RpcEndpoints::new([(
+            "zksync_sepolia",
+            RpcEndpoint::Url("http://127.0.0.1:3050".to_string()),
+        )])
+        .resolved();
+        assert_eq!(
+            config.chain_rpc_url(chain, env("http://127.0.0.1:8011")).unwrap(),
+            "http://127.0.0.1:3050"
+        );
+    }
+
     #[test]
     fn test_is_foundry_toml() {
         let root = "/my/project/root/";
@@ -241,4 +448,26 @@ mod tests {
         let f = format!("{root}lib/other/foundry.toml");
         assert!(!config.is_foundry_toml(f));
     }
+
+    #[test]
+    fn test_allowed_urls() {
+        let config = CheatsConfig::new(
+            &Config {
+                offline_tests: true,
+                offline_tests_allow_urls: vec!["https://mainnet.era.zksync.io/".to_string()],
+                ..Default::default()
+            },
+            EvmOpts { fork_url: Some("http://localhost:8545".to_string()), ..Default::default() },
+            None,
+            false,
+        );
+        assert!(config.ensure_url_allowed("http://localhost:8545").is_ok());
+        assert!(config.ensure_url_allowed("https://mainnet.era.zksync.io").is_ok());
+        assert!(config.ensure_url_allowed("https://eth.llamarpc.com").is_err());
+
+        assert!(CheatsConfig::default().ensure_url_allowed("https://eth.llamarpc.com").is_ok());
+
+        assert!(config.ensure_offline_allowed("vm.ffi").is_err());
+        assert!(CheatsConfig::default().ensure_offline_allowed("vm.ffi").is_ok());
+    }
 }
@@ -296,6 +296,7 @@ impl_from!(
     std::num::TryFromIntError,
     std::str::Utf8Error,
     std::string::FromUtf8Error,
+    toml::ser::Error,
     UnresolvedEnvVarError,
     WalletError,
     SignerError,
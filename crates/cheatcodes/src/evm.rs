@@ -10,6 +10,7 @@ use foundry_evm_core::{
     backend::{DatabaseExt, RevertSnapshotAction},
     constants::{CALLER, CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS, TEST_CONTRACT_ADDRESS},
 };
+use foundry_zksync_core::era_state::EraState;
 use revm::{
     primitives::{Account, Bytecode, SpecId, KECCAK_EMPTY},
     EVMData,
@@ -148,6 +149,45 @@ impl Cheatcode for dumpStateCall {
     }
 }
 
+impl Cheatcode for loadEraStateCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { pathToStateJson } = self;
+
+        let path = Path::new(pathToStateJson);
+        ensure!(path.exists(), "state file does not exist: {pathToStateJson}");
+        let state = EraState::read(path).map_err(|e| fmt_err!("failed to load state: {e}"))?;
+
+        foundry_zksync_core::cheatcodes::load_era_state(
+            &state,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for dumpEraStateCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { pathToStateJson } = self;
+
+        // load the accounts committed to the database, like the ones deployed in `setUp`, so the
+        // dump isn't limited to the accounts touched by the current call
+        for address in ccx.data.db.committed_accounts() {
+            let storage = ccx.data.db.account_storage(address);
+            ccx.data.journaled_state.load_account(address, ccx.data.db)?;
+            for slot in storage.into_keys() {
+                ccx.data.journaled_state.sload(address, slot, ccx.data.db)?;
+            }
+        }
+
+        let state = foundry_zksync_core::cheatcodes::dump_era_state(&ccx.data.journaled_state);
+        state
+            .write(Path::new(pathToStateJson))
+            .map_err(|e| fmt_err!("failed to dump state: {e}"))?;
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for sign_0Call {
     fn apply_full<DB: DatabaseExt>(&self, _: &mut CheatsCtxt<DB>) -> Result {
         let Self { privateKey, digest } = self;
@@ -455,6 +495,45 @@ impl Cheatcode for storeCall {
     }
 }
 
+impl Cheatcode for copyStorageCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { from, to } = *self;
+        ensure_not_precompile!(&to, ccx);
+        // only the slots loaded so far of the contracts of a fork are known, copying them would
+        // silently leave the others behind
+        if ccx.data.db.is_forked_mode() {
+            if fork::is_fork_contract(ccx, from)? {
+                bail!(
+                    "cannot copy the storage of {from}, a contract of the fork: \
+                     its full storage can't be read over RPC"
+                );
+            }
+            if fork::is_fork_contract(ccx, to)? {
+                bail!(
+                    "cannot copy the storage to {to}, a contract of the fork: \
+                     its slots can't be cleared without reading its full storage over RPC"
+                );
+            }
+        }
+        // the slots committed to the database, overridden by the ones changed in the journal
+        let mut storage = ccx.data.db.account_storage(from);
+        let account = journaled_account(ccx.data, from)?;
+        storage.extend(account.storage.iter().map(|(slot, value)| (*slot, value.present_value())));
+
+        // clear the slots of `to` that `from` doesn't have
+        let mut cleared = ccx.data.db.account_storage(to).into_keys().collect::<Vec<_>>();
+        let account = journaled_account(ccx.data, to)?;
+        cleared.extend(account.storage.keys().copied());
+        cleared.retain(|slot| !storage.contains_key(slot));
+
+        let cleared = cleared.into_iter().map(|slot| (slot, U256::ZERO));
+        for (slot, value) in storage.into_iter().chain(cleared) {
+            ccx.data.journaled_state.sstore(to, slot, value, ccx.data.db)?;
+        }
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for coolCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { target } = self;
@@ -1,12 +1,47 @@
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{address, Address, Bytes, B256, U256};
 use alloy_providers::provider::TempProvider;
-use alloy_rpc_types::Filter;
-use alloy_sol_types::SolValue;
+use alloy_rpc_types::{
+    request::{TransactionInput, TransactionRequest},
+    BlockId, Filter,
+};
+use alloy_sol_types::{sol, SolCall, SolValue};
 use eyre::WrapErr;
-use foundry_common::{provider::alloy::ProviderBuilder, types::ToEthers};
+use foundry_common::{
+    provider::{
+        alloy::ProviderBuilder,
+        cassette::{self, RecordedResponse},
+    },
+    types::ToEthers,
+};
 use foundry_compilers::utils::RuntimeOrHandle;
 use foundry_evm_core::fork::CreateFork;
+use revm::{primitives::KECCAK_EMPTY, Database};
+use serde::Deserialize;
+
+sol! {
+    /// The subset of Multicall3 used by `multicallStatic`.
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Call3Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls)
+            external
+            payable
+            returns (Call3Result[] memory returnData);
+    }
+}
+
+/// The address of Multicall3, which is the same on most EVM chains.
+const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
 
 impl Cheatcode for activeForkCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
@@ -221,8 +256,7 @@ impl Cheatcode for isPersistentCall {
 impl Cheatcode for rpcCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { method, params } = self;
-        let url =
-            ccx.data.db.active_fork_url().ok_or_else(|| fmt_err!("no active fork URL found"))?;
+        let url = active_fork_url(ccx)?;
         let provider = ProviderBuilder::new(&url).build()?;
         let method: &'static str = Box::new(method.clone()).leak();
         let params_json: serde_json::Value = serde_json::from_str(params)?;
@@ -237,6 +271,30 @@ impl Cheatcode for rpcCall {
     }
 }
 
+impl Cheatcode for multicallStaticCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { calls } = self;
+        let url = active_fork_url(ccx)?;
+        // the calls are executed on the state of the fork, whichever block the test rolled to
+        let id = ccx.data.db.active_fork_id().ok_or_else(|| fmt_err!("no active fork"))?;
+        let block = ccx.data.db.get_fork_info(id)?.fork_env.block.number.saturating_to::<u64>();
+        let block = BlockId::Number(block.into());
+
+        // zkSync chains don't have Multicall3 at the canonical address
+        let has_multicall3 = !ccx.state.use_zk_vm &&
+            ccx.data
+                .db
+                .basic(MULTICALL3_ADDRESS)?
+                .is_some_and(|info| info.code_hash != KECCAK_EMPTY);
+        let results = if has_multicall3 {
+            multicall3(&url, block, calls)?
+        } else {
+            batch_eth_call(&url, block, calls)?
+        };
+        Ok(results.abi_encode())
+    }
+}
+
 impl Cheatcode for eth_getLogsCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { fromBlock, toBlock, target, topics } = self;
@@ -249,8 +307,7 @@ impl Cheatcode for eth_getLogsCall {
             bail!("topics array must contain at most 4 elements")
         }
 
-        let url =
-            ccx.data.db.active_fork_url().ok_or_else(|| fmt_err!("no active fork URL found"))?;
+        let url = active_fork_url(ccx)?;
         let provider = ProviderBuilder::new(&url).build()?;
         let mut filter = Filter::new().address(*target).from_block(from_block).to_block(to_block);
         for (i, topic) in topics.iter().enumerate() {
@@ -292,6 +349,96 @@ impl Cheatcode for eth_getLogsCall {
     }
 }
 
+/// Performs static calls with a single `eth_call` to Multicall3.
+fn multicall3(url: &str, block: BlockId, calls: &[StaticCall]) -> Result<Vec<StaticCallResult>> {
+    let calls = calls
+        .iter()
+        .map(|call| IMulticall3::Call3 {
+            target: call.target,
+            allowFailure: true,
+            callData: call.callData.clone(),
+        })
+        .collect();
+    let request = TransactionRequest {
+        to: Some(MULTICALL3_ADDRESS),
+        input: TransactionInput::new(IMulticall3::aggregate3Call { calls }.abi_encode().into()),
+        ..Default::default()
+    };
+    let provider = ProviderBuilder::new(url).build()?;
+    let output = RuntimeOrHandle::new()
+        .block_on(provider.call(request, Some(block)))
+        .map_err(|err| fmt_err!("eth_call: {err}"))?;
+    let results = IMulticall3::aggregate3Call::abi_decode_returns(&output, false)?.returnData;
+    Ok(results
+        .into_iter()
+        .map(|result| StaticCallResult { success: result.success, returnData: result.returnData })
+        .collect())
+}
+
+/// A JSON-RPC error.
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+    data: Option<serde_json::Value>,
+}
+
+/// Performs static calls with a batched JSON-RPC request of `eth_call`s, recorded or replayed
+/// with the RPC cassette like the requests of the providers.
+fn batch_eth_call(
+    url: &str,
+    block: BlockId,
+    calls: &[StaticCall],
+) -> Result<Vec<StaticCallResult>> {
+    if !url.starts_with("http") {
+        bail!("batched calls are only supported over HTTP, the fork URL is {url}")
+    }
+
+    let block = serde_json::to_value(block)?;
+    let requests = calls
+        .iter()
+        .map(|call| {
+            ("eth_call", serde_json::json!([{ "to": call.target, "data": call.callData }, block]))
+        })
+        .collect::<Vec<_>>();
+    let responses = RuntimeOrHandle::new()
+        .block_on(cassette::send_batch(url, &requests, |body| async move {
+            let response = reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await?;
+            Ok::<_, eyre::Report>(response.error_for_status()?.bytes().await?.to_vec())
+        }))
+        .map_err(|err| fmt_err!("batched eth_call: {err}"))?;
+
+    calls
+        .iter()
+        .zip(responses)
+        .map(|(call, response)| -> Result<StaticCallResult> {
+            match response {
+                RecordedResponse::Result(output) => {
+                    let output = serde_json::from_value::<Option<Bytes>>(output)?;
+                    Ok(StaticCallResult { success: true, returnData: output.unwrap_or_default() })
+                }
+                RecordedResponse::Error(error) => {
+                    let error: RpcError = serde_json::from_value(error)?;
+                    let revert_data = error
+                        .data
+                        .as_ref()
+                        .and_then(|data| data.as_str())
+                        .and_then(|data| hex::decode(data).ok());
+                    if revert_data.is_none() && !error.message.contains("revert") {
+                        bail!("eth_call to {}: {}", call.target, error.message)
+                    }
+                    let revert_data = revert_data.unwrap_or_default().into();
+                    Ok(StaticCallResult { success: false, returnData: revert_data })
+                }
+            }
+        })
+        .collect()
+}
+
 /// Creates and then also selects the new fork
 fn create_select_fork<DB: DatabaseExt>(
     ccx: &mut CheatsCtxt<DB>,
@@ -333,12 +480,9 @@ fn create_select_fork_at_transaction<DB: DatabaseExt>(
     ccx.state.corrected_nonce = true;
 
     let fork = create_fork_request(ccx, url_or_alias, None)?;
-    let id = ccx.data.db.create_select_fork_at_transaction(
-        fork,
-        ccx.data.env,
-        &mut ccx.data.journaled_state,
-        *transaction,
-    )?;
+    let id = ccx.data.db.create_fork_at_transaction(fork, *transaction)?;
+    ccx.state.select_fork_vm(ccx.data, id);
+    ccx.data.db.select_fork(id, ccx.data.env, &mut ccx.data.journaled_state)?;
     Ok(id.abi_encode())
 }
 
@@ -353,6 +497,31 @@ fn create_fork_at_transaction<DB: DatabaseExt>(
     Ok(id.abi_encode())
 }
 
+/// Returns whether `address` has code at the block of the active fork.
+///
+/// The storage of such an account is only known for the slots loaded so far, as it can't be
+/// listed over RPC.
+pub(super) fn is_fork_contract<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    address: Address,
+) -> Result<bool> {
+    let url = active_fork_url(ccx)?;
+    let id = ccx.data.db.active_fork_id().ok_or_else(|| fmt_err!("no active fork"))?;
+    let block = ccx.data.db.get_fork_info(id)?.fork_env.block.number.saturating_to::<u64>();
+    let provider = ProviderBuilder::new(&url).build()?;
+    let code = RuntimeOrHandle::new()
+        .block_on(provider.get_code_at(address, Some(BlockId::Number(block.into()))))
+        .map_err(|err| fmt_err!("eth_getCode: {err}"))?;
+    Ok(!code.is_empty())
+}
+
+/// Returns the URL of the active fork, if tests are allowed to access it.
+fn active_fork_url<DB: DatabaseExt>(ccx: &mut CheatsCtxt<DB>) -> Result<String> {
+    let url = ccx.data.db.active_fork_url().ok_or_else(|| fmt_err!("no active fork URL found"))?;
+    ccx.state.config.ensure_url_allowed(&url)?;
+    Ok(url)
+}
+
 /// Creates the request object for a new fork request
 fn create_fork_request<DB: DatabaseExt>(
     ccx: &mut CheatsCtxt<DB>,
@@ -360,6 +529,7 @@ fn create_fork_request<DB: DatabaseExt>(
     block: Option<u64>,
 ) -> Result<CreateFork> {
     let url = ccx.state.config.rpc_url(url_or_alias)?;
+    ccx.state.config.ensure_url_allowed(&url)?;
     let mut evm_opts = ccx.state.config.evm_opts.clone();
     evm_opts.fork_block_number = block;
     let fork = CreateFork {
@@ -1,16 +1,18 @@
 //! Implementations of [`Filesystem`](crate::Group::Filesystem) cheatcodes.
 
-use crate::{Cheatcode, Cheatcodes, Result, Vm::*};
+use crate::{Cheatcode, Cheatcodes, FfiInvocation, Result, Vm::*};
 use alloy_json_abi::ContractObject;
-use alloy_primitives::U256;
+use alloy_primitives::{Address, U256};
 use alloy_sol_types::SolValue;
 use foundry_common::{fs, get_artifact_path};
 use foundry_config::fs_permissions::FsAccessKind;
 use std::{
-    collections::hash_map::Entry,
-    io::{BufRead, BufReader, Write},
+    collections::{hash_map::Entry, BTreeMap},
+    io::{self, BufRead, BufReader, IsTerminal, Write},
     path::Path,
     process::Command,
+    sync::mpsc,
+    thread,
     time::{SystemTime, UNIX_EPOCH},
 };
 use walkdir::WalkDir;
@@ -296,6 +298,42 @@ impl Cheatcode for tryFfiCall {
     }
 }
 
+impl Cheatcode for promptCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        prompt(state, text, read_line).map(|answer| answer.abi_encode())
+    }
+}
+
+impl Cheatcode for promptSecretCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        prompt(state, text, read_secret).map(|answer| answer.abi_encode())
+    }
+}
+
+impl Cheatcode for promptAddressCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        let answer = prompt(state, text, read_line)?;
+        let address = answer
+            .parse::<Address>()
+            .map_err(|err| fmt_err!("invalid address {answer:?} for prompt {text:?}: {err}"))?;
+        Ok(address.abi_encode())
+    }
+}
+
+impl Cheatcode for promptUintCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { promptText: text } = self;
+        let answer = prompt(state, text, read_line)?;
+        let value = answer
+            .parse::<U256>()
+            .map_err(|err| fmt_err!("invalid uint256 {answer:?} for prompt {text:?}: {err}"))?;
+        Ok(value.abi_encode())
+    }
+}
+
 pub(super) fn write_file(state: &Cheatcodes, path: &Path, contents: &[u8]) -> Result {
     let path = state.config.ensure_path_allowed(path, FsAccessKind::Write)?;
     // write access to foundry.toml is not allowed
@@ -339,20 +377,31 @@ fn read_dir(state: &Cheatcodes, path: &Path, max_depth: u64, follow_links: bool)
 }
 
 fn ffi(state: &Cheatcodes, input: &[String]) -> Result<FfiResult> {
+    let allowlist = &state.config.ffi_allowlist;
     ensure!(
         state.config.ffi,
         "FFI is disabled; add the `--ffi` flag to allow tests to call external commands"
     );
+    state.config.ensure_offline_allowed("vm.ffi")?;
     ensure!(!input.is_empty() && !input[0].is_empty(), "can't execute empty command");
+
+    let log = |allowed, exit_code| {
+        let invocation = FfiInvocation { command: input.to_vec(), allowed, exit_code };
+        state.config.ffi_log.lock().push(invocation);
+    };
+    if !allowlist.is_empty() && !foundry_config::ffi::is_allowed(allowlist, input) {
+        log(false, None);
+        bail!("command {input:?} is not allowed by the `ffi_allowlist` of the config");
+    }
+
     let mut cmd = Command::new(&input[0]);
     cmd.args(&input[1..]);
 
     debug!(target: "cheatcodes", ?cmd, "invoking ffi");
 
-    let output = cmd
-        .current_dir(&state.config.root)
-        .output()
-        .map_err(|err| fmt_err!("failed to execute command {cmd:?}: {err}"))?;
+    let output = cmd.current_dir(&state.config.root).output();
+    log(true, output.as_ref().ok().and_then(|output| output.status.code()));
+    let output = output.map_err(|err| fmt_err!("failed to execute command {cmd:?}: {err}"))?;
 
     // The stdout might be encoded on valid hex, or it might just be a string,
     // so we need to determine which it is to avoid improperly encoding later.
@@ -370,10 +419,82 @@ fn ffi(state: &Cheatcodes, input: &[String]) -> Result<FfiResult> {
     })
 }
 
+/// Answers a prompt, either from the terminal when the session is interactive, or from the
+/// configured answers file and `FOUNDRY_PROMPT_*` environment variables otherwise.
+fn prompt(state: &Cheatcodes, text: &str, input: fn(&str) -> io::Result<String>) -> Result<String> {
+    if io::stdin().is_terminal() && io::stderr().is_terminal() {
+        let (tx, rx) = mpsc::channel();
+        let text_owned = text.to_string();
+        thread::spawn(move || {
+            let _ = tx.send(input(&text_owned));
+        });
+
+        return match rx.recv_timeout(state.config.prompt_timeout) {
+            Ok(Ok(answer)) => Ok(answer.trim().to_string()),
+            Ok(Err(err)) => Err(fmt_err!("failed to read answer for prompt {text:?}: {err}")),
+            Err(_) => Err(fmt_err!(
+                "prompt {text:?} timed out after {}s",
+                state.config.prompt_timeout.as_secs()
+            )),
+        };
+    }
+
+    non_interactive_answer(state, text)
+}
+
+/// Looks up the answer to a prompt in non-interactive sessions.
+///
+/// The answers file, if configured, takes precedence over the environment.
+fn non_interactive_answer(state: &Cheatcodes, text: &str) -> Result<String> {
+    let env_key = prompt_env_key(text);
+    if let Some(path) = &state.config.prompt_answers {
+        let answers: BTreeMap<String, serde_json::Value> = fs::read_json_file(path)?;
+        if let Some(answer) = answers.get(text).or_else(|| answers.get(&env_key)) {
+            return Ok(match answer {
+                serde_json::Value::String(s) => s.trim().to_string(),
+                other => other.to_string(),
+            });
+        }
+    }
+
+    match std::env::var(&env_key) {
+        Ok(answer) => Ok(answer.trim().to_string()),
+        Err(_) => Err(fmt_err!(
+            "no answer for prompt {text:?} in non-interactive mode; \
+             set `{env_key}` or add it to the `prompt_answers` file"
+        )),
+    }
+}
+
+/// Returns the environment variable holding the answer of a prompt, e.g.
+/// `Owner address?` => `FOUNDRY_PROMPT_OWNER_ADDRESS`.
+fn prompt_env_key(text: &str) -> String {
+    let mut key = String::from("FOUNDRY_PROMPT");
+    for word in text.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+        key.push('_');
+        key.push_str(&word.to_ascii_uppercase());
+    }
+    key
+}
+
+fn read_line(text: &str) -> io::Result<String> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{text} ")?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer)
+}
+
+fn read_secret(text: &str) -> io::Result<String> {
+    rpassword::prompt_password(format!("{text} "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::CheatsConfig;
+    use foundry_config::FfiAllowedCommand;
     use std::{path::PathBuf, sync::Arc};
 
     fn cheats() -> Cheatcodes {
@@ -403,6 +524,57 @@ mod tests {
         assert_eq!(output.stdout, msg.as_bytes());
     }
 
+    #[test]
+    fn test_ffi_allowlist() {
+        let config = CheatsConfig {
+            ffi: true,
+            ffi_allowlist: vec![FfiAllowedCommand {
+                command: "echo".to_string(),
+                args: Some(vec!["gm".to_string()]),
+            }],
+            root: PathBuf::from(&env!("CARGO_MANIFEST_DIR")),
+            ..Default::default()
+        };
+        let cheats = Cheatcodes { config: Arc::new(config.clone()), ..Default::default() };
+
+        let output = ffi(&cheats, &["echo".to_string(), "gm".to_string()]).unwrap();
+        assert_eq!(output.stdout, b"gm");
+        let err = ffi(&cheats, &["echo".to_string(), "gn".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("is not allowed by the `ffi_allowlist`"), "{err}");
+
+        let log = cheats.config.ffi_log.lock();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].to_string(), "executed `echo gm` (exit code 0)");
+        assert_eq!(log[1].to_string(), "denied `echo gn`");
+
+        // the allowlist restricts the commands of `--ffi`, it doesn't enable it
+        let config = CheatsConfig { ffi: false, ffi_log: Default::default(), ..config };
+        let cheats = Cheatcodes { config: Arc::new(config), ..Default::default() };
+        let err = ffi(&cheats, &["echo".to_string(), "gm".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("FFI is disabled"), "{err}");
+        assert!(cheats.config.ffi_log.lock().is_empty());
+    }
+
+    #[test]
+    fn test_prompt_env_key() {
+        assert_eq!(prompt_env_key("Owner address?"), "FOUNDRY_PROMPT_OWNER_ADDRESS");
+        assert_eq!(prompt_env_key("  fee (bps):"), "FOUNDRY_PROMPT_FEE_BPS");
+    }
+
+    #[test]
+    fn test_prompt_answers_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.json");
+        std::fs::write(&path, r#"{"Owner address?": " 0xdead ", "FOUNDRY_PROMPT_FEE": 30}"#)
+            .unwrap();
+        let config = CheatsConfig { prompt_answers: Some(path), ..Default::default() };
+        let cheats = Cheatcodes { config: Arc::new(config), ..Default::default() };
+
+        assert_eq!(non_interactive_answer(&cheats, "Owner address?").unwrap(), "0xdead");
+        assert_eq!(non_interactive_answer(&cheats, "fee").unwrap(), "30");
+        assert!(non_interactive_answer(&cheats, "unknown prompt").is_err());
+    }
+
     #[test]
     fn test_artifact_parsing() {
         let s = include_str!("../../evm/test-data/solc-obj.json");
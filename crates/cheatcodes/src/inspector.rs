@@ -7,15 +7,18 @@ use crate::{
         prank::Prank,
         DealRecord, RecordAccess,
     },
-    script::{Broadcast, ScriptWallets},
-    test::expect::{self, ExpectedEmit, ExpectedRevert, ExpectedRevertKind},
+    script::{Broadcast, BroadcastableUserOperation, ExpectedBroadcastEvent, ScriptWallets},
+    test::{
+        expect::{self, ExpectedEmit, ExpectedRevert, ExpectedRevertKind, ExpectedStorageWrite},
+        messaging::Messaging,
+    },
     CheatsConfig, CheatsCtxt, Error, Result,
     Vm::{self, AccountAccess},
 };
 
 use alloy_primitives::{Address, Bytes, Log, LogData, B256, U256, U64};
 use alloy_rpc_types::request::{TransactionInput, TransactionRequest};
-use alloy_sol_types::{SolInterface, SolValue};
+use alloy_sol_types::{SolCall, SolInterface, SolValue};
 use foundry_cheatcodes_common::{
     expect::{ExpectedCallData, ExpectedCallTracker, ExpectedCallType},
     mock::{MockCallDataContext, MockCallReturnData},
@@ -28,15 +31,21 @@ use foundry_evm_core::{
         HARDHAT_CONSOLE_ADDRESS,
     },
 };
-use foundry_zksync_compiler::{DualCompiledContract, FindContract};
+use foundry_zksync_compiler::DualCompiledContracts;
 use foundry_zksync_core::{
     convert::{ConvertAddress, ConvertH160, ConvertH256, ConvertRU256, ConvertU256},
+    is_system_address,
+    vm::{
+        SizeLimitKind, SizeLimitWarning, ZkExecutionMetrics, ZkFuzzValues, ZkPaymasterData,
+        ZkResourceLimitExceeded,
+    },
     ZkTransactionMetadata,
 };
 use itertools::Itertools;
 use revm::{
     interpreter::{
-        opcode, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult, Interpreter,
+        opcode, CallContext, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult,
+        Interpreter, Transfer,
     },
     primitives::{
         AccountInfo, BlockEnv, Bytecode, CreateScheme, Env, ExecutionResult, HashMap as rHashMap,
@@ -46,7 +55,7 @@ use revm::{
 };
 use serde_json::Value;
 use std::{
-    collections::{BTreeMap, HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fs::File,
     io::BufReader,
     ops::Range,
@@ -102,6 +111,8 @@ pub struct BroadcastableTransaction {
     pub transaction: TransactionRequest,
     /// ZK-VM factory deps
     pub zk_tx: Option<ZkTransactionMetadata>,
+    /// Events the transaction is expected to emit, set by `expectBroadcastEvent`.
+    pub expected_events: Vec<ExpectedBroadcastEvent>,
 }
 
 /// List of transactions that can be broadcasted.
@@ -174,6 +185,8 @@ pub struct Cheatcodes {
     pub expected_calls: ExpectedCallTracker,
     /// Expected emits
     pub expected_emits: VecDeque<ExpectedEmit>,
+    /// Expected storage writes
+    pub expected_storage_writes: Vec<ExpectedStorageWrite>,
 
     /// Map of context depths to memory offset ranges that may be written to within the call depth.
     pub allowed_mem_writes: HashMap<u64, Vec<Range<u64>>>,
@@ -188,6 +201,12 @@ pub struct Cheatcodes {
     /// Scripting based transactions
     pub broadcastable_transactions: BroadcastableTransactions,
 
+    /// User operations recorded by `broadcastUserOp`
+    pub broadcastable_user_operations: Vec<BroadcastableUserOperation>,
+
+    /// Expected events for the next broadcasted transaction
+    pub expected_broadcast_events: Vec<ExpectedBroadcastEvent>,
+
     /// Additional, user configurable context this Inspector has access to when inspecting a call
     pub config: Arc<CheatsConfig>,
 
@@ -226,12 +245,21 @@ pub struct Cheatcodes {
     /// Breakpoints supplied by the `breakpoint` cheatcode.
     /// `char -> (address, pc)`
     pub breakpoints: Breakpoints,
+    /// The call site of the `assume` cheatcode that rejected the inputs of the current call,
+    /// `(address, pc)`, without the pc of the contracts executed in the ZK-VM.
+    pub assume_rejected_at: Option<(Address, Option<usize>)>,
 
     /// Use ZK-VM to execute CALLs and CREATEs.
     pub use_zk_vm: bool,
 
+    /// The test contract, whose calls are executed in the EVM when using the ZK-VM. Defaults to
+    /// the callee of the transaction, which the fuzzed calls of the invariant tests are not.
+    pub zk_test_contract: Option<Address>,
+
     /// Dual compiled contracts
-    pub dual_compiled_contracts: Vec<DualCompiledContract>,
+    ///
+    /// Shared with the [`CheatsConfig`] and only copied when a contract gets registered.
+    pub dual_compiled_contracts: Arc<DualCompiledContracts>,
 
     /// Logs printed during ZK-VM execution.
     /// EVM logs have the value `None` so they can be interpolated later, since
@@ -240,6 +268,39 @@ pub struct Cheatcodes {
 
     /// Starts the cheatcode inspector in ZK mode
     pub startup_zk: bool,
+
+    /// Relays messages between L1 (EVM) and L2 (ZK-VM), set by `zkStartMessaging`
+    pub messaging: Option<Messaging>,
+
+    /// The resource limit a ZK-VM execution was aborted for during the current root call.
+    ///
+    /// Once set, all further ZK-VM executions revert and the root call fails.
+    pub zk_limit_exceeded: Option<ZkResourceLimitExceeded>,
+
+    /// Whether calls to the ZK-VM are made with the system call flag, set by `zkSystemMode`.
+    pub zk_system_mode: bool,
+
+    /// The paymaster paying for the calls and creates on the ZK-VM, set by `zkUsePaymaster`.
+    pub zk_paymaster: Option<ZkPaymasterData>,
+
+    /// The contracts validating and executing their calls and creates on the ZK-VM with their own
+    /// code, as accounts, and the signature of their transactions, set by `zkRegisterAccount`.
+    pub zk_accounts: HashMap<Address, Bytes>,
+
+    /// The ergs used, pubdata published and gas refunded by the ZK-VM executions of the current
+    /// root call.
+    pub zk_metrics: ZkExecutionMetrics,
+
+    /// The values observed by the ZK-VM executions of the current root call for the fuzz
+    /// dictionary, if collected.
+    pub zk_fuzz_values: Option<ZkFuzzValues>,
+
+    /// The calls and creations executed in the ZK-VM from the EVM, as `(caller, callee)` pairs.
+    pub zk_calls: HashSet<(Address, Address)>,
+
+    /// The contracts which delegated a call in the ZK-VM since the end of the last EVM call,
+    /// taken by the inspector stack to detect the proxies among them.
+    pub zk_delegators: Vec<Address>,
 }
 
 impl Cheatcodes {
@@ -250,8 +311,10 @@ impl Cheatcodes {
         let script_wallets = config.script_wallets.clone();
         let dual_compiled_contracts = config.dual_compiled_contracts.clone();
         let startup_zk = config.use_zk;
+        let messaging = config.zk_messaging.map(Messaging::new);
         Self {
             config,
+            messaging,
             fs_commit: true,
             labels,
             script_wallets,
@@ -381,10 +444,36 @@ impl Cheatcodes {
         data.env.block.number = U256::from(block_number);
         data.env.block.timestamp = U256::from(block_timestamp);
 
-        for address in data.db.persistent_accounts() {
+        let persistent_accounts = data.db.persistent_accounts();
+        let accounts = persistent_accounts
+            .iter()
+            .copied()
+            .chain(migrated_journaled_accounts(data, &persistent_accounts))
+            .collect::<Vec<_>>();
+        for address in accounts {
             info!(?address, "importing to evm state");
 
             let zk_address = address.to_h160();
+            let account_code_key = get_code_key(&zk_address).key().to_ru256();
+            let (zk_bytecode_hash, _) = data
+                .journaled_state
+                .sload(account_code_account, account_code_key, data.db)
+                .unwrap_or_default();
+            let contract = self.dual_compiled_contracts.find_by_zk_hash(zk_bytecode_hash.to_h256());
+
+            // Other accounts are only migrated if they're dual compiled contracts or EOAs, the
+            // contracts only deployed on one of the VMs are left as they are.
+            if contract.is_none() && !persistent_accounts.contains(&address) {
+                let has_evm_code = data
+                    .journaled_state
+                    .state
+                    .get(&address)
+                    .is_some_and(|account| account.info.code_hash != KECCAK_EMPTY);
+                if zk_bytecode_hash != U256::ZERO || has_evm_code {
+                    continue
+                }
+            }
+
             let balance_key = storage_key_for_eth_balance(&zk_address).key().to_ru256();
             let nonce_key = get_nonce_key(&zk_address).key().to_ru256();
 
@@ -397,25 +486,14 @@ impl Cheatcodes {
             let (tx_nonce, _deployment_nonce) = decompose_full_nonce(full_nonce.to_u256());
             let nonce = tx_nonce.as_u64();
 
-            let account_code_key = get_code_key(&zk_address).key().to_ru256();
-            let (code_hash, code) = data
-                .journaled_state
-                .sload(account_code_account, account_code_key, data.db)
-                .map(|(value, _)| value)
-                .ok()
-                .and_then(|zk_bytecode_hash| {
-                    let zk_bytecode_hash = zk_bytecode_hash.to_h256();
-                    self.dual_compiled_contracts
-                        .iter()
-                        .find(|c| c.zk_bytecode_hash == zk_bytecode_hash)
-                        .map(|contract| {
-                            (
-                                contract.evm_bytecode_hash,
-                                Some(Bytecode::new_raw(Bytes::from(
-                                    contract.evm_deployed_bytecode.clone(),
-                                ))),
-                            )
-                        })
+            let (code_hash, code) = contract
+                .map(|contract| {
+                    (
+                        contract.evm_bytecode_hash,
+                        Some(Bytecode::new_raw(Bytes::from(
+                            contract.evm_deployed_bytecode.clone(),
+                        ))),
+                    )
                 })
                 .unwrap_or_else(|| (KECCAK_EMPTY, None));
 
@@ -456,13 +534,33 @@ impl Cheatcodes {
         let mut known_codes_storage: rHashMap<U256, StorageSlot> = Default::default();
         let mut deployed_codes: HashMap<Address, AccountInfo> = Default::default();
 
-        for address in data.db.persistent_accounts() {
+        let persistent_accounts = data.db.persistent_accounts();
+        let accounts = persistent_accounts
+            .iter()
+            .copied()
+            .chain(migrated_journaled_accounts(data, &persistent_accounts))
+            .collect::<Vec<_>>();
+        for address in accounts {
             info!(?address, "importing to zk state");
 
             let account = journaled_account(data, address).expect("failed to load account");
             let info = &account.info;
             let zk_address = address.to_h160();
 
+            let contract = self
+                .dual_compiled_contracts
+                .find_by_evm_hash(info.code_hash)
+                .filter(|_| info.code_hash != KECCAK_EMPTY);
+
+            // Other accounts are only migrated if they're dual compiled contracts or EOAs, the
+            // contracts only deployed on one of the VMs are left as they are.
+            if contract.is_none() &&
+                info.code_hash != KECCAK_EMPTY &&
+                !persistent_accounts.contains(&address)
+            {
+                continue
+            }
+
             let balance_key = storage_key_for_eth_balance(&zk_address).key().to_ru256();
             let nonce_key = get_nonce_key(&zk_address).key().to_ru256();
             l2_eth_storage.insert(balance_key, StorageSlot::new(info.balance));
@@ -471,9 +569,7 @@ impl Cheatcodes {
             let full_nonce = nonces_to_full_nonce(info.nonce.into(), info.nonce.into());
             nonce_storage.insert(nonce_key, StorageSlot::new(full_nonce.to_ru256()));
 
-            if let Some(contract) = self.dual_compiled_contracts.iter().find(|contract| {
-                info.code_hash != KECCAK_EMPTY && info.code_hash == contract.evm_bytecode_hash
-            }) {
+            if let Some(contract) = contract {
                 account_code_storage.insert(
                     zk_address.to_h256().to_ru256(),
                     StorageSlot::new(contract.zk_bytecode_hash.to_ru256()),
@@ -527,6 +623,159 @@ impl Cheatcodes {
             account.info.code = info.code.clone();
         }
     }
+
+    /// Executes all pending L1 -> L2 priority requests on the ZK-VM, in the order they were made,
+    /// returning their results.
+    ///
+    /// Failed requests are logged and skipped, like on L1 they don't affect the caller.
+    fn relay_priority_requests<DB: DatabaseExt + Send>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+    ) -> Vec<Vm::PriorityRequestResult> {
+        let requests = match &mut self.messaging {
+            Some(messaging) => std::mem::take(&mut messaging.pending_requests),
+            None => return vec![],
+        };
+
+        let mut results = Vec::with_capacity(requests.len());
+        for request in requests {
+            info!(?request, "relaying priority request to zk vm");
+
+            let balance =
+                foundry_zksync_core::balance(request.sender, data.db, &mut data.journaled_state);
+            foundry_zksync_core::cheatcodes::deal(
+                request.sender,
+                balance.saturating_add(request.mint),
+                data.db,
+                &mut data.journaled_state,
+            );
+
+            let ccx = foundry_zksync_core::vm::CheatcodeTracerContext {
+                mocked_calls: self.mocked_calls.clone(),
+                expected_calls: None,
+                limits: self.config.zk_resource_limits,
+                limit_exceeded: Some(&mut self.zk_limit_exceeded),
+                system_call: false,
+                paymaster: None,
+                custom_account: None,
+                metrics: None,
+                storage_writes: None,
+                fuzz_values: None,
+                delegators: None,
+            };
+            let factory_deps = request.factory_deps.iter().map(|dep| dep.to_vec()).collect();
+            let result = foundry_zksync_core::vm::call_with_factory_deps::<_, DatabaseError>(
+                &request.call_inputs(),
+                Some(factory_deps),
+                data.env,
+                data.db,
+                &mut data.journaled_state,
+                ccx,
+            );
+            let (success, return_data) = match result {
+                Ok(ExecutionResult::Success { output, logs, .. }) => {
+                    self.record_zk_logs(logs);
+                    (true, output.into_data())
+                }
+                Ok(result) => {
+                    warn!(?request, ?result, "priority request failed on zk vm");
+                    (false, result.into_output().unwrap_or_default())
+                }
+                Err(err) => {
+                    error!(?request, ?err, "failed executing priority request on zk vm");
+                    (false, Bytes::new())
+                }
+            };
+            results.push(Vm::PriorityRequestResult {
+                txHash: request.tx_hash,
+                success,
+                returnData: return_data,
+            });
+        }
+        results
+    }
+
+    /// Executes `zkSystemCall` on the ZK-VM, which cheatcodes can't access from their dispatch:
+    /// calls the target from `caller` with the system call flag set.
+    fn zk_system_call<DB: DatabaseExt + Send>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        caller: Address,
+        system_call: Vm::zkSystemCallCall,
+    ) -> Result {
+        let Vm::zkSystemCallCall { target, data: input } = system_call;
+        if !self.use_zk_vm {
+            bail!("`zkSystemCall` requires the ZK-VM, enable it with `zkVm(true)`")
+        }
+        if let Some(exceeded) = &self.zk_limit_exceeded {
+            return Err(zk_limit_error(exceeded).into())
+        }
+        self.relay_priority_requests(data);
+
+        let call = CallInputs {
+            contract: target,
+            transfer: Transfer { source: caller, target, value: U256::ZERO },
+            input,
+            gas_limit: data.env.tx.gas_limit,
+            context: CallContext {
+                address: target,
+                caller,
+                code_address: target,
+                apparent_value: U256::ZERO,
+                scheme: CallScheme::Call,
+            },
+            is_static: false,
+        };
+        let ccx = foundry_zksync_core::vm::CheatcodeTracerContext {
+            mocked_calls: self.mocked_calls.clone(),
+            expected_calls: Some(&mut self.expected_calls),
+            limits: self.config.zk_resource_limits,
+            limit_exceeded: Some(&mut self.zk_limit_exceeded),
+            system_call: true,
+            paymaster: None,
+            custom_account: None,
+            metrics: Some(&mut self.zk_metrics),
+            storage_writes: None,
+            fuzz_values: None,
+            delegators: None,
+        };
+        match foundry_zksync_core::vm::call::<_, DatabaseError>(
+            &call,
+            None,
+            data.env,
+            data.db,
+            &mut data.journaled_state,
+            ccx,
+        ) {
+            Ok(ExecutionResult::Success { output, logs, .. }) => {
+                self.record_zk_logs(logs);
+                Ok(output.into_data().abi_encode())
+            }
+            Ok(ExecutionResult::Revert { output, .. }) => Err(output.into()),
+            Ok(ExecutionResult::Halt { reason, .. }) => bail!("zk vm halted: {reason:?}"),
+            Err(err) => bail!("failed executing the system call on zk vm: {err:?}"),
+        }
+    }
+
+    /// Records the size of the data of a ZK-VM transaction, a call from the EVM or a deployment,
+    /// if it approaches the EraVM limit.
+    fn record_zk_size(&self, kind: SizeLimitKind, context: String, size: usize) {
+        let Some(warnings) = &self.config.zk_size_warnings else { return };
+        if let Some(warning) = SizeLimitWarning::check(kind, context, size, false) {
+            warn!(%warning, "zk vm call approaches the size limits");
+            warning.record(&mut warnings.lock());
+        }
+    }
+
+    /// Records the logs of a ZK-VM execution, and the L2 -> L1 messages among them.
+    fn record_zk_logs(&mut self, logs: Vec<revm::primitives::Log>) {
+        if let Some(messaging) = &mut self.messaging {
+            messaging.collect_messages(&logs);
+        }
+        self.combined_logs.extend(logs.into_iter().map(|log| {
+            Some(Log { address: log.address, data: LogData::new_unchecked(log.topics, log.data) })
+        }));
+    }
 }
 
 impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
@@ -657,6 +906,15 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
             }
         }
 
+        // Record the storage writes if `expectStorageWrite` has been called
+        if !self.expected_storage_writes.is_empty() &&
+            interpreter.current_opcode() == opcode::SSTORE
+        {
+            let key = try_or_continue!(interpreter.stack().peek(0));
+            let value = try_or_continue!(interpreter.stack().peek(1));
+            expect::handle_expect_storage_write(self, interpreter.contract().address, key, value);
+        }
+
         // Record account access via SELFDESTRUCT if `recordAccountAccesses` has been called
         if let Some(account_accesses) = &mut self.recorded_account_diffs_stack {
             if interpreter.current_opcode() == opcode::SELFDESTRUCT {
@@ -973,7 +1231,24 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
     ) -> (InstructionResult, Gas, Bytes) {
         let gas = Gas::new(call.gas_limit);
 
+        if data.journaled_state.depth() == 0 {
+            self.zk_metrics = ZkExecutionMetrics::default();
+            if let Some(fuzz_values) = &mut self.zk_fuzz_values {
+                *fuzz_values = ZkFuzzValues::default();
+            }
+        }
+
         if call.contract == CHEATCODE_ADDRESS {
+            if let Ok(system_call) = Vm::zkSystemCallCall::abi_decode(&call.input, false) {
+                return match self.zk_system_call(data, call.context.caller, system_call) {
+                    Ok(retdata) => (InstructionResult::Return, gas, retdata.into()),
+                    Err(err) => (InstructionResult::Revert, gas, err.abi_encode().into()),
+                };
+            }
+            if Vm::zkRelayPriorityRequestsCall::abi_decode(&call.input, false).is_ok() {
+                let results = self.relay_priority_requests(data);
+                return (InstructionResult::Return, gas, results.abi_encode().into())
+            }
             return match self.apply_cheatcode(data, call) {
                 Ok(retdata) => (InstructionResult::Return, gas, retdata.into()),
                 Err(err) => (InstructionResult::Revert, gas, err.abi_encode().into()),
@@ -986,6 +1261,15 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
             return (InstructionResult::Continue, gas, Bytes::new());
         }
 
+        if let Some(messaging) = &mut self.messaging {
+            if !self.use_zk_vm && call.contract == messaging.mailbox {
+                return match messaging.handle_mailbox_call(call, data) {
+                    Ok(retdata) => (InstructionResult::Return, gas, retdata.into()),
+                    Err(err) => (InstructionResult::Revert, gas, err.abi_encode().into()),
+                };
+            }
+        }
+
         // Handle expected calls
 
         // Grab the different calldatas expected.
@@ -1099,7 +1383,10 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
 
                     let zk_tx = if self.use_zk_vm {
                         // We shouldn't need factory_deps for CALLs
-                        Some(ZkTransactionMetadata { factory_deps: Default::default() })
+                        Some(ZkTransactionMetadata {
+                            factory_deps: Default::default(),
+                            paymaster: self.zk_paymaster.clone(),
+                        })
                     } else {
                         None
                     };
@@ -1120,6 +1407,7 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                             ..Default::default()
                         },
                         zk_tx,
+                        expected_events: std::mem::take(&mut self.expected_broadcast_events),
                     });
                     debug!(target: "cheatcodes", tx=?self.broadcastable_transactions.back().unwrap(), "broadcastable call");
 
@@ -1181,14 +1469,21 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
         }
 
         if self.use_zk_vm {
-            if let TransactTo::Call(test_contract) = data.env.tx.transact_to {
-                if call.contract == test_contract {
-                    info!("using evm for calls to test contract {:?}", data.env);
-                    return (InstructionResult::Continue, gas, Bytes::new())
-                }
+            let test_contract = self.zk_test_contract.or(match data.env.tx.transact_to {
+                TransactTo::Call(test_contract) => Some(test_contract),
+                TransactTo::Create(_) => None,
+            });
+            if test_contract == Some(call.contract) {
+                info!("using evm for calls to test contract {:?}", data.env);
+                return (InstructionResult::Continue, gas, Bytes::new())
             }
 
             info!("running call in zk vm {:#?}", call);
+            self.zk_calls.insert((call.context.caller, call.contract));
+            if let Some(exceeded) = &self.zk_limit_exceeded {
+                return (InstructionResult::Revert, gas, zk_limit_error(exceeded))
+            }
+            self.relay_priority_requests(data);
 
             let code_hash = data
                 .journaled_state
@@ -1196,18 +1491,33 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                 .map(|(account, _)| account.info.code_hash)
                 .unwrap_or_default();
             let contract = if code_hash != KECCAK_EMPTY {
-                self.dual_compiled_contracts
-                    .find_zk_bytecode_hash(zksync_types::H256::from(code_hash.0))
+                self.dual_compiled_contracts.find_by_zk_hash(zksync_types::H256::from(code_hash.0))
             } else {
                 None
             };
             if contract.is_none() {
                 error!("no zk contract was found for {code_hash:?}");
             }
+            self.record_zk_size(
+                SizeLimitKind::Calldata,
+                format!("call to {}", call.contract),
+                call.input.len(),
+            );
 
+            let mut storage_writes = Vec::new();
             let ccx = foundry_zksync_core::vm::CheatcodeTracerContext {
                 mocked_calls: self.mocked_calls.clone(),
                 expected_calls: Some(&mut self.expected_calls),
+                limits: self.config.zk_resource_limits,
+                limit_exceeded: Some(&mut self.zk_limit_exceeded),
+                system_call: self.zk_system_mode,
+                paymaster: self.zk_paymaster.clone(),
+                custom_account: self.zk_accounts.get(&call.context.caller).cloned(),
+                metrics: Some(&mut self.zk_metrics),
+                storage_writes: (!self.expected_storage_writes.is_empty())
+                    .then_some(&mut storage_writes),
+                fuzz_values: self.zk_fuzz_values.as_mut(),
+                delegators: Some(&mut self.zk_delegators),
             };
             if let Ok(result) = foundry_zksync_core::vm::call::<_, DatabaseError>(
                 call,
@@ -1217,9 +1527,20 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                 &mut data.journaled_state,
                 ccx,
             ) {
+                for write in storage_writes {
+                    expect::handle_expect_storage_write(
+                        self,
+                        write.address,
+                        write.slot,
+                        write.value,
+                    );
+                }
                 return match result {
                     ExecutionResult::Success { output, logs, .. } => match output {
                         Output::Call(bytes) => {
+                            if let Some(messaging) = &mut self.messaging {
+                                messaging.collect_messages(&logs);
+                            }
                             self.combined_logs.extend(logs.clone().into_iter().map(|log| {
                                 Some(Log {
                                     address: log.address,
@@ -1415,6 +1736,17 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
             }
         }
 
+        // Check the storage writes expected from the depth of the call, ignoring staticcalls
+        if !call.is_static {
+            if let Some(msg) = expect::check_expected_storage_writes(
+                self,
+                data.journaled_state.depth(),
+                status.is_revert(),
+            ) {
+                return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
+            }
+        }
+
         // this will ensure we don't have false positives when trying to diagnose reverts in fork
         // mode
         let diag = self.fork_revert_diagnostic.take();
@@ -1443,6 +1775,12 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
 
         // If the depth is 0, then this is the root call terminating
         if data.journaled_state.depth() == 0 {
+            // A ZK-VM execution was aborted, which must fail the root call even if the revert was
+            // caught
+            if let Some(exceeded) = self.zk_limit_exceeded.take() {
+                return (InstructionResult::Revert, remaining_gas, zk_limit_error(&exceeded))
+            }
+
             // If we already have a revert, we shouldn't run the below logic as it can obfuscate an
             // earlier error that happened first with unrelated information about
             // another error when using cheatcodes.
@@ -1511,6 +1849,22 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                 };
                 return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
             }
+
+            // Check if we have any leftover expected storage writes, not followed by a call
+            if let Some(expected) = self.expected_storage_writes.iter().find(|e| !e.found()) {
+                let msg = expected.failure(false);
+                return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
+            }
+            self.expected_storage_writes.clear();
+
+            // Check if we have any broadcast event expectations without a transaction
+            if let Some(expected) = self.expected_broadcast_events.first() {
+                let msg = format!(
+                    "expected a broadcasted transaction emitting {expected}, \
+                     but no transaction was broadcasted afterwards"
+                );
+                return (InstructionResult::Revert, remaining_gas, Error::encode(msg));
+            }
         }
 
         (status, remaining_gas, retdata)
@@ -1567,7 +1921,7 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                         ) as u64;
                         let contract = self
                             .dual_compiled_contracts
-                            .find_evm_bytecode(&call.init_code.0)
+                            .find_by_evm_bytecode(&call.init_code.0)
                             .unwrap_or_else(|| {
                                 panic!("failed finding contract for {:?}", call.init_code)
                             });
@@ -1579,9 +1933,12 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                             constructor_input,
                         );
                         bytecode = Bytes::from(create_input);
-                        let factory_deps = vec![contract.zk_deployed_bytecode.clone()];
+                        let factory_deps = contract.factory_deps();
 
-                        Some(ZkTransactionMetadata { factory_deps })
+                        Some(ZkTransactionMetadata {
+                            factory_deps,
+                            paymaster: self.zk_paymaster.clone(),
+                        })
                     } else {
                         None
                     };
@@ -1602,6 +1959,7 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                             ..Default::default()
                         },
                         zk_tx,
+                        expected_events: std::mem::take(&mut self.expected_broadcast_events),
                     });
                     let kind = match call.scheme {
                         CreateScheme::Create => "create",
@@ -1669,15 +2027,44 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                 info!("ignoring DEFAULT_CREATE2_DEPLOYER_CODE for zk");
                 return (InstructionResult::Continue, None, gas, Bytes::new())
             }
+            if let Some(exceeded) = &self.zk_limit_exceeded {
+                return (InstructionResult::Revert, None, gas, zk_limit_error(exceeded))
+            }
+
+            self.relay_priority_requests(data);
 
             let zk_contract = self
                 .dual_compiled_contracts
-                .find_evm_bytecode(&call.init_code.0)
+                .find_by_evm_bytecode(&call.init_code.0)
                 .unwrap_or_else(|| panic!("failed finding contract for {:?}", call.init_code));
-
+            let constructor_input = &call.init_code[zk_contract.evm_bytecode.len()..];
+            if let Err(err) = zk_contract.check_constructor_input(constructor_input) {
+                return (InstructionResult::Revert, None, gas, Error::encode(err))
+            }
+            // the factory dependencies are sent with the deployment transaction
+            let deployment_size = constructor_input.len() +
+                zk_contract.zk_deployed_bytecode.len() +
+                zk_contract.zk_factory_deps.iter().map(Vec::len).sum::<usize>();
+            self.record_zk_size(
+                SizeLimitKind::Calldata,
+                format!("deployment of {}", zk_contract.name),
+                deployment_size,
+            );
+
+            let mut storage_writes = Vec::new();
             let ccx = foundry_zksync_core::vm::CheatcodeTracerContext {
                 mocked_calls: self.mocked_calls.clone(),
                 expected_calls: Some(&mut self.expected_calls),
+                limits: self.config.zk_resource_limits,
+                limit_exceeded: Some(&mut self.zk_limit_exceeded),
+                system_call: false,
+                paymaster: self.zk_paymaster.clone(),
+                custom_account: self.zk_accounts.get(&call.caller).cloned(),
+                metrics: Some(&mut self.zk_metrics),
+                storage_writes: (!self.expected_storage_writes.is_empty())
+                    .then_some(&mut storage_writes),
+                fuzz_values: self.zk_fuzz_values.as_mut(),
+                delegators: Some(&mut self.zk_delegators),
             };
             if let Ok(result) = foundry_zksync_core::vm::create::<_, DatabaseError>(
                 call,
@@ -1687,9 +2074,23 @@ impl<DB: DatabaseExt + Send> Inspector<DB> for Cheatcodes {
                 &mut data.journaled_state,
                 ccx,
             ) {
+                for write in storage_writes {
+                    expect::handle_expect_storage_write(
+                        self,
+                        write.address,
+                        write.slot,
+                        write.value,
+                    );
+                }
                 return match result {
                     ExecutionResult::Success { output, logs, .. } => match output {
                         Output::Create(bytes, address) => {
+                            if let Some(address) = address {
+                                self.zk_calls.insert((call.caller, address));
+                            }
+                            if let Some(messaging) = &mut self.messaging {
+                                messaging.collect_messages(&logs);
+                            }
                             self.combined_logs.extend(logs.into_iter().map(|log| {
                                 Some(Log {
                                     address: log.address,
@@ -1857,6 +2258,30 @@ fn mstore_revert_string(interpreter: &mut Interpreter<'_>, bytes: &[u8]) {
     interpreter.return_len = interpreter.shared_memory.len() - starting_offset
 }
 
+/// Returns the revert data of a ZK-VM execution aborted for exceeding a resource limit.
+fn zk_limit_error(exceeded: &ZkResourceLimitExceeded) -> Bytes {
+    Error::encode(format!("zk vm execution aborted: {exceeded}"))
+}
+
+/// Returns the accounts of the journaled state to migrate when switching between the VMs, besides
+/// the persistent accounts: all of them but the system contracts, precompiles and cheatcodes.
+fn migrated_journaled_accounts<DB: DatabaseExt>(
+    data: &EVMData<'_, DB>,
+    persistent_accounts: &[Address],
+) -> Vec<Address> {
+    data.journaled_state
+        .state
+        .keys()
+        .copied()
+        .filter(|address| {
+            !persistent_accounts.contains(address) &&
+                !is_system_address(*address) &&
+                *address != CHEATCODE_ADDRESS &&
+                *address != HARDHAT_CONSOLE_ADDRESS
+        })
+        .collect()
+}
+
 /// Applies the default CREATE2 deployer for contract creation.
 ///
 /// This function is invoked during the contract creation process and updates the caller of the
@@ -147,6 +147,18 @@ impl Cheatcode for parseJsonKeysCall {
     }
 }
 
+impl Cheatcode for parseJsonAsCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { schemaId, json } = self;
+        let schema = std::str::from_utf8(schemaId)
+            .map_err(|_| fmt_err!("schema ID must be an UTF-8 encoded type description"))?;
+        let schema = Schema::parse(schema)?;
+        let json = parse_json_str(json)?;
+        let value = schema.validate(&json, "$")?;
+        Ok(encode(vec![value]))
+    }
+}
+
 impl Cheatcode for serializeJsonCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { objectKey, value } = self;
@@ -311,6 +323,183 @@ fn parse_json_coerce(json: &str, path: &str, ty: &DynSolType) -> Result {
     }
 }
 
+/// A typed description of a JSON value, as passed to `parseJsonAs`.
+///
+/// Structs are written like Solidity tuples with named components, e.g.
+/// `(address owner,(string name,bool enabled)[] modules)`, where each name is the JSON key the
+/// component is read from. Anything else is parsed as a regular Solidity type.
+#[derive(Clone, Debug, PartialEq)]
+enum Schema {
+    /// A value coerced to the given type, like the `parseJson*` cheatcodes do.
+    Value(DynSolType),
+    /// An array of values matching the inner schema, with an optional fixed length.
+    Array(Box<Schema>, Option<usize>),
+    /// A JSON object with exactly the given keys, encoded as a tuple in declaration order.
+    Struct(Vec<(String, Schema)>),
+}
+
+impl Schema {
+    fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let Some(inner) = s.strip_prefix('(') else {
+            let ty = DynSolType::parse(s)
+                .map_err(|e| fmt_err!("invalid type {s:?} in JSON schema: {e}"))?;
+            return Ok(Self::Value(ty))
+        };
+
+        // find the closing parenthesis of the struct, anything after it are array suffixes
+        let mut depth = 0usize;
+        let end = inner
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => return true,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                false
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| fmt_err!("missing closing parenthesis in JSON schema {s:?}"))?;
+        let (components, mut suffix) = (&inner[..end], &inner[end + 1..]);
+
+        let mut fields = Vec::new();
+        for component in split_top_level(components) {
+            let component = component.trim();
+            let Some((ty, name)) = component.rsplit_once(char::is_whitespace) else {
+                bail!("component {component:?} in JSON schema {s:?} must be named after its key");
+            };
+            ensure!(
+                !name.contains(['(', ')', '[', ']', ',']),
+                "component {component:?} in JSON schema {s:?} must be named after its key"
+            );
+            ensure!(
+                fields.iter().all(|(n, _)| n != name),
+                "duplicate key {name:?} in JSON schema {s:?}"
+            );
+            fields.push((name.to_string(), Self::parse(ty)?));
+        }
+
+        let mut schema = Self::Struct(fields);
+        while !suffix.is_empty() {
+            let Some((len, rest)) = suffix.strip_prefix('[').and_then(|s| s.split_once(']')) else {
+                bail!("invalid array suffix {suffix:?} in JSON schema {s:?}");
+            };
+            let len = match len.trim() {
+                "" => None,
+                len => Some(
+                    len.parse()
+                        .map_err(|_| fmt_err!("invalid array length {len:?} in JSON schema"))?,
+                ),
+            };
+            schema = Self::Array(Box::new(schema), len);
+            suffix = rest;
+        }
+        Ok(schema)
+    }
+
+    /// Validates `value` against the schema and converts it, reporting the JSON path of the first
+    /// value that doesn't match.
+    fn validate(&self, value: &Value, path: &str) -> Result<DynSolValue> {
+        match self {
+            Self::Value(ty) => match ty {
+                DynSolType::Array(inner) => {
+                    Self::Array(Box::new(Self::Value((**inner).clone())), None)
+                        .validate(value, path)
+                }
+                DynSolType::FixedArray(inner, len) => {
+                    Self::Array(Box::new(Self::Value((**inner).clone())), Some(*len))
+                        .validate(value, path)
+                }
+                ty => {
+                    let s = match value {
+                        Value::String(s) => s.clone(),
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
+                        value => bail!("expected `{ty}` at {path:?}, found {}", json_kind(value)),
+                    };
+                    string::parse_value(&s, ty)
+                        .map_err(|e| fmt_err!("invalid value at {path:?}: {e}"))
+                }
+            },
+            Self::Array(inner, len) => {
+                let Value::Array(array) = value else {
+                    bail!("expected an array at {path:?}, found {}", json_kind(value));
+                };
+                if let Some(len) = *len {
+                    ensure!(
+                        array.len() == len,
+                        "expected {len} elements at {path:?}, found {}",
+                        array.len()
+                    );
+                }
+                let values = array
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| inner.validate(value, &format!("{path}[{i}]")))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(if len.is_some() {
+                    DynSolValue::FixedArray(values)
+                } else {
+                    DynSolValue::Array(values)
+                })
+            }
+            Self::Struct(fields) => {
+                let Value::Object(object) = value else {
+                    bail!("expected an object at {path:?}, found {}", json_kind(value));
+                };
+                if let Some(key) = object.keys().find(|key| fields.iter().all(|(n, _)| n != *key)) {
+                    bail!("unexpected key {:?} not in schema", format!("{path}.{key}"));
+                }
+                fields
+                    .iter()
+                    .map(|(name, schema)| {
+                        let path = format!("{path}.{name}");
+                        let value =
+                            object.get(name).ok_or_else(|| fmt_err!("missing key {path:?}"))?;
+                        schema.validate(value, &path)
+                    })
+                    .collect::<Result<_>>()
+                    .map(DynSolValue::Tuple)
+            }
+        }
+    }
+}
+
+/// Splits `s` at all commas that are not nested in parentheses.
+fn split_top_level(s: &str) -> Vec<&str> {
+    if s.trim().is_empty() {
+        return Vec::new()
+    }
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
 fn parse_json_str(json: &str) -> Result<Value> {
     serde_json::from_str(json).map_err(|e| fmt_err!("failed parsing JSON: {e}"))
 }
@@ -345,7 +534,7 @@ fn encode(values: Vec<DynSolValue>) -> Vec<u8> {
 
 /// Canonicalize a json path key to always start from the root of the document.
 /// Read more about json path syntax: <https://goessner.net/articles/JsonPath/>
-fn canonicalize_json_path(path: &str) -> Cow<'_, str> {
+pub(super) fn canonicalize_json_path(path: &str) -> Cow<'_, str> {
     if !path.starts_with('$') {
         format!("${path}").into()
     } else {
@@ -21,7 +21,7 @@ mod error;
 pub use error::{Error, ErrorKind, Result};
 
 mod config;
-pub use config::CheatsConfig;
+pub use config::{CheatsConfig, FfiInvocation};
 
 mod inspector;
 pub use inspector::{BroadcastableTransaction, BroadcastableTransactions, Cheatcodes, Context};
@@ -34,9 +34,10 @@ mod json;
 mod script;
 mod string;
 mod test;
+mod toml;
 mod utils;
 
-pub use script::ScriptWallets;
+pub use script::{BroadcastableUserOperation, ExpectedBroadcastEvent, ScriptWallets};
 
 /// Cheatcode implementation.
 pub(crate) trait Cheatcode: CheatcodeDef + DynCheatcode {
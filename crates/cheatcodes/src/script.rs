@@ -1,12 +1,15 @@
 //! Implementations of [`Scripting`](crate::Group::Scripting) cheatcodes.
 
-use crate::{Cheatcode, CheatsCtxt, DatabaseExt, Result, Vm::*};
-use alloy_primitives::{Address, U256};
+use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
+use alloy_primitives::{Address, Bytes, B256, U256};
 use alloy_signer::{LocalWallet, Signer};
+use foundry_common::provider::alloy::RpcUrl;
 use foundry_config::Config;
 use foundry_wallets::{multi_wallet::MultiWallet, WalletSigner};
+use itertools::Itertools;
 use parking_lot::Mutex;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
 
 impl Cheatcode for broadcast_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
@@ -61,6 +64,125 @@ impl Cheatcode for stopBroadcastCall {
     }
 }
 
+impl Cheatcode for expectBroadcastEvent_0Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { emitter, topics } = self;
+        expect_broadcast_event(state, *emitter, topics, None)
+    }
+}
+
+impl Cheatcode for expectBroadcastEvent_1Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { emitter, topics, data } = self;
+        expect_broadcast_event(state, *emitter, topics, Some(data.clone().into()))
+    }
+}
+
+impl Cheatcode for broadcastUserOp_0Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { entryPoint, account, callData } = self;
+        let signer = match &ccx.state.broadcast {
+            Some(broadcast) => broadcast.new_origin,
+            None => default_origin(ccx)?,
+        };
+        broadcast_user_op(ccx, *entryPoint, *account, callData, signer)
+    }
+}
+
+impl Cheatcode for broadcastUserOp_1Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { entryPoint, account, callData, signer } = self;
+        broadcast_user_op(ccx, *entryPoint, *account, callData, *signer)
+    }
+}
+
+/// A user operation recorded by `broadcastUserOp`, to be submitted through a bundler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BroadcastableUserOperation {
+    /// The RPC URL of the fork the operation was recorded on, if any.
+    pub rpc: Option<RpcUrl>,
+    /// The `EntryPoint` the operation is submitted to.
+    pub entry_point: Address,
+    /// The smart account sending the operation.
+    pub account: Address,
+    /// The calldata the account is called with.
+    pub call_data: Bytes,
+    /// The account owner signing the operation.
+    pub signer: Address,
+}
+
+fn broadcast_user_op<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    entry_point: Address,
+    account: Address,
+    call_data: &[u8],
+    signer: Address,
+) -> Result {
+    let op = BroadcastableUserOperation {
+        rpc: ccx.data.db.active_fork_url(),
+        entry_point,
+        account,
+        call_data: call_data.to_vec().into(),
+        signer,
+    };
+    debug!(target: "cheatcodes", ?op, "recorded user operation");
+    ccx.state.broadcastable_user_operations.push(op);
+    Ok(Default::default())
+}
+
+/// An event that a broadcasted transaction is expected to emit, set by `expectBroadcastEvent`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedBroadcastEvent {
+    /// The address of the contract emitting the event.
+    pub emitter: Address,
+    /// The first topics of the event.
+    pub topics: Vec<B256>,
+    /// The data of the event, if it should be checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+impl ExpectedBroadcastEvent {
+    /// Returns whether the given log satisfies this expectation.
+    pub fn matches(&self, address: Address, topics: &[B256], data: &[u8]) -> bool {
+        address == self.emitter &&
+            topics.starts_with(&self.topics) &&
+            self.data.as_ref().map_or(true, |expected| expected[..] == *data)
+    }
+
+    /// Returns whether any of the given logs satisfies this expectation.
+    pub fn is_satisfied_by<'a>(
+        &self,
+        mut logs: impl Iterator<Item = (Address, &'a [B256], &'a [u8])>,
+    ) -> bool {
+        logs.any(|(address, topics, data)| self.matches(address, topics, data))
+    }
+}
+
+impl fmt::Display for ExpectedBroadcastEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let topics = self.topics.iter().format(", ");
+        write!(f, "an event from {} with topics [{topics}]", self.emitter)?;
+        if let Some(data) = &self.data {
+            write!(f, " and data {data}")?;
+        }
+        Ok(())
+    }
+}
+
+fn expect_broadcast_event(
+    state: &mut Cheatcodes,
+    emitter: Address,
+    topics: &[B256],
+    data: Option<Bytes>,
+) -> Result {
+    ensure!(topics.len() <= 4, "an event can have at most 4 topics, got {}", topics.len());
+    let expected = ExpectedBroadcastEvent { emitter, topics: topics.to_vec(), data };
+    debug!(target: "cheatcodes", %expected, "expecting broadcast event");
+    state.expected_broadcast_events.push(expected);
+    Ok(Default::default())
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Broadcast {
     /// Address of the transaction origin
@@ -128,25 +250,13 @@ fn broadcast<DB: DatabaseExt>(
     // TODO probably need to correct nonce in ZK scope
     correct_sender_nonce(ccx)?;
 
-    let mut new_origin = new_origin.cloned();
-
-    if new_origin.is_none() {
-        if let Some(script_wallets) = &ccx.state.script_wallets {
-            let mut script_wallets = script_wallets.inner.lock();
-            if let Some(provided_sender) = script_wallets.provided_sender {
-                new_origin = Some(provided_sender);
-            } else {
-                let signers = script_wallets.multi_wallet.signers()?;
-                if signers.len() == 1 {
-                    let address = signers.keys().next().unwrap();
-                    new_origin = Some(*address);
-                }
-            }
-        }
-    }
+    let new_origin = match new_origin {
+        Some(new_origin) => *new_origin,
+        None => default_origin(ccx)?,
+    };
 
     let broadcast = Broadcast {
-        new_origin: new_origin.unwrap_or(ccx.data.env.tx.caller),
+        new_origin,
         original_caller: ccx.caller,
         original_origin: ccx.data.env.tx.caller,
         depth: ccx.data.journaled_state.depth(),
@@ -157,6 +267,22 @@ fn broadcast<DB: DatabaseExt>(
     Ok(Default::default())
 }
 
+/// Returns the sender used when broadcasting without an explicit signer: the `--sender` of the
+/// script, its only signer, or the caller of the script otherwise.
+fn default_origin<DB: DatabaseExt>(ccx: &mut CheatsCtxt<DB>) -> Result<Address> {
+    if let Some(script_wallets) = &ccx.state.script_wallets {
+        let mut script_wallets = script_wallets.inner.lock();
+        if let Some(provided_sender) = script_wallets.provided_sender {
+            return Ok(provided_sender)
+        }
+        let signers = script_wallets.multi_wallet.signers()?;
+        if signers.len() == 1 {
+            return Ok(*signers.keys().next().unwrap())
+        }
+    }
+    Ok(ccx.data.env.tx.caller)
+}
+
 /// Sets up broadcasting from a script with the sender derived from `private_key`.
 /// Adds this private key to `state`'s `script_wallets` vector to later be used for signing
 /// if broadcast is successful.
@@ -157,7 +157,7 @@ where
 }
 
 #[instrument(target = "cheatcodes", level = "debug", skip(ty), fields(%ty), ret)]
-fn parse_value(s: &str, ty: &DynSolType) -> Result<DynSolValue> {
+pub(super) fn parse_value(s: &str, ty: &DynSolType) -> Result<DynSolValue> {
     match ty.coerce_str(s) {
         Ok(value) => Ok(value),
         Err(e) => match parse_value_fallback(s, ty) {
@@ -1,13 +1,17 @@
 //! Implementations of [`Testing`](crate::Group::Testing) cheatcodes.
 
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Error, Result, Vm::*};
-use alloy_primitives::Address;
+use alloy_primitives::{Address, U256};
 use alloy_sol_types::SolValue;
+use foundry_config::ChainInfo;
 use foundry_evm_core::constants::{MAGIC_ASSUME, MAGIC_SKIP};
 use foundry_zksync_compiler::DualCompiledContract;
+use foundry_zksync_core::vm::ZkPaymasterData;
+use std::sync::Arc;
 
 pub(crate) mod assert;
 pub(crate) mod expect;
+pub(crate) mod messaging;
 
 impl Cheatcode for zkVmCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
@@ -41,33 +45,179 @@ impl Cheatcode for zkRegisterContractCall {
             evm_bytecode_hash: *evmBytecodeHash,
             evm_deployed_bytecode: evmDeployedBytecode.clone(),
             evm_bytecode: evmBytecode.clone(),
+            zk_factory_deps: vec![],
+            constructor_inputs: None,
         };
 
-        if let Some(existing) = ccx.state.dual_compiled_contracts.iter().find(|contract| {
-            contract.evm_bytecode_hash == new_contract.evm_bytecode_hash &&
-                contract.zk_bytecode_hash == new_contract.zk_bytecode_hash
-        }) {
+        if let Some(existing) = ccx
+            .state
+            .dual_compiled_contracts
+            .find_by_hashes(new_contract.evm_bytecode_hash, new_contract.zk_bytecode_hash)
+        {
             warn!(name = existing.name, "contract already exists with the given bytecode hashes");
             return Ok(Default::default())
         }
 
-        ccx.state.dual_compiled_contracts.push(new_contract);
+        Arc::make_mut(&mut ccx.state.dual_compiled_contracts).insert(new_contract);
 
         Ok(Default::default())
     }
 }
 
-impl Cheatcode for assumeCall {
+impl Cheatcode for zkSystemCallCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        // executed on the ZK-VM by the inspector, before cheatcodes are dispatched
+        bail!("`zkSystemCall` must be executed by the cheatcode inspector")
+    }
+}
+
+impl Cheatcode for zkSystemModeCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { enable } = *self;
+        state.zk_system_mode = enable;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkUsePaymasterCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { paymaster, paymasterInput } = self;
+        state.zk_paymaster = (!paymaster.is_zero())
+            .then(|| ZkPaymasterData { address: *paymaster, input: paymasterInput.clone().into() });
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkRegisterAccount_0Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { account } = *self;
+        state.zk_accounts.insert(account, Default::default());
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkRegisterAccount_1Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { account, signature } = self;
+        state.zk_accounts.insert(*account, signature.clone());
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkMarkBytecodeKnownCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { bytecodeHash } = *self;
+        foundry_zksync_core::cheatcodes::set_bytecode_known(
+            bytecodeHash,
+            true,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkMarkBytecodeUnknownCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { bytecodeHash } = *self;
+        foundry_zksync_core::cheatcodes::set_bytecode_known(
+            bytecodeHash,
+            false,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkIsBytecodeKnownCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { bytecodeHash } = *self;
+        let known = foundry_zksync_core::cheatcodes::is_bytecode_known(
+            bytecodeHash,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(known.abi_encode())
+    }
+}
+
+impl Cheatcode for zkSetL2BlockCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { number, timestamp } = *self;
+        let (number, timestamp) = block_info("L2 block", number, timestamp)?;
+        foundry_zksync_core::cheatcodes::set_l2_block(
+            number,
+            timestamp,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkSetL2BlockHashCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { number, hash } = *self;
+        let number = u64::try_from(number).map_err(|_| fmt_err!("invalid L2 block {number}"))?;
+        foundry_zksync_core::cheatcodes::set_l2_block_hash(
+            number,
+            hash,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkSetBatchInfoCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { number, timestamp } = *self;
+        let (number, timestamp) = block_info("batch", number, timestamp)?;
+        foundry_zksync_core::cheatcodes::set_batch_info(
+            number,
+            timestamp,
+            ccx.data.db,
+            &mut ccx.data.journaled_state,
+        );
+        Ok(Default::default())
+    }
+}
+
+/// Checks the number and timestamp of a block or batch set by a cheatcode, which follow the
+/// stored ones and can't be 0.
+fn block_info(kind: &str, number: U256, timestamp: U256) -> Result<(u64, u64)> {
+    let number = u64::try_from(number).map_err(|_| fmt_err!("invalid {kind} {number}"))?;
+    let timestamp =
+        u64::try_from(timestamp).map_err(|_| fmt_err!("invalid {kind} timestamp {timestamp}"))?;
+    ensure!(number > 0, "{kind} number must be greater than 0");
+    ensure!(timestamp > 0, "{kind} timestamp must be greater than 0");
+    Ok((number, timestamp))
+}
+
+impl Cheatcode for assumeCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { condition } = self;
         if *condition {
             Ok(Default::default())
         } else {
+            // in zk mode only the test contract is executed in the EVM, the program counter of
+            // the other contracts isn't one of their EraVM bytecode
+            let in_evm = !ccx.state.use_zk_vm || ccx.state.zk_test_contract == Some(ccx.caller);
+            ccx.state.assume_rejected_at = Some((ccx.caller, in_evm.then_some(ccx.state.pc)));
             Err(Error::from(MAGIC_ASSUME))
         }
     }
 }
 
+impl Cheatcode for setTraceVerbosityCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        // the verbosity is applied by the tracer of the inspector stack, which records the calls
+        // to this cheatcode
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for breakpoint_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { char } = self;
@@ -103,6 +253,24 @@ impl Cheatcode for rpcUrlStructsCall {
     }
 }
 
+impl Cheatcode for getChain_0Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { chainAlias } = self;
+        let chain = ChainInfo::by_alias(chainAlias)
+            .ok_or_else(|| fmt_err!("unknown chain alias: {chainAlias}"))?;
+        state.config.chain(chain).map(|chain| chain.abi_encode())
+    }
+}
+
+impl Cheatcode for getChain_1Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { chainId } = self;
+        let chain = ChainInfo::by_id(chainId.saturating_to())
+            .ok_or_else(|| fmt_err!("unknown chain ID: {chainId}"))?;
+        state.config.chain(chain).map(|chain| chain.abi_encode())
+    }
+}
+
 impl Cheatcode for sleepCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
         let Self { duration } = self;
@@ -2,6 +2,7 @@ use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
 use alloy_primitives::{address, Address, Bytes, LogData as RawLog, B256, U256};
 use alloy_sol_types::{SolError, SolValue};
 use foundry_cheatcodes_common::expect::{ExpectedCallData, ExpectedCallType};
+use itertools::Itertools;
 use revm::interpreter::{return_ok, InstructionResult};
 use spec::Vm;
 use std::collections::hash_map::Entry;
@@ -59,6 +60,44 @@ pub struct ExpectedEmit {
     pub found: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExpectedStorageWrite {
+    /// The depth at which we expect this write to have occurred
+    pub depth: u64,
+    /// The account whose storage we expect to be written
+    pub address: Address,
+    /// The slot we expect to be written
+    pub slot: B256,
+    /// The value we expect to be written
+    pub value: B256,
+    /// The `(slot, value)` writes to the storage of `address` observed since the expectation
+    pub observed: Vec<(B256, B256)>,
+}
+
+impl ExpectedStorageWrite {
+    /// Returns whether the expected write was observed.
+    pub fn found(&self) -> bool {
+        self.observed.contains(&(self.slot, self.value))
+    }
+
+    /// Returns the failure message of the expectation, listing the writes observed.
+    pub fn failure(&self, reverted: bool) -> String {
+        let Self { address, slot, value, observed, .. } = self;
+        let expected = format!("expected a write of {value} to slot {slot} of {address}");
+        if reverted {
+            return format!(
+                "{expected}, but the call reverted instead; \
+                 ensure you're testing the happy path when using `expectStorageWrite`"
+            )
+        }
+        if observed.is_empty() {
+            return format!("{expected}, but no storage of {address} was written")
+        }
+        let writes = observed.iter().map(|(slot, value)| format!("{slot} = {value}")).join(", ");
+        format!("{expected}, but the writes to {address} were: {writes}")
+    }
+}
+
 impl Cheatcode for expectCall_0Call {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { callee, data } = self;
@@ -198,6 +237,20 @@ impl Cheatcode for expectEmit_3Call {
     }
 }
 
+impl Cheatcode for expectStorageWriteCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { target, slot, value } = *self;
+        ccx.state.expected_storage_writes.push(ExpectedStorageWrite {
+            depth: ccx.data.journaled_state.depth(),
+            address: target,
+            slot,
+            value,
+            observed: Vec::new(),
+        });
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for expectRevert_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
@@ -437,6 +490,37 @@ pub(crate) fn handle_expect_emit(
     }
 }
 
+/// Records a write of `value` to the storage slot `slot` of `address` for the expected storage
+/// writes of that account.
+pub(crate) fn handle_expect_storage_write(
+    state: &mut Cheatcodes,
+    address: Address,
+    slot: U256,
+    value: U256,
+) {
+    for expected in &mut state.expected_storage_writes {
+        if expected.address == address {
+            expected.observed.push((slot.into(), value.into()));
+        }
+    }
+}
+
+/// Checks the expected storage writes declared at `depth`, at the end of a call from it, and
+/// returns the failure message of the first one not observed.
+pub(crate) fn check_expected_storage_writes(
+    state: &mut Cheatcodes,
+    depth: u64,
+    reverted: bool,
+) -> Option<String> {
+    let failure = state
+        .expected_storage_writes
+        .iter()
+        .find(|expected| expected.depth == depth && (reverted || !expected.found()))
+        .map(|expected| expected.failure(reverted));
+    state.expected_storage_writes.retain(|expected| expected.depth != depth);
+    failure
+}
+
 fn expect_revert(
     state: &mut Cheatcodes,
     reason: Option<&[u8]>,
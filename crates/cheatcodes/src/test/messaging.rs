@@ -0,0 +1,615 @@
+//! Relaying of messages between L1 (EVM) and L2 (ZK-VM).
+
+use crate::{Cheatcode, Cheatcodes, Result, Vm::*};
+use alloy_primitives::{address, keccak256, Address, Bytes, B256, U256};
+use alloy_sol_types::{sol, sol_data, SolEvent, SolInterface, SolType, SolValue};
+use foundry_evm_core::backend::DatabaseExt;
+use foundry_zksync_core::{
+    convert::{ConvertH160, ConvertH256},
+    hash_bytecode,
+    proof::{self, L2_LOGS_TREE_HEIGHT},
+};
+use revm::{
+    interpreter::{CallContext, CallInputs, CallScheme, Transfer},
+    primitives::Log,
+    EVMData,
+};
+use std::collections::VecDeque;
+use zksync_types::L1_MESSENGER_ADDRESS;
+
+sol! {
+    struct L2Message {
+        uint16 txNumberInBatch;
+        address sender;
+        bytes data;
+    }
+
+    /// The subset of the zkSync Era `Mailbox` facet handled by the messaging harness.
+    interface IMailbox {
+        function requestL2Transaction(
+            address _contractL2,
+            uint256 _l2Value,
+            bytes calldata _calldata,
+            uint256 _l2GasLimit,
+            uint256 _l2GasPerPubdataByteLimit,
+            bytes[] calldata _factoryDeps,
+            address _refundRecipient
+        ) external payable returns (bytes32 canonicalTxHash);
+
+        function proveL2MessageInclusion(
+            uint256 _batchNumber,
+            uint256 _index,
+            L2Message calldata _message,
+            bytes32[] calldata _proof
+        ) external view returns (bool);
+    }
+
+    /// An L1 -> L2 transaction, whose hash is its canonical hash.
+    struct L2CanonicalTransaction {
+        uint256 txType;
+        uint256 from;
+        uint256 to;
+        uint256 gasLimit;
+        uint256 gasPerPubdataByteLimit;
+        uint256 maxFeePerGas;
+        uint256 maxPriorityFeePerGas;
+        uint256 paymaster;
+        uint256 nonce;
+        uint256 value;
+        uint256[4] reserved;
+        bytes data;
+        bytes signature;
+        uint256[] factoryDeps;
+        bytes paymasterInput;
+        bytes reservedDynamic;
+    }
+
+    /// Emitted by the `L1Messenger` system contract for every message sent to L1.
+    event L1MessageSent(address indexed _sender, bytes32 indexed _hash, bytes _message);
+
+    struct L2Log {
+        uint8 l2ShardId;
+        bool isService;
+        uint16 txNumberInBlock;
+        address sender;
+        bytes32 key;
+        bytes32 value;
+    }
+
+    /// Emitted by the `L1Messenger` system contract for every log sent to L1, including the ones
+    /// of the messages.
+    event L2ToL1LogSent(L2Log _l2log);
+}
+
+/// The offset added to the address of an L1 contract sending a priority request.
+const L1_TO_L2_ALIAS_OFFSET: Address = address!("1111000000000000000000000000000000001111");
+
+/// The transaction type of L1 -> L2 transactions.
+const PRIORITY_OPERATION_L2_TX_TYPE: u64 = 255;
+
+/// The minimal L2 gas price of L1 -> L2 transactions, `FAIR_L2_GAS_PRICE` of the Era contracts.
+const FAIR_L2_GAS_PRICE: u64 = 500_000_000;
+
+/// The L1 gas paid per byte of pubdata, `L1_GAS_PER_PUBDATA_BYTE` of the Era contracts.
+const L1_GAS_PER_PUBDATA_BYTE: u64 = 17;
+
+/// Applies the L1 -> L2 alias to the address of an L1 contract.
+pub fn apply_l1_to_l2_alias(address: Address) -> Address {
+    let aliased = U256::from_be_slice(address.as_slice()) +
+        U256::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice());
+    // only the lower 160 bits are kept, i.e. the addition wraps around
+    Address::from_word(aliased.into())
+}
+
+/// Returns the L2 gas price of a priority request made with the given L1 gas price, like
+/// `_deriveL2GasPrice` of the `Mailbox`.
+fn derive_l2_gas_price(l1_gas_price: U256, gas_per_pubdata: U256) -> U256 {
+    let pubdata_price = U256::from(L1_GAS_PER_PUBDATA_BYTE).saturating_mul(l1_gas_price);
+    let min_l2_gas_price = if gas_per_pubdata.is_zero() {
+        U256::ZERO
+    } else {
+        pubdata_price.div_ceil(gas_per_pubdata)
+    };
+    min_l2_gas_price.max(U256::from(FAIR_L2_GAS_PRICE))
+}
+
+/// Returns the packed L2 -> L1 log sent by the `L1Messenger` for a message.
+fn message_log(tx_number_in_batch: u16, sender: Address, data: &[u8]) -> proof::L2ToL1Log {
+    proof::L2ToL1Log {
+        shard_id: 0,
+        is_service: true,
+        tx_number_in_batch,
+        sender: L1_MESSENGER_ADDRESS.to_address(),
+        key: sender.into_word(),
+        value: keccak256(data),
+    }
+}
+
+impl From<&L2ToL1Log> for proof::L2ToL1Log {
+    fn from(log: &L2ToL1Log) -> Self {
+        Self {
+            shard_id: log.l2ShardId,
+            is_service: log.isService,
+            tx_number_in_batch: log.txNumberInBatch,
+            sender: log.sender,
+            key: log.key,
+            value: log.value,
+        }
+    }
+}
+
+/// An L1 -> L2 transaction requested through the mailbox.
+#[derive(Clone, Debug)]
+pub struct PriorityRequest {
+    /// The L2 sender of the transaction, aliased if the request was made by a contract.
+    pub sender: Address,
+    /// The L2 contract that's called.
+    pub target: Address,
+    /// The value transferred to `target`.
+    pub value: U256,
+    /// The amount of ether minted to `sender` on L2 before executing the transaction.
+    pub mint: U256,
+    /// The calldata.
+    pub data: Bytes,
+    /// The L2 gas limit.
+    pub gas_limit: u64,
+    /// The bytecodes published with the transaction.
+    pub factory_deps: Vec<Bytes>,
+    /// The canonical hash of the transaction.
+    pub tx_hash: B256,
+}
+
+impl PriorityRequest {
+    /// Returns the inputs to execute the request as a call on L2.
+    pub fn call_inputs(&self) -> CallInputs {
+        CallInputs {
+            contract: self.target,
+            transfer: Transfer { source: self.sender, target: self.target, value: self.value },
+            input: self.data.clone(),
+            gas_limit: self.gas_limit,
+            context: CallContext {
+                address: self.target,
+                caller: self.sender,
+                code_address: self.target,
+                apparent_value: self.value,
+                scheme: CallScheme::Call,
+            },
+            is_static: false,
+        }
+    }
+}
+
+/// State of the messaging between L1 and L2, started with `zkStartMessaging` or for every test
+/// with the `zk_messaging` config.
+///
+/// Every ZK-VM execution sending logs to L1 is executed as the only transaction of its batch,
+/// numbered from 1, whose L2 logs tree is the one of the logs it sent.
+#[derive(Clone, Debug)]
+pub struct Messaging {
+    /// The L1 address whose calls are handled as `Mailbox` calls.
+    pub mailbox: Address,
+    /// Priority requests waiting to be executed on L2.
+    pub pending_requests: VecDeque<PriorityRequest>,
+    /// The number of priority requests made so far.
+    pub request_count: u64,
+    /// All messages sent from L2 to L1 so far.
+    pub messages: Vec<L2ToL1Message>,
+    /// All logs sent from L2 to L1 so far.
+    pub logs: Vec<L2ToL1Log>,
+    /// The roots of the L2 logs trees of the batches, by batch number minus one.
+    pub batch_roots: Vec<B256>,
+}
+
+impl Messaging {
+    /// Creates a new messaging harness for the given mailbox address.
+    pub fn new(mailbox: Address) -> Self {
+        Self {
+            mailbox,
+            pending_requests: VecDeque::new(),
+            request_count: 0,
+            messages: vec![],
+            logs: vec![],
+            batch_roots: vec![],
+        }
+    }
+
+    /// Handles an L1 call to the mailbox, returning the ABI encoded return data.
+    pub fn handle_mailbox_call<DB: DatabaseExt>(
+        &mut self,
+        call: &CallInputs,
+        data: &mut EVMData<'_, DB>,
+    ) -> Result {
+        match IMailbox::IMailboxCalls::abi_decode(&call.input, false)? {
+            IMailbox::IMailboxCalls::requestL2Transaction(request) => {
+                ensure!(
+                    call.transfer.value >= request._l2Value,
+                    "msg.value ({}) is lower than the L2 value ({})",
+                    call.transfer.value,
+                    request._l2Value
+                );
+
+                // same as the mailbox, only contracts get their address aliased
+                let caller = call.context.caller;
+                let sender = if caller == data.env.tx.caller {
+                    caller
+                } else {
+                    apply_l1_to_l2_alias(caller)
+                };
+                let refund_recipient = if request._refundRecipient.is_zero() {
+                    sender
+                } else if has_code(data, request._refundRecipient)? {
+                    apply_l1_to_l2_alias(request._refundRecipient)
+                } else {
+                    request._refundRecipient
+                };
+
+                let mut factory_dep_hashes = Vec::with_capacity(request._factoryDeps.len());
+                for dep in &request._factoryDeps {
+                    ensure!(
+                        !dep.is_empty() && dep.len() % 64 == 32,
+                        "invalid factory dep of {} bytes, the bytecode of a contract has an odd \
+                         number of 32 bytes words",
+                        dep.len()
+                    );
+                    factory_dep_hashes.push(U256::from_be_bytes(hash_bytecode(dep).to_b256().0));
+                }
+
+                let data_bytes = Bytes::from(request._calldata);
+                let transaction = L2CanonicalTransaction {
+                    txType: U256::from(PRIORITY_OPERATION_L2_TX_TYPE),
+                    from: U256::from_be_slice(sender.as_slice()),
+                    to: U256::from_be_slice(request._contractL2.as_slice()),
+                    gasLimit: request._l2GasLimit,
+                    gasPerPubdataByteLimit: request._l2GasPerPubdataByteLimit,
+                    maxFeePerGas: derive_l2_gas_price(
+                        data.env.tx.gas_price,
+                        request._l2GasPerPubdataByteLimit,
+                    ),
+                    maxPriorityFeePerGas: U256::ZERO,
+                    paymaster: U256::ZERO,
+                    nonce: U256::from(self.request_count),
+                    value: request._l2Value,
+                    reserved: [
+                        call.transfer.value,
+                        U256::from_be_slice(refund_recipient.as_slice()),
+                        U256::ZERO,
+                        U256::ZERO,
+                    ],
+                    data: data_bytes.clone(),
+                    signature: Bytes::new(),
+                    factoryDeps: factory_dep_hashes,
+                    paymasterInput: Bytes::new(),
+                    reservedDynamic: Bytes::new(),
+                };
+                let tx_hash = keccak256(transaction.abi_encode());
+
+                let request = PriorityRequest {
+                    sender,
+                    target: request._contractL2,
+                    value: request._l2Value,
+                    mint: call.transfer.value,
+                    data: data_bytes,
+                    gas_limit: request._l2GasLimit.saturating_to(),
+                    factory_deps: request._factoryDeps,
+                    tx_hash,
+                };
+                debug!(target: "cheatcodes", ?request, "queued priority request");
+
+                self.request_count += 1;
+                self.pending_requests.push_back(request);
+                Ok(tx_hash.abi_encode())
+            }
+            IMailbox::IMailboxCalls::proveL2MessageInclusion(inclusion) => {
+                let IMailbox::proveL2MessageInclusionCall {
+                    _batchNumber: batch_number,
+                    _index: index,
+                    _message: message,
+                    _proof: path,
+                } = inclusion;
+                let root = usize::try_from(batch_number)
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                    .and_then(|batch| self.batch_roots.get(batch));
+                let Some(root) = root else { bail!("batch {batch_number} is not executed") };
+                ensure!(
+                    !path.is_empty() && path.len() <= L2_LOGS_TREE_HEIGHT,
+                    "invalid Merkle path of {} hashes",
+                    path.len()
+                );
+                let index = match u32::try_from(index) {
+                    Ok(index) if index >> path.len() == 0 => index,
+                    _ => bail!("log index {index} is out of the Merkle path of the tree"),
+                };
+
+                let leaf =
+                    message_log(message.txNumberInBatch, message.sender, &message.data).leaf_hash();
+                Ok((proof::l2_logs_root(leaf, index, &path) == *root).abi_encode())
+            }
+        }
+    }
+
+    /// Records the messages and logs sent to L1 in the given L2 logs of a ZK-VM execution.
+    ///
+    /// The execution is the batch of the logs it sent, the proof of a message is the path of its
+    /// log in the L2 logs tree of the batch.
+    pub fn collect_messages(&mut self, logs: &[Log]) {
+        let messenger = L1_MESSENGER_ADDRESS.to_address();
+        let mut batch_logs = vec![];
+        let mut batch_messages = vec![];
+        for log in logs {
+            if log.address != messenger {
+                continue
+            }
+            match log.topics.first() {
+                Some(topic) if *topic == L2ToL1LogSent::SIGNATURE_HASH => {
+                    let Ok(sent) = <L2Log as SolType>::abi_decode(&log.data, false) else {
+                        continue
+                    };
+                    debug!(
+                        target: "cheatcodes",
+                        sender = ?sent.sender,
+                        key = ?sent.key,
+                        "collected L2 -> L1 log"
+                    );
+                    batch_logs.push(L2ToL1Log {
+                        l2ShardId: sent.l2ShardId,
+                        isService: sent.isService,
+                        txNumberInBatch: sent.txNumberInBlock,
+                        sender: sent.sender,
+                        key: sent.key,
+                        value: sent.value,
+                    });
+                }
+                Some(topic) if *topic == L1MessageSent::SIGNATURE_HASH && log.topics.len() == 3 => {
+                    let Ok(data) = sol_data::Bytes::abi_decode(&log.data, false) else { continue };
+                    let sender = Address::from_word(log.topics[1]);
+                    debug!(
+                        target: "cheatcodes",
+                        ?sender,
+                        data = hex::encode(&data),
+                        "collected L2 -> L1 message"
+                    );
+                    batch_messages.push((sender, data));
+                }
+                _ => {}
+            }
+        }
+        self.push_batch(batch_logs, batch_messages);
+    }
+
+    /// Records a message sent to L1 by `sender`, with the log the `L1Messenger` sends for it, as
+    /// the only log of a new batch.
+    pub fn send_message(&mut self, sender: Address, data: Bytes) {
+        let log = message_log(0, sender, &data);
+        let log = L2ToL1Log {
+            l2ShardId: log.shard_id,
+            isService: log.is_service,
+            txNumberInBatch: log.tx_number_in_batch,
+            sender: log.sender,
+            key: log.key,
+            value: log.value,
+        };
+        self.push_batch(vec![log], vec![(sender, data)]);
+    }
+
+    /// Records the logs and messages sent to L1 by a batch, proving every message with the path
+    /// of its log in the L2 logs tree of the batch.
+    ///
+    /// Nothing is recorded for a batch that sent no logs.
+    fn push_batch(&mut self, logs: Vec<L2ToL1Log>, messages: Vec<(Address, Bytes)>) {
+        if logs.is_empty() {
+            return
+        }
+        if logs.len() > 1 << L2_LOGS_TREE_HEIGHT {
+            warn!(target: "cheatcodes", logs = logs.len(), "too many L2 -> L1 logs in a batch");
+            return
+        }
+
+        let leaves =
+            logs.iter().map(|log| proof::L2ToL1Log::from(log).leaf_hash()).collect::<Vec<_>>();
+        self.batch_roots.push(proof::l2_logs_tree_root(&leaves));
+        let batch_number = U256::from(self.batch_roots.len());
+
+        // the log of a message is sent before the message, the n-th message of a sender with
+        // some data is the one of its n-th log
+        let mut claimed = vec![false; logs.len()];
+        for (sender, data) in messages {
+            let position = logs.iter().zip(&claimed).position(|(log, claimed)| {
+                !claimed &&
+                    proof::L2ToL1Log::from(log) ==
+                        message_log(log.txNumberInBatch, sender, &data)
+            });
+            let Some(index) = position else {
+                warn!(target: "cheatcodes", ?sender, "no L2 -> L1 log for the message");
+                continue
+            };
+            claimed[index] = true;
+            self.messages.push(L2ToL1Message {
+                sender,
+                data,
+                batchNumber: batch_number,
+                index: U256::from(index),
+                txNumberInBatch: logs[index].txNumberInBatch,
+                proof: proof::l2_logs_proof(&leaves, index),
+            });
+        }
+        self.logs.extend(logs);
+    }
+}
+
+/// Returns whether `address` has code on L1.
+fn has_code<DB: DatabaseExt>(data: &mut EVMData<'_, DB>, address: Address) -> Result<bool> {
+    let (account, _) = data.journaled_state.load_code(address, data.db)?;
+    Ok(account.info.code.as_ref().is_some_and(|code| !code.is_empty()))
+}
+
+impl Cheatcode for zkStartMessagingCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { mailbox } = *self;
+        state.messaging = Some(Messaging::new(mailbox));
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkStopMessagingCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        state.messaging = None;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkGetL2ToL1MessagesCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        let messages = state.messaging.as_ref().map(|m| m.messages.clone()).unwrap_or_default();
+        Ok(messages.abi_encode())
+    }
+}
+
+impl Cheatcode for zkGetL2ToL1LogsCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        let logs = state.messaging.as_ref().map(|m| m.logs.clone()).unwrap_or_default();
+        Ok(logs.abi_encode())
+    }
+}
+
+impl Cheatcode for zkSendL1MessageCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { sender, data } = self;
+        let Some(messaging) = &mut state.messaging else {
+            bail!("`zkSendL1Message` requires messaging, start it with `zkStartMessaging`")
+        };
+        messaging.send_message(*sender, data.clone());
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkRelayPriorityRequestsCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        // executed on the ZK-VM by the inspector, before cheatcodes are dispatched
+        bail!("`zkRelayPriorityRequests` must be executed by the cheatcode inspector")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn l1_to_l2_alias() {
+        assert_eq!(
+            apply_l1_to_l2_alias(address!("0000000000000000000000000000000000000001")),
+            address!("1111000000000000000000000000000000001112")
+        );
+        // wraps around
+        assert_eq!(
+            apply_l1_to_l2_alias(address!("ffffffffffffffffffffffffffffffffffffffff")),
+            address!("1111000000000000000000000000000000001110")
+        );
+    }
+
+    #[test]
+    fn collects_l2_to_l1_logs() {
+        let messenger = L1_MESSENGER_ADDRESS.to_address();
+        let sender = address!("0000000000000000000000000000000000001234");
+        let mut messaging = Messaging::new(Address::ZERO);
+        let log = L2Log {
+            l2ShardId: 0,
+            isService: false,
+            txNumberInBlock: 0,
+            sender,
+            key: B256::with_last_byte(1),
+            value: B256::with_last_byte(2),
+        };
+        messaging.collect_messages(&[
+            Log {
+                address: messenger,
+                topics: vec![L2ToL1LogSent::SIGNATURE_HASH],
+                data: log.abi_encode().into(),
+            },
+            Log {
+                address: sender,
+                topics: vec![L2ToL1LogSent::SIGNATURE_HASH],
+                data: log.abi_encode().into(),
+            },
+        ]);
+        assert_eq!(messaging.logs.len(), 1);
+        assert_eq!(messaging.logs[0].sender, sender);
+        assert!(messaging.messages.is_empty());
+
+        messaging.send_message(sender, Bytes::from_static(b"hello L1"));
+        assert_eq!(messaging.messages.len(), 1);
+        assert_eq!(messaging.logs[1].sender, messenger);
+        assert_eq!(messaging.logs[1].key, sender.into_word());
+        assert_eq!(messaging.logs[1].value, keccak256(b"hello L1"));
+        assert_eq!(messaging.batch_roots.len(), 2);
+    }
+
+    #[test]
+    fn proves_l2_to_l1_messages() {
+        let sender = address!("0000000000000000000000000000000000001234");
+        let mut messaging = Messaging::new(Address::ZERO);
+        let logs = [b"first".as_slice(), b"second"].map(|data| {
+            let log = message_log(0, sender, data);
+            Log {
+                address: L1_MESSENGER_ADDRESS.to_address(),
+                topics: vec![L2ToL1LogSent::SIGNATURE_HASH],
+                data: L2Log {
+                    l2ShardId: log.shard_id,
+                    isService: log.is_service,
+                    txNumberInBlock: log.tx_number_in_batch,
+                    sender: log.sender,
+                    key: log.key,
+                    value: log.value,
+                }
+                .abi_encode()
+                .into(),
+            }
+        });
+        let messages = [b"first".as_slice(), b"second"].map(|data| Log {
+            address: L1_MESSENGER_ADDRESS.to_address(),
+            topics: vec![L1MessageSent::SIGNATURE_HASH, sender.into_word(), keccak256(data)],
+            data: Bytes::copy_from_slice(data).abi_encode().into(),
+        });
+        messaging.collect_messages(&[
+            logs[0].clone(),
+            messages[0].clone(),
+            logs[1].clone(),
+            messages[1].clone(),
+        ]);
+        messaging.send_message(sender, Bytes::from_static(b"third"));
+
+        assert_eq!(messaging.batch_roots.len(), 2);
+        assert_eq!(messaging.messages.len(), 3);
+        for (message, (batch, index)) in messaging.messages.iter().zip([(1, 0), (1, 1), (2, 0)]) {
+            assert_eq!(message.batchNumber, U256::from(batch));
+            assert_eq!(message.index, U256::from(index));
+            let leaf = message_log(0, sender, &message.data).leaf_hash();
+            assert_eq!(
+                proof::l2_logs_root(leaf, index, &message.proof),
+                messaging.batch_roots[batch - 1]
+            );
+        }
+
+        // no batch for executions that sent nothing
+        messaging.collect_messages(&[]);
+        assert_eq!(messaging.batch_roots.len(), 2);
+    }
+
+    #[test]
+    fn derives_l2_gas_price() {
+        let gas_per_pubdata = U256::from(800);
+        assert_eq!(derive_l2_gas_price(U256::ZERO, gas_per_pubdata), U256::from(FAIR_L2_GAS_PRICE));
+        // 17 * 100 gwei / 800, rounded up
+        assert_eq!(
+            derive_l2_gas_price(U256::from(100_000_000_000u64), gas_per_pubdata),
+            U256::from(2_125_000_000u64)
+        );
+        assert_eq!(
+            derive_l2_gas_price(U256::from(100), U256::from(3)),
+            U256::from(FAIR_L2_GAS_PRICE)
+        );
+    }
+}
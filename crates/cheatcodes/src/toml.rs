@@ -0,0 +1,98 @@
+//! Implementations of [`Toml`](crate::Group::Toml) cheatcodes.
+
+use crate::{json::canonicalize_json_path, Cheatcode, Cheatcodes, Result, Vm::*};
+use foundry_common::fs;
+use foundry_config::fs_permissions::FsAccessKind;
+use serde_json::Value as JsonValue;
+use toml::Value as TomlValue;
+
+impl Cheatcode for writeToml_0Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { json, path } = self;
+        let json = parse_json_object(json)?;
+        let toml_string = toml::to_string_pretty(&json_to_toml(&json, "$")?)?;
+        super::fs::write_file(state, path.as_ref(), toml_string.as_bytes())
+    }
+}
+
+impl Cheatcode for writeToml_1Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { json, path, valueKey } = self;
+        let json =
+            serde_json::from_str(json).unwrap_or_else(|_| JsonValue::String(json.to_owned()));
+
+        let data_path = state.config.ensure_path_allowed(path, FsAccessKind::Read)?;
+        let data_s = fs::read_to_string(data_path)?;
+        let data = data_s
+            .parse::<TomlValue>()
+            .map_err(|e| fmt_err!("failed parsing TOML file {path:?}: {e}"))?;
+        let value = jsonpath_lib::replace_with(
+            toml_to_json(data),
+            &canonicalize_json_path(valueKey),
+            &mut |_| Some(json.clone()),
+        )?;
+
+        let toml_string = toml::to_string_pretty(&json_to_toml(&value, "$")?)?;
+        super::fs::write_file(state, path.as_ref(), toml_string.as_bytes())
+    }
+}
+
+fn parse_json_object(json: &str) -> Result<JsonValue> {
+    let value: JsonValue =
+        serde_json::from_str(json).map_err(|e| fmt_err!("failed parsing JSON: {e}"))?;
+    ensure!(value.is_object(), "only JSON objects can be written as TOML documents");
+    Ok(value)
+}
+
+/// Converts a JSON [`JsonValue`] to a [`TomlValue`].
+///
+/// Integers that don't fit in TOML's 64-bit integers, like most `uint256` values, are written as
+/// strings, which the coercing `parseJson*` cheatcodes accept when reading them back.
+fn json_to_toml(value: &JsonValue, path: &str) -> Result<TomlValue> {
+    Ok(match value {
+        JsonValue::Null => bail!("TOML has no null value, found null at {path:?}"),
+        JsonValue::Bool(b) => TomlValue::Boolean(*b),
+        JsonValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                TomlValue::Integer(i)
+            } else if !n.to_string().contains(['.', 'e', 'E']) {
+                TomlValue::String(n.to_string())
+            } else if let Some(f) = n.as_f64() {
+                TomlValue::Float(f)
+            } else {
+                bail!("unsupported number {n} at {path:?}")
+            }
+        }
+        JsonValue::String(s) => TomlValue::String(s.clone()),
+        JsonValue::Array(array) => TomlValue::Array(
+            array
+                .iter()
+                .enumerate()
+                .map(|(i, value)| json_to_toml(value, &format!("{path}[{i}]")))
+                .collect::<Result<_>>()?,
+        ),
+        JsonValue::Object(object) => TomlValue::Table(
+            object
+                .iter()
+                .map(|(key, value)| {
+                    Ok((key.clone(), json_to_toml(value, &format!("{path}.{key}"))?))
+                })
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+/// Converts a [`TomlValue`] to a [`JsonValue`], writing datetimes as strings.
+fn toml_to_json(value: TomlValue) -> JsonValue {
+    match value {
+        TomlValue::String(s) => JsonValue::String(s),
+        TomlValue::Integer(i) => i.into(),
+        TomlValue::Float(f) => f.into(),
+        TomlValue::Boolean(b) => JsonValue::Bool(b),
+        TomlValue::Datetime(d) => JsonValue::String(d.to_string()),
+        TomlValue::Array(array) => JsonValue::Array(array.into_iter().map(toml_to_json).collect()),
+        TomlValue::Table(table) => JsonValue::Object(
+            table.into_iter().map(|(key, value)| (key, toml_to_json(value))).collect(),
+        ),
+    }
+}
@@ -1,7 +1,7 @@
 //! Implementations of [`Utils`](crate::Group::Utils) cheatcodes.
 
 use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
-use alloy_primitives::{keccak256, B256, U256};
+use alloy_primitives::{keccak256, Address, B256, KECCAK256_EMPTY, U256};
 use alloy_signer::{
     coins_bip39::{
         ChineseSimplified, ChineseTraditional, Czech, English, French, Italian, Japanese, Korean,
@@ -11,6 +11,7 @@ use alloy_signer::{
 };
 use alloy_sol_types::SolValue;
 use foundry_evm_core::constants::DEFAULT_CREATE2_DEPLOYER;
+use foundry_zksync_core::convert::ConvertH256;
 use k256::{
     ecdsa::SigningKey,
     elliptic_curve::{sec1::ToEncodedPoint, Curve},
@@ -114,26 +115,77 @@ impl Cheatcode for getLabelCall {
     }
 }
 
+impl Cheatcode for getFixtureCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { fixture } = self;
+        match state.config.fixtures.get(fixture) {
+            Some(address) => Ok(address.abi_encode()),
+            None => bail!("the test suite doesn't depend on the fixture {fixture}"),
+        }
+    }
+}
+
 impl Cheatcode for computeCreateAddressCall {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { nonce, deployer } = self;
+        if state.use_zk_vm {
+            // the nonce is the deployment nonce of the deployer in EraVM
+            return Ok(foundry_zksync_core::compute_create_address(*deployer, *nonce).abi_encode())
+        }
         ensure!(*nonce <= U256::from(u64::MAX), "nonce must be less than 2^64 - 1");
         Ok(deployer.create(nonce.to()).abi_encode())
     }
 }
 
 impl Cheatcode for computeCreate2Address_0Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { salt, initCodeHash, deployer } = self;
-        Ok(deployer.create2(salt, initCodeHash).abi_encode())
+        Ok(compute_create2_address(state, *deployer, *salt, *initCodeHash)?.abi_encode())
     }
 }
 
 impl Cheatcode for computeCreate2Address_1Call {
-    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { salt, initCodeHash } = self;
-        Ok(DEFAULT_CREATE2_DEPLOYER.create2(salt, initCodeHash).abi_encode())
+        Ok(compute_create2_address(state, DEFAULT_CREATE2_DEPLOYER, *salt, *initCodeHash)?
+            .abi_encode())
+    }
+}
+
+/// Computes the address of a contract created by `deployer` with CREATE2.
+///
+/// In zk mode the address is derived as in EraVM, from the zksolc bytecode hash of the contract
+/// and the hash of its constructor input. `init_code_hash` is then either the hash of the solc
+/// init code of a dual compiled contract without constructor arguments, which is mapped to its
+/// zksolc bytecode hash, or the zksolc bytecode hash of a dual compiled contract. The constructor
+/// arguments can't be recovered from the hash of an init code including them, so any other hash
+/// is an error.
+fn compute_create2_address(
+    state: &Cheatcodes,
+    deployer: Address,
+    salt: B256,
+    init_code_hash: B256,
+) -> Result<Address> {
+    if !state.use_zk_vm {
+        return Ok(deployer.create2(salt, init_code_hash))
     }
+
+    let bytecode_hash = state
+        .dual_compiled_contracts
+        .iter()
+        .map(|contract| (keccak256(&contract.evm_bytecode), contract.zk_bytecode_hash.to_b256()))
+        .find(|(evm_hash, zk_hash)| *evm_hash == init_code_hash || *zk_hash == init_code_hash)
+        .map(|(_, zk_hash)| zk_hash)
+        .ok_or_else(|| {
+            fmt_err!(
+                "{init_code_hash} is neither the hash of the init code of a contract without \
+                 constructor arguments nor the zksolc bytecode hash of a contract, the Era address \
+                 of a deployment with constructor arguments can't be derived from the hash of its \
+                 init code"
+            )
+        })?;
+    // the constructor input of the contract is empty
+    Ok(foundry_zksync_core::compute_create2_address(deployer, bytecode_hash, salt, KECCAK256_EMPTY))
 }
 
 /// Using a given private key, return its public ETH address, its public key affine x and y
@@ -108,7 +108,8 @@ async fn main() -> eyre::Result<()> {
     let mut interrupt = false;
 
     // Load configuration
-    let (config, evm_opts) = args.load_config_and_evm_opts()?;
+    let (config, mut evm_opts) = args.load_config_and_evm_opts()?;
+    utils::pin_config_fork_block(&config, &mut evm_opts, false).await?;
 
     // Create a new cli dispatcher
     let mut dispatcher = ChiselDispatcher::new(chisel::session_source::SessionSourceConfig {
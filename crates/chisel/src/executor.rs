@@ -309,7 +309,6 @@ impl SessionSource {
                         &self.config.foundry_config,
                         self.config.evm_opts.clone(),
                         None,
-                        Default::default(),
                         false,
                     )
                     .into(),
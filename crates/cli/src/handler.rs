@@ -1,4 +1,5 @@
 use eyre::EyreHandler;
+use foundry_common::redact::redact;
 use std::error::Error;
 use yansi::Paint;
 
@@ -16,7 +17,7 @@ impl EyreHandler for Handler {
             return core::fmt::Debug::fmt(error, f)
         }
         writeln!(f)?;
-        write!(f, "{}", Paint::red(error))?;
+        write!(f, "{}", Paint::red(redact(&error.to_string())))?;
 
         if let Some(cause) = error.source() {
             write!(f, "\n\nContext:")?;
@@ -25,6 +26,7 @@ impl EyreHandler for Handler {
             let errors = std::iter::successors(Some(cause), |e| (*e).source());
 
             for (n, error) in errors.enumerate() {
+                let error = redact(&error.to_string());
                 writeln!(f)?;
                 if multiple {
                     write!(f, "- Error #{n}: {error}")?;
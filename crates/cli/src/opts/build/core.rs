@@ -236,6 +236,30 @@ impl Provider for CoreBuildArgs {
             dict.insert("extra_output_files".to_string(), selection.into());
         }
 
+        // the zksolc settings override the `zksync_settings` section of the config
+        let mut zksync_settings = Dict::new();
+        if self.compiler.zk_optimizer {
+            zksync_settings.insert("optimizer".to_string(), true.into());
+        }
+        if let Some(ref mode) = self.compiler.mode {
+            zksync_settings.insert("optimizer_mode".to_string(), mode.clone().into());
+        }
+        if let Some(system_mode) = self.compiler.is_system {
+            zksync_settings.insert("system_mode".to_string(), system_mode.into());
+        }
+        if let Some(force_evmla) = self.compiler.force_evmla {
+            zksync_settings.insert("force_evmla".to_string(), force_evmla.into());
+        }
+        if let Some(fallback_oz) = self.compiler.fallback_oz {
+            zksync_settings.insert("fallback_oz".to_string(), fallback_oz.into());
+        }
+        if self.compiler.detect_missing_libraries {
+            zksync_settings.insert("detect_missing_libraries".to_string(), true.into());
+        }
+        if !zksync_settings.is_empty() {
+            dict.insert("zksync_settings".to_string(), zksync_settings.into());
+        }
+
         if let Some(ref revert) = self.revert_strings {
             dict.insert("revert_strings".to_string(), revert.to_string().into());
         }
@@ -63,7 +63,7 @@ pub struct CompilerArgs {
         long = "is-system",
         value_name = "SYSTEM_MODE"
     )]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip)]
     pub is_system: Option<bool>,
 
     /// A flag indicating whether to forcibly switch to the EVM legacy assembly pipeline.
@@ -73,7 +73,7 @@ pub struct CompilerArgs {
         long = "force-evmla",
         value_name = "FORCE_EVMLA"
     )]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip)]
     pub force_evmla: Option<bool>,
 
     /// Try to recompile with -Oz if the bytecode is too large.
@@ -82,11 +82,13 @@ pub struct CompilerArgs {
         long = "fallback-oz",
         value_name = "FALLBACK_OZ"
     )]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip)]
     pub fallback_oz: Option<bool>,
 
-    /// Path to cache missing library dependencies, used for compiling and deploying libraries.
+    /// Only detect the libraries the contracts link that aren't deployed, without producing
+    /// bytecode.
     #[clap(help_heading = "zkSync Compiler options", long = "detect-missing-libraries")]
+    #[serde(skip)]
     pub detect_missing_libraries: bool,
 
     /// Set the LLVM optimization parameter `-O[0 | 1 | 2 | 3 | s | z]`.
@@ -97,7 +99,7 @@ pub struct CompilerArgs {
         long = "optimization",
         value_name = "LEVEL"
     )]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip)]
     pub mode: Option<String>,
 
     /// Enables optimizations
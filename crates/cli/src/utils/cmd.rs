@@ -12,6 +12,7 @@ use foundry_compilers::{
 use foundry_config::{error::ExtractConfigError, figment::Figment, Chain, Config, NamedChain};
 use foundry_debugger::Debugger;
 use foundry_evm::{
+    backend::ForkType,
     debug::DebugArena,
     executors::{DeployResult, EvmError, ExecutionErr, RawCallResult},
     opts::EvmOpts,
@@ -20,7 +21,7 @@ use foundry_evm::{
         render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
     },
 };
-use std::{fmt::Write, path::PathBuf, str::FromStr};
+use std::{collections::HashMap, fmt::Write, path::PathBuf, str::FromStr};
 use yansi::Paint;
 
 /// Given a `Project`'s output, removes the matching ABI, Bytecode and
@@ -30,24 +31,45 @@ pub fn remove_contract(
     output: &mut ProjectCompileOutput,
     info: &ContractInfo,
 ) -> Result<(JsonAbi, CompactBytecode, CompactDeployedBytecode)> {
-    let contract = if let Some(contract) = output.remove_contract(info) {
-        contract
-    } else {
-        let mut err = format!("could not find artifact: `{}`", info.name);
-        if let Some(suggestion) =
-            super::did_you_mean(&info.name, output.artifacts().map(|(name, _)| name)).pop()
-        {
-            if suggestion != info.name {
-                err = format!(
-                    r#"{err}
+    let Some(contract) = output.remove_contract(info) else {
+        return Err(missing_contract(output, info))
+    };
+    contract_parts(&contract, info)
+}
+
+/// Given a `Project`'s output, returns the matching ABI, Bytecode and Runtime Bytecode of the
+/// given contract, leaving the output untouched.
+#[track_caller]
+pub fn find_contract(
+    output: &ProjectCompileOutput,
+    info: &ContractInfo,
+) -> Result<(JsonAbi, CompactBytecode, CompactDeployedBytecode)> {
+    let Some(contract) = output.find_contract(info) else {
+        return Err(missing_contract(output, info))
+    };
+    contract_parts(contract, info)
+}
+
+fn missing_contract(output: &ProjectCompileOutput, info: &ContractInfo) -> eyre::Report {
+    let mut err = format!("could not find artifact: `{}`", info.name);
+    if let Some(suggestion) =
+        super::did_you_mean(&info.name, output.artifacts().map(|(name, _)| name)).pop()
+    {
+        if suggestion != info.name {
+            err = format!(
+                r#"{err}
 
         Did you mean `{suggestion}`?"#
-                );
-            }
+            );
         }
-        eyre::bail!(err)
-    };
+    }
+    eyre::eyre!(err)
+}
 
+fn contract_parts(
+    contract: &impl Artifact,
+    info: &ContractInfo,
+) -> Result<(JsonAbi, CompactBytecode, CompactDeployedBytecode)> {
     let abi = contract
         .get_abi()
         .ok_or_else(|| eyre::eyre!("contract {} does not contain abi", info))?
@@ -180,6 +202,27 @@ pub fn has_different_gas_calc(chain_id: u64) -> bool {
     false
 }
 
+/// Enables `zksync` in `config` if `fork_url` is the RPC of a zkSync Era chain, whose state is
+/// only executable in EraVM. Returns whether it was enabled.
+pub async fn detect_zksync_fork(config: &mut Config, fork_url: Option<&str>) -> bool {
+    let (false, Some(fork_url)) = (config.zksync, fork_url) else { return false };
+    config.zksync = ForkType::detect(fork_url).await.is_zk();
+    config.zksync
+}
+
+/// Fails if the RPC of `config` is the one of a zkSync Era chain, whose contracts are EraVM
+/// bytecode which the EVM executor of cast can't execute.
+pub async fn ensure_evm_fork(config: &Config) -> Result<()> {
+    let fork_url = config.get_rpc_url_or_localhost_http()?;
+    if ForkType::detect(&fork_url).await.is_zk() {
+        eyre::bail!(
+            "the RPC is the one of a zkSync Era chain, whose contracts only execute in EraVM and \
+             can't be traced locally"
+        )
+    }
+    Ok(())
+}
+
 /// True if it supports broadcasting in batches.
 pub fn has_batch_support(chain_id: u64) -> bool {
     if let Some(chain) = Chain::from(chain_id).named() {
@@ -322,17 +365,20 @@ pub struct TraceResult {
     pub traces: Traces,
     pub debug: DebugArena,
     pub gas_used: u64,
+    /// The EIP-1967 proxies called during the execution, mapped to their implementation
+    pub proxies: HashMap<Address, Address>,
 }
 
 impl From<RawCallResult> for TraceResult {
     fn from(result: RawCallResult) -> Self {
-        let RawCallResult { gas_used, traces, reverted, debug, .. } = result;
+        let RawCallResult { gas_used, traces, reverted, debug, proxies, .. } = result;
 
         Self {
             success: !reverted,
             traces: vec![(TraceKind::Execution, traces.expect("traces is None"))],
             debug: debug.unwrap_or_default(),
             gas_used,
+            proxies,
         }
     }
 }
@@ -346,6 +392,7 @@ impl From<DeployResult> for TraceResult {
             traces: vec![(TraceKind::Execution, traces.expect("traces is None"))],
             debug: debug.unwrap_or_default(),
             gas_used,
+            proxies: Default::default(),
         }
     }
 }
@@ -356,12 +403,14 @@ impl TryFrom<EvmError> for TraceResult {
     fn try_from(err: EvmError) -> Result<Self, Self::Error> {
         match err {
             EvmError::Execution(err) => {
-                let ExecutionErr { reverted, gas_used, traces, debug: run_debug, .. } = *err;
+                let ExecutionErr { reverted, gas_used, traces, debug: run_debug, proxies, .. } =
+                    *err;
                 Ok(TraceResult {
                     success: !reverted,
                     traces: vec![(TraceKind::Execution, traces.expect("traces is None"))],
                     debug: run_debug.unwrap_or_default(),
                     gas_used,
+                    proxies,
                 })
             }
             _ => Err(err),
@@ -390,6 +439,7 @@ pub async fn handle_traces(
     let config_labels = config.labels.clone().into_iter();
     let mut decoder = CallTraceDecoderBuilder::new()
         .with_labels(labels.chain(config_labels))
+        .with_proxies(result.proxies.clone())
         .with_signature_identifier(SignaturesIdentifier::new(
             Config::foundry_cache_dir(),
             config.offline,
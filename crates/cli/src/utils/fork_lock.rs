@@ -0,0 +1,161 @@
+//! The fork lockfile, pinning the tag of `fork_block_number` to the block it first resolved to.
+//!
+//! Tags like `latest-safe` resolve to a different block on every run. The lockfile records the
+//! block they resolved to once, per chain, so that the forked tests of a project run against the
+//! same state until the pin is refreshed with `forge test --refresh-fork-lock`.
+//!
+//! The commands forking from the config, like `forge test`, `forge script` and `cast call
+//! --trace`, pin the tag with [`pin_config_fork_block`].
+
+use alloy_primitives::B256;
+use ethers_core::types::BlockNumber;
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::{fs, provider::ethers::try_get_http_provider, shell, types::ToAlloy};
+use foundry_config::{Config, ForkBlock};
+use foundry_evm::opts::EvmOpts;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The name of the fork lockfile, in the root of the project.
+pub const FORK_LOCK_FILE: &str = "fork.lock";
+
+/// The blocks pinned by the fork lockfile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkLock {
+    /// The pinned blocks, by `<chain ID>:<tag>`.
+    pub pins: BTreeMap<String, ForkPin>,
+}
+
+/// A block a tag resolved to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkPin {
+    /// The block number.
+    pub block: u64,
+    /// The block hash.
+    pub hash: B256,
+    /// The L1 batch of the block, on zkSync Era chains.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch: Option<u64>,
+}
+
+impl ForkLock {
+    /// Reads the lockfile at `path`, which is empty if it doesn't exist.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default())
+        }
+        Ok(fs::read_json_file(path)?)
+    }
+
+    /// Writes the lockfile to `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        Ok(fs::write_json_file(path, self)?)
+    }
+}
+
+/// The details of an Era block, of which only the L1 batch is used.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockDetails {
+    l1_batch_number: u64,
+}
+
+/// Sets the fork block of `evm_opts` to the block the `fork_block_number` tag of `config` is
+/// pinned to, unless a block number was given, e.g. with `--fork-block-number`.
+///
+/// [`EvmOpts`] only holds block numbers, a tag of the config is `None` there until it's pinned.
+pub async fn pin_config_fork_block(
+    config: &Config,
+    evm_opts: &mut EvmOpts,
+    refresh: bool,
+) -> Result<()> {
+    let (Some(block), None) = (config.fork_block_number, evm_opts.fork_block_number) else {
+        return Ok(())
+    };
+    let fork_url = match &evm_opts.fork_url {
+        Some(fork_url) => fork_url.clone(),
+        None => match config.get_rpc_url() {
+            Some(fork_url) => fork_url?.into_owned(),
+            None => return Ok(()),
+        },
+    };
+    evm_opts.fork_block_number =
+        Some(pin_fork_block(&config.root.0, &fork_url, block, refresh).await?);
+    Ok(())
+}
+
+/// Returns the block `block` is pinned to on the chain of `fork_url`, resolving and recording it
+/// in the lockfile of the project at `root` if it's not pinned yet or `refresh` is set.
+pub async fn pin_fork_block(
+    root: &Path,
+    fork_url: &str,
+    block: ForkBlock,
+    refresh: bool,
+) -> Result<u64> {
+    let tag = match block {
+        ForkBlock::Number(number) => return Ok(number),
+        ForkBlock::Latest => BlockNumber::Latest,
+        ForkBlock::Safe => BlockNumber::Safe,
+        ForkBlock::Finalized => BlockNumber::Finalized,
+    };
+
+    let provider = try_get_http_provider(fork_url)?;
+    let chain_id = provider.get_chainid().await.wrap_err("failed to get the fork chain ID")?;
+    let key = format!("{chain_id}:{block}");
+
+    let path = root.join(FORK_LOCK_FILE);
+    let mut lock = ForkLock::read(&path)
+        .wrap_err_with(|| format!("failed to read the fork lockfile {}", path.display()))?;
+    if !refresh {
+        if let Some(pin) = lock.pins.get(&key) {
+            return Ok(pin.block)
+        }
+    }
+
+    let resolved = provider
+        .get_block(tag)
+        .await?
+        .ok_or_else(|| eyre::eyre!("the fork has no `{block}` block"))?;
+    let (Some(number), Some(hash)) = (resolved.number, resolved.hash) else {
+        eyre::bail!("the `{block}` block of the fork is pending")
+    };
+    let number = number.as_u64();
+    // Era chains also report the L1 batch of the block, other chains don't support the method.
+    let l1_batch = provider
+        .request::<_, Option<BlockDetails>>("zks_getBlockDetails", [number])
+        .await
+        .ok()
+        .flatten()
+        .map(|details| details.l1_batch_number);
+
+    let pin = ForkPin { block: number, hash: hash.to_alloy(), l1_batch };
+    let batch = pin.l1_batch.map(|batch| format!(" (L1 batch {batch})")).unwrap_or_default();
+    shell::println(format!(
+        "Pinned the `{block}` fork block of chain {chain_id} to {number}{batch} in {FORK_LOCK_FILE}"
+    ))?;
+    lock.pins.insert(key, pin);
+    lock.write(&path)?;
+    Ok(number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_fork_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FORK_LOCK_FILE);
+        assert_eq!(ForkLock::read(&path).unwrap(), ForkLock::default());
+
+        let mut lock = ForkLock::default();
+        lock.pins.insert(
+            "324:latest-safe".to_string(),
+            ForkPin { block: 19579636, hash: B256::repeat_byte(1), l1_batch: Some(400000) },
+        );
+        lock.write(&path).unwrap();
+        assert_eq!(ForkLock::read(&path).unwrap(), lock);
+    }
+}
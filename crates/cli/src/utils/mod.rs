@@ -20,6 +20,9 @@ use yansi::Paint;
 mod cmd;
 pub use cmd::*;
 
+mod fork_lock;
+pub use fork_lock::*;
+
 mod suggestions;
 pub use suggestions::*;
 
@@ -0,0 +1,97 @@
+//! Helpers for comparing bytecode regardless of its metadata.
+//!
+//! Both solc and zksolc append a hash of the contract's metadata to the bytecode, which includes
+//! e.g. the source paths, so otherwise identical contracts compiled on different machines have
+//! different bytecode.
+
+use alloy_primitives::{keccak256, B256};
+
+/// The size of an EraVM word.
+const ERAVM_WORD_SIZE: usize = 32;
+
+/// Returns `code` without the CBOR encoded metadata solc appends to it, if any.
+///
+/// The metadata is followed by its length as a big-endian `u16`, see
+/// <https://docs.soliditylang.org/en/latest/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode>.
+pub fn strip_evm_metadata(code: &[u8]) -> &[u8] {
+    let Some((len, code_with_metadata)) = code
+        .len()
+        .checked_sub(2)
+        .map(|end| (u16::from_be_bytes([code[end], code[end + 1]]) as usize, &code[..end]))
+    else {
+        return code
+    };
+    let Some(start) = code_with_metadata.len().checked_sub(len) else { return code };
+    // the metadata is a CBOR map, its first byte is the major type 5 with the number of entries
+    match code_with_metadata.get(start) {
+        Some(0xa1..=0xb7) => &code_with_metadata[..start],
+        _ => code,
+    }
+}
+
+/// Returns EraVM `code` without the keccak256 metadata hash zksolc appends to it.
+///
+/// The hash is the last word of the bytecode, which may be preceded by a zero word that keeps the
+/// number of words odd. Unlike solc's metadata this can't be detected, so `code` must have been
+/// compiled with a metadata hash.
+pub fn strip_eravm_metadata(code: &[u8]) -> &[u8] {
+    let Some(end) = code.len().checked_sub(ERAVM_WORD_SIZE) else { return code };
+    let code = &code[..end];
+    match code.len().checked_sub(ERAVM_WORD_SIZE) {
+        Some(end) if code[end..].iter().all(|byte| *byte == 0) => &code[..end],
+        _ => code,
+    }
+}
+
+/// Returns the keccak256 hash of `code` without its metadata, which is the same for all
+/// compilations of a contract that only differ in their metadata.
+///
+/// `eravm` selects whether `code` is EraVM bytecode compiled with a metadata hash, or EVM
+/// bytecode.
+pub fn runtime_equivalent_hash(code: &[u8], eravm: bool) -> B256 {
+    if eravm {
+        keccak256(strip_eravm_metadata(code))
+    } else {
+        keccak256(strip_evm_metadata(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    #[test]
+    fn strips_evm_metadata() {
+        // `{"solc": 0x000814}`
+        let metadata = hex!("a164736f6c6343000814");
+        let len = (metadata.len() as u16).to_be_bytes();
+        let code = [&hex!("6080604052")[..], &metadata, &len].concat();
+        assert_eq!(strip_evm_metadata(&code), hex!("6080604052"));
+
+        // no metadata
+        let code = hex!("6080604052600080fd");
+        assert_eq!(strip_evm_metadata(&code), code);
+        assert_eq!(strip_evm_metadata(&[]), &[] as &[u8]);
+        assert_eq!(strip_evm_metadata(&[0xff, 0xff]), [0xff, 0xff]);
+    }
+
+    #[test]
+    fn strips_eravm_metadata() {
+        let instruction = [1u8; ERAVM_WORD_SIZE];
+        let hash = [2u8; ERAVM_WORD_SIZE];
+        let padding = [0u8; ERAVM_WORD_SIZE];
+
+        let code = [instruction, instruction, hash].concat();
+        assert_eq!(strip_eravm_metadata(&code), [instruction, instruction].concat());
+
+        let code = [instruction, padding, hash].concat();
+        assert_eq!(strip_eravm_metadata(&code), instruction);
+
+        let with_other_hash = [instruction, padding, [3u8; ERAVM_WORD_SIZE]].concat();
+        assert_eq!(
+            runtime_equivalent_hash(&code, true),
+            runtime_equivalent_hash(&with_other_hash, true)
+        );
+    }
+}
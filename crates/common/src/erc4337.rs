@@ -0,0 +1,284 @@
+//! Helpers for building [ERC-4337](https://eips.ethereum.org/EIPS/eip-4337) user operations and
+//! submitting them through a bundler.
+//!
+//! User operations use the v0.6 `EntryPoint` format, and are signed with an EIP-191 signature
+//! over the user operation hash, as expected by `SimpleAccount` and most accounts derived from it.
+
+use crate::{
+    provider::ethers::{estimate_eip1559_fees, try_get_http_provider, RetryProvider},
+    types::{ToAlloy, ToEthers},
+};
+use alloy_primitives::{address, hex, keccak256, Address, Bytes, B256, U256};
+use alloy_sol_types::{sol, SolCall, SolValue};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, TransactionReceipt, TransactionRequest,
+};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// The address of the canonical v0.6 `EntryPoint` contract.
+pub const DEFAULT_ENTRY_POINT: Address = address!("5FF137D4b0FDCD49DcA30c7CF57E578a026d2789");
+
+/// A well-formed signature used while estimating gas, before the operation can be signed.
+const DUMMY_SIGNATURE: [u8; 65] = hex!(
+    "fffffffffffffffffffffffffffffff000000000000000000000000000000000"
+    "7aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+    "1c"
+);
+
+sol! {
+    #[allow(missing_docs)]
+    interface IEntryPoint {
+        function getNonce(address sender, uint192 key) external view returns (uint256 nonce);
+    }
+
+    #[allow(missing_docs)]
+    interface IAccount {
+        function execute(address dest, uint256 value, bytes calldata func) external;
+    }
+}
+
+/// Encodes a call of `execute(dest, value, func)`, the call executor of most smart accounts.
+pub fn encode_execute(dest: Address, value: U256, func: Bytes) -> Bytes {
+    IAccount::executeCall { dest, value, func: func.to_vec() }.abi_encode().into()
+}
+
+/// A v0.6 user operation, as accepted by `eth_sendUserOperation`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    /// The smart account sending the operation.
+    pub sender: Address,
+    /// The account nonce, as tracked by the `EntryPoint`.
+    pub nonce: U256,
+    /// The factory address and calldata, if the account has yet to be deployed.
+    pub init_code: Bytes,
+    /// The calldata the account is called with.
+    pub call_data: Bytes,
+    /// The gas limit of the account call.
+    pub call_gas_limit: U256,
+    /// The gas limit of the account validation and deployment.
+    pub verification_gas_limit: U256,
+    /// The gas paid to the bundler for the calldata and overhead of the operation.
+    pub pre_verification_gas: U256,
+    /// The maximum fee per gas, as in EIP-1559.
+    pub max_fee_per_gas: U256,
+    /// The maximum priority fee per gas, as in EIP-1559.
+    pub max_priority_fee_per_gas: U256,
+    /// The paymaster address and data, if the operation is sponsored.
+    pub paymaster_and_data: Bytes,
+    /// The signature checked by the account.
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Returns the hash of the operation, which is signed by the account owner.
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> B256 {
+        let packed = (
+            self.sender,
+            self.nonce,
+            keccak256(&self.init_code),
+            keccak256(&self.call_data),
+            self.call_gas_limit,
+            self.verification_gas_limit,
+            self.pre_verification_gas,
+            self.max_fee_per_gas,
+            self.max_priority_fee_per_gas,
+            keccak256(&self.paymaster_and_data),
+        )
+            .abi_encode();
+        keccak256((keccak256(packed), entry_point, U256::from(chain_id)).abi_encode())
+    }
+}
+
+/// The parts of a user operation chosen by its author, everything else is filled in by
+/// [build_user_operation].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserOperationRequest {
+    /// The smart account sending the operation.
+    pub sender: Address,
+    /// The calldata the account is called with.
+    pub call_data: Bytes,
+    /// The factory address and calldata, if the account has yet to be deployed.
+    pub init_code: Bytes,
+    /// The paymaster address and data, if the operation is sponsored.
+    pub paymaster_and_data: Bytes,
+}
+
+/// Gas limits estimated by a bundler for a user operation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGas {
+    /// See [UserOperation::pre_verification_gas].
+    pub pre_verification_gas: U256,
+    /// See [UserOperation::verification_gas_limit].
+    pub verification_gas_limit: U256,
+    /// See [UserOperation::call_gas_limit].
+    pub call_gas_limit: U256,
+}
+
+/// The receipt of an included user operation, as returned by `eth_getUserOperationReceipt`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    /// The hash of the operation.
+    pub user_op_hash: B256,
+    /// Whether the account call succeeded.
+    pub success: bool,
+    /// The amount paid for the operation, in wei.
+    pub actual_gas_cost: U256,
+    /// The gas used by the operation.
+    pub actual_gas_used: U256,
+    /// The revert reason of the account call, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The receipt of the bundle transaction which included the operation.
+    pub receipt: TransactionReceipt,
+}
+
+/// A bundler RPC endpoint, bound to an `EntryPoint`.
+#[derive(Debug)]
+pub struct Bundler {
+    provider: RetryProvider,
+    entry_point: Address,
+}
+
+impl Bundler {
+    /// Creates a client for the bundler at `url`.
+    pub fn new(url: &str, entry_point: Address) -> Result<Self> {
+        let provider = try_get_http_provider(url)
+            .wrap_err_with(|| format!("invalid bundler RPC URL `{url}`"))?;
+        Ok(Self { provider, entry_point })
+    }
+
+    /// Returns the `EntryPoint` the bundler is used with.
+    pub fn entry_point(&self) -> Address {
+        self.entry_point
+    }
+
+    /// Estimates the gas limits of `op` with `eth_estimateUserOperationGas`.
+    pub async fn estimate_gas(&self, op: &UserOperation) -> Result<UserOperationGas> {
+        self.provider
+            .request("eth_estimateUserOperationGas", (op, self.entry_point))
+            .await
+            .wrap_err("failed to estimate user operation gas")
+    }
+
+    /// Submits `op` with `eth_sendUserOperation`, returning its hash.
+    pub async fn send(&self, op: &UserOperation) -> Result<B256> {
+        self.provider
+            .request("eth_sendUserOperation", (op, self.entry_point))
+            .await
+            .wrap_err("failed to send user operation")
+    }
+
+    /// Returns the receipt of the operation with the given hash, if it was included.
+    pub async fn receipt(&self, hash: B256) -> Result<Option<UserOperationReceipt>> {
+        self.provider
+            .request("eth_getUserOperationReceipt", [hash])
+            .await
+            .wrap_err("failed to get user operation receipt")
+    }
+
+    /// Polls for the receipt of the operation with the given hash until `timeout` elapses.
+    pub async fn wait_for_receipt(
+        &self,
+        hash: B256,
+        timeout: Duration,
+    ) -> Result<UserOperationReceipt> {
+        let start = Instant::now();
+        loop {
+            if let Some(receipt) = self.receipt(hash).await? {
+                return Ok(receipt)
+            }
+            if start.elapsed() >= timeout {
+                eyre::bail!("user operation {hash} was not included after {}s", timeout.as_secs())
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    }
+}
+
+/// Fills in the nonce, fees and gas limits of `request` and signs it with `signer`.
+pub async fn build_user_operation<S: Signer>(
+    provider: &RetryProvider,
+    bundler: &Bundler,
+    signer: &S,
+    request: UserOperationRequest,
+) -> Result<UserOperation>
+where
+    S::Error: 'static,
+{
+    let UserOperationRequest { sender, call_data, init_code, paymaster_and_data } = request;
+    let chain_id = provider.get_chainid().await?.as_u64();
+
+    let get_nonce = IEntryPoint::getNonceCall { sender, key: Default::default() };
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(bundler.entry_point().to_ethers())
+        .data(get_nonce.abi_encode())
+        .into();
+    let ret = provider.call(&tx, None).await.wrap_err("failed to get the account nonce")?;
+    let nonce = IEntryPoint::getNonceCall::abi_decode_returns(&ret, true)?.nonce;
+
+    let (max_fee_per_gas, max_priority_fee_per_gas) =
+        estimate_eip1559_fees(provider, Some(chain_id)).await?;
+
+    let mut op = UserOperation {
+        sender,
+        nonce,
+        init_code,
+        call_data,
+        max_fee_per_gas: max_fee_per_gas.to_alloy(),
+        max_priority_fee_per_gas: max_priority_fee_per_gas.to_alloy(),
+        paymaster_and_data,
+        signature: DUMMY_SIGNATURE.into(),
+        ..Default::default()
+    };
+
+    let gas = bundler.estimate_gas(&op).await?;
+    op.call_gas_limit = gas.call_gas_limit;
+    op.verification_gas_limit = gas.verification_gas_limit;
+    op.pre_verification_gas = gas.pre_verification_gas;
+
+    let hash = op.hash(bundler.entry_point(), chain_id);
+    let signature =
+        signer.sign_message(hash.as_slice()).await.wrap_err("failed to sign user operation")?;
+    op.signature = signature.to_vec().into();
+
+    Ok(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_as_rpc_object() {
+        let op = UserOperation {
+            sender: Address::with_last_byte(1),
+            nonce: U256::from(2),
+            call_gas_limit: U256::from(0x10000),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&op).unwrap();
+        assert_eq!(json["sender"], "0x0000000000000000000000000000000000000001");
+        assert_eq!(json["nonce"], "0x2");
+        assert_eq!(json["callGasLimit"], "0x10000");
+        assert_eq!(json["initCode"], "0x");
+        assert_eq!(serde_json::from_value::<UserOperation>(json).unwrap(), op);
+    }
+
+    #[test]
+    fn hash_commits_to_entry_point_and_chain() {
+        let op = UserOperation { sender: Address::with_last_byte(1), ..Default::default() };
+        let hash = op.hash(DEFAULT_ENTRY_POINT, 1);
+        assert_ne!(hash, op.hash(DEFAULT_ENTRY_POINT, 2));
+        assert_ne!(hash, op.hash(Address::ZERO, 1));
+
+        let signed = UserOperation { signature: DUMMY_SIGNATURE.into(), ..op };
+        assert_eq!(hash, signed.hash(DEFAULT_ENTRY_POINT, 1));
+    }
+}
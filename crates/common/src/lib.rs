@@ -8,17 +8,20 @@ extern crate self as foundry_common;
 extern crate tracing;
 
 pub mod abi;
+pub mod bytecode;
 pub mod calc;
 pub mod compile;
 pub mod console;
 pub mod constants;
 pub mod contracts;
+pub mod erc4337;
 pub mod errors;
 pub mod evm;
 pub mod fmt;
 pub mod fs;
 pub mod glob;
 pub mod provider;
+pub mod redact;
 pub mod retry;
 pub mod rpc;
 pub mod runtime_client;
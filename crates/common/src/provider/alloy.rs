@@ -18,7 +18,7 @@ use std::{
 };
 use url::ParseError;
 
-use super::tower::RetryBackoffLayer;
+use super::{cassette::CassetteLayer, tower::RetryBackoffLayer};
 
 /// Helper type alias for a retry provider
 pub type RetryProvider = Provider<BoxTransport>;
@@ -241,7 +241,10 @@ impl ProviderBuilder {
             .with_headers(headers)
             .with_jwt(jwt)
             .build();
-        let client = ClientBuilder::default().layer(retry_layer).transport(transport, false);
+        let client = ClientBuilder::default()
+            .layer(CassetteLayer::new(&url))
+            .layer(retry_layer)
+            .transport(transport, false);
 
         // todo: provider polling interval
         Ok(Provider::new_with_client(client.boxed()))
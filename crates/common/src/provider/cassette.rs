@@ -0,0 +1,519 @@
+//! Recording and replay of the RPC requests of all the providers of the process.
+//!
+//! While recording, the response to every request is stored in a [`Cassette`], by endpoint and
+//! request. While replaying, the providers answer the requests from the cassette without
+//! connecting to their endpoint, and fail the requests that weren't recorded. This makes runs
+//! that fork a chain, like forked tests, reproducible without network access.
+//!
+//! Endpoints are identified by their host, port and path, so that two endpoints served by the
+//! same host, like the networks of a provider, don't share their recordings. The API keys in the
+//! path and the query of a URL are redacted, so that they are never written to a cassette and a
+//! cassette can be replayed with a different key.
+
+use crate::fs;
+use alloy_json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload};
+use alloy_transport::{TransportError, TransportErrorKind, TransportFut};
+use eyre::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    path::Path,
+    sync::RwLock,
+    task::{Context, Poll},
+};
+use tower::Service;
+use url::Url;
+
+/// The cassette being recorded or replayed, if any.
+static CASSETTE: Lazy<RwLock<Option<Mode>>> = Lazy::new(Default::default);
+
+enum Mode {
+    Record(Cassette),
+    Replay(Cassette),
+}
+
+/// The recorded responses to RPC requests, by endpoint and request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// The responses by request, by endpoint.
+    pub endpoints: BTreeMap<String, BTreeMap<String, RecordedResponse>>,
+}
+
+/// A recorded response to an RPC request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordedResponse {
+    /// The result of a successful request.
+    Result(Value),
+    /// The error object of a failed request, with its `code`, `message` and `data`.
+    Error(Value),
+}
+
+impl Cassette {
+    /// Reads the cassette at `path`.
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(fs::read_json_file(path)?)
+    }
+
+    /// Writes the cassette to `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Returns the number of recorded requests.
+    pub fn len(&self) -> usize {
+        self.endpoints.values().map(BTreeMap::len).sum()
+    }
+
+    /// Returns whether no request was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the recorded response to `request` of `endpoint`.
+    pub fn get(&self, endpoint: &str, request: &str) -> Option<&RecordedResponse> {
+        self.endpoints.get(endpoint)?.get(request)
+    }
+
+    /// Records the response to `request` of `endpoint`, unless it was already recorded.
+    pub fn insert(&mut self, endpoint: &str, request: String, response: RecordedResponse) {
+        self.endpoints.entry(endpoint.to_string()).or_default().entry(request).or_insert(response);
+    }
+}
+
+/// Starts recording the RPC requests of the process into a new cassette.
+pub fn start_recording() {
+    *CASSETTE.write().unwrap_or_else(|err| err.into_inner()) =
+        Some(Mode::Record(Cassette::default()));
+}
+
+/// Starts answering the RPC requests of the process from `cassette`.
+pub fn start_replay(cassette: Cassette) {
+    *CASSETTE.write().unwrap_or_else(|err| err.into_inner()) = Some(Mode::Replay(cassette));
+}
+
+/// Stops recording or replaying, and returns the cassette.
+pub fn stop() -> Option<Cassette> {
+    CASSETTE.write().unwrap_or_else(|err| err.into_inner()).take().map(|mode| match mode {
+        Mode::Record(cassette) | Mode::Replay(cassette) => cassette,
+    })
+}
+
+/// Returns whether a cassette is being recorded or replayed.
+pub(crate) fn is_active() -> bool {
+    CASSETTE.read().unwrap_or_else(|err| err.into_inner()).is_some()
+}
+
+/// What to do with a request.
+pub(crate) enum Playback {
+    /// Send it, no cassette is used.
+    Send,
+    /// Send it and record its response.
+    Record,
+    /// Answer it with the recorded response.
+    Replay(RecordedResponse),
+    /// Fail it, it wasn't recorded.
+    Missing,
+}
+
+/// Returns what to do with `request` of `endpoint`.
+pub(crate) fn playback(endpoint: &str, request: &str) -> Playback {
+    match &*CASSETTE.read().unwrap_or_else(|err| err.into_inner()) {
+        None => Playback::Send,
+        Some(Mode::Record(_)) => Playback::Record,
+        Some(Mode::Replay(cassette)) => match cassette.get(endpoint, request) {
+            Some(response) => Playback::Replay(response.clone()),
+            None => Playback::Missing,
+        },
+    }
+}
+
+/// Records the response to `request` of `endpoint`, if recording.
+pub(crate) fn record(endpoint: &str, request: String, response: RecordedResponse) {
+    let mut mode = CASSETTE.write().unwrap_or_else(|err| err.into_inner());
+    if let Some(Mode::Record(cassette)) = &mut *mode {
+        cassette.insert(endpoint, request, response);
+    }
+}
+
+/// The placeholder of the API keys in the endpoints of cassettes.
+const REDACTED_KEY: &str = "<key>";
+
+/// Returns the endpoint of `url` in cassettes, its host, port and path, with the API keys in its
+/// path and query redacted.
+pub(crate) fn endpoint(url: &Url) -> String {
+    let mut endpoint = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return url.path().to_string(),
+    };
+    for segment in url.path_segments().into_iter().flatten().filter(|s| !s.is_empty()) {
+        endpoint.push('/');
+        endpoint.push_str(if is_api_key(segment) { REDACTED_KEY } else { segment });
+    }
+    let query = url
+        .query_pairs()
+        .map(|(name, value)| {
+            if is_api_key(&value) || is_key_param(&name) {
+                format!("{name}={REDACTED_KEY}")
+            } else {
+                format!("{name}={value}")
+            }
+        })
+        .collect::<Vec<_>>();
+    if !query.is_empty() {
+        endpoint.push('?');
+        endpoint.push_str(&query.join("&"));
+    }
+    endpoint
+}
+
+/// Returns whether a path segment or a query value of a URL looks like an API key: a long token
+/// mixing letters and digits, like the keys of Alchemy, Infura or QuickNode.
+fn is_api_key(s: &str) -> bool {
+    s.len() >= 16 &&
+        s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') &&
+        s.chars().any(|c| c.is_ascii_digit()) &&
+        s.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Returns whether a query parameter of a URL holds a credential.
+fn is_key_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["key", "token", "secret", "auth"].iter().any(|part| name.contains(part))
+}
+
+/// Returns the request of a method call in cassettes, the method name and its JSON params.
+pub(crate) fn request_key(method: &str, params: &Value) -> String {
+    format!("{method} {params}")
+}
+
+/// Returns the error message of a request missing from the replayed cassette.
+pub(crate) fn missing_message(endpoint: &str, request: &str) -> String {
+    format!(
+        "the request `{request}` to {endpoint} is not in the replayed RPC cassette, record it \
+         again with `--record-rpc`"
+    )
+}
+
+/// Sends a batch of method calls to `url`, or answers it from the cassette of the process, and
+/// returns the result or the error object of every call, in order.
+///
+/// This is for the batches sent outside of a provider: `send` posts the JSON-RPC request body to
+/// `url` and returns the response body.
+pub async fn send_batch<F, Fut>(
+    url: &str,
+    calls: &[(&str, Value)],
+    send: F,
+) -> Result<Vec<RecordedResponse>>
+where
+    F: FnOnce(Vec<u8>) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    let endpoint = endpoint(&Url::parse(url)?);
+    let requests =
+        calls.iter().map(|(method, params)| request_key(method, params)).collect::<Vec<_>>();
+    let playbacks = requests.iter().map(|request| playback(&endpoint, request)).collect::<Vec<_>>();
+    if playbacks.iter().any(|playback| matches!(playback, Playback::Replay(_) | Playback::Missing))
+    {
+        return requests
+            .iter()
+            .zip(playbacks)
+            .map(|(request, playback)| match playback {
+                Playback::Replay(response) => Ok(response),
+                _ => eyre::bail!(missing_message(&endpoint, request)),
+            })
+            .collect()
+    }
+
+    let body = calls
+        .iter()
+        .enumerate()
+        .map(|(id, (method, params))| {
+            serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+        })
+        .collect::<Vec<_>>();
+    let response = send(serde_json::to_vec(&body)?).await?;
+    let responses = match serde_json::from_slice::<Value>(&response)? {
+        Value::Array(responses) => responses,
+        // nodes that don't support batches return a single error
+        response => eyre::bail!("unexpected response to a batch: {response}"),
+    };
+
+    let mut results = vec![None; calls.len()];
+    for mut response in responses {
+        let id = response.get("id").and_then(Value::as_u64).unwrap_or(u64::MAX) as usize;
+        let Some(result) = results.get_mut(id) else {
+            eyre::bail!("unexpected response id {} in a batch", response["id"])
+        };
+        let recorded = match response.get_mut("error").map(Value::take) {
+            Some(error) if !error.is_null() => RecordedResponse::Error(error),
+            _ => RecordedResponse::Result(
+                response.get_mut("result").map(Value::take).unwrap_or_default(),
+            ),
+        };
+        if matches!(playbacks[id], Playback::Record) {
+            record(&endpoint, requests[id].clone(), recorded.clone());
+        }
+        *result = Some(recorded);
+    }
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(id, result)| {
+            result.ok_or_else(|| eyre::eyre!("no response to call {id} of a batch"))
+        })
+        .collect()
+}
+
+/// A Tower Layer recording or replaying the requests of a transport to an endpoint, with the
+/// cassette of the process.
+#[derive(Clone, Debug)]
+pub struct CassetteLayer {
+    endpoint: String,
+}
+
+impl CassetteLayer {
+    /// Creates a new [CassetteLayer] for the transport to `url`.
+    pub fn new(url: &Url) -> Self {
+        Self { endpoint: endpoint(url) }
+    }
+}
+
+impl<S> tower::layer::Layer<S> for CassetteLayer {
+    type Service = CassetteService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CassetteService { inner, endpoint: self.endpoint.clone() }
+    }
+}
+
+/// A Tower Service recording or replaying the requests of a transport. See [CassetteLayer].
+#[derive(Clone, Debug)]
+pub struct CassetteService<S> {
+    /// The inner service
+    inner: S,
+    /// The endpoint of the inner service in cassettes
+    endpoint: String,
+}
+
+impl<S> Service<RequestPacket> for CassetteService<S>
+where
+    S: Service<
+            RequestPacket,
+            Response = ResponsePacket,
+            Error = TransportError,
+            Future = TransportFut<'static>,
+        > + Send
+        + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        if !is_active() {
+            return self.inner.call(request)
+        }
+
+        let requests = match &request {
+            RequestPacket::Single(request) => vec![request],
+            RequestPacket::Batch(requests) => requests.iter().collect(),
+        };
+        // the id and the request of every call of the packet
+        let calls = requests
+            .into_iter()
+            .map(|request| {
+                let call: Value = serde_json::from_str(request.serialized().get())?;
+                let method = call.get("method").and_then(Value::as_str).unwrap_or_default();
+                let params = call.get("params").cloned().unwrap_or_default();
+                Ok((serde_json::to_value(request.id())?, request_key(method, &params)))
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>();
+        let calls = match calls {
+            Ok(calls) => calls,
+            Err(err) => return Box::pin(async move { Err(TransportErrorKind::custom(err)) }),
+        };
+
+        let endpoint = self.endpoint.clone();
+        let playbacks = calls.iter().map(|(_, call)| playback(&endpoint, call)).collect::<Vec<_>>();
+        if playbacks.iter().all(|playback| matches!(playback, Playback::Send)) {
+            return self.inner.call(request)
+        }
+        if playbacks.iter().all(|playback| matches!(playback, Playback::Record)) {
+            let response = self.inner.call(request);
+            return Box::pin(async move {
+                let response = response.await?;
+                record_packet(&endpoint, calls, &response);
+                Ok(response)
+            })
+        }
+
+        let is_batch = matches!(request, RequestPacket::Batch(_));
+        Box::pin(async move {
+            let mut responses = Vec::with_capacity(calls.len());
+            for ((id, call), playback) in calls.into_iter().zip(playbacks) {
+                let Playback::Replay(recorded) = playback else {
+                    return Err(TransportErrorKind::custom_str(&missing_message(&endpoint, &call)))
+                };
+                let response = match recorded {
+                    RecordedResponse::Result(result) => {
+                        serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result })
+                    }
+                    RecordedResponse::Error(error) => {
+                        serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": error })
+                    }
+                };
+                let response: Response = serde_json::from_str(&response.to_string())
+                    .map_err(TransportErrorKind::custom)?;
+                responses.push(response);
+            }
+            Ok(if is_batch {
+                ResponsePacket::Batch(responses)
+            } else {
+                ResponsePacket::Single(responses.remove(0))
+            })
+        })
+    }
+}
+
+/// Records the responses of a packet, matched to their calls by id.
+fn record_packet(endpoint: &str, calls: Vec<(Value, String)>, response: &ResponsePacket) {
+    let responses = match response {
+        ResponsePacket::Single(response) => vec![response],
+        ResponsePacket::Batch(responses) => responses.iter().collect(),
+    };
+    for response in responses {
+        let Ok(id) = serde_json::to_value(&response.id) else { continue };
+        let Some((_, call)) = calls.iter().find(|(call_id, _)| *call_id == id) else { continue };
+        let recorded = match &response.payload {
+            ResponsePayload::Success(result) => match serde_json::from_str(result.get()) {
+                Ok(result) => RecordedResponse::Result(result),
+                Err(_) => continue,
+            },
+            ResponsePayload::Failure(error) => {
+                let data =
+                    error.data.as_ref().and_then(|data| serde_json::from_str(data.get()).ok());
+                RecordedResponse::Error(serde_json::json!({
+                    "code": error.code,
+                    "message": error.message,
+                    "data": data.unwrap_or(Value::Null),
+                }))
+            }
+        };
+        record(endpoint, call.clone(), recorded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_cassette() {
+        let url =
+            Url::parse("https://eth-mainnet.g.alchemy.com/v2/Xk2n8v9QpLm3RtY7uWz4aB1c").unwrap();
+        let endpoint = endpoint(&url);
+        assert_eq!(endpoint, "eth-mainnet.g.alchemy.com/v2/<key>");
+
+        let request = request_key("eth_getBalance", &serde_json::json!(["0x00", "0x10"]));
+        assert_eq!(request, r#"eth_getBalance ["0x00","0x10"]"#);
+
+        let mut cassette = Cassette::default();
+        cassette.insert(&endpoint, request.clone(), RecordedResponse::Result("0x1".into()));
+        cassette.insert(&endpoint, request.clone(), RecordedResponse::Result("0x2".into()));
+        assert_eq!(cassette.len(), 1);
+        let recorded = cassette.get(&endpoint, &request);
+        assert_eq!(recorded, Some(&RecordedResponse::Result("0x1".into())));
+        assert_eq!(cassette.get("localhost:8545", &request), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+        cassette.write(&path).unwrap();
+        assert_eq!(Cassette::read(&path).unwrap(), cassette);
+    }
+
+    #[tokio::test]
+    async fn records_and_replays_batches() {
+        let url = "http://localhost:8545";
+        let calls = [
+            ("eth_call", serde_json::json!([{ "to": "0x01" }, "0x1"])),
+            ("eth_chainId", Value::Null),
+        ];
+        let responses = br#"[
+            {"jsonrpc":"2.0","id":1,"result":"0x1"},
+            {"jsonrpc":"2.0","id":0,"error":{"code":3,"message":"execution reverted","data":"0x"}}
+        ]"#;
+        let expected = vec![
+            RecordedResponse::Error(
+                serde_json::json!({ "code": 3, "message": "execution reverted", "data": "0x" }),
+            ),
+            RecordedResponse::Result("0x1".into()),
+        ];
+
+        start_recording();
+        let recorded = send_batch(url, &calls, |body| async move {
+            let body: Value = serde_json::from_slice(&body)?;
+            assert_eq!(body[1], serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": null }));
+            Ok::<_, eyre::Report>(responses.to_vec())
+        })
+        .await
+        .unwrap();
+        assert_eq!(recorded, expected);
+        let cassette = stop().unwrap();
+        assert_eq!(cassette.len(), 2);
+
+        // the batch is answered from the cassette, without sending it
+        start_replay(cassette);
+        let replayed = send_batch(url, &calls, |_| async {
+            Err::<Vec<u8>, _>(eyre::eyre!("sent a replayed batch"))
+        })
+        .await
+        .unwrap();
+        assert_eq!(replayed, expected);
+        let replayed =
+            send_batch(url, &calls[1..], |_| async { Ok::<_, eyre::Report>(Vec::new()) })
+                .await
+                .unwrap();
+        assert_eq!(replayed, expected[1..]);
+        let err = send_batch(url, &[("eth_blockNumber", Value::Null)], |_| async {
+            Ok::<_, eyre::Report>(Vec::new())
+        })
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("is not in the replayed RPC cassette"), "{err}");
+        stop();
+    }
+
+    #[test]
+    fn redacts_api_keys_of_endpoints() {
+        let endpoint = |url: &str| endpoint(&Url::parse(url).unwrap());
+
+        assert_eq!(endpoint("http://localhost:8545"), "localhost:8545");
+        assert_eq!(
+            endpoint("https://mainnet.infura.io/v3/9aa3d95b3bc440fa88ea12eaa4456161"),
+            "mainnet.infura.io/v3/<key>"
+        );
+        assert_eq!(
+            endpoint("https://rpc.ankr.com/eth_sepolia?apikey=secret&network=sepolia"),
+            "rpc.ankr.com/eth_sepolia?apikey=<key>&network=sepolia"
+        );
+
+        // the networks of a provider are different endpoints, whichever the key
+        assert_ne!(
+            endpoint("https://eth-mainnet.g.alchemy.com/v2/Xk2n8v9QpLm3RtY7uWz4aB1c"),
+            endpoint("https://eth-sepolia.g.alchemy.com/v2/Xk2n8v9QpLm3RtY7uWz4aB1c")
+        );
+        assert_eq!(
+            endpoint("https://mainnet.era.zksync.io/rpc/3f5c1a9e7b2d4c6f8a0e"),
+            endpoint("https://mainnet.era.zksync.io/rpc/0b1d3f5a7c9e2b4d6f8a")
+        );
+    }
+}
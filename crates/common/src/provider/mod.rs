@@ -1,6 +1,7 @@
 //! Provider-related instantiation and usage utilities.
 
 pub mod alloy;
+pub mod cassette;
 pub mod ethers;
 pub mod retry;
 pub mod runtime_transport;
@@ -2,7 +2,7 @@
 //! and applying backoff.
 use std::{
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicU32, AtomicU64, Ordering},
         Arc,
     },
     task::{Context, Poll},
@@ -17,6 +17,15 @@ use super::{
     runtime_transport::RuntimeTransport,
 };
 
+/// The number of requests sent by all the retrying services of the process, retries excluded.
+static REQUESTS_SENT: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the number of RPC requests sent by the providers of the process so far, retries
+/// excluded.
+pub fn requests_sent() -> u64 {
+    REQUESTS_SENT.load(Ordering::Relaxed)
+}
+
 /// An Alloy Tower Layer that is responsible for retrying requests based on the
 /// error type. See [TransportError].
 #[derive(Debug, Clone)]
@@ -98,6 +107,7 @@ impl Service<RequestPacket> for RetryBackoffService<RuntimeTransport> {
 
     fn call(&mut self, request: RequestPacket) -> Self::Future {
         let mut this = self.clone();
+        REQUESTS_SENT.fetch_add(1, Ordering::Relaxed);
         Box::pin(async move {
             let ahead_in_queue = this.requests_enqueued.fetch_add(1, Ordering::SeqCst) as u64;
             let mut rate_limit_retry_number: u32 = 0;
@@ -0,0 +1,119 @@
+//! Redaction of secrets from everything foundry prints or persists.
+//!
+//! Secrets, like private keys passed on the command line, keystore passwords or API keys, are
+//! registered once with [`register_secret`] when they're read. All output paths, i.e. the shell,
+//! error reports, rendered traces and broadcast logs, then pass their output through [`redact`].
+
+use once_cell::sync::Lazy;
+use std::{borrow::Cow, cmp::Reverse, sync::RwLock};
+
+/// The placeholder secrets are replaced with.
+pub const REDACTED: &str = "<redacted>";
+
+/// Secrets shorter than this are not registered, as they'd likely match unrelated output.
+///
+/// This is the usual minimum length of a password, keys and API tokens are much longer.
+const MIN_SECRET_LEN: usize = 8;
+
+/// All registered secrets.
+static SECRETS: Lazy<Secrets> = Lazy::new(Default::default);
+
+/// A set of secrets to redact, longest first.
+#[derive(Debug, Default)]
+struct Secrets(RwLock<Vec<String>>);
+
+impl Secrets {
+    fn register(&self, secret: &str) {
+        let secret = secret.trim();
+        let unprefixed = secret.strip_prefix("0x").unwrap_or(secret);
+        if unprefixed.len() < MIN_SECRET_LEN {
+            return
+        }
+
+        let mut variants = vec![secret.to_string(), unprefixed.to_string()];
+        if unprefixed.chars().all(|c| c.is_ascii_hexdigit()) {
+            variants.push(unprefixed.to_ascii_lowercase());
+        }
+
+        let mut secrets = self.0.write().unwrap_or_else(|err| err.into_inner());
+        for s in variants {
+            if !secrets.contains(&s) {
+                secrets.push(s);
+            }
+        }
+        // replace longer secrets first so a prefixed key isn't left with a dangling `0x`
+        secrets.sort_by_key(|s| Reverse(s.len()));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.read().unwrap_or_else(|err| err.into_inner()).is_empty()
+    }
+
+    fn redact<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let secrets = self.0.read().unwrap_or_else(|err| err.into_inner());
+        let mut out = Cow::Borrowed(s);
+        for secret in secrets.iter() {
+            if out.contains(secret.as_str()) {
+                out = Cow::Owned(out.replace(secret.as_str(), REDACTED));
+            }
+        }
+        out
+    }
+
+    fn find(&self, s: &str) -> Vec<String> {
+        let secrets = self.0.read().unwrap_or_else(|err| err.into_inner());
+        secrets.iter().filter(|secret| s.contains(secret.as_str())).cloned().collect()
+    }
+}
+
+/// Registers a secret that must never show up in any output.
+///
+/// Hex encoded secrets are registered both with and without their `0x` prefix, and in lowercase
+/// since that's how they'd show up in calldata.
+pub fn register_secret(secret: impl AsRef<str>) {
+    SECRETS.register(secret.as_ref())
+}
+
+/// Returns `true` if any secrets have been registered.
+pub fn has_secrets() -> bool {
+    !SECRETS.is_empty()
+}
+
+/// Replaces all registered secrets in `s` with [`REDACTED`].
+pub fn redact(s: &str) -> Cow<'_, str> {
+    SECRETS.redact(s)
+}
+
+/// Returns all registered secrets contained in `s`.
+///
+/// Used in tests to assert that nothing secret escapes into the output.
+pub fn find_secrets(s: &str) -> Vec<String> {
+    SECRETS.find(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_redact_secrets() {
+        // a local set, so the secrets of this test don't leak into the output of other tests
+        let secrets = Secrets::default();
+        assert!(secrets.is_empty());
+
+        let key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcaee0ba3d4d8fd3e1";
+        secrets.register(key);
+        secrets.register("abcdef");
+        assert!(!secrets.is_empty());
+
+        let out = secrets.redact(&format!("private key {key} or {}", &key[2..]));
+        assert_eq!(out, format!("private key {REDACTED} or {REDACTED}"));
+        assert!(secrets.find(&out).is_empty());
+        // too short to be registered
+        assert!(matches!(secrets.redact("nothing secret in abcdef"), Cow::Borrowed(_)));
+        assert_eq!(secrets.find(key).len(), 2);
+
+        secrets.register("0xDEADBEEFCAFE");
+        assert_eq!(secrets.redact("calldata 0x00deadbeefcafe"), format!("calldata 0x00{REDACTED}"));
+    }
+}
@@ -1,5 +1,6 @@
 //! Wrap different providers
 // todo: remove
+use crate::provider::cassette::{self, Playback, RecordedResponse};
 use async_trait::async_trait;
 use ethers_core::types::U256;
 use ethers_providers::{
@@ -49,12 +50,17 @@ pub enum RuntimeClientError {
     /// Invalid file path
     #[error("Invalid IPC file path: {0}")]
     BadPath(String),
+
+    /// Error response replayed from the RPC cassette
+    #[error(transparent)]
+    RecordedError(JsonRpcError),
 }
 
 impl RpcError for RuntimeClientError {
     fn as_error_response(&self) -> Option<&JsonRpcError> {
         match self {
             RuntimeClientError::ProviderError(err) => err.as_error_response(),
+            RuntimeClientError::RecordedError(err) => Some(err),
             _ => None,
         }
     }
@@ -278,6 +284,53 @@ impl JsonRpcClient for RuntimeClient {
     type Error = RuntimeClientError;
 
     async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        if !cassette::is_active() {
+            return self.send(method, params).await
+        }
+
+        let endpoint = cassette::endpoint(&self.url);
+        let json_params = serde_json::to_value(&params)
+            .map_err(|e| RuntimeClientError::ProviderError(e.into()))?;
+        let request = cassette::request_key(method, &json_params);
+        let from_value = |value| {
+            serde_json::from_value(value).map_err(|e| RuntimeClientError::ProviderError(e.into()))
+        };
+        match cassette::playback(&endpoint, &request) {
+            Playback::Send => self.send(method, params).await,
+            Playback::Record => {
+                let res = self.send::<_, serde_json::Value>(method, params).await;
+                let recorded = match &res {
+                    Ok(result) => Some(RecordedResponse::Result(result.clone())),
+                    Err(err) => err
+                        .as_error_response()
+                        .and_then(|err| serde_json::to_value(err).ok())
+                        .map(RecordedResponse::Error),
+                };
+                if let Some(recorded) = recorded {
+                    cassette::record(&endpoint, request, recorded);
+                }
+                from_value(res?)
+            }
+            Playback::Replay(RecordedResponse::Result(result)) => from_value(result),
+            Playback::Replay(RecordedResponse::Error(error)) => {
+                let error = serde_json::from_value(error)
+                    .map_err(|e| RuntimeClientError::ProviderError(e.into()))?;
+                Err(RuntimeClientError::RecordedError(error))
+            }
+            Playback::Missing => Err(RuntimeClientError::ProviderError(
+                ProviderError::CustomError(cassette::missing_message(&endpoint, &request)),
+            )),
+        }
+    }
+}
+
+impl RuntimeClient {
+    /// Sends the request to the endpoint, connecting on the first request.
+    async fn send<T, R>(&self, method: &str, params: T) -> Result<R, RuntimeClientError>
     where
         T: Debug + Serialize + Send + Sync,
         R: DeserializeOwned + Send,
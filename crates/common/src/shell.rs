@@ -1,5 +1,6 @@
 //! Helpers for printing to output
 
+use crate::redact::redact;
 use once_cell::sync::OnceCell;
 use serde::Serialize;
 use std::{
@@ -107,21 +108,21 @@ impl Shell {
     ///
     /// Caller is responsible for deciding whether [`Shell::verbosity`] is affects output.
     pub fn write_stdout(&self, fragment: impl fmt::Display) -> io::Result<()> {
-        self.output.write_stdout(fragment)
+        self.output.write_stdout(redact(&fragment.to_string()))
     }
 
     /// Write a fragment to stderr
     ///
     /// Caller is responsible for deciding whether [`Shell::verbosity`] is affects output.
     pub fn write_stderr(&self, fragment: impl fmt::Display) -> io::Result<()> {
-        self.output.write_stderr(fragment)
+        self.output.write_stderr(redact(&fragment.to_string()))
     }
 
     /// Prints the object to stdout as json
     pub fn print_json<T: serde::ser::Serialize>(&self, obj: &T) -> serde_json::Result<()> {
         if self.verbosity.is_json() {
             let json = serde_json::to_string(&obj)?;
-            let _ = self.output.with_stdout(|out| writeln!(out, "{json}"));
+            let _ = self.output.with_stdout(|out| writeln!(out, "{}", redact(&json)));
         }
         Ok(())
     }
@@ -129,7 +130,7 @@ impl Shell {
     pub fn pretty_print_json<T: serde::ser::Serialize>(&self, obj: &T) -> serde_json::Result<()> {
         if self.verbosity.is_json() {
             let json = serde_json::to_string_pretty(&obj)?;
-            let _ = self.output.with_stdout(|out| writeln!(out, "{json}"));
+            let _ = self.output.with_stdout(|out| writeln!(out, "{}", redact(&json)));
         }
         Ok(())
     }
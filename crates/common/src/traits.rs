@@ -15,6 +15,16 @@ pub trait TestFilter: Send + Sync {
 
     /// Returns a contract with the given path should be included.
     fn matches_path(&self, path: &Path) -> bool;
+
+    /// Returns whether the test of the given contract should be included.
+    ///
+    /// By default every part is checked on its own, filters with conditions spanning multiple
+    /// parts have to override this.
+    fn matches_qualified_test(&self, path: &Path, contract_name: &str, test_name: &str) -> bool {
+        self.matches_path(path) &&
+            self.matches_contract(contract_name) &&
+            self.matches_test(test_name)
+    }
 }
 
 /// Extension trait for `Function`.
@@ -0,0 +1,187 @@
+//! Configuration for test execution time budgets.
+
+use crate::inline::{
+    parse_config_bool, remove_whitespaces, InlineConfigParser, InlineConfigParserError,
+    INLINE_CONFIG_MAX_DURATION_KEY, INLINE_CONFIG_PREFIX,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr, time::Duration};
+
+/// The wall time budget of a single test, whole fuzz and invariant campaigns included.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestBudgetConfig {
+    /// The maximum wall time a test may take, unlimited if `None`.
+    pub max_duration: Option<TestDuration>,
+    /// Whether a test exceeding `max_duration` only produces a warning instead of failing.
+    pub warn_only: bool,
+}
+
+impl TestBudgetConfig {
+    /// Returns the configured budget if `elapsed` exceeds it.
+    pub fn exceeded_by(&self, elapsed: Duration) -> Option<TestDuration> {
+        self.max_duration.filter(|max| elapsed > max.0)
+    }
+}
+
+impl InlineConfigParser for TestBudgetConfig {
+    fn config_key() -> String {
+        INLINE_CONFIG_MAX_DURATION_KEY.into()
+    }
+
+    fn try_merge(&self, configs: &[String]) -> Result<Option<Self>, InlineConfigParserError> {
+        let overrides: Vec<(String, String)> = Self::get_config_overrides(configs);
+
+        if overrides.is_empty() {
+            return Ok(None)
+        }
+
+        let mut conf_clone = *self;
+
+        for (key, value) in overrides {
+            match key.as_str() {
+                "max_duration" => {
+                    let duration = value
+                        .parse()
+                        .map_err(|_| InlineConfigParserError::ParseDuration(key, value))?;
+                    conf_clone.max_duration = Some(duration);
+                }
+                "max_duration_warn_only" => conf_clone.warn_only = parse_config_bool(key, value)?,
+                _ => Err(InlineConfigParserError::InvalidConfigProperty(key))?,
+            }
+        }
+        Ok(Some(conf_clone))
+    }
+
+    /// Budgets are not namespaced under a section like `fuzz`, so the keys directly follow the
+    /// profile, i.e. `forge-config: default.max_duration = 5s`.
+    fn get_config_overrides(config_lines: &[String]) -> Vec<(String, String)> {
+        let prefix = format!("{INLINE_CONFIG_PREFIX}:");
+        config_lines
+            .iter()
+            .map(|l| remove_whitespaces(l))
+            .filter_map(|line| {
+                let (_profile, line) = line.strip_prefix(&prefix)?.split_once('.')?;
+                let (key, value) = line.split_once('=')?;
+                key.starts_with(INLINE_CONFIG_MAX_DURATION_KEY)
+                    .then(|| (key.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// A human readable duration, like `500ms`, `5s`, `1.5m` or `1h`.
+///
+/// A plain number is interpreted as seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TestDuration(pub Duration);
+
+impl TestDuration {
+    /// Returns the inner [`Duration`].
+    pub fn as_duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for TestDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl FromStr for TestDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (value, unit) = s.split_at(split);
+        let value: f64 = value.parse().map_err(|_| format!("invalid duration: {s}"))?;
+        let scale = match unit.trim() {
+            "ms" => 0.001,
+            "" | "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            unit => return Err(format!("unknown duration unit `{unit}`, expected ms, s, m or h")),
+        };
+        Duration::try_from_secs_f64(value * scale)
+            .map(Self)
+            .map_err(|_| format!("invalid duration: {s}"))
+    }
+}
+
+impl fmt::Display for TestDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `Duration`'s debug format is already human readable, i.e. `1.5s` or `500ms`
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl Serialize for TestDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TestDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Val {
+            Secs(u64),
+            Str(String),
+        }
+
+        match Val::deserialize(deserializer)? {
+            Val::Secs(secs) => Ok(Self(Duration::from_secs(secs))),
+            Val::Str(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_durations() {
+        assert_eq!("500ms".parse(), Ok(TestDuration(Duration::from_millis(500))));
+        assert_eq!("5s".parse(), Ok(TestDuration(Duration::from_secs(5))));
+        assert_eq!("5".parse(), Ok(TestDuration(Duration::from_secs(5))));
+        assert_eq!("1.5m".parse(), Ok(TestDuration(Duration::from_secs(90))));
+        assert_eq!("1h".parse(), Ok(TestDuration(Duration::from_secs(3600))));
+        assert!("5d".parse::<TestDuration>().is_err());
+        assert!("fast".parse::<TestDuration>().is_err());
+        assert_eq!(TestDuration(Duration::from_millis(1500)).to_string(), "1.5s");
+    }
+
+    #[test]
+    fn successful_merge() {
+        let configs = &[
+            "forge-config: default.max_duration = 1.5s".to_string(),
+            "forge-config: default.max_duration_warn_only = true".to_string(),
+            "forge-config: default.fuzz.runs = 10".to_string(),
+        ];
+        let merged = TestBudgetConfig::default().try_merge(configs).expect("No errors").unwrap();
+        assert_eq!(merged.max_duration, Some(TestDuration(Duration::from_millis(1500))));
+        assert!(merged.warn_only);
+        assert_eq!(merged.exceeded_by(Duration::from_secs(1)), None);
+        assert!(merged.exceeded_by(Duration::from_secs(2)).is_some());
+    }
+
+    #[test]
+    fn merge_is_none_unrelated_property() {
+        let configs = &["forge-config: default.fuzz.runs = 2".to_string()];
+        let merged = TestBudgetConfig::default().try_merge(configs).expect("No errors");
+        assert!(merged.is_none());
+    }
+
+    #[test]
+    fn invalid_duration() {
+        let configs = &["forge-config: default.max_duration = soon".to_string()];
+        let err = TestBudgetConfig::default().try_merge(configs).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid config value for key 'max_duration'. Unable to parse 'soon' into a duration"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Pinned bytecode hashes of compiled contracts.
+
+use alloy_primitives::B256;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The expected hash of the deployed bytecode of a contract, in the form `<contract>=<hash>`.
+///
+/// The contract is identified by `<name>` or `<path>:<name>`. The hash is the keccak256 hash of
+/// the EVM deployed bytecode, or the EraVM bytecode hash when compiling with zksolc.
+///
+/// ```toml
+/// assert_bytecode = ["Counter=0x5c5f…", "src/Token.sol:Token=0x0100…"]
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytecodePin {
+    /// The contract identifier.
+    pub contract: String,
+    /// The expected bytecode hash.
+    pub hash: B256,
+}
+
+impl FromStr for BytecodePin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (contract, hash) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("invalid bytecode pin `{s}`, expected `<contract>=<hash>`"))?;
+        if contract.is_empty() {
+            return Err(format!("missing contract in bytecode pin `{s}`"))
+        }
+        let hash =
+            hash.parse().map_err(|err| format!("invalid hash in bytecode pin `{s}`: {err}"))?;
+        Ok(Self { contract: contract.to_string(), hash })
+    }
+}
+
+impl fmt::Display for BytecodePin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.contract, self.hash)
+    }
+}
+
+impl Serialize for BytecodePin {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BytecodePin {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bytecode_pin() {
+        let hash = "0x5c5f3c0b2ac8e8d6c94cf0e6b0297ec5a72fbbad31cd4a8d4a05d3e1d8a4e5f1";
+        let pin: BytecodePin = format!("src/Counter.sol:Counter={hash}").parse().unwrap();
+        assert_eq!(pin.contract, "src/Counter.sol:Counter");
+        assert_eq!(pin.hash, hash.parse::<B256>().unwrap());
+        assert_eq!(pin.to_string(), format!("src/Counter.sol:Counter={hash}"));
+
+        assert!("Counter".parse::<BytecodePin>().is_err());
+        assert!(format!("={hash}").parse::<BytecodePin>().is_err());
+        assert!("Counter=0x1234".parse::<BytecodePin>().is_err());
+    }
+}
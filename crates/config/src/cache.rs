@@ -3,7 +3,14 @@
 use crate::Chain;
 use number_prefix::NumberPrefix;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{fmt, fmt::Formatter, str::FromStr};
+use std::{
+    fmt,
+    fmt::Formatter,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+use walkdir::WalkDir;
 
 /// Settings to configure caching of remote
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -227,6 +234,196 @@ pub struct ChainCache {
     pub block_explorer: u64,
 }
 
+/// The kind of data a [`CacheEntry`] holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CacheKind {
+    /// The `solc` build artifacts of the project.
+    SolcArtifacts,
+    /// The `zksolc` build artifacts of the project.
+    ZkSolcArtifacts,
+    /// The storage cached for a forked block.
+    RpcStorage,
+    /// Responses cached from a block explorer.
+    BlockExplorer,
+    /// An installed `solc` binary.
+    SolcBinary,
+    /// An installed `zksolc` binary.
+    ZkSolcBinary,
+}
+
+impl fmt::Display for CacheKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheKind::SolcArtifacts => "solc artifacts",
+            CacheKind::ZkSolcArtifacts => "zksolc artifacts",
+            CacheKind::RpcStorage => "rpc storage",
+            CacheKind::BlockExplorer => "block explorer",
+            CacheKind::SolcBinary => "solc binary",
+            CacheKind::ZkSolcBinary => "zksolc binary",
+        })
+    }
+}
+
+/// A file or directory that can be garbage collected as a whole.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// What the entry holds.
+    pub kind: CacheKind,
+    /// The path of the file or directory.
+    pub path: PathBuf,
+    /// The size of the entry in bytes.
+    pub size: u64,
+    /// The most recent modification time of the entry, or any file in it.
+    pub modified: SystemTime,
+}
+
+impl CacheEntry {
+    /// Reads the size and modification time of the file or directory at `path`.
+    ///
+    /// Returns `None` if nothing exists at `path`.
+    pub fn read(kind: CacheKind, path: PathBuf) -> Option<Self> {
+        if !path.exists() {
+            return None
+        }
+        let (mut size, mut modified) = (0, SystemTime::UNIX_EPOCH);
+        for metadata in WalkDir::new(&path).into_iter().flatten().flat_map(|e| e.metadata()) {
+            if metadata.is_file() {
+                size += metadata.len();
+            }
+            if let Ok(time) = metadata.modified() {
+                modified = modified.max(time);
+            }
+        }
+        Some(Self { kind, path, size, modified })
+    }
+
+    /// Removes the entry from disk.
+    pub fn remove(&self) -> std::io::Result<()> {
+        if self.path.is_dir() {
+            std::fs::remove_dir_all(&self.path)
+        } else {
+            std::fs::remove_file(&self.path)
+        }
+    }
+}
+
+/// The entries selected for removal by a garbage collection run.
+#[derive(Clone, Debug, Default)]
+pub struct GcPlan {
+    /// The entries to remove, oldest first.
+    pub remove: Vec<CacheEntry>,
+    /// The entries to keep.
+    pub keep: Vec<CacheEntry>,
+}
+
+impl GcPlan {
+    /// Selects the entries to remove.
+    ///
+    /// All entries not modified within `older_than` are removed first. If the remaining entries
+    /// still exceed `max_size`, the least recently modified ones are removed until they fit.
+    pub fn new(
+        mut entries: Vec<CacheEntry>,
+        max_size: Option<u64>,
+        older_than: Option<Duration>,
+        now: SystemTime,
+    ) -> Self {
+        entries.sort_by_key(|entry| entry.modified);
+
+        let mut plan = GcPlan::default();
+        for entry in entries {
+            let age = now.duration_since(entry.modified).unwrap_or_default();
+            if older_than.is_some_and(|older_than| age > older_than) {
+                plan.remove.push(entry);
+            } else {
+                plan.keep.push(entry);
+            }
+        }
+
+        if let Some(max_size) = max_size {
+            let mut kept_size: u64 = plan.keep.iter().map(|entry| entry.size).sum();
+            let mut evict = 0;
+            while kept_size > max_size && evict < plan.keep.len() {
+                kept_size -= plan.keep[evict].size;
+                evict += 1;
+            }
+            plan.remove.extend(plan.keep.drain(..evict));
+            plan.remove.sort_by_key(|entry| entry.modified);
+        }
+
+        plan
+    }
+
+    /// Returns the number of bytes freed by removing the selected entries.
+    pub fn freed(&self) -> u64 {
+        self.remove.iter().map(|entry| entry.size).sum()
+    }
+
+    /// Returns the number of bytes kept.
+    pub fn kept(&self) -> u64 {
+        self.keep.iter().map(|entry| entry.size).sum()
+    }
+
+    /// Removes all selected entries from disk.
+    pub fn execute(&self) -> std::io::Result<()> {
+        self.remove.iter().try_for_each(CacheEntry::remove)
+    }
+}
+
+impl fmt::Display for GcPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for entry in &self.remove {
+            writeln!(f, "- {} {} ({})", entry.kind, entry.path.display(), CacheSize(entry.size))?;
+        }
+        write!(
+            f,
+            "{} entries ({}) selected for removal, {} entries ({}) kept",
+            self.remove.len(),
+            CacheSize(self.freed()),
+            self.keep.len(),
+            CacheSize(self.kept())
+        )
+    }
+}
+
+/// A size in bytes, like `500MB`, `5GB` or `1.5GiB`.
+///
+/// A plain number is interpreted as bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CacheSize(pub u64);
+
+impl FromStr for CacheSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (value, unit) = s.split_at(split);
+        let value: f64 = value.parse().map_err(|_| format!("invalid size: {s}"))?;
+        let scale: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" => 1_000,
+            "MB" => 1_000_000,
+            "GB" => 1_000_000_000,
+            "TB" => 1_000_000_000_000,
+            "KIB" => 1 << 10,
+            "MIB" => 1 << 20,
+            "GIB" => 1 << 30,
+            "TIB" => 1 << 40,
+            unit => return Err(format!("unknown size unit `{unit}`, expected B, KB, MB or GB")),
+        };
+        Ok(Self((value * scale as f64) as u64))
+    }
+}
+
+impl fmt::Display for CacheSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match NumberPrefix::decimal(self.0 as f32) {
+            NumberPrefix::Standalone(size) => write!(f, "{size:.1} B"),
+            NumberPrefix::Prefixed(prefix, size) => write!(f, "{size:.1} {prefix}B"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,4 +506,37 @@ mod tests {
                 - Block 2 (2.0 B)\n";
         assert_str_eq!(format!("{cache}"), expected);
     }
+
+    #[test]
+    fn can_parse_cache_size() {
+        assert_eq!("500".parse(), Ok(CacheSize(500)));
+        assert_eq!("5GB".parse(), Ok(CacheSize(5_000_000_000)));
+        assert_eq!("1.5 MB".parse(), Ok(CacheSize(1_500_000)));
+        assert_eq!("2GiB".parse(), Ok(CacheSize(2 << 30)));
+        assert!("5 apples".parse::<CacheSize>().is_err());
+        assert_eq!(CacheSize(4_230_000).to_string(), "4.2 MB");
+    }
+
+    #[test]
+    fn gc_plan_selects_old_and_oversized_entries() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 86400);
+        let entry = |name: &str, size, days_old| CacheEntry {
+            kind: CacheKind::RpcStorage,
+            path: PathBuf::from(name),
+            size,
+            modified: now - Duration::from_secs(days_old * 86400),
+        };
+        let entries = || vec![entry("a", 10, 40), entry("b", 20, 10), entry("c", 30, 5)];
+
+        let plan = GcPlan::new(entries(), None, Some(Duration::from_secs(30 * 86400)), now);
+        assert_eq!(plan.remove, vec![entry("a", 10, 40)]);
+        assert_eq!(plan.freed(), 10);
+
+        let plan = GcPlan::new(entries(), Some(35), None, now);
+        assert_eq!(plan.remove, vec![entry("a", 10, 40), entry("b", 20, 10)]);
+        assert_eq!(plan.kept(), 30);
+
+        let plan = GcPlan::new(entries(), Some(100), None, now);
+        assert!(plan.remove.is_empty());
+    }
 }
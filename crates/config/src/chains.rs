@@ -0,0 +1,561 @@
+//! The built-in chains database: the chains of forge-std's `StdChains`, with the same aliases,
+//! IDs and RPC URLs, and the zkSync Era chains, with their base tokens and whether they publish
+//! their data to their settlement layer.
+//!
+//! As in forge-std, the RPC URL of a chain is overridden by the `${ALIAS}_RPC_URL` environment
+//! variable, e.g. `ARBITRUM_ONE_RPC_URL`.
+
+/// The token a chain pays its fees with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseToken {
+    /// The name of the token.
+    pub name: &'static str,
+    /// The symbol of the token.
+    pub symbol: &'static str,
+    /// The decimals of the token.
+    pub decimals: u8,
+}
+
+impl BaseToken {
+    /// Ether, the base token of Ethereum and of most Era chains.
+    pub const ETHER: Self = Self { name: "Ether", symbol: "ETH", decimals: 18 };
+}
+
+/// A chain of the built-in database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChainInfo {
+    /// The alias of the chain, also the `rpc_endpoints` alias setting its RPC URL.
+    pub alias: &'static str,
+    /// The name of the chain.
+    pub name: &'static str,
+    /// The chain ID.
+    pub id: u64,
+    /// The public RPC URL of the chain, used unless its alias is set in the `rpc_endpoints` or
+    /// its [rpc_url_var](Self::rpc_url_var) is set.
+    pub rpc_url: &'static str,
+    /// The API URL of the block explorer of the chain, if it has an Etherscan compatible one.
+    pub explorer_api_url: Option<&'static str>,
+    /// The token paying the fees of the chain.
+    pub base_token: BaseToken,
+    /// Whether the chain is a zkSync Era chain, executing its transactions in EraVM.
+    pub is_era: bool,
+    /// Whether the chain is a validium, keeping its data off its settlement layer.
+    pub is_validium: bool,
+    /// The chain ID of the settlement layer of the chain, if it's not a settlement layer itself.
+    pub settlement_chain_id: Option<u64>,
+}
+
+impl ChainInfo {
+    /// Returns the chain with the alias `alias`.
+    pub fn by_alias(alias: &str) -> Option<&'static Self> {
+        CHAINS.iter().find(|chain| chain.alias == alias)
+    }
+
+    /// Returns the chain with the ID `id`.
+    pub fn by_id(id: u64) -> Option<&'static Self> {
+        CHAINS.iter().find(|chain| chain.id == id)
+    }
+
+    /// Returns the environment variable setting the RPC URL of the chain, e.g. `ZKSYNC_RPC_URL`,
+    /// as in forge-std.
+    pub fn rpc_url_var(&self) -> String {
+        format!("{}_RPC_URL", self.alias.to_uppercase())
+    }
+}
+
+/// The chains of the built-in database.
+pub static CHAINS: &[ChainInfo] = &[
+    ChainInfo {
+        alias: "anvil",
+        name: "Anvil",
+        id: 31337,
+        rpc_url: "http://127.0.0.1:8545",
+        explorer_api_url: None,
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "mainnet",
+        name: "Mainnet",
+        id: 1,
+        rpc_url: "https://eth.llamarpc.com",
+        explorer_api_url: Some("https://api.etherscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "sepolia",
+        name: "Sepolia",
+        id: 11155111,
+        rpc_url: "https://ethereum-sepolia-rpc.publicnode.com",
+        explorer_api_url: Some("https://api-sepolia.etherscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "holesky",
+        name: "Holesky",
+        id: 17000,
+        rpc_url: "https://rpc.holesky.ethpandaops.io",
+        explorer_api_url: Some("https://api-holesky.etherscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "optimism",
+        name: "Optimism",
+        id: 10,
+        rpc_url: "https://mainnet.optimism.io",
+        explorer_api_url: Some("https://api-optimistic.etherscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "optimism_sepolia",
+        name: "Optimism Sepolia",
+        id: 11155420,
+        rpc_url: "https://sepolia.optimism.io",
+        explorer_api_url: Some("https://api-sepolia-optimistic.etherscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "arbitrum_one",
+        name: "Arbitrum One",
+        id: 42161,
+        rpc_url: "https://arb1.arbitrum.io/rpc",
+        explorer_api_url: Some("https://api.arbiscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "arbitrum_one_sepolia",
+        name: "Arbitrum One Sepolia",
+        id: 421614,
+        rpc_url: "https://sepolia-rollup.arbitrum.io/rpc",
+        explorer_api_url: Some("https://api-sepolia.arbiscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "arbitrum_nova",
+        name: "Arbitrum Nova",
+        id: 42170,
+        rpc_url: "https://nova.arbitrum.io/rpc",
+        explorer_api_url: Some("https://api-nova.arbiscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "polygon",
+        name: "Polygon",
+        id: 137,
+        rpc_url: "https://polygon-rpc.com",
+        explorer_api_url: Some("https://api.polygonscan.com/api"),
+        base_token: BaseToken { name: "Polygon Ecosystem Token", symbol: "POL", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "polygon_amoy",
+        name: "Polygon Amoy",
+        id: 80002,
+        rpc_url: "https://rpc-amoy.polygon.technology",
+        explorer_api_url: Some("https://api-amoy.polygonscan.com/api"),
+        base_token: BaseToken { name: "Polygon Ecosystem Token", symbol: "POL", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "avalanche",
+        name: "Avalanche",
+        id: 43114,
+        rpc_url: "https://api.avax.network/ext/bc/C/rpc",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "Avalanche", symbol: "AVAX", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "avalanche_fuji",
+        name: "Avalanche Fuji",
+        id: 43113,
+        rpc_url: "https://api.avax-test.network/ext/bc/C/rpc",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "Avalanche", symbol: "AVAX", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "bnb_smart_chain",
+        name: "BNB Smart Chain",
+        id: 56,
+        rpc_url: "https://bsc-dataseed1.binance.org",
+        explorer_api_url: Some("https://api.bscscan.com/api"),
+        base_token: BaseToken { name: "BNB", symbol: "BNB", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "bnb_smart_chain_testnet",
+        name: "BNB Smart Chain Testnet",
+        id: 97,
+        rpc_url: "https://rpc.ankr.com/bsc_testnet_chapel",
+        explorer_api_url: Some("https://api-testnet.bscscan.com/api"),
+        base_token: BaseToken { name: "BNB", symbol: "tBNB", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "gnosis_chain",
+        name: "Gnosis Chain",
+        id: 100,
+        rpc_url: "https://rpc.gnosischain.com",
+        explorer_api_url: Some("https://api.gnosisscan.io/api"),
+        base_token: BaseToken { name: "xDAI", symbol: "xDAI", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "moonbeam",
+        name: "Moonbeam",
+        id: 1284,
+        rpc_url: "https://rpc.api.moonbeam.network",
+        explorer_api_url: Some("https://api-moonbeam.moonscan.io/api"),
+        base_token: BaseToken { name: "Glimmer", symbol: "GLMR", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "moonriver",
+        name: "Moonriver",
+        id: 1285,
+        rpc_url: "https://rpc.api.moonriver.moonbeam.network",
+        explorer_api_url: Some("https://api-moonriver.moonscan.io/api"),
+        base_token: BaseToken { name: "Moonriver", symbol: "MOVR", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "moonbase",
+        name: "Moonbase",
+        id: 1287,
+        rpc_url: "https://rpc.testnet.moonbeam.network",
+        explorer_api_url: Some("https://api-moonbase.moonscan.io/api"),
+        base_token: BaseToken { name: "Dev", symbol: "DEV", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "base_sepolia",
+        name: "Base Sepolia",
+        id: 84532,
+        rpc_url: "https://sepolia.base.org",
+        explorer_api_url: Some("https://api-sepolia.basescan.org/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "base",
+        name: "Base",
+        id: 8453,
+        rpc_url: "https://mainnet.base.org",
+        explorer_api_url: Some("https://api.basescan.org/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "blast_sepolia",
+        name: "Blast Sepolia",
+        id: 168587773,
+        rpc_url: "https://sepolia.blast.io",
+        explorer_api_url: Some("https://api-sepolia.blastscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "blast",
+        name: "Blast",
+        id: 81457,
+        rpc_url: "https://rpc.blast.io",
+        explorer_api_url: Some("https://api.blastscan.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "fantom_opera",
+        name: "Fantom Opera",
+        id: 250,
+        rpc_url: "https://rpc.ankr.com/fantom/",
+        explorer_api_url: Some("https://api.ftmscan.com/api"),
+        base_token: BaseToken { name: "Fantom", symbol: "FTM", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "fantom_opera_testnet",
+        name: "Fantom Opera Testnet",
+        id: 4002,
+        rpc_url: "https://rpc.ankr.com/fantom_testnet/",
+        explorer_api_url: Some("https://api-testnet.ftmscan.com/api"),
+        base_token: BaseToken { name: "Fantom", symbol: "FTM", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "fraxtal",
+        name: "Fraxtal",
+        id: 252,
+        rpc_url: "https://rpc.frax.com",
+        explorer_api_url: Some("https://api.fraxscan.com/api"),
+        base_token: BaseToken { name: "Frax Ether", symbol: "frxETH", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "fraxtal_testnet",
+        name: "Fraxtal Testnet",
+        id: 2522,
+        rpc_url: "https://rpc.testnet.frax.com",
+        explorer_api_url: Some("https://api-holesky.fraxscan.com/api"),
+        base_token: BaseToken { name: "Frax Ether", symbol: "frxETH", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "berachain_bartio_testnet",
+        name: "Berachain bArtio Testnet",
+        id: 80084,
+        rpc_url: "https://bartio.rpc.berachain.com",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "BERA", symbol: "BERA", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "flare",
+        name: "Flare",
+        id: 14,
+        rpc_url: "https://flare-api.flare.network/ext/C/rpc",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "Flare", symbol: "FLR", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "flare_coston2",
+        name: "Flare Coston2",
+        id: 114,
+        rpc_url: "https://coston2-api.flare.network/ext/C/rpc",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "Coston2 Flare", symbol: "C2FLR", decimals: 18 },
+        is_era: false,
+        is_validium: false,
+        settlement_chain_id: None,
+    },
+    ChainInfo {
+        alias: "zksync",
+        name: "zkSync Era",
+        id: 324,
+        rpc_url: "https://mainnet.era.zksync.io",
+        explorer_api_url: Some("https://block-explorer-api.mainnet.zksync.io/api"),
+        base_token: BaseToken::ETHER,
+        is_era: true,
+        is_validium: false,
+        settlement_chain_id: Some(1),
+    },
+    ChainInfo {
+        alias: "zksync_sepolia",
+        name: "zkSync Era Sepolia",
+        id: 300,
+        rpc_url: "https://sepolia.era.zksync.dev",
+        explorer_api_url: Some("https://block-explorer-api.sepolia.zksync.dev/api"),
+        base_token: BaseToken::ETHER,
+        is_era: true,
+        is_validium: false,
+        settlement_chain_id: Some(11155111),
+    },
+    ChainInfo {
+        alias: "abstract_testnet",
+        name: "Abstract Testnet",
+        id: 11124,
+        rpc_url: "https://api.testnet.abs.xyz",
+        explorer_api_url: Some("https://api-sepolia.abscan.org/api"),
+        base_token: BaseToken::ETHER,
+        is_era: true,
+        is_validium: false,
+        settlement_chain_id: Some(11155111),
+    },
+    ChainInfo {
+        alias: "cronos_zkevm",
+        name: "Cronos zkEVM",
+        id: 388,
+        rpc_url: "https://mainnet.zkevm.cronos.org",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "zkCRO", symbol: "zkCRO", decimals: 18 },
+        is_era: true,
+        is_validium: true,
+        settlement_chain_id: Some(1),
+    },
+    ChainInfo {
+        alias: "cronos_zkevm_testnet",
+        name: "Cronos zkEVM Testnet",
+        id: 240,
+        rpc_url: "https://testnet.zkevm.cronos.org",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "zkTCRO", symbol: "zkTCRO", decimals: 18 },
+        is_era: true,
+        is_validium: true,
+        settlement_chain_id: Some(11155111),
+    },
+    ChainInfo {
+        alias: "sophon",
+        name: "Sophon",
+        id: 50104,
+        rpc_url: "https://rpc.sophon.xyz",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "Sophon", symbol: "SOPH", decimals: 18 },
+        is_era: true,
+        is_validium: true,
+        settlement_chain_id: Some(1),
+    },
+    ChainInfo {
+        alias: "sophon_testnet",
+        name: "Sophon Testnet",
+        id: 531050104,
+        rpc_url: "https://rpc.testnet.sophon.xyz",
+        explorer_api_url: None,
+        base_token: BaseToken { name: "Sophon", symbol: "SOPH", decimals: 18 },
+        is_era: true,
+        is_validium: true,
+        settlement_chain_id: Some(11155111),
+    },
+    ChainInfo {
+        alias: "zklink_nova",
+        name: "zkLink Nova",
+        id: 810180,
+        rpc_url: "https://rpc.zklink.io",
+        explorer_api_url: None,
+        base_token: BaseToken::ETHER,
+        is_era: true,
+        is_validium: true,
+        settlement_chain_id: Some(1),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn chains_are_unique() {
+        let aliases = CHAINS.iter().map(|chain| chain.alias).collect::<HashSet<_>>();
+        let ids = CHAINS.iter().map(|chain| chain.id).collect::<HashSet<_>>();
+        assert_eq!(aliases.len(), CHAINS.len());
+        assert_eq!(ids.len(), CHAINS.len());
+
+        for chain in CHAINS {
+            if let Some(settlement) = chain.settlement_chain_id {
+                assert!(chain.is_era, "{}", chain.alias);
+                assert!(ChainInfo::by_id(settlement).is_some(), "{}", chain.alias);
+            }
+        }
+    }
+
+    #[test]
+    fn finds_era_chains() {
+        let zksync = ChainInfo::by_alias("zksync").unwrap();
+        assert_eq!(zksync.id, 324);
+        assert!(zksync.is_era && !zksync.is_validium);
+        assert_eq!(zksync.rpc_url_var(), "ZKSYNC_RPC_URL");
+        assert_eq!(ChainInfo::by_id(300).unwrap().alias, "zksync_sepolia");
+        assert_eq!(ChainInfo::by_alias("sophon").unwrap().base_token.symbol, "SOPH");
+        assert!(ChainInfo::by_alias("unknown").is_none());
+    }
+
+    #[test]
+    fn ports_forge_std_chains() {
+        for (alias, id) in [
+            ("anvil", 31337),
+            ("mainnet", 1),
+            ("sepolia", 11155111),
+            ("holesky", 17000),
+            ("optimism", 10),
+            ("optimism_sepolia", 11155420),
+            ("arbitrum_one", 42161),
+            ("arbitrum_one_sepolia", 421614),
+            ("arbitrum_nova", 42170),
+            ("polygon", 137),
+            ("polygon_amoy", 80002),
+            ("avalanche", 43114),
+            ("avalanche_fuji", 43113),
+            ("bnb_smart_chain", 56),
+            ("bnb_smart_chain_testnet", 97),
+            ("gnosis_chain", 100),
+            ("moonbeam", 1284),
+            ("moonriver", 1285),
+            ("moonbase", 1287),
+            ("base_sepolia", 84532),
+            ("base", 8453),
+            ("blast_sepolia", 168587773),
+            ("blast", 81457),
+            ("fantom_opera", 250),
+            ("fantom_opera_testnet", 4002),
+            ("fraxtal", 252),
+            ("fraxtal_testnet", 2522),
+            ("berachain_bartio_testnet", 80084),
+            ("flare", 14),
+            ("flare_coston2", 114),
+        ] {
+            let chain = ChainInfo::by_alias(alias).unwrap_or_else(|| panic!("{alias}"));
+            assert_eq!(chain.id, id, "{alias}");
+            assert!(!chain.is_era, "{alias}");
+        }
+        let arbitrum = ChainInfo::by_alias("arbitrum_one").unwrap();
+        assert_eq!(arbitrum.rpc_url_var(), "ARBITRUM_ONE_RPC_URL");
+        assert_eq!(ChainInfo::by_id(137).unwrap().base_token.symbol, "POL");
+    }
+}
@@ -15,6 +15,7 @@ use std::{
     collections::BTreeMap,
     fmt,
     ops::{Deref, DerefMut},
+    str::FromStr,
     time::Duration,
 };
 
@@ -168,6 +169,43 @@ impl DerefMut for ResolvedEtherscanConfigs {
     }
 }
 
+/// The providers contracts can be verified with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationProviderType {
+    #[default]
+    Etherscan,
+    Sourcify,
+    Blockscout,
+    #[serde(rename = "zksync")]
+    ZkSync,
+}
+
+impl FromStr for VerificationProviderType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "e" | "etherscan" => Ok(VerificationProviderType::Etherscan),
+            "s" | "sourcify" => Ok(VerificationProviderType::Sourcify),
+            "b" | "blockscout" => Ok(VerificationProviderType::Blockscout),
+            "z" | "zksync" => Ok(VerificationProviderType::ZkSync),
+            _ => Err(format!("Unknown provider: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for VerificationProviderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationProviderType::Etherscan => f.write_str("etherscan"),
+            VerificationProviderType::Sourcify => f.write_str("sourcify"),
+            VerificationProviderType::Blockscout => f.write_str("blockscout"),
+            VerificationProviderType::ZkSync => f.write_str("zksync"),
+        }
+    }
+}
+
 /// Represents all info required to create an etherscan client
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EtherscanConfig {
@@ -179,6 +217,11 @@ pub struct EtherscanConfig {
     pub url: Option<String>,
     /// The etherscan API KEY that's required to make requests
     pub key: EtherscanApiKey,
+    /// The verification provider to use for this chain, e.g. `blockscout` or `zksync`.
+    ///
+    /// If not set, contracts are verified against the etherscan compatible `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<VerificationProviderType>,
 }
 
 // === impl EtherscanConfig ===
@@ -194,7 +237,7 @@ impl EtherscanConfig {
         self,
         alias: Option<&str>,
     ) -> Result<ResolvedEtherscanConfig, EtherscanConfigError> {
-        let EtherscanConfig { chain, mut url, key } = self;
+        let EtherscanConfig { chain, mut url, key, verifier } = self;
 
         if let Some(url) = &mut url {
             *url = interpolate(url)?;
@@ -228,14 +271,21 @@ impl EtherscanConfig {
                 browser_url: chain.etherscan_urls().map(|(_, url)| url.to_string()),
                 key,
                 chain: Some(chain),
+                verifier,
             }),
-            (Some(chain), None) => ResolvedEtherscanConfig::create(key, chain).ok_or_else(|| {
-                let msg = alias.map(|a| format!(" `{a}`")).unwrap_or_default();
-                EtherscanConfigError::UnknownChain(msg, chain)
+            (Some(chain), None) => ResolvedEtherscanConfig::create(key, chain)
+                .map(|config| ResolvedEtherscanConfig { verifier, ..config })
+                .ok_or_else(|| {
+                    let msg = alias.map(|a| format!(" `{a}`")).unwrap_or_default();
+                    EtherscanConfigError::UnknownChain(msg, chain)
+                }),
+            (None, Some(api_url)) => Ok(ResolvedEtherscanConfig {
+                api_url,
+                browser_url: None,
+                key,
+                chain: None,
+                verifier,
             }),
-            (None, Some(api_url)) => {
-                Ok(ResolvedEtherscanConfig { api_url, browser_url: None, key, chain: None })
-            }
             (None, None) => {
                 let msg = alias
                     .map(|a| format!(" for Etherscan config with unknown alias `{a}`"))
@@ -260,6 +310,9 @@ pub struct ResolvedEtherscanConfig {
     /// The chain name or EIP-155 chain ID.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chain: Option<Chain>,
+    /// The verification provider configured for the chain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<VerificationProviderType>,
 }
 
 // === impl ResolvedEtherscanConfig ===
@@ -274,6 +327,7 @@ impl ResolvedEtherscanConfig {
             browser_url: Some(browser_url.to_string()),
             key: api_key.into(),
             chain: Some(chain),
+            verifier: None,
         })
     }
 
@@ -302,7 +356,7 @@ impl ResolvedEtherscanConfig {
         self,
     ) -> Result<foundry_block_explorers::Client, foundry_block_explorers::errors::EtherscanError>
     {
-        let ResolvedEtherscanConfig { api_url, browser_url, key: api_key, chain } = self;
+        let ResolvedEtherscanConfig { api_url, browser_url, key: api_key, chain, .. } = self;
         let (mainnet_api, mainnet_url) = NamedChain::Mainnet.etherscan_urls().expect("exist; qed");
 
         let cache = chain
@@ -433,6 +487,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: None,
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                verifier: None,
             },
         );
 
@@ -450,6 +505,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                verifier: None,
             },
         );
 
@@ -468,6 +524,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Env(format!("${{{env}}}")),
+                verifier: None,
             },
         );
 
@@ -494,6 +551,7 @@ mod tests {
                 chain: None,
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                verifier: None,
             },
         );
 
@@ -508,6 +566,7 @@ mod tests {
             chain: None,
             url: Some("https://api.etherscan.io/api".to_string()),
             key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+            verifier: None,
         };
         let resolved = config.clone().resolve(Some("base_sepolia")).unwrap();
         assert_eq!(resolved.chain, Some(Chain::base_sepolia()));
@@ -515,4 +574,37 @@ mod tests {
         let resolved = config.resolve(Some("base-sepolia")).unwrap();
         assert_eq!(resolved.chain, Some(Chain::base_sepolia()));
     }
+
+    #[test]
+    fn resolve_etherscan_config_with_verifier() {
+        let config = EtherscanConfig {
+            chain: Some(NamedChain::ZkSync.into()),
+            url: Some("https://block-explorer-api.mainnet.zksync.io/api".to_string()),
+            key: EtherscanApiKey::Key(String::new()),
+            verifier: Some(VerificationProviderType::Blockscout),
+        };
+        let resolved = config.resolve(Some("zksync-era")).unwrap();
+        assert_eq!(resolved.chain, Some(NamedChain::ZkSync.into()));
+        assert_eq!(resolved.verifier, Some(VerificationProviderType::Blockscout));
+        assert_eq!(resolved.api_url, "https://block-explorer-api.mainnet.zksync.io/api");
+    }
+
+    #[test]
+    fn parse_etherscan_config_verifier() {
+        let config: EtherscanConfig = toml::from_str(
+            r#"key = ""
+            chain = 324
+            url = "https://zksync2-mainnet-explorer.zksync.io/contract_verification"
+            verifier = "zksync""#,
+        )
+        .unwrap();
+        assert_eq!(config.verifier, Some(VerificationProviderType::ZkSync));
+
+        let err = toml::from_str::<EtherscanConfig>(
+            r#"key = ""
+            verifier = "etherscann""#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("etherscann"), "{err}");
+    }
 }
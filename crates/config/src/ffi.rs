@@ -0,0 +1,99 @@
+//! The allowlist of the commands the `ffi` cheatcodes can execute.
+
+use globset::GlobBuilder;
+use serde::{Deserialize, Serialize};
+
+/// The `args` pattern matching any number of remaining arguments.
+pub const ANY_ARGS: &str = "**";
+
+/// A command the `ffi` cheatcodes are allowed to execute.
+///
+/// `command` is a glob pattern matching the executable, and `args` glob patterns matching its
+/// arguments one by one. A last `args` pattern of `**` matches any remaining arguments, and
+/// leaving `args` out allows any arguments.
+///
+/// ```toml
+/// ffi_allowlist = [
+///     { command = "node", args = ["scripts/*.js", "**"] },
+///     { command = "/usr/bin/jq" },
+/// ]
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FfiAllowedCommand {
+    /// The pattern matching the executable.
+    pub command: String,
+    /// The patterns matching the arguments, any arguments if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+}
+
+impl FfiAllowedCommand {
+    /// Returns whether the command allows executing `input`, the executable followed by its
+    /// arguments.
+    pub fn allows(&self, input: &[String]) -> bool {
+        let Some((command, args)) = input.split_first() else { return false };
+        if !matches(&self.command, command) {
+            return false
+        }
+        let Some(patterns) = &self.args else { return true };
+
+        let (patterns, any_remaining) = match patterns.split_last() {
+            Some((last, patterns)) if last == ANY_ARGS => (patterns, true),
+            _ => (&patterns[..], false),
+        };
+        if args.len() < patterns.len() || (!any_remaining && args.len() != patterns.len()) {
+            return false
+        }
+        patterns.iter().zip(args).all(|(pattern, arg)| matches(pattern, arg))
+    }
+}
+
+/// Returns whether one of the commands of `allowlist` allows executing `input`.
+pub fn is_allowed(allowlist: &[FfiAllowedCommand], input: &[String]) -> bool {
+    allowlist.iter().any(|command| command.allows(input))
+}
+
+/// Returns whether `value` matches the glob `pattern`, whose wildcards don't match `/`. Invalid
+/// patterns match nothing.
+fn matches(pattern: &str, value: &str) -> bool {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .is_ok_and(|glob| glob.compile_matcher().is_match(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_commands() {
+        let node = FfiAllowedCommand {
+            command: "node".to_string(),
+            args: Some(vec!["scripts/*.js".to_string(), ANY_ARGS.to_string()]),
+        };
+        assert!(node.allows(&input(&["node", "scripts/deploy.js"])));
+        assert!(node.allows(&input(&["node", "scripts/deploy.js", "--json", "1"])));
+        assert!(!node.allows(&input(&["node", "scripts/nested/deploy.js"])));
+        assert!(!node.allows(&input(&["node", "-e", "process.exit()"])));
+        assert!(!node.allows(&input(&["nodejs", "scripts/deploy.js"])));
+
+        let jq = FfiAllowedCommand { command: "/usr/bin/jq".to_string(), args: None };
+        assert!(jq.allows(&input(&["/usr/bin/jq", ".a", "file.json"])));
+        assert!(!jq.allows(&input(&["jq"])));
+
+        let echo = FfiAllowedCommand { command: "echo".to_string(), args: Some(vec![]) };
+        assert!(echo.allows(&input(&["echo"])));
+        assert!(!echo.allows(&input(&["echo", "gm"])));
+
+        assert!(is_allowed(&[echo, jq], &input(&["/usr/bin/jq"])));
+        assert!(!is_allowed(&[node], &input(&[])));
+
+        let invalid = FfiAllowedCommand { command: "node[".to_string(), args: None };
+        assert!(!invalid.allows(&input(&["node["])));
+    }
+}
@@ -0,0 +1,127 @@
+//! The block of the state fork.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The block the state fork is pinned to, either a block number or a tag resolved once by
+/// `forge test` and recorded in the fork lockfile.
+///
+/// It's (de)serialized like the block numbers `fork_block_number` used to be limited to, so it
+/// also accepts them as decimal or hex strings, e.g. from environment variables.
+///
+/// ```toml
+/// fork_block_number = 19579636
+/// fork_block_number = "latest-safe"
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForkBlock {
+    /// A block number.
+    Number(u64),
+    /// The latest block when resolved.
+    Latest,
+    /// The latest safe block when resolved.
+    Safe,
+    /// The latest finalized block when resolved.
+    Finalized,
+}
+
+impl ForkBlock {
+    /// Returns the block number, if it's not a tag.
+    pub fn number(self) -> Option<u64> {
+        match self {
+            Self::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the block is a tag to resolve.
+    pub fn is_tag(self) -> bool {
+        !matches!(self, Self::Number(_))
+    }
+}
+
+impl From<u64> for ForkBlock {
+    fn from(number: u64) -> Self {
+        Self::Number(number)
+    }
+}
+
+impl FromStr for ForkBlock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "safe" | "latest-safe" => Ok(Self::Safe),
+            "finalized" | "latest-finalized" => Ok(Self::Finalized),
+            s => match s.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => s.parse(),
+            }
+            .map(Self::Number)
+            .map_err(|_| {
+                format!(
+                    "invalid fork block `{s}`, expected a block number, `latest`, `latest-safe` or \
+                     `latest-finalized`"
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ForkBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => number.fmt(f),
+            Self::Latest => f.write_str("latest"),
+            Self::Safe => f.write_str("latest-safe"),
+            Self::Finalized => f.write_str("latest-finalized"),
+        }
+    }
+}
+
+impl Serialize for ForkBlock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Number(number) => serializer.serialize_u64(*number),
+            tag => serializer.collect_str(tag),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ForkBlock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Block {
+            Number(u64),
+            Text(String),
+        }
+
+        match Block::deserialize(deserializer)? {
+            Block::Number(number) => Ok(Self::Number(number)),
+            Block::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fork_blocks() {
+        assert_eq!("100".parse::<ForkBlock>().unwrap(), ForkBlock::Number(100));
+        assert_eq!("latest-safe".parse::<ForkBlock>().unwrap(), ForkBlock::Safe);
+        assert_eq!("finalized".parse::<ForkBlock>().unwrap(), ForkBlock::Finalized);
+        assert_eq!("0x64".parse::<ForkBlock>().unwrap(), ForkBlock::Number(100));
+        assert!("pending".parse::<ForkBlock>().is_err());
+
+        let block: ForkBlock = serde_json::from_str("\"latest\"").unwrap();
+        assert_eq!(block, ForkBlock::Latest);
+        let block: ForkBlock = serde_json::from_str("\"100\"").unwrap();
+        assert_eq!(block, ForkBlock::Number(100));
+        assert_eq!(serde_json::to_string(&ForkBlock::Number(7)).unwrap(), "7");
+        assert_eq!(serde_json::to_string(&ForkBlock::Safe).unwrap(), "\"latest-safe\"");
+    }
+}
@@ -19,6 +19,13 @@ pub struct FuzzConfig {
     pub max_test_rejects: u32,
     /// Optional seed for the fuzzing RNG algorithm
     pub seed: Option<U256>,
+    /// The number of workers the runs of a single fuzz test are split across, `1` executes them
+    /// sequentially.
+    ///
+    /// Every worker executes its share of the runs on its own copy of the state after `setUp`,
+    /// with an RNG derived from `seed`, so the result is the same for the same seed and number of
+    /// workers.
+    pub parallel: u32,
     /// The fuzz dictionary configuration
     #[serde(flatten)]
     pub dictionary: FuzzDictionaryConfig,
@@ -30,6 +37,7 @@ impl Default for FuzzConfig {
             runs: 256,
             max_test_rejects: 65536,
             seed: None,
+            parallel: 1,
             dictionary: FuzzDictionaryConfig::default(),
         }
     }
@@ -56,6 +64,7 @@ impl InlineConfigParser for FuzzConfig {
             match key.as_str() {
                 "runs" => conf_clone.runs = parse_config_u32(key, value)?,
                 "max-test-rejects" => conf_clone.max_test_rejects = parse_config_u32(key, value)?,
+                "parallel" => conf_clone.parallel = parse_config_u32(key, value)?,
                 "dictionary-weight" => {
                     conf_clone.dictionary.dictionary_weight = parse_config_u32(key, value)?
                 }
@@ -76,6 +85,9 @@ pub struct FuzzDictionaryConfig {
     pub include_storage: bool,
     /// The flag indicating whether to include push bytes values
     pub include_push_bytes: bool,
+    /// The flag indicating whether to include the data returned by the zkSync Era system contracts
+    /// to the ZK-VM executions, e.g. the nonces and balances read by the bootloader
+    pub include_zk_return_data: bool,
     /// How many addresses to record at most.
     /// Once the fuzzer exceeds this limit, it will start evicting random entries
     ///
@@ -94,6 +106,7 @@ impl Default for FuzzDictionaryConfig {
             dictionary_weight: 40,
             include_storage: true,
             include_push_bytes: true,
+            include_zk_return_data: true,
             // limit this to 300MB
             max_fuzz_dictionary_addresses: (300 * 1024 * 1024) / 20,
             // limit this to 200MB
@@ -122,11 +135,13 @@ mod tests {
         let configs = &[
             "forge-config: default.fuzz.runs = 42424242".to_string(),
             "forge-config: default.fuzz.dictionary-weight = 42".to_string(),
+            "forge-config: default.fuzz.parallel = 4".to_string(),
         ];
         let base_config = FuzzConfig::default();
         let merged: FuzzConfig = base_config.try_merge(configs).expect("No errors").unwrap();
         assert_eq!(merged.runs, 42424242);
         assert_eq!(merged.dictionary.dictionary_weight, 42);
+        assert_eq!(merged.parallel, 4);
     }
 
     #[test]
@@ -0,0 +1,74 @@
+//! Configuration of contracts that are deployed before running tests.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The code of a contract in the `[test.genesis]` section.
+///
+/// The code is set directly in the in-memory state before `setUp()` is called, so constructors are
+/// not executed.
+///
+/// ```toml
+/// [test.genesis]
+/// 0x1000000000000000000000000000000000000001 = "src/Token.sol:Token"
+/// 0x1000000000000000000000000000000000000002 = { bytecode = "genesis/Oracle.hex", zk_bytecode = "genesis/Oracle.zk.hex" }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GenesisContract {
+    /// The deployed bytecode of a compiled artifact, identified by `<name>` or `<path>:<name>`.
+    Artifact(String),
+    /// Raw deployed bytecode read from hex encoded files.
+    Bytecode {
+        /// The file with the EVM deployed bytecode.
+        bytecode: PathBuf,
+        /// The file with the EraVM deployed bytecode, required when running in zkEVM mode.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        zk_bytecode: Option<PathBuf>,
+    },
+}
+
+impl GenesisContract {
+    /// Joins all relative bytecode file paths with the given root.
+    pub fn join_all(&mut self, root: impl AsRef<Path>) {
+        let root = root.as_ref();
+        if let Self::Bytecode { bytecode, zk_bytecode } = self {
+            *bytecode = root.join(&*bytecode);
+            if let Some(zk_bytecode) = zk_bytecode {
+                *zk_bytecode = root.join(&*zk_bytecode);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Address};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parse_genesis_contracts() {
+        let s = r#"
+            0x1000000000000000000000000000000000000001 = "src/Token.sol:Token"
+            0x1000000000000000000000000000000000000002 = { bytecode = "Oracle.hex", zk_bytecode = "Oracle.zk.hex" }
+        "#;
+        let genesis: BTreeMap<Address, GenesisContract> = toml::from_str(s).unwrap();
+        assert_eq!(
+            genesis,
+            BTreeMap::from([
+                (
+                    address!("1000000000000000000000000000000000000001"),
+                    GenesisContract::Artifact("src/Token.sol:Token".to_string())
+                ),
+                (
+                    address!("1000000000000000000000000000000000000002"),
+                    GenesisContract::Bytecode {
+                        bytecode: "Oracle.hex".into(),
+                        zk_bytecode: Some("Oracle.zk.hex".into()),
+                    }
+                ),
+            ])
+        );
+    }
+}
@@ -11,7 +11,8 @@ mod natspec;
 
 pub const INLINE_CONFIG_FUZZ_KEY: &str = "fuzz";
 pub const INLINE_CONFIG_INVARIANT_KEY: &str = "invariant";
-const INLINE_CONFIG_PREFIX: &str = "forge-config";
+pub const INLINE_CONFIG_MAX_DURATION_KEY: &str = "max_duration";
+pub(crate) const INLINE_CONFIG_PREFIX: &str = "forge-config";
 
 static INLINE_CONFIG_PREFIX_SELECTED_PROFILE: Lazy<String> = Lazy::new(|| {
     let selected_profile = Config::selected_profile().to_string();
@@ -57,6 +58,11 @@ impl<T> InlineConfig<T> {
         };
         self.configs.insert(key, config);
     }
+
+    /// Removes the inline configurations of all the test functions of a contract.
+    pub fn remove_contract(&mut self, contract_id: &str) {
+        self.configs.retain(|key, _| key.contract != contract_id);
+    }
 }
 
 /// Represents a (test-contract, test-function) pair
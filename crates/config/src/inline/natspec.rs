@@ -1,13 +1,13 @@
 use super::{remove_whitespaces, INLINE_CONFIG_PREFIX, INLINE_CONFIG_PREFIX_SELECTED_PROFILE};
 use foundry_compilers::{
     artifacts::{ast::NodeType, Node},
-    ProjectCompileOutput,
+    ArtifactId, ConfigurableContractArtifact, ProjectCompileOutput,
 };
 use serde_json::Value;
 use std::{collections::BTreeMap, path::Path};
 
 /// Convenient struct to hold in-line per-test configurations
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct NatSpec {
     /// The parent contract of the natspec
     pub contract: String,
@@ -26,21 +26,36 @@ impl NatSpec {
     /// a solc compiler output. The root path is to express contract base dirs.
     /// That is essential to match per-test configs at runtime.
     pub fn parse(output: &ProjectCompileOutput, root: &Path) -> Vec<Self> {
+        output
+            .artifact_ids()
+            .flat_map(|(id, artifact)| Self::parse_artifact(&id, artifact, root))
+            .collect()
+    }
+
+    /// Extracts the [`NatSpec`] instances of a single artifact, e.g. to re-read the in-line
+    /// configurations of a recompiled test contract.
+    pub fn parse_artifact(
+        id: &ArtifactId,
+        artifact: &ConfigurableContractArtifact,
+        root: &Path,
+    ) -> Vec<Self> {
         let mut natspecs: Vec<Self> = vec![];
-
-        for (id, artifact) in output.artifact_ids() {
-            let Some(ast) = &artifact.ast else { continue };
-            let path = id.source.as_path();
-            let path = path.strip_prefix(root).unwrap_or(path);
-            // id.identifier
-            let contract = format!("{}:{}", path.display(), id.name);
-            let Some(node) = contract_root_node(&ast.nodes, &contract) else { continue };
-            apply(&mut natspecs, &contract, node)
+        let Some(ast) = &artifact.ast else { return natspecs };
+        let contract = Self::contract_id(id, root);
+        if let Some(node) = contract_root_node(&ast.nodes, &contract) {
+            apply(&mut natspecs, &contract, node);
         }
-
         natspecs
     }
 
+    /// Returns the identifier of the contract of an artifact, as the natspecs of its functions
+    /// name it.
+    pub fn contract_id(id: &ArtifactId, root: &Path) -> String {
+        let path = id.source.as_path();
+        let path = path.strip_prefix(root).unwrap_or(path);
+        format!("{}:{}", path.display(), id.name)
+    }
+
     /// Returns a string describing the natspec
     /// context, for debugging purposes 🐞
     /// i.e. `test/Counter.t.sol:CounterTest:testFuzz_SetNumber`
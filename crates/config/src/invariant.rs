@@ -8,9 +8,10 @@ use crate::{
     },
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Contains for invariant testing
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InvariantConfig {
     /// The number of runs that must execute for each invariant test group.
     pub runs: u32,
@@ -28,6 +29,17 @@ pub struct InvariantConfig {
     pub shrink_sequence: bool,
     /// The maximum number of attempts to shrink the sequence
     pub shrink_run_limit: usize,
+    /// Relative selection weights of target functions, keyed by signature (`deposit(uint256)`)
+    /// or selector (`0xb6b55f25`). Functions without a weight have a weight of 1.
+    ///
+    /// Weights returned by `targetSelectorWeights()` in the test contract take precedence.
+    #[serde(default)]
+    pub selector_weights: BTreeMap<String, u32>,
+    /// The number of runs after which the state of a campaign is checkpointed to the cache
+    /// directory, so that `forge test --resume-campaign` can resume it. `0` disables the
+    /// checkpoints.
+    #[serde(default)]
+    pub checkpoint_interval: u32,
 }
 
 impl Default for InvariantConfig {
@@ -40,6 +52,8 @@ impl Default for InvariantConfig {
             dictionary: FuzzDictionaryConfig { dictionary_weight: 80, ..Default::default() },
             shrink_sequence: true,
             shrink_run_limit: 2usize.pow(18_u32),
+            selector_weights: Default::default(),
+            checkpoint_interval: 0,
         }
     }
 }
@@ -56,8 +70,7 @@ impl InlineConfigParser for InvariantConfig {
             return Ok(None)
         }
 
-        // self is Copy. We clone it with dereference.
-        let mut conf_clone = *self;
+        let mut conf_clone = self.clone();
 
         for pair in overrides {
             let key = pair.0;
@@ -68,6 +81,9 @@ impl InlineConfigParser for InvariantConfig {
                 "fail-on-revert" => conf_clone.fail_on_revert = parse_config_bool(key, value)?,
                 "call-override" => conf_clone.call_override = parse_config_bool(key, value)?,
                 "shrink-sequence" => conf_clone.shrink_sequence = parse_config_bool(key, value)?,
+                "checkpoint-interval" => {
+                    conf_clone.checkpoint_interval = parse_config_u32(key, value)?
+                }
                 _ => Err(InlineConfigParserError::InvalidConfigProperty(key.to_string()))?,
             }
         }
@@ -31,12 +31,16 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::RwLock,
 };
 
+/// The profile selected with [`Config::select_profile`].
+static SELECTED_PROFILE: RwLock<Option<Profile>> = RwLock::new(None);
+
 // Macros useful for creating a figment.
 mod macros;
 
@@ -48,11 +52,12 @@ mod endpoints;
 pub use endpoints::{ResolvedRpcEndpoints, RpcEndpoint, RpcEndpoints};
 
 mod etherscan;
+pub use etherscan::{ResolvedEtherscanConfig, VerificationProviderType};
 mod resolve;
 pub use resolve::UnresolvedEnvVarError;
 
 pub mod cache;
-use cache::{Cache, ChainCache};
+use cache::{Cache, CacheEntry, CacheKind, ChainCache};
 
 pub mod fmt;
 pub use fmt::FormatterConfig;
@@ -60,6 +65,24 @@ pub use fmt::FormatterConfig;
 pub mod fs_permissions;
 pub use crate::fs_permissions::FsPermissions;
 
+mod genesis;
+pub use genesis::GenesisContract;
+
+mod bytecode_pin;
+pub use bytecode_pin::BytecodePin;
+
+mod fork_block;
+pub use fork_block::ForkBlock;
+
+pub mod chains;
+pub use chains::ChainInfo;
+
+pub mod ffi;
+pub use ffi::FfiAllowedCommand;
+
+mod zksync;
+pub use zksync::{ZkSolcSettings, ZkSyncConfig};
+
 pub mod error;
 pub use error::SolidityErrorCode;
 
@@ -81,23 +104,32 @@ pub mod providers;
 
 use crate::{
     error::ExtractConfigError,
-    etherscan::{EtherscanConfigError, EtherscanConfigs, ResolvedEtherscanConfig},
+    etherscan::{EtherscanConfigError, EtherscanConfigs},
 };
 use providers::*;
 
 mod fuzz;
 pub use fuzz::{FuzzConfig, FuzzDictionaryConfig};
 
+mod budget;
+pub use budget::{TestBudgetConfig, TestDuration};
+
 mod invariant;
 use crate::fs_permissions::PathPermission;
 pub use invariant::InvariantConfig;
 use providers::remappings::RemappingsProvider;
 
+mod matrix;
+pub use matrix::TestMatrixCell;
+
 mod inline;
 use crate::etherscan::EtherscanEnvProvider;
 pub use inline::{validate_profiles, InlineConfig, InlineConfigError, InlineConfigParser, NatSpec};
 
-use foundry_zksync_compiler::{ZkSolcConfig, ZkSolcConfigBuilder, DEFAULT_ZKSOLC_VERSION};
+use foundry_zksync_compiler::{
+    ZkContractOverride, ZkLibrary, ZkSolcConfig, ZkSolcConfigBuilder, ZkWarningsOverride,
+    DEFAULT_ZKSOLC_VERSION,
+};
 
 /// Foundry configuration
 ///
@@ -208,6 +240,11 @@ pub struct Config {
     pub eth_rpc_url: Option<String>,
     /// JWT secret that should be used for any rpc calls
     pub eth_rpc_jwt: Option<String>,
+    /// url of the ERC-4337 bundler rpc that should be used to submit user operations
+    ///
+    /// `{chain_id}` is replaced with the chain id of the operations `vm.broadcastUserOp` submits
+    /// through it, for scripts submitting them on several chains.
+    pub bundler_url: Option<String>,
     /// etherscan API key, or alias for an `EtherscanConfig` in `etherscan` table
     pub etherscan_api_key: Option<String>,
     /// Multiple etherscan api configs and their aliases
@@ -220,6 +257,18 @@ pub struct Config {
     pub ignored_file_paths: Vec<PathBuf>,
     /// When true, compiler warnings are treated as errors
     pub deny_warnings: bool,
+    /// Expected hashes of the deployed bytecode of contracts, `forge build` fails if a compiled
+    /// contract doesn't match its pinned hash.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assert_bytecode: Vec<BytecodePin>,
+    /// Whether the `assert_bytecode` hashes are computed without the metadata of the bytecode.
+    ///
+    /// The hash is then the keccak256 of the deployed bytecode with its metadata removed, so
+    /// pinned hashes don't change when only the metadata of a contract does.
+    pub assert_bytecode_ignore_metadata: bool,
+    /// The address of the project key `forge verify-attestation` requires the attestations to
+    /// be signed with, unless overridden with `--signer`.
+    pub attestation_signer: Option<Address>,
     /// Only run test functions matching the specified regex pattern.
     #[serde(rename = "match_test")]
     pub test_pattern: Option<RegexWrapper>,
@@ -242,8 +291,34 @@ pub struct Config {
     pub fuzz: FuzzConfig,
     /// Configuration for invariant testing
     pub invariant: InvariantConfig,
+    /// The maximum wall time a single test may take, fuzz and invariant campaigns included.
+    ///
+    /// Can be overridden per test with `forge-config: default.max_duration = 5s`.
+    pub max_duration: Option<TestDuration>,
+    /// Whether tests exceeding `max_duration` only produce a warning instead of failing.
+    pub max_duration_warn_only: bool,
+    /// The chains and hardforks `forge test --matrix` runs the test suite against, one run per
+    /// cell.
+    pub test_matrix: Vec<TestMatrixCell>,
+    /// Whether tests are forbidden to access the network, except for the fork URL, the
+    /// `rpc_endpoints` and `offline_tests_allow_urls`.
+    pub offline_tests: bool,
+    /// Additional URLs tests may access with `offline_tests`.
+    pub offline_tests_allow_urls: Vec<String>,
+    /// Whether `forge test` runs in performance mode, enabled by default in the built-in `ci`
+    /// profile: the verbosity is capped at `-vvv`, the unit tests run without tracing unless
+    /// they fail, and the traces of the other passing tests are dropped without being decoded.
+    pub perf_mode: bool,
     /// Whether to allow ffi cheatcodes in test
     pub ffi: bool,
+    /// The commands the `ffi` cheatcodes can execute when `ffi` is enabled. If set, only these
+    /// commands can be executed.
+    pub ffi_allowlist: Vec<FfiAllowedCommand>,
+    /// Timeout in seconds for the `prompt*` cheatcodes when waiting for terminal input.
+    pub prompt_timeout: u64,
+    /// JSON file mapping prompt texts to answers, used by the `prompt*` cheatcodes when the
+    /// session is not interactive.
+    pub prompt_answers: Option<PathBuf>,
     /// Use the create 2 factory in all cases including tests and non-broadcasting scripts.
     pub always_use_create_2_factory: bool,
     /// The address which will be executing all tests
@@ -254,8 +329,9 @@ pub struct Config {
     pub initial_balance: U256,
     /// the block.number value during EVM execution
     pub block_number: u64,
-    /// pins the block number for the state fork
-    pub fork_block_number: Option<u64>,
+    /// pins the block number for the state fork, or the tag resolved once and recorded in the
+    /// fork lockfile
+    pub fork_block_number: Option<ForkBlock>,
     /// The chain name or EIP-155 chain ID.
     #[serde(rename = "chain_id", alias = "chain")]
     pub chain: Option<Chain>,
@@ -347,6 +423,11 @@ pub struct Config {
     /// If this is `false` and the `bytecode_hash` option above is not `None` solc will issue a
     /// warning.
     pub cbor_metadata: bool,
+    /// Whether to strip the metadata from the bytecode compiled with both solc and zksolc.
+    ///
+    /// This overrides `bytecode_hash` and `cbor_metadata`, so the bytecode doesn't depend on the
+    /// absolute paths of the machine the project is compiled on.
+    pub strip_metadata: bool,
     /// How to treat revert (and require) reason strings.
     #[serde(with = "serde_helpers::display_from_str_opt")]
     pub revert_strings: Option<RevertStrings>,
@@ -381,9 +462,21 @@ pub struct Config {
     /// Useful for more correct gas accounting and EVM behavior in general.
     pub isolate: bool,
 
+    /// Maximum number of bytes of return data the traces record of a call that succeeded, all of
+    /// it if `None`.
+    ///
+    /// The return data of calls that reverted or halted is always recorded in full, so their
+    /// revert can be decoded.
+    pub trace_return_data_limit: Option<usize>,
+
     /// Address labels
     pub labels: HashMap<Address, String>,
 
+    /// Contracts whose code is set at the given addresses before `setUp()` is called in tests.
+    ///
+    /// Configured in the standalone `[test.genesis]` section.
+    pub genesis: BTreeMap<Address, GenesisContract>,
+
     /// The root path where the config detection started from, `Config::with_root`
     #[doc(hidden)]
     //  We're skipping serialization here, so it won't be included in the [`Config::to_string()`]
@@ -412,6 +505,11 @@ pub struct Config {
     pub zksync: bool,
     /// Path to zksolc binary. Can be a URL.
     pub compiler_path: PathBuf,
+    /// The zksolc version to compile with, installed to `~/.foundry/zksolc` if missing, or the
+    /// path to a local zksolc binary. The default version if `None`.
+    pub zksolc: Option<SolcReq>,
+    /// path to where the zksolc artifacts shut be written to
+    pub zk_out: PathBuf,
     /// Optimizer settings for zkSync
     pub zk_optimizer: bool,
     /// The optimization mode string.
@@ -424,13 +522,51 @@ pub struct Config {
     pub is_system: bool,
     /// Force evmla for zkSync
     pub force_evmla: bool,
-    /// Path to cache missing library dependencies, used for compiling and deploying libraries.
+    /// Whether zksolc only detects the libraries the contracts link that aren't deployed,
+    /// reporting them instead of producing bytecode.
     pub detect_missing_libraries: bool,
+    /// The zksolc settings, replacing `zk_optimizer`, `mode`, `is_system`, `force_evmla`,
+    /// `fallback_oz` and `detect_missing_libraries` for the ones it sets.
+    pub zksync_settings: ZkSyncConfig,
+    /// Maximum number of cycles a single ZK-VM execution may run before it's aborted.
+    pub zk_max_cycles: Option<u64>,
+    /// Maximum memory in bytes a single ZK-VM execution may use before it's aborted.
+    pub zk_max_memory: Option<u64>,
+    /// Glob patterns of sources that are never compiled with zksolc, e.g. `["test/mocks/**"]`.
+    ///
+    /// Skipped sources are still compiled with solc and available to EVM-only tests. The sources
+    /// importing them, directly or transitively, are skipped too, and sources that use
+    /// instructions EraVM doesn't support are skipped automatically.
+    pub zk_skip: Vec<String>,
+    /// zksolc warnings that fail the compilation, by error code or message regex, e.g.
+    /// `["ecrecover", "5667"]`. `[".*"]` denies all warnings.
+    pub zk_deny_warnings: Vec<String>,
+    /// zksolc warnings that never fail the compilation, even if they match `zk_deny_warnings`.
+    pub zk_allow_warnings: Vec<String>,
+    /// The denied and allowed zksolc warnings of the sources matching a glob, replacing
+    /// `zk_deny_warnings` and `zk_allow_warnings` for them.
+    pub zk_warnings_overrides: Vec<ZkWarningsOverride>,
+    /// The optimizer settings of single contracts, by `<path>:<contract>` identifier, replacing
+    /// the ones of the profile, e.g. `{ "src/Hot.sol:Hot" = { optimizer_mode = "z" } }`.
+    ///
+    /// Each overridden contract is compiled by a separate zksolc invocation.
+    pub zk_overrides: BTreeMap<String, ZkContractOverride>,
+    /// Whether `forge build --zksync` also writes a combined artifact per contract, with the solc
+    /// and zksolc bytecodes, to the `combined` directory of `zk_out`.
+    pub zk_combined_artifacts: bool,
+    /// The L1 address handled as the zkSync Era `Mailbox` in every test: L1 -> L2 priority
+    /// requests are executed on the ZK-VM and L2 -> L1 messages can be proven, like after
+    /// `vm.zkStartMessaging`.
+    pub zk_messaging: Option<Address>,
 }
 
 /// Mapping of fallback standalone sections. See [`FallbackProfileProvider`]
 pub const STANDALONE_FALLBACK_SECTIONS: &[(&str, &str)] = &[("invariant", "fuzz")];
 
+/// Standalone sections whose keys are config keys of their own, e.g. `[test.genesis]`, and the keys
+/// they may contain.
+pub const NESTED_STANDALONE_SECTIONS: &[(&str, &[&str])] = &[("test", &["genesis"])];
+
 /// Deprecated keys and their replacements.
 ///
 /// See [Warning::DeprecatedKey]
@@ -443,12 +579,15 @@ impl Config {
     /// The hardhat profile: "hardhat"
     pub const HARDHAT_PROFILE: Profile = Profile::const_new("hardhat");
 
+    /// The built-in CI profile: "ci", enabling `perf_mode` unless it's configured
+    pub const CI_PROFILE: Profile = Profile::const_new("ci");
+
     /// TOML section for profiles
     pub const PROFILE_SECTION: &'static str = "profile";
 
     /// Standalone sections in the config which get integrated into the selected profile
     pub const STANDALONE_SECTIONS: &'static [&'static str] =
-        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant", "labels"];
+        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant", "labels", "test"];
 
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
@@ -562,6 +701,7 @@ impl Config {
         self.test = p(&root, &self.test);
         self.script = p(&root, &self.script);
         self.out = p(&root, &self.out);
+        self.zk_out = p(&root, &self.zk_out);
         self.broadcast = p(&root, &self.broadcast);
         self.cache_path = p(&root, &self.cache_path);
 
@@ -580,6 +720,8 @@ impl Config {
 
         self.fs_permissions.join_all(&root);
 
+        self.genesis.values_mut().for_each(|contract| contract.join_all(&root));
+
         if let Some(ref mut model_checker) = self.model_checker {
             model_checker.contracts = std::mem::take(&mut model_checker.contracts)
                 .into_iter()
@@ -688,11 +830,22 @@ impl Config {
     /// ```
     pub fn zk_project(&self) -> Result<Project, SolcError> {
         self.create_project(self.cache, false).map(|mut project| {
-            project.paths.artifacts = project.paths.root.join("zkout");
+            project.paths.artifacts = project.paths.root.join(&self.zk_out);
+            // `zk_out` isn't cleaned up, so with `force` the cached zksolc artifacts are ignored
+            project.cached &= !self.force;
             project
         })
     }
 
+    /// Returns the libraries of `libraries` deployed in zkSync tests and scripts, with their paths
+    /// relative to the root.
+    ///
+    /// They're deployed in the order of [`foundry_zksync_compiler::order_libraries`], with the
+    /// code they're compiled to.
+    pub fn zk_libraries(&self) -> eyre::Result<Vec<ZkLibrary>> {
+        ZkLibrary::configured(&self.libraries_with_remappings()?, &self.__root.0)
+    }
+
     /// Serves as the entrypoint for using zksolc for compilation on zkSync projects.
     ///
     /// Returns the [ZkSolcConfigBuilder] configured with all `zksolc` and path related values.
@@ -711,27 +864,75 @@ impl Config {
             Ok(libs) => libs.with_applied_remappings(&self.project_paths()),
             Err(e) => return Err(format!("Failed to parse libraries: {}", e)),
         };
+        let settings = self.zksolc_settings();
         let optimizer_details =
-            if self.zk_optimizer { self.optimizer_details.clone() } else { None };
-
-        let builder = builder.compiler_version(DEFAULT_ZKSOLC_VERSION).settings(|builder| {
-            builder
-                .libraries(libraries)
-                .is_system(self.is_system)
-                .force_evmla(self.force_evmla)
-                .optimizer(|builder| {
-                    builder
-                        .enabled(self.zk_optimizer)
-                        .mode(self.mode.clone())
-                        .optimize_for_size_fallback(self.fallback_oz)
-                        .disable_system_request_memoization(true)
-                        .details(optimizer_details)
-                })
+            if settings.optimizer { self.optimizer_details.clone() } else { None };
+        // zksolc appends the keccak256 hash of its metadata by default
+        let metadata = self.strip_metadata.then(|| SettingsMetadata {
+            use_literal_content: None,
+            bytecode_hash: Some(BytecodeHash::None),
+            cbor_metadata: None,
         });
 
+        let builder = match &self.zksolc {
+            Some(SolcReq::Local(path)) => {
+                if !path.is_file() {
+                    return Err(format!("`zksolc` {} does not exist", path.display()))
+                }
+                builder.compiler_path(path.clone())
+            }
+            Some(SolcReq::Version(version)) => builder.compiler_version(&version.to_string()),
+            None => builder.compiler_version(DEFAULT_ZKSOLC_VERSION),
+        };
+        let builder = builder
+            .skip_contracts(self.zk_skip.clone())
+            .deny_warnings(self.zk_deny_warnings.clone())
+            .allow_warnings(self.zk_allow_warnings.clone())
+            .warnings_overrides(self.zk_warnings_overrides.clone())
+            .overrides(self.zk_overrides.clone())
+            .settings(|builder| {
+                builder
+                    .libraries(libraries)
+                    .is_system(settings.system_mode)
+                    .force_evmla(settings.force_evmla)
+                    .detect_missing_libraries(settings.detect_missing_libraries)
+                    .metadata(metadata)
+                    .optimizer(|builder| {
+                        builder
+                            .enabled(settings.optimizer)
+                            .mode(settings.optimizer_mode)
+                            .optimize_for_size_fallback(settings.fallback_oz)
+                            .disable_system_request_memoization(true)
+                            .details(optimizer_details)
+                    })
+            });
+
         Ok(builder)
     }
 
+    /// Returns the zksolc settings zkSync projects are compiled with, the ones of the
+    /// `zksync_settings` section or of the top-level keys of the profile.
+    pub fn zksolc_settings(&self) -> ZkSolcSettings {
+        self.zksync_settings.apply(ZkSolcSettings {
+            optimizer: self.zk_optimizer,
+            optimizer_mode: self.mode.clone(),
+            system_mode: self.is_system,
+            force_evmla: self.force_evmla,
+            fallback_oz: self.fallback_oz,
+            detect_missing_libraries: self.detect_missing_libraries,
+        })
+    }
+
+    /// Returns the zksolc version zkSync projects are compiled with, `None` if a local zksolc
+    /// binary is configured.
+    pub fn zksolc_version(&self) -> Option<String> {
+        match &self.zksolc {
+            Some(SolcReq::Local(_)) => None,
+            Some(SolcReq::Version(version)) => Some(format!("v{version}")),
+            None => Some(DEFAULT_ZKSOLC_VERSION.to_string()),
+        }
+    }
+
     /// Serves as the entrypoint for using zksolc for compilation on zkSync projects.
     ///
     /// Returns the default [ZkSolcConfig] configured with all `zksolc` and path related values.
@@ -835,6 +1036,12 @@ impl Config {
         evm_spec_id(&self.evm_version)
     }
 
+    /// Returns the base [TestBudgetConfig] of all tests, derived from `max_duration` and
+    /// `max_duration_warn_only`.
+    pub fn test_budget(&self) -> TestBudgetConfig {
+        TestBudgetConfig { max_duration: self.max_duration, warn_only: self.max_duration_warn_only }
+    }
+
     /// Returns whether the compiler version should be auto-detected
     ///
     /// Returns `false` if `solc_version` is explicitly set, otherwise returns the value of
@@ -1168,14 +1375,20 @@ impl Config {
             }
         }
 
+        let (bytecode_hash, cbor_metadata) = if self.strip_metadata {
+            (BytecodeHash::None, false)
+        } else {
+            (self.bytecode_hash, self.cbor_metadata)
+        };
+
         let mut settings = Settings {
             libraries: self.libraries_with_remappings()?,
             optimizer: self.optimizer(),
             evm_version: Some(self.evm_version),
             metadata: Some(SettingsMetadata {
                 use_literal_content: Some(self.use_literal_content),
-                bytecode_hash: Some(self.bytecode_hash),
-                cbor_metadata: Some(self.cbor_metadata),
+                bytecode_hash: Some(bytecode_hash),
+                cbor_metadata: Some(cbor_metadata),
             }),
             debug: self.revert_strings.map(|revert_strings| DebuggingSettings {
                 revert_strings: Some(revert_strings),
@@ -1408,11 +1621,23 @@ impl Config {
 
     /// Returns the selected profile
     ///
-    /// If the `FOUNDRY_PROFILE` env variable is not set, this returns the `DEFAULT_PROFILE`
+    /// This is the profile selected with [`Config::select_profile`], if any, otherwise the one of
+    /// the `FOUNDRY_PROFILE` env variable. If neither is set, this returns the `DEFAULT_PROFILE`
     pub fn selected_profile() -> Profile {
+        if let Some(profile) = SELECTED_PROFILE.read().unwrap().clone() {
+            return profile
+        }
         Profile::from_env_or("FOUNDRY_PROFILE", Config::DEFAULT_PROFILE)
     }
 
+    /// Selects the profile of the configs loaded from now on, overriding the `FOUNDRY_PROFILE`
+    /// env variable, e.g. for the `--profile` argument of `forge test`.
+    ///
+    /// Unlike setting `FOUNDRY_PROFILE`, this is safe while other threads are running.
+    pub fn select_profile(profile: Profile) {
+        *SELECTED_PROFILE.write().unwrap() = Some(profile);
+    }
+
     /// Returns the path to foundry's global toml file that's stored at `~/.foundry/foundry.toml`
     pub fn foundry_dir_toml() -> Option<PathBuf> {
         Self::foundry_dir().map(|p| p.join(Config::FILE_NAME))
@@ -1611,6 +1836,55 @@ impl Config {
         }
     }
 
+    /// Lists everything `forge cache gc` can remove: the project's `solc` and `zksolc`
+    /// artifacts, cached fork storage per block, block explorer caches per chain and the installed
+    /// `solc` and `zksolc` binaries.
+    pub fn list_foundry_cache_entries(&self) -> eyre::Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        let root = &self.__root.0;
+        entries.extend(CacheEntry::read(CacheKind::SolcArtifacts, root.join(&self.out)));
+        entries.extend(CacheEntry::read(CacheKind::ZkSolcArtifacts, root.join(&self.zk_out)));
+
+        let read_children = |kind: CacheKind, dir: Option<PathBuf>, filter: fn(&str) -> bool| {
+            dir.and_then(|dir| fs::read_dir(dir).ok())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(move |entry| filter(&entry.file_name().to_string_lossy()))
+                .filter_map(move |entry| CacheEntry::read(kind, entry.path()))
+        };
+
+        if let Some(rpc_dir) = Config::foundry_rpc_cache_dir() {
+            for chain in fs::read_dir(rpc_dir).into_iter().flatten().flatten() {
+                entries.extend(read_children(CacheKind::RpcStorage, Some(chain.path()), |_| true));
+            }
+        }
+        entries.extend(read_children(
+            CacheKind::BlockExplorer,
+            Config::foundry_etherscan_cache_dir(),
+            |_| true,
+        ));
+        // installed by svm to `~/.svm/<version>`
+        entries.extend(read_children(
+            CacheKind::SolcBinary,
+            dirs_next::home_dir().map(|home| home.join(".svm")),
+            |name| Version::parse(name).is_ok(),
+        ));
+        // installed by the zksolc manager to `~/.foundry/zksolc/zksolc-<os>-v<version>`, and to
+        // `~/.zksync` by older versions
+        let zksolc_dirs = [
+            Config::foundry_dir().map(|dir| dir.join("zksolc")),
+            dirs_next::home_dir().map(|home| home.join(".zksync")),
+        ];
+        for dir in zksolc_dirs {
+            entries.extend(read_children(CacheKind::ZkSolcBinary, dir, |name| {
+                name.starts_with("zksolc-")
+            }));
+        }
+
+        Ok(entries)
+    }
+
     //The path provided to this function should point to a cached chain folder
     fn get_cached_blocks(chain_path: &Path) -> eyre::Result<Vec<(String, u64)>> {
         let mut blocks = vec![];
@@ -1676,7 +1950,11 @@ impl Config {
         }
         // merge special keys into config
         for standalone_key in Config::STANDALONE_SECTIONS {
-            if let Some((_, fallback)) =
+            if let Some((_, keys)) =
+                NESTED_STANDALONE_SECTIONS.iter().find(|(key, _)| standalone_key == key)
+            {
+                figment = figment.merge(provider.unwrap_section(standalone_key, keys, &profile));
+            } else if let Some((_, fallback)) =
                 STANDALONE_FALLBACK_SECTIONS.iter().find(|(key, _)| standalone_key == key)
             {
                 figment = figment.merge(
@@ -1782,6 +2060,11 @@ impl From<Config> for Figment {
         };
         let merge = figment.merge(remappings);
 
+        // the built-in `ci` profile defaults to the performance mode of the tests
+        if profile == Config::CI_PROFILE {
+            c.perf_mode = true;
+        }
+
         // normalize defaults
         let merge = c.normalize_defaults(merge);
 
@@ -1923,6 +2206,7 @@ impl Default for Config {
             fs_permissions: FsPermissions::new([PathPermission::read("out")]),
             cancun: false,
             isolate: false,
+            trace_return_data_limit: None,
             __root: Default::default(),
             src: "src".into(),
             test: "test".into(),
@@ -1957,8 +2241,17 @@ impl Default for Config {
             path_pattern_inverse: None,
             fuzz: Default::default(),
             invariant: Default::default(),
+            max_duration: None,
+            max_duration_warn_only: false,
+            test_matrix: vec![],
+            offline_tests: false,
+            offline_tests_allow_urls: vec![],
+            perf_mode: false,
             always_use_create_2_factory: false,
             ffi: false,
+            ffi_allowlist: vec![],
+            prompt_timeout: 120,
+            prompt_answers: None,
             sender: Config::DEFAULT_SENDER,
             tx_origin: Config::DEFAULT_SENDER,
             initial_balance: U256::from(0xffffffffffffffffffffffffu128),
@@ -1977,6 +2270,7 @@ impl Default for Config {
             memory_limit: 1 << 27, // 2**27 = 128MiB = 134_217_728 bytes
             eth_rpc_url: None,
             eth_rpc_jwt: None,
+            bundler_url: None,
             etherscan_api_key: None,
             verbosity: 0,
             remappings: vec![],
@@ -1989,6 +2283,9 @@ impl Default for Config {
             ],
             ignored_file_paths: vec![],
             deny_warnings: false,
+            assert_bytecode: vec![],
+            assert_bytecode_ignore_metadata: false,
+            attestation_signer: None,
             via_ir: false,
             ast: false,
             rpc_storage_caching: Default::default(),
@@ -1999,6 +2296,7 @@ impl Default for Config {
             use_literal_content: false,
             bytecode_hash: BytecodeHash::Ipfs,
             cbor_metadata: true,
+            strip_metadata: false,
             revert_strings: None,
             sparse_mode: false,
             build_info: false,
@@ -2006,10 +2304,13 @@ impl Default for Config {
             fmt: Default::default(),
             doc: Default::default(),
             labels: Default::default(),
+            genesis: Default::default(),
             __non_exhaustive: (),
             __warnings: vec![],
             // @zkSync
             compiler_path: Default::default(),
+            zksolc: None,
+            zk_out: "zkout".into(),
             zk_optimizer: true,
             mode: "3".to_string(),
             zksync: false,
@@ -2018,6 +2319,16 @@ impl Default for Config {
             force_evmla: false,
             is_system: false,
             detect_missing_libraries: false,
+            zksync_settings: Default::default(),
+            zk_max_cycles: None,
+            zk_max_memory: None,
+            zk_skip: vec![],
+            zk_deny_warnings: vec![],
+            zk_allow_warnings: vec![],
+            zk_warnings_overrides: vec![],
+            zk_overrides: Default::default(),
+            zk_combined_artifacts: false,
+            zk_messaging: None,
         }
     }
 }
@@ -2527,6 +2838,44 @@ impl<P: Provider> Provider for WrapProfileProvider<P> {
     }
 }
 
+/// Moves the `keys` of a standalone section into a profile
+///
+/// For example given:
+///
+/// ```toml
+/// [test.genesis]
+/// key = "value"
+/// ```
+///
+/// UnwrapSectionProvider will output:
+///
+/// ```toml
+/// [profile.genesis]
+/// key = "value"
+/// ```
+struct UnwrapSectionProvider<P> {
+    provider: P,
+    section: Profile,
+    keys: &'static [&'static str],
+    profile: Profile,
+}
+
+impl<P: Provider> Provider for UnwrapSectionProvider<P> {
+    fn metadata(&self) -> Metadata {
+        self.provider.metadata()
+    }
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let Some(section) = self.provider.data()?.remove(&self.section) else {
+            return Ok(Default::default())
+        };
+        let dict = section.into_iter().filter(|(key, _)| self.keys.contains(&key.as_str()));
+        Ok(self.profile.collect(dict.collect()))
+    }
+    fn profile(&self) -> Option<Profile> {
+        Some(self.profile.clone())
+    }
+}
+
 /// Extracts the profile from the `profile` key and using the original key as backup, merging
 /// values where necessary
 ///
@@ -2622,6 +2971,20 @@ trait ProviderExt: Provider {
     ) -> FallbackProfileProvider<&Self> {
         FallbackProfileProvider::new(self, profile, fallback)
     }
+
+    fn unwrap_section(
+        &self,
+        section: impl Into<Profile>,
+        keys: &'static [&'static str],
+        profile: &Profile,
+    ) -> UnwrapSectionProvider<&Self> {
+        UnwrapSectionProvider {
+            provider: self,
+            section: section.into(),
+            keys,
+            profile: profile.clone(),
+        }
+    }
 }
 impl<P: Provider> ProviderExt for P {}
 
@@ -3176,6 +3539,7 @@ mod tests {
                             chain: Some(NamedChain::Mainnet.into()),
                             browser_url: Some(mainnet_urls.1.to_string()),
                             key: "FX42Z3BBJJEWXWGYV2X1CIPRSCN".to_string(),
+                            verifier: None,
                         }
                     ),
                     (
@@ -3185,6 +3549,7 @@ mod tests {
                             chain: Some(Moonbeam.into()),
                             browser_url: Some(mb_urls.1.to_string()),
                             key: "123456789".to_string(),
+                            verifier: None,
                         }
                     ),
                 ])
@@ -3762,6 +4127,145 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_zksolc_req() {
+        figment::Jail::expect_with(|jail| {
+            let config = Config::load();
+            assert_eq!(config.zksolc, None);
+            assert_eq!(config.zksolc_version().as_deref(), Some(DEFAULT_ZKSOLC_VERSION));
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zksolc = "1.3.23"
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(config.zksolc, Some(SolcReq::Version("1.3.23".parse().unwrap())));
+            assert_eq!(config.zksolc_version().as_deref(), Some("v1.3.23"));
+
+            jail.set_env("FOUNDRY_ZKSOLC", "path/to/local/zksolc");
+            let config = Config::load();
+            assert_eq!(config.zksolc, Some(SolcReq::Local("path/to/local/zksolc".into())));
+            assert_eq!(config.zksolc_version(), None);
+            assert!(config.new_zksolc_config_builder().is_err());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_warnings_policy() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_deny_warnings = [".*"]
+                zk_allow_warnings = ["5667"]
+                zk_warnings_overrides = [{ paths = "test/**", allow = ["txorigin"] }]
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(config.zk_deny_warnings, vec![".*".to_string()]);
+            assert_eq!(config.zk_allow_warnings, vec!["5667".to_string()]);
+            assert_eq!(
+                config.zk_warnings_overrides,
+                vec![ZkWarningsOverride {
+                    paths: "test/**".to_string(),
+                    deny: vec![],
+                    allow: vec!["txorigin".to_string()],
+                }]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_overrides() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_optimizer = true
+                mode = "3"
+
+                [profile.default.zk_overrides."src/Hot.sol:Hot"]
+                optimizer_mode = "z"
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(
+                config.zk_overrides,
+                BTreeMap::from([(
+                    "src/Hot.sol:Hot".to_string(),
+                    ZkContractOverride {
+                        optimizer_mode: Some("z".to_string()),
+                        ..Default::default()
+                    }
+                )])
+            );
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default.zk_overrides."src/Hot.sol"]
+                optimizer = false
+            "#,
+            )?;
+            let config = Config::load();
+            assert!(config.new_zksolc_config_builder().unwrap().build().is_err());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zksync_settings() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_optimizer = true
+                mode = "3"
+                is_system = true
+
+                [profile.default.zksync_settings]
+                optimizer_mode = "z"
+                fallback_oz = true
+                detect_missing_libraries = true
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(
+                config.zksync_settings,
+                ZkSyncConfig {
+                    optimizer_mode: Some("z".to_string()),
+                    fallback_oz: Some(true),
+                    detect_missing_libraries: Some(true),
+                    ..Default::default()
+                }
+            );
+            assert_eq!(
+                config.zksolc_settings(),
+                ZkSolcSettings {
+                    optimizer: true,
+                    optimizer_mode: "z".to_string(),
+                    system_mode: true,
+                    force_evmla: false,
+                    fallback_oz: true,
+                    detect_missing_libraries: true,
+                }
+            );
+            Ok(())
+        });
+    }
+
     // ensures the newer `solc` takes precedence over `solc_version`
     #[test]
     fn test_backwards_solc_version() {
@@ -4054,6 +4558,27 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_ci_profile_perf_mode() {
+        figment::Jail::expect_with(|jail| {
+            assert!(!Config::load().perf_mode);
+
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            assert!(Config::load().perf_mode);
+
+            jail.create_file(
+                "foundry.toml",
+                r"
+                [profile.ci]
+                perf_mode = false
+            ",
+            )?;
+            assert!(!Config::load().perf_mode);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn can_handle_deviating_dapp_aliases() {
         figment::Jail::expect_with(|jail| {
@@ -4072,7 +4597,7 @@ mod tests {
             assert_eq!(config.sender, addr);
             assert_eq!(config.fuzz.runs, 420);
             assert_eq!(config.invariant.depth, 20);
-            assert_eq!(config.fork_block_number, Some(100));
+            assert_eq!(config.fork_block_number, Some(ForkBlock::Number(100)));
             assert_eq!(config.optimizer_runs, 999);
             assert!(!config.optimizer);
 
@@ -4080,6 +4605,44 @@ mod tests {
         });
     }
 
+    #[test]
+    fn can_parse_fork_block_number() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r"
+                [profile.default]
+                fork_block_number = 19579636
+            ",
+            )?;
+            let config = Config::load();
+            assert_eq!(config.fork_block_number, Some(ForkBlock::Number(19579636)));
+
+            // numbers are still serialized as numbers
+            let toml = config.to_string_pretty().unwrap();
+            assert!(toml.contains("fork_block_number = 19579636"), "{toml}");
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                fork_block_number = "latest-safe"
+            "#,
+            )?;
+            let config = Config::load();
+            assert_eq!(config.fork_block_number, Some(ForkBlock::Safe));
+            let toml = config.to_string_pretty().unwrap();
+            assert!(toml.contains("fork_block_number = \"latest-safe\""), "{toml}");
+
+            jail.set_env("FOUNDRY_FORK_BLOCK_NUMBER", "0x64");
+            assert_eq!(Config::load().fork_block_number, Some(ForkBlock::Number(100)));
+            jail.set_env("FOUNDRY_FORK_BLOCK_NUMBER", "latest-finalized");
+            assert_eq!(Config::load().fork_block_number, Some(ForkBlock::Finalized));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn can_parse_libraries() {
         figment::Jail::expect_with(|jail| {
@@ -4488,6 +5051,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_zk_out() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r"
+                [profile.default]
+                zk_out = 'my-zkout'
+            ",
+            )?;
+            jail.create_dir("my-zkout")?;
+            jail.create_file("my-zkout/Counter.json", "{}")?;
+
+            let config = Config::load();
+            assert!(config.zk_project().unwrap().artifacts_path().ends_with("my-zkout"));
+            let entries = config.list_foundry_cache_entries().unwrap();
+            assert!(entries.iter().any(|entry| entry.kind == CacheKind::ZkSolcArtifacts &&
+                entry.path.ends_with("my-zkout")));
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_implicit_profile_loads() {
         figment::Jail::expect_with(|jail| {
@@ -4760,6 +5345,35 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_parse_test_genesis() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [test.genesis]
+                0x1000000000000000000000000000000000000001 = "src/Token.sol:Token"
+                0x1000000000000000000000000000000000000002 = { bytecode = "genesis/Oracle.hex" }
+            "#,
+            )?;
+
+            let config = Config::load();
+            let genesis = config.genesis.into_iter().collect::<Vec<_>>();
+            assert_eq!(genesis.len(), 2);
+            assert_eq!(
+                genesis[0],
+                (
+                    Address::from_str("0x1000000000000000000000000000000000000001").unwrap(),
+                    GenesisContract::Artifact("src/Token.sol:Token".to_string())
+                )
+            );
+            assert!(matches!(genesis[1].1, GenesisContract::Bytecode { zk_bytecode: None, .. }));
+            assert!(!config.__warnings.iter().any(|warning| warning.to_string().contains("test")));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_parse_labels() {
         figment::Jail::expect_with(|jail| {
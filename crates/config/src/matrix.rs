@@ -0,0 +1,85 @@
+//! Configuration for running the test suite against a matrix of chains and hardforks.
+
+use crate::{Chain, Config};
+use foundry_compilers::EvmVersion;
+use serde::{Deserialize, Serialize};
+
+/// A cell of the test matrix, overriding the chain or the hardfork the test suite is executed
+/// with.
+///
+/// Unset fields keep the value of the profile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestMatrixCell {
+    /// The name of the cell in the test output, derived from the overrides if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The chain name or EIP-155 chain ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<Chain>,
+    /// The EVM hardfork the tests are executed with.
+    ///
+    /// Note that the project is still compiled for the `evm_version` of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_version: Option<EvmVersion>,
+}
+
+impl TestMatrixCell {
+    /// Returns the name of the cell, e.g. `chain_id=324,evm_version=paris` if it's not named.
+    pub fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone()
+        }
+
+        let mut parts = Vec::new();
+        if let Some(chain) = self.chain_id {
+            parts.push(format!("chain_id={chain}"));
+        }
+        if let Some(evm_version) = self.evm_version {
+            parts.push(format!("evm_version={evm_version}"));
+        }
+        if parts.is_empty() {
+            return "default".to_string()
+        }
+        parts.join(",")
+    }
+
+    /// Returns a copy of `config` with the overrides of the cell applied.
+    pub fn apply(&self, config: &Config) -> Config {
+        let mut config = config.clone();
+        if let Some(chain) = self.chain_id {
+            config.chain = Some(chain);
+        }
+        if let Some(evm_version) = self.evm_version {
+            config.evm_version = evm_version;
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_applies_cells() {
+        let cells: Vec<TestMatrixCell> = serde_json::from_value(serde_json::json!([
+            { "chain_id": 324, "evm_version": "paris" },
+            { "name": "era", "chain_id": 324 },
+            {},
+        ]))
+        .unwrap();
+
+        assert_eq!(cells[0].name(), "chain_id=324,evm_version=paris");
+        assert_eq!(cells[1].name(), "era");
+        assert_eq!(cells[2].name(), "default");
+
+        let config = Config::default();
+        let applied = cells[0].apply(&config);
+        assert_eq!(applied.chain, Some(Chain::from_id(324)));
+        assert_eq!(applied.evm_version, EvmVersion::Paris);
+
+        let applied = cells[1].apply(&config);
+        assert_eq!(applied.chain, Some(Chain::from_id(324)));
+        assert_eq!(applied.evm_version, config.evm_version);
+    }
+}
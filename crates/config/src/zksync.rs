@@ -0,0 +1,75 @@
+//! The zksolc settings of the `zksync_settings` section.
+
+use serde::{Deserialize, Serialize};
+
+/// The `[profile.<profile>.zksync_settings]` section, the zksolc settings zkSync projects are
+/// compiled with.
+///
+/// The settings left out fall back to the top-level keys of the profile, `zk_optimizer`, `mode`,
+/// `is_system`, `force_evmla`, `fallback_oz` and `detect_missing_libraries`.
+///
+/// ```toml
+/// [profile.default.zksync_settings]
+/// optimizer = true
+/// optimizer_mode = "z"
+/// system_mode = false
+/// force_evmla = false
+/// fallback_oz = true
+/// detect_missing_libraries = false
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZkSyncConfig {
+    /// Whether the LLVM optimizer is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimizer: Option<bool>,
+    /// The LLVM optimization level, `0`, `1`, `2`, `3`, `s` or `z`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimizer_mode: Option<String>,
+    /// Whether to compile in system mode, enabling the system contract calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_mode: Option<bool>,
+    /// Whether to forcibly switch to the EVM legacy assembly pipeline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_evmla: Option<bool>,
+    /// Whether to try to recompile with `-Oz` if the bytecode is too large.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_oz: Option<bool>,
+    /// Whether to only detect the libraries the contracts link that aren't deployed. zksolc
+    /// reports them instead of producing bytecode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detect_missing_libraries: Option<bool>,
+}
+
+/// The resolved zksolc settings of a profile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkSolcSettings {
+    /// Whether the LLVM optimizer is enabled.
+    pub optimizer: bool,
+    /// The LLVM optimization level.
+    pub optimizer_mode: String,
+    /// Whether to compile in system mode.
+    pub system_mode: bool,
+    /// Whether to forcibly switch to the EVM legacy assembly pipeline.
+    pub force_evmla: bool,
+    /// Whether to try to recompile with `-Oz` if the bytecode is too large.
+    pub fallback_oz: bool,
+    /// Whether to only detect the missing libraries.
+    pub detect_missing_libraries: bool,
+}
+
+impl ZkSyncConfig {
+    /// Returns `defaults` with the settings set by the section replaced.
+    pub fn apply(&self, defaults: ZkSolcSettings) -> ZkSolcSettings {
+        ZkSolcSettings {
+            optimizer: self.optimizer.unwrap_or(defaults.optimizer),
+            optimizer_mode: self.optimizer_mode.clone().unwrap_or(defaults.optimizer_mode),
+            system_mode: self.system_mode.unwrap_or(defaults.system_mode),
+            force_evmla: self.force_evmla.unwrap_or(defaults.force_evmla),
+            fallback_oz: self.fallback_oz.unwrap_or(defaults.fallback_oz),
+            detect_missing_libraries: self
+                .detect_missing_libraries
+                .unwrap_or(defaults.detect_missing_libraries),
+        }
+    }
+}
@@ -17,6 +17,20 @@ impl ForkType {
     pub fn is_evm(&self) -> bool {
         matches!(self, ForkType::Evm)
     }
+
+    /// Detects the [ForkType] of a url.
+    /// We attempt to query the rpc provider for the "zks_L1ChainId" method of the `zks_`
+    /// namespace. If it returns successfully, then the chain is [ForkType::Zk], else it's
+    /// [ForkType::Evm].
+    pub async fn detect(fork_url: &str) -> Self {
+        let Ok(provider) = foundry_common::provider::ethers::try_get_http_provider(fork_url) else {
+            return ForkType::Evm
+        };
+        match provider.request::<_, String>("zks_L1ChainId", ()).await {
+            Ok(_) => ForkType::Zk,
+            Err(_) => ForkType::Evm,
+        }
+    }
 }
 
 /// A cached implementation for retrieving the [ForkType] of a given url.
@@ -24,30 +38,18 @@ impl ForkType {
 pub struct CachedForkType(HashMap<String, ForkType>);
 
 impl CachedForkType {
-    /// Retrieve the [ForkType] of a url.
-    /// We attempt to query the rpc provider for "zks_L1ChainId" method. If it returns successfully,
-    /// then the chain is [ForkType::Zk], else it's [ForkType::Evm].
+    /// Retrieve the [ForkType] of a url, detected with [ForkType::detect].
     /// The result is then cached
     pub fn get(&mut self, fork_url: &str) -> ForkType {
         if let Some(fork_url_type) = self.0.get(fork_url) {
             return fork_url_type.clone()
         }
 
-        let is_zk_url = foundry_common::provider::ethers::try_get_http_provider(fork_url)
-            .map(|provider| {
-                let is_zk_url = tokio::runtime::Builder::new_multi_thread()
-                    .enable_all()
-                    .build()
-                    .unwrap()
-                    .block_on(provider.request("zks_L1ChainId", ()))
-                    .map(|_: String| true)
-                    .unwrap_or_default();
-
-                is_zk_url
-            })
-            .unwrap_or_default();
-
-        let fork_type = if is_zk_url { ForkType::Zk } else { ForkType::Evm };
+        let fork_type = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(ForkType::detect(fork_url));
         self.0.insert(fork_url.to_string(), fork_type.clone());
 
         fork_type
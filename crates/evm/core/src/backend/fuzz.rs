@@ -234,6 +234,14 @@ impl<'a> DatabaseExt for FuzzBackendWrapper<'a> {
         self.backend_mut(&Env::default()).load_allocs(allocs, journaled_state)
     }
 
+    fn account_storage(&self, address: Address) -> Map<U256, U256> {
+        self.backend.account_storage(address)
+    }
+
+    fn committed_accounts(&self) -> Vec<Address> {
+        self.backend.committed_accounts()
+    }
+
     fn is_persistent(&self, acc: &Address) -> bool {
         self.backend.is_persistent(acc)
     }
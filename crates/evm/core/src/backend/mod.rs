@@ -10,7 +10,9 @@ use alloy_genesis::GenesisAccount;
 use alloy_primitives::{b256, keccak256, Address, B256, U256, U64};
 use alloy_rpc_types::{Block, BlockNumberOrTag, BlockTransactions, Transaction};
 use foundry_common::{is_known_system_sender, SYSTEM_TRANSACTION_TYPE};
-use foundry_zksync_core::{convert::ConvertH160, L2_ETH_TOKEN_ADDRESS};
+use foundry_zksync_core::{
+    convert::ConvertH160, ACCOUNT_CODE_STORAGE_ADDRESS, L2_ETH_TOKEN_ADDRESS, NONCE_HOLDER_ADDRESS,
+};
 use itertools::Itertools;
 use revm::{
     db::{CacheDB, DatabaseRef},
@@ -282,6 +284,16 @@ pub trait DatabaseExt: Database<Error = DatabaseError> {
         journaled_state: &mut JournaledState,
     ) -> Result<(), DatabaseError>;
 
+    /// Returns the storage of `address` committed to the active database.
+    ///
+    /// For an account of a fork, these are only the slots that were loaded from the fork so far.
+    fn account_storage(&self, address: Address) -> Map<U256, U256>;
+
+    /// Returns the accounts committed to the active database.
+    ///
+    /// For a fork, these are only the accounts that were loaded from the fork so far.
+    fn committed_accounts(&self) -> Vec<Address>;
+
     /// Returns true if the given account is currently marked as persistent.
     fn is_persistent(&self, acc: &Address) -> bool;
 
@@ -844,6 +856,18 @@ impl Backend {
         foundry_zksync_core::vm::transact(factory_deps, env, self)
     }
 
+    /// Executes the configured zk transaction of the `env` without committing state changes and
+    /// returns the state it modified
+    pub fn simulate_zk(
+        &mut self,
+        env: &mut Env,
+        factory_deps: Option<Vec<Vec<u8>>>,
+    ) -> eyre::Result<ResultAndState> {
+        self.initialize(env);
+
+        foundry_zksync_core::vm::simulate(factory_deps, env, self)
+    }
+
     /// Returns true if the address is a precompile
     pub fn is_existing_precompile(&self, addr: &Address) -> bool {
         self.inner.precompiles().contains(addr)
@@ -1467,6 +1491,22 @@ impl DatabaseExt for Backend {
         self.inner.persistent_accounts.remove(account)
     }
 
+    fn account_storage(&self, address: Address) -> Map<U256, U256> {
+        let accounts = match self.active_fork_db() {
+            Some(db) => &db.accounts,
+            None => &self.mem_db.accounts,
+        };
+        accounts.get(&address).map(|account| account.storage.clone()).unwrap_or_default()
+    }
+
+    fn committed_accounts(&self) -> Vec<Address> {
+        let accounts = match self.active_fork_db() {
+            Some(db) => &db.accounts,
+            None => &self.mem_db.accounts,
+        };
+        accounts.keys().copied().collect()
+    }
+
     fn is_persistent(&self, acc: &Address) -> bool {
         self.inner.persistent_accounts.contains(acc)
     }
@@ -1890,6 +1930,13 @@ pub(crate) fn merge_account_data<ExtDB: DatabaseRef>(
             merge_zk_account_data(addr, active, &mut target_fork.db);
         }
         merge_journaled_state_data(addr, active_journaled_state, &mut target_fork.journaled_state);
+        if merge_zk_db {
+            merge_zk_journaled_state_data(
+                addr,
+                active_journaled_state,
+                &mut target_fork.journaled_state,
+            );
+        }
     }
 
     // need to mock empty journal entries in case the current checkpoint is higher than the existing
@@ -1948,6 +1995,19 @@ fn merge_db_account_data<ExtDB: DatabaseRef>(
     fork_db.accounts.insert(addr, acc);
 }
 
+/// Returns the system contract storage slots holding the zk data of `addr`: its balance, its
+/// nonces and its code hash.
+fn zk_account_slots(addr: Address) -> [(Address, U256); 3] {
+    [
+        (L2_ETH_TOKEN_ADDRESS.to_address(), foundry_zksync_core::get_balance_key(addr)),
+        (NONCE_HOLDER_ADDRESS.to_address(), foundry_zksync_core::get_nonce_key(addr)),
+        (
+            ACCOUNT_CODE_STORAGE_ADDRESS.to_address(),
+            foundry_zksync_core::get_account_code_key(addr),
+        ),
+    ]
+}
+
 /// Clones the zk account data from the `active` db into the `ForkDB`
 fn merge_zk_account_data<ExtDB: DatabaseRef>(
     addr: Address,
@@ -1956,31 +2016,49 @@ fn merge_zk_account_data<ExtDB: DatabaseRef>(
 ) {
     trace!(?addr, "merging zk database data");
 
-    // TODO: do the same for nonce and codes
-    let balance_addr = L2_ETH_TOKEN_ADDRESS.to_address();
-    let mut acc = if let Some(acc) = active.accounts.get(&balance_addr).cloned() {
-        acc
-    } else {
-        // Account does not exist
-        return;
-    };
+    for (system_addr, slot) in zk_account_slots(addr) {
+        let Some(acc) = active.accounts.get(&system_addr) else {
+            // Account does not exist
+            continue
+        };
+        let Some(value) = acc.storage.get(&slot).copied() else { continue };
 
-    let mut balances = Map::<U256, U256>::default();
-    let slot = foundry_zksync_core::get_balance_key(addr);
-    if let Some(value) = acc.storage.get(&slot) {
-        balances.insert(slot, *value);
-    }
+        // the code hash is only usable with the bytecode it refers to
+        if system_addr == ACCOUNT_CODE_STORAGE_ADDRESS.to_address() {
+            let code_hash = B256::from(value);
+            if let Some(code) = active.contracts.get(&code_hash).cloned() {
+                fork_db.contracts.insert(code_hash, code);
+            }
+        }
 
-    if let Some(fork_account) = fork_db.accounts.get_mut(&balance_addr) {
-        // This will merge the fork's tracked storage with active storage and update values
-        fork_account.storage.extend(balances);
-        // swap them so we can insert the account as whole in the next step
-        std::mem::swap(&mut fork_account.storage, &mut acc.storage);
-    } else {
-        std::mem::swap(&mut balances, &mut acc.storage)
+        if let Some(fork_account) = fork_db.accounts.get_mut(&system_addr) {
+            fork_account.storage.insert(slot, value);
+        } else {
+            let mut acc = acc.clone();
+            acc.storage = Map::from([(slot, value)]);
+            fork_db.accounts.insert(system_addr, acc);
+        }
     }
+}
+
+/// Clones the zk account data from the `active_journaled_state` into the `fork_journaled_state`
+fn merge_zk_journaled_state_data(
+    addr: Address,
+    active_journaled_state: &JournaledState,
+    fork_journaled_state: &mut JournaledState,
+) {
+    for (system_addr, slot) in zk_account_slots(addr) {
+        let Some(acc) = active_journaled_state.state.get(&system_addr) else { continue };
+        let Some(value) = acc.storage.get(&slot).cloned() else { continue };
+        trace!(?addr, ?system_addr, "updating zk journaled_state account data");
 
-    fork_db.accounts.insert(balance_addr, acc);
+        fork_journaled_state
+            .state
+            .entry(system_addr)
+            .or_insert_with(|| Account { storage: Default::default(), ..acc.clone() })
+            .storage
+            .insert(slot, value);
+    }
 }
 
 /// Returns true of the address is a contract
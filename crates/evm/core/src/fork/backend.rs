@@ -22,11 +22,31 @@ use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
     pin::Pin,
     sync::{
+        atomic::{AtomicU64, Ordering},
         mpsc::{channel as oneshot_channel, Sender as OneshotSender},
         Arc,
     },
 };
 
+/// The number of account, storage and block hash requests of all the backends of the process
+/// answered from their cache.
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+/// The number of account, storage and block hash requests of all the backends of the process
+/// fetched from their provider.
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the number of account, storage and block hash requests of the fork backends of the
+/// process that were answered from their cache, and that were fetched from the provider.
+pub fn cache_stats() -> (u64, u64) {
+    (CACHE_HITS.load(Ordering::Relaxed), CACHE_MISSES.load(Ordering::Relaxed))
+}
+
+/// Records whether a request was answered from the cache.
+fn record_cache_lookup(hit: bool) {
+    let counter = if hit { &CACHE_HITS } else { &CACHE_MISSES };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
 // Various future/request type aliases
 
 type AccountFuture<Err> =
@@ -144,6 +164,7 @@ where
             BackendRequest::Basic(addr, sender) => {
                 trace!(target: "backendhandler", "received request basic address={:?}", addr);
                 let acc = self.db.accounts().read().get(&addr).cloned();
+                record_cache_lookup(acc.is_some());
                 if let Some(basic) = acc {
                     let _ = sender.send(Ok(basic));
                 } else {
@@ -152,6 +173,7 @@ where
             }
             BackendRequest::BlockHash(number, sender) => {
                 let hash = self.db.block_hashes().read().get(&U256::from(number)).cloned();
+                record_cache_lookup(hash.is_some());
                 if let Some(hash) = hash {
                     let _ = sender.send(Ok(hash));
                 } else {
@@ -168,6 +190,7 @@ where
                 // account is already stored in the cache
                 let value =
                     self.db.storage().read().get(&addr).and_then(|acc| acc.get(&idx).copied());
+                record_cache_lookup(value.is_some());
                 if let Some(value) = value {
                     let _ = sender.send(Ok(value));
                 } else {
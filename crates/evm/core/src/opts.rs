@@ -9,7 +9,7 @@ use foundry_common::{
     ALCHEMY_FREE_TIER_CUPS,
 };
 use foundry_compilers::utils::RuntimeOrHandle;
-use foundry_config::{Chain, Config};
+use foundry_config::{Chain, Config, ForkBlock};
 use revm::primitives::{BlockEnv, CfgEnv, SpecId, TxEnv};
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -24,6 +24,10 @@ pub struct EvmOpts {
     pub fork_url: Option<RpcUrl>,
 
     /// Pins the block number for the state fork.
+    ///
+    /// Tags of the config are `None` here, until the commands forking from the config set the
+    /// block they're pinned to in the fork lockfile.
+    #[serde(default, deserialize_with = "fork_block_number_opt")]
     pub fork_block_number: Option<u64>,
 
     /// The number of retries.
@@ -291,3 +295,10 @@ where
         _ => Ok(None),
     }
 }
+
+fn fork_block_number_opt<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<ForkBlock>::deserialize(deserializer)?.and_then(ForkBlock::number))
+}
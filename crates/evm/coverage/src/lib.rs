@@ -10,6 +10,7 @@ extern crate tracing;
 use alloy_primitives::{Bytes, B256};
 use foundry_compilers::sourcemap::SourceElement;
 use semver::Version;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::Display,
@@ -154,7 +155,7 @@ impl CoverageReport {
 }
 
 /// A collection of [HitMap]s
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct HitMaps(pub HashMap<B256, HitMap>);
 
 impl HitMaps {
@@ -171,6 +172,16 @@ impl HitMaps {
     }
 }
 
+/// Utility function to merge coverage options
+pub fn merge_coverages(coverage: Option<HitMaps>, other: Option<HitMaps>) -> Option<HitMaps> {
+    match (coverage, other) {
+        (Some(coverage), Some(other)) => Some(coverage.merge(other)),
+        (None, Some(other)) => Some(other),
+        (Some(coverage), None) => Some(coverage),
+        (None, None) => None,
+    }
+}
+
 impl Deref for HitMaps {
     type Target = HashMap<B256, HitMap>;
 
@@ -188,7 +199,7 @@ impl DerefMut for HitMaps {
 /// Hit data for an address.
 ///
 /// Contains low-level data about hit counters for the instructions in the bytecode of a contract.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HitMap {
     pub bytecode: Bytes,
     pub hits: BTreeMap<usize, u64>,
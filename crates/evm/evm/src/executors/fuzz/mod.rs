@@ -11,14 +11,19 @@ use foundry_evm_core::{
 use foundry_evm_coverage::HitMaps;
 use foundry_evm_fuzz::{
     strategies::{
-        build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_state,
-        EvmFuzzState,
+        build_initial_state, collect_state_from_call, collect_zk_state_from_call, fuzz_calldata,
+        fuzz_calldata_from_state, EvmFuzzState,
     },
-    BaseCounterExample, CounterExample, FuzzCase, FuzzError, FuzzTestResult,
+    AssumeRejection, BaseCounterExample, CounterExample, FuzzCase, FuzzError, FuzzTestResult,
 };
 use foundry_evm_traces::CallTraceArena;
 use proptest::test_runner::{TestCaseError, TestError, TestRunner};
-use std::cell::RefCell;
+use rayon::prelude::*;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 mod types;
 pub use types::{CaseOutcome, CounterExampleOutcome, FuzzOutcome};
@@ -42,11 +47,14 @@ pub struct FuzzedExecutor {
 impl FuzzedExecutor {
     /// Instantiates a fuzzed executor given a testrunner
     pub fn new(
-        executor: Executor,
+        mut executor: Executor,
         runner: TestRunner,
         sender: Address,
         config: FuzzConfig,
     ) -> Self {
+        if executor.use_zk {
+            executor.inspector.collect_zk_fuzz_values();
+        }
         Self { executor, runner, sender, config }
     }
 
@@ -55,12 +63,66 @@ impl FuzzedExecutor {
     /// test case.
     ///
     /// Returns a list of all the consumed gas and calldata of every fuzz case
+    ///
+    /// If [FuzzConfig::parallel] is greater than 1, the runs are split across that many workers.
     pub fn fuzz(
         &self,
         func: &Function,
         address: Address,
         should_fail: bool,
         rd: &RevertDecoder,
+    ) -> FuzzTestResult {
+        let cases = self.runner.config().cases;
+        let workers = self.config.parallel.clamp(1, cases.max(1));
+        if workers == 1 {
+            let runner = self.runner.clone();
+            return self.fuzz_worker(&self.executor, runner, func, address, should_fail, rd, None)
+        }
+
+        debug!(func=?func.name, workers, "fuzzing in parallel");
+        // The RNGs of the workers are derived from the runner's in order, so the runs of every
+        // worker only depend on the seed.
+        let mut runner = self.runner.clone();
+        let runners = (0..workers)
+            .map(|worker| {
+                let mut config = runner.config().clone();
+                // the cases `worker`, `worker + workers`, ... of the runs
+                config.cases = (cases - worker).div_ceil(workers);
+                config.max_global_rejects = config.max_global_rejects.div_ceil(workers);
+                let rng = runner.new_rng();
+                TestRunner::new_with_rng(config, rng)
+            })
+            .collect::<Vec<_>>();
+
+        // Every worker runs its cases on its own copy of the executor, in the state it was left in
+        // by `setUp()`, so the workers don't share anything but the cancellation.
+        let runners =
+            runners.into_iter().map(|runner| (self.executor.clone(), runner)).collect::<Vec<_>>();
+        let first_failure = AtomicUsize::new(usize::MAX);
+        let results = runners
+            .into_par_iter()
+            .enumerate()
+            .map(|(worker, (executor, runner))| {
+                let cancellation = WorkerCancellation { worker, first_failure: &first_failure };
+                let cancellation = Some(cancellation);
+                self.fuzz_worker(&executor, runner, func, address, should_fail, rd, cancellation)
+            })
+            .collect();
+        merge_worker_results(results)
+    }
+
+    /// Executes the runs of `runner` on `executor`, which are either all runs of the test or the
+    /// share of a parallel worker.
+    #[allow(clippy::too_many_arguments)]
+    fn fuzz_worker(
+        &self,
+        executor: &Executor,
+        mut runner: TestRunner,
+        func: &Function,
+        address: Address,
+        should_fail: bool,
+        rd: &RevertDecoder,
+        cancellation: Option<WorkerCancellation<'_>>,
     ) -> FuzzTestResult {
         // Stores the first Fuzzcase
         let first_case: RefCell<Option<FuzzCase>> = RefCell::default();
@@ -68,6 +130,9 @@ impl FuzzedExecutor {
         // gas usage per case
         let gas_by_case: RefCell<Vec<(u64, u64)>> = RefCell::default();
 
+        // ergs used in the ZK-VM per case
+        let zk_ergs_by_case: RefCell<Vec<u64>> = RefCell::default();
+
         // Stores the result and calldata of the last failed call, if any.
         let counterexample: RefCell<(Bytes, RawCallResult)> = RefCell::default();
 
@@ -77,7 +142,10 @@ impl FuzzedExecutor {
         // Stores coverage information for all fuzz cases
         let coverage: RefCell<Option<HitMaps>> = RefCell::default();
 
-        let state = self.build_fuzz_state();
+        // Counts the inputs rejected by `vm.assume`, by call site
+        let assume_rejections: RefCell<AssumeRejectionCounts> = RefCell::default();
+
+        let state = build_fuzz_state(executor, &self.config);
 
         let mut weights = vec![];
         let dictionary_weight = self.config.dictionary.dictionary_weight.min(100);
@@ -93,13 +161,26 @@ impl FuzzedExecutor {
 
         let strat = proptest::strategy::Union::new_weighted(weights);
         debug!(func=?func.name, should_fail, "fuzzing");
-        let run_result = self.runner.clone().run(&strat, |calldata| {
-            let fuzz_res = self.single_fuzz(&state, address, should_fail, calldata)?;
+        let run_result = runner.run(&strat, |calldata| {
+            // the remaining cases of a cancelled worker pass without being executed
+            if cancellation.as_ref().is_some_and(WorkerCancellation::is_cancelled) {
+                return Ok(())
+            }
+
+            let fuzz_res = self.fuzz_case(
+                executor,
+                &state,
+                address,
+                should_fail,
+                calldata,
+                Some(&assume_rejections),
+            )?;
 
             match fuzz_res {
                 FuzzOutcome::Case(case) => {
                     let mut first_case = first_case.borrow_mut();
                     gas_by_case.borrow_mut().push((case.case.gas, case.case.stipend));
+                    zk_ergs_by_case.borrow_mut().push(case.case.zk_ergs);
                     if first_case.is_none() {
                         first_case.replace(case.case);
                     }
@@ -129,6 +210,9 @@ impl FuzzedExecutor {
                     // case.
                     let call_res = _counterexample.1.result.clone();
                     *counterexample.borrow_mut() = _counterexample;
+                    if let Some(cancellation) = &cancellation {
+                        cancellation.fail();
+                    }
                     // HACK: we have to use an empty string here to denote `None`
                     let reason = rd.maybe_decode(&call_res, Some(status));
                     Err(TestCaseError::fail(reason.unwrap_or_default()))
@@ -136,18 +220,26 @@ impl FuzzedExecutor {
             }
         });
 
+        if let (Some(cancellation), Err(_)) = (&cancellation, &run_result) {
+            cancellation.fail();
+        }
+
         let (calldata, call) = counterexample.into_inner();
         let mut result = FuzzTestResult {
             first_case: first_case.take().unwrap_or_default(),
             gas_by_case: gas_by_case.take(),
+            zk_ergs_by_case: zk_ergs_by_case.take(),
             success: run_result.is_ok(),
             reason: None,
             counterexample: None,
             decoded_logs: decode_console_logs(&call.logs),
             logs: call.logs,
             labeled_addresses: call.labels,
+            proxies: call.proxies,
+            zk_calls: call.zk_calls,
             traces: if run_result.is_ok() { traces.into_inner() } else { call.traces.clone() },
             coverage: coverage.into_inner(),
+            assume_rejections: AssumeRejection::from_counts(assume_rejections.into_inner()),
         };
 
         match run_result {
@@ -196,8 +288,21 @@ impl FuzzedExecutor {
         should_fail: bool,
         calldata: alloy_primitives::Bytes,
     ) -> Result<FuzzOutcome, TestCaseError> {
-        let call = self
-            .executor
+        self.fuzz_case(&self.executor, state, address, should_fail, calldata, None)
+    }
+
+    /// Same as [Self::single_fuzz] on `executor`, but counts the call site of `vm.assume` if it
+    /// rejects the input.
+    fn fuzz_case(
+        &self,
+        executor: &Executor,
+        state: &EvmFuzzState,
+        address: Address,
+        should_fail: bool,
+        calldata: alloy_primitives::Bytes,
+        assume_rejections: Option<&RefCell<AssumeRejectionCounts>>,
+    ) -> Result<FuzzOutcome, TestCaseError> {
+        let call = executor
             .call_raw(self.sender, address, calldata.clone(), U256::ZERO)
             .map_err(|_| TestCaseError::fail(FuzzError::FailedContractCall))?;
         let state_changeset = call
@@ -212,9 +317,18 @@ impl FuzzedExecutor {
             state.clone(),
             &self.config.dictionary,
         );
+        if let Some(values) =
+            call.cheatcodes.as_ref().and_then(|cheats| cheats.zk_fuzz_values.as_ref())
+        {
+            collect_zk_state_from_call(values, state.clone(), &self.config.dictionary);
+        }
 
         // When the `assume` cheatcode is called it returns a special string
         if call.result.as_ref() == MAGIC_ASSUME {
+            let site = call.cheatcodes.as_ref().and_then(|cheats| cheats.assume_rejected_at);
+            if let (Some(rejections), Some(site)) = (assume_rejections, site) {
+                *rejections.borrow_mut().entry(site).or_default() += 1;
+            }
             return Err(TestCaseError::reject(FuzzError::AssumeReject))
         }
 
@@ -224,11 +338,16 @@ impl FuzzedExecutor {
             .map_or_else(Default::default, |cheats| cheats.breakpoints.clone());
 
         let success =
-            self.executor.is_raw_call_success(address, state_changeset.clone(), &call, should_fail);
+            executor.is_raw_call_success(address, state_changeset.clone(), &call, should_fail);
 
         if success {
             Ok(FuzzOutcome::Case(CaseOutcome {
-                case: FuzzCase { calldata, gas: call.gas_used, stipend: call.stipend },
+                case: FuzzCase {
+                    calldata,
+                    gas: call.gas_used,
+                    stipend: call.stipend,
+                    zk_ergs: call.zk_ergs_used(),
+                },
                 traces: call.traces,
                 coverage: call.coverage,
                 debug: call.debug,
@@ -246,10 +365,72 @@ impl FuzzedExecutor {
 
     /// Stores fuzz state for use with [fuzz_calldata_from_state]
     pub fn build_fuzz_state(&self) -> EvmFuzzState {
-        if let Some(fork_db) = self.executor.backend.active_fork_db() {
-            build_initial_state(fork_db, &self.config.dictionary)
-        } else {
-            build_initial_state(self.executor.backend.mem_db(), &self.config.dictionary)
-        }
+        build_fuzz_state(&self.executor, &self.config)
+    }
+}
+
+/// Builds the fuzz state from the state of `executor`, see [FuzzedExecutor::build_fuzz_state].
+fn build_fuzz_state(executor: &Executor, config: &FuzzConfig) -> EvmFuzzState {
+    if let Some(fork_db) = executor.backend.active_fork_db() {
+        build_initial_state(fork_db, &config.dictionary)
+    } else {
+        build_initial_state(executor.backend.mem_db(), &config.dictionary)
     }
 }
+
+/// The number of inputs rejected by `vm.assume`, by `(address, pc)` call site.
+type AssumeRejectionCounts = BTreeMap<(Address, Option<usize>), u32>;
+
+/// Cancels the remaining runs of a parallel fuzz worker once a worker before it failed, as only
+/// the failure of the first failing worker is reported.
+struct WorkerCancellation<'a> {
+    /// The index of the worker.
+    worker: usize,
+    /// The index of the first worker that failed so far.
+    first_failure: &'a AtomicUsize,
+}
+
+impl WorkerCancellation<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.first_failure.load(Ordering::Relaxed) < self.worker
+    }
+
+    fn fail(&self) {
+        self.first_failure.fetch_min(self.worker, Ordering::Relaxed);
+    }
+}
+
+/// Merges the results of parallel fuzz workers as if their runs were executed one after another,
+/// up to the first failing worker.
+///
+/// The workers before the first failing one ran all of their cases, so the merged result doesn't
+/// depend on which worker finished first.
+fn merge_worker_results(mut results: Vec<FuzzTestResult>) -> FuzzTestResult {
+    let last = results.iter().position(|result| !result.success).unwrap_or(results.len() - 1);
+    results.truncate(last + 1);
+
+    let first_case = results
+        .iter()
+        .find(|result| !result.gas_by_case.is_empty())
+        .map(|result| result.first_case.clone())
+        .unwrap_or_default();
+    let gas_by_case =
+        results.iter().flat_map(|result| result.gas_by_case.iter().copied()).collect();
+    let zk_ergs_by_case =
+        results.iter().flat_map(|result| result.zk_ergs_by_case.iter().copied()).collect();
+    let coverage =
+        results.iter_mut().filter_map(|result| result.coverage.take()).reduce(HitMaps::merge);
+    let mut assume_rejections = AssumeRejectionCounts::new();
+    for rejection in results.iter().flat_map(|result| &result.assume_rejections) {
+        *assume_rejections.entry((rejection.address, rejection.pc)).or_default() += rejection.count;
+    }
+
+    // the logs, traces and failure are the ones of the last worker, like for sequential runs
+    let mut merged = results.pop().expect("no fuzz workers");
+    merged.first_case = first_case;
+    merged.gas_by_case = gas_by_case;
+    merged.zk_ergs_by_case = zk_ergs_by_case;
+    merged.coverage = coverage;
+    merged.assume_rejections = AssumeRejection::from_counts(assume_rejections);
+    merged
+}
@@ -1,10 +1,11 @@
-use super::{BasicTxDetails, InvariantContract};
+use super::{fund_zk_sender, BasicTxDetails, InvariantContract};
 use crate::executors::{Executor, RawCallResult};
 use alloy_json_abi::Function;
 use alloy_primitives::{Address, Bytes, Log};
 use eyre::Result;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::{constants::CALLER, decode::RevertDecoder};
+use foundry_evm_coverage::HitMaps;
 use foundry_evm_fuzz::{BaseCounterExample, CounterExample, FuzzedCases, Reason};
 use foundry_evm_traces::{load_contracts, CallTraceArena, TraceKind, Traces};
 use itertools::Itertools;
@@ -13,7 +14,7 @@ use proptest::test_runner::TestError;
 use rand::{seq, thread_rng, Rng};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use revm::primitives::U256;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 /// Stores information about failures and reverts of the invariant tests.
 #[derive(Clone, Default)]
@@ -50,6 +51,14 @@ pub struct InvariantFuzzTestResult {
     /// The entire inputs of the last run of the invariant campaign, used for
     /// replaying the run for collecting traces.
     pub last_run_inputs: Vec<BasicTxDetails>,
+
+    /// The coverage of a checkpointed campaign, including the runs before it was resumed.
+    pub coverage: Option<HitMaps>,
+
+    /// Number of runs completed before the campaign was resumed from a checkpoint.
+    pub resumed_runs: usize,
+    /// Number of calls of the runs completed before the campaign was resumed.
+    pub resumed_calls: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -138,7 +147,9 @@ impl InvariantFuzzError {
         set_up_inner_replay(&mut executor, &self.inner_sequence);
 
         // Replay each call from the sequence until we break the invariant.
+        let mut funded = HashSet::new();
         for (sender, (addr, bytes)) in calls.iter() {
+            fund_zk_sender(&mut executor, *sender, &mut funded);
             let call_result = executor
                 .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
                 .expect("bad call to evm");
@@ -207,9 +218,11 @@ impl InvariantFuzzError {
             }
         }
 
+        let mut funded = HashSet::new();
         for (seq_idx, call_index) in new_sequence.iter().enumerate() {
             let (sender, (addr, bytes)) = &calls[*call_index];
 
+            fund_zk_sender(&mut executor, *sender, &mut funded);
             executor
                 .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
                 .expect("bad call to evm");
@@ -2,13 +2,18 @@ use super::{InvariantFailures, InvariantFuzzError};
 use crate::executors::{Executor, RawCallResult};
 use alloy_dyn_abi::JsonAbiExt;
 use alloy_json_abi::Function;
-use alloy_primitives::Log;
+use alloy_primitives::{Address, Log};
 use foundry_common::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::constants::CALLER;
 use foundry_evm_coverage::HitMaps;
 use foundry_evm_fuzz::invariant::{BasicTxDetails, InvariantContract};
 use foundry_evm_traces::{load_contracts, TraceKind, Traces};
-use revm::primitives::U256;
+use revm::{primitives::U256, DatabaseRef};
+use std::collections::HashSet;
+
+/// The minimum balance of the senders of the fuzzed calls in zk mode, paying the fees the
+/// bootloader charges for their transactions in the ZK-VM.
+const ZK_SENDER_MIN_BALANCE: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
 
 /// Given the executor state, asserts that no invariant has been broken. Otherwise, it fills the
 /// external `invariant_failures.failed_invariant` map and returns a generic error.
@@ -66,6 +71,25 @@ pub fn assert_invariants(
     Some(call_result)
 }
 
+/// Funds the `sender` of a fuzzed call with [ZK_SENDER_MIN_BALANCE] in zk mode, if lower, so it
+/// can pay for its transaction in the ZK-VM.
+///
+/// A sender is only funded before its first call of a sequence, recorded in `funded`, so its
+/// later calls see the balance its previous calls left it with. The fuzzed calls, their replays
+/// and the shrinking of their sequences fund their senders alike, so a sequence executes the same
+/// way in all of them.
+pub fn fund_zk_sender(executor: &mut Executor, sender: Address, funded: &mut HashSet<Address>) {
+    if !executor.use_zk || !funded.insert(sender) {
+        return
+    }
+    let (account, slot) = foundry_zksync_core::state::get_balance_storage(sender);
+    let balance = executor.backend.storage_ref(account, slot).unwrap_or_default();
+    if balance < ZK_SENDER_MIN_BALANCE {
+        trace!(?sender, ?balance, "funding zk sender");
+        let _ = executor.set_balance(sender, ZK_SENDER_MIN_BALANCE);
+    }
+}
+
 /// Replays the provided invariant run for collecting the logs and traces from all depths.
 #[allow(clippy::too_many_arguments)]
 pub fn replay_run(
@@ -85,7 +109,9 @@ pub fn replay_run(
     // set_up_inner_replay(&mut executor, &inputs);
 
     // Replay each call from the sequence until we break the invariant.
+    let mut funded = HashSet::new();
     for (sender, (addr, bytes)) in inputs.iter() {
+        fund_zk_sender(&mut executor, *sender, &mut funded);
         let call_result = executor
             .call_raw_committing(*sender, *addr, bytes.clone(), U256::ZERO)
             .expect("bad call to evm");
@@ -3,8 +3,8 @@ use crate::{
     inspectors::Fuzzer,
 };
 use alloy_dyn_abi::DynSolValue;
-use alloy_json_abi::JsonAbi;
-use alloy_primitives::{Address, FixedBytes, U256};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{keccak256, Address, FixedBytes, Selector, B256, U256};
 use eyre::{eyre, ContextCompat, Result};
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_config::{FuzzDictionaryConfig, InvariantConfig};
@@ -12,30 +12,40 @@ use foundry_evm_core::{
     constants::{CALLER, CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
     utils::{get_function, StateChangeset},
 };
+use foundry_evm_coverage::merge_coverages;
 use foundry_evm_fuzz::{
     invariant::{
-        ArtifactFilters, BasicTxDetails, FuzzRunIdentifiedContracts, InvariantContract,
-        RandomCallGenerator, SenderFilters, TargetedContracts,
+        ArtifactFilters, BasicTxDetails, CheckpointConfig, FuzzRunIdentifiedContracts,
+        InvariantCheckpoint, InvariantContract, RandomCallGenerator, SelectorWeights,
+        SenderFilters, TargetedContracts,
     },
     strategies::{
-        build_initial_state, collect_created_contracts, collect_state_from_call, invariant_strat,
-        override_call_strat, EvmFuzzState,
+        build_initial_state, collect_created_contracts, collect_state_from_call,
+        collect_zk_state_from_call, invariant_strat, override_call_strat, EvmFuzzState,
     },
     FuzzCase, FuzzedCases,
 };
+use foundry_zksync_core::{
+    convert::ConvertH160, get_account_code_key, ACCOUNT_CODE_STORAGE_ADDRESS,
+};
 use parking_lot::{Mutex, RwLock};
 use proptest::{
     strategy::{BoxedStrategy, Strategy, ValueTree},
-    test_runner::{TestCaseError, TestRunner},
+    test_runner::{RngAlgorithm, TestCaseError, TestCaseResult, TestRng, TestRunner},
+};
+use rand::Rng;
+use revm::{primitives::HashMap, DatabaseCommit, DatabaseRef};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    sync::Arc,
 };
-use revm::{primitives::HashMap, DatabaseCommit};
-use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
 
 mod error;
 pub use error::{InvariantFailures, InvariantFuzzError, InvariantFuzzTestResult};
 
 mod funcs;
-pub use funcs::{assert_invariants, replay_run};
+pub use funcs::{assert_invariants, fund_zk_sender, replay_run};
 
 /// Alias for (Dictionary for fuzzing, initial contracts to fuzz and an InvariantStrategy).
 type InvariantPreparation =
@@ -73,17 +83,22 @@ pub struct InvariantExecutor<'a> {
     project_contracts: &'a ContractsByArtifact,
     /// Filters contracts to be fuzzed through their artifact identifiers.
     artifact_filters: ArtifactFilters,
+    /// Where and how often the campaign is checkpointed, if it is.
+    checkpoint: Option<CheckpointConfig>,
 }
 
 impl<'a> InvariantExecutor<'a> {
     /// Instantiates a fuzzed executor EVM given a testrunner
     pub fn new(
-        executor: Executor,
+        mut executor: Executor,
         runner: TestRunner,
         config: InvariantConfig,
         setup_contracts: &'a ContractsByAddress,
         project_contracts: &'a ContractsByArtifact,
     ) -> Self {
+        if executor.use_zk {
+            executor.inspector.collect_zk_fuzz_values();
+        }
         Self {
             executor,
             runner,
@@ -91,9 +106,17 @@ impl<'a> InvariantExecutor<'a> {
             setup_contracts,
             project_contracts,
             artifact_filters: ArtifactFilters::default(),
+            checkpoint: None,
         }
     }
 
+    /// Checkpoints the campaign to `checkpoint.path` every `checkpoint.interval` runs, and resumes
+    /// it from its last checkpoint if `checkpoint.resume` is set.
+    pub fn with_checkpoint(mut self, checkpoint: Option<CheckpointConfig>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
     /// Fuzzes any deployed contract and checks any broken invariant at `invariant_address`.
     pub fn invariant_fuzz(
         &mut self,
@@ -104,13 +127,45 @@ impl<'a> InvariantExecutor<'a> {
             return Err(eyre!("Invariant test function should have no inputs"))
         }
 
+        // Resumes the campaign from its last checkpoint, with the RNG, the fuzz dictionary and the
+        // coverage it had then, unless the checkpoint was written by a campaign of other contracts
+        // or of another configuration.
+        let code_hash = self.code_hash(invariant_contract.address)?;
+        let resumed = match &self.checkpoint {
+            Some(checkpoint) if checkpoint.resume => InvariantCheckpoint::read(&checkpoint.path)?,
+            _ => None,
+        };
+        let resumed = resumed.filter(|resumed| match resumed.mismatch(code_hash, &self.config) {
+            Some(reason) => {
+                warn!(target: "forge::test::invariant", %reason, "discarding the checkpoint of the campaign");
+                false
+            }
+            None => true,
+        });
+        if let Some(resumed) = &resumed {
+            reseed(&mut self.runner, resumed.seed);
+        }
+
         let (fuzz_state, targeted_contracts, strat) = self.prepare_fuzzing(&invariant_contract)?;
 
+        if let Some(resumed) = &resumed {
+            resumed.restore_dictionary(&fuzz_state);
+        }
+        let mut resumed = resumed.unwrap_or_default();
+
+        // Stores the coverage of the campaign, to checkpoint it.
+        let coverage = RefCell::new(resumed.coverage.take());
+
+        // Stores the EraVM bytecode hashes collected in the fuzz dictionary of a zk campaign.
+        let zk_bytecode_hashes =
+            RefCell::new(BTreeSet::from_iter(std::mem::take(&mut resumed.zk_bytecode_hashes)));
+
         // Stores the consumed gas and calldata of every successful fuzz call.
         let fuzz_cases: RefCell<Vec<FuzzedCases>> = RefCell::new(Default::default());
 
         // Stores data related to reverts or failed assertions of the test.
-        let failures = RefCell::new(InvariantFailures::new());
+        let failures =
+            RefCell::new(InvariantFailures { reverts: resumed.reverts, ..Default::default() });
 
         // Stores the calldata in the last run.
         let last_run_calldata: RefCell<Vec<BasicTxDetails>> = RefCell::new(vec![]);
@@ -137,7 +192,7 @@ impl<'a> InvariantExecutor<'a> {
         // during the run. We need another proptest runner to query for random
         // values.
         let branch_runner = RefCell::new(self.runner.clone());
-        let _ = self.runner.run(&strat, |mut inputs| {
+        let run = |mut inputs: Vec<BasicTxDetails>| -> TestCaseResult {
             // We stop the run immediately if we have reverted, and `fail_on_revert` is set.
             if self.config.fail_on_revert && failures.borrow().reverts > 0 {
                 return Err(TestCaseError::fail("Revert occurred."))
@@ -152,14 +207,32 @@ impl<'a> InvariantExecutor<'a> {
             // Created contracts during a run.
             let mut created_contracts = vec![];
 
+            // The senders funded in zk mode during the run.
+            let mut funded_senders = HashSet::new();
+
             for current_run in 0..self.config.depth {
                 let (sender, (address, calldata)) = inputs.last().expect("no input generated");
 
                 // Executes the call from the randomly generated sequence.
-                let call_result = executor
+                fund_zk_sender(&mut executor, *sender, &mut funded_senders);
+                let mut call_result = executor
                     .call_raw(*sender, *address, calldata.clone(), U256::ZERO)
                     .expect("could not make raw evm call");
 
+                if self.checkpoint.is_some() {
+                    let mut coverage = coverage.borrow_mut();
+                    *coverage = merge_coverages(coverage.take(), call_result.coverage.take());
+                }
+                if self.config.dictionary.include_storage {
+                    if let Some(values) = call_result
+                        .cheatcodes
+                        .as_ref()
+                        .and_then(|cheats| cheats.zk_fuzz_values.as_ref())
+                    {
+                        zk_bytecode_hashes.borrow_mut().extend(values.bytecode_hashes.iter());
+                    }
+                }
+
                 // Collect data for fuzzing from the state changeset.
                 let mut state_changeset =
                     call_result.state_changeset.to_owned().expect("no changesets");
@@ -190,6 +263,7 @@ impl<'a> InvariantExecutor<'a> {
                     calldata: calldata.clone(),
                     gas: call_result.gas_used,
                     stipend: call_result.stipend,
+                    zk_ergs: call_result.zk_ergs_used(),
                 });
 
                 let RichInvariantResults { success: can_continue, call_result: call_results } =
@@ -237,7 +311,62 @@ impl<'a> InvariantExecutor<'a> {
             fuzz_cases.borrow_mut().push(FuzzedCases::new(fuzz_runs));
 
             Ok(())
-        });
+        };
+
+        // With checkpoints, the runs are split in batches of `checkpoint.interval` runs, and the
+        // campaign is checkpointed after each of them. Once an invariant is broken it no longer
+        // is, so that an interrupted campaign that broke one runs the runs since its last
+        // checkpoint again.
+        if let Some(checkpoint) = &self.checkpoint {
+            let total_runs = self.runner.config().cases;
+            let mut completed = resumed.runs;
+            while completed < total_runs {
+                let runs = match checkpoint.interval {
+                    0 => total_runs - completed,
+                    interval => interval.min(total_runs - completed),
+                };
+                let config =
+                    proptest::test_runner::Config { cases: runs, ..self.runner.config().clone() };
+                let mut runner = TestRunner::new_with_rng(config, self.runner.new_rng());
+                if runner.run(&strat, &run).is_err() {
+                    break
+                }
+                completed += runs;
+
+                if completed == total_runs || failures.borrow().error.is_some() {
+                    continue
+                }
+                let seed = B256::from(self.runner.rng().gen::<[u8; 32]>());
+                let mut state = InvariantCheckpoint {
+                    code_hash,
+                    config: self.config.clone(),
+                    runs: completed,
+                    calls: resumed.calls +
+                        fuzz_cases
+                            .borrow()
+                            .iter()
+                            .map(|sequence| sequence.cases().len())
+                            .sum::<usize>(),
+                    reverts: failures.borrow().reverts,
+                    seed,
+                    zk_bytecode_hashes: zk_bytecode_hashes.borrow().iter().copied().collect(),
+                    coverage: coverage.borrow().clone(),
+                    ..Default::default()
+                };
+                state.capture_dictionary(&fuzz_state);
+                if let Err(err) = state.write(&checkpoint.path) {
+                    warn!(target: "forge::test::invariant", %err, "failed to checkpoint the campaign");
+                }
+                reseed(&mut self.runner, seed);
+            }
+
+            // The campaign is over, it's no longer resumed
+            if let Err(err) = InvariantCheckpoint::remove(&checkpoint.path) {
+                warn!(target: "forge::test::invariant", %err, "failed to remove the checkpoint");
+            }
+        } else {
+            let _ = self.runner.run(&strat, &run);
+        }
 
         trace!(target: "forge::test::invariant::dictionary", "{:?}", fuzz_state.read().values().iter().map(hex::encode).collect::<Vec<_>>());
 
@@ -248,9 +377,30 @@ impl<'a> InvariantExecutor<'a> {
             cases: fuzz_cases.into_inner(),
             reverts,
             last_run_inputs: last_run_calldata.take(),
+            coverage: coverage.into_inner(),
+            resumed_runs: resumed.runs as usize,
+            resumed_calls: resumed.calls,
         })
     }
 
+    /// Returns the hash of the code of the test contract and of the contracts deployed by its
+    /// `setUp()`, with their EraVM bytecode hashes in zk mode.
+    fn code_hash(&self, test_address: Address) -> Result<B256> {
+        let mut hashes = Vec::new();
+        for address in std::iter::once(test_address).chain(self.setup_contracts.keys().copied()) {
+            let info = self.executor.backend.basic_ref(address)?.unwrap_or_default();
+            hashes.extend_from_slice(info.code_hash.as_slice());
+            if self.executor.use_zk {
+                let era_hash = self.executor.backend.storage_ref(
+                    ACCOUNT_CODE_STORAGE_ADDRESS.to_address(),
+                    get_account_code_key(address),
+                )?;
+                hashes.extend_from_slice(&era_hash.to_be_bytes::<32>());
+            }
+        }
+        Ok(keccak256(hashes))
+    }
+
     /// Prepares certain structures to execute the invariant tests:
     /// * Fuzz dictionary
     /// * Targeted contracts
@@ -277,12 +427,18 @@ impl<'a> InvariantExecutor<'a> {
         let targeted_contracts: FuzzRunIdentifiedContracts =
             Arc::new(Mutex::new(targeted_contracts));
 
+        // Relative weights used when sampling the targeted functions.
+        let selector_weights = Arc::new(
+            self.select_selector_weights(invariant_contract.address, invariant_contract.abi)?,
+        );
+
         // Creates the invariant strategy.
         let strat = invariant_strat(
             fuzz_state.clone(),
             targeted_senders,
             targeted_contracts.clone(),
             self.config.dictionary.dictionary_weight,
+            selector_weights.clone(),
         )
         .no_shrink()
         .boxed();
@@ -300,6 +456,7 @@ impl<'a> InvariantExecutor<'a> {
                     fuzz_state.clone(),
                     targeted_contracts.clone(),
                     target_contract_ref.clone(),
+                    selector_weights,
                 ),
                 target_contract_ref,
             ));
@@ -625,6 +782,45 @@ impl<'a> InvariantExecutor<'a> {
         Ok(())
     }
 
+    /// Selects the relative weights of the functions to fuzz, from the `selector_weights`
+    /// configuration and the contract method `targetSelectorWeights()`.
+    ///
+    /// Priority:
+    ///
+    /// targetSelectorWeights > selector_weights
+    pub fn select_selector_weights(
+        &self,
+        address: Address,
+        abi: &JsonAbi,
+    ) -> eyre::Result<SelectorWeights> {
+        let mut weights = SelectorWeights::new();
+        for (key, weight) in &self.config.selector_weights {
+            let selector = if key.starts_with("0x") {
+                hex::decode(key)
+                    .ok()
+                    .filter(|bytes| bytes.len() == 4)
+                    .map(|bytes| Selector::from_slice(&bytes))
+            } else {
+                Function::parse(key).ok().map(|func| func.selector())
+            };
+            let selector = selector.ok_or_else(|| {
+                eyre!("[selector_weights] invalid function signature or selector: {key}")
+            })?;
+            weights.insert(selector, *weight);
+        }
+
+        // `targetSelectorWeights() -> (bytes4, uint256)[]`.
+        if let Some(func) = abi.functions().find(|func| func.name == "targetSelectorWeights") {
+            let call_result = self
+                .executor
+                .call::<_, _>(CALLER, address, func.clone(), vec![], U256::ZERO, None)
+                .map_err(|err| eyre!("failed to call targetSelectorWeights(): {err}"))?;
+            weights.extend(decode_selector_weights(call_result.result)?);
+        }
+
+        Ok(weights)
+    }
+
     /// Adds the address and fuzzable functions to `TargetedContracts`.
     fn add_address_with_functions(
         &self,
@@ -681,6 +877,34 @@ impl<'a> InvariantExecutor<'a> {
     }
 }
 
+/// Decodes the `(bytes4 selector, uint256 weight)[]` returned by `targetSelectorWeights()`.
+fn decode_selector_weights(value: DynSolValue) -> Result<Vec<(Selector, u32)>> {
+    let invalid = || eyre!("targetSelectorWeights() should return a (bytes4, uint256)[] array");
+    let mut weights = Vec::new();
+    for entry in value.as_array().ok_or_else(invalid)? {
+        let elements = match entry {
+            DynSolValue::CustomStruct { tuple, .. } => tuple.as_slice(),
+            DynSolValue::Tuple(tuple) => tuple.as_slice(),
+            _ => return Err(invalid()),
+        };
+        let [selector, weight] = elements else { return Err(invalid()) };
+        let selector = selector
+            .as_fixed_bytes()
+            .filter(|(_, size)| *size == 4)
+            .map(|(bytes, _)| Selector::from_slice(&bytes[..4]))
+            .ok_or_else(invalid)?;
+        let (weight, _) = weight.as_uint().ok_or_else(invalid)?;
+        weights.push((selector, weight.saturating_to()));
+    }
+    Ok(weights)
+}
+
+/// Reseeds the runner the runs of a campaign are generated from.
+fn reseed(runner: &mut TestRunner, seed: B256) {
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed.0);
+    *runner = TestRunner::new_with_rng(runner.config().clone(), rng);
+}
+
 /// Collects data from call for fuzzing. However, it first verifies that the sender is not an EOA
 /// before inserting it into the dictionary. Otherwise, we flood the dictionary with
 /// randomly generated addresses.
@@ -704,7 +928,12 @@ fn collect_data(
         sender_changeset = state_changeset.remove(sender);
     }
 
-    collect_state_from_call(&call_result.logs, &*state_changeset, fuzz_state, config);
+    collect_state_from_call(&call_result.logs, &*state_changeset, fuzz_state.clone(), config);
+    if let Some(values) =
+        call_result.cheatcodes.as_ref().and_then(|cheats| cheats.zk_fuzz_values.as_ref())
+    {
+        collect_zk_state_from_call(values, fuzz_state, config);
+    }
 
     // Re-add changes
     if let Some(changed) = sender_changeset {
@@ -24,7 +24,11 @@ use foundry_evm_core::{
 };
 use foundry_evm_coverage::HitMaps;
 use foundry_evm_traces::CallTraceArena;
-use foundry_zksync_core::ZkTransactionMetadata;
+use foundry_zksync_core::{
+    era_state::EraState,
+    vm::{ZkExecutionMetrics, ZkResourceLimitExceeded},
+    ZkTransactionMetadata,
+};
 use itertools::Itertools;
 use revm::{
     db::{DatabaseCommit, DatabaseRef},
@@ -34,7 +38,7 @@ use revm::{
     },
     Database,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 mod builder;
 pub use builder::ExecutorBuilder;
@@ -159,6 +163,34 @@ impl Executor {
         Ok(self)
     }
 
+    /// Set the code of an account.
+    pub fn set_code(&mut self, address: Address, code: Bytecode) -> DatabaseResult<&mut Self> {
+        let mut account = self.backend.basic_ref(address)?.unwrap_or_default();
+        account.code_hash = code.hash_slow();
+        account.code = Some(code);
+
+        self.backend.insert_account_info(address, account);
+
+        Ok(self)
+    }
+
+    /// Loads the storage of an era_test_node state, and sets the code of its accounts.
+    ///
+    /// The code keeps its EraVM bytecode hash, so it can be found as a factory dependency.
+    pub fn load_era_state(&mut self, state: &EraState) -> DatabaseResult<&mut Self> {
+        for (slot, value) in &state.fork_storage.storage {
+            let (key, value) = (U256::from_be_bytes(slot.key.0), U256::from_be_bytes(value.0));
+            self.backend.insert_account_storage(slot.address, key, value)?;
+        }
+        for (address, hash, code) in state.account_codes() {
+            let mut account = self.backend.basic_ref(address)?.unwrap_or_default();
+            account.code_hash = hash;
+            account.code = Some(Bytecode::new_raw(code.clone()).to_checked());
+            self.backend.insert_account_info(address, account);
+        }
+        Ok(self)
+    }
+
     /// Gets the nonce of an account
     pub fn get_nonce(&self, address: Address) -> DatabaseResult<u64> {
         Ok(self.backend.basic_ref(address)?.map(|acc| acc.nonce).unwrap_or_default())
@@ -224,8 +256,11 @@ impl Executor {
                         logs: res.logs,
                         debug: res.debug,
                         labels: res.labels,
+                        proxies: res.proxies,
+                        zk_calls: res.zk_calls,
                         state_changeset: None,
                         transactions: None,
+                        zk_limit_exceeded: None,
                     })))
                 }
             }
@@ -393,6 +428,8 @@ impl Executor {
         let mut result = self.call_raw_with_env(env)?;
         self.commit(&mut result);
 
+        let zk_limit_exceeded =
+            result.cheatcodes.as_ref().and_then(|c| c.zk_limit_exceeded.clone());
         let RawCallResult {
             exit_reason,
             out,
@@ -400,6 +437,8 @@ impl Executor {
             gas_refunded,
             logs,
             labels,
+            proxies,
+            zk_calls,
             traces,
             debug,
             env,
@@ -427,8 +466,11 @@ impl Executor {
                         logs,
                         debug,
                         labels,
+                        proxies,
+                        zk_calls,
                         state_changeset: None,
                         transactions: None,
+                        zk_limit_exceeded,
                     })));
                 }
             }
@@ -444,8 +486,11 @@ impl Executor {
                     logs,
                     debug,
                     labels,
+                    proxies,
+                    zk_calls,
                     state_changeset: None,
                     transactions: None,
+                    zk_limit_exceeded,
                 })))
             }
         };
@@ -624,8 +669,12 @@ pub struct ExecutionErr {
     pub traces: Option<CallTraceArena>,
     pub debug: Option<DebugArena>,
     pub labels: HashMap<Address, String>,
+    pub proxies: HashMap<Address, Address>,
+    pub zk_calls: HashSet<(Address, Address)>,
     pub transactions: Option<BroadcastableTransactions>,
     pub state_changeset: Option<StateChangeset>,
+    /// The resource limit a ZK-VM execution of the call was aborted for, if any
+    pub zk_limit_exceeded: Option<ZkResourceLimitExceeded>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -683,6 +732,10 @@ pub struct CallResult {
     pub logs: Vec<Log>,
     /// The labels assigned to addresses during the call
     pub labels: HashMap<Address, String>,
+    /// The EIP-1967 proxies called while tracing, mapped to their implementation
+    pub proxies: HashMap<Address, Address>,
+    /// The calls made from the EVM to the ZK-VM, as `(caller, callee)` pairs
+    pub zk_calls: HashSet<(Address, Address)>,
     /// The traces of the call
     pub traces: Option<CallTraceArena>,
     /// The coverage info collected during the call
@@ -700,6 +753,12 @@ pub struct CallResult {
     pub env: Env,
     /// breakpoints
     pub breakpoints: Breakpoints,
+    /// The ergs used by the ZK-VM executions of the call, if any
+    pub zk_ergs_used: Option<u64>,
+    /// The pubdata published and gas refunded by the ZK-VM executions of the call, if any
+    pub zk_metrics: Option<ZkExecutionMetrics>,
+    /// The resource limit a ZK-VM execution of the call was aborted for, if any
+    pub zk_limit_exceeded: Option<ZkResourceLimitExceeded>,
 }
 
 /// The result of a raw call.
@@ -726,6 +785,10 @@ pub struct RawCallResult {
     pub logs: Vec<Log>,
     /// The labels assigned to addresses during the call
     pub labels: HashMap<Address, String>,
+    /// The EIP-1967 proxies called while tracing, mapped to their implementation
+    pub proxies: HashMap<Address, Address>,
+    /// The calls made from the EVM to the ZK-VM, as `(caller, callee)` pairs
+    pub zk_calls: HashSet<(Address, Address)>,
     /// The traces of the call
     pub traces: Option<CallTraceArena>,
     /// The coverage info collected during the call
@@ -761,6 +824,8 @@ impl Default for RawCallResult {
             stipend: 0,
             logs: Vec::new(),
             labels: HashMap::new(),
+            proxies: HashMap::new(),
+            zk_calls: HashSet::new(),
             traces: None,
             coverage: None,
             debug: None,
@@ -774,6 +839,13 @@ impl Default for RawCallResult {
     }
 }
 
+impl RawCallResult {
+    /// Returns the ergs used by the ZK-VM executions of the call, which aren't part of its EVM gas.
+    pub fn zk_ergs_used(&self) -> u64 {
+        self.cheatcodes.as_ref().map_or(0, |cheatcodes| cheatcodes.zk_metrics.ergs_used)
+    }
+}
+
 /// Calculates the initial gas stipend for a transaction
 fn calc_stipend(calldata: &[u8], spec: SpecId) -> u64 {
     let non_zero_data_cost = if SpecId::enabled(spec, SpecId::ISTANBUL) { 16 } else { 68 };
@@ -809,8 +881,17 @@ fn convert_executed_result(
 
     let combined_logs =
         inspector.cheatcodes.as_ref().map(|cheatcodes| cheatcodes.combined_logs.clone());
-    let InspectorData { mut logs, labels, traces, coverage, debug, cheatcodes, chisel_state } =
-        inspector.collect();
+    let InspectorData {
+        mut logs,
+        labels,
+        proxies,
+        zk_calls,
+        traces,
+        coverage,
+        debug,
+        cheatcodes,
+        chisel_state,
+    } = inspector.collect();
 
     let logs = match combined_logs {
         Some(combined_logs) => {
@@ -850,6 +931,8 @@ fn convert_executed_result(
         stipend,
         logs,
         labels,
+        proxies,
+        zk_calls,
         traces,
         coverage,
         debug,
@@ -876,6 +959,8 @@ fn convert_call_result(
         stipend,
         logs,
         labels,
+        proxies,
+        zk_calls,
         traces,
         coverage,
         debug,
@@ -885,6 +970,14 @@ fn convert_call_result(
         ..
     } = call_result;
 
+    let zk_metrics = call_result
+        .cheatcodes
+        .as_ref()
+        .map(|c| c.zk_metrics)
+        .filter(|metrics| metrics.ergs_used > 0);
+    let zk_ergs_used = zk_metrics.map(|metrics| metrics.ergs_used);
+    let zk_limit_exceeded =
+        call_result.cheatcodes.as_ref().and_then(|c| c.zk_limit_exceeded.clone());
     let breakpoints = if let Some(c) = call_result.cheatcodes {
         c.breakpoints
     } else {
@@ -908,6 +1001,8 @@ fn convert_call_result(
                 stipend,
                 logs,
                 labels,
+                proxies,
+                zk_calls,
                 traces,
                 coverage,
                 debug,
@@ -915,6 +1010,9 @@ fn convert_call_result(
                 state_changeset,
                 env,
                 breakpoints,
+                zk_ergs_used,
+                zk_metrics,
+                zk_limit_exceeded,
                 skipped: false,
             })
         }
@@ -933,8 +1031,11 @@ fn convert_call_result(
                 traces,
                 debug,
                 labels,
+                proxies,
+                zk_calls,
                 transactions,
                 state_changeset,
+                zk_limit_exceeded,
             })))
         }
     }
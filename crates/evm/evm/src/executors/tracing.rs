@@ -1,6 +1,6 @@
 use crate::executors::{Executor, ExecutorBuilder};
 use foundry_compilers::EvmVersion;
-use foundry_config::{utils::evm_spec_id, Chain, Config};
+use foundry_config::{utils::evm_spec_id, Chain, Config, ForkBlock};
 use foundry_evm_core::{backend::Backend, fork::CreateFork, opts::EvmOpts};
 use revm::primitives::Env;
 use std::ops::{Deref, DerefMut};
@@ -34,7 +34,10 @@ impl TracingExecutor {
         mut evm_opts: EvmOpts,
     ) -> eyre::Result<(Env, Option<CreateFork>, Option<Chain>)> {
         evm_opts.fork_url = Some(config.get_rpc_url_or_localhost_http()?.into_owned());
-        evm_opts.fork_block_number = config.fork_block_number;
+        // a tag is only resolved once it's pinned, see `foundry_cli::utils::pin_config_fork_block`
+        if let Some(number) = config.fork_block_number.and_then(ForkBlock::number) {
+            evm_opts.fork_block_number = Some(number);
+        }
 
         let env = evm_opts.evm_env().await?;
 
@@ -2,9 +2,12 @@ use super::{
     Cheatcodes, CheatsConfig, ChiselState, CoverageCollector, Debugger, Fuzzer, LogCollector,
     StackSnapshotType, TracePrinter, TracingInspector, TracingInspectorConfig,
 };
-use alloy_primitives::{Address, Bytes, Log, B256, U256};
+use alloy_primitives::{b256, Address, Bytes, Log, B256, U256};
+use alloy_sol_types::SolCall;
+use foundry_cheatcodes::Vm;
 use foundry_evm_core::{
     backend::DatabaseExt,
+    constants::CHEATCODE_ADDRESS,
     debug::DebugArena,
     utils::{eval_to_instruction_result, halt_to_instruction_result},
 };
@@ -13,13 +16,32 @@ use foundry_evm_traces::CallTraceArena;
 use revm::{
     evm_inner,
     interpreter::{
-        return_revert, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult, Interpreter,
-        Stack,
+        return_ok, return_revert, CallInputs, CallScheme, CreateInputs, Gas, InstructionResult,
+        Interpreter, Stack,
     },
     primitives::{BlockEnv, Env, ExecutionResult, Output, State, TransactTo},
     DatabaseCommit, EVMData, Inspector,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// The EIP-1967 slot holding the implementation of a proxy,
+/// `bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)`.
+pub const EIP1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// The EIP-1967 slot holding the beacon of a beacon proxy,
+/// `bytes32(uint256(keccak256('eip1967.proxy.beacon')) - 1)`.
+pub const EIP1967_BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// The selector of `implementation()`, which a beacon proxy calls on its beacon.
+const BEACON_IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// The lowest verbosity the traces of the calls are displayed at.
+const MIN_TRACE_VERBOSITY: u8 = 3;
 
 #[derive(Clone, Debug, Default)]
 #[must_use = "builders do nothing unless you call `build` on them"]
@@ -40,6 +62,13 @@ pub struct InspectorStackBuilder {
     pub fuzzer: Option<Fuzzer>,
     /// Whether to enable tracing.
     pub trace: Option<bool>,
+    /// Whether the tracer records the steps of the calls, to locate their source lines.
+    pub trace_steps: Option<bool>,
+    /// The bytes of return data the tracer records of a successful call, all of it if `None`.
+    pub trace_return_data_limit: Option<usize>,
+    /// The verbosity the tracer starts recording the calls at, if it's changed by
+    /// `vm.setTraceVerbosity`.
+    pub trace_verbosity: Option<u8>,
     /// Whether to enable the debugger.
     pub debug: Option<bool>,
     /// Whether logs should be collected.
@@ -133,6 +162,28 @@ impl InspectorStackBuilder {
         self
     }
 
+    /// Set whether the tracer records the steps of the calls.
+    #[inline]
+    pub fn trace_steps(mut self, yes: bool) -> Self {
+        self.trace_steps = Some(yes);
+        self
+    }
+
+    /// Set the bytes of return data the tracer records of a successful call, all of it if `None`.
+    #[inline]
+    pub fn trace_return_data_limit(mut self, limit: Option<usize>) -> Self {
+        self.trace_return_data_limit = limit;
+        self
+    }
+
+    /// Set the verbosity the tracer starts recording the calls at, if it's changed by
+    /// `vm.setTraceVerbosity`.
+    #[inline]
+    pub fn trace_verbosity(mut self, verbosity: Option<u8>) -> Self {
+        self.trace_verbosity = verbosity;
+        self
+    }
+
     /// Set whether to enable the call isolation.
     /// For description of call isolation, see [`InspectorStack::enable_isolation`].
     #[inline]
@@ -151,6 +202,9 @@ impl InspectorStackBuilder {
             cheatcodes,
             fuzzer,
             trace,
+            trace_steps,
+            trace_return_data_limit,
+            trace_verbosity,
             debug,
             logs,
             coverage,
@@ -174,7 +228,9 @@ impl InspectorStackBuilder {
         stack.collect_logs(logs.unwrap_or(true));
         stack.enable_debugger(debug.unwrap_or(false));
         stack.print(print.unwrap_or(false));
-        stack.tracing(trace.unwrap_or(false));
+        stack.tracing_with_steps(trace.unwrap_or(false), trace_steps.unwrap_or(false));
+        stack.limit_trace_return_data(trace_return_data_limit);
+        stack.filter_trace_verbosity(trace_verbosity);
 
         stack.enable_isolation(enable_isolation);
 
@@ -233,10 +289,34 @@ fn update_state<DB: DatabaseExt>(state: &mut State, db: &mut DB) {
     }
 }
 
+/// Reads a storage slot of `address` loaded in the journaled state, or from the database if
+/// `from_db` is set and the slot isn't loaded.
+fn read_slot<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    address: Address,
+    slot: B256,
+    from_db: bool,
+) -> Option<B256> {
+    let key = U256::from_be_bytes(slot.0);
+    let loaded = data
+        .journaled_state
+        .state
+        .get(&address)
+        .and_then(|account| account.storage.get(&key))
+        .map(|value| value.present_value);
+    match loaded {
+        Some(value) => Some(value.into()),
+        None if from_db => data.db.storage(address, key).ok().map(B256::from),
+        None => None,
+    }
+}
+
 /// The collected results of [`InspectorStack`].
 pub struct InspectorData {
     pub logs: Vec<Log>,
     pub labels: HashMap<Address, String>,
+    pub proxies: HashMap<Address, Address>,
+    pub zk_calls: HashSet<(Address, Address)>,
     pub traces: Option<CallTraceArena>,
     pub debug: Option<DebugArena>,
     pub coverage: Option<HitMaps>,
@@ -276,7 +356,26 @@ pub struct InspectorStack {
     pub log_collector: Option<LogCollector>,
     pub printer: Option<TracePrinter>,
     pub tracer: Option<TracingInspector>,
+    /// The bytes of return data the tracer records of a successful call, all of it if `None`.
+    ///
+    /// Failing calls are always recorded in full, so their revert can be decoded, and so is the
+    /// code returned by creations, which identifies the created contracts.
+    pub trace_return_data_limit: Option<usize>,
+    /// The verbosity the calls are made at, changed by `vm.setTraceVerbosity`, if the tracer
+    /// filters the calls by verbosity.
+    ///
+    /// The tracer then only records the calls made at a verbosity at which traces are displayed,
+    /// along with the calls to `vm.setTraceVerbosity` and the outermost call.
+    pub trace_verbosity: Option<u8>,
+    /// Whether the tracer records each of the calls being executed, from the outermost one.
+    traced_calls: Vec<bool>,
     pub enable_isolation: bool,
+    /// The EIP-1967 proxies called while tracing, mapped to the implementation they delegated
+    /// to, read from their implementation slot or returned by their beacon.
+    pub proxies: HashMap<Address, Address>,
+    /// The contracts which delegated a call while tracing, the only ones whose implementation
+    /// slot is read.
+    pub delegators: HashSet<Address>,
 
     /// Flag marking if we are in the inner EVM context.
     pub in_inner_context: bool,
@@ -317,6 +416,22 @@ impl InspectorStack {
         }
     }
 
+    /// Sets the test contract, whose calls are executed in the EVM when using the ZK-VM.
+    #[inline]
+    pub fn set_zk_test_contract(&mut self, address: Address) {
+        if let Some(cheatcodes) = &mut self.cheatcodes {
+            cheatcodes.zk_test_contract = Some(address);
+        }
+    }
+
+    /// Collects the values observed by the ZK-VM executions of every call for the fuzz dictionary.
+    #[inline]
+    pub fn collect_zk_fuzz_values(&mut self) {
+        if let Some(cheatcodes) = &mut self.cheatcodes {
+            cheatcodes.zk_fuzz_values = Some(Default::default());
+        }
+    }
+
     /// Set the cheatcodes inspector.
     #[inline]
     pub fn set_cheatcodes(&mut self, cheatcodes: Cheatcodes) {
@@ -368,9 +483,15 @@ impl InspectorStack {
     /// Set whether to enable the tracer.
     #[inline]
     pub fn tracing(&mut self, yes: bool) {
+        self.tracing_with_steps(yes, false);
+    }
+
+    /// Set whether to enable the tracer, recording the steps of the calls if `steps` is set.
+    #[inline]
+    pub fn tracing_with_steps(&mut self, yes: bool, steps: bool) {
         self.tracer = yes.then(|| {
             TracingInspector::new(TracingInspectorConfig {
-                record_steps: false,
+                record_steps: steps,
                 record_memory_snapshots: false,
                 record_stack_snapshots: StackSnapshotType::None,
                 record_state_diff: false,
@@ -381,6 +502,126 @@ impl InspectorStack {
         });
     }
 
+    /// Set the bytes of return data the tracer records of a successful call, all of it if `None`.
+    #[inline]
+    pub fn limit_trace_return_data(&mut self, limit: Option<usize>) {
+        self.trace_return_data_limit = limit;
+    }
+
+    /// Set the verbosity the tracer starts recording the calls at, if it's changed by
+    /// `vm.setTraceVerbosity`.
+    #[inline]
+    pub fn filter_trace_verbosity(&mut self, verbosity: Option<u8>) {
+        self.trace_verbosity = verbosity;
+    }
+
+    /// Returns whether the tracer records a call to `address` with `input`.
+    fn records_call(&self, address: Address, input: &[u8]) -> bool {
+        let Some(verbosity) = self.trace_verbosity else { return true };
+        verbosity >= MIN_TRACE_VERBOSITY ||
+            self.traced_calls.is_empty() ||
+            (address == CHEATCODE_ADDRESS &&
+                input.starts_with(&Vm::setTraceVerbosityCall::SELECTOR))
+    }
+
+    /// Returns the tracer if it records the call being executed.
+    fn recording_tracer(&mut self) -> Option<&mut TracingInspector> {
+        if self.traced_calls.last() == Some(&false) {
+            return None
+        }
+        self.tracer.as_mut()
+    }
+
+    /// Returns the depth the tracer records the call being started at: the number of its
+    /// recorded parents if the calls are filtered, its depth otherwise.
+    fn traced_depth(&self, depth: u64) -> u64 {
+        match self.trace_verbosity {
+            Some(_) => self.traced_calls.iter().filter(|traced| **traced).count() as u64 - 1,
+            None if self.in_inner_context => depth + 1,
+            None => depth,
+        }
+    }
+
+    /// Returns the return data the tracer records of a call ending with `status`, cut to the
+    /// limit if the call succeeded.
+    ///
+    /// The cut return data is copied, a slice would keep the whole of it in memory.
+    fn traced_return_data(&self, status: InstructionResult, retdata: &Bytes) -> Bytes {
+        match self.trace_return_data_limit {
+            Some(limit) if retdata.len() > limit && matches!(status, return_ok!()) => {
+                Bytes::copy_from_slice(&retdata[..limit])
+            }
+            _ => retdata.clone(),
+        }
+    }
+
+    /// Records the implementation of the called contract if it's an EIP-1967 proxy, for the
+    /// traced calls which are decoded.
+    ///
+    /// The implementation slot is only read for the contracts which delegated a call, in the EVM
+    /// or in the ZK-VM, from the journaled state, where it was loaded if the proxy read it during
+    /// the call, or from the database if the call reverted. A beacon proxy is recorded when its
+    /// beacon, the one of its beacon slot, returns the implementation.
+    fn record_proxy<DB: DatabaseExt>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CallInputs,
+        status: InstructionResult,
+        retdata: &Bytes,
+    ) {
+        // delegate calls and call codes execute code on the storage of the caller
+        if !matches!(call.context.scheme, CallScheme::Call | CallScheme::StaticCall) {
+            return
+        }
+        let reverted = !matches!(status, return_ok!());
+
+        if self.delegators.contains(&call.contract) {
+            self.record_implementation(data, call.contract, reverted);
+            if self.proxies.contains_key(&call.contract) {
+                return
+            }
+        }
+
+        if reverted ||
+            !call.input.starts_with(&BEACON_IMPLEMENTATION_SELECTOR) ||
+            retdata.len() != 32
+        {
+            return
+        }
+        let proxy = call.context.caller;
+        if read_slot(data, proxy, EIP1967_BEACON_SLOT, false) == Some(call.contract.into_word()) {
+            self.proxies.insert(proxy, Address::from_word(B256::from_slice(retdata)));
+        }
+    }
+
+    /// Records the implementation of `proxy` if its EIP-1967 implementation slot is set.
+    fn record_implementation<DB: DatabaseExt>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        proxy: Address,
+        from_db: bool,
+    ) {
+        let implementation = read_slot(data, proxy, EIP1967_IMPLEMENTATION_SLOT, from_db);
+        if let Some(implementation) = implementation.filter(|slot| !slot.is_zero()) {
+            self.proxies.insert(proxy, Address::from_word(implementation));
+        }
+    }
+
+    /// Records the implementations of the proxies which delegated a call in the ZK-VM during the
+    /// call, the ones called by other EraVM contracts included.
+    fn record_zk_proxies<DB: DatabaseExt>(&mut self, data: &mut EVMData<'_, DB>) {
+        let Some(cheatcodes) = &mut self.cheatcodes else { return };
+        let delegators = std::mem::take(&mut cheatcodes.zk_delegators);
+        if self.tracer.is_none() {
+            return
+        }
+        for delegator in delegators {
+            if self.delegators.insert(delegator) {
+                self.record_implementation(data, delegator, true);
+            }
+        }
+    }
+
     /// Collects all the data gathered during inspection into a single struct.
     #[inline]
     pub fn collect(self) -> InspectorData {
@@ -393,6 +634,12 @@ impl InspectorStack {
                     cheatcodes.labels.clone().into_iter().map(|l| (l.0, l.1)).collect()
                 })
                 .unwrap_or_default(),
+            proxies: self.proxies,
+            zk_calls: self
+                .cheatcodes
+                .as_ref()
+                .map(|cheatcodes| cheatcodes.zk_calls.clone())
+                .unwrap_or_default(),
             traces: self.tracer.map(|tracer| tracer.get_traces().clone()),
             debug: self.debugger.map(|debugger| debugger.arena),
             coverage: self.coverage.map(|coverage| coverage.maps),
@@ -409,11 +656,38 @@ impl InspectorStack {
         status: InstructionResult,
         retdata: Bytes,
     ) -> (InstructionResult, Gas, Bytes) {
+        // The tracer records the return data of a successful call cut to the limit. It doesn't
+        // alter the outcome of the call, so it's called separately from the inspectors
+        // which may.
+        let traced_retdata = self.traced_return_data(status, &retdata);
+        let traced = self.traced_calls.pop().unwrap_or(true);
+        self.record_zk_proxies(data);
+        if traced && self.tracer.is_some() {
+            self.record_proxy(data, call, status, &retdata);
+        }
+        if traced {
+            call_inspectors_adjust_depth!(
+                [&mut self.tracer],
+                |inspector| {
+                    inspector.call_end(data, call, remaining_gas, status, traced_retdata.clone());
+                    None::<(InstructionResult, Gas, Bytes)>
+                },
+                self,
+                data
+            );
+        }
+        if self.trace_verbosity.is_some() &&
+            call.contract == CHEATCODE_ADDRESS &&
+            matches!(status, return_ok!())
+        {
+            if let Ok(cheatcode) = Vm::setTraceVerbosityCall::abi_decode(&call.input, false) {
+                self.trace_verbosity = Some(cheatcode.verbosity);
+            }
+        }
         call_inspectors_adjust_depth!(
             [
                 &mut self.fuzzer,
                 &mut self.debugger,
-                &mut self.tracer,
                 &mut self.coverage,
                 &mut self.log_collector,
                 &mut self.cheatcodes,
@@ -561,7 +835,7 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
             [
                 &mut self.debugger,
                 &mut self.coverage,
-                &mut self.tracer,
+                self.recording_tracer(),
                 &mut self.log_collector,
                 &mut self.cheatcodes,
                 &mut self.printer
@@ -587,7 +861,7 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
             [
                 &mut self.fuzzer,
                 &mut self.debugger,
-                &mut self.tracer,
+                self.recording_tracer(),
                 &mut self.coverage,
                 &mut self.log_collector,
                 &mut self.cheatcodes,
@@ -616,7 +890,12 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
         data: &Bytes,
     ) {
         call_inspectors_adjust_depth!(
-            [&mut self.tracer, &mut self.log_collector, &mut self.cheatcodes, &mut self.printer],
+            [
+                self.recording_tracer(),
+                &mut self.log_collector,
+                &mut self.cheatcodes,
+                &mut self.printer
+            ],
             |inspector| {
                 inspector.log(evm_data, address, topics, data);
                 None
@@ -631,7 +910,7 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
         call_inspectors_adjust_depth!(
             [
                 &mut self.debugger,
-                &mut self.tracer,
+                self.recording_tracer(),
                 &mut self.log_collector,
                 &mut self.cheatcodes,
                 &mut self.printer,
@@ -662,16 +941,43 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
             return (InstructionResult::Continue, Gas::new(call.gas_limit), Bytes::new());
         }
 
+        let traced = self.records_call(call.contract, &call.input);
+        self.traced_calls.push(traced);
+        if self.tracer.is_some() &&
+            matches!(call.context.scheme, CallScheme::DelegateCall | CallScheme::CallCode)
+        {
+            self.delegators.insert(call.context.address);
+        }
+
         call_inspectors_adjust_depth!(
-            [
-                &mut self.fuzzer,
-                &mut self.debugger,
-                &mut self.tracer,
-                &mut self.coverage,
-                &mut self.log_collector,
-                &mut self.cheatcodes,
-                &mut self.printer
-            ],
+            [&mut self.fuzzer, &mut self.debugger],
+            |inspector| {
+                let (status, gas, retdata) = inspector.call(data, call);
+
+                // Allow inspectors to exit early
+                if status != InstructionResult::Continue {
+                    Some((status, gas, retdata))
+                } else {
+                    None
+                }
+            },
+            self,
+            data
+        );
+
+        // The tracer records the call as a subcall of its closest recorded parent.
+        if traced {
+            let depth = data.journaled_state.depth;
+            let traced_depth = self.traced_depth(depth);
+            if let Some(tracer) = &mut self.tracer {
+                data.journaled_state.depth = traced_depth;
+                tracer.call(data, call);
+                data.journaled_state.depth = depth;
+            }
+        }
+
+        call_inspectors_adjust_depth!(
+            [&mut self.coverage, &mut self.log_collector, &mut self.cheatcodes, &mut self.printer],
             |inspector| {
                 let (status, gas, retdata) = inspector.call(data, call);
 
@@ -742,15 +1048,38 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
             return (InstructionResult::Continue, None, Gas::new(call.gas_limit), Bytes::new());
         }
 
+        let traced = self.records_call(Address::ZERO, &[]);
+        self.traced_calls.push(traced);
+
         call_inspectors_adjust_depth!(
-            [
-                &mut self.debugger,
-                &mut self.tracer,
-                &mut self.coverage,
-                &mut self.log_collector,
-                &mut self.cheatcodes,
-                &mut self.printer
-            ],
+            [&mut self.debugger],
+            |inspector| {
+                let (status, addr, gas, retdata) = inspector.create(data, call);
+
+                // Allow inspectors to exit early
+                if status != InstructionResult::Continue {
+                    Some((status, addr, gas, retdata))
+                } else {
+                    None
+                }
+            },
+            self,
+            data
+        );
+
+        // The tracer records the creation as a subcall of its closest recorded parent.
+        if traced {
+            let depth = data.journaled_state.depth;
+            let traced_depth = self.traced_depth(depth);
+            if let Some(tracer) = &mut self.tracer {
+                data.journaled_state.depth = traced_depth;
+                tracer.create(data, call);
+                data.journaled_state.depth = depth;
+            }
+        }
+
+        call_inspectors_adjust_depth!(
+            [&mut self.coverage, &mut self.log_collector, &mut self.cheatcodes, &mut self.printer],
             |inspector| {
                 let (status, addr, gas, retdata) = inspector.create(data, call);
 
@@ -793,10 +1122,11 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
         if self.in_inner_context && data.journaled_state.depth == 0 {
             return (status, address, remaining_gas, retdata);
         }
+        let traced = self.traced_calls.pop().unwrap_or(true);
         call_inspectors_adjust_depth!(
             [
                 &mut self.debugger,
-                &mut self.tracer,
+                self.tracer.as_mut().filter(|_| traced),
                 &mut self.coverage,
                 &mut self.log_collector,
                 &mut self.cheatcodes,
@@ -829,7 +1159,7 @@ impl<DB: DatabaseExt + DatabaseCommit + Send> Inspector<DB> for InspectorStack {
         call_inspectors!(
             [
                 &mut self.debugger,
-                &mut self.tracer,
+                self.recording_tracer(),
                 &mut self.log_collector,
                 &mut self.cheatcodes,
                 &mut self.printer,
@@ -0,0 +1,174 @@
+//! Checkpoints of long-running invariant campaigns, so that they can be resumed.
+
+use crate::strategies::EvmFuzzState;
+use alloy_primitives::{Address, B256};
+use eyre::{Result, WrapErr};
+use foundry_common::fs;
+use foundry_config::InvariantConfig;
+use foundry_evm_coverage::HitMaps;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where and how often an invariant campaign is checkpointed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckpointConfig {
+    /// The file the checkpoints of the campaign are written to.
+    pub path: PathBuf,
+    /// The number of runs between two checkpoints.
+    pub interval: u32,
+    /// Whether the campaign resumes from the checkpoint at `path`, if there is one.
+    pub resume: bool,
+}
+
+/// The state of an invariant campaign after some of its runs, from which the remaining runs can
+/// be resumed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InvariantCheckpoint {
+    /// The hash of the code of the test contract and of the contracts deployed by its `setUp()`.
+    pub code_hash: B256,
+    /// The invariant configuration of the campaign.
+    pub config: InvariantConfig,
+    /// The number of completed runs.
+    pub runs: u32,
+    /// The number of calls of the completed runs.
+    pub calls: usize,
+    /// The number of reverted calls of the completed runs.
+    pub reverts: usize,
+    /// The seed of the RNG generating the remaining runs.
+    pub seed: B256,
+    /// The values of the fuzz dictionary.
+    pub dictionary: Vec<B256>,
+    /// The addresses whose PUSH bytes are in the fuzz dictionary.
+    pub addresses: Vec<Address>,
+    /// The hashes of the EraVM bytecodes the calls of the completed runs deployed or called, which
+    /// are in the fuzz dictionary of a zk campaign.
+    pub zk_bytecode_hashes: Vec<B256>,
+    /// The coverage of the completed runs, if it's collected.
+    pub coverage: Option<HitMaps>,
+}
+
+impl InvariantCheckpoint {
+    /// Reads the checkpoint at `path`, `None` if there is none.
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None)
+        }
+        let checkpoint = fs::read_json_file(path)
+            .wrap_err_with(|| format!("invalid invariant checkpoint {}", path.display()))?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes the checkpoint to `path`.
+    ///
+    /// The checkpoint is written next to it first, so that a crash while writing keeps the
+    /// previous one.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write_json_file(&tmp, self)?;
+        std::fs::rename(&tmp, path)
+            .wrap_err_with(|| format!("failed to write invariant checkpoint {}", path.display()))
+    }
+
+    /// Returns why the campaign of the test contracts with `code_hash` and of `config` can't be
+    /// resumed from the checkpoint, `None` if it can.
+    ///
+    /// The number of runs and the checkpoint interval of the campaign may differ from the ones of
+    /// the checkpoint, as long as it has no more runs than the campaign.
+    pub fn mismatch(&self, code_hash: B256, config: &InvariantConfig) -> Option<String> {
+        if self.code_hash != code_hash {
+            return Some("the test contracts changed".to_string())
+        }
+        if self.runs > config.runs {
+            return Some(format!(
+                "it has {} runs, more than the {} runs of the campaign",
+                self.runs, config.runs
+            ))
+        }
+        let normalized = InvariantConfig {
+            runs: config.runs,
+            checkpoint_interval: config.checkpoint_interval,
+            ..self.config.clone()
+        };
+        (normalized != *config).then(|| "the invariant configuration changed".to_string())
+    }
+
+    /// Removes the checkpoint at `path`, once the campaign is over.
+    pub fn remove(path: &Path) -> Result<()> {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Records the fuzz dictionary of the campaign.
+    pub fn capture_dictionary(&mut self, state: &EvmFuzzState) {
+        let state = state.read();
+        self.dictionary = state.values().iter().copied().map(B256::from).collect();
+        self.dictionary.sort_unstable();
+        self.addresses = state.addresses().iter().copied().collect();
+        self.addresses.sort_unstable();
+    }
+
+    /// Adds the recorded fuzz dictionary to the one of the resumed campaign.
+    pub fn restore_dictionary(&self, state: &EvmFuzzState) {
+        let mut state = state.write();
+        state.values_mut().extend(self.dictionary.iter().map(|value| value.0));
+        state.values_mut().extend(self.zk_bytecode_hashes.iter().map(|hash| hash.0));
+        state.addresses_mut().extend(self.addresses.iter().copied());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_dictionary() {
+        let state = EvmFuzzState::default();
+        state.write().values_mut().extend([[1; 32], [2; 32]]);
+        state.write().addresses_mut().insert(Address::with_last_byte(1));
+
+        let mut checkpoint = InvariantCheckpoint {
+            runs: 10,
+            zk_bytecode_hashes: vec![B256::repeat_byte(4)],
+            ..Default::default()
+        };
+        checkpoint.capture_dictionary(&state);
+        assert_eq!(checkpoint.dictionary, vec![B256::repeat_byte(1), B256::repeat_byte(2)]);
+        assert_eq!(checkpoint.addresses, vec![Address::with_last_byte(1)]);
+
+        let resumed = EvmFuzzState::default();
+        resumed.write().values_mut().insert([3; 32]);
+        checkpoint.restore_dictionary(&resumed);
+        let resumed = resumed.read();
+        assert_eq!(resumed.values().len(), 4);
+        assert!(resumed.values().contains(&[1; 32]));
+        assert!(resumed.values().contains(&[2; 32]));
+        assert!(resumed.values().contains(&[4; 32]));
+    }
+
+    #[test]
+    fn rejects_mismatched_campaign() {
+        let config = InvariantConfig { runs: 20, checkpoint_interval: 5, ..Default::default() };
+        let checkpoint = InvariantCheckpoint {
+            code_hash: B256::repeat_byte(1),
+            config: config.clone(),
+            runs: 15,
+            ..Default::default()
+        };
+        assert_eq!(checkpoint.mismatch(B256::repeat_byte(1), &config), None);
+
+        // more runs and another interval can be resumed
+        let longer = InvariantConfig { runs: 50, checkpoint_interval: 10, ..config.clone() };
+        assert_eq!(checkpoint.mismatch(B256::repeat_byte(1), &longer), None);
+
+        assert!(checkpoint.mismatch(B256::repeat_byte(2), &config).is_some());
+        let shorter = InvariantConfig { runs: 10, ..config.clone() };
+        assert!(checkpoint.mismatch(B256::repeat_byte(1), &shorter).is_some());
+        let deeper = InvariantConfig { depth: config.depth + 1, ..config };
+        assert!(checkpoint.mismatch(B256::repeat_byte(1), &deeper).is_some());
+    }
+}
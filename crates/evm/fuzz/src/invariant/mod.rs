@@ -1,17 +1,23 @@
 use alloy_json_abi::{Function, JsonAbi};
-use alloy_primitives::{Address, Bytes};
+use alloy_primitives::{Address, Bytes, Selector};
 use parking_lot::Mutex;
 use std::{collections::BTreeMap, sync::Arc};
 
 mod call_override;
 pub use call_override::RandomCallGenerator;
 
+mod checkpoint;
+pub use checkpoint::{CheckpointConfig, InvariantCheckpoint};
+
 mod filters;
 pub use filters::{ArtifactFilters, SenderFilters};
 
 pub type TargetedContracts = BTreeMap<Address, (String, JsonAbi, Vec<Function>)>;
 pub type FuzzRunIdentifiedContracts = Arc<Mutex<TargetedContracts>>;
 
+/// Relative selection weights of target functions. Functions without a weight have a weight of 1.
+pub type SelectorWeights = BTreeMap<Selector, u32>;
+
 /// (Sender, (TargetContract, Calldata))
 pub type BasicTxDetails = (Address, (Address, Bytes));
 
@@ -14,7 +14,10 @@ use foundry_evm_coverage::HitMaps;
 use foundry_evm_traces::CallTraceArena;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 pub use proptest::test_runner::{Config as FuzzConfig, Reason};
 
@@ -122,6 +125,8 @@ pub struct FuzzTestResult {
     pub first_case: FuzzCase,
     /// Gas usage (gas_used, call_stipend) per cases
     pub gas_by_case: Vec<(u64, u64)>,
+    /// The ergs used by the ZK-VM executions per cases
+    pub zk_ergs_by_case: Vec<u64>,
     /// Whether the test case was successful. This means that the transaction executed
     /// properly, or that there was a revert and that the test was expected to fail
     /// (prefixed with `testFail`)
@@ -144,6 +149,12 @@ pub struct FuzzTestResult {
     /// Labeled addresses
     pub labeled_addresses: HashMap<Address, String>,
 
+    /// The EIP-1967 proxies called by the fuzz call, mapped to their implementation
+    pub proxies: HashMap<Address, Address>,
+
+    /// The calls made from the EVM to the ZK-VM by the fuzz call, as `(caller, callee)` pairs
+    pub zk_calls: HashSet<(Address, Address)>,
+
     /// Exemplary traces for a fuzz run of the test function
     ///
     /// **Note** We only store a single trace of a successful fuzz call, otherwise we would get
@@ -152,6 +163,9 @@ pub struct FuzzTestResult {
 
     /// Raw coverage info
     pub coverage: Option<HitMaps>,
+
+    /// The inputs rejected by `vm.assume`, by call site with the most rejections first
+    pub assume_rejections: Vec<AssumeRejection>,
 }
 
 impl FuzzTestResult {
@@ -169,6 +183,11 @@ impl FuzzTestResult {
         calc::mean(&values)
     }
 
+    /// Returns the median ergs used by the ZK-VM executions of all test cases, if any
+    pub fn median_zk_ergs(&self) -> Option<u64> {
+        median_zk_ergs(self.zk_ergs_by_case.iter().copied())
+    }
+
     fn gas_values(&self, with_stipend: bool) -> Vec<u64> {
         self.gas_by_case
             .iter()
@@ -177,6 +196,44 @@ impl FuzzTestResult {
     }
 }
 
+/// Returns the median of the ergs used by the ZK-VM executions of fuzz cases, if any of them
+/// executed in the ZK-VM
+pub fn median_zk_ergs(ergs: impl IntoIterator<Item = u64>) -> Option<u64> {
+    let mut values = ergs.into_iter().collect::<Vec<_>>();
+    if values.iter().all(|ergs| *ergs == 0) {
+        return None
+    }
+    values.sort_unstable();
+    Some(calc::median_sorted(&values))
+}
+
+/// The inputs of a fuzz test rejected by a single `vm.assume` call site
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssumeRejection {
+    /// The contract that called `vm.assume`
+    pub address: Address,
+    /// The program counter of the call to `vm.assume`, unknown in the ZK-VM
+    pub pc: Option<usize>,
+    /// The number of rejected inputs
+    pub count: u32,
+}
+
+impl AssumeRejection {
+    /// Collects rejection counts by `(address, pc)` call site, with the most rejections first.
+    pub fn from_counts(
+        counts: impl IntoIterator<Item = ((Address, Option<usize>), u32)>,
+    ) -> Vec<Self> {
+        let mut rejections = counts
+            .into_iter()
+            .map(|((address, pc), count)| Self { address, pc, count })
+            .collect::<Vec<_>>();
+        rejections.sort_by(|a, b| {
+            b.count.cmp(&a.count).then_with(|| (a.address, a.pc).cmp(&(b.address, b.pc)))
+        });
+        rejections
+    }
+}
+
 /// Data of a single fuzz test case
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct FuzzCase {
@@ -186,6 +243,9 @@ pub struct FuzzCase {
     pub gas: u64,
     /// The initial gas stipend for the transaction
     pub stipend: u64,
+    /// The ergs used by the ZK-VM executions of the case, separate from its EVM gas
+    #[serde(default)]
+    pub zk_ergs: u64,
 }
 
 /// Container type for all successful test cases
@@ -1,6 +1,6 @@
 use super::fuzz_param_from_state;
 use crate::{
-    invariant::{BasicTxDetails, FuzzRunIdentifiedContracts, SenderFilters},
+    invariant::{BasicTxDetails, FuzzRunIdentifiedContracts, SelectorWeights, SenderFilters},
     strategies::{fuzz_calldata, fuzz_calldata_from_state, fuzz_param, EvmFuzzState},
 };
 use alloy_json_abi::{Function, JsonAbi};
@@ -14,6 +14,7 @@ pub fn override_call_strat(
     fuzz_state: EvmFuzzState,
     contracts: FuzzRunIdentifiedContracts,
     target: Arc<RwLock<Address>>,
+    selector_weights: Arc<SelectorWeights>,
 ) -> SBoxedStrategy<(Address, Bytes)> {
     let contracts_ref = contracts.clone();
 
@@ -28,7 +29,7 @@ pub fn override_call_strat(
     .prop_flat_map(move |target_address| {
         let fuzz_state = fuzz_state.clone();
         let (_, abi, functions) = contracts.lock().get(&target_address).unwrap().clone();
-        let func = select_random_function(abi, functions);
+        let func = select_random_function(abi, functions, &selector_weights);
         func.prop_flat_map(move |func| {
             fuzz_contract_with_calldata(fuzz_state.clone(), target_address, func)
         })
@@ -46,15 +47,19 @@ pub fn override_call_strat(
 /// contract:
 ///
 /// `targetContracts()`, `targetSenders()`, `excludeContracts()`, `targetSelectors()`
+///
+/// Target functions are sampled according to their `selector_weights`.
 pub fn invariant_strat(
     fuzz_state: EvmFuzzState,
     senders: SenderFilters,
     contracts: FuzzRunIdentifiedContracts,
     dictionary_weight: u32,
+    selector_weights: Arc<SelectorWeights>,
 ) -> impl Strategy<Value = Vec<BasicTxDetails>> {
     // We only want to seed the first value, since we want to generate the rest as we mutate the
     // state
-    generate_call(fuzz_state, senders, contracts, dictionary_weight).prop_map(|x| vec![x])
+    generate_call(fuzz_state, senders, contracts, dictionary_weight, selector_weights)
+        .prop_map(|x| vec![x])
 }
 
 /// Strategy to generate a transaction where the `sender`, `target` and `calldata` are all generated
@@ -64,12 +69,13 @@ fn generate_call(
     senders: SenderFilters,
     contracts: FuzzRunIdentifiedContracts,
     dictionary_weight: u32,
+    selector_weights: Arc<SelectorWeights>,
 ) -> BoxedStrategy<BasicTxDetails> {
     let random_contract = select_random_contract(contracts);
     let senders = Rc::new(senders);
     random_contract
         .prop_flat_map(move |(contract, abi, functions)| {
-            let func = select_random_function(abi, functions);
+            let func = select_random_function(abi, functions, &selector_weights);
             let senders = senders.clone();
             let fuzz_state = fuzz_state.clone();
             func.prop_flat_map(move |func| {
@@ -133,32 +139,46 @@ fn select_random_contract(
 ///
 /// If `targeted_functions` is not empty, select one from it. Otherwise, take any
 /// of the available abi functions.
+///
+/// If any of the candidates has a weight in `selector_weights`, functions are sampled
+/// proportionally to their weight, with a default weight of 1.
 fn select_random_function(
     abi: JsonAbi,
     targeted_functions: Vec<Function>,
+    selector_weights: &SelectorWeights,
 ) -> BoxedStrategy<Function> {
-    let selectors = any::<prop::sample::Selector>();
-    let possible_funcs: Vec<Function> = abi
-        .functions()
-        .filter(|func| {
-            !matches!(
-                func.state_mutability,
-                alloy_json_abi::StateMutability::Pure | alloy_json_abi::StateMutability::View
-            )
-        })
-        .cloned()
-        .collect();
-    let total_random = selectors.prop_map(move |selector| {
-        let func = selector.select(&possible_funcs);
-        func.clone()
-    });
-    if !targeted_functions.is_empty() {
-        let selector = any::<prop::sample::Selector>()
-            .prop_map(move |selector| selector.select(targeted_functions.clone()));
-        selector.boxed()
+    let possible_funcs: Vec<Function> = if !targeted_functions.is_empty() {
+        targeted_functions
     } else {
-        total_random.boxed()
+        abi.functions()
+            .filter(|func| {
+                !matches!(
+                    func.state_mutability,
+                    alloy_json_abi::StateMutability::Pure | alloy_json_abi::StateMutability::View
+                )
+            })
+            .cloned()
+            .collect()
+    };
+
+    if possible_funcs.iter().any(|func| selector_weights.contains_key(&func.selector())) {
+        let weighted_funcs: Vec<_> = possible_funcs
+            .iter()
+            .map(|func| {
+                let weight = selector_weights.get(&func.selector()).copied().unwrap_or(1);
+                (weight, Just(func.clone()).boxed())
+            })
+            .filter(|(weight, _)| *weight > 0)
+            .collect();
+        // If all functions have a weight of 0, fall back to uniform sampling.
+        if !weighted_funcs.is_empty() {
+            return proptest::strategy::Union::new_weighted(weighted_funcs).boxed()
+        }
     }
+
+    any::<prop::sample::Selector>()
+        .prop_map(move |selector| selector.select(&possible_funcs).clone())
+        .boxed()
 }
 
 /// Given a function, it returns a proptest strategy which generates valid abi-encoded calldata
@@ -6,6 +6,7 @@ use alloy_primitives::{Address, Bytes, Log, B256, U256};
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_config::FuzzDictionaryConfig;
 use foundry_evm_core::utils::StateChangeset;
+use foundry_zksync_core::vm::ZkFuzzValues;
 use hashbrown::HashSet;
 use parking_lot::RwLock;
 use proptest::prelude::{BoxedStrategy, Strategy};
@@ -50,7 +51,7 @@ impl FuzzDictionary {
     }
 
     #[inline]
-    pub fn addresses(&mut self) -> &HashSet<Address> {
+    pub fn addresses(&self) -> &HashSet<Address> {
         &self.addresses
     }
 
@@ -202,6 +203,51 @@ pub fn collect_state_from_call(
     }
 }
 
+/// Collects the values observed by the ZK-VM executions of a call into an [EvmFuzzState]
+/// according to the given [FuzzDictionaryConfig]: the storage written and the bytecode hashes
+/// deployed with `include_storage`, and the data returned by the system contracts with
+/// `include_zk_return_data`.
+pub fn collect_zk_state_from_call(
+    values: &ZkFuzzValues,
+    state: EvmFuzzState,
+    config: &FuzzDictionaryConfig,
+) {
+    let mut state = state.write();
+
+    if config.include_storage {
+        for (slot, value) in &values.storage {
+            if state.state_values.len() >= config.max_fuzz_dictionary_values {
+                return
+            }
+            state.values_mut().insert(B256::from(*slot).0);
+            state.values_mut().insert(B256::from(*value).0);
+            // also add the value below and above the storage value to the dictionary.
+            if *value != U256::ZERO {
+                state.values_mut().insert(B256::from(value - U256::from(1)).0);
+            }
+            if *value != U256::MAX {
+                state.values_mut().insert(B256::from(value + U256::from(1)).0);
+            }
+        }
+        for hash in &values.bytecode_hashes {
+            state.values_mut().insert(hash.0);
+        }
+    }
+
+    if config.include_zk_return_data {
+        for data in &values.system_return_data {
+            if state.state_values.len() >= config.max_fuzz_dictionary_values {
+                return
+            }
+            data.chunks(32).for_each(|chunk| {
+                let mut buffer: [u8; 32] = [0; 32];
+                buffer[..chunk.len()].copy_from_slice(chunk);
+                state.values_mut().insert(buffer);
+            });
+        }
+    }
+}
+
 /// The maximum number of bytes we will look at in bytecodes to find push bytes (24 KiB).
 ///
 /// This is to limit the performance impact of fuzz tests that might deploy arbitrarily sized
@@ -276,3 +322,33 @@ pub fn collect_created_contracts(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_zk_values() {
+        let values = ZkFuzzValues {
+            storage: vec![(U256::from(1), U256::from(42))],
+            bytecode_hashes: vec![B256::with_last_byte(7)],
+            system_return_data: vec![Bytes::from(U256::from(5).to_be_bytes_vec())],
+        };
+        let state = EvmFuzzState::default();
+        collect_zk_state_from_call(&values, state.clone(), &FuzzDictionaryConfig::default());
+        let collected = state.read().values().clone();
+        for value in [1u64, 41, 42, 43, 5] {
+            assert!(collected.contains(&U256::from(value).to_be_bytes::<32>()), "{value}");
+        }
+        assert!(collected.contains(&B256::with_last_byte(7).0));
+
+        let state = EvmFuzzState::default();
+        let config = FuzzDictionaryConfig {
+            include_storage: false,
+            include_zk_return_data: false,
+            ..Default::default()
+        };
+        collect_zk_state_from_call(&values, state.clone(), &config);
+        assert!(state.read().values().is_empty());
+    }
+}
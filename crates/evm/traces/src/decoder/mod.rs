@@ -2,7 +2,8 @@ use crate::{
     identifier::{
         AddressIdentity, LocalTraceIdentifier, SingleSignaturesIdentifier, TraceIdentifier,
     },
-    CallTrace, CallTraceArena, CallTraceNode, DecodedCallData, DecodedCallLog, DecodedCallTrace,
+    CallKind, CallTrace, CallTraceArena, CallTraceNode, DecodedCallData, DecodedCallLog,
+    DecodedCallTrace,
 };
 use alloy_dyn_abi::{DecodedEvent, DynSolValue, EventExt, FunctionExt, JsonAbiExt};
 use alloy_json_abi::{Error, Event, Function, JsonAbi};
@@ -12,7 +13,7 @@ use foundry_common::{
     abi::get_indexed_event,
     console::{Console, HardhatConsole, HARDHAT_CONSOLE_SELECTOR_PATCHES},
     fmt::format_token,
-    SELECTOR_LEN,
+    get_contract_name, SELECTOR_LEN,
 };
 use foundry_evm_core::{
     constants::{
@@ -21,11 +22,14 @@ use foundry_evm_core::{
     },
     decode::RevertDecoder,
 };
+use foundry_zksync_core::{convert::ConvertH160, CONTRACT_DEPLOYER_ADDRESS};
 use itertools::Itertools;
 use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
 use std::collections::{hash_map::Entry, BTreeMap, HashMap};
 
 mod precompiles;
+mod system_contracts;
 
 /// Build a new [CallTraceDecoder].
 #[derive(Default)]
@@ -48,6 +52,13 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Add the EIP-1967 proxies called by the traces, mapped to their implementation.
+    #[inline]
+    pub fn with_proxies(mut self, proxies: impl IntoIterator<Item = (Address, Address)>) -> Self {
+        self.decoder.proxies.extend(proxies);
+        self
+    }
+
     /// Add known errors to the decoder.
     #[inline]
     pub fn with_abi(mut self, abi: &JsonAbi) -> Self {
@@ -104,6 +115,13 @@ pub struct CallTraceDecoder {
     pub labels: HashMap<Address, String>,
     /// Contract addresses that have a receive function.
     pub receive_contracts: Vec<Address>,
+    /// EIP-1967 proxy addresses mapped to the implementation they delegate calls to.
+    ///
+    /// Recorded by the inspector stack from the implementation and beacon slots of the proxies.
+    pub proxies: HashMap<Address, Address>,
+    /// Contracts deployed with `CREATE2`, mapped to the salt and deployer their address is derived
+    /// from.
+    pub create2_deployments: HashMap<Address, Create2Deployment>,
 
     /// All known functions.
     pub functions: HashMap<Selector, Vec<Function>>,
@@ -121,8 +139,8 @@ pub struct CallTraceDecoder {
 impl CallTraceDecoder {
     /// Creates a new call trace decoder.
     ///
-    /// The call trace decoder always knows how to decode calls to the cheatcode address, as well
-    /// as DSTest-style logs.
+    /// The call trace decoder always knows how to decode calls to the cheatcode address and to the
+    /// zkSync Era system contracts, as well as DSTest-style logs.
     pub fn new() -> &'static Self {
         // If you want to take arguments in this function, assign them to the fields of the cloned
         // lazy instead of removing it
@@ -148,7 +166,7 @@ impl CallTraceDecoder {
             functions.into_iter()
         }
 
-        Self {
+        let mut decoder = Self {
             contracts: Default::default(),
             labels: [
                 (CHEATCODE_ADDRESS, "VM".to_string()),
@@ -157,8 +175,11 @@ impl CallTraceDecoder {
                 (CALLER, "DefaultSender".to_string()),
                 (TEST_CONTRACT_ADDRESS, "DefaultTestContract".to_string()),
             ]
-            .into(),
+            .into_iter()
+            .chain(system_contracts::labels())
+            .collect(),
             receive_contracts: Default::default(),
+            proxies: Default::default(),
 
             functions: hh_funcs()
                 .chain(
@@ -178,7 +199,16 @@ impl CallTraceDecoder {
 
             signature_identifier: None,
             verbosity: 0,
+        };
+
+        // the zkSync Era system contracts are called by the ZK-VM, e.g. on every deployment
+        for function in system_contracts::functions() {
+            decoder.push_function(function);
+        }
+        for event in system_contracts::events() {
+            decoder.push_event(event);
         }
+        decoder
     }
 
     /// Clears all known addresses.
@@ -191,6 +221,8 @@ impl CallTraceDecoder {
         }
 
         self.receive_contracts.clear();
+        self.proxies.clear();
+        self.create2_deployments.clear();
     }
 
     /// Identify unknown addresses in the specified call trace using the specified identifier.
@@ -198,6 +230,35 @@ impl CallTraceDecoder {
     /// Unknown contracts are contracts that either lack a label or an ABI.
     pub fn identify(&mut self, trace: &CallTraceArena, identifier: &mut impl TraceIdentifier) {
         self.collect_identities(identifier.identify_addresses(self.addresses(trace)));
+        self.identify_create2_deployments(trace);
+    }
+
+    /// Identifies the contracts deployed with `CREATE2` in the specified call trace.
+    ///
+    /// The traces don't record the salts, which are recovered from the calls deploying the
+    /// contracts: calls to a factory with the salt followed by the init code, like the default
+    /// `CREATE2` deployer, and calls to the zkSync Era `ContractDeployer`.
+    pub fn identify_create2_deployments(&mut self, trace: &CallTraceArena) {
+        let nodes = trace.nodes();
+        for node in nodes {
+            let children = node.children.iter().map(|&idx| &nodes[idx].trace);
+            let deployments = create2_deployments(&node.trace, children);
+            for (address, deployment) in deployments {
+                trace!(target: "evm::traces", %address, salt=%deployment.salt, deployer=%deployment.deployer, "identified CREATE2 deployment");
+                self.create2_deployments.insert(address, deployment);
+            }
+        }
+    }
+
+    /// Returns the salt and deployer of the contract deployed by the trace, if it was deployed
+    /// with `CREATE2`.
+    ///
+    /// On zkSync Era, the deployment is the call of the `ContractDeployer` to the constructor.
+    pub fn create2_deployment(&self, trace: &CallTrace) -> Option<&Create2Deployment> {
+        if !trace.kind.is_any_create() && trace.caller != CONTRACT_DEPLOYER_ADDRESS.to_address() {
+            return None;
+        }
+        self.create2_deployments.get(&trace.address)
     }
 
     /// Adds a single event to the decoder.
@@ -295,6 +356,7 @@ impl CallTraceDecoder {
                 return_data: None,
                 contract: None,
                 func: Some(func),
+                implementation: None,
             };
         }
 
@@ -304,6 +366,13 @@ impl CallTraceDecoder {
         // Set contract name
         let contract = self.contracts.get(&trace.address).cloned();
 
+        // Set the implementation the call is forwarded to, the delegate call itself is rendered
+        // as a regular call
+        let implementation = (trace.kind != CallKind::DelegateCall)
+            .then(|| self.proxies.get(&trace.address))
+            .flatten()
+            .map(|implementation| self.implementation_label(implementation));
+
         let cdata = &trace.data;
         if trace.address == DEFAULT_CREATE2_DEPLOYER {
             return DecodedCallTrace {
@@ -311,6 +380,7 @@ impl CallTraceDecoder {
                 return_data: None,
                 contract,
                 func: Some(DecodedCallData { signature: "create2".to_string(), args: vec![] }),
+                implementation,
             };
         }
 
@@ -331,7 +401,13 @@ impl CallTraceDecoder {
                 }
             };
             let [func, ..] = &functions[..] else {
-                return DecodedCallTrace { label, return_data: None, contract, func: None };
+                return DecodedCallTrace {
+                    label,
+                    return_data: None,
+                    contract,
+                    func: None,
+                    implementation,
+                };
             };
 
             DecodedCallTrace {
@@ -339,6 +415,7 @@ impl CallTraceDecoder {
                 func: Some(self.decode_function_input(trace, func)),
                 return_data: self.decode_function_output(trace, functions),
                 contract,
+                implementation,
             }
         } else {
             let has_receive = self.receive_contracts.contains(&trace.address);
@@ -354,10 +431,22 @@ impl CallTraceDecoder {
                 },
                 contract,
                 func: Some(DecodedCallData { signature, args }),
+                implementation,
             }
         }
     }
 
+    /// Returns the label of a proxy implementation, falling back to its contract name or address.
+    fn implementation_label(&self, implementation: &Address) -> String {
+        self.labels
+            .get(implementation)
+            .cloned()
+            .or_else(|| {
+                self.contracts.get(implementation).map(|id| get_contract_name(id).to_string())
+            })
+            .unwrap_or_else(|| implementation.to_checksum(None))
+    }
+
     /// Decodes a function's input into the given trace.
     fn decode_function_input(&self, trace: &CallTrace, func: &Function) -> DecodedCallData {
         let mut args = None;
@@ -574,6 +663,63 @@ impl CallTraceDecoder {
     }
 }
 
+/// The salt and deployer the address of a contract deployed with `CREATE2` is derived from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Create2Deployment {
+    /// The salt of the deployment.
+    pub salt: B256,
+    /// The deployer the address is derived from, the factory or the caller of the zkSync Era
+    /// `ContractDeployer`.
+    pub deployer: Address,
+}
+
+impl Create2Deployment {
+    /// Returns the deployment of a call to the `CREATE2` factory `factory`, whose calldata is the
+    /// salt followed by the init code.
+    pub fn from_factory_call(factory: Address, data: &[u8]) -> Option<Self> {
+        let salt = data.get(..32)?;
+        Some(Self { salt: B256::from_slice(salt), deployer: factory })
+    }
+
+    /// Returns the deployment of a call of `sender` to the zkSync Era `ContractDeployer`, if it
+    /// deploys a contract with `CREATE2`.
+    pub fn from_contract_deployer_call(sender: Address, data: &[u8]) -> Option<Self> {
+        system_contracts::create2_salt(data).map(|salt| Self { salt, deployer: sender })
+    }
+}
+
+/// Returns the contracts deployed with `CREATE2` by the call `trace`, given its `children`.
+fn create2_deployments<'a>(
+    trace: &CallTrace,
+    children: impl Iterator<Item = &'a CallTrace>,
+) -> Vec<(Address, Create2Deployment)> {
+    if trace.kind.is_any_create() {
+        return vec![];
+    }
+
+    // the `ContractDeployer` returns the address of the deployed contract
+    if trace.address == CONTRACT_DEPLOYER_ADDRESS.to_address() {
+        let deployment = Create2Deployment::from_contract_deployer_call(trace.caller, &trace.data);
+        let address = trace.output.get(..32).map(|word| Address::from_word(B256::from_slice(word)));
+        return match (deployment, address) {
+            (Some(deployment), Some(address)) if trace.success => vec![(address, deployment)],
+            _ => vec![],
+        };
+    }
+
+    let Some(deployment) = Create2Deployment::from_factory_call(trace.address, &trace.data) else {
+        return vec![];
+    };
+    children
+        .filter(|child| child.kind == CallKind::Create2 && child.success)
+        // the calldata is only the salt and init code if it derives the address
+        .filter(|child| {
+            trace.address.create2_from_code(deployment.salt, &child.data) == child.address
+        })
+        .map(|child| (child.address, deployment))
+        .collect()
+}
+
 /// Restore the order of the params of a decoded event,
 /// as Alloy returns the indexed and unindexed params separately.
 fn reconstruct_params(event: &Event, decoded: &DecodedEvent) -> Vec<DynSolValue> {
@@ -601,6 +747,7 @@ fn indexed_inputs(event: &Event) -> usize {
 mod tests {
     use super::*;
     use alloy_primitives::hex;
+    use alloy_sol_types::SolCall;
 
     #[test]
     fn test_should_redact_pk() {
@@ -680,4 +827,53 @@ mod tests {
             assert_eq!(result, expected, "Output case failed for: {}", function_signature);
         }
     }
+
+    #[test]
+    fn identifies_create2_deployments() {
+        let salt = B256::with_last_byte(1);
+        let init_code = hex!("600a600c600039600a6000f3602a60005260206000f3");
+        let factory = DEFAULT_CREATE2_DEPLOYER;
+        let factory_call = CallTrace {
+            address: factory,
+            data: [salt.as_slice(), &init_code].concat().into(),
+            success: true,
+            ..Default::default()
+        };
+        let create = CallTrace {
+            kind: CallKind::Create2,
+            caller: factory,
+            address: factory.create2_from_code(salt, init_code),
+            data: init_code.into(),
+            success: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            create2_deployments(&factory_call, [&create].into_iter()),
+            vec![(create.address, Create2Deployment { salt, deployer: factory })]
+        );
+
+        // a salt not deriving the address isn't the salt of the deployment
+        let other_create = CallTrace { address: Address::with_last_byte(1), ..create.clone() };
+        assert!(create2_deployments(&factory_call, [&other_create].into_iter()).is_empty());
+
+        let deployed = Address::with_last_byte(2);
+        let deployer_call = CallTrace {
+            caller: CALLER,
+            address: CONTRACT_DEPLOYER_ADDRESS.to_address(),
+            data: system_contracts::ContractDeployer::create2Call {
+                salt,
+                bytecodeHash: B256::with_last_byte(2),
+                input: Default::default(),
+            }
+            .abi_encode()
+            .into(),
+            output: deployed.into_word().into(),
+            success: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            create2_deployments(&deployer_call, std::iter::empty()),
+            vec![(deployed, Create2Deployment { salt, deployer: CALLER })]
+        );
+    }
 }
@@ -0,0 +1,229 @@
+use alloy_json_abi::{Event, Function};
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::{sol, SolCall};
+
+sol! {
+/// The zkSync Era `ContractDeployer` system contract.
+#[sol(abi)]
+interface ContractDeployer {
+    event ContractDeployed(address indexed deployerAddress, bytes32 indexed bytecodeHash, address indexed contractAddress);
+
+    function create(bytes32 salt, bytes32 bytecodeHash, bytes input) external payable returns (address);
+    function create2(bytes32 salt, bytes32 bytecodeHash, bytes input) external payable returns (address);
+    function createAccount(bytes32 salt, bytes32 bytecodeHash, bytes input, uint8 aaVersion) external payable returns (address);
+    function create2Account(bytes32 salt, bytes32 bytecodeHash, bytes input, uint8 aaVersion) external payable returns (address);
+    function getNewAddressCreate(address sender, uint256 senderNonce) external view returns (address newAddress);
+    function getNewAddressCreate2(address sender, bytes32 bytecodeHash, bytes32 salt, bytes input) external view returns (address newAddress);
+}
+
+/// The zkSync Era `NonceHolder` system contract.
+#[sol(abi)]
+interface NonceHolder {
+    event ValueSetUnderNonce(address indexed accountAddress, uint256 indexed key, uint256 value);
+
+    function getMinNonce(address _address) external view returns (uint256);
+    function getRawNonce(address _address) external view returns (uint256);
+    function increaseMinNonce(uint256 value) external returns (uint256);
+    function incrementMinNonceIfEquals(uint256 expectedNonce) external;
+    function getDeploymentNonce(address _address) external view returns (uint256);
+    function incrementDeploymentNonce(address _address) external returns (uint256);
+    function setValueUnderNonce(uint256 key, uint256 value) external;
+    function getValueUnderNonce(uint256 key) external view returns (uint256);
+    function isNonceUsed(address _address, uint256 nonce) external view returns (bool);
+    function validateNonceUsage(address _address, uint256 key, bool shouldBeUsed) external view;
+}
+
+/// The zkSync Era `AccountCodeStorage` system contract.
+#[sol(abi)]
+interface AccountCodeStorage {
+    function storeAccountConstructingCodeHash(address _address, bytes32 hash) external;
+    function storeAccountConstructedCodeHash(address _address, bytes32 hash) external;
+    function markAccountCodeHashAsConstructed(address _address) external;
+    function getRawCodeHash(address _address) external view returns (bytes32 codeHash);
+    function getCodeHash(uint256 input) external view returns (bytes32 codeHash);
+    function getCodeSize(uint256 input) external view returns (uint256 codeSize);
+}
+
+/// The zkSync Era `KnownCodesStorage` system contract.
+#[sol(abi)]
+interface KnownCodesStorage {
+    event MarkedAsKnown(bytes32 indexed bytecodeHash, bool indexed sendBytecodeToL1);
+
+    function markFactoryDeps(bool shouldSendToL1, bytes32[] hashes) external;
+    function markBytecodeAsPublished(bytes32 bytecodeHash) external;
+    function getMarker(bytes32 hash) external view returns (uint256);
+}
+
+/// The zkSync Era `ImmutableSimulator` system contract.
+#[sol(abi)]
+interface ImmutableSimulator {
+    struct ImmutableData {
+        uint256 index;
+        bytes32 value;
+    }
+
+    function getImmutable(address dest, uint256 index) external view returns (bytes32);
+    function setImmutables(address dest, ImmutableData[] immutables) external;
+}
+
+/// The zkSync Era `L1Messenger` system contract.
+#[sol(abi)]
+interface L1Messenger {
+    event L1MessageSent(address indexed sender, bytes32 indexed hash, bytes message);
+    event BytecodeL1PublicationRequested(bytes32 bytecodeHash);
+
+    function sendToL1(bytes message) external returns (bytes32);
+    function sendL2ToL1Log(bool isService, bytes32 key, bytes32 value) external returns (uint256 logIdInMerkleTree);
+    function requestBytecodeL1Publication(bytes32 bytecodeHash) external;
+}
+
+/// The zkSync Era `L2BaseToken` system contract, holding the balances of the base token.
+#[sol(abi)]
+interface L2BaseToken {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Mint(address indexed account, uint256 amount);
+    event Withdrawal(address indexed l2Sender, address indexed l1Receiver, uint256 amount);
+
+    function balanceOf(uint256 account) external view returns (uint256);
+    function transferFromTo(address from, address to, uint256 amount) external;
+    function totalSupply() external view returns (uint256);
+    function mint(address account, uint256 amount) external;
+    function withdraw(address l1Receiver) external payable;
+    function withdrawWithMessage(address l1Receiver, bytes additionalData) external payable;
+}
+
+/// The zkSync Era `SystemContext` system contract.
+#[sol(abi)]
+interface SystemContext {
+    function chainId() external view returns (uint256);
+    function origin() external view returns (address);
+    function gasPrice() external view returns (uint256);
+    function blockGasLimit() external view returns (uint256);
+    function coinbase() external view returns (address);
+    function difficulty() external view returns (uint256);
+    function baseFee() external view returns (uint256);
+    function txNumberInBlock() external view returns (uint16);
+    function getBlockHashEVM(uint256 blockNumber) external view returns (bytes32);
+    function getBatchHash(uint256 batchNumber) external view returns (bytes32 hash);
+    function getBlockNumber() external view returns (uint128);
+    function getBlockTimestamp() external view returns (uint128);
+    function getBatchNumberAndTimestamp() external view returns (uint128 blockNumber, uint128 blockTimestamp);
+    function getL2BlockNumberAndTimestamp() external view returns (uint128 blockNumber, uint128 blockTimestamp);
+}
+
+/// The zkSync Era `Compressor` system contract.
+#[sol(abi)]
+interface Compressor {
+    function publishCompressedBytecode(bytes bytecode, bytes rawCompressedData) external returns (bytes32 bytecodeHash);
+}
+}
+
+/// Returns the labels of the zkSync Era system contracts, at the same addresses on every chain.
+pub(super) fn labels() -> impl Iterator<Item = (Address, String)> {
+    [
+        (0x8001, "Bootloader"),
+        (0x8002, "AccountCodeStorage"),
+        (0x8003, "NonceHolder"),
+        (0x8004, "KnownCodesStorage"),
+        (0x8005, "ImmutableSimulator"),
+        (0x8006, "ContractDeployer"),
+        (0x8007, "ForceDeployer"),
+        (0x8008, "L1Messenger"),
+        (0x8009, "MsgValueSimulator"),
+        (0x800a, "L2BaseToken"),
+        (0x800b, "SystemContext"),
+        (0x800c, "BootloaderUtilities"),
+        (0x800d, "EventWriter"),
+        (0x800e, "Compressor"),
+        (0x800f, "ComplexUpgrader"),
+        (0x8010, "Keccak256"),
+        (0x8011, "PubdataChunkPublisher"),
+        (0x8012, "CodeOracle"),
+    ]
+    .into_iter()
+    .map(|(address, label)| (system_address(address), label.to_string()))
+}
+
+/// Returns the functions of the zkSync Era system contracts.
+pub(super) fn functions() -> impl Iterator<Item = Function> {
+    [
+        ContractDeployer::abi::functions(),
+        NonceHolder::abi::functions(),
+        AccountCodeStorage::abi::functions(),
+        KnownCodesStorage::abi::functions(),
+        ImmutableSimulator::abi::functions(),
+        L1Messenger::abi::functions(),
+        L2BaseToken::abi::functions(),
+        SystemContext::abi::functions(),
+        Compressor::abi::functions(),
+    ]
+    .into_iter()
+    .flat_map(|functions| functions.into_values().flatten())
+}
+
+/// Returns the events of the zkSync Era system contracts.
+pub(super) fn events() -> impl Iterator<Item = Event> {
+    [
+        ContractDeployer::abi::events(),
+        NonceHolder::abi::events(),
+        KnownCodesStorage::abi::events(),
+        L1Messenger::abi::events(),
+        L2BaseToken::abi::events(),
+    ]
+    .into_iter()
+    .flat_map(|events| events.into_values().flatten())
+}
+
+/// Returns the salt of a call to the `ContractDeployer` deploying a contract with `CREATE2`.
+pub(super) fn create2_salt(data: &[u8]) -> Option<B256> {
+    if let Ok(call) = ContractDeployer::create2Call::abi_decode(data, false) {
+        return Some(call.salt)
+    }
+    ContractDeployer::create2AccountCall::abi_decode(data, false).ok().map(|call| call.salt)
+}
+
+fn system_address(address: u16) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[18..].copy_from_slice(&address.to_be_bytes());
+    Address::new(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::CallTraceDecoder;
+    use alloy_primitives::{address, Selector};
+    use foundry_zksync_core::{convert::ConvertH160, CONTRACT_DEPLOYER_ADDRESS};
+
+    #[test]
+    fn decodes_system_contracts() {
+        let decoder = CallTraceDecoder::new();
+        assert_eq!(decoder.labels[&CONTRACT_DEPLOYER_ADDRESS.to_address()], "ContractDeployer");
+        assert_eq!(
+            decoder.labels[&address!("0000000000000000000000000000000000008009")],
+            "MsgValueSimulator"
+        );
+
+        let functions =
+            &decoder.functions[&Selector::from(ContractDeployer::create2Call::SELECTOR)];
+        assert_eq!(functions[0].signature(), "create2(bytes32,bytes32,bytes)");
+    }
+
+    #[test]
+    fn decodes_create2_salts() {
+        let salt = B256::with_last_byte(7);
+        let call = ContractDeployer::create2Call {
+            salt,
+            bytecodeHash: B256::with_last_byte(1),
+            input: Default::default(),
+        };
+        assert_eq!(create2_salt(&call.abi_encode()), Some(salt));
+
+        let call = ContractDeployer::createCall {
+            salt,
+            bytecodeHash: B256::with_last_byte(1),
+            input: Default::default(),
+        };
+        assert_eq!(create2_salt(&call.abi_encode()), None);
+    }
+}
@@ -8,6 +8,8 @@
 extern crate tracing;
 
 use alloy_primitives::LogData;
+use alloy_sol_types::SolCall;
+use foundry_cheatcodes_spec::Vm;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::constants::CHEATCODE_ADDRESS;
 use futures::{future::BoxFuture, FutureExt};
@@ -22,7 +24,10 @@ pub mod identifier;
 use identifier::LocalTraceIdentifier;
 
 mod decoder;
-pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder};
+pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder, Create2Deployment};
+
+mod sources;
+pub use sources::{SourceLine, TraceSources};
 
 use revm_inspectors::tracing::types::LogCallOrder;
 pub use revm_inspectors::tracing::{
@@ -45,6 +50,8 @@ pub struct DecodedCallTrace {
     pub return_data: Option<String>,
     pub func: Option<DecodedCallData>,
     pub contract: Option<String>,
+    /// The label of the implementation if the call was made to a proxy.
+    pub implementation: Option<String>,
 }
 
 #[derive(Debug)]
@@ -70,12 +77,74 @@ const RETURN: &str = "← ";
 pub async fn render_trace_arena(
     arena: &CallTraceArena,
     decoder: &CallTraceDecoder,
+) -> Result<String, std::fmt::Error> {
+    render_trace_arena_with_sources(arena, decoder, None).await
+}
+
+/// Render a collection of call traces, with the source lines of the call sites and revert points
+/// of the contracts of `sources` interleaved.
+///
+/// The source lines are only found if the traces were recorded with steps.
+pub async fn render_trace_arena_with_sources(
+    arena: &CallTraceArena,
+    decoder: &CallTraceDecoder,
+    sources: Option<&TraceSources>,
+) -> Result<String, std::fmt::Error> {
+    render_trace_nodes(arena, decoder, sources, None).await
+}
+
+/// Render the call traces of `arena` whose node is `shown`, like
+/// [render_trace_arena_with_sources].
+///
+/// The subcalls of a hidden call are rendered in its place.
+pub async fn render_trace_arena_filtered(
+    arena: &CallTraceArena,
+    decoder: &CallTraceDecoder,
+    sources: Option<&TraceSources>,
+    shown: &[bool],
+) -> Result<String, std::fmt::Error> {
+    render_trace_nodes(arena, decoder, sources, Some(shown)).await
+}
+
+/// Returns the verbosity each call of `arena` was made with, starting at `verbosity` and changed
+/// by the `vm.setTraceVerbosity` calls of the arena for the calls after them.
+pub fn node_verbosities(arena: &CallTraceArena, verbosity: u8) -> Vec<u8> {
+    let mut verbosity = verbosity;
+    arena
+        .nodes()
+        .iter()
+        .map(|node| {
+            let current = verbosity;
+            if node.trace.address == CHEATCODE_ADDRESS {
+                if let Ok(call) = Vm::setTraceVerbosityCall::abi_decode(&node.trace.data, false) {
+                    verbosity = call.verbosity;
+                }
+            }
+            current
+        })
+        .collect()
+}
+
+/// Returns whether the contract with `code` may call `vm.setTraceVerbosity`, i.e. whether its
+/// calls must be filtered by verbosity when they are traced.
+pub fn sets_trace_verbosity(code: &[u8]) -> bool {
+    code.windows(4).any(|window| window == Vm::setTraceVerbosityCall::SELECTOR)
+}
+
+async fn render_trace_nodes(
+    arena: &CallTraceArena,
+    decoder: &CallTraceDecoder,
+    sources: Option<&TraceSources>,
+    shown: Option<&[bool]>,
 ) -> Result<String, std::fmt::Error> {
     decoder.prefetch_signatures(arena.nodes()).await;
 
+    #[allow(clippy::too_many_arguments)]
     fn inner<'a>(
         arena: &'a [CallTraceNode],
         decoder: &'a CallTraceDecoder,
+        sources: Option<&'a TraceSources>,
+        shown: Option<&'a [bool]>,
         s: &'a mut String,
         idx: usize,
         left: &'a str,
@@ -84,10 +153,21 @@ pub async fn render_trace_arena(
         async move {
             let node = &arena[idx];
 
+            if shown.is_some_and(|shown| !shown.get(idx).copied().unwrap_or_default()) {
+                for index in &node.children {
+                    inner(arena, decoder, sources, shown, s, *index, left, child).await?;
+                }
+                return Ok(())
+            }
+
             // Display trace header
             let (trace, return_data) = render_trace(&node.trace, decoder).await?;
             writeln!(s, "{left}{}", trace)?;
 
+            let (call_sites, revert) = sources
+                .map(|sources| sources.node_lines(node, &decoder.contracts))
+                .unwrap_or_default();
+
             // Display logs and subcalls
             let left_prefix = format!("{child}{BRANCH}");
             let right_prefix = format!("{child}{PIPE}");
@@ -107,9 +187,15 @@ pub async fn render_trace_arena(
                         })?;
                     }
                     LogCallOrder::Call(index) => {
+                        if let Some(Some(line)) = call_sites.get(*index) {
+                            let line = Paint::new(format!("@ {line}")).dimmed();
+                            writeln!(s, "{right_prefix}{line}")?;
+                        }
                         inner(
                             arena,
                             decoder,
+                            sources,
+                            shown,
                             s,
                             node.children[*index],
                             &left_prefix,
@@ -120,6 +206,10 @@ pub async fn render_trace_arena(
                 }
             }
 
+            if let Some(line) = revert {
+                writeln!(s, "{child}{PIPE}{}", Paint::red(format!("reverted at {line}")))?;
+            }
+
             // Display trace return data
             let color = trace_color(&node.trace);
             write!(s, "{child}{EDGE}{}", color.paint(RETURN))?;
@@ -146,8 +236,9 @@ pub async fn render_trace_arena(
     }
 
     let mut s = String::new();
-    inner(arena.nodes(), decoder, &mut s, 0, "  ", "  ").await?;
-    Ok(s)
+    inner(arena.nodes(), decoder, sources, shown, &mut s, 0, "  ", "  ").await?;
+    // secrets like private keys can end up in the calldata of cheatcodes such as `vm.broadcast`
+    Ok(foundry_common::redact::redact(&s).into_owned())
 }
 
 /// Render a call trace.
@@ -171,6 +262,7 @@ pub async fn render_trace(
             decoded.label.as_deref().unwrap_or("<unknown>"),
             address
         )?;
+        write_create2_deployment(&mut s, trace, decoder)?;
     } else {
         let (func_name, inputs) = match &decoded.func {
             Some(DecodedCallData { signature, args }) => {
@@ -197,10 +289,16 @@ pub async fn render_trace(
         };
 
         let color = trace_color(trace);
+        let addr = decoded.label.as_deref().unwrap_or(&address);
+        // render calls to proxies as `Proxy(Implementation)::function(inputs)`
+        let addr = match &decoded.implementation {
+            Some(implementation) => format!("{addr}({implementation})"),
+            None => addr.to_string(),
+        };
         write!(
             &mut s,
             "{addr}::{func_name}{opt_value}({inputs}){action}",
-            addr = color.paint(decoded.label.as_deref().unwrap_or(&address)),
+            addr = color.paint(addr),
             func_name = color.paint(func_name),
             opt_value = if trace.value.is_zero() {
                 String::new()
@@ -209,11 +307,26 @@ pub async fn render_trace(
             },
             action = Paint::yellow(action),
         )?;
+        write_create2_deployment(&mut s, trace, decoder)?;
     }
 
     Ok((s, decoded.return_data))
 }
 
+/// Writes the salt and deployer of the contract deployed by the trace, if deployed with `CREATE2`.
+fn write_create2_deployment(
+    s: &mut String,
+    trace: &CallTrace,
+    decoder: &CallTraceDecoder,
+) -> std::fmt::Result {
+    let Some(Create2Deployment { salt, deployer }) = decoder.create2_deployment(trace) else {
+        return Ok(())
+    };
+    let deployer =
+        decoder.labels.get(deployer).cloned().unwrap_or_else(|| deployer.to_checksum(None));
+    write!(s, " {}", Paint::new(format!("{{salt: {salt}, deployer: {deployer}}}")).dimmed())
+}
+
 /// Render a trace log.
 async fn render_trace_log(
     log: &LogData,
@@ -317,3 +430,48 @@ pub fn load_contracts(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_abi::Function;
+    use alloy_primitives::{Address, U256};
+
+    fn render(trace: &CallTrace, decoder: &CallTraceDecoder) -> String {
+        Paint::disable();
+        futures::executor::block_on(render_trace(trace, decoder)).unwrap().0
+    }
+
+    #[test]
+    fn renders_proxy_calls() {
+        let proxy = Address::with_last_byte(1);
+        let implementation = Address::with_last_byte(2);
+        let function = Function::parse("store(uint256)").unwrap();
+
+        let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels([(proxy, "Proxy".to_string()), (implementation, "Impl".to_string())])
+            .with_proxies([(proxy, implementation)])
+            .build();
+        decoder.push_function(function.clone());
+
+        let data = [function.selector().as_slice(), &U256::from(42).to_be_bytes::<32>()].concat();
+        let call = CallTrace { address: proxy, data: data.clone().into(), ..Default::default() };
+        let rendered = render(&call, &decoder);
+        assert!(rendered.ends_with("Proxy(Impl)::store(42)"), "{rendered}");
+
+        // the delegate call of the proxy to its implementation is rendered as is
+        let delegate_call = CallTrace {
+            kind: CallKind::DelegateCall,
+            address: proxy,
+            data: data.into(),
+            ..Default::default()
+        };
+        let rendered = render(&delegate_call, &decoder);
+        assert!(rendered.ends_with("Proxy::store(42) [delegatecall]"), "{rendered}");
+
+        // calls to other contracts aren't annotated
+        let other = CallTrace { address: implementation, ..call };
+        let rendered = render(&other, &decoder);
+        assert!(rendered.ends_with("Impl::store(42)"), "{rendered}");
+    }
+}
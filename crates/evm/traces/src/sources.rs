@@ -0,0 +1,155 @@
+//! Source lines of the call sites and revert points of traces.
+
+use alloy_primitives::Address;
+use foundry_compilers::sourcemap::SourceMap;
+use foundry_evm_core::utils::PcIcMap;
+use revm::{interpreter::opcode, primitives::SpecId};
+use revm_inspectors::tracing::types::CallTraceNode;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+};
+
+/// The sources of the compiled contracts, used to show the source lines executed at the call
+/// boundaries and revert points of traces recorded with steps.
+#[derive(Default)]
+pub struct TraceSources {
+    /// The source files, by file id.
+    files: BTreeMap<u32, SourceFile>,
+    /// The deployed code and source map of the contracts, by artifact identifier.
+    contracts: HashMap<String, ContractSources>,
+}
+
+struct SourceFile {
+    path: PathBuf,
+    content: String,
+}
+
+struct ContractSources {
+    pc_ic: PcIcMap,
+    source_map: SourceMap,
+}
+
+/// A source line of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLine {
+    /// The path of the source file, as displayed.
+    pub path: PathBuf,
+    /// The 1-based line number.
+    pub line: usize,
+    /// The trimmed source code of the line.
+    pub code: String,
+}
+
+impl fmt::Display for SourceLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.code)
+    }
+}
+
+impl TraceSources {
+    /// Adds the source file with the given id, displayed as `path`.
+    pub fn insert_file(&mut self, id: u32, path: PathBuf, content: String) {
+        self.files.insert(id, SourceFile { path, content });
+    }
+
+    /// Adds the deployed code of the contract with the given artifact identifier and its source
+    /// map.
+    pub fn insert_contract(&mut self, identifier: String, code: &[u8], source_map: SourceMap) {
+        let pc_ic = PcIcMap::new(SpecId::LATEST, code);
+        self.contracts.insert(identifier, ContractSources { pc_ic, source_map });
+    }
+
+    /// Returns whether there are no contracts to locate the sources of.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// Returns the source line of the instruction at `pc` of the contract with the given artifact
+    /// identifier.
+    pub fn line(&self, identifier: &str, pc: usize) -> Option<SourceLine> {
+        let contract = self.contracts.get(identifier)?;
+        let element = contract.source_map.get(contract.pc_ic.get(pc)?)?;
+        let file = self.files.get(&element.index?)?;
+        let before = file.content.get(..element.offset)?;
+        let start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        let end = file.content[start..].find('\n').map_or(file.content.len(), |end| start + end);
+        Some(SourceLine {
+            path: file.path.clone(),
+            line: before.matches('\n').count() + 1,
+            code: file.content[start..end].trim().to_string(),
+        })
+    }
+
+    /// Returns the source lines of the call sites of the subcalls of `node`, by child index, and
+    /// of its revert point if it failed.
+    ///
+    /// `contracts` identifies the code executed by the node by its address. Lines are only found
+    /// in the deployed code of identified contracts, and only if the steps of the node were
+    /// recorded.
+    pub(crate) fn node_lines(
+        &self,
+        node: &CallTraceNode,
+        contracts: &HashMap<Address, String>,
+    ) -> (Vec<Option<SourceLine>>, Option<SourceLine>) {
+        let identifier = match contracts.get(&node.trace.address) {
+            Some(identifier) if !node.trace.kind.is_any_create() => identifier,
+            _ => return (vec![], None),
+        };
+
+        // every subcall starts at a call or create instruction of the node, in order
+        let call_sites = node
+            .trace
+            .steps
+            .iter()
+            .filter(|step| is_call(step.op.get()))
+            .map(|step| self.line(identifier, step.pc))
+            .collect::<Vec<_>>();
+        let call_sites = if call_sites.len() == node.children.len() { call_sites } else { vec![] };
+
+        let revert = node
+            .trace
+            .steps
+            .last()
+            .filter(|_| !node.trace.success)
+            .and_then(|step| self.line(identifier, step.pc));
+        (call_sites, revert)
+    }
+}
+
+fn is_call(op: u8) -> bool {
+    matches!(
+        op,
+        opcode::CALL |
+            opcode::CALLCODE |
+            opcode::DELEGATECALL |
+            opcode::STATICCALL |
+            opcode::CREATE |
+            opcode::CREATE2
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers::sourcemap::parse;
+
+    #[test]
+    fn locates_source_lines() {
+        let content = "contract C {\n    function f() external {\n        revert();\n    }\n}\n";
+        let offset = content.find("revert").unwrap();
+        // PUSH1 0x00, DUP1, REVERT
+        let code = [0x60, 0x00, 0x80, 0xfd];
+        let source_map = parse(&format!("0:0:0;;{offset}:8:0")).unwrap();
+
+        let mut sources = TraceSources::default();
+        sources.insert_file(0, "src/C.sol".into(), content.to_string());
+        sources.insert_contract("src/C.sol:C".to_string(), &code, source_map);
+
+        let line = sources.line("src/C.sol:C", 3).unwrap();
+        assert_eq!(line.to_string(), "src/C.sol:3: revert();");
+        assert_eq!(sources.line("src/C.sol:C", 1), None);
+        assert_eq!(sources.line("src/D.sol:D", 3), None);
+    }
+}
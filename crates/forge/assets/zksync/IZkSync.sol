@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+/// The system contracts of zkSync Era used by the project, and the types of their interfaces.
+
+address constant BOOTLOADER = address(0x8001);
+address constant NONCE_HOLDER = address(0x8003);
+address constant L1_MESSENGER = address(0x8008);
+
+/// The address zksolc simulates the calls made with the system call flag with, in system mode.
+address constant SYSTEM_CALL_CALL_ADDRESS = address((1 << 16) - 11);
+
+/// Calls contracts with the system call flag, like `SystemContractsCaller` of the system
+/// contracts. The caller must be compiled in system mode, i.e. with `system_mode = true`.
+library SystemCall {
+    function call(address to, bytes memory data) internal returns (bool success) {
+        address callAddr = SYSTEM_CALL_CALL_ADDRESS;
+        uint256 dataStart;
+        assembly {
+            dataStart := add(data, 0x20)
+        }
+        // the far call ABI of the heap slice of `data`, with all the gas left and the system call
+        // flag
+        uint256 farCallAbi =
+            (dataStart << 64) | (data.length << 96) | (uint256(uint32(gasleft())) << 192) | (1 << 248);
+        assembly {
+            success := call(to, callAddr, 0, 0, farCallAbi, 0, 0)
+        }
+    }
+}
+
+/// The transaction the bootloader passes to accounts and paymasters.
+struct Transaction {
+    uint256 txType;
+    uint256 from;
+    uint256 to;
+    uint256 gasLimit;
+    uint256 gasPerPubdataByteLimit;
+    uint256 maxFeePerGas;
+    uint256 maxPriorityFeePerGas;
+    uint256 paymaster;
+    uint256 nonce;
+    uint256 value;
+    uint256[4] reserved;
+    bytes data;
+    bytes signature;
+    bytes32[] factoryDeps;
+    bytes paymasterInput;
+    bytes reservedDynamic;
+}
+
+interface IAccount {
+    function validateTransaction(bytes32 txHash, bytes32 suggestedSignedHash, Transaction calldata transaction)
+        external
+        payable
+        returns (bytes4 magic);
+
+    function executeTransaction(bytes32 txHash, bytes32 suggestedSignedHash, Transaction calldata transaction)
+        external
+        payable;
+
+    function payForTransaction(bytes32 txHash, bytes32 suggestedSignedHash, Transaction calldata transaction)
+        external
+        payable;
+
+    function prepareForPaymaster(bytes32 txHash, bytes32 possibleSignedHash, Transaction calldata transaction)
+        external
+        payable;
+}
+
+enum ExecutionResult {
+    Revert,
+    Success
+}
+
+interface IPaymaster {
+    function validateAndPayForPaymasterTransaction(
+        bytes32 txHash,
+        bytes32 suggestedSignedHash,
+        Transaction calldata transaction
+    ) external payable returns (bytes4 magic, bytes memory context);
+
+    function postTransaction(
+        bytes calldata context,
+        Transaction calldata transaction,
+        bytes32 txHash,
+        bytes32 suggestedSignedHash,
+        ExecutionResult txResult,
+        uint256 maxRefundedGas
+    ) external payable;
+}
+
+/// The flows of the `paymasterInput` of a transaction.
+interface IPaymasterFlow {
+    function general(bytes calldata input) external;
+}
+
+interface INonceHolder {
+    function incrementMinNonceIfEquals(uint256 expectedNonce) external;
+}
+
+interface IL1Messenger {
+    function sendToL1(bytes calldata message) external returns (bytes32);
+}
+
+/// The mailbox of the zkSync Era diamond proxy on L1.
+interface IMailbox {
+    struct L2Message {
+        uint16 txNumberInBatch;
+        address sender;
+        bytes data;
+    }
+
+    function requestL2Transaction(
+        address contractL2,
+        uint256 l2Value,
+        bytes calldata l2Calldata,
+        uint256 l2GasLimit,
+        uint256 l2GasPerPubdataByteLimit,
+        bytes[] calldata factoryDeps,
+        address refundRecipient
+    ) external payable returns (bytes32 canonicalTxHash);
+
+    function proveL2MessageInclusion(
+        uint256 batchNumber,
+        uint256 index,
+        L2Message calldata message,
+        bytes32[] calldata proof
+    ) external view returns (bool);
+}
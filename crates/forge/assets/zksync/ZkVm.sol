@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+/// The zkSync cheatcodes used by the tests, at the address of `vm`.
+interface ZkVm {
+    struct L2ToL1Message {
+        address sender;
+        bytes data;
+        uint256 batchNumber;
+        uint256 index;
+        uint16 txNumberInBatch;
+        bytes32[] proof;
+    }
+
+    /// Runs the calls and deployments that follow in EraVM if `enable`, in the EVM otherwise.
+    function zkVm(bool enable) external pure;
+
+    /// Validates and executes the calls of `account` in EraVM with its own `IAccount` code, with
+    /// `signature` as the signature of its transactions if given.
+    function zkRegisterAccount(address account) external;
+    function zkRegisterAccount(address account, bytes calldata signature) external;
+
+    /// Makes `paymaster` pay for the calls in EraVM that follow, stopped with `address(0)`.
+    function zkUsePaymaster(address paymaster, bytes calldata paymasterInput) external;
+
+    /// Relays the L1 to L2 transactions requested to `mailbox` to EraVM and collects the L2 to
+    /// L1 messages.
+    function zkStartMessaging(address mailbox) external;
+
+    /// Returns the L2 to L1 messages sent since messaging started.
+    function zkGetL2ToL1Messages() external view returns (L2ToL1Message[] memory messages);
+}
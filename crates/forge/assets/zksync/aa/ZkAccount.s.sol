@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Script} from "forge-std/Script.sol";
+import {ZkAccount} from "../src/ZkAccount.sol";
+
+/// Deploys an account owned by the sender and funds it, with
+/// `forge script script/ZkAccount.s.sol --rpc-url <RPC> --broadcast`.
+contract ZkAccountScript is Script {
+    function run() public returns (ZkAccount account) {
+        vm.startBroadcast();
+        account = new ZkAccount(msg.sender);
+        (bool success,) = address(account).call{value: 0.01 ether}("");
+        require(success, "failed funding the account");
+        vm.stopBroadcast();
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {BOOTLOADER, NONCE_HOLDER, IAccount, INonceHolder, SystemCall, Transaction} from "./interfaces/IZkSync.sol";
+
+/// An account validating and executing its own transactions, which its owner can lock.
+contract ZkAccount is IAccount {
+    address public owner;
+    bool public locked;
+
+    modifier onlyBootloader() {
+        require(msg.sender == BOOTLOADER, "only the bootloader can call the account");
+        _;
+    }
+
+    constructor(address _owner) {
+        owner = _owner;
+    }
+
+    function setLocked(bool _locked) external {
+        require(msg.sender == owner, "only the owner can lock the account");
+        locked = _locked;
+    }
+
+    function validateTransaction(bytes32, bytes32, Transaction calldata transaction)
+        external
+        payable
+        onlyBootloader
+        returns (bytes4 magic)
+    {
+        // the nonce holder only accepts calls with the system call flag
+        bool success = SystemCall.call(
+            NONCE_HOLDER, abi.encodeCall(INonceHolder.incrementMinNonceIfEquals, (transaction.nonce))
+        );
+        require(success, "failed incrementing the nonce");
+        magic = locked ? bytes4(0) : IAccount.validateTransaction.selector;
+    }
+
+    function executeTransaction(bytes32, bytes32, Transaction calldata transaction)
+        external
+        payable
+        onlyBootloader
+    {
+        (bool success, bytes memory data) =
+            address(uint160(transaction.to)).call{value: transaction.value}(transaction.data);
+        if (!success) {
+            assembly {
+                revert(add(data, 0x20), mload(data))
+            }
+        }
+    }
+
+    function payForTransaction(bytes32, bytes32, Transaction calldata transaction) external payable onlyBootloader {
+        (bool success,) = BOOTLOADER.call{value: transaction.gasLimit * transaction.maxFeePerGas}("");
+        require(success, "failed paying the bootloader");
+    }
+
+    function prepareForPaymaster(bytes32, bytes32, Transaction calldata) external payable onlyBootloader {}
+
+    receive() external payable {}
+}
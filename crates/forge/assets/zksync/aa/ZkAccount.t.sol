@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test} from "forge-std/Test.sol";
+import {ZkVm} from "./utils/ZkVm.sol";
+import {Counter} from "../src/Counter.sol";
+import {ZkAccount} from "../src/ZkAccount.sol";
+
+contract ZkAccountTest is Test {
+    ZkVm constant zk = ZkVm(VM_ADDRESS);
+
+    ZkAccount public account;
+    Counter public counter;
+
+    function setUp() public {
+        zk.zkVm(true);
+        account = new ZkAccount(address(this));
+        counter = new Counter();
+        vm.deal(address(account), 1 ether);
+        zk.zkRegisterAccount(address(account));
+    }
+
+    function test_AccountExecutes() public {
+        vm.prank(address(account));
+        counter.increment();
+
+        assertEq(counter.number(), 1);
+        // the account paid the fees of its transaction
+        assertLt(address(account).balance, 1 ether);
+    }
+
+    function test_LockedAccountFailsValidation() public {
+        account.setLocked(true);
+
+        vm.expectRevert();
+        vm.prank(address(account));
+        counter.increment();
+    }
+
+    /// The counter deployed in EraVM behaves like the one deployed in the EVM.
+    function test_CounterInBothVms() public {
+        counter.increment();
+        uint256 zkNumber = counter.number();
+
+        zk.zkVm(false);
+        Counter evmCounter = new Counter();
+        evmCounter.increment();
+
+        assertEq(evmCounter.number(), zkNumber);
+    }
+}
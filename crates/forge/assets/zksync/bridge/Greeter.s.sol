@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Script} from "forge-std/Script.sol";
+import {IMailbox} from "../src/interfaces/IZkSync.sol";
+import {L1Greeter} from "../src/L1Greeter.sol";
+import {L2Greeter} from "../src/L2Greeter.sol";
+
+/// Deploys the L1 greeter with the mailbox at `MAILBOX`, on L1 with
+/// `FOUNDRY_ZKSYNC=false forge script script/Greeter.s.sol:L1GreeterScript --rpc-url <L1_RPC> --broadcast`.
+contract L1GreeterScript is Script {
+    function run() public returns (L1Greeter l1Greeter) {
+        IMailbox mailbox = IMailbox(vm.envAddress("MAILBOX"));
+        vm.startBroadcast();
+        l1Greeter = new L1Greeter(mailbox);
+        vm.stopBroadcast();
+    }
+}
+
+/// Deploys the L2 greeter of the L1 greeter at `L1_GREETER`, on zkSync Era with
+/// `forge script script/Greeter.s.sol:L2GreeterScript --rpc-url <L2_RPC> --broadcast`.
+///
+/// The L1 greeter is then connected to it with `L1Greeter.setL2Greeter`.
+contract L2GreeterScript is Script {
+    function run() public returns (L2Greeter l2Greeter) {
+        address l1Greeter = vm.envAddress("L1_GREETER");
+        vm.startBroadcast();
+        l2Greeter = new L2Greeter(l1Greeter);
+        vm.stopBroadcast();
+    }
+}
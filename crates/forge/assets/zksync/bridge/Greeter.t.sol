@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test} from "forge-std/Test.sol";
+import {ZkVm} from "./utils/ZkVm.sol";
+import {IMailbox} from "../src/interfaces/IZkSync.sol";
+import {L1Greeter} from "../src/L1Greeter.sol";
+import {L2Greeter} from "../src/L2Greeter.sol";
+
+/// Tests the greeters on both sides: the L1 greeter in the EVM and the L2 greeter in EraVM, with
+/// the messages between them relayed by `zkStartMessaging`.
+contract GreeterTest is Test {
+    ZkVm constant zk = ZkVm(VM_ADDRESS);
+
+    /// The mailbox of the zkSync Era diamond proxy on Ethereum mainnet.
+    IMailbox constant MAILBOX = IMailbox(0x32400084C286CF3E17e7B677ea9583e60a000324);
+
+    L1Greeter public l1Greeter;
+    L2Greeter public l2Greeter;
+
+    function setUp() public {
+        zk.zkStartMessaging(address(MAILBOX));
+
+        zk.zkVm(false);
+        l1Greeter = new L1Greeter(MAILBOX);
+
+        zk.zkVm(true);
+        l2Greeter = new L2Greeter(address(l1Greeter));
+
+        zk.zkVm(false);
+        l1Greeter.setL2Greeter(address(l2Greeter));
+    }
+
+    function test_GreetL2() public {
+        l1Greeter.greetL2("hello L2", 1_000_000);
+
+        // the priority request is relayed before the next call in EraVM
+        zk.zkVm(true);
+        assertEq(l2Greeter.greeting(), "hello L2");
+    }
+
+    function test_OnlyL1GreeterGreetsL2() public {
+        zk.zkVm(true);
+        vm.expectRevert();
+        l2Greeter.setGreeting("not from L1");
+    }
+
+    function test_GreetL1() public {
+        ZkVm.L2ToL1Message memory message = sendGreetingToL1("hello L1");
+        assertEq(message.sender, address(l2Greeter));
+
+        l1Greeter.receiveFromL2(
+            message.batchNumber, message.index, message.txNumberInBatch, "hello L1", message.proof
+        );
+        assertEq(l1Greeter.greeting(), "hello L1");
+    }
+
+    function test_UnsentGreetingIsNotProven() public {
+        ZkVm.L2ToL1Message memory message = sendGreetingToL1("hello L1");
+
+        // the proof of the sent greeting doesn't prove another one
+        vm.expectRevert("the message is not proven");
+        l1Greeter.receiveFromL2(
+            message.batchNumber, message.index, message.txNumberInBatch, "never sent", message.proof
+        );
+    }
+
+    /// Sends `greeting` from the L2 greeter, returning its message to L1.
+    function sendGreetingToL1(string memory greeting) internal returns (ZkVm.L2ToL1Message memory) {
+        zk.zkVm(true);
+        l2Greeter.greetL1(greeting);
+
+        ZkVm.L2ToL1Message[] memory messages = zk.zkGetL2ToL1Messages();
+        assertEq(messages.length, 1);
+
+        zk.zkVm(false);
+        return messages[0];
+    }
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {IMailbox} from "./interfaces/IZkSync.sol";
+import {L2Greeter} from "./L2Greeter.sol";
+
+/// The L1 side of the greeter, sending greetings to its L2 side through the zkSync Era mailbox and
+/// receiving the greetings its L2 side sends back once they're proven.
+contract L1Greeter {
+    /// The gas per pubdata byte the L2 transactions are requested with.
+    uint256 constant L2_GAS_PER_PUBDATA_BYTE = 800;
+
+    IMailbox public immutable mailbox;
+    address public immutable owner;
+    address public l2Greeter;
+    string public greeting;
+
+    constructor(IMailbox _mailbox) {
+        mailbox = _mailbox;
+        owner = msg.sender;
+    }
+
+    function setL2Greeter(address _l2Greeter) external {
+        require(msg.sender == owner, "only the owner can set the L2 greeter");
+        require(l2Greeter == address(0), "the L2 greeter is already set");
+        l2Greeter = _l2Greeter;
+    }
+
+    /// Requests an L2 transaction setting the greeting of the L2 greeter, paid by the sent value.
+    function greetL2(string calldata _greeting, uint256 l2GasLimit) external payable returns (bytes32 txHash) {
+        txHash = mailbox.requestL2Transaction{value: msg.value}(
+            l2Greeter,
+            0,
+            abi.encodeCall(L2Greeter.setGreeting, (_greeting)),
+            l2GasLimit,
+            L2_GAS_PER_PUBDATA_BYTE,
+            new bytes[](0),
+            msg.sender
+        );
+    }
+
+    /// Sets the greeting the L2 greeter sent in the message at `index` of the batch `batchNumber`.
+    function receiveFromL2(
+        uint256 batchNumber,
+        uint256 index,
+        uint16 txNumberInBatch,
+        string calldata _greeting,
+        bytes32[] calldata proof
+    ) external {
+        IMailbox.L2Message memory message = IMailbox.L2Message(txNumberInBatch, l2Greeter, bytes(_greeting));
+        require(mailbox.proveL2MessageInclusion(batchNumber, index, message, proof), "the message is not proven");
+        greeting = _greeting;
+    }
+}
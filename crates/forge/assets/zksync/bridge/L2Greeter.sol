@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {L1_MESSENGER, IL1Messenger} from "./interfaces/IZkSync.sol";
+
+/// The L2 side of the greeter, receiving greetings from its L1 side and sending greetings back.
+contract L2Greeter {
+    /// The offset of the addresses L1 contracts call L2 with.
+    uint160 constant L1_TO_L2_ALIAS_OFFSET = uint160(0x1111000000000000000000000000000000001111);
+
+    address public immutable l1Greeter;
+    string public greeting;
+
+    constructor(address _l1Greeter) {
+        l1Greeter = _l1Greeter;
+    }
+
+    /// Sets the greeting, called by the L1 greeter through an L1 to L2 transaction.
+    function setGreeting(string calldata _greeting) external {
+        address aliased;
+        unchecked {
+            aliased = address(uint160(l1Greeter) + L1_TO_L2_ALIAS_OFFSET);
+        }
+        require(msg.sender == aliased, "only the L1 greeter can set the greeting");
+        greeting = _greeting;
+    }
+
+    /// Sends a greeting to the L1 greeter, which it can receive once the batch is proven.
+    function greetL1(string calldata _greeting) external returns (bytes32 hash) {
+        hash = IL1Messenger(L1_MESSENGER).sendToL1(bytes(_greeting));
+    }
+}
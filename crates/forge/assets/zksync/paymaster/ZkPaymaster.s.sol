@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Script} from "forge-std/Script.sol";
+import {Counter} from "../src/Counter.sol";
+import {ZkPaymaster} from "../src/ZkPaymaster.sol";
+
+/// Deploys a paymaster owned by the sender sponsoring a new counter, and funds it, with
+/// `forge script script/ZkPaymaster.s.sol --rpc-url <RPC> --broadcast`.
+contract ZkPaymasterScript is Script {
+    function run() public returns (ZkPaymaster paymaster, Counter counter) {
+        vm.startBroadcast();
+        paymaster = new ZkPaymaster(msg.sender);
+        counter = new Counter();
+        paymaster.setSponsored(address(counter), true);
+        (bool success,) = address(paymaster).call{value: 0.01 ether}("");
+        require(success, "failed funding the paymaster");
+        vm.stopBroadcast();
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {BOOTLOADER, ExecutionResult, IPaymaster, IPaymasterFlow, Transaction} from "./interfaces/IZkSync.sol";
+
+/// A paymaster paying the fees of the transactions calling the contracts its owner sponsors, with
+/// the general paymaster flow.
+contract ZkPaymaster is IPaymaster {
+    address public owner;
+    mapping(address => bool) public sponsored;
+
+    modifier onlyBootloader() {
+        require(msg.sender == BOOTLOADER, "only the bootloader can call the paymaster");
+        _;
+    }
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "only the owner can manage the paymaster");
+        _;
+    }
+
+    constructor(address _owner) {
+        owner = _owner;
+    }
+
+    function setSponsored(address target, bool _sponsored) external onlyOwner {
+        sponsored[target] = _sponsored;
+    }
+
+    function withdraw(address payable to, uint256 amount) external onlyOwner {
+        (bool success,) = to.call{value: amount}("");
+        require(success, "failed withdrawing");
+    }
+
+    function validateAndPayForPaymasterTransaction(bytes32, bytes32, Transaction calldata transaction)
+        external
+        payable
+        onlyBootloader
+        returns (bytes4 magic, bytes memory context)
+    {
+        require(transaction.paymasterInput.length >= 4, "the paymaster input must select a flow");
+        require(bytes4(transaction.paymasterInput[0:4]) == IPaymasterFlow.general.selector, "unsupported flow");
+        require(sponsored[address(uint160(transaction.to))], "the target is not sponsored");
+
+        (bool success,) = BOOTLOADER.call{value: transaction.gasLimit * transaction.maxFeePerGas}("");
+        require(success, "failed paying the bootloader");
+        return (IPaymaster.validateAndPayForPaymasterTransaction.selector, context);
+    }
+
+    function postTransaction(bytes calldata, Transaction calldata, bytes32, bytes32, ExecutionResult, uint256)
+        external
+        payable
+        onlyBootloader
+    {}
+
+    receive() external payable {}
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test} from "forge-std/Test.sol";
+import {ZkVm} from "./utils/ZkVm.sol";
+import {IPaymasterFlow} from "../src/interfaces/IZkSync.sol";
+import {Counter} from "../src/Counter.sol";
+import {ZkPaymaster} from "../src/ZkPaymaster.sol";
+
+contract ZkPaymasterTest is Test {
+    ZkVm constant zk = ZkVm(VM_ADDRESS);
+
+    ZkPaymaster public paymaster;
+    Counter public counter;
+
+    function setUp() public {
+        zk.zkVm(true);
+        paymaster = new ZkPaymaster(address(this));
+        counter = new Counter();
+        paymaster.setSponsored(address(counter), true);
+        vm.deal(address(paymaster), 1 ether);
+    }
+
+    function test_PaymasterPaysFees() public {
+        uint256 balance = address(this).balance;
+
+        zk.zkUsePaymaster(address(paymaster), abi.encodeCall(IPaymasterFlow.general, ("")));
+        counter.increment();
+        zk.zkUsePaymaster(address(0), "");
+
+        assertEq(counter.number(), 1);
+        assertEq(address(this).balance, balance);
+        assertLt(address(paymaster).balance, 1 ether);
+    }
+
+    function test_PaymasterRejectsUnsponsoredTargets() public {
+        paymaster.setSponsored(address(counter), false);
+
+        zk.zkUsePaymaster(address(paymaster), abi.encodeCall(IPaymasterFlow.general, ("")));
+        vm.expectRevert();
+        counter.increment();
+    }
+
+    /// The counter deployed in EraVM behaves like the one deployed in the EVM, where there are no
+    /// paymasters.
+    function test_CounterInBothVms() public {
+        zk.zkUsePaymaster(address(paymaster), abi.encodeCall(IPaymasterFlow.general, ("")));
+        counter.increment();
+        zk.zkUsePaymaster(address(0), "");
+        uint256 zkNumber = counter.number();
+
+        zk.zkVm(false);
+        Counter evmCounter = new Counter();
+        evmCounter.increment();
+
+        assertEq(evmCounter.number(), zkNumber);
+    }
+}
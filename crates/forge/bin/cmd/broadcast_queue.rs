@@ -0,0 +1,111 @@
+use super::script::queue::{BroadcastQueue, QueueEntry, QUEUE_FILE_SUFFIX};
+use clap::{Parser, ValueHint};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use eyre::Result;
+use foundry_cli::{
+    opts::{CoreBuildArgs, ProjectPathsArgs},
+    utils::LoadConfig,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// CLI arguments for `forge broadcast-queue`.
+#[derive(Clone, Debug, Parser)]
+pub struct BroadcastQueueArgs {
+    /// The broadcast queue to inspect.
+    ///
+    /// If not specified, all queues left in the cache directory are shown.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH")]
+    path: Option<PathBuf>,
+
+    /// Print the queues as JSON, including the raw signed transactions.
+    #[clap(long)]
+    json: bool,
+
+    #[clap(flatten)]
+    project_paths: ProjectPathsArgs,
+}
+
+impl BroadcastQueueArgs {
+    pub fn run(self) -> Result<()> {
+        let paths = match self.path {
+            Some(path) => vec![path],
+            None => {
+                let build_args =
+                    CoreBuildArgs { project_paths: self.project_paths, ..Default::default() };
+                let config = build_args.try_load_config_emit_warnings()?;
+                let mut paths = vec![];
+                find_queues(&config.cache_path, &mut paths)?;
+                paths.sort();
+                paths
+            }
+        };
+
+        let queues = paths
+            .into_iter()
+            .map(|path| Ok((BroadcastQueue::read(&path)?, path)))
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.json {
+            let queues = queues
+                .iter()
+                .map(|(entries, path)| (path.display().to_string(), entries.values().collect()))
+                .collect::<BTreeMap<_, Vec<_>>>();
+            println!("{}", serde_json::to_string_pretty(&queues)?);
+            return Ok(())
+        }
+
+        if queues.is_empty() {
+            println!("No broadcast queues found.");
+            return Ok(())
+        }
+
+        for (entries, path) in &queues {
+            println!("{}", path.display());
+            println!("{}\n", queue_table(entries));
+        }
+        println!("Run `forge script --resume` to finish broadcasting the queued transactions.");
+
+        Ok(())
+    }
+}
+
+fn queue_table(entries: &BTreeMap<usize, QueueEntry>) -> Table {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(["#", "Type", "From", "To", "Nonce", "State", "Hash"]);
+
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    for entry in entries.values() {
+        let tx = entry.transaction.as_ref();
+        let kind = if entry.zk.is_some() { "eip712" } else { "evm" };
+        table.add_row([
+            entry.index.to_string(),
+            kind.to_string(),
+            or_dash(tx.and_then(|tx| tx.from()).map(|from| format!("{from:?}"))),
+            or_dash(tx.and_then(|tx| tx.to_addr()).map(|to| format!("{to:?}"))),
+            or_dash(tx.and_then(|tx| tx.nonce()).map(|nonce| nonce.to_string())),
+            entry.state.to_string(),
+            or_dash(entry.hash.map(|hash| hash.to_string())),
+        ]);
+    }
+    table
+}
+
+/// Collects all broadcast queues in `dir`, recursively.
+fn find_queues(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(())
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_queues(&path, paths)?;
+        } else if path.to_string_lossy().ends_with(QUEUE_FILE_SUFFIX) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
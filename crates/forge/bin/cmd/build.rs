@@ -1,9 +1,16 @@
 use super::{install, watch::WatchArgs};
-use clap::Parser;
-use eyre::Result;
+use alloy_primitives::{keccak256, B256};
+use clap::{Parser, ValueHint};
+use ethers_signers::LocalWallet;
+use eyre::{Result, WrapErr};
+use forge::attestation::AttestedArtifacts;
 use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
-use foundry_common::compile::{ProjectCompiler, SkipBuildFilter, SkipBuildFilters};
-use foundry_compilers::{Project, ProjectCompileOutput};
+use foundry_common::{
+    bytecode::runtime_equivalent_hash,
+    compile::{ProjectCompiler, SkipBuildFilter, SkipBuildFilters},
+    redact::register_secret,
+};
+use foundry_compilers::{artifacts::BytecodeHash, Project, ProjectCompileOutput};
 use foundry_config::{
     figment::{
         self,
@@ -11,14 +18,23 @@ use foundry_config::{
         value::{Dict, Map, Value},
         Metadata, Profile, Provider,
     },
-    Config,
+    BytecodePin, Config,
+};
+use foundry_zksync_compiler::{
+    missing_libraries_deployment_order, write_combined_artifacts, write_missing_libraries,
+    PackedEraBytecode, ZkMissingLibrary, ZkSolc, COMBINED_ARTIFACTS_DIR,
+    ZKSOLC_MISSING_LIBRARIES_FILE,
 };
-use foundry_zksync_compiler::{ZkSolc, DEFAULT_ZKSOLC_VERSION};
+use foundry_zksync_core::convert::ConvertH256;
 use serde::Serialize;
+use std::path::{Path, PathBuf};
 use watchexec::config::{InitConfig, RuntimeConfig};
 
 foundry_config::merge_impl_figment_convert!(BuildArgs, args);
 
+/// The default file name of the attestation, in the artifacts directory.
+pub const ATTESTATION_FILE: &str = "attestation.json";
+
 /// CLI arguments for `forge build`.
 ///
 /// CLI arguments take the highest precedence in the Config/Figment hierarchy.
@@ -73,6 +89,50 @@ pub struct BuildArgs {
     #[clap(long, conflicts_with = "silent")]
     #[serde(skip)]
     pub format_json: bool,
+
+    /// Fail if the deployed bytecode hash of a contract differs from the given one.
+    ///
+    /// The contract is identified by `<name>` or `<path>:<name>`. The hash is the keccak256 of the
+    /// EVM deployed bytecode, or the EraVM bytecode hash when compiling with `--zksync`.
+    ///
+    /// These are checked in addition to the `assert_bytecode` list of the config.
+    #[clap(long, num_args(1..), value_name = "CONTRACT=HASH")]
+    #[serde(skip)]
+    pub assert_bytecode: Vec<BytecodePin>,
+
+    /// Compute the pinned bytecode hashes without the metadata of the bytecode.
+    ///
+    /// The hash is then the keccak256 of the deployed bytecode with its metadata removed.
+    #[clap(long)]
+    #[serde(skip)]
+    pub ignore_metadata: bool,
+
+    /// Sign the hashes of the compiled artifacts, their standard json inputs and compiler
+    /// versions, and write them to an attestation file.
+    ///
+    /// The attestation can be checked with `forge verify-attestation`.
+    #[clap(long, requires = "attestation_key")]
+    #[serde(skip)]
+    pub attest: bool,
+
+    /// The private key of the project, used to sign the attestation.
+    #[clap(long, env = "FOUNDRY_ATTESTATION_KEY", value_name = "KEY", hide_env_values = true)]
+    #[serde(skip)]
+    pub attestation_key: Option<String>,
+
+    /// The file to write the attestation to. Defaults to `attestation.json` in the artifacts
+    /// directory.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH", requires = "attest")]
+    #[serde(skip)]
+    pub attestation_out: Option<PathBuf>,
+
+    /// With `--zksync`, also write a combined artifact per contract, with the solc ABI and
+    /// bytecodes and the zksolc bytecode, bytecode hash and factory dependencies.
+    ///
+    /// The artifacts are written to `zkout/combined/<path>/<contract>.json`.
+    #[clap(long)]
+    #[serde(skip)]
+    pub combined_artifacts: bool,
 }
 
 impl BuildArgs {
@@ -106,7 +166,6 @@ impl BuildArgs {
                     .new_zksolc_config_builder()
                     .and_then(|builder| {
                         builder
-                            .compiler_version(DEFAULT_ZKSOLC_VERSION)
                             .avoid_contracts(self.args.compiler.avoid_contracts.clone())
                             .contracts_to_compile(self.args.compiler.contracts_to_compile.clone())
                             .build()
@@ -119,11 +178,63 @@ impl BuildArgs {
                 Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
             };
 
+            // zksolc only reports the missing libraries, without producing bytecode
+            if config.zksolc_settings().detect_missing_libraries {
+                report_missing_libraries(
+                    zksolc.missing_libraries(),
+                    &config.cache_path,
+                    self.format_json,
+                )?;
+                return Ok(zk_output)
+            }
+
+            if self.combined_artifacts || config.zk_combined_artifacts {
+                let zk_project = config.zk_project()?;
+                let dir = zk_project.artifacts_path().join(COMBINED_ARTIFACTS_DIR);
+                let written = write_combined_artifacts(&output, &zk_output, project.root(), &dir)?;
+                if !self.format_json {
+                    println!("Wrote {written} combined artifacts to {}", dir.display());
+                }
+            }
+
             zk_output
         } else {
             output
         };
 
+        let pins = config.assert_bytecode.iter().chain(&self.assert_bytecode).collect::<Vec<_>>();
+        if !pins.is_empty() {
+            let ignore_metadata = self.ignore_metadata || config.assert_bytecode_ignore_metadata;
+            // there's no metadata hash to ignore if it's stripped, or not appended, when compiling,
+            // and the last word of zksolc bytecode is an instruction then
+            let has_metadata_hash =
+                !config.strip_metadata && config.bytecode_hash != BytecodeHash::None;
+            let ignore_metadata = ignore_metadata && has_metadata_hash;
+            assert_bytecode_hashes(&output, &pins, config.zksync, ignore_metadata)?;
+        }
+
+        if self.attest {
+            let key = self.attestation_key.as_deref().unwrap_or_default();
+            register_secret(key.trim_start_matches("0x"));
+            let key: LocalWallet =
+                key.trim_start_matches("0x").parse().wrap_err("invalid attestation key")?;
+            let zksolc_version = config.zksync.then(|| config.zksolc_version()).flatten();
+            let attestation =
+                AttestedArtifacts::new(&project, &output, zksolc_version)?.sign(&key)?;
+            let path = self
+                .attestation_out
+                .unwrap_or_else(|| project.artifacts_path().join(ATTESTATION_FILE));
+            attestation.write(&path)?;
+            if !self.format_json {
+                println!(
+                    "Attested {} artifacts signed by {} in {}",
+                    attestation.artifacts.artifacts.len(),
+                    attestation.signer,
+                    path.display()
+                );
+            }
+        }
+
         if self.format_json {
             println!("{}", serde_json::to_string_pretty(&output.clone().output())?);
         }
@@ -149,13 +260,103 @@ impl BuildArgs {
     /// bootstrap a new [`watchexe::Watchexec`] loop.
     pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
         // use the path arguments or if none where provided the `src` dir
-        self.watch.watchexec_config(|| {
-            let config = Config::from(self);
+        let config = Config::from(self);
+        self.watch.watchexec_config(config.get_config_path(), || {
             vec![config.src, config.test, config.script]
         })
     }
 }
 
+/// Prints the contracts linking libraries that aren't set in `libraries`, and the order to
+/// deploy the libraries in.
+///
+/// The report is also written to the cache directory, for tools reading it. Tests and scripts
+/// don't read it, as it may be stale: they order the deployments with the current compilation.
+fn report_missing_libraries(
+    report: &[ZkMissingLibrary],
+    cache_path: &Path,
+    json: bool,
+) -> Result<()> {
+    let order = missing_libraries_deployment_order(report)?;
+    write_missing_libraries(&cache_path.join(ZKSOLC_MISSING_LIBRARIES_FILE), report)?;
+
+    if json {
+        let report = serde_json::json!({ "contracts": report, "deploymentOrder": order });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(())
+    }
+    if report.is_empty() {
+        println!("No missing libraries");
+        return Ok(())
+    }
+    println!("Contracts linking missing libraries:");
+    for contract in report {
+        println!("  {contract}");
+    }
+    println!(
+        "\nDeploy the libraries in this order, and add them to `libraries` as \
+         `<path>:<name>:<address>`:"
+    );
+    for (i, library) in order.iter().enumerate() {
+        println!("  {}. {library}", i + 1);
+    }
+    Ok(())
+}
+
+/// Checks the deployed bytecode hashes of the compiled contracts against the pinned ones.
+fn assert_bytecode_hashes(
+    output: &ProjectCompileOutput,
+    pins: &[&BytecodePin],
+    zksync: bool,
+    ignore_metadata: bool,
+) -> Result<()> {
+    let mut mismatches = vec![];
+    for pin in pins {
+        let (path, name) = match pin.contract.rsplit_once(':') {
+            Some((path, name)) => (Some(path), name),
+            None => (None, pin.contract.as_str()),
+        };
+        let mut artifacts = output.artifact_ids().filter(|(id, _)| {
+            id.name == name && path.map_or(true, |path| id.source.ends_with(path))
+        });
+        let Some((id, artifact)) = artifacts.next() else {
+            eyre::bail!("could not find artifact `{}` of bytecode pin", pin.contract);
+        };
+        if artifacts.next().is_some() {
+            eyre::bail!(
+                "multiple artifacts match `{}`, use `<path>:<name>` to pin its bytecode",
+                pin.contract
+            );
+        }
+
+        let code = artifact
+            .deployed_bytecode
+            .as_ref()
+            .and_then(|deployed| deployed.bytecode.as_ref())
+            .and_then(|bytecode| bytecode.object.as_bytes())
+            .filter(|code| !code.is_empty())
+            .ok_or_else(|| eyre::eyre!("`{}` has no deployed bytecode", id.identifier()))?;
+        // zksolc artifacts contain the bytecode packed with its factory dependencies
+        let code =
+            if zksync { PackedEraBytecode::from_vec(code).bytecode() } else { code.to_vec() };
+        let hash: B256 = if ignore_metadata {
+            runtime_equivalent_hash(&code, zksync)
+        } else if zksync {
+            foundry_zksync_core::hash_bytecode(&code).to_b256()
+        } else {
+            keccak256(&code)
+        };
+        if hash != pin.hash {
+            mismatches.push(format!("  {}: expected {}, got {hash}", pin.contract, pin.hash));
+        }
+    }
+
+    if !mismatches.is_empty() {
+        eyre::bail!("bytecode hashes differ from the pinned values:\n{}", mismatches.join("\n"));
+    }
+    Ok(())
+}
+
 // Make this args a `figment::Provider` so that it can be merged into the `Config`
 impl Provider for BuildArgs {
     fn metadata(&self) -> Metadata {
@@ -199,6 +400,17 @@ mod tests {
         assert_eq!(args.skip, Some(vec![SkipBuildFilter::Tests, SkipBuildFilter::Scripts]));
     }
 
+    #[test]
+    fn can_parse_bytecode_pins() {
+        let hash = "0x5c5f3c0b2ac8e8d6c94cf0e6b0297ec5a72fbbad31cd4a8d4a05d3e1d8a4e5f1";
+        let args: BuildArgs =
+            BuildArgs::parse_from(["foundry-cli", "--assert-bytecode", &format!("Counter={hash}")]);
+        assert_eq!(args.assert_bytecode, vec![format!("Counter={hash}").parse().unwrap()]);
+
+        let args = BuildArgs::try_parse_from(["foundry-cli", "--assert-bytecode", "Counter"]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn check_conflicts() {
         let args: std::result::Result<BuildArgs, clap::Error> =
@@ -4,8 +4,15 @@ use clap::{
     Arg, Command, Parser, Subcommand,
 };
 use eyre::Result;
-use foundry_config::{cache, Chain, Config, NamedChain};
-use std::{ffi::OsStr, str::FromStr};
+use foundry_config::{
+    cache::{self, CacheSize, GcPlan},
+    Chain, Config, NamedChain,
+};
+use std::{
+    ffi::OsStr,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 use strum::VariantNames;
 
 /// CLI arguments for `forge cache`.
@@ -22,6 +29,9 @@ pub enum CacheSubcommands {
 
     /// Shows cached data from the global foundry directory.
     Ls(LsArgs),
+
+    /// Removes the least recently used caches, artifacts and compiler binaries.
+    Gc(GcArgs),
 }
 
 /// CLI arguments for `forge clean`.
@@ -107,6 +117,56 @@ impl LsArgs {
     }
 }
 
+/// CLI arguments for `forge cache gc`.
+#[derive(Debug, Parser)]
+#[clap(group = clap::ArgGroup::new("limits").required(true).multiple(true))]
+pub struct GcArgs {
+    /// Remove the least recently used entries until everything fits, e.g. `5GB`.
+    #[clap(long, value_name = "SIZE", group = "limits")]
+    max_size: Option<CacheSize>,
+
+    /// Remove entries that weren't modified within this duration, e.g. `30d`.
+    #[clap(long, value_name = "AGE", value_parser = parse_age, group = "limits")]
+    older_than: Option<Duration>,
+
+    /// Only report what would be removed.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+impl GcArgs {
+    pub fn run(self) -> Result<()> {
+        let GcArgs { max_size, older_than, dry_run } = self;
+        let config = Config::load();
+        let entries = config.list_foundry_cache_entries()?;
+        let plan = GcPlan::new(entries, max_size.map(|size| size.0), older_than, SystemTime::now());
+
+        println!("{plan}");
+        if !dry_run {
+            plan.execute()?;
+            println!("Freed {}", CacheSize(plan.freed()));
+        }
+        Ok(())
+    }
+}
+
+/// Parses an age like `12h`, `30d` or `2w`.
+fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value.parse().map_err(|_| format!("invalid age: {s}"))?;
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown age unit `{unit}`, expected s, m, h, d or w")),
+    };
+    Ok(Duration::from_secs(value * scale))
+}
+
 #[derive(Clone, Debug)]
 pub enum ChainOrAll {
     NamedChain(NamedChain),
@@ -186,4 +246,17 @@ mod tests {
         let args: CacheArgs = CacheArgs::parse_from(["cache", "ls"]);
         assert!(matches!(args.sub, CacheSubcommands::Ls(_)));
     }
+
+    #[test]
+    fn can_parse_cache_gc() {
+        let args: CacheArgs =
+            CacheArgs::parse_from(["cache", "gc", "--max-size", "5GB", "--older-than", "30d"]);
+        let CacheSubcommands::Gc(args) = args.sub else { panic!("expected gc") };
+        assert_eq!(args.max_size, Some(CacheSize(5_000_000_000)));
+        assert_eq!(args.older_than, Some(Duration::from_secs(30 * 24 * 60 * 60)));
+        assert!(!args.dry_run);
+
+        assert!(CacheArgs::try_parse_from(["cache", "gc", "--dry-run"]).is_err());
+        assert!(CacheArgs::try_parse_from(["cache", "gc", "--older-than", "30y"]).is_err());
+    }
 }
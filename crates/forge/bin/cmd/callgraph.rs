@@ -0,0 +1,539 @@
+use super::test;
+use alloy_primitives::Address;
+use clap::{Parser, ValueEnum, ValueHint};
+use eyre::Result;
+use forge::{
+    constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    traces::{
+        identifier::LocalTraceIdentifier, CallTrace, CallTraceDecoder, CallTraceDecoderBuilder,
+    },
+};
+use foundry_cli::utils::LoadConfig;
+use foundry_common::{
+    compile::ProjectCompiler, contracts::get_contract_name, fs, shell, ContractsByArtifact,
+};
+use semver::Version;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Interfaces and libraries of the zkSync Era system contracts.
+///
+/// Calls through them are marked as system calls, since they are handled by the EraVM kernel.
+const SYSTEM_CONTRACT_TYPES: &[&str] = &[
+    "IAccountCodeStorage",
+    "IBaseToken",
+    "IBootloaderUtilities",
+    "IComplexUpgrader",
+    "ICompressor",
+    "IContractDeployer",
+    "IImmutableSimulator",
+    "IKnownCodesStorage",
+    "IL1Messenger",
+    "IL2EthToken",
+    "IMsgValueSimulator",
+    "INonceHolder",
+    "IPubdataChunkPublisher",
+    "ISystemContext",
+    "SystemContractsCaller",
+    "SystemContractHelper",
+];
+
+/// CLI arguments for `forge callgraph`.
+#[derive(Clone, Debug, Parser)]
+pub struct CallgraphArgs {
+    /// The format of the call graph.
+    #[clap(long, value_enum, default_value_t = CallgraphFormat::Dot)]
+    format: CallgraphFormat,
+
+    /// Write the call graph to the given file instead of stdout.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    export: Option<PathBuf>,
+
+    /// Only build the call graph from the ASTs, without running the tests to observe calls.
+    #[clap(long)]
+    static_only: bool,
+
+    /// All test arguments are supported, they select the tests whose calls are observed.
+    #[clap(flatten)]
+    test: test::TestArgs,
+}
+
+/// The output format of `forge callgraph`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CallgraphFormat {
+    /// Graphviz DOT.
+    Dot,
+    /// A JSON object with `nodes` and `edges`.
+    Json,
+}
+
+impl CallgraphArgs {
+    pub async fn run(mut self) -> Result<()> {
+        let mut config = self.test.load_config_emit_warnings();
+        // The static call graph is built from the Solc AST output.
+        config.ast = true;
+
+        let project = config.ephemeral_no_artifacts_project()?;
+        let output = ProjectCompiler::new()
+            .quiet(true)
+            .compile(&project)?
+            .with_stripped_file_prefixes(project.root());
+
+        let mut graph = CallGraph::default();
+        let project_paths = config.project_paths();
+        let (artifacts, sources) = output.into_artifacts_with_sources();
+        let mut declarations = Declarations::default();
+        let mut asts = Vec::new();
+        for (path, source_file, version) in sources.into_sources_with_version() {
+            let Some(ast) = source_file.ast else { continue };
+            let ast = serde_json::to_value(ast)?;
+            declarations.collect(&version, &ast);
+            // calls made from dependencies are out of scope
+            if !project_paths.has_library_ancestor(Path::new(&path)) {
+                asts.push((version, ast));
+            }
+        }
+        for (version, ast) in &asts {
+            declarations.add_static_edges(version, ast, &mut graph);
+        }
+
+        if !self.static_only {
+            let known_contracts = ContractsByArtifact(
+                artifacts
+                    .into_iter()
+                    .filter_map(|(id, artifact)| {
+                        let code = artifact
+                            .deployed_bytecode
+                            .as_ref()?
+                            .bytecode
+                            .as_ref()?
+                            .object
+                            .as_bytes()?
+                            .to_vec();
+                        Some((id, (artifact.abi.clone().unwrap_or_default(), code)))
+                    })
+                    .collect(),
+            );
+            let mut local_identifier = LocalTraceIdentifier::new(&known_contracts);
+            let mut decoder = CallTraceDecoderBuilder::new()
+                .with_local_identifier_abis(&local_identifier)
+                .build();
+
+            // traces are only recorded from verbosity level 3 on
+            self.test.evm_opts.verbosity = self.test.evm_opts.verbosity.max(3);
+            // the results of the tests are only needed for their traces, only the call graph is
+            // written to stdout
+            self.test.json = true;
+            let _ = shell::set_shell(shell::Shell::silent());
+            let outcome = self.test.execute_tests().await?;
+            for (_, result) in outcome.tests() {
+                decoder.clear_addresses();
+                decoder
+                    .labels
+                    .extend(result.labeled_addresses.iter().map(|(k, v)| (*k, v.clone())));
+                decoder.proxies.extend(result.proxies.iter().map(|(k, v)| (*k, *v)));
+                for (_, arena) in &result.traces {
+                    decoder.identify(arena, &mut local_identifier);
+                    let nodes = arena.nodes();
+                    for node in nodes {
+                        let Some(parent) = node.parent else { continue };
+                        let callee = &node.trace;
+                        if callee.address == CHEATCODE_ADDRESS ||
+                            callee.address == HARDHAT_CONSOLE_ADDRESS
+                        {
+                            continue
+                        }
+                        let caller = &nodes[parent].trace;
+                        let from = node_name(caller, &decoder).await;
+                        let to = node_name(callee, &decoder).await;
+                        // the calls executed in EraVM are recorded by the cheatcodes when they
+                        // switch from the EVM, the calls within EraVM don't show up in the traces
+                        let cross_vm = result.zk_calls.contains(&(caller.address, callee.address));
+                        graph.observe(from, to, cross_vm, is_system_contract(callee.address));
+                    }
+                }
+            }
+        }
+
+        let rendered = match self.format {
+            CallgraphFormat::Dot => graph.to_string(),
+            CallgraphFormat::Json => serde_json::to_string_pretty(&graph)?,
+        };
+        match &self.export {
+            Some(path) => {
+                fs::write(path, rendered)?;
+                println!("Call graph written to {}", path.display());
+            }
+            None => println!("{rendered}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the `Contract.function` name of the function called in `trace`.
+async fn node_name(trace: &CallTrace, decoder: &CallTraceDecoder) -> String {
+    let decoded = decoder.decode_function(trace).await;
+    let contract = decoded
+        .contract
+        .as_deref()
+        .map(get_contract_name)
+        .or(decoded.label.as_deref())
+        .map(str::to_string)
+        .unwrap_or_else(|| trace.address.to_string());
+    let function = if trace.kind.is_any_create() {
+        "constructor".to_string()
+    } else {
+        match &decoded.func {
+            Some(func) => func.signature.split('(').next().unwrap_or_default().to_string(),
+            None if trace.data.len() < 4 => "fallback".to_string(),
+            None => alloy_primitives::hex::encode_prefixed(&trace.data[..4]),
+        }
+    };
+    format!("{contract}.{function}")
+}
+
+/// Returns true if `address` is in the kernel space of EraVM, where the system contracts live.
+fn is_system_contract(address: Address) -> bool {
+    let bytes = address.as_slice();
+    bytes[..18].iter().all(|b| *b == 0) && u16::from_be_bytes([bytes[18], bytes[19]]) >= 0x8000
+}
+
+/// How a call is made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// A call to a function of the same contract or one of its bases.
+    Internal,
+    /// A message call to another contract.
+    External,
+    /// A call to a library function.
+    Library,
+    /// A call only observed in the test traces.
+    Observed,
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EdgeKind::Internal => "internal",
+            EdgeKind::External => "external",
+            EdgeKind::Library => "library",
+            EdgeKind::Observed => "observed",
+        })
+    }
+}
+
+/// A call from one function to another.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CallEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    /// Whether the call crosses from the EVM into EraVM.
+    pub cross_vm: bool,
+    /// Whether the call targets a zkSync system contract.
+    pub system: bool,
+    /// How many times the call was observed in the test traces.
+    pub observed: u64,
+}
+
+/// A call graph over `Contract.function` nodes.
+#[derive(Clone, Debug, Default)]
+pub struct CallGraph {
+    edges: BTreeMap<(String, String), CallEdge>,
+}
+
+impl CallGraph {
+    /// Adds a call found in the AST.
+    pub fn add_static(&mut self, from: String, to: String, kind: EdgeKind, system: bool) {
+        self.edges.entry((from.clone(), to.clone())).or_insert(CallEdge {
+            from,
+            to,
+            kind,
+            cross_vm: false,
+            system,
+            observed: 0,
+        });
+    }
+
+    /// Records a call observed in a trace.
+    pub fn observe(&mut self, from: String, to: String, cross_vm: bool, system: bool) {
+        let edge = self.edges.entry((from.clone(), to.clone())).or_insert(CallEdge {
+            from,
+            to,
+            kind: EdgeKind::Observed,
+            cross_vm,
+            system,
+            observed: 0,
+        });
+        edge.cross_vm |= cross_vm;
+        edge.system |= system;
+        edge.observed += 1;
+    }
+
+    /// Returns all nodes of the graph.
+    pub fn nodes(&self) -> BTreeSet<&str> {
+        self.edges.values().flat_map(|edge| [edge.from.as_str(), edge.to.as_str()]).collect()
+    }
+
+    /// Returns all edges of the graph.
+    pub fn edges(&self) -> impl Iterator<Item = &CallEdge> {
+        self.edges.values()
+    }
+}
+
+impl Serialize for CallGraph {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Graph<'a> {
+            nodes: BTreeSet<&'a str>,
+            edges: Vec<&'a CallEdge>,
+        }
+
+        Graph { nodes: self.nodes(), edges: self.edges().collect() }.serialize(serializer)
+    }
+}
+
+impl fmt::Display for CallGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph callgraph {{")?;
+        writeln!(f, "    node [shape=box];")?;
+        for node in self.nodes() {
+            writeln!(f, "    {node:?};")?;
+        }
+        for edge in self.edges() {
+            let mut attrs = format!("label=\"{}\"", edge.kind);
+            if edge.cross_vm {
+                attrs.push_str(", color=red, penwidth=2");
+            } else if edge.system {
+                attrs.push_str(", color=blue");
+            }
+            if edge.kind == EdgeKind::Observed {
+                attrs.push_str(", style=dashed");
+            }
+            if edge.observed > 0 {
+                write!(attrs, ", tooltip=\"observed {} times\"", edge.observed)?;
+            }
+            writeln!(f, "    {:?} -> {:?} [{attrs}];", edge.from, edge.to)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// A function or modifier declared in a contract.
+#[derive(Clone, Debug)]
+struct Declaration {
+    /// The `Contract.function` name.
+    name: String,
+    /// Whether the declaring contract is a library.
+    library: bool,
+    /// Whether the declaring contract is a zkSync system contract interface or library.
+    system: bool,
+}
+
+/// All function declarations of the project, by compiler version and AST node ID, since the IDs
+/// are only unique within a single compiler run.
+#[derive(Debug, Default)]
+struct Declarations(HashMap<(Version, u64), Declaration>);
+
+impl Declarations {
+    /// Collects all function and modifier declarations in the AST of a source file.
+    fn collect(&mut self, version: &Version, ast: &Value) {
+        for contract in contracts(ast) {
+            let contract_name = contract["name"].as_str().unwrap_or_default();
+            let library = contract["contractKind"] == "library";
+            let system = SYSTEM_CONTRACT_TYPES.contains(&contract_name);
+            for function in functions(contract) {
+                let Some(id) = function["id"].as_u64() else { continue };
+                let name = format!("{contract_name}.{}", function_name(function));
+                self.0.insert((version.clone(), id), Declaration { name, library, system });
+            }
+        }
+    }
+
+    /// Adds an edge for every call to a known declaration made in the AST of a source file.
+    fn add_static_edges(&self, version: &Version, ast: &Value, graph: &mut CallGraph) {
+        for contract in contracts(ast) {
+            let contract_name = contract["name"].as_str().unwrap_or_default();
+            for function in functions(contract) {
+                let from = format!("{contract_name}.{}", function_name(function));
+                let mut calls = Vec::new();
+                find_calls(&function["body"], &mut calls);
+                // modifier invocations are calls too
+                if let Some(modifiers) = function["modifiers"].as_array() {
+                    for modifier in modifiers {
+                        calls.push(&modifier["modifierName"]);
+                    }
+                }
+
+                for callee in calls {
+                    let Some(id) = callee["referencedDeclaration"].as_u64() else { continue };
+                    let Some(declaration) = self.0.get(&(version.clone(), id)) else { continue };
+                    let kind = if declaration.library {
+                        EdgeKind::Library
+                    } else if is_external_call(callee) {
+                        EdgeKind::External
+                    } else {
+                        EdgeKind::Internal
+                    };
+                    let to = declaration.name.clone();
+                    graph.add_static(from.clone(), to, kind, declaration.system);
+                }
+            }
+        }
+    }
+}
+
+/// Returns all contract definitions of a source unit.
+fn contracts(ast: &Value) -> impl Iterator<Item = &Value> {
+    nodes(ast).filter(|node| node["nodeType"] == "ContractDefinition")
+}
+
+/// Returns all function and modifier definitions of a contract.
+fn functions(contract: &Value) -> impl Iterator<Item = &Value> {
+    nodes(contract).filter(|node| {
+        node["nodeType"] == "FunctionDefinition" || node["nodeType"] == "ModifierDefinition"
+    })
+}
+
+fn nodes(node: &Value) -> impl Iterator<Item = &Value> {
+    node["nodes"].as_array().into_iter().flatten()
+}
+
+/// Returns the name of a function, or its kind for unnamed functions like the constructor.
+fn function_name(function: &Value) -> &str {
+    match function["name"].as_str() {
+        Some(name) if !name.is_empty() => name,
+        _ => function["kind"].as_str().unwrap_or("fallback"),
+    }
+}
+
+/// Returns true if the called expression is a member of a contract instance, i.e. `token.transfer`.
+fn is_external_call(callee: &Value) -> bool {
+    callee["nodeType"] == "MemberAccess" &&
+        callee["expression"]["typeDescriptions"]["typeString"]
+            .as_str()
+            .is_some_and(|ty| ty.starts_with("contract "))
+}
+
+/// Recursively collects the called expressions of all function calls below `node`.
+fn find_calls<'a>(node: &'a Value, calls: &mut Vec<&'a Value>) {
+    match node {
+        Value::Object(map) => {
+            if map.get("nodeType").is_some_and(|ty| ty == "FunctionCall") {
+                calls.push(&map["expression"]);
+            }
+            map.values().for_each(|value| find_calls(value, calls));
+        }
+        Value::Array(values) => values.iter().for_each(|value| find_calls(value, calls)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_build_static_call_graph() {
+        let call = |expression| json!({ "nodeType": "FunctionCall", "expression": expression });
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "nodes": [
+                {
+                    "nodeType": "ContractDefinition",
+                    "name": "Counter",
+                    "contractKind": "contract",
+                    "nodes": [
+                        {
+                            "nodeType": "FunctionDefinition",
+                            "id": 1,
+                            "name": "increment",
+                            "kind": "function"
+                        },
+                        {
+                            "nodeType": "FunctionDefinition",
+                            "id": 2,
+                            "name": "",
+                            "kind": "constructor",
+                            "body": { "statements": [{
+                                "expression": call(json!({
+                                    "nodeType": "Identifier",
+                                    "referencedDeclaration": 1
+                                }))
+                            }]}
+                        }
+                    ]
+                },
+                {
+                    "nodeType": "ContractDefinition",
+                    "name": "CounterTest",
+                    "contractKind": "contract",
+                    "nodes": [{
+                        "nodeType": "FunctionDefinition",
+                        "id": 3,
+                        "name": "testIncrement",
+                        "kind": "function",
+                        "body": { "statements": [{
+                            "expression": call(json!({
+                                "nodeType": "MemberAccess",
+                                "referencedDeclaration": 1,
+                                "expression": {
+                                    "typeDescriptions": { "typeString": "contract Counter" }
+                                }
+                            }))
+                        }]}
+                    }]
+                }
+            ]
+        });
+
+        let version = Version::new(0, 8, 20);
+        let mut declarations = Declarations::default();
+        declarations.collect(&version, &ast);
+        let mut graph = CallGraph::default();
+        declarations.add_static_edges(&version, &ast, &mut graph);
+
+        let edges: Vec<_> =
+            graph.edges().map(|e| (e.from.as_str(), e.to.as_str(), e.kind)).collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("Counter.constructor", "Counter.increment", EdgeKind::Internal),
+                ("CounterTest.testIncrement", "Counter.increment", EdgeKind::External),
+            ]
+        );
+
+        graph.observe(
+            "CounterTest.testIncrement".to_string(),
+            "Counter.increment".to_string(),
+            true,
+            false,
+        );
+        graph.observe(
+            "Counter.increment".to_string(),
+            "ContractDeployer.create".to_string(),
+            false,
+            true,
+        );
+        let dot = graph.to_string();
+        assert!(dot.contains(
+            r#""CounterTest.testIncrement" -> "Counter.increment" [label="external", color=red"#
+        ));
+        assert!(dot.contains(r#"[label="observed", color=blue, style=dashed"#));
+    }
+
+    #[test]
+    fn can_detect_system_contracts() {
+        assert!(!is_system_contract(Address::with_last_byte(0x06)));
+        assert!(is_system_contract("0x0000000000000000000000000000000000008006".parse().unwrap()));
+        assert!(!is_system_contract(CHEATCODE_ADDRESS));
+    }
+}
@@ -17,7 +17,7 @@ use forge::{
 use foundry_cli::{
     opts::CoreBuildArgs,
     p_println,
-    utils::{LoadConfig, STATIC_FUZZ_SEED},
+    utils::{pin_config_fork_block, LoadConfig, STATIC_FUZZ_SEED},
 };
 use foundry_common::{compile::ProjectCompiler, evm::EvmArgs, fs};
 use foundry_compilers::{
@@ -75,7 +75,8 @@ pub struct CoverageArgs {
 
 impl CoverageArgs {
     pub async fn run(self) -> Result<()> {
-        let (mut config, evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        let (mut config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        pin_config_fork_block(&config, &mut evm_opts, false).await?;
 
         // install missing dependencies
         if install::install_missing_dependencies(&mut config, self.build_args().silent) &&
@@ -300,19 +301,14 @@ impl CoverageArgs {
             .evm_spec(config.evm_spec_id())
             .sender(evm_opts.sender)
             .with_fork(evm_opts.get_fork(&config, env.clone()))
-            .with_cheats_config(CheatsConfig::new(
-                &config,
-                evm_opts.clone(),
-                None,
-                Default::default(),
-                false,
-            ))
+            .with_cheats_config(CheatsConfig::new(&config, evm_opts.clone(), None, false))
             .with_test_options(TestOptions {
                 fuzz: config.fuzz,
-                invariant: config.invariant,
+                invariant: config.invariant.clone(),
                 ..Default::default()
             })
             .set_coverage(true)
+            .with_genesis(config.genesis.clone())
             .build(&root, output, env, evm_opts)?;
 
         // Run tests
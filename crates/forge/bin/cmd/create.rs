@@ -1,7 +1,7 @@
 use super::{retry::RetryArgs, verify};
 use alloy_dyn_abi::{DynSolValue, JsonAbiExt, ResolveSolType};
 use alloy_json_abi::{Constructor, JsonAbi};
-use alloy_primitives::{Address, Bytes};
+use alloy_primitives::{utils::format_units, Address, Bytes, U256};
 use clap::{Parser, ValueHint};
 use ethers_contract::ContractError;
 use ethers_core::{
@@ -16,7 +16,7 @@ use ethers_providers::Middleware;
 use eyre::{Context, Result};
 use foundry_cli::{
     opts::{CoreBuildArgs, EthereumOpts, EtherscanOpts, TransactionOpts},
-    utils::{self, read_constructor_args_file, remove_contract, LoadConfig},
+    utils::{self, find_contract, read_constructor_args_file, LoadConfig},
 };
 use foundry_common::{
     compile::ProjectCompiler,
@@ -28,14 +28,81 @@ use foundry_compilers::{
     artifacts::{BytecodeObject, CompactBytecode},
     info::ContractInfo,
     utils::canonicalized,
+    ProjectCompileOutput,
 };
+use foundry_config::Config;
 use foundry_wallets::WalletSigner;
-use foundry_zksync_compiler::{
-    new_dual_compiled_contracts, DualCompiledContract, FindContract, ZkSolc,
-};
+use foundry_zksync_compiler::{DualCompiledContract, DualCompiledContracts, ZkSolc};
+use foundry_zksync_core::pubdata;
 use serde_json::json;
 use std::{borrow::Borrow, marker::PhantomData, path::PathBuf, sync::Arc};
 
+/// The outcome of `forge create`.
+#[derive(Debug)]
+pub struct CreateOutcome {
+    /// The address of the deployed contract, or the one it would be deployed at with
+    /// `--estimate-only`.
+    pub address: Address,
+    /// The result of the verification of the contract, if it was requested.
+    pub verification: Option<Result<()>>,
+}
+
+/// The compiled contracts of a project, shared by the deployments of a `forge deploy` plan.
+pub struct CompiledProject {
+    /// The root of the project.
+    root: PathBuf,
+    /// The solc output of the project.
+    output: ProjectCompileOutput,
+    /// The zksolc contracts of the project, if it's compiled with zksolc.
+    dual_compiled_contracts: Option<DualCompiledContracts>,
+}
+
+impl CompiledProject {
+    /// Compiles the project of `opts`, with zksolc as well if `zksync` is set.
+    pub fn compile(
+        opts: &CoreBuildArgs,
+        config: &Config,
+        zksync: bool,
+        quiet: bool,
+    ) -> Result<Self> {
+        let project = opts.project()?;
+        let output = ProjectCompiler::new().quiet_if(quiet).compile(&project)?;
+
+        let dual_compiled_contracts = if zksync {
+            let mut zksolc = ZkSolc::new(
+                config
+                    .new_zksolc_config_builder()
+                    .and_then(|builder| {
+                        builder
+                            .avoid_contracts(opts.compiler.avoid_contracts.clone())
+                            .contracts_to_compile(opts.compiler.contracts_to_compile.clone())
+                            .build()
+                    })
+                    .map_err(|e| eyre::eyre!(e))?,
+                config.zk_project()?,
+            );
+            let (zk_output, _contract_bytecodes) = match zksolc.compile() {
+                Ok(compiled) => compiled,
+                Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
+            };
+            Some(DualCompiledContracts::new(&output, &zk_output))
+        } else {
+            None
+        };
+
+        Ok(Self { root: project.root().clone(), output, dual_compiled_contracts })
+    }
+
+    /// Returns the identifier of `contract` in the output, whose paths are absolute.
+    fn contract_info(&self, contract: &ContractInfo) -> ContractInfo {
+        let mut contract = contract.clone();
+        if let Some(ref mut path) = contract.path {
+            *path = canonicalized(self.root.join(&path)).to_string_lossy().to_string();
+        }
+        contract
+    }
+}
+
 /// CLI arguments for `forge create`.
 #[derive(Clone, Debug, Parser)]
 pub struct CreateArgs {
@@ -78,6 +145,12 @@ pub struct CreateArgs {
     #[clap(long, requires = "verify")]
     show_standard_json_input: bool,
 
+    /// Print the estimated gas and fee of the deployment without sending it.
+    ///
+    /// With `--zksync`, the bytes of pubdata published by the deployment are printed as well.
+    #[clap(long, conflicts_with = "verify")]
+    estimate_only: bool,
+
     #[clap(flatten)]
     opts: CoreBuildArgs,
 
@@ -95,46 +168,79 @@ pub struct CreateArgs {
 }
 
 impl CreateArgs {
+    /// Returns the arguments to deploy `contract`, e.g. as a step of a deployment plan.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        contract: ContractInfo,
+        constructor_args: Vec<String>,
+        verify: bool,
+        opts: CoreBuildArgs,
+        tx: TransactionOpts,
+        eth: EthereumOpts,
+        verifier: verify::VerifierArgs,
+        retry: RetryArgs,
+    ) -> Self {
+        Self {
+            contract,
+            constructor_args,
+            constructor_args_path: None,
+            json: false,
+            verify,
+            unlocked: false,
+            show_standard_json_input: false,
+            estimate_only: false,
+            opts,
+            tx,
+            eth,
+            verifier,
+            retry,
+        }
+    }
+
     /// Executes the command to create a contract
-    pub async fn run(mut self) -> Result<()> {
-        // Find Project & Compile
-        let project = self.opts.project()?;
-        let mut output =
-            ProjectCompiler::new().quiet_if(self.json || self.opts.silent).compile(&project)?;
+    pub async fn run(self) -> Result<()> {
+        if let Some(verification) = self.create().await?.verification {
+            verification?;
+        }
+        Ok(())
+    }
 
+    /// Deploys the contract, and verifies it if requested.
+    pub async fn create(self) -> Result<CreateOutcome> {
         let config = self.eth.try_load_config_emit_warnings()?;
-        let zksync = self.opts.compiler.zksync;
-        let mut zksolc = ZkSolc::new(
-            config
-                .new_zksolc_config_builder()
-                .and_then(|builder| {
-                    builder
-                        .avoid_contracts(self.opts.compiler.avoid_contracts.clone())
-                        .contracts_to_compile(self.opts.compiler.contracts_to_compile.clone())
-                        .build()
-                })
-                .map_err(|e| eyre::eyre!(e))?,
-            config.zk_project()?,
-        );
-        let (zk_output, _contract_bytecodes) = match zksolc.compile() {
-            Ok(compiled) => compiled,
-            Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
-        };
-        let dual_compiled_contracts = new_dual_compiled_contracts(&output, &zk_output);
+        let compiled = CompiledProject::compile(
+            &self.opts,
+            &config,
+            self.opts.compiler.zksync,
+            self.json || self.opts.silent,
+        )?;
+        self.create_with(&compiled).await
+    }
 
-        if let Some(ref mut path) = self.contract.path {
-            // paths are absolute in the project's output
-            *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
+    /// Deploys the contract from the already compiled `compiled` project, and verifies it if
+    /// requested.
+    pub(crate) async fn create_with(mut self, compiled: &CompiledProject) -> Result<CreateOutcome> {
+        let config = self.eth.try_load_config_emit_warnings()?;
+        let zksync = self.opts.compiler.zksync;
+        if zksync && self.unlocked {
+            eyre::bail!(
+                "`--unlocked` is not supported with `--zksync`, the EIP-712 deployment \
+                 transaction must be signed"
+            )
         }
 
-        let (abi, bin, _) = remove_contract(&mut output, &self.contract)?;
+        self.contract = compiled.contract_info(&self.contract);
+        let (abi, bin, _) = find_contract(&compiled.output, &self.contract)?;
 
         let (abi, bin, zk_contract) = if zksync {
-            println!("{:?}", bin);
+            let dual_compiled_contracts = compiled
+                .dual_compiled_contracts
+                .as_ref()
+                .ok_or_else(|| eyre::eyre!("the project is not compiled with zksolc"))?;
             let contract = bin
                 .object
                 .as_bytes()
-                .and_then(|bytes| dual_compiled_contracts.find_evm_bytecode(&bytes.0))
+                .and_then(|bytes| dual_compiled_contracts.find_by_evm_bytecode(&bytes.0))
                 .ok_or(eyre::eyre!(
                     "Could not find zksolc contract for contract {}",
                     self.contract.name
@@ -224,6 +330,7 @@ impl CreateArgs {
             address: Default::default(),
             contract: self.contract.clone(),
             compiler_version: None,
+            zksolc_version: None,
             constructor_args,
             constructor_args_path: None,
             num_of_optimizations: None,
@@ -262,7 +369,7 @@ impl CreateArgs {
         chain: u64,
         zk_contract: Option<&DualCompiledContract>,
         signer: Option<WalletSigner>,
-    ) -> Result<()> {
+    ) -> Result<CreateOutcome> {
         let deployer_address =
             provider.default_sender().expect("no sender address set for provider");
         let bin = bin.into_bytes().unwrap_or_else(|| {
@@ -285,7 +392,7 @@ impl CreateArgs {
         })?;
 
         let deployer = if let Some(contract) = zk_contract {
-            deployer.set_zk_factory_deps(vec![contract.zk_deployed_bytecode.clone()])
+            deployer.set_zk_factory_deps(contract.factory_deps())
         } else {
             deployer
         };
@@ -299,6 +406,8 @@ impl CreateArgs {
             deployer.tx.set_value(value.to_ethers());
         }
 
+        // The address of the contract in EraVM, derived from the deployment nonce of the deployer
+        let mut zk_address = None;
         match zk_contract {
             None => provider.fill_transaction(&mut deployer.tx, None).await?,
             Some(contract) => {
@@ -331,12 +440,22 @@ impl CreateArgs {
 
                 let estimated_gas = foundry_zksync_core::estimate_gas(
                     &deployer.tx,
-                    vec![contract.zk_deployed_bytecode.clone()],
+                    contract.factory_deps(),
                     &provider,
                 )
                 .await?;
                 deployer.tx.set_gas(estimated_gas.limit.to_ethers());
                 deployer.tx.set_gas_price(estimated_gas.price.to_ethers());
+
+                let deployment_nonce = foundry_zksync_core::get_deployment_nonce(
+                    deployer_address.to_alloy(),
+                    &provider,
+                )
+                .await?;
+                zk_address = Some(foundry_zksync_core::compute_create_address(
+                    deployer_address.to_alloy(),
+                    deployment_nonce,
+                ));
             }
         }
 
@@ -346,8 +465,9 @@ impl CreateArgs {
         // set gas price if specified
         if let Some(gas_price) = self.tx.gas_price {
             deployer.tx.set_gas_price(gas_price.to_ethers());
-        } else if !is_legacy {
-            // estimate EIP1559 fees
+        } else if !is_legacy && zk_contract.is_none() {
+            // estimate EIP1559 fees, the fees of zkSync deployments are estimated with
+            // `zks_estimateFee`
             let (max_fee, max_priority_fee) = estimate_eip1559_fees(&provider, Some(chain))
                 .await
                 .wrap_err("Failed to estimate EIP1559 fees. This chain might not support EIP1559, try adding --legacy to your command.")?;
@@ -380,6 +500,15 @@ impl CreateArgs {
             };
         }
 
+        if self.estimate_only {
+            let nonce = deployer.tx.nonce().map_or(0, |nonce| nonce.as_u64());
+            let address = zk_address.unwrap_or_else(|| deployer_address.to_alloy().create(nonce));
+            let zk_pubdata =
+                zk_contract.map(|contract| pubdata::deployment_pubdata(&contract.factory_deps()));
+            self.print_estimate(&deployer.tx, address, zk_pubdata);
+            return Ok(CreateOutcome { address, verification: None });
+        }
+
         // Before we actually deploy the contract we try check if the verify settings are valid
         let mut constructor_args = None;
         if self.verify {
@@ -395,7 +524,7 @@ impl CreateArgs {
         }
 
         // Deploy the actual contract
-        let (deployed_contract, receipt) = deployer.send_with_receipt(signer).await?;
+        let (deployed_contract, receipt) = deployer.send_with_receipt(signer, zk_address).await?;
 
         let address = deployed_contract;
         if self.json {
@@ -412,9 +541,52 @@ impl CreateArgs {
         };
 
         if !self.verify {
-            return Ok(());
+            return Ok(CreateOutcome { address, verification: None });
         }
 
+        let verification = self.verify_contract(address, constructor_args, chain).await;
+        Ok(CreateOutcome { address, verification: Some(verification) })
+    }
+
+    /// Prints the estimated gas and fee of the deployment `tx` of the contract at `address`, and
+    /// the bytes of pubdata it publishes if it's deployed in EraVM.
+    fn print_estimate(&self, tx: &TypedTransaction, address: Address, zk_pubdata: Option<usize>) {
+        let gas = tx.gas().copied().unwrap_or_default().to_alloy();
+        let gas_price = tx.gas_price().unwrap_or_default().to_alloy();
+        let fee = gas.saturating_mul(gas_price);
+        if self.json {
+            let output = json!({
+                "address": address.to_string(),
+                "gas": gas.to_string(),
+                "gasPrice": gas_price.to_string(),
+                "fee": fee.to_string(),
+                "pubdata": zk_pubdata,
+            });
+            println!("{output}");
+            return
+        }
+
+        let units = |value: U256, decimals: u8| {
+            format_units(value, decimals)
+                .map(|value| value.trim_end_matches('0').trim_end_matches('.').to_string())
+                .unwrap_or_else(|_| "[Could not calculate]".to_string())
+        };
+        println!("Address: {address}");
+        println!("Estimated gas: {gas}");
+        println!("Estimated gas price: {} gwei", units(gas_price, 9));
+        println!("Estimated fee: {} ETH", units(fee, 18));
+        if let Some(pubdata) = zk_pubdata {
+            println!("Estimated pubdata: {pubdata} bytes");
+        }
+    }
+
+    /// Verifies the contract deployed at `address`.
+    async fn verify_contract(
+        self,
+        address: Address,
+        constructor_args: Option<String>,
+        chain: u64,
+    ) -> Result<()> {
         println!("Starting contract verification...");
 
         let num_of_optimizations =
@@ -423,6 +595,7 @@ impl CreateArgs {
             address,
             contract: self.contract,
             compiler_version: None,
+            zksolc_version: None,
             constructor_args,
             constructor_args_path: None,
             num_of_optimizations,
@@ -443,6 +616,33 @@ impl CreateArgs {
         verify.run().await
     }
 
+    /// Verifies the contract already deployed at `address` with the constructor arguments of
+    /// these arguments, e.g. if its verification failed when it was created.
+    pub(crate) async fn verify_deployed(
+        mut self,
+        address: Address,
+        compiled: &CompiledProject,
+    ) -> Result<()> {
+        self.contract = compiled.contract_info(&self.contract);
+        let (abi, _, _) = find_contract(&compiled.output, &self.contract)?;
+        let constructor_args = match abi.constructor() {
+            Some(constructor) if !self.constructor_args.is_empty() => {
+                let args = self.parse_constructor_args(constructor, &self.constructor_args)?;
+                Some(hex::encode(constructor.abi_encode_input(&args)?))
+            }
+            _ => None,
+        };
+
+        let chain = match self.chain_id() {
+            Some(chain) => chain,
+            None => {
+                let config = self.eth.try_load_config_emit_warnings()?;
+                utils::get_provider(&config)?.get_chainid().await?.as_u64()
+            }
+        };
+        self.verify_contract(address, constructor_args, chain).await
+    }
+
     /// Parses the given constructor arguments into a vector of `DynSolValue`s, by matching them
     /// against the constructor's input params.
     ///
@@ -560,9 +760,12 @@ where
     /// be sufficiently confirmed (default: 1), it returns a tuple with
     /// the [`Contract`](crate::Contract) struct at the deployed contract's address
     /// and the corresponding [`TransactionReceipt`].
+    ///
+    /// The address is the one of the receipt, or `expected_address` if the receipt has none.
     pub async fn send_with_receipt(
         self,
         signer: Option<WalletSigner>,
+        expected_address: Option<Address>,
     ) -> Result<(Address, TransactionReceipt), ContractError<M>> {
         let pending_tx = match self.zk_factory_deps {
             None => self
@@ -596,9 +799,13 @@ where
             .ok()
             .flatten()
             .ok_or(ContractError::ContractNotDeployed)?;
-        let address = receipt.contract_address.ok_or(ContractError::ContractNotDeployed)?;
+        let address = receipt
+            .contract_address
+            .map(ToAlloy::to_alloy)
+            .or(expected_address)
+            .ok_or(ContractError::ContractNotDeployed)?;
 
-        Ok((address.to_alloy(), receipt))
+        Ok((address, receipt))
     }
 }
 
@@ -754,7 +961,7 @@ where
             tx: tx.into(),
             confs: 1,
             block: BlockNumber::Latest,
-            zk_factory_deps: Some(vec![contract.zk_deployed_bytecode.clone()]),
+            zk_factory_deps: Some(contract.factory_deps()),
             _m: PhantomData,
         })
     }
@@ -0,0 +1,193 @@
+use super::{
+    create::{CompiledProject, CreateArgs},
+    retry::RetryArgs,
+    verify::VerifierArgs,
+};
+use clap::{Parser, ValueHint};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_cli::{
+    opts::{CoreBuildArgs, EthereumOpts, TransactionOpts},
+    utils::{self, LoadConfig},
+};
+use foundry_common::types::ToEthers;
+use std::{collections::BTreeMap, path::PathBuf};
+
+pub mod plan;
+use plan::{resolve_arg, DeployPlan, DeployState, DeployedContract};
+
+/// CLI arguments for `forge deploy`.
+#[derive(Clone, Debug, Parser)]
+pub struct DeployArgs {
+    /// The deployment plan.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PLAN", default_value = "deploy.toml")]
+    plan: PathBuf,
+
+    /// Only deploy to these chains of the plan.
+    #[clap(long, value_name = "CHAIN")]
+    only: Vec<String>,
+
+    /// Print the deployments of the plan without sending any transaction.
+    #[clap(long)]
+    dry_run: bool,
+
+    /// The file recording the deployed contracts.
+    ///
+    /// Defaults to `deploy/<plan>.json` in the broadcast directory.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    state: Option<PathBuf>,
+
+    #[clap(flatten)]
+    opts: CoreBuildArgs,
+
+    #[clap(flatten)]
+    tx: TransactionOpts,
+
+    #[clap(flatten)]
+    eth: EthereumOpts,
+
+    #[clap(flatten)]
+    verifier: VerifierArgs,
+
+    #[clap(flatten)]
+    retry: RetryArgs,
+}
+
+impl DeployArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = self.opts.try_load_config_emit_warnings()?;
+        let plan = DeployPlan::read(&self.plan)?;
+        let steps = plan.steps()?;
+        if let Some(chain) = self.only.iter().find(|chain| !plan.chains.contains_key(*chain)) {
+            eyre::bail!("`{chain}` is not a chain of the plan")
+        }
+
+        let state_path = self.state.clone().unwrap_or_else(|| {
+            let name = self.plan.file_stem().unwrap_or_default().to_string_lossy();
+            config.broadcast.join("deploy").join(format!("{name}.json"))
+        });
+        let mut state = DeployState::read(&state_path)
+            .wrap_err_with(|| format!("failed to read {}", state_path.display()))?;
+
+        // the project is compiled once, with zksolc if any deployed chain is an Era chain
+        let compiled = if self.dry_run {
+            None
+        } else {
+            let zksync = plan.chains.iter().any(|(name, chain)| {
+                chain.zksync && (self.only.is_empty() || self.only.contains(name))
+            });
+            Some(CompiledProject::compile(&self.opts, &config, zksync, self.opts.silent)?)
+        };
+
+        let mut chain_ids = BTreeMap::new();
+        for step in steps {
+            if !self.only.is_empty() && !self.only.iter().any(|chain| chain == step.chain) {
+                continue
+            }
+            let chain = &plan.chains[step.chain];
+            let id = format!("{}/{}", step.chain, step.name);
+
+            let args = step
+                .contract
+                .args
+                .iter()
+                .map(|arg| {
+                    resolve_arg(arg, |name| {
+                        let address = state.get(step.chain, name).map(|d| d.address.to_string());
+                        address.or_else(|| self.dry_run.then(|| format!("<{name}>")))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let deployed = state
+                .get(step.chain, step.name)
+                .filter(|d| d.contract == step.contract.contract && d.args == args)
+                .cloned();
+
+            if self.dry_run {
+                match deployed {
+                    Some(deployed) => println!("{id}: deployed at {}", deployed.address),
+                    None => {
+                        println!("{id}: deploy {}({})", step.contract.contract, args.join(", "))
+                    }
+                }
+                continue
+            }
+
+            let compiled =
+                compiled.as_ref().expect("the project is compiled unless it is a dry run");
+            let mut eth = self.eth.clone();
+            eth.rpc.url = Some(chain.rpc_url.clone());
+            let mut opts = self.opts.clone();
+            opts.compiler.zksync = chain.zksync;
+            let create = CreateArgs::new(
+                step.contract.contract.parse().map_err(|e| eyre::eyre!("{id}: {e}"))?,
+                args.clone(),
+                step.contract.verify,
+                opts,
+                self.tx.clone(),
+                eth.clone(),
+                self.verifier.clone(),
+                self.retry,
+            );
+
+            let chain_id = match chain_ids.get(step.chain) {
+                Some(chain_id) => *chain_id,
+                None => {
+                    let provider = utils::get_provider(&eth.try_load_config_emit_warnings()?)?;
+                    let chain_id = provider.get_chainid().await?.as_u64();
+                    chain_ids.insert(step.chain, chain_id);
+                    chain_id
+                }
+            };
+
+            // skip the contracts that are still deployed with the same arguments
+            if let Some(mut deployed) = deployed.filter(|d| d.chain_id == chain_id) {
+                let provider = utils::get_provider(&eth.try_load_config_emit_warnings()?)?;
+                let code = provider.get_code(deployed.address.to_ethers(), None).await?;
+                if !code.is_empty() {
+                    println!("{id}: already deployed at {}", deployed.address);
+                    if step.contract.verify && !deployed.verified {
+                        create
+                            .verify_deployed(deployed.address, compiled)
+                            .await
+                            .wrap_err_with(|| format!("failed to verify {id}"))?;
+                        deployed.verified = true;
+                        state.insert(step.chain, step.name, deployed);
+                        state.write(&state_path)?;
+                    }
+                    continue
+                }
+            }
+
+            println!("{id}: deploying {}", step.contract.contract);
+            let outcome = create
+                .create_with(compiled)
+                .await
+                .wrap_err_with(|| format!("failed to deploy {id}"))?;
+            let verified = outcome.verification.as_ref().is_some_and(|v| v.is_ok());
+            state.insert(
+                step.chain,
+                step.name,
+                DeployedContract {
+                    contract: step.contract.contract.clone(),
+                    args,
+                    chain_id,
+                    address: outcome.address,
+                    verified,
+                },
+            );
+            state.write(&state_path)?;
+
+            if let Some(Err(err)) = outcome.verification {
+                return Err(err.wrap_err(format!(
+                    "failed to verify {id}, run `forge deploy` again to retry the verification"
+                )))
+            }
+        }
+
+        if !self.dry_run {
+            println!("Deployments recorded in {}", state_path.display());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use alloy_primitives::Address;
+use eyre::{Result, WrapErr};
+use foundry_common::fs;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// A declarative deployment plan, read from a TOML file.
+///
+/// ```toml
+/// [chains.sepolia]
+/// rpc_url = "sepolia"
+///
+/// [chains.era]
+/// rpc_url = "https://sepolia.era.zksync.dev"
+/// zksync = true
+///
+/// [contracts.registry]
+/// contract = "src/Registry.sol:Registry"
+/// verify = true
+///
+/// [contracts.token]
+/// contract = "Token"
+/// args = ["Token", "TKN", "${registry}"]
+/// chains = ["era"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeployPlan {
+    /// The chains to deploy to, by name.
+    #[serde(default)]
+    pub chains: BTreeMap<String, PlanChain>,
+    /// The contracts to deploy, by name.
+    #[serde(default)]
+    pub contracts: BTreeMap<String, PlanContract>,
+}
+
+/// A chain of a deployment plan.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanChain {
+    /// The RPC endpoint, or an alias of the `rpc_endpoints` of the config.
+    pub rpc_url: String,
+    /// Whether this is an Era chain, to which the EraVM bytecode is deployed.
+    #[serde(default)]
+    pub zksync: bool,
+}
+
+/// A contract of a deployment plan.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanContract {
+    /// The contract to deploy, `<path>:<name>` or `<name>`.
+    pub contract: String,
+    /// The constructor arguments. `${name}` is replaced with the address of the contract `name`
+    /// of the plan on the same chain.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The contracts of the plan to deploy first, in addition to the ones referenced in `args`.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// The chains to deploy to. Defaults to all chains of the plan.
+    #[serde(default)]
+    pub chains: Option<Vec<String>>,
+    /// Whether to verify the contract after deploying it.
+    #[serde(default)]
+    pub verify: bool,
+}
+
+impl PlanContract {
+    /// Returns the contracts this contract depends on.
+    pub fn dependencies(&self) -> BTreeSet<&str> {
+        self.depends_on
+            .iter()
+            .map(String::as_str)
+            .chain(self.args.iter().flat_map(|arg| references(arg)))
+            .collect()
+    }
+}
+
+/// The deployment of a contract of a plan to one of its chains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlanStep<'a> {
+    /// The name of the chain.
+    pub chain: &'a str,
+    /// The name of the contract.
+    pub name: &'a str,
+    /// The contract.
+    pub contract: &'a PlanContract,
+}
+
+impl DeployPlan {
+    /// Reads a plan file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let plan = fs::read_to_string(path)?;
+        Self::parse(&plan).wrap_err_with(|| format!("invalid deployment plan {}", path.display()))
+    }
+
+    /// Parses a plan.
+    pub fn parse(plan: &str) -> Result<Self> {
+        Ok(toml::from_str(plan)?)
+    }
+
+    /// Returns the chains `contract` is deployed to.
+    pub fn contract_chains<'a>(&'a self, contract: &'a PlanContract) -> BTreeSet<&'a str> {
+        match &contract.chains {
+            Some(chains) => chains.iter().map(String::as_str).collect(),
+            None => self.chains.keys().map(String::as_str).collect(),
+        }
+    }
+
+    /// Returns the deployments of the plan, grouped by chain and ordered so that every contract is
+    /// deployed after its dependencies.
+    pub fn steps(&self) -> Result<Vec<PlanStep<'_>>> {
+        for (name, contract) in &self.contracts {
+            let chains = self.contract_chains(contract);
+            if let Some(chain) = chains.iter().find(|chain| !self.chains.contains_key(**chain)) {
+                eyre::bail!("`{name}` is deployed to the unknown chain `{chain}`")
+            }
+            for dependency in contract.dependencies() {
+                let Some(dependency_contract) = self.contracts.get(dependency) else {
+                    eyre::bail!("`{name}` depends on the unknown contract `{dependency}`")
+                };
+                let dependency_chains = self.contract_chains(dependency_contract);
+                if let Some(chain) = chains.difference(&dependency_chains).next() {
+                    eyre::bail!(
+                        "`{name}` depends on `{dependency}`, which isn't deployed to `{chain}`"
+                    )
+                }
+            }
+        }
+
+        // topological order, breaking ties by name
+        let mut order = Vec::with_capacity(self.contracts.len());
+        let mut deployed = BTreeSet::new();
+        while order.len() < self.contracts.len() {
+            let Some((name, contract)) = self.contracts.iter().find(|(name, contract)| {
+                !deployed.contains(name.as_str()) &&
+                    contract
+                        .dependencies()
+                        .iter()
+                        .all(|dependency| deployed.contains(dependency))
+            }) else {
+                let cycle = self
+                    .contracts
+                    .keys()
+                    .filter(|name| !deployed.contains(name.as_str()))
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>();
+                eyre::bail!("circular dependencies between {}", cycle.join(", "))
+            };
+            deployed.insert(name.as_str());
+            order.push((name.as_str(), contract));
+        }
+
+        let mut steps = Vec::new();
+        for chain in self.chains.keys() {
+            for (name, contract) in &order {
+                if self.contract_chains(contract).contains(chain.as_str()) {
+                    steps.push(PlanStep { chain, name, contract });
+                }
+            }
+        }
+        Ok(steps)
+    }
+}
+
+/// Returns the names of the contracts referenced with `${name}` in a constructor argument.
+pub fn references(arg: &str) -> impl Iterator<Item = &str> {
+    arg.split("${").skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name.trim())
+}
+
+/// Replaces the references to other contracts in a constructor argument with their addresses.
+pub fn resolve_arg(arg: &str, address: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut resolved = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else { break };
+        let name = rest[start + 2..start + len].trim();
+        let address = address(name)
+            .ok_or_else(|| eyre::eyre!("`{name}` is not deployed, it's referenced in `{arg}`"))?;
+        resolved.push_str(&rest[..start]);
+        resolved.push_str(&address);
+        rest = &rest[start + len + 1..];
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/// The contracts deployed by a plan, which make re-running the plan idempotent.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployState {
+    /// The deployed contracts by chain and name.
+    pub chains: BTreeMap<String, BTreeMap<String, DeployedContract>>,
+}
+
+/// A contract deployed by a plan.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployedContract {
+    /// The contract, as in the plan.
+    pub contract: String,
+    /// The resolved constructor arguments.
+    pub args: Vec<String>,
+    /// The id of the chain the contract was deployed to.
+    pub chain_id: u64,
+    /// The address of the contract.
+    pub address: Address,
+    /// Whether the contract was verified.
+    #[serde(default)]
+    pub verified: bool,
+}
+
+impl DeployState {
+    /// Reads the state file, which may not exist yet.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default())
+        }
+        Ok(fs::read_json_file(path)?)
+    }
+
+    /// Writes the state file.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Returns the deployment of a contract to a chain.
+    pub fn get(&self, chain: &str, name: &str) -> Option<&DeployedContract> {
+        self.chains.get(chain)?.get(name)
+    }
+
+    /// Records the deployment of a contract to a chain.
+    pub fn insert(&mut self, chain: &str, name: &str, deployed: DeployedContract) {
+        self.chains.entry(chain.to_string()).or_default().insert(name.to_string(), deployed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"
+[chains.sepolia]
+rpc_url = "sepolia"
+
+[chains.era]
+rpc_url = "https://sepolia.era.zksync.dev"
+zksync = true
+
+[contracts.token]
+contract = "Token"
+args = ["Token", "${registry}"]
+depends_on = ["oracle"]
+chains = ["era"]
+
+[contracts.registry]
+contract = "src/Registry.sol:Registry"
+verify = true
+
+[contracts.oracle]
+contract = "Oracle"
+"#;
+
+    #[test]
+    fn orders_steps() {
+        let plan = DeployPlan::parse(PLAN).unwrap();
+        assert!(plan.chains["era"].zksync);
+
+        let steps = plan.steps().unwrap();
+        let steps = steps.iter().map(|step| (step.chain, step.name)).collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            [
+                ("era", "oracle"),
+                ("era", "registry"),
+                ("era", "token"),
+                ("sepolia", "oracle"),
+                ("sepolia", "registry"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_plans() {
+        let mut plan = DeployPlan::parse(PLAN).unwrap();
+        plan.contracts.get_mut("registry").unwrap().depends_on.push("token".to_string());
+        let err = plan.steps().unwrap_err().to_string();
+        assert_eq!(err, "`registry` depends on `token`, which isn't deployed to `sepolia`");
+
+        plan.contracts.get_mut("registry").unwrap().chains = Some(vec!["era".to_string()]);
+        let err = plan.steps().unwrap_err().to_string();
+        assert_eq!(err, "circular dependencies between `registry`, `token`");
+
+        plan.contracts.get_mut("oracle").unwrap().chains = Some(vec!["mainnet".to_string()]);
+        assert!(plan.steps().is_err());
+
+        assert!(DeployPlan::parse("[contracts.token]\ncontract = \"Token\"\nargz = []").is_err());
+    }
+
+    #[test]
+    fn resolves_references() {
+        let address = |name: &str| (name == "registry").then(|| "0x1234".to_string());
+        assert_eq!(resolve_arg("${registry}", address).unwrap(), "0x1234");
+        assert_eq!(resolve_arg("[${ registry },0x00]", address).unwrap(), "[0x1234,0x00]");
+        assert_eq!(resolve_arg("plain", address).unwrap(), "plain");
+        assert!(resolve_arg("${token}", address).is_err());
+        assert_eq!(references("[${a},${b}]").collect::<Vec<_>>(), ["a", "b"]);
+    }
+}
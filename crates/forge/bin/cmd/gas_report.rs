@@ -0,0 +1,69 @@
+use super::test;
+use alloy_primitives::U256;
+use clap::{Parser, ValueHint};
+use eyre::{Result, WrapErr};
+use forge::gas_report::{GasReport, GasReportDiff};
+use foundry_cli::utils::STATIC_FUZZ_SEED;
+use foundry_common::fs;
+use std::path::PathBuf;
+use yansi::Paint;
+
+/// CLI arguments for `forge gas-report`.
+#[derive(Clone, Debug, Parser)]
+pub struct GasReportArgs {
+    /// Compare the gas report against a pre-existing report, as written with
+    /// `--gas-report-format json --gas-report-out <PATH>`.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "OLD_REPORT")]
+    diff: Option<PathBuf>,
+
+    /// Only print the changes of more than the given percentage.
+    #[clap(long, default_value_t = 0.0, value_name = "PERCENT", requires = "diff")]
+    threshold: f64,
+
+    /// Exit with code 1 if any cost increased by more than the given percentage.
+    #[clap(long, value_name = "PERCENT", requires = "diff")]
+    tolerance: Option<f64>,
+
+    /// All test arguments are supported
+    #[clap(flatten)]
+    test: test::TestArgs,
+}
+
+impl GasReportArgs {
+    pub async fn run(mut self) -> Result<()> {
+        // Read the old report first, so a wrong path doesn't require running the tests.
+        let old = self
+            .diff
+            .as_ref()
+            .map(|path| {
+                fs::read_json_file::<GasReport>(path)
+                    .wrap_err_with(|| format!("failed to read the gas report {}", path.display()))
+            })
+            .transpose()?;
+
+        // Set fuzz seed so gas reports are deterministic
+        self.test.fuzz_seed = Some(U256::from_be_bytes(STATIC_FUZZ_SEED));
+        self.test.gas_report = true;
+
+        let outcome = self.test.execute_tests().await?;
+        outcome.ensure_ok()?;
+
+        let (Some(old), Some(new)) = (old, &outcome.gas_report) else { return Ok(()) };
+        let diff = GasReportDiff::new(&old, new, self.threshold);
+        println!("\n{diff}");
+
+        if let Some(tolerance) = self.tolerance {
+            let regressions = diff.regressions(tolerance).count();
+            if regressions > 0 {
+                eprintln!(
+                    "{}",
+                    Paint::red(format!(
+                        "{regressions} cost(s) increased by more than {tolerance}%"
+                    ))
+                );
+                std::process::exit(1)
+            }
+        }
+        Ok(())
+    }
+}
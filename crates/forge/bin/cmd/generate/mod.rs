@@ -1,7 +1,8 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueHint};
 use eyre::Result;
+use foundry_cheatcodes_spec::Cheatcodes;
 use foundry_common::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use yansi::Paint;
 
 /// CLI arguments for `forge generate`.
@@ -15,6 +16,9 @@ pub struct GenerateArgs {
 pub enum GenerateSubcommands {
     /// Scaffolds test file for given contract.
     Test(GenerateTestArgs),
+
+    /// Generates the cheatcodes interface, including the zkSync specific cheatcodes.
+    Cheatcodes(GenerateCheatcodesArgs),
 }
 
 #[derive(Debug, Parser)]
@@ -49,6 +53,45 @@ impl GenerateTestArgs {
     }
 }
 
+#[derive(Debug, Parser)]
+pub struct GenerateCheatcodesArgs {
+    /// Generate Rust bindings instead of a Solidity interface.
+    #[clap(long)]
+    pub rust: bool,
+
+    /// The name of the generated interface.
+    #[clap(long, default_value = "Vm", value_name = "NAME")]
+    pub name: String,
+
+    /// The file to write the generated code to. Prints to stdout if not specified.
+    #[clap(long, short, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+}
+
+impl GenerateCheatcodesArgs {
+    pub fn run(self) -> Result<()> {
+        let cheatcodes = Cheatcodes::new();
+        let content = if self.rust {
+            cheatcodes.rust_bindings(&self.name)
+        } else {
+            cheatcodes.sol_interface(&self.name)
+        };
+
+        let Some(out) = self.out else {
+            print!("{content}");
+            return Ok(());
+        };
+
+        if let Some(parent) = out.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&out, content)?;
+
+        println!("{} cheatcodes interface: {}", Paint::green("Generated"), out.display());
+        Ok(())
+    }
+}
+
 /// Utility function to convert an identifier to pascal or camel case.
 fn format_identifier(input: &str, is_pascal_case: bool) -> String {
     let mut result = String::new();
@@ -16,6 +16,9 @@ pub struct InitArgs {
     root: PathBuf,
 
     /// The template to start from.
+    ///
+    /// Either a repository, fetched from GitHub unless it's a URL, or one of the built-in zkSync
+    /// templates: `zksync-aa`, `zksync-paymaster` and `zksync-bridge`.
     #[clap(long, short)]
     template: Option<String>,
 
@@ -25,16 +28,22 @@ pub struct InitArgs {
     branch: Option<String>,
 
     /// Do not install dependencies from the network.
-    #[clap(long, conflicts_with = "template", visible_alias = "no-deps")]
+    ///
+    /// Can't be used with a repository template.
+    #[clap(long, visible_alias = "no-deps")]
     offline: bool,
 
     /// Create the project even if the specified root directory is not empty.
-    #[clap(long, conflicts_with = "template")]
+    ///
+    /// Can't be used with a repository template.
+    #[clap(long)]
     force: bool,
 
     /// Create a .vscode/settings.json file with Solidity settings, and generate a remappings.txt
     /// file.
-    #[clap(long, conflicts_with = "template")]
+    ///
+    /// Can't be used with a repository template.
+    #[clap(long)]
     vscode: bool,
 
     #[clap(flatten)]
@@ -53,10 +62,21 @@ impl InitArgs {
         let root = dunce::canonicalize(root)?;
         let git = Git::new(&root).quiet(quiet).shallow(shallow);
 
+        // the built-in templates are generated like the default project, from embedded files
+        let zk_template = template.as_deref().and_then(ZkTemplate::from_name);
+        if zk_template.is_some() && branch.is_some() {
+            eyre::bail!("`--branch` can't be used with a built-in template");
+        }
+
         // if a template is provided, then this command initializes a git repo,
         // fetches the template repo, and resets the git history to the head of the fetched
         // repo with no other history
-        if let Some(template) = template {
+        if let Some(template) = template.filter(|_| zk_template.is_none()) {
+            if offline || force || vscode {
+                eyre::bail!(
+                    "`--offline`, `--force` and `--vscode` can't be used with a repository template"
+                );
+            }
             let template = if template.contains("://") {
                 template
             } else {
@@ -116,18 +136,31 @@ impl InitArgs {
             let script = root.join("script");
             fs::create_dir_all(&script)?;
 
-            // write the contract file
-            let contract_path = src.join("Counter.sol");
-            fs::write(contract_path, include_str!("../../assets/CounterTemplate.sol"))?;
-            // write the tests
-            let contract_path = test.join("Counter.t.sol");
-            fs::write(contract_path, include_str!("../../assets/CounterTemplate.t.sol"))?;
-            // write the script
-            let contract_path = script.join("Counter.s.sol");
-            fs::write(contract_path, include_str!("../../assets/CounterTemplate.s.sol"))?;
-            // Write the default README file
-            let readme_path = root.join("README.md");
-            fs::write(readme_path, include_str!("../../assets/README.md"))?;
+            if let Some(zk_template) = zk_template {
+                // write the files of the template, and its foundry.toml if it doesn't exist
+                // already
+                for (path, content) in zk_template.files() {
+                    let dest = root.join(path);
+                    if path == Config::FILE_NAME && dest.exists() {
+                        continue
+                    }
+                    fs::create_dir_all(dest.parent().unwrap())?;
+                    fs::write(dest, content)?;
+                }
+            } else {
+                // write the contract file
+                let contract_path = src.join("Counter.sol");
+                fs::write(contract_path, include_str!("../../assets/CounterTemplate.sol"))?;
+                // write the tests
+                let contract_path = test.join("Counter.t.sol");
+                fs::write(contract_path, include_str!("../../assets/CounterTemplate.t.sol"))?;
+                // write the script
+                let contract_path = script.join("Counter.s.sol");
+                fs::write(contract_path, include_str!("../../assets/CounterTemplate.s.sol"))?;
+                // Write the default README file
+                let readme_path = root.join("README.md");
+                fs::write(readme_path, include_str!("../../assets/README.md"))?;
+            }
 
             // write foundry.toml, if it doesn't exist already
             let dest = root.join(Config::FILE_NAME);
@@ -164,6 +197,73 @@ impl InitArgs {
     }
 }
 
+/// The built-in templates of zkSync Era use cases, embedded in the binary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ZkTemplate {
+    /// A custom account, `zksync-aa`.
+    Account,
+    /// A paymaster, `zksync-paymaster`.
+    Paymaster,
+    /// An app messaging between L1 and L2, `zksync-bridge`.
+    Bridge,
+}
+
+impl ZkTemplate {
+    /// Returns the built-in template named `name`, if any.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "zksync-aa" => Some(Self::Account),
+            "zksync-paymaster" => Some(Self::Paymaster),
+            "zksync-bridge" => Some(Self::Bridge),
+            _ => None,
+        }
+    }
+
+    /// Returns the files of the template, by path relative to the project root.
+    fn files(self) -> Vec<(&'static str, &'static str)> {
+        let mut files = vec![
+            ("src/interfaces/IZkSync.sol", include_str!("../../assets/zksync/IZkSync.sol")),
+            ("test/utils/ZkVm.sol", include_str!("../../assets/zksync/ZkVm.sol")),
+        ];
+        files.extend(match self {
+            Self::Account => [
+                ("foundry.toml", include_str!("../../assets/zksync/aa/foundry.toml")),
+                ("README.md", include_str!("../../assets/zksync/aa/README.md")),
+                ("src/Counter.sol", include_str!("../../assets/CounterTemplate.sol")),
+                ("src/ZkAccount.sol", include_str!("../../assets/zksync/aa/ZkAccount.sol")),
+                ("test/ZkAccount.t.sol", include_str!("../../assets/zksync/aa/ZkAccount.t.sol")),
+                ("script/ZkAccount.s.sol", include_str!("../../assets/zksync/aa/ZkAccount.s.sol")),
+            ],
+            Self::Paymaster => [
+                ("foundry.toml", include_str!("../../assets/zksync/paymaster/foundry.toml")),
+                ("README.md", include_str!("../../assets/zksync/paymaster/README.md")),
+                ("src/Counter.sol", include_str!("../../assets/CounterTemplate.sol")),
+                (
+                    "src/ZkPaymaster.sol",
+                    include_str!("../../assets/zksync/paymaster/ZkPaymaster.sol"),
+                ),
+                (
+                    "test/ZkPaymaster.t.sol",
+                    include_str!("../../assets/zksync/paymaster/ZkPaymaster.t.sol"),
+                ),
+                (
+                    "script/ZkPaymaster.s.sol",
+                    include_str!("../../assets/zksync/paymaster/ZkPaymaster.s.sol"),
+                ),
+            ],
+            Self::Bridge => [
+                ("foundry.toml", include_str!("../../assets/zksync/bridge/foundry.toml")),
+                ("README.md", include_str!("../../assets/zksync/bridge/README.md")),
+                ("src/L1Greeter.sol", include_str!("../../assets/zksync/bridge/L1Greeter.sol")),
+                ("src/L2Greeter.sol", include_str!("../../assets/zksync/bridge/L2Greeter.sol")),
+                ("test/Greeter.t.sol", include_str!("../../assets/zksync/bridge/Greeter.t.sol")),
+                ("script/Greeter.s.sol", include_str!("../../assets/zksync/bridge/Greeter.s.sol")),
+            ],
+        });
+        files
+    }
+}
+
 /// Returns the commit hash of the project if it exists
 pub fn get_commit_hash(root: &Path) -> Option<String> {
     Git::new(root).commit_hash(true, "HEAD").ok()
@@ -40,15 +40,19 @@
 //! ```
 
 pub mod bind;
+pub mod broadcast_queue;
 pub mod build;
 pub mod cache;
+pub mod callgraph;
 pub mod config;
 pub mod coverage;
 pub mod create;
 pub mod debug;
+pub mod deploy;
 pub mod doc;
 pub mod flatten;
 pub mod fmt;
+pub mod gas_report;
 pub mod geiger;
 pub mod generate;
 pub mod init;
@@ -61,7 +65,12 @@ pub mod script;
 pub mod selectors;
 pub mod snapshot;
 pub mod test;
+pub mod trace_diff;
 pub mod tree;
 pub mod update;
+pub mod upgrade_sim;
 pub mod verify;
+pub mod verify_attestation;
 pub mod watch;
+pub mod whatis;
+pub mod zk;
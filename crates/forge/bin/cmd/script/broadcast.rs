@@ -1,6 +1,7 @@
 use super::{
     multi::MultiChainSequence,
-    providers::ProvidersManager,
+    providers::{BaseToken, ProviderInfo, ProvidersManager},
+    queue::{BroadcastQueue, QueueEvent, QueueSlot},
     receipts::clear_pendings,
     sequence::ScriptSequence,
     transaction::{TransactionWithMetadata, ZkTransaction},
@@ -29,18 +30,19 @@ use foundry_common::{
 use foundry_compilers::{artifacts::Libraries, ArtifactId};
 use foundry_config::Config;
 use foundry_wallets::WalletSigner;
-use foundry_zksync_compiler::DualCompiledContract;
+use foundry_zksync_compiler::DualCompiledContracts;
+use foundry_zksync_core::{pubdata, ZkTransactionMetadata};
 use futures::StreamExt;
 use std::{
     cmp::min,
     collections::{HashMap, HashSet, VecDeque},
-    sync::Arc,
-};
-use zksync_web3_rs::{
-    eip712::{Eip712Meta, Eip712Transaction, Eip712TransactionRequest},
-    zks_utils::EIP712_TX_TYPE,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
+/// The interval the receipts of zkSync Era transactions are polled at.
+const ZK_RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 impl ScriptArgs {
     /// Sends the transactions which haven't been broadcasted yet.
     pub async fn send_transactions(
@@ -49,7 +51,13 @@ impl ScriptArgs {
         fork_url: &str,
         signers: &HashMap<Address, WalletSigner>,
     ) -> Result<()> {
-        let provider = Arc::new(try_get_http_provider(fork_url)?);
+        let mut provider = try_get_http_provider(fork_url)?;
+        if deployment_sequence.transactions.iter().any(|tx| tx.zk.is_some()) {
+            // zkSync Era chains seal an L2 block about every second, so the receipts are polled
+            // more often than the blocks of the L1 the chain defaults to
+            provider = provider.interval(ZK_RECEIPT_POLL_INTERVAL);
+        }
+        let provider = Arc::new(provider);
         let already_broadcasted = deployment_sequence.receipts.len();
 
         if already_broadcasted < deployment_sequence.transactions.len() {
@@ -167,6 +175,23 @@ impl ScriptArgs {
                 })
                 .collect::<Result<Vec<_>>>()?;
 
+            // Record the queue before sending anything, so a crash doesn't lose track of
+            // transactions which were signed or sent but not yet saved to the broadcast file.
+            let queue_path = deployment_sequence.queue_path();
+            let mut queue = if self.resume {
+                BroadcastQueue::open(queue_path)?
+            } else {
+                BroadcastQueue::create(queue_path)?
+            };
+            for (i, (tx, zk, _, _)) in sequence.iter().enumerate() {
+                queue.record(QueueEvent::Queued {
+                    index: already_broadcasted + i,
+                    transaction: tx.clone(),
+                    zk: zk.clone(),
+                })?;
+            }
+            let queue = Mutex::new(queue);
+
             let pb = init_progress!(deployment_sequence.transactions, "txes");
 
             // We send transactions and wait for receipts in batches of 100, since some networks
@@ -183,7 +208,11 @@ impl ScriptArgs {
                     batch_number * batch_size,
                     batch_number * batch_size + min(batch_size, batch.len()) - 1
                 ))?;
-                for (tx, zk, kind, is_fixed_gas_limit) in batch.into_iter() {
+                for (i, (tx, zk, kind, is_fixed_gas_limit)) in batch.into_iter().enumerate() {
+                    let slot = QueueSlot {
+                        queue: &queue,
+                        index: already_broadcasted + batch_number * batch_size + i,
+                    };
                     let tx_hash = self.send_transaction(
                         provider.clone(),
                         tx,
@@ -192,6 +221,7 @@ impl ScriptArgs {
                         sequential_broadcast,
                         fork_url,
                         is_fixed_gas_limit,
+                        slot,
                     );
 
                     if sequential_broadcast {
@@ -203,6 +233,7 @@ impl ScriptArgs {
 
                         clear_pendings(provider.clone(), deployment_sequence, Some(vec![tx_hash]))
                             .await?;
+                        queue.lock().unwrap().record_receipts(deployment_sequence)?;
                     } else {
                         pending_transactions.push(tx_hash);
                     }
@@ -225,12 +256,16 @@ impl ScriptArgs {
                     if !sequential_broadcast {
                         shell::println("##\nWaiting for receipts.")?;
                         clear_pendings(provider.clone(), deployment_sequence, None).await?;
+                        queue.lock().unwrap().record_receipts(deployment_sequence)?;
                     }
                 }
 
                 // Checkpoint save
                 deployment_sequence.save()?;
             }
+
+            // Everything made it into the broadcast file, the queue isn't needed anymore.
+            queue.into_inner().unwrap().remove()?;
         }
 
         shell::println("\n\n==========================")?;
@@ -244,13 +279,13 @@ impl ScriptArgs {
                 (acc.0 + gas_used, acc.1 + gas_price, acc.2 + gas_used * gas_price)
             },
         );
-        let paid = format_units(total_paid, 18).unwrap_or_else(|_| "N/A".to_string());
+        let base_token = BaseToken::detect(&provider).await;
+        let paid = base_token.format_units(total_paid).unwrap_or_else(|_| "N/A".to_string());
         let avg_gas_price =
             format_units(total_gas_price / U256::from(deployment_sequence.receipts.len()), 9)
                 .unwrap_or_else(|_| "N/A".to_string());
         shell::println(format!(
-            "Total Paid: {} ETH ({} gas * avg {} gwei)",
-            paid.trim_end_matches('0'),
+            "Total Paid: {paid} {base_token} ({} gas * avg {} gwei)",
             total_gas,
             avg_gas_price.trim_end_matches('0').trim_end_matches('.')
         ))?;
@@ -268,6 +303,7 @@ impl ScriptArgs {
         sequential_broadcast: bool,
         fork_url: &str,
         is_fixed_gas_limit: bool,
+        slot: QueueSlot<'_>,
     ) -> Result<TxHash> {
         let from = tx.from().expect("no sender");
 
@@ -288,6 +324,14 @@ impl ScriptArgs {
             SendTransactionKind::Unlocked(addr) => {
                 debug!("sending transaction from unlocked account {:?}: {:?}", addr, tx);
 
+                // `eth_sendTransaction` can't carry the custom fields of EIP-712 transactions
+                if zk.is_some() {
+                    bail!(
+                        "zkSync transactions are signed locally as EIP-712 transactions, \
+                         sending them from the unlocked account {addr} isn't supported"
+                    );
+                }
+
                 // Chains which use `eth_estimateGas` are being sent sequentially and require their
                 // gas to be re-estimated right before broadcasting.
                 if !is_fixed_gas_limit &&
@@ -299,10 +343,14 @@ impl ScriptArgs {
 
                 // Submit the transaction
                 let pending = provider.send_transaction(tx, None).await?;
+                let hash = pending.tx_hash().to_alloy();
+                slot.record(|index| QueueEvent::Sent { index, hash })?;
 
-                Ok(pending.tx_hash().to_alloy())
+                Ok(hash)
+            }
+            SendTransactionKind::Raw(signer) => {
+                self.broadcast(provider, signer, tx, zk, is_fixed_gas_limit, slot).await
             }
-            SendTransactionKind::Raw(signer) => self.broadcast(provider, signer, tx, zk).await,
         }
     }
 
@@ -317,7 +365,7 @@ impl ScriptArgs {
         mut script_config: ScriptConfig,
         verify: VerifyBundle,
         signers: &HashMap<Address, WalletSigner>,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<()> {
         if let Some(txs) = result.transactions.take() {
             script_config.collect_rpcs(&txs);
@@ -338,6 +386,15 @@ impl ScriptArgs {
                     )
                     .await?;
 
+                if let Some(safe) = self.safe {
+                    trace!(target: "script", "writing safe bundles");
+
+                    for sequence in &deployments {
+                        self.send_to_safe(sequence, safe, signers).await?;
+                    }
+                    return Ok(())
+                }
+
                 if script_config.has_multiple_rpcs() {
                     trace!(target: "script", "broadcasting multi chain deployment");
 
@@ -419,7 +476,7 @@ impl ScriptArgs {
         script_config: &mut ScriptConfig,
         decoder: &CallTraceDecoder,
         known_contracts: &ContractsByArtifact,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<Vec<ScriptSequence>> {
         if !txs.is_empty() {
             let gas_filled_txs = self
@@ -458,7 +515,7 @@ impl ScriptArgs {
         script_config: &ScriptConfig,
         decoder: &CallTraceDecoder,
         known_contracts: &ContractsByArtifact,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<VecDeque<TransactionWithMetadata>> {
         let gas_filled_txs = if self.skip_simulation {
             shell::println("\nSKIPPING ON CHAIN SIMULATION.")?;
@@ -466,7 +523,7 @@ impl ScriptArgs {
                 .map(|btx| {
                     let mut tx = TransactionWithMetadata::from_zk_tx_request(
                         btx.transaction,
-                        btx.zk_tx.map(|zk_tx| ZkTransaction { factory_deps: zk_tx.factory_deps }),
+                        btx.zk_tx.map(ZkTransaction::from),
                     );
                     tx.rpc = btx.rpc;
                     tx
@@ -503,6 +560,8 @@ impl ScriptArgs {
         let is_multi_deployment = transactions.iter().any(|tx| &tx.rpc != last_rpc);
 
         let mut total_gas_per_rpc: HashMap<RpcUrl, U256> = HashMap::new();
+        let mut zk_pubdata_per_rpc: HashMap<RpcUrl, usize> = HashMap::new();
+        let mut funds_per_rpc: HashMap<RpcUrl, HashMap<Address, RequiredFunds>> = HashMap::new();
 
         // Batches sequence of transactions from different rpcs.
         let mut new_sequence = VecDeque::new();
@@ -535,6 +594,7 @@ impl ScriptArgs {
             tx.transaction.set_chain_id(provider_info.chain);
 
             if !self.skip_simulation {
+                let zk = tx.zk.clone();
                 let typed_tx = tx.typed_tx_mut();
 
                 if has_different_gas_calc(provider_info.chain) {
@@ -560,8 +620,52 @@ impl ScriptArgs {
                     }
                 }
 
+                // The fee of a zkSync transaction, including publishing its factory dependencies,
+                // is estimated by the node. Like above, it fails for the transactions depending on
+                // previous ones, which fall back to the simulated gas and the gas of publishing
+                // their factory dependencies.
+                let zk_fee = match &zk {
+                    Some(zk) => self
+                        .estimate_zk_fee(typed_tx, zk.clone().into(), provider_info)
+                        .await
+                        .map_err(|err| trace!("zkSync fee estimation failed: {err}"))
+                        .ok(),
+                    None => None,
+                };
+
+                if let Some(zk) = &zk {
+                    *zk_pubdata_per_rpc.entry(tx_rpc.clone()).or_default() +=
+                        pubdata::factory_deps_pubdata(&zk.factory_deps);
+                }
+
+                let gas = match (zk_fee, &zk) {
+                    (Some((gas, _)), _) => gas,
+                    (None, Some(zk)) => {
+                        let pubdata = pubdata::factory_deps_pubdata(&zk.factory_deps) as u64;
+                        let pubdata_gas = pubdata.saturating_mul(pubdata::DEFAULT_GAS_PER_PUBDATA);
+                        (*typed_tx.gas().expect("gas is set")).to_alloy() + U256::from(pubdata_gas)
+                    }
+                    (None, None) => (*typed_tx.gas().expect("gas is set")).to_alloy(),
+                };
                 let total_gas = total_gas_per_rpc.entry(tx_rpc.clone()).or_insert(U256::ZERO);
-                *total_gas += (*typed_tx.gas().expect("gas is set")).to_alloy();
+                *total_gas += gas;
+
+                if let Some(from) = typed_tx.from() {
+                    let value = typed_tx.value().map(|value| value.to_alloy()).unwrap_or_default();
+                    let funds = funds_per_rpc
+                        .entry(tx_rpc.clone())
+                        .or_default()
+                        .entry(from.to_alloy())
+                        .or_default();
+                    funds.value += value;
+                    // The fees of a transaction with a paymaster are paid by the paymaster
+                    if zk.as_ref().map_or(true, |zk| zk.paymaster.is_none()) {
+                        match zk_fee {
+                            Some((_, fee)) => funds.zk_fees += fee,
+                            None => funds.gas += gas,
+                        }
+                    }
+                }
             }
 
             new_sequence.push_back(tx);
@@ -616,18 +720,114 @@ impl ScriptArgs {
                         .trim_end_matches('.')
                 ))?;
                 shell::println(format!("\nEstimated total gas used for script: {total_gas}"))?;
+                let zk_pubdata = zk_pubdata_per_rpc.get(&rpc).copied().unwrap_or_default();
+                if zk_pubdata > 0 {
+                    shell::println(format!(
+                        "\nEstimated pubdata of the factory dependencies: {zk_pubdata} bytes"
+                    ))?;
+                }
                 shell::println(format!(
-                    "\nEstimated amount required: {} ETH",
-                    format_units(total_gas.saturating_mul(per_gas), 18)
-                        .unwrap_or_else(|_| "[Could not calculate]".to_string())
-                        .trim_end_matches('0')
+                    "\nEstimated amount required: {} {}",
+                    provider_info
+                        .base_token
+                        .format_units(total_gas.saturating_mul(per_gas))
+                        .unwrap_or_else(|_| "[Could not calculate]".to_string()),
+                    provider_info.base_token
                 ))?;
+                if provider_info.base_token.is_custom() {
+                    shell::println(
+                        "\nValues and fees are paid in the chain's base token, not ETH.",
+                    )?;
+                }
                 shell::println("\n==========================")?;
+
+                // The transactions of a Safe bundle are paid for by the owner executing them.
+                if let (Some(funds), None) = (funds_per_rpc.get(&rpc), self.safe) {
+                    self.check_sender_funds(provider_info, funds, per_gas).await?;
+                }
             }
         }
         Ok(deployments)
     }
 
+    /// Ensures every sender can cover the value it transfers and the estimated fees on top,
+    /// failing with the shortfall of every sender which can't.
+    ///
+    /// On zkSync Era chains, the fees of the zkSync transactions are estimated with
+    /// `zks_estimateFee`, which accounts for publishing their factory dependencies.
+    ///
+    /// Both are denominated in the chain's base token, which is queried with `eth_getBalance` on
+    /// zkSync Era chains regardless of whether it's ETH or a custom token.
+    async fn check_sender_funds(
+        &self,
+        provider_info: &ProviderInfo,
+        funds: &HashMap<Address, RequiredFunds>,
+        gas_price: U256,
+    ) -> Result<()> {
+        let token = provider_info.base_token;
+        let units = |amount: U256| token.format_units(amount).unwrap_or_default();
+        let mut senders = funds.iter().collect::<Vec<_>>();
+        senders.sort_by_key(|(sender, _)| **sender);
+
+        let mut shortfalls = vec![];
+        for (sender, required) in senders {
+            let balance = provider_info
+                .provider
+                .get_balance(sender.to_ethers(), None)
+                .await
+                .wrap_err_with(|| format!("Failed to get the balance of {sender}"))?
+                .to_alloy();
+
+            let shortfall = match required.shortfall(balance, gas_price) {
+                None => continue,
+                Some(Shortfall::Transfers) => format!(
+                    "{sender} transfers {} {token} but only has {} {token}",
+                    units(required.value),
+                    units(balance),
+                ),
+                Some(Shortfall::Fees(fees)) => format!(
+                    "{sender} can't cover the estimated fees of {} {token}, it only has {} {token} \
+                     left after transfers",
+                    units(fees),
+                    units(balance - required.value),
+                ),
+            };
+            shortfalls.push(shortfall);
+        }
+
+        if !shortfalls.is_empty() {
+            bail!(
+                "{} sender(s) have insufficient funds on chain {}:\n{}",
+                shortfalls.len(),
+                provider_info.chain,
+                shortfalls.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the gas limit and the fee of a zkSync transaction estimated with
+    /// `zks_estimateFee`.
+    async fn estimate_zk_fee(
+        &self,
+        tx: &TypedTransaction,
+        zk_tx: ZkTransactionMetadata,
+        provider_info: &ProviderInfo,
+    ) -> Result<(U256, U256)> {
+        let mut tx = tx.clone();
+        if tx.gas_price().is_none() {
+            tx.set_gas_price(provider_info.gas_price()?.to_ethers());
+        }
+        let request = foundry_zksync_core::new_eip712_request(&tx, &zk_tx)?;
+        let fee =
+            foundry_zksync_core::estimate_eip712_fee(&request, provider_info.provider.as_ref())
+                .await?;
+        let gas =
+            fee.gas_limit.to_alloy() * U256::from(self.gas_estimate_multiplier) / U256::from(100);
+        let gas_price = self.with_gas_price.unwrap_or_else(|| fee.max_fee_per_gas.to_alloy());
+        Ok((gas, gas.saturating_mul(gas_price)))
+    }
+
     /// Uses the signer to submit a transaction to the network. If it fails, it tries to retrieve
     /// the transaction hash that can be used on a later run with `--resume`.
     async fn broadcast(
@@ -636,67 +836,94 @@ impl ScriptArgs {
         signer: &WalletSigner,
         mut legacy_or_1559: TypedTransaction,
         zk: Option<ZkTransaction>,
+        is_fixed_gas_limit: bool,
+        slot: QueueSlot<'_>,
     ) -> Result<TxHash> {
         debug!("sending transaction: {:?}", legacy_or_1559);
 
-        // Chains which use `eth_estimateGas` are being sent sequentially and require their gas
-        // to be re-estimated right before broadcasting.
-        if has_different_gas_calc(signer.chain_id()) || self.skip_simulation {
-            // if already set, some RPC endpoints might simply return the gas value that is
-            // already set in the request and omit the estimate altogether, so
-            // we remove it here
-            let _ = legacy_or_1559.gas_mut().take();
-
-            self.estimate_gas(&mut legacy_or_1559, &provider).await?;
-        }
-
-        let signed_tx = if let Some(zk) = zk {
-            let custom_data = Eip712Meta::new().factory_deps(zk.factory_deps);
-
-            let mut deploy_request = Eip712TransactionRequest::new()
-                .r#type(EIP712_TX_TYPE)
-                .from(*legacy_or_1559.from().unwrap())
-                .to(*legacy_or_1559.to().and_then(|to| to.as_address()).unwrap())
-                .chain_id(legacy_or_1559.chain_id().unwrap().as_u64())
-                .nonce(legacy_or_1559.nonce().unwrap())
-                .gas_price(legacy_or_1559.gas_price().unwrap())
-                .max_fee_per_gas(legacy_or_1559.max_cost().unwrap())
-                .data(legacy_or_1559.data().cloned().unwrap())
-                .custom_data(custom_data);
-
-            let gas_price = provider.get_gas_price().await?;
-            let fee: zksync_web3_rs::zks_provider::types::Fee =
-                provider.request("zks_estimateFee", [deploy_request.clone()]).await.unwrap();
-            deploy_request = deploy_request
-                .gas_limit(fee.gas_limit)
-                .max_fee_per_gas(fee.max_fee_per_gas)
-                .max_priority_fee_per_gas(fee.max_priority_fee_per_gas)
-                .gas_price(gas_price);
-
-            let signable: Eip712Transaction =
-                deploy_request.clone().try_into().expect("converting deploy request");
-            debug!("sending transaction: {:?}", signable);
-
-            let signature =
-                signer.sign_typed_data(&signable).await.wrap_err("Failed to sign typed data")?;
-
-            let encoded_rlp =
-                &*deploy_request.rlp_signed(signature).expect("able to rlp encode deploy request");
-            [&[EIP712_TX_TYPE], encoded_rlp].concat().into()
+        let (signed_tx, gas_per_pubdata) = if let Some(zk) = zk {
+            let (signed_tx, gas_per_pubdata) = self
+                .sign_zk_transaction(
+                    &provider,
+                    signer,
+                    &legacy_or_1559,
+                    zk.into(),
+                    is_fixed_gas_limit,
+                )
+                .await?;
+            (signed_tx, Some(gas_per_pubdata))
         } else {
+            // Chains which use `eth_estimateGas` are being sent sequentially and require their
+            // gas to be re-estimated right before broadcasting.
+            if has_different_gas_calc(signer.chain_id()) || self.skip_simulation {
+                // if already set, some RPC endpoints might simply return the gas value that is
+                // already set in the request and omit the estimate altogether, so
+                // we remove it here
+                let _ = legacy_or_1559.gas_mut().take();
+
+                self.estimate_gas(&mut legacy_or_1559, &provider).await?;
+            }
+
             // Signing manually so we skip `fill_transaction` and its `eth_createAccessList`
             // request.
             let signature = signer
                 .sign_transaction(&legacy_or_1559)
                 .await
                 .wrap_err("Failed to sign transaction")?;
-            legacy_or_1559.rlp_signed(&signature)
+            (legacy_or_1559.rlp_signed(&signature), None)
         };
 
+        // Journal the signed transaction, so it can be resubmitted as is if we crash from here on
+        let raw = signed_tx.clone().to_alloy();
+        slot.record(|index| QueueEvent::Signed { index, raw, gas_per_pubdata })?;
+
         // Submit the raw transaction
         let pending = provider.send_raw_transaction(signed_tx).await?;
+        let hash = pending.tx_hash().to_alloy();
+        slot.record(|index| QueueEvent::Sent { index, hash })?;
 
-        Ok(pending.tx_hash().to_alloy())
+        Ok(hash)
+    }
+
+    /// Signs the EIP-712 (type `0x71`) transaction of a zkSync Era chain, with the factory deps
+    /// and the paymaster of `zk_tx` as its custom fields.
+    ///
+    /// The gas limit, fees and gas per pubdata are estimated with `zks_estimateFee`, which
+    /// accounts for the factory deps to publish and the validation of the paymaster. The gas
+    /// limit is scaled by `--gas-estimate-multiplier`, unless fixed by the script, and
+    /// `--with-gas-price` and `--priority-gas-price` override the estimated fees.
+    ///
+    /// Returns the raw signed transaction, and the gas per pubdata it was signed with.
+    async fn sign_zk_transaction(
+        &self,
+        provider: &RetryProvider,
+        signer: &WalletSigner,
+        legacy_or_1559: &TypedTransaction,
+        zk_tx: ZkTransactionMetadata,
+        is_fixed_gas_limit: bool,
+    ) -> Result<(ethers_core::types::Bytes, U256)> {
+        let request = foundry_zksync_core::new_eip712_request(legacy_or_1559, &zk_tx)?;
+        let mut fee = foundry_zksync_core::estimate_eip712_fee(&request, provider)
+            .await
+            .wrap_err("Failed to estimate the fee of the zkSync transaction")?;
+        fee.gas_limit = match legacy_or_1559.gas() {
+            Some(gas) if is_fixed_gas_limit => *gas,
+            _ => fee.gas_limit * self.gas_estimate_multiplier / 100,
+        };
+        if let Some(gas_price) = self.with_gas_price {
+            fee.max_fee_per_gas = gas_price.to_ethers();
+        }
+        if let Some(priority_gas_price) = self.priority_gas_price {
+            fee.max_priority_fee_per_gas = priority_gas_price.to_ethers();
+        }
+        fee.max_priority_fee_per_gas = fee.max_priority_fee_per_gas.min(fee.max_fee_per_gas);
+
+        let request = foundry_zksync_core::set_eip712_fee(request, &zk_tx, &fee);
+        debug!("sending zkSync transaction: {:?}", request);
+        let signed_tx = foundry_zksync_core::sign_eip712_request(request, signer)
+            .await
+            .wrap_err("Failed to sign typed data")?;
+        Ok((signed_tx.to_ethers(), fee.gas_per_pubdata_limit.to_alloy()))
     }
 
     async fn estimate_gas<T>(&self, tx: &mut TypedTransaction, provider: &Provider<T>) -> Result<()>
@@ -719,6 +946,46 @@ impl ScriptArgs {
     }
 }
 
+/// The funds a sender needs on a chain, in the chain's base token.
+#[derive(Clone, Copy, Debug, Default)]
+struct RequiredFunds {
+    /// The total value transferred by the sender.
+    value: U256,
+    /// The total gas estimated for the sender's transactions, priced at the gas price of the
+    /// chain.
+    gas: U256,
+    /// The total fees of the sender's zkSync transactions estimated by the node.
+    zk_fees: U256,
+}
+
+impl RequiredFunds {
+    /// Returns the fees of the sender's transactions at `gas_price`.
+    fn fees(&self, gas_price: U256) -> U256 {
+        self.gas.saturating_mul(gas_price).saturating_add(self.zk_fees)
+    }
+
+    /// Returns what a sender with `balance` can't cover, if anything.
+    fn shortfall(&self, balance: U256, gas_price: U256) -> Option<Shortfall> {
+        let fees = self.fees(gas_price);
+        if balance < self.value {
+            Some(Shortfall::Transfers)
+        } else if balance < self.value.saturating_add(fees) {
+            Some(Shortfall::Fees(fees))
+        } else {
+            None
+        }
+    }
+}
+
+/// What a sender can't cover with its balance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Shortfall {
+    /// The value it transfers.
+    Transfers,
+    /// The estimated fees on top of the transfers.
+    Fees(U256),
+}
+
 /// How to send a single transaction
 #[derive(Clone)]
 enum SendTransactionKind<'a> {
@@ -764,3 +1031,168 @@ impl SendTransactionsKind<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{hex, Bytes};
+    use clap::Parser;
+    use ethers_core::types::Eip1559TransactionRequest;
+    use foundry_common::provider::ethers::get_http_provider;
+    use foundry_zksync_core::vm::ZkPaymasterData;
+    use serde_json::Value;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+    use zksync_web3_rs::zks_provider::types::Fee;
+
+    /// Serves `zks_estimateFee` with `fee`, recording the requests it estimates.
+    fn mock_fee_estimator(fee: &Fee) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (fee, estimated) = (serde_json::to_value(fee).unwrap(), requests.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(request["method"], "zks_estimateFee", "{request}");
+                estimated.lock().unwrap().push(request["params"][0].clone());
+
+                let response = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": fee,
+                })
+                .to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn signs_zk_transactions_with_the_estimated_fee() {
+        let estimate = Fee {
+            gas_limit: 1_000_000.into(),
+            gas_per_pubdata_limit: 50_000.into(),
+            max_fee_per_gas: 250_000_000.into(),
+            max_priority_fee_per_gas: 1_000.into(),
+        };
+        let (url, estimated) = mock_fee_estimator(&estimate);
+        let provider = get_http_provider(&url);
+
+        let signer = WalletSigner::from_private_key([0x42; 32]).unwrap();
+        let tx = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .from(signer.address())
+                .to(Address::with_last_byte(2).to_ethers())
+                .chain_id(260)
+                .nonce(0)
+                .gas(21_000)
+                .max_fee_per_gas(1)
+                .data(vec![0xd0, 0xe3, 0x0d, 0xb0]),
+        );
+        let paymaster = ZkPaymasterData {
+            address: Address::with_last_byte(0x99),
+            input: Bytes::from(vec![0x8c, 0x5a, 0x34, 0x45]),
+        };
+        let zk_tx = ZkTransactionMetadata {
+            factory_deps: vec![vec![0; 32]],
+            paymaster: Some(paymaster.clone()),
+        };
+        let signed = |fee: Fee| {
+            let request = foundry_zksync_core::new_eip712_request(&tx, &zk_tx).unwrap();
+            let request = foundry_zksync_core::set_eip712_fee(request, &zk_tx, &fee);
+            let signer = &signer;
+            async move {
+                foundry_zksync_core::sign_eip712_request(request, signer).await.unwrap().to_ethers()
+            }
+        };
+
+        // the estimated gas limit is scaled, and the fees are overridden
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Script.s.sol",
+            "--gas-estimate-multiplier",
+            "150",
+            "--with-gas-price",
+            "300000000",
+            "--priority-gas-price",
+            "2000",
+        ]);
+        let (raw, gas_per_pubdata) =
+            args.sign_zk_transaction(&provider, &signer, &tx, zk_tx.clone(), false).await.unwrap();
+        let expected = Fee {
+            gas_limit: 1_500_000.into(),
+            max_fee_per_gas: 300_000_000.into(),
+            max_priority_fee_per_gas: 2_000.into(),
+            ..estimate.clone()
+        };
+        assert_eq!(raw, signed(expected).await);
+        assert_eq!(gas_per_pubdata, U256::from(50_000));
+
+        // the paymaster is signed, and its validation is part of the estimate
+        let paymaster_address = hex::encode(paymaster.address);
+        let paymaster_input = hex::encode(&paymaster.input);
+        assert!(hex::encode(&raw).contains(&paymaster_address));
+        assert!(hex::encode(&raw).contains(&paymaster_input));
+        let request = estimated.lock().unwrap()[0].to_string();
+        assert!(request.contains(&paymaster_address), "{request}");
+        assert!(request.contains(&paymaster_input), "{request}");
+
+        // a gas limit fixed by the script is kept, and the priority fee is capped by the max fee
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Script.s.sol",
+            "--priority-gas-price",
+            "500000000",
+        ]);
+        let (raw, _) =
+            args.sign_zk_transaction(&provider, &signer, &tx, zk_tx.clone(), true).await.unwrap();
+        let expected = Fee {
+            gas_limit: 21_000.into(),
+            max_priority_fee_per_gas: estimate.max_fee_per_gas,
+            ..estimate.clone()
+        };
+        assert_eq!(raw, signed(expected).await);
+        assert_eq!(estimated.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn computes_shortfall() {
+        let required =
+            RequiredFunds { value: U256::from(100), gas: U256::from(10), zk_fees: U256::from(5) };
+        let gas_price = U256::from(2);
+        assert_eq!(required.fees(gas_price), U256::from(25));
+
+        assert_eq!(required.shortfall(U256::from(99), gas_price), Some(Shortfall::Transfers));
+        assert_eq!(
+            required.shortfall(U256::from(124), gas_price),
+            Some(Shortfall::Fees(U256::from(25)))
+        );
+        assert_eq!(required.shortfall(U256::from(125), gas_price), None);
+    }
+}
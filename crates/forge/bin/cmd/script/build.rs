@@ -9,9 +9,9 @@ use foundry_compilers::{
     cache::SolFilesCache,
     contracts::ArtifactContracts,
     info::ContractInfo,
-    ArtifactId, Project, ProjectCompileOutput,
+    Artifact, ArtifactId, Project, ProjectCompileOutput,
 };
-use foundry_zksync_compiler::{new_dual_compiled_contracts, DualCompiledContract, ZkSolc};
+use foundry_zksync_compiler::{order_libraries, DualCompiledContracts, ZkSolc};
 use std::str::FromStr;
 
 impl ScriptArgs {
@@ -46,7 +46,23 @@ impl ScriptArgs {
             Ok(compiled) => compiled,
             Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
         };
-        let dual_compiled_contracts = new_dual_compiled_contracts(&output, &zk_output);
+        let dual_compiled_contracts = DualCompiledContracts::new(&output, &zk_output);
+
+        // The configured libraries zksolc linked the script with, deployed if they aren't yet
+        if config.zksync {
+            let mut zk_libraries = Vec::new();
+            for library in config.zk_libraries()? {
+                let id = library.identifier();
+                let code = output
+                    .find(&library.path.to_string_lossy(), &library.name)
+                    .and_then(|artifact| artifact.get_deployed_bytecode_bytes())
+                    .filter(|code| !code.is_empty())
+                    .ok_or_else(|| eyre::eyre!("library {id} has no deployed bytecode"))?
+                    .into_owned();
+                zk_libraries.push((library, code));
+            }
+            script_config.zk_libraries = order_libraries(zk_libraries)?;
+        }
 
         let sources = ContractSources::from_project_output(&output, root)?;
         let contracts = output.into_artifacts().collect();
@@ -228,5 +244,5 @@ pub struct BuildOutput {
     pub libraries: Libraries,
     pub predeploy_libraries: Vec<Bytes>,
     pub sources: ContractSources,
-    pub dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+    pub dual_compiled_contracts: Option<DualCompiledContracts>,
 }
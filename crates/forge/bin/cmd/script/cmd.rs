@@ -2,15 +2,15 @@ use super::{
     multi::MultiChainSequence, sequence::ScriptSequence, verify::VerifyBundle, ScriptArgs,
     ScriptConfig, ScriptResult,
 };
-use crate::cmd::script::{build::BuildOutput, receipts};
+use crate::cmd::script::{build::BuildOutput, queue, receipts};
 use alloy_primitives::{Address, Bytes};
 use ethers_providers::Middleware;
 use ethers_signers::Signer;
 use eyre::{OptionExt, Result};
 use forge::{link::Linker, traces::CallTraceDecoder};
-use foundry_cli::utils::LoadConfig;
+use foundry_cli::utils::{detect_zksync_fork, pin_config_fork_block, LoadConfig};
 use foundry_common::{
-    contracts::flatten_contracts, provider::ethers::try_get_http_provider, types::ToAlloy,
+    contracts::flatten_contracts, provider::ethers::try_get_http_provider, shell, types::ToAlloy,
 };
 use foundry_compilers::{
     artifacts::{ContractBytecodeSome, Libraries},
@@ -19,7 +19,7 @@ use foundry_compilers::{
 use foundry_debugger::Debugger;
 use foundry_evm::inspectors::cheatcodes::{BroadcastableTransaction, ScriptWallets};
 use foundry_wallets::WalletSigner;
-use foundry_zksync_compiler::DualCompiledContract;
+use foundry_zksync_compiler::DualCompiledContracts;
 use std::{collections::HashMap, sync::Arc};
 
 /// Helper alias type for the collection of data changed due to the new sender.
@@ -30,7 +30,13 @@ impl ScriptArgs {
     pub async fn run_script(mut self) -> Result<()> {
         trace!(target: "script", "executing script command");
 
-        let (config, evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        let (mut config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        pin_config_fork_block(&config, &mut evm_opts, false).await?;
+
+        // The state of a zkSync Era chain is only executable in EraVM
+        if detect_zksync_fork(&mut config, evm_opts.fork_url.as_deref()).await && !self.json {
+            shell::println("Forking a zkSync Era chain, running the script in EraVM")?;
+        }
         let mut script_config = ScriptConfig {
             // dapptools compatibility
             sender_nonce: 1,
@@ -48,8 +54,9 @@ impl ScriptArgs {
             // when forking, override the sender's nonce to the onchain value
             script_config.sender_nonce =
                 forge::next_nonce(script_config.evm_opts.sender, fork_url, None).await?
-        } else {
-            // if not forking, then ignore any pre-deployed library addresses
+        } else if !script_config.config.zksync {
+            // if not forking, then ignore any pre-deployed library addresses, unless zksolc links
+            // them at compile time, then their code is set for the execution
             script_config.config.libraries = Default::default();
         }
 
@@ -138,6 +145,11 @@ impl ScriptArgs {
 
         let signers = script_wallets.into_multi_wallet().into_signers()?;
 
+        let user_operations = std::mem::take(&mut result.user_operations);
+        let user_ops_config = script_config.config.clone();
+        let target = script_config.target_contract().clone();
+        let fork_url = script_config.evm_opts.fork_url.clone();
+
         self.handle_broadcastable_transactions(
             result,
             libraries,
@@ -147,6 +159,15 @@ impl ScriptArgs {
             &signers,
             dual_compiled_contracts,
         )
+        .await?;
+
+        self.send_user_operations(
+            &user_operations,
+            &user_ops_config,
+            &target,
+            fork_url.as_deref(),
+            &signers,
+        )
         .await
     }
 
@@ -159,7 +180,7 @@ impl ScriptArgs {
         predeploy_libraries: Vec<Bytes>,
         result: &mut ScriptResult,
         script_wallets: ScriptWallets,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<Option<NewSenderChanges>> {
         if let Some(new_sender) = self.maybe_new_sender(
             &script_config.evm_opts,
@@ -195,6 +216,13 @@ impl ScriptArgs {
             &predeploy_libraries,
             &script_config.evm_opts.fork_url,
         );
+        // The zkSync libraries that aren't deployed yet are deployed after them
+        lib_deploy.extend(self.create_zk_library_deployments(
+            script_config,
+            &result.zk_libraries,
+            script_config.sender_nonce + predeploy_libraries.len() as u64,
+            dual_compiled_contracts.as_ref(),
+        )?);
 
         if let Some(txs) = &mut result.transactions {
             for tx in txs.iter() {
@@ -202,6 +230,7 @@ impl ScriptArgs {
                     rpc: tx.rpc.clone(),
                     transaction: tx.transaction.clone(),
                     zk_tx: tx.zk_tx.clone(),
+                    expected_events: tx.expected_events.clone(),
                 });
             }
             *txs = lib_deploy;
@@ -264,7 +293,7 @@ impl ScriptArgs {
         let provider = Arc::new(try_get_http_provider(fork_url)?);
 
         let chain = provider.get_chainid().await?.as_u64();
-        verify.set_chain(&script_config.config, chain.into());
+        verify.set_chain(&script_config.config, chain.into())?;
 
         let broadcasted = self.broadcast || self.resume;
         let mut deployment_sequence = match ScriptSequence::load(
@@ -291,6 +320,9 @@ impl ScriptArgs {
             deployment_sequence.verify_preflight_check(&script_config.config, &verify)?;
         }
 
+        if self.resume {
+            queue::recover_queue(provider.clone(), &mut deployment_sequence).await?;
+        }
         receipts::wait_for_pending(provider, &mut deployment_sequence).await?;
 
         if self.resume {
@@ -332,7 +364,7 @@ impl ScriptArgs {
         first_run_result: &mut ScriptResult,
         linker: Linker,
         script_wallets: ScriptWallets,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<(Libraries, ArtifactContracts<ContractBytecodeSome>)> {
         // if we had a new sender that requires relinking, we need to
         // get the nonce mainnet for accurate addresses for predeploy libs
@@ -381,6 +413,7 @@ impl ScriptArgs {
                     rpc: new_tx.rpc.clone(),
                     transaction: new_tx.transaction.clone(),
                     zk_tx: new_tx.zk_tx.clone(),
+                    expected_events: new_tx.expected_events.clone(),
                 });
             }
         }
@@ -2,7 +2,6 @@ use crate::cmd::script::transaction::ZkTransaction;
 
 use super::{
     artifacts::ArtifactInfo,
-    runner::{ScriptRunner, SimulationStage},
     transaction::{AdditionalContract, TransactionWithMetadata},
     ScriptArgs, ScriptConfig, ScriptResult,
 };
@@ -12,13 +11,14 @@ use forge::{
     backend::Backend,
     executors::ExecutorBuilder,
     inspectors::{cheatcodes::BroadcastableTransactions, CheatsConfig},
+    script::{encode_calldata, ScriptRunner, SimulationStage},
     traces::{render_trace_arena, CallTraceDecoder},
 };
 use foundry_cli::utils::{ensure_clean_constructor, needs_setup};
 use foundry_common::{get_contract_name, provider::ethers::RpcUrl, shell, ContractsByArtifact};
 use foundry_compilers::artifacts::ContractBytecodeSome;
 use foundry_evm::inspectors::cheatcodes::ScriptWallets;
-use foundry_zksync_compiler::DualCompiledContract;
+use foundry_zksync_compiler::DualCompiledContracts;
 use futures::future::join_all;
 use parking_lot::RwLock;
 use std::{
@@ -36,7 +36,7 @@ impl ScriptArgs {
         sender: Address,
         predeploy_libraries: &[Bytes],
         script_wallets: ScriptWallets,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<ScriptResult> {
         trace!(target: "script", "start executing script");
 
@@ -57,42 +57,25 @@ impl ScriptArgs {
                 dual_compiled_contracts,
             )
             .await?;
-        let (address, mut result) = runner.setup(
+        let zk_libraries = script_config
+            .zk_libraries
+            .iter()
+            .map(|(library, code)| (library.address, code.clone()))
+            .collect::<Vec<_>>();
+        let (func, calldata) = encode_calldata(&abi, &self.sig, &self.args)?;
+        script_config.called_function = Some(func);
+
+        let (_, result) = runner.run(
             predeploy_libraries,
+            &zk_libraries,
             bytecode,
             needs_setup(&abi),
             script_config.sender_nonce,
             self.broadcast,
             script_config.evm_opts.fork_url.is_none(),
+            calldata,
         )?;
 
-        let (func, calldata) = self.get_method_and_calldata(&abi)?;
-        script_config.called_function = Some(func);
-
-        // Only call the method if `setUp()` succeeded.
-        if result.success {
-            let script_result = runner.script(address, calldata)?;
-
-            result.success &= script_result.success;
-            result.gas_used = script_result.gas_used;
-            result.logs.extend(script_result.logs);
-            result.traces.extend(script_result.traces);
-            result.debug = script_result.debug;
-            result.labeled_addresses.extend(script_result.labeled_addresses);
-            result.returned = script_result.returned;
-            result.breakpoints = script_result.breakpoints;
-
-            match (&mut result.transactions, script_result.transactions) {
-                (Some(txs), Some(new_txs)) => {
-                    txs.extend(new_txs);
-                }
-                (None, Some(new_txs)) => {
-                    result.transactions = Some(new_txs);
-                }
-                _ => {}
-            }
-        }
-
         Ok(result)
     }
 
@@ -104,7 +87,7 @@ impl ScriptArgs {
         script_config: &ScriptConfig,
         decoder: &CallTraceDecoder,
         contracts: &ContractsByArtifact,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<VecDeque<TransactionWithMetadata>> {
         trace!(target: "script", "executing onchain simulation");
 
@@ -164,9 +147,23 @@ impl ScriptArgs {
                     .wrap_err("Internal EVM error during simulation")?;
 
                 if !result.success || result.traces.is_empty() {
-                    return Ok((None, result.traces));
+                    return Ok((None, result.traces, vec![]));
                 }
 
+                let unmet_expectations = transaction
+                    .expected_events
+                    .iter()
+                    .filter(|expected| {
+                        !expected.is_satisfied_by(
+                            result
+                                .logs
+                                .iter()
+                                .map(|log| (log.address, log.topics(), &log.data.data[..])),
+                        )
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+
                 let created_contracts = result
                     .traces
                     .iter()
@@ -202,7 +199,7 @@ impl ScriptArgs {
                     }
                 }
 
-                let tx = TransactionWithMetadata::new_with_zk(
+                let mut tx = TransactionWithMetadata::new_with_zk(
                     tx,
                     transaction.rpc,
                     &result,
@@ -210,16 +207,21 @@ impl ScriptArgs {
                     decoder,
                     created_contracts,
                     is_fixed_gas_limit,
-                    zk.map(|zk_tx| ZkTransaction { factory_deps: zk_tx.factory_deps }),
+                    zk.map(ZkTransaction::from),
                 )?;
+                tx.expected_events = transaction.expected_events;
 
-                eyre::Ok((Some(tx), result.traces))
+                eyre::Ok((Some(tx), result.traces, unmet_expectations))
             })
             .collect::<Vec<_>>();
 
         let mut abort = false;
-        for res in join_all(futs).await {
-            let (tx, traces) = res?;
+        let mut unmet_expectations = vec![];
+        for (i, res) in join_all(futs).await.into_iter().enumerate() {
+            let (tx, traces, unmet) = res?;
+            unmet_expectations.extend(unmet.into_iter().map(|expected| {
+                format!("transaction #{i} was expected to emit {expected}, but it didn't")
+            }));
 
             // Transaction will be `None`, if execution didn't pass.
             if tx.is_none() || script_config.evm_opts.verbosity > 3 {
@@ -246,6 +248,13 @@ impl ScriptArgs {
             eyre::bail!("Simulated execution failed.")
         }
 
+        if !unmet_expectations.is_empty() {
+            eyre::bail!(
+                "Simulated execution didn't satisfy the expected broadcast events:\n{}",
+                unmet_expectations.join("\n")
+            )
+        }
+
         Ok(final_txs)
     }
 
@@ -253,7 +262,7 @@ impl ScriptArgs {
     async fn build_runners(
         &self,
         script_config: &ScriptConfig,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<HashMap<RpcUrl, ScriptRunner>> {
         let sender = script_config.evm_opts.sender;
 
@@ -292,7 +301,7 @@ impl ScriptArgs {
         sender: Address,
         stage: SimulationStage,
         script_wallets: Option<ScriptWallets>,
-        dual_compiled_contracts: Option<Vec<DualCompiledContract>>,
+        dual_compiled_contracts: Option<DualCompiledContracts>,
     ) -> Result<ScriptRunner> {
         trace!("preparing script runner");
         let env = script_config.evm_opts.evm_env().await?;
@@ -319,7 +328,11 @@ impl ScriptArgs {
 
         // We need to enable tracing to decode contract names: local or external.
         let mut builder = ExecutorBuilder::new()
-            .inspectors(|stack| stack.trace(true))
+            .inspectors(|stack| {
+                stack
+                    .trace(true)
+                    .trace_return_data_limit(script_config.config.trace_return_data_limit)
+            })
             .spec(script_config.config.evm_spec_id())
             .gas_limit(script_config.evm_opts.gas_limit());
 
@@ -332,9 +345,9 @@ impl ScriptArgs {
                             &script_config.config,
                             script_config.evm_opts.clone(),
                             script_wallets,
-                            dual_compiled_contracts.unwrap_or_default(),
                             script_config.config.zksync,
                         )
+                        .with_dual_compiled_contracts(dual_compiled_contracts.unwrap_or_default())
                         .into(),
                     )
                     .enable_isolation(script_config.evm_opts.isolate)
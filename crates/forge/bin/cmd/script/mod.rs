@@ -1,29 +1,28 @@
 use super::{build::BuildArgs, retry::RetryArgs};
 use alloy_dyn_abi::FunctionExt;
-use alloy_json_abi::{Function, InternalType, JsonAbi};
-use alloy_primitives::{Address, Bytes, Log, U256, U64};
+use alloy_json_abi::{Function, InternalType};
+use alloy_primitives::{keccak256, Address, Bytes, B256, KECCAK256_EMPTY, U256, U64};
 use alloy_rpc_types::request::TransactionRequest;
 use clap::{Parser, ValueHint};
 use dialoguer::Confirm;
 use ethers_providers::{Http, Middleware};
-use eyre::{ContextCompat, Result, WrapErr};
+use eyre::Result;
 use forge::{
     backend::Backend,
-    debug::DebugArena,
     decode::decode_console_logs,
     opts::EvmOpts,
+    script::ScriptResult,
     traces::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
-        render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
+        render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind,
     },
 };
 use foundry_common::{
-    abi::{encode_function_args, get_func},
     errors::UnlinkedByteCode,
-    evm::{Breakpoints, EvmArgs},
+    evm::EvmArgs,
     fmt::{format_token, format_token_raw},
     provider::ethers::RpcUrl,
-    shell, ContractsByArtifact, CONTRACT_MAX_SIZE, SELECTOR_LEN,
+    shell, ContractsByArtifact, CONTRACT_MAX_SIZE,
 };
 use foundry_compilers::{
     artifacts::{ContractBytecodeSome, Libraries},
@@ -40,9 +39,16 @@ use foundry_config::{
 use foundry_evm::{
     constants::DEFAULT_CREATE2_DEPLOYER,
     decode::RevertDecoder,
-    inspectors::cheatcodes::{BroadcastableTransaction, BroadcastableTransactions},
+    inspectors::cheatcodes::{BroadcastableTransaction, BroadcastableTransactions, FfiInvocation},
+    revm::primitives::CreateScheme,
 };
 use foundry_wallets::MultiWalletOpts;
+use foundry_zksync_compiler::{DualCompiledContracts, ZkLibrary};
+use foundry_zksync_core::{
+    compute_create2_address,
+    convert::{ConvertH160, ConvertH256},
+    encode_create_params, ZkTransactionMetadata, CONTRACT_DEPLOYER_ADDRESS,
+};
 use futures::future;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -56,11 +62,13 @@ mod cmd;
 mod executor;
 mod multi;
 mod providers;
+pub mod queue;
 mod receipts;
-mod runner;
+mod safe;
 mod sequence;
 pub mod transaction;
-mod verify;
+mod user_ops;
+pub mod verify;
 
 // Loads project's figment and merges the build cli arguments into it
 foundry_config::merge_impl_figment_convert!(ScriptArgs, opts, evm_opts);
@@ -134,6 +142,28 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub multi: bool,
 
+    /// Writes the transactions sent by the given Safe as a Safe Transaction Builder bundle,
+    /// instead of broadcasting them.
+    #[clap(long, value_name = "ADDRESS", conflicts_with_all = &["broadcast", "resume"])]
+    pub safe: Option<Address>,
+
+    /// Also proposes the transactions of the Safe bundle to the Safe Transaction Service, signed
+    /// by the `--sender` owner of the Safe.
+    #[clap(long, requires = "safe")]
+    pub safe_propose: bool,
+
+    /// The URL of the Safe Transaction Service to propose to.
+    ///
+    /// Defaults to the service of the chain, for the chains Safe hosts one for.
+    #[clap(long, requires = "safe_propose", value_name = "URL")]
+    pub safe_service_url: Option<String>,
+
+    /// The `MultiSendCallOnly` contract batching the proposed transactions.
+    ///
+    /// Defaults to the canonical Safe v1.3.0 deployment of the chain.
+    #[clap(long, requires = "safe_propose", value_name = "ADDRESS")]
+    pub safe_multi_send: Option<Address>,
+
     /// Open the script in the debugger.
     ///
     /// Takes precedence over broadcast.
@@ -206,6 +236,7 @@ impl ScriptArgs {
         let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
         let mut decoder = CallTraceDecoderBuilder::new()
             .with_labels(result.labeled_addresses.clone())
+            .with_proxies(result.proxies.clone())
             .with_verbosity(verbosity)
             .with_local_identifier_abis(&local_identifier)
             .with_signature_identifier(SignaturesIdentifier::new(
@@ -342,6 +373,13 @@ impl ScriptArgs {
             }
         }
 
+        if !result.ffi_invocations.is_empty() {
+            shell::println("\n== FFI ==")?;
+            for invocation in &result.ffi_invocations {
+                shell::println(format!("  {invocation}"))?;
+            }
+        }
+
         if !result.success {
             return Err(eyre::eyre!(
                 "script failed: {}",
@@ -356,7 +394,12 @@ impl ScriptArgs {
         let returns = self.get_returns(script_config, &result.returned)?;
 
         let console_logs = decode_console_logs(&result.logs);
-        let output = JsonResult { logs: console_logs, gas_used: result.gas_used, returns };
+        let output = JsonResult {
+            logs: console_logs,
+            gas_used: result.gas_used,
+            returns,
+            ffi_invocations: result.ffi_invocations.clone(),
+        };
         let j = serde_json::to_string(&output)?;
         shell::println(j)?;
 
@@ -423,42 +466,71 @@ impl ScriptArgs {
                     ..Default::default()
                 },
                 zk_tx: None,
+                expected_events: vec![],
             })
             .collect()
     }
 
-    /// Returns the Function and calldata based on the signature
+    /// Helper for building the zkSync transactions deploying the configured libraries at
+    /// `addresses`, which aren't deployed yet, in order from `nonce`. They're deployed with
+    /// `CREATE2` and a zero salt, so their addresses are known ahead of the deployment.
     ///
-    /// If the `sig` is a valid human-readable function we find the corresponding function in the
-    /// `abi` If the `sig` is valid hex, we assume it's calldata and try to find the
-    /// corresponding function by matching the selector, first 4 bytes in the calldata.
-    ///
-    /// Note: We assume that the `sig` is already stripped of its prefix, See [`ScriptArgs`]
-    fn get_method_and_calldata(&self, abi: &JsonAbi) -> Result<(Function, Bytes)> {
-        let (func, data) = if let Ok(func) = get_func(&self.sig) {
-            (
-                abi.functions().find(|&abi_func| abi_func.selector() == func.selector()).wrap_err(
-                    format!("Function `{}` is not implemented in your script.", self.sig),
-                )?,
-                encode_function_args(&func, &self.args)?.into(),
-            )
-        } else {
-            let decoded = hex::decode(&self.sig).wrap_err("Invalid hex calldata")?;
-            let selector = &decoded[..SELECTOR_LEN];
-            (
-                abi.functions().find(|&func| selector == &func.selector()[..]).ok_or_else(
-                    || {
-                        eyre::eyre!(
-                            "Function selector `{}` not found in the ABI",
-                            hex::encode(selector)
-                        )
-                    },
-                )?,
-                decoded.into(),
-            )
-        };
+    /// Fails if a library isn't configured at the address `CREATE2` deploys it at, as the
+    /// contracts are linked with the configured address.
+    fn create_zk_library_deployments(
+        &self,
+        script_config: &ScriptConfig,
+        addresses: &[Address],
+        nonce: u64,
+        dual_compiled_contracts: Option<&DualCompiledContracts>,
+    ) -> Result<BroadcastableTransactions> {
+        let from = script_config.evm_opts.sender;
+        let mut transactions = BroadcastableTransactions::new();
+        for (i, address) in addresses.iter().enumerate() {
+            let Some((library, code)) =
+                script_config.zk_libraries.iter().find(|(library, _)| library.address == *address)
+            else {
+                continue
+            };
+            let id = library.identifier();
+            let contract = dual_compiled_contracts
+                .and_then(|contracts| contracts.find_by_evm_hash(keccak256(code)))
+                .ok_or_else(|| eyre::eyre!("library {id} wasn't compiled with zksolc"))?;
+            let deployed_at = compute_create2_address(
+                from,
+                contract.zk_bytecode_hash.to_b256(),
+                B256::ZERO,
+                KECCAK256_EMPTY,
+            );
+            if deployed_at != library.address {
+                eyre::bail!(
+                    "library {id} is configured at {address}, but {from} deploys it at \
+                     {deployed_at}: set `{id}:{deployed_at}` in `libraries`"
+                );
+            }
 
-        Ok((func.clone(), data))
+            let input = encode_create_params(
+                &CreateScheme::Create2 { salt: U256::ZERO },
+                contract.zk_bytecode_hash,
+                vec![],
+            );
+            transactions.push_back(BroadcastableTransaction {
+                rpc: script_config.evm_opts.fork_url.clone(),
+                transaction: TransactionRequest {
+                    from: Some(from),
+                    to: Some(CONTRACT_DEPLOYER_ADDRESS.to_address()),
+                    input: Some(Bytes::from(input)).into(),
+                    nonce: Some(U64::from(nonce + i as u64)),
+                    ..Default::default()
+                },
+                zk_tx: Some(ZkTransactionMetadata {
+                    factory_deps: contract.factory_deps(),
+                    paymaster: None,
+                }),
+                expected_events: vec![],
+            });
+        }
+        Ok(transactions)
     }
 
     /// Checks if the transaction is a deployment with either a size above the `CONTRACT_MAX_SIZE`
@@ -582,25 +654,13 @@ impl Provider for ScriptArgs {
     }
 }
 
-#[derive(Default)]
-pub struct ScriptResult {
-    pub success: bool,
-    pub logs: Vec<Log>,
-    pub traces: Traces,
-    pub debug: Option<Vec<DebugArena>>,
-    pub gas_used: u64,
-    pub labeled_addresses: HashMap<Address, String>,
-    pub transactions: Option<BroadcastableTransactions>,
-    pub returned: Bytes,
-    pub address: Option<Address>,
-    pub breakpoints: Breakpoints,
-}
-
 #[derive(Serialize, Deserialize)]
 struct JsonResult {
     logs: Vec<String>,
     gas_used: u64,
     returns: HashMap<String, NestedValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ffi_invocations: Vec<FfiInvocation>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -626,6 +686,9 @@ pub struct ScriptConfig {
     pub missing_rpc: bool,
     /// Should return some debug information
     pub debug: bool,
+    /// The libraries of the `libraries` config, with their solc deployed code, that zkSync
+    /// scripts deploy if they aren't deployed yet, in deployment order
+    pub zk_libraries: Vec<(ZkLibrary, Bytes)>,
 }
 
 impl ScriptConfig {
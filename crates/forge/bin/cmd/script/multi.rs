@@ -1,5 +1,5 @@
 use super::{
-    receipts,
+    queue, receipts,
     sequence::{sig_to_file_name, ScriptSequence, SensitiveScriptSequence, DRY_RUN_DIR},
     verify::VerifyBundle,
     ScriptArgs,
@@ -203,6 +203,7 @@ impl ScriptArgs {
                 .map(|sequence| async move {
                     let rpc_url = sequence.rpc_url().unwrap();
                     let provider = Arc::new(get_http_provider(rpc_url));
+                    queue::recover_queue(provider.clone(), sequence).await?;
                     receipts::wait_for_pending(provider, sequence).await
                 })
                 .collect::<Vec<_>>();
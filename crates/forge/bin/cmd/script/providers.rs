@@ -1,14 +1,18 @@
-use alloy_primitives::U256;
+use alloy_primitives::{address, utils::format_units, Address, U256};
+use alloy_sol_types::{sol, SolCall};
+use ethers_core::types::{TransactionRequest, H160};
 use ethers_providers::{Middleware, Provider};
 use eyre::{Result, WrapErr};
 use foundry_common::{
     provider::ethers::{get_http_provider, RpcUrl},
     runtime_client::RuntimeClient,
-    types::ToAlloy,
+    types::{ToAlloy, ToEthers},
 };
 use foundry_config::Chain;
+use foundry_zksync_core::{convert::ConvertH160, L2_ETH_TOKEN_ADDRESS};
 use std::{
     collections::{hash_map::Entry, HashMap},
+    fmt,
     ops::Deref,
     sync::Arc,
 };
@@ -51,6 +55,96 @@ pub struct ProviderInfo {
     pub chain: u64,
     pub gas_price: GasPrice,
     pub is_legacy: bool,
+    pub base_token: BaseToken,
+}
+
+sol! {
+    interface IERC20Metadata {
+        function decimals() external view returns (uint8);
+    }
+}
+
+/// The token transaction values and fees are denominated in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BaseToken {
+    /// Ether, on L1s and zkSync Era chains settling in ETH.
+    Eth,
+    /// A custom ERC20 base token of a zkSync Era chain, identified by its L1 address.
+    Custom { l1_address: Address, decimals: u8 },
+}
+
+impl BaseToken {
+    /// The L1 address `zks_getBaseTokenL1Address` returns for chains using ETH.
+    pub const ETH_L1_ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+
+    /// The decimals of ETH, and of the base tokens whose `decimals()` can't be read.
+    pub const DEFAULT_DECIMALS: u8 = 18;
+
+    /// Detects the base token of the chain behind `provider`.
+    ///
+    /// Chains that don't support `zks_getBaseTokenL1Address`, like all non zkSync chains, use ETH.
+    /// The decimals of a custom base token are read from the `L2BaseToken` system contract.
+    pub async fn detect(provider: &Provider<RuntimeClient>) -> Self {
+        let l1_address = match provider.request::<_, H160>("zks_getBaseTokenL1Address", ()).await {
+            Ok(l1_address) => l1_address.to_alloy(),
+            Err(_) => return BaseToken::Eth,
+        };
+        if l1_address == Self::ETH_L1_ADDRESS {
+            return BaseToken::Eth
+        }
+
+        let decimals_call = TransactionRequest::new()
+            .to(L2_ETH_TOKEN_ADDRESS.to_address().to_ethers())
+            .data(IERC20Metadata::decimalsCall {}.abi_encode());
+        let decimals = match provider.call(&decimals_call.into(), None).await {
+            Ok(output) => IERC20Metadata::decimalsCall::abi_decode_returns(&output, true)
+                .map_or(Self::DEFAULT_DECIMALS, |decimals| decimals._0),
+            Err(_) => Self::DEFAULT_DECIMALS,
+        };
+        Self::from_l1_address(l1_address, decimals)
+    }
+
+    /// Returns the base token with the given L1 address, as returned by
+    /// `zks_getBaseTokenL1Address`, and decimals.
+    pub fn from_l1_address(l1_address: Address, decimals: u8) -> Self {
+        if l1_address == Self::ETH_L1_ADDRESS {
+            BaseToken::Eth
+        } else {
+            BaseToken::Custom { l1_address, decimals }
+        }
+    }
+
+    /// Returns true if this is a custom base token.
+    pub fn is_custom(&self) -> bool {
+        matches!(self, BaseToken::Custom { .. })
+    }
+
+    /// Returns the decimals amounts of the token are denominated with.
+    pub fn decimals(&self) -> u8 {
+        match self {
+            BaseToken::Eth => Self::DEFAULT_DECIMALS,
+            BaseToken::Custom { decimals, .. } => *decimals,
+        }
+    }
+
+    /// Formats `amount` in units of the token, without trailing zeros.
+    pub fn format_units(&self, amount: U256) -> Result<String> {
+        let units = format_units(amount, self.decimals())?;
+        match units.split_once('.') {
+            Some((int, fract)) if fract.trim_end_matches('0').is_empty() => Ok(int.to_string()),
+            Some(_) => Ok(units.trim_end_matches('0').to_string()),
+            None => Ok(units),
+        }
+    }
+}
+
+impl fmt::Display for BaseToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaseToken::Eth => f.write_str("ETH"),
+            BaseToken::Custom { l1_address, .. } => write!(f, "base token (L1: {l1_address})"),
+        }
+    }
 }
 
 /// Represents the outcome of a gas price request
@@ -87,7 +181,9 @@ impl ProviderInfo {
             )
         };
 
-        Ok(ProviderInfo { provider, chain, gas_price, is_legacy })
+        let base_token = BaseToken::detect(&provider).await;
+
+        Ok(ProviderInfo { provider, chain, gas_price, is_legacy, base_token })
     }
 
     /// Returns the gas price to use
@@ -102,3 +198,34 @@ impl ProviderInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_base_token_from_l1_address() {
+        let eth = BaseToken::from_l1_address(BaseToken::ETH_L1_ADDRESS, 6);
+        assert_eq!(eth, BaseToken::Eth);
+        assert!(!eth.is_custom());
+        assert_eq!(eth.decimals(), 18);
+        assert_eq!(eth.to_string(), "ETH");
+
+        let l1_address = address!("5A7d6b2F92C77FAD6CCaBd7EE0624E64907Eaf3E");
+        let custom = BaseToken::from_l1_address(l1_address, 6);
+        assert_eq!(custom, BaseToken::Custom { l1_address, decimals: 6 });
+        assert!(custom.is_custom());
+        assert_eq!(custom.decimals(), 6);
+        assert_eq!(custom.to_string(), format!("base token (L1: {l1_address})"));
+    }
+
+    #[test]
+    fn formats_amounts_with_the_token_decimals() {
+        let amount = U256::from(1_500_000u64);
+        assert_eq!(BaseToken::Eth.format_units(amount * U256::exp10(12)).unwrap(), "1.5");
+        let usdc = BaseToken::Custom { l1_address: Address::ZERO, decimals: 6 };
+        assert_eq!(usdc.format_units(amount).unwrap(), "1.5");
+        assert_eq!(usdc.format_units(U256::exp10(6)).unwrap(), "1");
+        assert_eq!(usdc.format_units(U256::ZERO).unwrap(), "0");
+    }
+}
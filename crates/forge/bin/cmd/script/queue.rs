@@ -0,0 +1,517 @@
+//! Durable store of the transactions being broadcast by a script.
+//!
+//! Every transaction is recorded before it's signed, once it's signed (with its raw bytes) and
+//! once it has been submitted, so a `forge script --broadcast` run that dies halfway can be
+//! resumed with `--resume` without re-simulating or re-signing, and without losing track of
+//! transactions that were handed to the node but never made it into the broadcast file.
+//!
+//! The EIP-712 fields of zkSync transactions, their factory deps and paymaster, are recorded with
+//! the transaction, and the gas per pubdata it was signed with once it's signed.
+//!
+//! The queue is a SQLite database with one row per transaction. Every update is its own
+//! transaction, committed before returning, so a crash leaves the queue as it was after the last
+//! recorded event.
+
+use super::{receipts::clear_pendings, sequence::ScriptSequence, transaction::ZkTransaction};
+use alloy_primitives::{keccak256, Bytes, TxHash, U256};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use yansi::Paint;
+
+/// File name suffix of broadcast queue databases.
+pub const QUEUE_FILE_SUFFIX: &str = "-queue.sqlite";
+
+/// The schema of the queue database.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS transactions (
+        idx         INTEGER PRIMARY KEY,
+        state           TEXT NOT NULL,
+        zk              TEXT,
+        tx              TEXT,
+        raw             BLOB,
+        gas_per_pubdata TEXT,
+        hash            BLOB
+    );
+";
+
+/// An update of a transaction in the broadcast queue.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum QueueEvent {
+    /// The transaction is about to be broadcast, with the EIP-712 fields of a zkSync
+    /// transaction.
+    Queued { index: usize, transaction: TypedTransaction, zk: Option<ZkTransaction> },
+    /// The transaction was signed and is about to be submitted, with the gas per pubdata of a
+    /// zkSync transaction.
+    Signed { index: usize, raw: Bytes, gas_per_pubdata: Option<U256> },
+    /// The transaction was accepted by the node.
+    Sent { index: usize, hash: TxHash },
+    /// A receipt was found for the transaction.
+    Confirmed { index: usize, hash: TxHash },
+}
+
+/// How far a queued transaction got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueueState {
+    #[default]
+    Queued,
+    Signed,
+    Sent,
+    Confirmed,
+}
+
+impl fmt::Display for QueueState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Queued => "queued",
+            Self::Signed => "signed",
+            Self::Sent => "sent",
+            Self::Confirmed => "confirmed",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for QueueState {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "queued" => Ok(Self::Queued),
+            "signed" => Ok(Self::Signed),
+            "sent" => Ok(Self::Sent),
+            "confirmed" => Ok(Self::Confirmed),
+            _ => eyre::bail!("unknown broadcast queue state `{s}`"),
+        }
+    }
+}
+
+/// The state of a transaction in the queue.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueEntry {
+    pub index: usize,
+    pub state: QueueState,
+    pub transaction: Option<TypedTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zk: Option<ZkTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_per_pubdata: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<TxHash>,
+}
+
+impl QueueEntry {
+    fn apply(&mut self, event: QueueEvent) {
+        match event {
+            QueueEvent::Queued { index, transaction, zk } => {
+                *self = Self { index, zk, transaction: Some(transaction), ..Default::default() };
+            }
+            QueueEvent::Signed { raw, gas_per_pubdata, .. } => {
+                self.state = QueueState::Signed;
+                self.raw = Some(raw);
+                self.gas_per_pubdata = gas_per_pubdata;
+            }
+            QueueEvent::Sent { hash, .. } => {
+                self.state = QueueState::Sent;
+                self.hash = Some(hash);
+            }
+            QueueEvent::Confirmed { hash, .. } => {
+                self.state = QueueState::Confirmed;
+                self.hash = Some(hash);
+            }
+        }
+    }
+}
+
+impl QueueEvent {
+    fn index(&self) -> usize {
+        match self {
+            Self::Queued { index, .. } |
+            Self::Signed { index, .. } |
+            Self::Sent { index, .. } |
+            Self::Confirmed { index, .. } => *index,
+        }
+    }
+}
+
+/// SQLite store of the transactions broadcast for a [ScriptSequence].
+///
+/// Each event is committed before returning, so the store always reflects what was handed to
+/// the node.
+#[derive(Debug)]
+pub struct BroadcastQueue {
+    path: PathBuf,
+    conn: Connection,
+    entries: BTreeMap<usize, QueueEntry>,
+}
+
+impl BroadcastQueue {
+    /// Creates a new, empty queue at `path`, discarding any previous one.
+    pub fn create(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .wrap_err_with(|| format!("failed to remove broadcast queue {}", path.display()))?;
+        }
+        Self::open(path)
+    }
+
+    /// Opens the queue at `path`, or creates it if it doesn't exist.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let conn = Connection::open(&path)
+            .and_then(|conn| {
+                conn.pragma_update(None, "synchronous", "FULL")?;
+                conn.execute_batch(SCHEMA)?;
+                Ok(conn)
+            })
+            .wrap_err_with(|| format!("failed to open broadcast queue {}", path.display()))?;
+        let entries = read_entries(&conn)
+            .wrap_err_with(|| format!("corrupt broadcast queue {}", path.display()))?;
+        Ok(Self { path, conn, entries })
+    }
+
+    /// Reads the transactions of the queue at `path`, without modifying it.
+    pub fn read(path: &Path) -> Result<BTreeMap<usize, QueueEntry>> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .wrap_err_with(|| format!("failed to read broadcast queue {}", path.display()))?;
+        read_entries(&conn).wrap_err_with(|| format!("corrupt broadcast queue {}", path.display()))
+    }
+
+    /// Returns the state of all transactions in the queue.
+    pub fn entries(&self) -> &BTreeMap<usize, QueueEntry> {
+        &self.entries
+    }
+
+    /// Records an event and commits it to disk.
+    pub fn record(&mut self, event: QueueEvent) -> Result<()> {
+        let mut entry = self.entries.get(&event.index()).cloned().unwrap_or_default();
+        entry.index = event.index();
+        entry.apply(event);
+
+        let transaction = entry.transaction.as_ref().map(serde_json::to_string).transpose()?;
+        let zk = entry.zk.as_ref().map(serde_json::to_string).transpose()?;
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO transactions (idx, state, zk, tx, raw, gas_per_pubdata, hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    entry.index as i64,
+                    entry.state.to_string(),
+                    zk,
+                    transaction,
+                    entry.raw.as_ref().map(|raw| raw.to_vec()),
+                    entry.gas_per_pubdata.map(|gas| gas.to_string()),
+                    entry.hash.map(|hash| hash.to_vec()),
+                ],
+            )
+            .wrap_err_with(|| {
+                format!("failed to write to broadcast queue {}", self.path.display())
+            })?;
+
+        self.entries.insert(entry.index, entry);
+        Ok(())
+    }
+
+    /// Marks every sent transaction of `sequence` with a receipt as confirmed.
+    pub fn record_receipts(&mut self, sequence: &ScriptSequence) -> Result<()> {
+        let confirmed = self
+            .entries
+            .values()
+            .filter(|entry| entry.state == QueueState::Sent)
+            .filter_map(|entry| Some((entry.index, entry.hash?)))
+            .filter(|(_, hash)| {
+                sequence.receipts.iter().any(|receipt| receipt.transaction_hash.to_alloy() == *hash)
+            })
+            .collect::<Vec<_>>();
+        for (index, hash) in confirmed {
+            self.record(QueueEvent::Confirmed { index, hash })?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the queue, once all of its transactions made it into the broadcast file.
+    pub fn remove(self) -> Result<()> {
+        self.conn.close().map_err(|(_, err)| err)?;
+        std::fs::remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Reads all rows of the `transactions` table.
+fn read_entries(conn: &Connection) -> Result<BTreeMap<usize, QueueEntry>> {
+    let mut stmt =
+        conn.prepare("SELECT idx, state, zk, tx, raw, gas_per_pubdata, hash FROM transactions")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<Vec<u8>>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+            row.get::<_, Option<Vec<u8>>>(6)?,
+        ))
+    })?;
+
+    let mut entries = BTreeMap::new();
+    for row in rows {
+        let (index, state, zk, transaction, raw, gas_per_pubdata, hash) = row?;
+        let index = index as usize;
+        let hash = hash
+            .map(|hash| {
+                TxHash::try_from(hash.as_slice())
+                    .map_err(|_| eyre::eyre!("invalid hash of transaction #{index}"))
+            })
+            .transpose()?;
+        let entry = QueueEntry {
+            index,
+            state: state.parse()?,
+            transaction: transaction.as_deref().map(serde_json::from_str).transpose()?,
+            zk: zk.as_deref().map(serde_json::from_str).transpose()?,
+            raw: raw.map(Into::into),
+            gas_per_pubdata: gas_per_pubdata.as_deref().map(U256::from_str).transpose()?,
+            hash,
+        };
+        entries.insert(index, entry);
+    }
+    Ok(entries)
+}
+
+/// The slot of a single transaction in a shared [BroadcastQueue].
+#[derive(Clone, Copy)]
+pub struct QueueSlot<'a> {
+    pub queue: &'a Mutex<BroadcastQueue>,
+    pub index: usize,
+}
+
+impl QueueSlot<'_> {
+    /// Records an event for this transaction.
+    pub fn record(&self, event: impl FnOnce(usize) -> QueueEvent) -> Result<()> {
+        self.queue.lock().unwrap().record(event(self.index))
+    }
+}
+
+/// Recovers the transactions a crashed broadcast left in the queue of `sequence`.
+///
+/// Transactions which were sent are added back as pending, so their receipts are fetched,
+/// while transactions which were signed but may not have reached the node are resubmitted as
+/// is. Only the leading run of signed or sent transactions is recovered, anything after the
+/// first transaction that was never signed is broadcast again from the sequence.
+pub async fn recover_queue(
+    provider: Arc<RetryProvider>,
+    sequence: &mut ScriptSequence,
+) -> Result<()> {
+    let path = sequence.queue_path();
+    if !path.exists() {
+        return Ok(())
+    }
+    let mut queue = BroadcastQueue::open(&path)?;
+
+    let matches_sequence = queue.entries().values().all(|entry| {
+        let Some(tx) = sequence.transactions.get(entry.index).map(|tx| tx.typed_tx()) else {
+            return false
+        };
+        let Some(queued) = &entry.transaction else { return true };
+        queued.from() == tx.from() && queued.nonce() == tx.nonce()
+    });
+    if !matches_sequence {
+        shell::println(
+            Paint::yellow(format!(
+                "Ignoring broadcast queue {}, it doesn't match the deployment being resumed.",
+                path.display()
+            ))
+            .to_string(),
+        )?;
+        return queue.remove()
+    }
+
+    let already_broadcasted = sequence.receipts.len();
+    let to_recover = queue
+        .entries()
+        .range(already_broadcasted..)
+        .map(|(_, entry)| entry.clone())
+        .take_while(|entry| entry.state > QueueState::Queued)
+        .collect::<Vec<_>>();
+    if to_recover.is_empty() {
+        return Ok(())
+    }
+
+    shell::println(format!(
+        "##\nRecovering {} transaction(s) from broadcast queue {}",
+        to_recover.len(),
+        path.display()
+    ))?;
+
+    let mut hashes = vec![];
+    for entry in to_recover {
+        let hash = match (entry.state, entry.hash, entry.raw) {
+            (QueueState::Sent | QueueState::Confirmed, Some(hash), _) => hash,
+            (_, _, Some(raw)) => match provider.send_raw_transaction(raw.clone().to_ethers()).await
+            {
+                Ok(pending) => {
+                    let hash = pending.tx_hash().to_alloy();
+                    queue.record(QueueEvent::Sent { index: entry.index, hash })?;
+                    hash
+                }
+                Err(err) => match known_transaction(&provider, entry.zk.is_some(), &raw).await? {
+                    Some(hash) => {
+                        queue.record(QueueEvent::Sent { index: entry.index, hash })?;
+                        hash
+                    }
+                    None => eyre::bail!(
+                        "Failed to resubmit signed transaction #{} from broadcast queue {}: \
+                         {err}\nCheck whether it was included on chain before resuming again.",
+                        entry.index,
+                        path.display()
+                    ),
+                },
+            },
+            _ => break,
+        };
+        sequence.add_pending(entry.index, hash);
+        hashes.push(hash);
+    }
+
+    clear_pendings(provider, sequence, Some(hashes)).await?;
+    queue.record_receipts(sequence)?;
+    sequence.save()?;
+
+    Ok(())
+}
+
+/// Returns the hash of the raw transaction if the node already knows it.
+///
+/// Resubmitting a transaction which was already accepted fails, and only then it's looked up by
+/// hash. The hash of an EIP-712 transaction isn't the hash of its raw bytes, so those can't be
+/// looked up.
+async fn known_transaction(
+    provider: &RetryProvider,
+    zk: bool,
+    raw: &Bytes,
+) -> Result<Option<TxHash>> {
+    if zk {
+        return Ok(None)
+    }
+    let hash = keccak256(raw);
+    let tx = provider
+        .get_transaction(hash.to_ethers())
+        .await
+        .wrap_err_with(|| format!("failed to look up transaction {hash}"))?;
+    Ok(tx.map(|_| hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::script::transaction::ZkPaymaster;
+    use alloy_primitives::Address;
+    use ethers_core::types::TransactionRequest;
+
+    #[test]
+    fn can_persist_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("run{QUEUE_FILE_SUFFIX}"));
+        let tx: TypedTransaction = TransactionRequest::new().nonce(1).into();
+        let raw = Bytes::from_static(&[0x71, 0x01]);
+        let hash = TxHash::with_last_byte(1);
+
+        let mut queue = BroadcastQueue::create(&path).unwrap();
+        let zk = ZkTransaction::default();
+        queue
+            .record(QueueEvent::Queued { index: 0, transaction: tx.clone(), zk: Some(zk.clone()) })
+            .unwrap();
+        queue.record(QueueEvent::Queued { index: 1, transaction: tx.clone(), zk: None }).unwrap();
+        let gas_per_pubdata = Some(U256::from(800));
+        queue.record(QueueEvent::Signed { index: 0, raw: raw.clone(), gas_per_pubdata }).unwrap();
+        queue.record(QueueEvent::Sent { index: 0, hash }).unwrap();
+        // the queue isn't closed, like after a crash
+        std::mem::forget(queue);
+
+        let entries = BroadcastQueue::read(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[&0],
+            QueueEntry {
+                index: 0,
+                state: QueueState::Sent,
+                transaction: Some(tx.clone()),
+                zk: Some(zk),
+                raw: Some(raw),
+                gas_per_pubdata,
+                hash: Some(hash),
+            }
+        );
+        assert_eq!(entries[&1].state, QueueState::Queued);
+        assert_eq!(entries[&1].transaction, Some(tx.clone()));
+
+        // reopening keeps the recorded transactions
+        let mut queue = BroadcastQueue::open(&path).unwrap();
+        assert_eq!(queue.entries(), &entries);
+        let raw = Bytes::from_static(&[0x02]);
+        queue.record(QueueEvent::Signed { index: 1, raw, gas_per_pubdata: None }).unwrap();
+        drop(queue);
+
+        let entries = BroadcastQueue::read(&path).unwrap();
+        assert_eq!(entries[&0].state, QueueState::Sent);
+        assert_eq!(entries[&1].state, QueueState::Signed);
+        assert_eq!(entries[&1].raw, Some(Bytes::from_static(&[0x02])));
+
+        // creating a queue discards the previous one
+        let queue = BroadcastQueue::create(&path).unwrap();
+        assert!(queue.entries().is_empty());
+        queue.remove().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn keeps_the_eip712_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("run{QUEUE_FILE_SUFFIX}"));
+        let tx: TypedTransaction = TransactionRequest::new().nonce(1).into();
+        let zk = ZkTransaction {
+            factory_deps: vec![vec![1; 32], vec![2; 64]],
+            paymaster: Some(ZkPaymaster {
+                address: Address::with_last_byte(0x42),
+                input: Bytes::from_static(&[0x8c, 0x5a, 0x34, 0x45]),
+            }),
+        };
+
+        let mut queue = BroadcastQueue::create(&path).unwrap();
+        queue
+            .record(QueueEvent::Queued { index: 0, transaction: tx.clone(), zk: Some(zk.clone()) })
+            .unwrap();
+        let raw = Bytes::from_static(&[0x71, 0x01]);
+        let gas_per_pubdata = Some(U256::from(50_000));
+        queue.record(QueueEvent::Signed { index: 0, raw, gas_per_pubdata }).unwrap();
+        drop(queue);
+
+        let entries = BroadcastQueue::read(&path).unwrap();
+        assert_eq!(entries[&0].zk, Some(zk));
+        assert_eq!(entries[&0].gas_per_pubdata, gas_per_pubdata);
+
+        let json = serde_json::to_value(&entries[&0]).unwrap();
+        assert_eq!(json["zk"]["paymaster"]["input"], "0x8c5a3445");
+        assert_eq!(json["zk"]["factoryDeps"].as_array().unwrap().len(), 2);
+        assert_eq!(json["gasPerPubdata"], "0xc350");
+    }
+}
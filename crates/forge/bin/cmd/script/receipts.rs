@@ -1,5 +1,5 @@
 use super::sequence::ScriptSequence;
-use alloy_primitives::TxHash;
+use alloy_primitives::{TxHash, B256};
 use ethers_core::types::TransactionReceipt;
 use ethers_providers::{Middleware, PendingTransaction};
 use eyre::Result;
@@ -88,6 +88,7 @@ pub async fn clear_pendings(
             Ok(TxStatus::Success(receipt)) => {
                 trace!(tx_hash=?tx_hash, "received tx receipt");
                 deployment_sequence.remove_pending(receipt.transaction_hash.to_alloy());
+                errors.extend(unmet_event_expectations(deployment_sequence, &receipt));
                 receipts.push(receipt);
             }
             Ok(TxStatus::Revert(receipt)) => {
@@ -126,6 +127,37 @@ pub async fn clear_pendings(
     Ok(())
 }
 
+/// Checks the logs of a receipt against the events its transaction was expected to emit, returning
+/// an error message for every expectation that isn't met.
+fn unmet_event_expectations(
+    deployment_sequence: &ScriptSequence,
+    receipt: &TransactionReceipt,
+) -> Vec<String> {
+    let hash = receipt.transaction_hash.to_alloy();
+    let Some(tx) = deployment_sequence.transactions.iter().find(|tx| tx.hash == Some(hash)) else {
+        return vec![]
+    };
+
+    let logs = receipt
+        .logs
+        .iter()
+        .map(|log| {
+            let topics = log.topics.iter().map(|topic| topic.to_alloy()).collect::<Vec<B256>>();
+            (log.address.to_alloy(), topics, log.data.to_vec())
+        })
+        .collect::<Vec<_>>();
+
+    tx.expected_events
+        .iter()
+        .filter(|expected| {
+            !expected.is_satisfied_by(
+                logs.iter().map(|(address, topics, data)| (*address, &topics[..], &data[..])),
+            )
+        })
+        .map(|expected| format!("Transaction {hash:?} was expected to emit {expected}"))
+        .collect()
+}
+
 /// Checks the status of a txhash by first polling for a receipt, then for
 /// mempool inclusion. Returns the tx hash, and a status
 async fn check_tx_status(
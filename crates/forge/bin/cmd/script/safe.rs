@@ -0,0 +1,367 @@
+//! Safe (multisig) bundles of the script transactions, for deployments that go through a Safe
+//! instead of being broadcast by the script.
+
+use super::{sequence::ScriptSequence, ScriptArgs};
+use alloy_primitives::{address, Address, Bytes, B256, U256};
+use alloy_sol_types::{eip712_domain, sol, SolCall, SolStruct};
+use ethers_core::types::TransactionRequest;
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::{ContextCompat, Result, WrapErr};
+use foundry_common::{
+    fs,
+    provider::ethers::try_get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_wallets::WalletSigner;
+use serde::Serialize;
+use std::{collections::HashMap, path::PathBuf};
+
+/// The `MultiSendCallOnly` contract of the canonical Safe v1.3.0 deployments.
+const MULTI_SEND_CALL_ONLY: Address = address!("40a2accbd92bca938b02010e17a5b8929b49130d");
+
+/// The `MultiSendCallOnly` contract of the Safe v1.3.0 deployments on zkSync Era.
+const ERA_MULTI_SEND_CALL_ONLY: Address = address!("f220d3b4dfb23c4ade8c88e526c1353abacbc38f");
+
+/// The version of the Transaction Builder app the bundles are written for.
+const TX_BUILDER_VERSION: &str = "1.16.5";
+
+sol! {
+    interface ISafe {
+        function nonce() external view returns (uint256);
+    }
+
+    interface IMultiSend {
+        function multiSend(bytes transactions) external payable;
+    }
+
+    struct SafeTx {
+        address to;
+        uint256 value;
+        bytes data;
+        uint8 operation;
+        uint256 safeTxGas;
+        uint256 baseGas;
+        uint256 gasPrice;
+        address gasToken;
+        address refundReceiver;
+        uint256 nonce;
+    }
+}
+
+/// A Safe Transaction Builder bundle.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SafeBundle {
+    version: &'static str,
+    chain_id: String,
+    created_at: u128,
+    meta: SafeBundleMeta,
+    transactions: Vec<SafeBundleTransaction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SafeBundleMeta {
+    name: String,
+    description: String,
+    tx_builder_version: &'static str,
+    created_from_safe_address: Address,
+    created_from_owner_address: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SafeBundleTransaction {
+    to: Address,
+    value: String,
+    data: Bytes,
+    contract_method: Option<()>,
+    contract_inputs_values: Option<()>,
+}
+
+/// A call of the Safe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SafeCall {
+    to: Address,
+    value: U256,
+    data: Bytes,
+}
+
+impl ScriptArgs {
+    /// Writes the transactions of the sequence as a Safe Transaction Builder bundle next to the
+    /// sequence, and proposes them to the Safe Transaction Service with `--safe-propose`.
+    pub async fn send_to_safe(
+        &self,
+        sequence: &ScriptSequence,
+        safe: Address,
+        signers: &HashMap<Address, WalletSigner>,
+    ) -> Result<()> {
+        let calls = safe_calls(sequence, safe)?;
+        if calls.is_empty() {
+            return Ok(())
+        }
+
+        let bundle = SafeBundle {
+            version: "1.0",
+            chain_id: sequence.chain.to_string(),
+            created_at: now_millis(),
+            meta: SafeBundleMeta {
+                name: format!("forge script {}", self.sig),
+                description: format!("{} transaction(s) of {}", calls.len(), self.path),
+                tx_builder_version: TX_BUILDER_VERSION,
+                created_from_safe_address: safe,
+                created_from_owner_address: String::new(),
+            },
+            transactions: calls
+                .iter()
+                .map(|call| SafeBundleTransaction {
+                    to: call.to,
+                    value: call.value.to_string(),
+                    data: call.data.clone(),
+                    contract_method: None,
+                    contract_inputs_values: None,
+                })
+                .collect(),
+        };
+        let path = bundle_path(sequence);
+        fs::write_json_file(&path, &bundle)?;
+        shell::println(format!(
+            "\nWrote the {} transaction(s) for the Safe {safe} on chain {} to {}",
+            calls.len(),
+            sequence.chain,
+            path.display()
+        ))?;
+
+        if self.safe_propose {
+            self.propose_to_safe(sequence, safe, &calls, signers).await?;
+        }
+        Ok(())
+    }
+
+    /// Proposes the calls as a single Safe transaction to the Safe Transaction Service, batched
+    /// through `MultiSendCallOnly` if there are several.
+    async fn propose_to_safe(
+        &self,
+        sequence: &ScriptSequence,
+        safe: Address,
+        calls: &[SafeCall],
+        signers: &HashMap<Address, WalletSigner>,
+    ) -> Result<()> {
+        let rpc = sequence
+            .transactions
+            .front()
+            .and_then(|tx| tx.rpc.clone())
+            .wrap_err("Proposing to the Safe requires an RPC URL.")?;
+        let provider = try_get_http_provider(&rpc)?;
+        let chain = provider.get_chainid().await?.as_u64();
+        // zkSync Era chains, whichever their chain ID, support `zks_L1ChainId`
+        let is_era =
+            provider.request::<_, ethers_core::types::U64>("zks_L1ChainId", ()).await.is_ok();
+
+        let service_url = self
+            .safe_service_url
+            .clone()
+            .or_else(|| default_service_url(chain).map(str::to_string))
+            .wrap_err_with(|| {
+                format!(
+                    "There's no known Safe Transaction Service for chain {chain}, pass it with \
+                     --safe-service-url."
+                )
+            })?;
+        let proposer = self.evm_opts.sender.wrap_err(
+            "Proposing to the Safe requires the wallet of an owner of it, pass it with --sender.",
+        )?;
+        let signer = signers.get(&proposer).wrap_err_with(|| {
+            format!("No associated wallet for the proposer {proposer}, pass its wallet options.")
+        })?;
+
+        let (to, value, data, operation) = match calls {
+            [call] => (call.to, call.value, call.data.clone(), 0u8),
+            calls => {
+                let multi_send = self.safe_multi_send.unwrap_or_else(|| multi_send_address(is_era));
+                (multi_send, U256::ZERO, multi_send_calldata(calls), 1u8)
+            }
+        };
+
+        let nonce_call =
+            TransactionRequest::new().to(safe.to_ethers()).data(ISafe::nonceCall {}.abi_encode());
+        let output = provider
+            .call(&nonce_call.into(), None)
+            .await
+            .wrap_err_with(|| format!("Failed to read the nonce of the Safe {safe}"))?;
+        let nonce = ISafe::nonceCall::abi_decode_returns(&output, true)?._0;
+
+        let safe_tx = SafeTx {
+            to,
+            value,
+            data: data.to_vec().into(),
+            operation,
+            safeTxGas: U256::ZERO,
+            baseGas: U256::ZERO,
+            gasPrice: U256::ZERO,
+            gasToken: Address::ZERO,
+            refundReceiver: Address::ZERO,
+            nonce,
+        };
+        let domain = eip712_domain! { chain_id: chain, verifying_contract: safe };
+        let safe_tx_hash: B256 = safe_tx.eip712_signing_hash(&domain);
+
+        // an `eth_sign` signature of the hash, which the Safe tells apart by `v + 4`
+        let mut signature = signer.sign_message(safe_tx_hash.as_slice()).await?.to_vec();
+        if let Some(v) = signature.last_mut() {
+            *v += 4;
+        }
+
+        let proposal = serde_json::json!({
+            "to": to,
+            "value": value.to_string(),
+            "data": data,
+            "operation": operation,
+            "safeTxGas": "0",
+            "baseGas": "0",
+            "gasPrice": "0",
+            "gasToken": Address::ZERO,
+            "refundReceiver": Address::ZERO,
+            "nonce": nonce.to_string(),
+            "contractTransactionHash": safe_tx_hash,
+            "sender": proposer,
+            "signature": Bytes::from(signature),
+            "origin": "forge script",
+        });
+        let url = format!(
+            "{}/api/v1/safes/{safe}/multisig-transactions/",
+            service_url.trim_end_matches('/')
+        );
+        let response = reqwest::Client::new().post(&url).json(&proposal).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            eyre::bail!("The Safe Transaction Service rejected the proposal ({status}): {body}");
+        }
+
+        shell::println(format!(
+            "Proposed the Safe transaction {safe_tx_hash} with nonce {nonce} to {service_url}"
+        ))?;
+        Ok(())
+    }
+}
+
+/// Returns the calls of the Safe making up the transactions of the sequence, which must all be
+/// sent by the Safe to existing contracts.
+fn safe_calls(sequence: &ScriptSequence, safe: Address) -> Result<Vec<SafeCall>> {
+    let mut calls = Vec::with_capacity(sequence.transactions.len());
+    for (i, tx) in sequence.transactions.iter().enumerate() {
+        let typed_tx = tx.typed_tx();
+        let from = typed_tx.from().map(|from| from.to_alloy());
+        if from != Some(safe) {
+            eyre::bail!(
+                "Transaction #{i} is sent by {}, not by the Safe {safe}. Broadcast the \
+                 transactions of the script from the Safe, e.g. with `vm.startBroadcast({safe})`.",
+                from.map_or_else(|| "an unknown sender".to_string(), |from| from.to_string())
+            );
+        }
+        if tx.zk.as_ref().is_some_and(|zk| !zk.factory_deps.is_empty()) {
+            eyre::bail!(
+                "Transaction #{i} publishes EraVM bytecodes, which a Safe transaction can't. \
+                 Publish them first, e.g. by deploying the contracts once from an account."
+            );
+        }
+        let Some(to) = typed_tx.to().and_then(|to| to.as_address()) else {
+            eyre::bail!(
+                "Transaction #{i} deploys a contract, which a Safe can't do directly. Deploy it \
+                 through the CREATE2 factory instead, e.g. with `new Contract{{salt: salt}}()`."
+            );
+        };
+        calls.push(SafeCall {
+            to: to.to_alloy(),
+            value: typed_tx.value().map(|value| value.to_alloy()).unwrap_or_default(),
+            data: typed_tx.data().map(|data| data.0.clone().into()).unwrap_or_default(),
+        });
+    }
+    Ok(calls)
+}
+
+/// Returns the calldata of `multiSend` executing the calls in order.
+fn multi_send_calldata(calls: &[SafeCall]) -> Bytes {
+    let mut transactions = Vec::new();
+    for call in calls {
+        // operation, to, value, data length and data, packed
+        transactions.push(0u8);
+        transactions.extend_from_slice(call.to.as_slice());
+        transactions.extend_from_slice(&call.value.to_be_bytes::<32>());
+        transactions.extend_from_slice(&U256::from(call.data.len()).to_be_bytes::<32>());
+        transactions.extend_from_slice(&call.data);
+    }
+    IMultiSend::multiSendCall { transactions: transactions.into() }.abi_encode().into()
+}
+
+/// Returns the `MultiSendCallOnly` deployment of a zkSync Era chain if `is_era`, of an EVM chain
+/// otherwise.
+fn multi_send_address(is_era: bool) -> Address {
+    if is_era {
+        ERA_MULTI_SEND_CALL_ONLY
+    } else {
+        MULTI_SEND_CALL_ONLY
+    }
+}
+
+/// Returns the Safe Transaction Service of the chain, if it has one.
+fn default_service_url(chain: u64) -> Option<&'static str> {
+    Some(match chain {
+        1 => "https://safe-transaction-mainnet.safe.global",
+        10 => "https://safe-transaction-optimism.safe.global",
+        100 => "https://safe-transaction-gnosis-chain.safe.global",
+        137 => "https://safe-transaction-polygon.safe.global",
+        324 => "https://safe-transaction-zksync.safe.global",
+        8453 => "https://safe-transaction-base.safe.global",
+        42161 => "https://safe-transaction-arbitrum.safe.global",
+        11155111 => "https://safe-transaction-sepolia.safe.global",
+        _ => return None,
+    })
+}
+
+/// Returns the path of the bundle of the sequence, next to it, e.g. `run-safe.json`.
+fn bundle_path(sequence: &ScriptSequence) -> PathBuf {
+    let file_name = sequence.path.file_name().unwrap_or_default().to_string_lossy();
+    let name = file_name.trim_end_matches("-latest.json");
+    sequence.path.with_file_name(format!("{name}-safe.json"))
+}
+
+fn now_millis() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_multi_send() {
+        let call = SafeCall {
+            to: Address::repeat_byte(0x11),
+            value: U256::from(5),
+            data: Bytes::from_static(&[0xab, 0xcd]),
+        };
+        let calldata = multi_send_calldata(&[call.clone(), call]);
+        let decoded = IMultiSend::multiSendCall::abi_decode(&calldata, true).unwrap();
+
+        let packed = decoded.transactions;
+        assert_eq!(packed.len(), 2 * (1 + 20 + 32 + 32 + 2));
+        assert_eq!(packed[0], 0);
+        assert_eq!(&packed[1..21], Address::repeat_byte(0x11).as_slice());
+        assert_eq!(U256::from_be_slice(&packed[21..53]), U256::from(5));
+        assert_eq!(U256::from_be_slice(&packed[53..85]), U256::from(2));
+        assert_eq!(&packed[85..87], &[0xab, 0xcd]);
+    }
+
+    #[test]
+    fn picks_era_multi_send() {
+        assert_eq!(multi_send_address(true), ERA_MULTI_SEND_CALL_ONLY);
+        assert_eq!(multi_send_address(false), MULTI_SEND_CALL_ONLY);
+    }
+}
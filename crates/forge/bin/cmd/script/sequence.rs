@@ -2,6 +2,7 @@ use super::NestedValue;
 use crate::cmd::{
     init::get_commit_hash,
     script::{
+        queue::QUEUE_FILE_SUFFIX,
         transaction::{wrapper, AdditionalContract, TransactionWithMetadata},
         verify::VerifyBundle,
     },
@@ -12,7 +13,9 @@ use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRece
 use eyre::{ContextCompat, Result, WrapErr};
 use foundry_cli::utils::now;
 use foundry_common::{
-    fs, shell,
+    fs,
+    redact::redact,
+    shell,
     types::{ToAlloy, ToEthers},
     SELECTOR_LEN,
 };
@@ -157,9 +160,9 @@ impl ScriptSequence {
 
         // broadcast folder writes
         //../run-latest.json
-        let mut writer = BufWriter::new(fs::create_file(&self.path)?);
-        serde_json::to_writer_pretty(&mut writer, &self)?;
-        writer.flush()?;
+        // the broadcast log is meant to be committed, so scrub any registered secrets from it
+        let json = serde_json::to_string_pretty(&self)?;
+        fs::write(&self.path, redact(&json).as_bytes())?;
         //../run-[timestamp].json
         fs::copy(&self.path, self.path.with_file_name(&ts_name))?;
 
@@ -245,6 +248,21 @@ impl ScriptSequence {
         Ok((broadcast, cache))
     }
 
+    /// Gets the path of the broadcast queue database in the format
+    /// ./cache/[contract_filename]/[chain_id]/[sig]-queue.sqlite
+    ///
+    /// The queue is shared by the broadcasted and `dry-run/` sequences, since a resumed
+    /// deployment may be loaded from either.
+    pub fn queue_path(&self) -> PathBuf {
+        let mut dir = self.sensitive_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        if dir.ends_with(DRY_RUN_DIR) {
+            dir.pop();
+        }
+        let file_name = self.sensitive_path.file_name().unwrap_or_default().to_string_lossy();
+        let name = file_name.strip_suffix("-latest.json").unwrap_or(&file_name);
+        dir.join(format!("{name}{QUEUE_FILE_SUFFIX}"))
+    }
+
     /// Checks that there is an Etherscan key for the chain id of this sequence.
     pub fn verify_preflight_check(&self, config: &Config, verify: &VerifyBundle) -> Result<()> {
         if config.get_etherscan_api_key(Some(self.chain.into())).is_none() &&
@@ -268,7 +286,7 @@ impl ScriptSequence {
     ) -> Result<()> {
         trace!(target: "script", "verifying {} contracts [{}]", verify.known_contracts.len(), self.chain);
 
-        verify.set_chain(config, self.chain.into());
+        verify.set_chain(config, self.chain.into())?;
 
         if verify.etherscan.has_key() ||
             verify.verifier.verifier != VerificationProviderType::Etherscan
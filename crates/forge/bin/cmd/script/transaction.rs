@@ -13,7 +13,14 @@ use foundry_common::{
     types::{ToAlloy, ToEthers},
     SELECTOR_LEN,
 };
-use foundry_evm::{constants::DEFAULT_CREATE2_DEPLOYER, traces::CallTraceDecoder};
+use foundry_evm::{
+    constants::DEFAULT_CREATE2_DEPLOYER,
+    inspectors::cheatcodes::ExpectedBroadcastEvent,
+    traces::{CallTraceDecoder, Create2Deployment},
+};
+use foundry_zksync_core::{
+    convert::ConvertH160, vm::ZkPaymasterData, ZkTransactionMetadata, CONTRACT_DEPLOYER_ADDRESS,
+};
 use itertools::Itertools;
 use revm_inspectors::tracing::types::CallKind;
 use serde::{Deserialize, Serialize};
@@ -29,10 +36,45 @@ pub struct AdditionalContract {
     pub init_code: Bytes,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ZkTransaction {
     pub factory_deps: Vec<Vec<u8>>,
+    /// The paymaster paying the fees of the transaction, set with `zkUsePaymaster`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<ZkPaymaster>,
+}
+
+/// The paymaster of a zkSync Era transaction, sent as its `paymasterParams`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkPaymaster {
+    pub address: Address,
+    pub input: Bytes,
+}
+
+impl From<ZkTransactionMetadata> for ZkTransaction {
+    fn from(zk_tx: ZkTransactionMetadata) -> Self {
+        Self {
+            factory_deps: zk_tx.factory_deps,
+            paymaster: zk_tx.paymaster.map(|paymaster| ZkPaymaster {
+                address: paymaster.address,
+                input: paymaster.input,
+            }),
+        }
+    }
+}
+
+impl From<ZkTransaction> for ZkTransactionMetadata {
+    fn from(zk: ZkTransaction) -> Self {
+        Self {
+            factory_deps: zk.factory_deps,
+            paymaster: zk.paymaster.map(|paymaster| ZkPaymasterData {
+                address: paymaster.address,
+                input: paymaster.input,
+            }),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -56,6 +98,12 @@ pub struct TransactionWithMetadata {
     pub is_fixed_gas_limit: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub zk: Option<ZkTransaction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_events: Vec<ExpectedBroadcastEvent>,
+    /// The salt and deployer of the contract deployed with `CREATE2`, through the default
+    /// `CREATE2` deployer or the zkSync Era `ContractDeployer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create2: Option<Create2Deployment>,
 }
 
 fn default_string() -> Option<String> {
@@ -151,6 +199,12 @@ impl TransactionWithMetadata {
                 metadata
                     .set_call(to.to_alloy(), local_contracts, decoder)
                     .wrap_err("Could not decode transaction type.")?;
+                if to.to_alloy() == CONTRACT_DEPLOYER_ADDRESS.to_address() {
+                    let from = metadata.transaction.from().copied().unwrap_or_default();
+                    metadata.create2 = metadata.transaction.data().and_then(|data| {
+                        Create2Deployment::from_contract_deployer_call(from.to_alloy(), data)
+                    });
+                }
             }
         } else if metadata.transaction.to().is_none() {
             metadata.set_create(
@@ -199,6 +253,9 @@ impl TransactionWithMetadata {
         self.contract_address = Some(address);
 
         let Some(data) = self.transaction.data() else { return Ok(()) };
+        if is_create2 {
+            self.create2 = Create2Deployment::from_factory_call(DEFAULT_CREATE2_DEPLOYER, data);
+        }
         let Some(info) = info else { return Ok(()) };
 
         // `create2` transactions are prefixed by a 32 byte salt.
@@ -511,3 +568,33 @@ pub mod wrapper {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn can_roundtrip_zk_transactions() {
+        let paymaster = ZkPaymasterData {
+            address: address!("5FbDB2315678afecb367f032d93F642f64180aa3"),
+            input: Bytes::from_static(&[0x8c, 0x5a, 0x34, 0x45]),
+        };
+        let zk = ZkTransaction::from(ZkTransactionMetadata {
+            factory_deps: vec![vec![1; 32]],
+            paymaster: Some(paymaster.clone()),
+        });
+
+        let json = serde_json::to_value(&zk).unwrap();
+        assert_eq!(json["paymaster"]["input"], "0x8c5a3445");
+        let zk: ZkTransaction = serde_json::from_value(json).unwrap();
+        let zk_tx = ZkTransactionMetadata::from(zk);
+        assert_eq!(zk_tx.factory_deps, vec![vec![1; 32]]);
+        assert_eq!(zk_tx.paymaster, Some(paymaster));
+
+        // the broadcasts of transactions without a paymaster can still be resumed
+        let zk: ZkTransaction = serde_json::from_str(r#"{"factoryDeps":[]}"#).unwrap();
+        assert!(zk.paymaster.is_none());
+        assert!(!serde_json::to_string(&zk).unwrap().contains("paymaster"));
+    }
+}
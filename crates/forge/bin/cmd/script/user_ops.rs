@@ -0,0 +1,232 @@
+//! Submission of the ERC-4337 user operations recorded with `vm.broadcastUserOp`.
+//!
+//! The operations are submitted on the chain of the fork they were recorded on, and the ones
+//! submitted on a chain are saved next to the transactions of the script on that chain, in
+//! `broadcast/<script>/<chain id>/<sig>-user-ops-latest.json`.
+
+use super::{sequence::ScriptSequence, ScriptArgs};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use ethers_providers::Middleware;
+use eyre::{ContextCompat, Result, WrapErr};
+use forge::inspectors::cheatcodes::BroadcastableUserOperation;
+use foundry_cli::utils::now;
+use foundry_common::{
+    erc4337::{build_user_operation, Bundler, UserOperationRequest},
+    fs,
+    provider::ethers::try_get_http_provider,
+    shell,
+    types::ToAlloy,
+};
+use foundry_compilers::ArtifactId;
+use foundry_config::Config;
+use foundry_wallets::WalletSigner;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How long to wait for each user operation to be included.
+const USER_OP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The placeholder of `bundler_url` replaced with the chain id of the operations submitted
+/// through it.
+const CHAIN_ID_PLACEHOLDER: &str = "{chain_id}";
+
+/// A user operation submitted by a script.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentUserOperation {
+    /// The hash of the operation.
+    pub hash: B256,
+    /// The `EntryPoint` the operation was submitted to.
+    pub entry_point: Address,
+    /// The smart account sending the operation.
+    pub sender: Address,
+    /// The account owner signing the operation.
+    pub signer: Address,
+    /// The nonce of the operation.
+    pub nonce: U256,
+    /// The calldata the account is called with.
+    pub call_data: Bytes,
+    /// Whether the account call succeeded.
+    pub success: bool,
+    /// The hash of the bundle transaction which included the operation.
+    pub transaction_hash: B256,
+}
+
+/// The user operations submitted by a script on a chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationsSequence {
+    pub user_operations: Vec<SentUserOperation>,
+    pub chain: u64,
+    pub timestamp: u64,
+}
+
+impl UserOperationsSequence {
+    /// Returns the path of the user operations of the script `sig` of `target` on `chain`, next
+    /// to its broadcasted transactions.
+    pub fn path(config: &Config, sig: &str, target: &ArtifactId, chain: u64) -> Result<PathBuf> {
+        let (path, _) = ScriptSequence::get_paths(
+            &config.broadcast,
+            &config.cache_path,
+            sig,
+            target,
+            chain,
+            true,
+        )?;
+        Ok(user_ops_file(&path, "latest"))
+    }
+
+    /// Saves the operations to `path`, and to a copy of the current timestamp.
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        self.timestamp = now().as_secs();
+        fs::write_json_file(path, self)?;
+        std::fs::copy(path, user_ops_file(path, &self.timestamp.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Returns the file of the user operations next to the transactions of `run`, the
+/// `<sig>-latest.json` of a script, with the suffix `suffix`.
+fn user_ops_file(run: &Path, suffix: &str) -> PathBuf {
+    let name = run.file_name().unwrap_or_default().to_string_lossy();
+    let sig = name.strip_suffix("-latest.json").unwrap_or(&name);
+    run.with_file_name(format!("{sig}-user-ops-{suffix}.json"))
+}
+
+/// Returns the URL of the bundler of the chain `chain_id`.
+fn bundler_url(bundler_url: &str, chain_id: u64) -> String {
+    bundler_url.replace(CHAIN_ID_PLACEHOLDER, &chain_id.to_string())
+}
+
+impl ScriptArgs {
+    /// Signs the user operations recorded by the script and submits them through the bundler of
+    /// the chain of the fork they were recorded on, one at a time, waiting for each of them to be
+    /// included. The operations of each chain are saved as they're included.
+    pub async fn send_user_operations(
+        &self,
+        user_operations: &[BroadcastableUserOperation],
+        config: &Config,
+        target: &ArtifactId,
+        fork_url: Option<&str>,
+        signers: &HashMap<Address, WalletSigner>,
+    ) -> Result<()> {
+        if user_operations.is_empty() {
+            return Ok(())
+        }
+
+        if !self.broadcast {
+            shell::println(format!(
+                "\n{} user operation(s) will be submitted through the bundler with --broadcast.",
+                user_operations.len()
+            ))?;
+            return Ok(())
+        }
+
+        let bundler_url_template = config
+            .bundler_url
+            .as_deref()
+            .wrap_err("The script records user operations, but no `bundler_url` is configured.")?;
+
+        // the operations are submitted in the order they were recorded, each on its chain
+        let mut sequences = HashMap::<u64, (PathBuf, UserOperationsSequence)>::new();
+        let mut providers = HashMap::new();
+
+        shell::println("\n###\nSending user operations...")?;
+        for (i, op) in user_operations.iter().enumerate() {
+            let rpc = op
+                .rpc
+                .as_deref()
+                .or(fork_url)
+                .wrap_err("Sending user operations requires `--fork-url`.")?;
+            if !providers.contains_key(rpc) {
+                let provider = try_get_http_provider(rpc)?;
+                let chain = provider.get_chainid().await?.as_u64();
+                providers.insert(rpc.to_string(), (provider, chain));
+            }
+            let (provider, chain) = &providers[rpc];
+
+            let signer = signers.get(&op.signer).wrap_err_with(|| {
+                format!("No associated wallet for the signer of user operation #{i}: {}", op.signer)
+            })?;
+            let bundler = Bundler::new(&bundler_url(bundler_url_template, *chain), op.entry_point)?;
+            let request = UserOperationRequest {
+                sender: op.account,
+                call_data: op.call_data.clone(),
+                ..Default::default()
+            };
+            let user_op = build_user_operation(provider, &bundler, signer, request)
+                .await
+                .wrap_err_with(|| format!("Failed to build user operation #{i}"))?;
+
+            let hash = bundler.send(&user_op).await?;
+            let receipt = bundler.wait_for_receipt(hash, USER_OP_TIMEOUT).await?;
+            let transaction_hash = receipt.receipt.transaction_hash.to_alloy();
+
+            let (path, sequence) = match sequences.entry(*chain) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let path = UserOperationsSequence::path(config, &self.sig, target, *chain)?;
+                    let sequence = UserOperationsSequence { chain: *chain, ..Default::default() };
+                    entry.insert((path, sequence))
+                }
+            };
+            sequence.user_operations.push(SentUserOperation {
+                hash,
+                entry_point: op.entry_point,
+                sender: op.account,
+                signer: op.signer,
+                nonce: user_op.nonce,
+                call_data: op.call_data.clone(),
+                success: receipt.success,
+                transaction_hash,
+            });
+            sequence.save(path)?;
+
+            if !receipt.success {
+                let reason = receipt.reason.map(|reason| format!(": {reason}")).unwrap_or_default();
+                eyre::bail!("User operation #{i} ({hash}) from {} failed{reason}", op.account);
+            }
+
+            shell::println(format!(
+                "[Success] UserOpHash: {hash} (included in {transaction_hash:?})"
+            ))?;
+        }
+
+        for (path, _) in sequences.values() {
+            shell::println(format!("\nUser operations saved to: {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_user_operations_next_to_the_transactions() {
+        let run = Path::new("broadcast/Deploy.s.sol/1/run-latest.json");
+        assert_eq!(
+            user_ops_file(run, "latest"),
+            Path::new("broadcast/Deploy.s.sol/1/run-user-ops-latest.json")
+        );
+        assert_eq!(
+            user_ops_file(run, "1700000000"),
+            Path::new("broadcast/Deploy.s.sol/1/run-user-ops-1700000000.json")
+        );
+    }
+
+    #[test]
+    fn uses_the_bundler_of_each_chain() {
+        assert_eq!(
+            bundler_url("https://bundler.example.com/v1/{chain_id}/rpc", 10),
+            "https://bundler.example.com/v1/10/rpc"
+        );
+        assert_eq!(bundler_url("http://localhost:4337", 10), "http://localhost:4337");
+    }
+}
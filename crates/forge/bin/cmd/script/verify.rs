@@ -3,6 +3,7 @@ use crate::cmd::{
     verify::{VerifierArgs, VerifyArgs},
 };
 use alloy_primitives::Address;
+use eyre::Result;
 use foundry_cli::opts::{EtherscanOpts, ProjectPathsArgs};
 use foundry_common::ContractsByArtifact;
 use foundry_compilers::{info::ContractInfo, Project};
@@ -18,6 +19,8 @@ pub struct VerifyBundle {
     pub etherscan: EtherscanOpts,
     pub retry: RetryArgs,
     pub verifier: VerifierArgs,
+    /// The verifier arguments of the command line, the chain's `[etherscan]` entry applies to
+    pub cli_verifier: VerifierArgs,
     pub via_ir: bool,
 }
 
@@ -53,17 +56,27 @@ impl VerifyBundle {
             etherscan: Default::default(),
             project_paths,
             retry,
+            cli_verifier: verifier.clone(),
             verifier,
             via_ir,
         }
     }
 
-    /// Configures the chain and sets the etherscan key, if available
-    pub fn set_chain(&mut self, config: &Config, chain: Chain) {
+    /// Configures the chain and sets the etherscan key and verifier, if available
+    ///
+    /// The verifier of the previous chain is reset, so it doesn't carry over to a chain without a
+    /// `verifier` of its own.
+    pub fn set_chain(&mut self, config: &Config, chain: Chain) -> Result<()> {
         // If dealing with multiple chains, we need to be able to change inbetween the config
         // chain_id.
-        self.etherscan.key = config.get_etherscan_api_key(Some(chain));
+        let etherscan_config = config.get_etherscan_config_with_chain(Some(chain))?;
+        self.verifier = self.cli_verifier.clone();
+        if let Some(etherscan_config) = &etherscan_config {
+            self.verifier.apply_etherscan_config(etherscan_config);
+        }
+        self.etherscan.key = etherscan_config.map(|c| c.key);
         self.etherscan.chain = Some(chain);
+        Ok(())
     }
 
     /// Given a `VerifyBundle` and contract details, it tries to generate a valid `VerifyArgs` to
@@ -101,6 +114,7 @@ impl VerifyBundle {
                     address: contract_address,
                     contract,
                     compiler_version: Some(version.to_string()),
+                    zksolc_version: None,
                     constructor_args: Some(hex::encode(constructor_args)),
                     constructor_args_path: None,
                     num_of_optimizations: self.num_of_optimizations,
@@ -124,3 +138,44 @@ impl VerifyBundle {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::verify::provider::VerificationProviderType;
+    use foundry_config::{
+        figment::{providers::Toml, Figment},
+        NamedChain,
+    };
+
+    #[test]
+    fn resets_verifier_on_chain_change() {
+        let config = Config::from_provider(Figment::from(Config::default()).merge(Toml::string(
+            r#"
+            [etherscan]
+            zksync-era = { key = "", chain = 324, url = "https://zksync2-mainnet-explorer.zksync.io/contract_verification", verifier = "zksync" }
+            "#,
+        )));
+        let mut bundle = VerifyBundle {
+            num_of_optimizations: None,
+            known_contracts: Default::default(),
+            project_paths: Default::default(),
+            etherscan: Default::default(),
+            retry: Default::default(),
+            verifier: Default::default(),
+            cli_verifier: Default::default(),
+            via_ir: false,
+        };
+
+        bundle.set_chain(&config, NamedChain::ZkSync.into()).unwrap();
+        assert_eq!(bundle.verifier.verifier, VerificationProviderType::ZkSync);
+        assert_eq!(
+            bundle.verifier.verifier_url.as_deref(),
+            Some("https://zksync2-mainnet-explorer.zksync.io/contract_verification")
+        );
+
+        bundle.set_chain(&config, NamedChain::Mainnet.into()).unwrap();
+        assert_eq!(bundle.verifier.verifier, VerificationProviderType::Etherscan);
+        assert_eq!(bundle.verifier.verifier_url, None);
+    }
+}
@@ -3,7 +3,7 @@ use alloy_primitives::U256;
 use clap::{builder::RangedU64ValueParser, Parser, ValueHint};
 use eyre::{Context, Result};
 use forge::result::{SuiteTestResult, TestKindReport, TestOutcome};
-use foundry_cli::utils::STATIC_FUZZ_SEED;
+use foundry_cli::utils::{LoadConfig, STATIC_FUZZ_SEED};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::{
@@ -17,10 +17,13 @@ use std::{
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 
+/// The default snapshot file of `forge snapshot --zksync`.
+const DEFAULT_ZK_SNAPSHOT: &str = ".zk-gas-snapshot";
+
 /// A regex that matches a basic snapshot entry like
 /// `Test:testDeposit() (gas: 58804)`
 pub static RE_BASIC_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+))|(runs:\s*(?P<invruns>\d+),\s*calls:\s*(?P<calls>\d+),\s*reverts:\s*(?P<reverts>\d+)))\)").unwrap()
+    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(ergs:\s*(?P<ergs>\d+),\s*pubdata:\s*(?P<pubdata>\d+))|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+))|(runs:\s*(?P<invruns>\d+),\s*calls:\s*(?P<calls>\d+),\s*reverts:\s*(?P<reverts>\d+)))\)").unwrap()
 });
 
 /// CLI arguments for `forge snapshot`.
@@ -28,7 +31,8 @@ pub static RE_BASIC_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
 pub struct SnapshotArgs {
     /// Output a diff against a pre-existing snapshot.
     ///
-    /// By default, the comparison is done with .gas-snapshot.
+    /// By default, the comparison is done with .gas-snapshot, or .zk-gas-snapshot with
+    /// `--zksync`.
     #[clap(
         conflicts_with = "snap",
         long,
@@ -41,7 +45,8 @@ pub struct SnapshotArgs {
     ///
     /// Outputs a diff if the snapshots do not match.
     ///
-    /// By default, the comparison is done with .gas-snapshot.
+    /// By default, the comparison is done with .gas-snapshot, or .zk-gas-snapshot with
+    /// `--zksync`.
     #[clap(
         conflicts_with = "diff",
         long,
@@ -56,13 +61,11 @@ pub struct SnapshotArgs {
     format: Option<Format>,
 
     /// Output file for the snapshot.
-    #[clap(
-        long,
-        default_value = ".gas-snapshot",
-        value_hint = ValueHint::FilePath,
-        value_name = "FILE",
-    )]
-    snap: PathBuf,
+    ///
+    /// By default, the snapshot is written to .gas-snapshot, or to .zk-gas-snapshot with
+    /// `--zksync`, recording the ergs and pubdata of the ZK-VM executions of the tests.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "FILE")]
+    snap: Option<PathBuf>,
 
     /// Tolerates gas deviations up to the specified percentage.
     #[clap(
@@ -97,24 +100,27 @@ impl SnapshotArgs {
         // Set fuzz seed so gas snapshots are deterministic
         self.test.fuzz_seed = Some(U256::from_be_bytes(STATIC_FUZZ_SEED));
 
+        // The ZK snapshots record the L2 costs, separately from the EVM gas snapshots
+        let zk = self.test.load_config().zksync;
+        let default_snap = if zk { DEFAULT_ZK_SNAPSHOT } else { ".gas-snapshot" };
+        let snap = self.snap.unwrap_or_else(|| default_snap.into());
+
         let outcome = self.test.execute_tests().await?;
         outcome.ensure_ok()?;
-        let tests = self.config.apply(outcome);
+        let tests = self.config.apply(outcome, zk);
 
         if let Some(path) = self.diff {
-            let snap = path.as_ref().unwrap_or(&self.snap);
-            let snaps = read_snapshot(snap)?;
-            diff(tests, snaps)?;
+            let snaps = read_snapshot(path.as_ref().unwrap_or(&snap))?;
+            diff(tests, snaps, zk)?;
         } else if let Some(path) = self.check {
-            let snap = path.as_ref().unwrap_or(&self.snap);
-            let snaps = read_snapshot(snap)?;
-            if check(tests, snaps, self.tolerance) {
+            let snaps = read_snapshot(path.as_ref().unwrap_or(&snap))?;
+            if check(tests, snaps, self.tolerance, zk) {
                 std::process::exit(0)
             } else {
                 std::process::exit(1)
             }
         } else {
-            write_to_snapshot_file(&tests, self.snap, self.format)?;
+            write_to_snapshot_file(&tests, snap, self.format, zk)?;
         }
         Ok(())
     }
@@ -172,16 +178,18 @@ impl SnapshotConfig {
         true
     }
 
-    fn apply(&self, outcome: TestOutcome) -> Vec<SuiteTestResult> {
+    /// Filters and sorts the tests by gas used, or by ergs used if `zk`.
+    fn apply(&self, outcome: TestOutcome, zk: bool) -> Vec<SuiteTestResult> {
+        let gas_used = |test: &SuiteTestResult| report(test, zk).gas();
         let mut tests = outcome
             .into_tests()
-            .filter(|test| self.is_in_gas_range(test.gas_used()))
+            .filter(|test| self.is_in_gas_range(gas_used(test)))
             .collect::<Vec<_>>();
 
         if self.asc {
-            tests.sort_by_key(|a| a.gas_used());
+            tests.sort_by_key(gas_used);
         } else if self.desc {
-            tests.sort_by_key(|b| std::cmp::Reverse(b.gas_used()))
+            tests.sort_by_key(|b| std::cmp::Reverse(gas_used(b)))
         }
 
         tests
@@ -194,6 +202,7 @@ impl SnapshotConfig {
 ///   `<signature>(gas:? 40181)` for normal tests
 ///   `<signature>(runs: 256, μ: 40181, ~: 40181)` for fuzz tests
 ///   `<signature>(runs: 256, calls: 40181, reverts: 40181)` for invariant tests
+///   `<signature>(ergs: 1204331, pubdata: 1066)` for tests with ZK-VM executions
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SnapshotEntry {
     pub contract_name: String,
@@ -218,6 +227,15 @@ impl FromStr for SnapshotEntry {
                                     gas: gas.as_str().parse().unwrap(),
                                 },
                             })
+                        } else if let Some(ergs) = cap.name("ergs") {
+                            cap.name("pubdata").map(|pubdata| SnapshotEntry {
+                                contract_name: file.as_str().to_string(),
+                                signature: sig.as_str().to_string(),
+                                gas_used: TestKindReport::ZkStandard {
+                                    ergs: ergs.as_str().parse().unwrap(),
+                                    pubdata: pubdata.as_str().parse().unwrap(),
+                                },
+                            })
                         } else if let Some(runs) = cap.name("runs") {
                             cap.name("avg")
                                 .and_then(|avg| cap.name("med").map(|med| (runs, avg, med)))
@@ -269,17 +287,25 @@ fn read_snapshot(path: impl AsRef<Path>) -> Result<Vec<SnapshotEntry>> {
     Ok(entries)
 }
 
+/// Returns the report of a test to snapshot, with its ergs and pubdata if `zk`
+fn report(test: &SuiteTestResult, zk: bool) -> TestKindReport {
+    if zk {
+        test.zk_report()
+    } else {
+        test.result.kind.report()
+    }
+}
+
 /// Writes a series of tests to a snapshot file after sorting them
 fn write_to_snapshot_file(
     tests: &[SuiteTestResult],
     path: impl AsRef<Path>,
     _format: Option<Format>,
+    zk: bool,
 ) -> Result<()> {
     let mut reports = tests
         .iter()
-        .map(|test| {
-            format!("{}:{} {}", test.contract_name(), test.signature, test.result.kind.report())
-        })
+        .map(|test| format!("{}:{} {}", test.contract_name(), test.signature, report(test, zk)))
         .collect::<Vec<_>>();
 
     // sort all reports
@@ -310,12 +336,38 @@ impl SnapshotDiff {
     fn gas_diff(&self) -> f64 {
         self.gas_change() as f64 / self.target_gas_used.gas() as f64
     }
+
+    /// Returns the pubdata diff of ZK snapshot entries
+    fn pubdata_change(&self) -> i128 {
+        pubdata(&self.source_gas_used) as i128 - pubdata(&self.target_gas_used) as i128
+    }
+
+    /// Determines the percentage change of the pubdata
+    fn pubdata_diff(&self) -> f64 {
+        match pubdata(&self.target_gas_used) {
+            0 => 0.,
+            target => self.pubdata_change() as f64 / target as f64,
+        }
+    }
+}
+
+/// Returns the bytes of pubdata of a ZK snapshot entry, `0` for other entries
+fn pubdata(report: &TestKindReport) -> u64 {
+    match report {
+        TestKindReport::ZkStandard { pubdata, .. } => *pubdata,
+        _ => 0,
+    }
 }
 
 /// Compares the set of tests with an existing snapshot
 ///
 /// Returns true all tests match
-fn check(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>, tolerance: Option<u32>) -> bool {
+fn check(
+    tests: Vec<SuiteTestResult>,
+    snaps: Vec<SnapshotEntry>,
+    tolerance: Option<u32>,
+    zk: bool,
+) -> bool {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
@@ -325,10 +377,11 @@ fn check(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>, tolerance: Opti
         if let Some(target_gas) =
             snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
         {
-            let source_gas = test.result.kind.report();
-            if !within_tolerance(source_gas.gas(), target_gas.gas(), tolerance) {
+            let source_gas = report(&test, zk);
+            if !reports_within_tolerance(&source_gas, &target_gas, tolerance) {
+                let unit = if zk { "" } else { " gas" };
                 eprintln!(
-                    "Diff in \"{}::{}\": consumed \"{}\" gas, expected \"{}\" gas ",
+                    "Diff in \"{}::{}\": consumed \"{}\"{unit}, expected \"{}\"{unit} ",
                     test.contract_name(),
                     test.signature,
                     source_gas,
@@ -348,8 +401,8 @@ fn check(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>, tolerance: Opti
     !has_diff
 }
 
-/// Compare the set of tests with an existing snapshot
-fn diff(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>) -> Result<()> {
+/// Compare the set of tests with an existing snapshot, with the ergs and pubdata changes if `zk`
+fn diff(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>, zk: bool) -> Result<()> {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
@@ -360,7 +413,7 @@ fn diff(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>) -> Result<()> {
             snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
         {
             diffs.push(SnapshotDiff {
-                source_gas_used: test.result.kind.report(),
+                source_gas_used: report(&test, zk),
                 signature: test.signature,
                 target_gas_used,
             });
@@ -368,6 +421,8 @@ fn diff(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>) -> Result<()> {
     }
     let mut overall_gas_change = 0i128;
     let mut overall_gas_used = 0i128;
+    let mut overall_pubdata_change = 0i128;
+    let mut overall_pubdata = 0i128;
 
     diffs.sort_by(|a, b| {
         a.gas_diff().abs().partial_cmp(&b.gas_diff().abs()).unwrap_or(Ordering::Equal)
@@ -378,20 +433,46 @@ fn diff(tests: Vec<SuiteTestResult>, snaps: Vec<SnapshotEntry>) -> Result<()> {
         overall_gas_change += gas_change;
         overall_gas_used += diff.target_gas_used.gas() as i128;
         let gas_diff = diff.gas_diff();
-        println!(
-            "{} (gas: {} ({})) ",
-            diff.signature,
-            fmt_change(gas_change),
-            fmt_pct_change(gas_diff)
-        );
+        if zk {
+            let pubdata_change = diff.pubdata_change();
+            overall_pubdata_change += pubdata_change;
+            overall_pubdata += pubdata(&diff.target_gas_used) as i128;
+            println!(
+                "{} (ergs: {} ({}), pubdata: {} ({})) ",
+                diff.signature,
+                fmt_change(gas_change),
+                fmt_pct_change(gas_diff),
+                fmt_change(pubdata_change),
+                fmt_pct_change(diff.pubdata_diff())
+            );
+        } else {
+            println!(
+                "{} (gas: {} ({})) ",
+                diff.signature,
+                fmt_change(gas_change),
+                fmt_pct_change(gas_diff)
+            );
+        }
     }
 
     let overall_gas_diff = overall_gas_change as f64 / overall_gas_used as f64;
     println!(
-        "Overall gas change: {} ({})",
+        "Overall {} change: {} ({})",
+        if zk { "ergs" } else { "gas" },
         fmt_change(overall_gas_change),
         fmt_pct_change(overall_gas_diff)
     );
+    if zk {
+        let overall_pubdata_diff = match overall_pubdata {
+            0 => 0.,
+            overall_pubdata => overall_pubdata_change as f64 / overall_pubdata as f64,
+        };
+        println!(
+            "Overall pubdata change: {} ({})",
+            fmt_change(overall_pubdata_change),
+            fmt_pct_change(overall_pubdata_diff)
+        );
+    }
     Ok(())
 }
 
@@ -420,6 +501,10 @@ fn fmt_change(change: i128) -> String {
 ///
 /// If `tolerance` is `None`, then this returns `true` if both gas values are equal
 fn within_tolerance(source_gas: u64, target_gas: u64, tolerance_pct: Option<u32>) -> bool {
+    // equal values are within any tolerance, and dividing zeros wouldn't be
+    if source_gas == target_gas {
+        return true
+    }
     if let Some(tolerance) = tolerance_pct {
         let (hi, lo) = if source_gas > target_gas {
             (source_gas, target_gas)
@@ -433,6 +518,26 @@ fn within_tolerance(source_gas: u64, target_gas: u64, tolerance_pct: Option<u32>
     }
 }
 
+/// Returns true if the source report is within the tolerance of the target one
+///
+/// The ergs and the pubdata of ZK snapshot entries must both be within the tolerance.
+fn reports_within_tolerance(
+    source: &TestKindReport,
+    target: &TestKindReport,
+    tolerance_pct: Option<u32>,
+) -> bool {
+    match (source, target) {
+        (
+            TestKindReport::ZkStandard { ergs, pubdata },
+            TestKindReport::ZkStandard { ergs: target_ergs, pubdata: target_pubdata },
+        ) => {
+            within_tolerance(*ergs, *target_ergs, tolerance_pct) &&
+                within_tolerance(*pubdata, *target_pubdata, tolerance_pct)
+        }
+        _ => within_tolerance(source.gas(), target.gas(), tolerance_pct),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -444,6 +549,8 @@ mod tests {
         assert!(!within_tolerance(100, 106, Some(5)));
         assert!(!within_tolerance(106, 100, Some(5)));
         assert!(within_tolerance(100, 100, None));
+        assert!(within_tolerance(0, 0, Some(5)));
+        assert!(!within_tolerance(0, 1, Some(5)));
     }
 
     #[test]
@@ -501,4 +608,35 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn can_parse_zk_snapshot_entry() {
+        let s = "Test:deposit() (ergs: 1204331, pubdata: 1066)";
+        let entry = SnapshotEntry::from_str(s).unwrap();
+        assert_eq!(
+            entry,
+            SnapshotEntry {
+                contract_name: "Test".to_string(),
+                signature: "deposit()".to_string(),
+                gas_used: TestKindReport::ZkStandard { ergs: 1204331, pubdata: 1066 }
+            }
+        );
+        assert_eq!(entry.gas_used.to_string(), "(ergs: 1204331, pubdata: 1066)");
+    }
+
+    #[test]
+    fn test_zk_tolerance() {
+        let target = TestKindReport::ZkStandard { ergs: 100, pubdata: 100 };
+        let source = TestKindReport::ZkStandard { ergs: 100, pubdata: 106 };
+        assert!(!reports_within_tolerance(&source, &target, Some(5)));
+        assert!(!reports_within_tolerance(&source, &target, None));
+        let source = TestKindReport::ZkStandard { ergs: 104, pubdata: 96 };
+        assert!(reports_within_tolerance(&source, &target, Some(5)));
+        assert!(reports_within_tolerance(&target, &target, None));
+
+        // a test without ZK-VM writes publishes no pubdata
+        let target = TestKindReport::ZkStandard { ergs: 100, pubdata: 0 };
+        let source = TestKindReport::ZkStandard { ergs: 102, pubdata: 0 };
+        assert!(reports_within_tolerance(&source, &target, Some(5)));
+    }
 }
@@ -0,0 +1,549 @@
+//! Failure triage bundles, written with `forge test --bundle-failures` and replayed with
+//! `forge test --from-bundle`.
+//!
+//! A bundle is a gzipped tar archive with a manifest of the failed tests and what they ran with,
+//! their results and decoded traces, the solc and zksolc artifacts of their test contracts and a
+//! snapshot of the era_test_node state they were run with, if any.
+
+use alloy_primitives::{keccak256, B256, U256};
+use eyre::{Result, WrapErr};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use forge::{
+    fuzz::CounterExample,
+    result::{TestOutcome, TestResult},
+    suite_code::SuiteCode,
+    traces::{identifier::LocalTraceIdentifier, render_trace_arena, CallTraceDecoderBuilder},
+    DeployableContracts,
+};
+use foundry_cli::utils::Git;
+use foundry_common::{fs, ContractsByArtifact, TestFunctionExt};
+use foundry_compilers::{ConfigurableContractArtifact, ProjectCompileOutput};
+use foundry_config::Config;
+use foundry_zksync_core::era_state::EraState;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+/// The version of the bundle format.
+pub const BUNDLE_VERSION: &str = "1";
+
+const MANIFEST_FILE: &str = "bundle.json";
+const RESULTS_FILE: &str = "results.json";
+const STATE_FILE: &str = "state.json";
+
+/// The manifest of a bundle.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    /// The version of the bundle format.
+    pub version: String,
+    /// The commit of the project, if it's a git repository.
+    pub commit: Option<String>,
+    /// The seed of the fuzz and invariant tests.
+    pub fuzz_seed: Option<U256>,
+    /// The `rpc_endpoints` alias the tests were forked from.
+    ///
+    /// Fork URLs that aren't aliases are not recorded, since they may contain API keys.
+    pub fork_rpc: Option<String>,
+    /// The block the tests were forked at, if they were forked.
+    pub fork_block: Option<u64>,
+    /// The aliases of the `rpc_endpoints` of the config, available to `vm.createFork`.
+    pub rpc_aliases: Vec<String>,
+    /// Whether the tests were run in EraVM.
+    pub zksync: bool,
+    /// The solc versions of the artifacts.
+    pub solc_versions: BTreeSet<String>,
+    /// The zksolc version, if the tests were run in EraVM.
+    pub zksolc_version: Option<String>,
+    /// Whether the bundle contains the era_test_node state the tests were run with.
+    pub state: bool,
+    /// The failed tests.
+    pub failures: Vec<BundledFailure>,
+}
+
+/// A failed test of a bundle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledFailure {
+    /// The identifier of the test contract, `<path>:<name>`.
+    pub suite: String,
+    /// The signature of the test function.
+    pub test: String,
+    /// The reason of the failure.
+    pub reason: Option<String>,
+    /// The counterexample of a failed fuzz or invariant test.
+    pub counterexample: Option<CounterExample>,
+    /// The hash of the artifact of the test contract, see [`forge::inputs::artifact_hashes`].
+    pub artifact_hash: Option<B256>,
+    /// The file of the artifact of the test contract within the bundle.
+    #[serde(default)]
+    pub artifact: Option<String>,
+    /// The hash of the zksolc artifact of the test contract, if the tests were run in EraVM.
+    #[serde(default)]
+    pub zk_artifact_hash: Option<B256>,
+    /// The file of the zksolc artifact of the test contract within the bundle.
+    #[serde(default)]
+    pub zk_artifact: Option<String>,
+    /// The file of the decoded traces of the test within the bundle.
+    pub traces: Option<String>,
+}
+
+impl BundleManifest {
+    /// Extracts the bundle at `path` to `dir`, and returns its manifest.
+    pub fn extract(path: &Path, dir: &Path) -> Result<Self> {
+        let mut archive = tar::Archive::new(GzDecoder::new(fs::open(path)?));
+        let entries = archive
+            .entries()
+            .wrap_err_with(|| format!("failed to read the bundle {}", path.display()))?;
+        for entry in entries {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue
+            }
+            let relative = entry.path()?.into_owned();
+            eyre::ensure!(
+                relative.components().all(|c| matches!(c, Component::Normal(_))),
+                "invalid bundle entry `{}`",
+                relative.display()
+            );
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            let target = dir.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, contents)?;
+        }
+
+        let manifest: Self = fs::read_json_file(&dir.join(MANIFEST_FILE))?;
+        eyre::ensure!(
+            manifest.version == BUNDLE_VERSION,
+            "unsupported bundle version {}, expected {BUNDLE_VERSION}",
+            manifest.version
+        );
+        Ok(manifest)
+    }
+
+    /// Returns the path of the era_test_node state of a bundle extracted to `dir`, if any.
+    pub fn state_path(&self, dir: &Path) -> Option<PathBuf> {
+        self.state.then(|| dir.join(STATE_FILE))
+    }
+
+    /// Returns a filter expression that matches exactly the failed tests.
+    pub fn filter_expr(&self) -> String {
+        self.failures
+            .iter()
+            .map(|failure| {
+                let (path, contract) =
+                    failure.suite.rsplit_once(':').unwrap_or(("", failure.suite.as_str()));
+                let test = failure.test.split('(').next().unwrap_or_default();
+                format!(
+                    "(path =~ \"{}$\" and contract =~ \"^{}$\" and test =~ \"^{}(\\(.*\\))?$\")",
+                    regex::escape(path),
+                    regex::escape(contract),
+                    regex::escape(test)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    /// Returns how the compiled project differs from the one the bundle was created with.
+    ///
+    /// The zksolc artifacts are only compared when the tests of the bundle were run in EraVM.
+    pub fn differences(
+        &self,
+        artifacts: &ArtifactHashes<'_>,
+        solc_versions: &BTreeSet<String>,
+    ) -> Vec<String> {
+        let mut differences = Vec::new();
+        if &self.solc_versions != solc_versions {
+            differences.push(format!(
+                "the bundle was compiled with solc {}",
+                self.solc_versions.iter().cloned().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        let suites = self
+            .failures
+            .iter()
+            .filter(|failure| artifacts.changed(failure))
+            .map(|failure| failure.suite.as_str())
+            .collect::<BTreeSet<_>>();
+        for suite in suites {
+            differences.push(format!("{suite} changed since the bundle was created"));
+        }
+        differences
+    }
+
+    /// Returns the artifacts of the bundle extracted to `dir` whose test contracts changed since
+    /// the bundle was created, by identifier.
+    pub fn changed_artifacts(
+        &self,
+        dir: &Path,
+        artifacts: &ArtifactHashes<'_>,
+    ) -> BTreeMap<String, BundledArtifact> {
+        self.failures
+            .iter()
+            .filter(|failure| artifacts.changed(failure))
+            .filter_map(|failure| {
+                let artifact = BundledArtifact {
+                    path: dir.join(failure.artifact.as_ref()?),
+                    zk_path: failure.zk_artifact.as_ref().map(|file| dir.join(file)),
+                };
+                Some((failure.suite.clone(), artifact))
+            })
+            .collect()
+    }
+}
+
+/// The hashes of the artifacts of the compiled project, see [`forge::inputs::artifact_hashes`],
+/// compared with the ones of a bundle.
+pub struct ArtifactHashes<'a> {
+    /// The hashes of the solc artifacts, by identifier.
+    pub artifacts: &'a BTreeMap<String, B256>,
+    /// The hashes of the zksolc artifacts, by identifier, if the tests are run in EraVM.
+    pub zk_artifacts: Option<&'a BTreeMap<String, B256>>,
+}
+
+impl ArtifactHashes<'_> {
+    /// Returns whether the test contract of `failure` changed since the bundle was created.
+    fn changed(&self, failure: &BundledFailure) -> bool {
+        if failure.artifact_hash.as_ref() != self.artifacts.get(&failure.suite) {
+            return true
+        }
+        match (self.zk_artifacts, &failure.zk_artifact_hash) {
+            (Some(zk_artifacts), Some(hash)) => zk_artifacts.get(&failure.suite) != Some(hash),
+            _ => false,
+        }
+    }
+}
+
+/// The bundled artifacts of a test contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundledArtifact {
+    /// The solc artifact.
+    pub path: PathBuf,
+    /// The zksolc artifact, if the tests of the bundle were run in EraVM.
+    pub zk_path: Option<PathBuf>,
+}
+
+/// Replaces the test contracts of `contracts` with the bundled artifacts of `bundled`, so that
+/// the failures are replayed with the code they were found with, in both VMs. Returns the
+/// replaced contracts.
+pub fn use_bundled_artifacts(
+    contracts: &mut DeployableContracts,
+    bundled: &BTreeMap<String, BundledArtifact>,
+) -> Result<Vec<String>> {
+    let mut replaced = Vec::new();
+    for (id, (abi, code, _)) in contracts.iter_mut() {
+        let identifier = id.identifier();
+        let Some(bundled) = bundled.get(&identifier) else { continue };
+        let artifact: ConfigurableContractArtifact = fs::read_json_file(&bundled.path)?;
+        if let Some(bundled_abi) = artifact.abi {
+            *abi = bundled_abi;
+        }
+        let (libraries, zk_path) = match code {
+            SuiteCode::Artifact { libraries, zk_path, .. } => (libraries.clone(), zk_path.clone()),
+            SuiteCode::Loaded(_) => Default::default(),
+        };
+        let zk_path = bundled.zk_path.clone().or(zk_path);
+        *code = SuiteCode::Artifact { path: bundled.path.clone(), libraries, zk_path };
+        replaced.push(identifier);
+    }
+    Ok(replaced)
+}
+
+/// The path of the artifact of a test contract within a bundle.
+fn artifact_file(suite: &str) -> String {
+    let suite = suite.trim_start_matches('/');
+    let (path, name) = suite.rsplit_once(':').unwrap_or(("", suite));
+    format!("artifacts/{path}/{name}.json")
+}
+
+/// The path of the zksolc artifact of a test contract within a bundle.
+fn zk_artifact_file(suite: &str) -> String {
+    format!("zk-{}", artifact_file(suite))
+}
+
+/// Returns the hashes and the JSON of the artifacts of the test contracts of `output`, by
+/// identifier.
+fn test_artifacts(output: &ProjectCompileOutput, root: &Path) -> BTreeMap<String, (B256, Vec<u8>)> {
+    let mut artifacts = BTreeMap::new();
+    for (id, artifact) in output.artifact_ids() {
+        let is_test_contract = artifact
+            .abi
+            .as_ref()
+            .is_some_and(|abi| abi.functions().any(|f| f.is_test() || f.is_invariant_test()));
+        if !is_test_contract {
+            continue
+        }
+        let (Ok(code), Ok(json)) = (
+            serde_json::to_vec(&(&artifact.bytecode, &artifact.deployed_bytecode)),
+            serde_json::to_vec_pretty(&artifact),
+        ) else {
+            continue
+        };
+        let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+        artifacts.insert(format!("{}:{}", source.display(), id.name), (keccak256(code), json));
+    }
+    artifacts
+}
+
+/// What the tests of a run were executed with, collected before they run.
+pub struct BundleContext {
+    /// The manifest without failures.
+    pub manifest: BundleManifest,
+    /// The contracts to identify in the traces.
+    pub known_contracts: ContractsByArtifact,
+    /// The hashes and the JSON of the artifacts of the test contracts, by identifier.
+    pub artifacts: BTreeMap<String, (B256, Vec<u8>)>,
+    /// The hashes and the JSON of the zksolc artifacts of the test contracts, by identifier.
+    pub zk_artifacts: BTreeMap<String, (B256, Vec<u8>)>,
+    /// The era_test_node state the tests were run with, set once the runner loaded it.
+    pub state: Option<EraState>,
+}
+
+impl BundleContext {
+    /// Collects what the tests are run with from the config and the compiled project, with its
+    /// zksolc output if the tests are run in EraVM, which must not have their file prefixes
+    /// stripped yet.
+    pub fn new(
+        config: &Config,
+        output: &ProjectCompileOutput,
+        zk_output: Option<&ProjectCompileOutput>,
+    ) -> Self {
+        let root = &config.__root.0;
+        let artifacts = test_artifacts(output, root);
+        let zk_artifacts = zk_output.map(|output| test_artifacts(output, root)).unwrap_or_default();
+
+        let manifest = BundleManifest {
+            version: BUNDLE_VERSION.to_string(),
+            commit: Git::new(root).commit_hash(false, "HEAD").ok(),
+            fuzz_seed: config.fuzz.seed,
+            fork_rpc: fork_alias(config),
+            fork_block: None,
+            rpc_aliases: config.rpc_endpoints.clone().resolved().keys().cloned().collect(),
+            zksync: config.zksync,
+            solc_versions: solc_versions(output),
+            zksolc_version: config.zksync.then(|| config.zksolc_version()).flatten(),
+            state: false,
+            failures: Vec::new(),
+        };
+        Self { manifest, known_contracts: Default::default(), artifacts, zk_artifacts, state: None }
+    }
+
+    /// Writes the bundle of the failed tests of `outcome` to `path`, and returns the number of
+    /// bundled failures.
+    pub async fn write(mut self, path: &Path, outcome: &TestOutcome) -> Result<usize> {
+        let mut identifier = LocalTraceIdentifier::new(&self.known_contracts);
+        let mut decoder =
+            CallTraceDecoderBuilder::new().with_local_identifier_abis(&identifier).build();
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut archive = BundleWriter::create(path)?;
+        let mut results = BTreeMap::<&str, BTreeMap<&str, &TestResult>>::new();
+        for (suite, suite_result) in &outcome.results {
+            for (test, result) in suite_result.failures() {
+                decoder.clear_addresses();
+                decoder
+                    .labels
+                    .extend(result.labeled_addresses.iter().map(|(k, v)| (*k, v.clone())));
+                decoder.proxies.extend(result.proxies.iter().map(|(k, v)| (*k, *v)));
+                let mut traces = String::new();
+                for (kind, arena) in &result.traces {
+                    decoder.identify(arena, &mut identifier);
+                    writeln!(traces, "{kind:?}:")?;
+                    traces.push_str(&render_trace_arena(arena, &decoder).await?);
+                    traces.push('\n');
+                }
+                let traces_file = (!traces.is_empty())
+                    .then(|| format!("traces/{}.txt", self.manifest.failures.len()));
+                if let Some(file) = &traces_file {
+                    archive.append(file, traces.as_bytes())?;
+                }
+
+                self.manifest.failures.push(BundledFailure {
+                    suite: suite.clone(),
+                    test: test.clone(),
+                    reason: result.reason.clone(),
+                    counterexample: result.counterexample.clone(),
+                    artifact_hash: self.artifacts.get(suite).map(|(hash, _)| *hash),
+                    artifact: self.artifacts.contains_key(suite).then(|| artifact_file(suite)),
+                    zk_artifact_hash: self.zk_artifacts.get(suite).map(|(hash, _)| *hash),
+                    zk_artifact: self
+                        .zk_artifacts
+                        .contains_key(suite)
+                        .then(|| zk_artifact_file(suite)),
+                    traces: traces_file,
+                });
+                results.entry(suite.as_str()).or_default().insert(test.as_str(), result);
+            }
+        }
+
+        for suite in results.keys() {
+            if let Some((_, artifact)) = self.artifacts.get(*suite) {
+                archive.append(&artifact_file(suite), artifact)?;
+            }
+            if let Some((_, artifact)) = self.zk_artifacts.get(*suite) {
+                archive.append(&zk_artifact_file(suite), artifact)?;
+            }
+        }
+        if let Some(state) = &self.state {
+            archive.append(STATE_FILE, &state.to_json()?)?;
+            self.manifest.state = true;
+        }
+        archive.append(RESULTS_FILE, &serde_json::to_vec_pretty(&results)?)?;
+        archive.append(MANIFEST_FILE, &serde_json::to_vec_pretty(&self.manifest)?)?;
+        archive.finish()?;
+
+        Ok(self.manifest.failures.len())
+    }
+}
+
+/// Returns the solc versions of the artifacts of `output`.
+pub fn solc_versions(output: &ProjectCompileOutput) -> BTreeSet<String> {
+    output.artifact_ids().map(|(id, _)| id.version.to_string()).collect()
+}
+
+/// Returns the `rpc_endpoints` alias of the fork URL of the config, if it has one.
+fn fork_alias(config: &Config) -> Option<String> {
+    let url = config.eth_rpc_url.as_deref()?;
+    let endpoints = config.rpc_endpoints.clone().resolved();
+    if endpoints.contains_key(url) {
+        return Some(url.to_string())
+    }
+    endpoints
+        .iter()
+        .find(|(_, resolved)| resolved.as_deref().ok() == Some(url))
+        .map(|(alias, _)| alias.clone())
+}
+
+/// A writer of the files of a gzipped tar archive.
+struct BundleWriter {
+    builder: tar::Builder<GzEncoder<File>>,
+}
+
+impl BundleWriter {
+    fn create(path: &Path) -> Result<Self> {
+        let encoder = GzEncoder::new(fs::create_file(path)?, Compression::default());
+        Ok(Self { builder: tar::Builder::new(encoder) })
+    }
+
+    fn append(&mut self, name: &str, contents: &[u8]) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        self.builder
+            .append_data(&mut header, name, contents)
+            .wrap_err_with(|| format!("failed to write the bundle entry `{name}`"))?;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        self.builder.into_inner()?.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_bundles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.tar.gz");
+        let suite = "test/deeply/nested/directories/of/the/project/Counter.t.sol:CounterTest";
+        let manifest = BundleManifest {
+            version: BUNDLE_VERSION.to_string(),
+            failures: vec![BundledFailure {
+                suite: suite.to_string(),
+                test: "test_Increment()".to_string(),
+                reason: None,
+                counterexample: None,
+                artifact_hash: Some(B256::repeat_byte(1)),
+                artifact: Some(artifact_file(suite)),
+                zk_artifact_hash: Some(B256::repeat_byte(3)),
+                zk_artifact: Some(zk_artifact_file(suite)),
+                traces: None,
+            }],
+            ..Default::default()
+        };
+        let mut archive = BundleWriter::create(&path).unwrap();
+        archive.append(&artifact_file(suite), b"{}").unwrap();
+        archive.append(&zk_artifact_file(suite), b"{\"zk\":true}").unwrap();
+        archive.append("traces/0.txt", &[b'a'; 600]).unwrap();
+        archive.append(MANIFEST_FILE, &serde_json::to_vec(&manifest).unwrap()).unwrap();
+        archive.finish().unwrap();
+
+        let extracted = dir.path().join("extracted");
+        let read = BundleManifest::extract(&path, &extracted).unwrap();
+        assert_eq!(read.failures.len(), 1);
+        assert_eq!(fs::read(extracted.join("traces/0.txt")).unwrap().len(), 600);
+
+        // the artifacts are keyed by path and name, names longer than 100 bytes included
+        let artifact = extracted.join(
+            "artifacts/test/deeply/nested/directories/of/the/project/Counter.t.sol/CounterTest.json",
+        );
+        assert_eq!(fs::read(&artifact).unwrap(), b"{}");
+        let zk_artifact = extracted.join(
+            "zk-artifacts/test/deeply/nested/directories/of/the/project/Counter.t.sol/CounterTest.json",
+        );
+        assert_eq!(fs::read(&zk_artifact).unwrap(), b"{\"zk\":true}");
+
+        let hashes = BTreeMap::from([(suite.to_string(), B256::repeat_byte(1))]);
+        let zk_hashes = BTreeMap::from([(suite.to_string(), B256::repeat_byte(3))]);
+        let unchanged = ArtifactHashes { artifacts: &hashes, zk_artifacts: Some(&zk_hashes) };
+        assert!(read.changed_artifacts(&extracted, &unchanged).is_empty());
+
+        let changed_hashes = BTreeMap::from([(suite.to_string(), B256::repeat_byte(2))]);
+        let changed = ArtifactHashes { artifacts: &changed_hashes, zk_artifacts: None };
+        assert_eq!(
+            read.changed_artifacts(&extracted, &changed)[suite],
+            BundledArtifact { path: artifact.clone(), zk_path: Some(zk_artifact.clone()) }
+        );
+
+        // a change of the EraVM bytecode alone replays the bundled artifacts too
+        let changed_zk_hashes = BTreeMap::from([(suite.to_string(), B256::repeat_byte(4))]);
+        let changed_zk =
+            ArtifactHashes { artifacts: &hashes, zk_artifacts: Some(&changed_zk_hashes) };
+        assert_eq!(
+            read.changed_artifacts(&extracted, &changed_zk)[suite],
+            BundledArtifact { path: artifact, zk_path: Some(zk_artifact) }
+        );
+        assert_eq!(
+            read.differences(&changed_zk, &Default::default()),
+            vec![format!("{suite} changed since the bundle was created")]
+        );
+    }
+
+    #[test]
+    fn filters_failed_tests() {
+        let manifest = BundleManifest {
+            failures: vec![BundledFailure {
+                suite: "test/Counter.t.sol:CounterTest".to_string(),
+                test: "testFuzz_SetNumber(uint256)".to_string(),
+                reason: None,
+                counterexample: None,
+                artifact_hash: None,
+                artifact: None,
+                zk_artifact_hash: None,
+                zk_artifact: None,
+                traces: None,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            manifest.filter_expr(),
+            r#"(path =~ "test/Counter\.t\.sol$" and contract =~ "^CounterTest$" and test =~ "^testFuzz_SetNumber(\(.*\))?$")"#
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use super::filter_expr::{FilterExpr, TestParts};
 use clap::Parser;
 use forge::TestFilter;
 use foundry_cli::utils::FoundryPathExt;
@@ -40,6 +41,16 @@ pub struct FilterArgs {
         value_name = "GLOB"
     )]
     pub path_pattern_inverse: Option<GlobMatcher>,
+
+    /// Only run tests matching the specified filter expression.
+    ///
+    /// Conditions on the test function (`test`), contract (`contract`) and source path (`path`)
+    /// are regex matches (`=~`) or non-matches (`!~`), and can be combined with `and`, `or`,
+    /// `not` and parentheses, e.g. `(contract =~ Token.* and not test =~ Fork) or path =~ zk/`.
+    ///
+    /// Applies in addition to the other filters.
+    #[clap(long = "filter-expr", visible_alias = "fe", value_name = "EXPR")]
+    pub filter_expr: Option<FilterExpr>,
 }
 
 impl FilterArgs {
@@ -50,7 +61,8 @@ impl FilterArgs {
             self.contract_pattern.is_none() &&
             self.contract_pattern_inverse.is_none() &&
             self.path_pattern.is_none() &&
-            self.path_pattern_inverse.is_none()
+            self.path_pattern_inverse.is_none() &&
+            self.filter_expr.is_none()
     }
 
     /// Merges the set filter globs with the config's values
@@ -73,7 +85,11 @@ impl FilterArgs {
         if self.path_pattern_inverse.is_none() {
             self.path_pattern_inverse = config.path_pattern_inverse.clone().map(Into::into);
         }
-        ProjectPathsAwareFilter { args_filter: self, paths: config.project_paths() }
+        ProjectPathsAwareFilter {
+            args_filter: self,
+            paths: config.project_paths(),
+            include_libs: Vec::new(),
+        }
     }
 }
 
@@ -86,6 +102,7 @@ impl fmt::Debug for FilterArgs {
             .field("no-match-contract", &self.contract_pattern_inverse.as_ref().map(|r| r.as_str()))
             .field("match-path", &self.path_pattern.as_ref().map(|g| g.as_str()))
             .field("no-match-path", &self.path_pattern_inverse.as_ref().map(|g| g.as_str()))
+            .field("filter-expr", &self.filter_expr.as_ref().map(|e| e.as_str()))
             .finish_non_exhaustive()
     }
 }
@@ -96,6 +113,11 @@ impl FileFilter for FilterArgs {
     /// If no file regex is set this returns true if the file ends with `.t.sol`, see
     /// [`FoundryPathExt::is_sol_test()`].
     fn is_match(&self, file: &Path) -> bool {
+        if let Some(expr) = &self.filter_expr {
+            if !expr.may_match(TestParts { path: Some(file), ..Default::default() }) {
+                return false
+            }
+        }
         if let Some(glob) = &self.path_pattern {
             return glob.is_match(file)
         }
@@ -115,6 +137,9 @@ impl TestFilter for FilterArgs {
         if let Some(re) = &self.test_pattern_inverse {
             ok = ok && !re.is_match(test_name);
         }
+        if let Some(expr) = &self.filter_expr {
+            ok = ok && expr.may_match(TestParts { test: Some(test_name), ..Default::default() });
+        }
         ok
     }
 
@@ -126,6 +151,10 @@ impl TestFilter for FilterArgs {
         if let Some(re) = &self.contract_pattern_inverse {
             ok = ok && !re.is_match(contract_name);
         }
+        if let Some(expr) = &self.filter_expr {
+            let parts = TestParts { contract: Some(contract_name), ..Default::default() };
+            ok = ok && expr.may_match(parts);
+        }
         ok
     }
 
@@ -137,6 +166,24 @@ impl TestFilter for FilterArgs {
         if let Some(re) = &self.path_pattern_inverse {
             ok = ok && !re.is_match(path);
         }
+        if let Some(expr) = &self.filter_expr {
+            ok = ok && expr.may_match(TestParts { path: Some(path), ..Default::default() });
+        }
+        ok
+    }
+
+    fn matches_qualified_test(&self, path: &Path, contract_name: &str, test_name: &str) -> bool {
+        let mut ok = self.matches_path(path) &&
+            self.matches_contract(contract_name) &&
+            self.matches_test(test_name);
+        if let Some(expr) = &self.filter_expr {
+            let parts = TestParts {
+                test: Some(test_name),
+                contract: Some(contract_name),
+                path: Some(path),
+            };
+            ok = ok && expr.may_match(parts);
+        }
         ok
     }
 }
@@ -161,6 +208,9 @@ impl fmt::Display for FilterArgs {
         if let Some(p) = &self.path_pattern_inverse {
             writeln!(f, "\tno-match-path: `{}`", p.as_str())?;
         }
+        if let Some(e) = &self.filter_expr {
+            writeln!(f, "\tfilter-expr: `{}`", e.as_str())?;
+        }
         Ok(())
     }
 }
@@ -170,6 +220,8 @@ impl fmt::Display for FilterArgs {
 pub struct ProjectPathsAwareFilter {
     args_filter: FilterArgs,
     paths: ProjectPathsConfig,
+    /// The library sources whose tests are run, see `--include-libs`
+    include_libs: Vec<GlobMatcher>,
 }
 
 // === impl ProjectPathsAwareFilter ===
@@ -189,6 +241,48 @@ impl ProjectPathsAwareFilter {
     pub fn args_mut(&mut self) -> &mut FilterArgs {
         &mut self.args_filter
     }
+
+    /// Also matches the tests of the library sources matching one of `globs`.
+    pub fn include_libs(mut self, globs: Vec<GlobMatcher>) -> Self {
+        self.include_libs = globs;
+        self
+    }
+
+    /// Returns the name of the library of `path` if its tests are included with `--include-libs`,
+    /// e.g. `protocol` for `lib/protocol/test/Pool.t.sol`.
+    pub fn included_library(&self, path: &Path) -> Option<String> {
+        if !self.include_libs.iter().any(|glob| glob.is_match(path)) {
+            return None
+        }
+        let path = self.paths.root.join(path);
+        self.paths.libraries.iter().find_map(|lib| {
+            let name = path.strip_prefix(self.paths.root.join(lib)).ok()?.components().next()?;
+            Some(name.as_os_str().to_string_lossy().into_owned())
+        })
+    }
+
+    /// Returns the name of the results of the suite `id`, `<path>:<name>`, namespaced with its
+    /// library if it is a library test, e.g. `lib/protocol/test/Pool.t.sol:PoolTest [protocol]`.
+    pub fn suite_name(&self, id: String) -> String {
+        let path = id.rsplit_once(':').map(|(path, _)| path).unwrap_or_default();
+        match self.included_library(Path::new(path)) {
+            Some(library) => format!("{id} [{library}]"),
+            None => id,
+        }
+    }
+
+    /// Returns `path` relative to the project root, so the filters always match the same paths
+    /// as the ones shown in the output, whether they're given the compiled files or the artifacts.
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.paths.root).unwrap_or(path)
+    }
+
+    /// Returns whether the tests of `path` can run: it doesn't belong to a library, or its library
+    /// tests are included.
+    fn is_project_or_included(&self, path: &Path) -> bool {
+        !self.paths.has_library_ancestor(self.paths.root.join(path)) ||
+            self.included_library(path).is_some()
+    }
 }
 
 impl FileFilter for ProjectPathsAwareFilter {
@@ -197,7 +291,7 @@ impl FileFilter for ProjectPathsAwareFilter {
     /// If no file regex is set this returns true if the file ends with `.t.sol`, see
     /// [FoundryPathExr::is_sol_test()]
     fn is_match(&self, file: &Path) -> bool {
-        self.args_filter.is_match(file)
+        self.args_filter.is_match(self.relative(file))
     }
 }
 
@@ -211,8 +305,15 @@ impl TestFilter for ProjectPathsAwareFilter {
     }
 
     fn matches_path(&self, path: &Path) -> bool {
-        // we don't want to test files that belong to a library
-        self.args_filter.matches_path(path) && !self.paths.has_library_ancestor(path)
+        let path = self.relative(path);
+        // we don't want to test files that belong to a library, unless they're included
+        self.args_filter.matches_path(path) && self.is_project_or_included(path)
+    }
+
+    fn matches_qualified_test(&self, path: &Path, contract_name: &str, test_name: &str) -> bool {
+        let path = self.relative(path);
+        self.args_filter.matches_qualified_test(path, contract_name, test_name) &&
+            self.is_project_or_included(path)
     }
 }
 
@@ -0,0 +1,343 @@
+//! Composable test filter expressions, e.g.
+//! `(contract =~ Token.* and not test =~ Fork) or path =~ zk/`.
+
+use regex::Regex;
+use std::{fmt, path::Path, str::FromStr};
+
+/// A boolean expression over the test function, contract and source path of a test.
+///
+/// ```text
+/// expr    := or
+/// or      := and ("or" and)*
+/// and     := unary ("and" unary)*
+/// unary   := "not" unary | "(" expr ")" | field op pattern
+/// field   := "test" | "contract" | "path"
+/// op      := "=~" | "!~"
+/// pattern := regex, quoted with `"` or `'` if it contains whitespace, unbalanced parentheses,
+///            `=~` or `!~`
+/// ```
+///
+/// Operators don't need to be surrounded by whitespace: `test=~Foo` is `test =~ Foo`.
+#[derive(Clone, Debug)]
+pub struct FilterExpr {
+    expr: Expr,
+    /// The expression as provided by the user.
+    source: String,
+}
+
+/// The parts of a test a filter expression can match against.
+///
+/// Parts that are `None` are not known yet, e.g. when only selecting the contracts to run.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TestParts<'a> {
+    pub test: Option<&'a str>,
+    pub contract: Option<&'a str>,
+    pub path: Option<&'a Path>,
+}
+
+impl FilterExpr {
+    /// Returns whether a test with the given, possibly incomplete, parts can match the expression.
+    ///
+    /// Conditions on unknown parts are considered satisfiable, so this only returns `false` if the
+    /// expression can't be satisfied regardless of the unknown parts.
+    pub fn may_match(&self, parts: TestParts<'_>) -> bool {
+        self.expr.eval(&parts) != Some(false)
+    }
+
+    /// Returns the expression as provided by the user.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected `{token}` in filter expression"))
+        }
+        Ok(Self { expr, source: s.trim().to_string() })
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Test,
+    Contract,
+    Path,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Match { field: Field, regex: Regex, negated: bool },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression using three-valued logic, `None` meaning unknown.
+    fn eval(&self, parts: &TestParts<'_>) -> Option<bool> {
+        match self {
+            Self::Match { field, regex, negated } => {
+                let is_match = match field {
+                    Field::Test => regex.is_match(parts.test?),
+                    Field::Contract => regex.is_match(parts.contract?),
+                    Field::Path => regex.is_match(&parts.path?.to_string_lossy()),
+                };
+                Some(is_match != *negated)
+            }
+            Self::Not(expr) => expr.eval(parts).map(|b| !b),
+            Self::And(lhs, rhs) => match (lhs.eval(parts), rhs.eval(parts)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Self::Or(lhs, rhs) => match (lhs.eval(parts), rhs.eval(parts)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    Match,
+    NotMatch,
+    /// A keyword, field name or pattern.
+    Word(String),
+    /// A quoted pattern.
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LParen => f.write_str("("),
+            Self::RParen => f.write_str(")"),
+            Self::Match => f.write_str("=~"),
+            Self::NotMatch => f.write_str("!~"),
+            Self::Word(s) => f.write_str(s),
+            Self::Quoted(s) => write!(f, "\"{s}\""),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '=' | '!' => {
+                chars.next();
+                if chars.next() != Some('~') {
+                    return Err(format!("expected `{c}~` in filter expression"))
+                }
+                tokens.push(if c == '=' { Token::Match } else { Token::NotMatch });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => quoted.push(ch),
+                        None => return Err(format!("unterminated `{c}` in filter expression")),
+                    }
+                }
+                tokens.push(Token::Quoted(quoted));
+            }
+            _ => {
+                // consume until whitespace, an operator or a closing parenthesis that isn't part
+                // of the word, so patterns like `test(Foo|Bar)` don't need to be quoted
+                let mut word = String::new();
+                let mut depth = 0usize;
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || (ch == ')' && depth == 0) {
+                        break
+                    }
+                    if matches!(ch, '=' | '!') && chars.clone().nth(1) == Some('~') {
+                        break
+                    }
+                    match ch {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn bump(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword)) {
+            self.pos += 1;
+            return true
+        }
+        false
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)))
+        }
+        match self.bump() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.bump() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("missing closing `)` in filter expression".to_string()),
+                }
+            }
+            Some(Token::Word(word)) => {
+                let field = match word.as_str() {
+                    "test" => Field::Test,
+                    "contract" => Field::Contract,
+                    "path" => Field::Path,
+                    _ => {
+                        return Err(format!(
+                            "unknown filter field `{word}`, expected `test`, `contract` or `path`"
+                        ))
+                    }
+                };
+                let negated = match self.bump() {
+                    Some(Token::Match) => false,
+                    Some(Token::NotMatch) => true,
+                    _ => return Err(format!("expected `=~` or `!~` after `{word}`")),
+                };
+                let pattern = match self.bump() {
+                    Some(Token::Word(pattern) | Token::Quoted(pattern)) => pattern,
+                    _ => return Err("expected a pattern in filter expression".to_string()),
+                };
+                let regex = Regex::new(pattern)
+                    .map_err(|err| format!("invalid pattern `{pattern}`: {err}"))?;
+                Ok(Expr::Match { field, regex, negated })
+            }
+            Some(token) => Err(format!("unexpected `{token}` in filter expression")),
+            None => Err("unexpected end of filter expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts<'a>(test: &'a str, contract: &'a str, path: &'a str) -> TestParts<'a> {
+        TestParts { test: Some(test), contract: Some(contract), path: Some(Path::new(path)) }
+    }
+
+    #[test]
+    fn can_evaluate_filter_expr() {
+        let expr: FilterExpr =
+            "(contract =~ Token.* and not test =~ Fork) or path =~ zk/".parse().unwrap();
+
+        assert!(expr.may_match(parts("testTransfer()", "TokenTest", "test/Token.t.sol")));
+        assert!(!expr.may_match(parts("testForkTransfer()", "TokenTest", "test/Token.t.sol")));
+        assert!(expr.may_match(parts("testForkTransfer()", "TokenTest", "test/zk/Token.t.sol")));
+        assert!(!expr.may_match(parts("testTransfer()", "VaultTest", "test/Vault.t.sol")));
+    }
+
+    #[test]
+    fn can_evaluate_partial_filter_expr() {
+        let expr: FilterExpr = "contract =~ Token and test !~ Fork".parse().unwrap();
+
+        let contract_only = TestParts { contract: Some("TokenTest"), ..Default::default() };
+        assert!(expr.may_match(contract_only));
+        let contract_only = TestParts { contract: Some("VaultTest"), ..Default::default() };
+        assert!(!expr.may_match(contract_only));
+        assert!(!expr.may_match(TestParts { test: Some("testFork()"), ..Default::default() }));
+    }
+
+    #[test]
+    fn can_parse_nested_and_quoted_patterns() {
+        let expr: FilterExpr = "test =~ test(Foo|Bar) and path !~ 'lib/ forks'".parse().unwrap();
+        assert!(expr.may_match(parts("testFoo()", "C", "test/C.t.sol")));
+        assert!(!expr.may_match(parts("testBaz()", "C", "test/C.t.sol")));
+        assert!(!expr.may_match(parts("testFoo()", "C", "lib/ forks/C.t.sol")));
+    }
+
+    #[test]
+    fn can_parse_operators_without_whitespace() {
+        let expr: FilterExpr = "(contract=~Token and test!~Fork)or path=~'zk/'".parse().unwrap();
+        assert!(expr.may_match(parts("testTransfer()", "TokenTest", "test/Token.t.sol")));
+        assert!(!expr.may_match(parts("testForkTransfer()", "TokenTest", "test/Token.t.sol")));
+        assert!(expr.may_match(parts("testForkTransfer()", "TokenTest", "test/zk/Token.t.sol")));
+
+        let expr: FilterExpr = "test =~ 'a=~b'".parse().unwrap();
+        assert!(expr.may_match(parts("a=~b", "C", "test/C.t.sol")));
+    }
+
+    #[test]
+    fn rejects_invalid_filter_expr() {
+        assert!("name =~ Foo".parse::<FilterExpr>().is_err());
+        assert!("(test =~ Foo".parse::<FilterExpr>().is_err());
+        assert!("test = Foo".parse::<FilterExpr>().is_err());
+        assert!("test =~ Foo bar".parse::<FilterExpr>().is_err());
+        assert!("test =~ (".parse::<FilterExpr>().is_err());
+        assert!("test =~ 'Foo".parse::<FilterExpr>().is_err());
+        assert!("test=~".parse::<FilterExpr>().is_err());
+    }
+}
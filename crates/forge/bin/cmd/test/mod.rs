@@ -1,27 +1,40 @@
 use super::{install, test::filter::ProjectPathsAwareFilter, watch::WatchArgs};
-use alloy_primitives::U256;
-use clap::Parser;
-use eyre::Result;
+use alloy_primitives::{B256, U256};
+use clap::{Parser, ValueEnum, ValueHint};
+use eyre::{Result, WrapErr};
 use forge::{
     decode::decode_console_logs,
+    events::{EventStream, TestEvent},
     gas_report::GasReport,
+    inputs::{artifact_hashes, config_hash, SuiteInputs, TestInputs},
     inspectors::CheatsConfig,
+    metrics::{RunCounters, TestMetrics},
+    opts::EvmOpts,
     result::{SuiteResult, TestOutcome, TestStatus},
+    size_limits::{abi_size_warnings, imported_size_warnings},
+    trace_diff::ExportedTraces,
     traces::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
         CallTraceDecoderBuilder, TraceKind,
     },
-    MultiContractRunner, MultiContractRunnerBuilder, TestOptions, TestOptionsBuilder,
+    MultiContractRunner, MultiContractRunnerBuilder, TestFilter, TestOptions, TestOptionsBuilder,
 };
 use foundry_cli::{
     opts::CoreBuildArgs,
-    utils::{self, LoadConfig},
+    utils::{self, FoundryPathExt, LoadConfig},
 };
 use foundry_common::{
     compile::{ContractSources, ProjectCompiler},
     evm::EvmArgs,
+    fs,
+    glob::{expand_globs, GlobMatcher},
+    provider::cassette::{self, Cassette},
     shell,
 };
+use foundry_compilers::{
+    cache::{CacheEntry, SolFilesCache},
+    Project, ProjectCompileOutput,
+};
 use foundry_config::{
     figment,
     figment::{
@@ -31,18 +44,29 @@ use foundry_config::{
     get_available_profiles, Config,
 };
 use foundry_debugger::Debugger;
-use foundry_zksync_compiler::{new_dual_compiled_contracts, ZkSolc};
+use foundry_zksync_compiler::ZkSolc;
+use foundry_zksync_core::{pubdata, vm::SizeLimitWarning};
 use regex::Regex;
-use std::{sync::mpsc::channel, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Arc},
+    time::{Duration, Instant},
+};
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 
+mod bundle;
 mod filter;
+mod filter_expr;
+mod perf;
 mod summary;
-use summary::TestSummaryReporter;
+use bundle::{solc_versions, use_bundled_artifacts, ArtifactHashes, BundleContext, BundleManifest};
+use perf::{PerfModeReport, PERF_MODE_MAX_VERBOSITY};
+use summary::{print_matrix_summary, MatrixCellSummary, TestSummaryReporter};
 
 pub use filter::FilterArgs;
-use forge::traces::render_trace_arena;
+use forge::traces::{node_verbosities, render_trace_arena_filtered};
 
 // Loads project's figment and merges the build cli arguments into it
 foundry_config::merge_impl_figment_convert!(TestArgs, opts, evm_opts);
@@ -69,9 +93,39 @@ pub struct TestArgs {
     #[clap(long, value_name = "TEST_FUNCTION")]
     debug: Option<Regex>,
 
+    /// Show the source lines of the call sites and revert points of the project's contracts in
+    /// the traces, printed from `-vvv`.
+    ///
+    /// The lines are located with the solc source maps, so EraVM calls show the lines of the
+    /// EVM contracts calling into them.
+    #[clap(long)]
+    sources: bool,
+
     /// Print a gas report.
     #[clap(long, env = "FORGE_GAS_REPORT")]
-    gas_report: bool,
+    pub gas_report: bool,
+
+    /// The format of the gas report.
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = GasReportFormat::Table,
+        help_heading = "Display options"
+    )]
+    pub gas_report_format: GasReportFormat,
+
+    /// Write the gas report to the given file instead of printing it.
+    ///
+    /// A gas report written in the JSON format can be compared with `forge gas-report --diff`.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        requires = "gas_report",
+        help_heading = "Display options"
+    )]
+    pub gas_report_out: Option<PathBuf>,
 
     /// Exit with code 0 even if a test fails.
     #[clap(long, env = "FORGE_ALLOW_FAILURE")]
@@ -79,7 +133,7 @@ pub struct TestArgs {
 
     /// Output test results in JSON format.
     #[clap(long, short, help_heading = "Display options")]
-    json: bool,
+    pub json: bool,
 
     /// Stop running tests after the first failure.
     #[clap(long)]
@@ -100,11 +154,38 @@ pub struct TestArgs {
     #[clap(long, env = "FOUNDRY_FUZZ_RUNS", value_name = "RUNS")]
     pub fuzz_runs: Option<u64>,
 
+    /// Resume the invariant campaigns from their last checkpoint, written every
+    /// `invariant.checkpoint_interval` runs to the cache directory.
+    ///
+    /// Campaigns without a checkpoint start from the beginning.
+    #[clap(long)]
+    pub resume_campaign: bool,
+
+    /// Forbid tests to access the network, except for the fork URL, the `rpc_endpoints` and the
+    /// `offline_tests_allow_urls` of the config.
+    ///
+    /// Traces are decoded without Etherscan and the signatures database.
+    #[clap(long)]
+    pub offline_tests: bool,
+
     #[clap(flatten)]
     filter: FilterArgs,
 
+    /// Also run the tests of the library sources matching the specified glob pattern, e.g.
+    /// `lib/protocol/test/**`.
+    ///
+    /// The matching sources are compiled with the project, and their results are namespaced
+    /// with the name of their library.
+    #[clap(
+        long,
+        value_name = "GLOB",
+        help_heading = "Test filtering",
+        conflicts_with_all = ["incremental", "bundle_failures"]
+    )]
+    pub include_libs: Vec<GlobMatcher>,
+
     #[clap(flatten)]
-    evm_opts: EvmArgs,
+    pub evm_opts: EvmArgs,
 
     #[clap(flatten)]
     opts: CoreBuildArgs,
@@ -119,6 +200,155 @@ pub struct TestArgs {
     /// Print detailed test summary table.
     #[clap(long, help_heading = "Display options", requires = "summary")]
     pub detailed: bool,
+
+    /// Emit machine-readable progress events (compilation, suites and tests) during the run.
+    #[clap(long, value_enum, value_name = "FORMAT", help_heading = "Display options")]
+    pub event_stream: Option<EventStreamFormat>,
+
+    /// The file to write the event stream to. Defaults to stderr.
+    ///
+    /// On unix, an already opened file descriptor can be used with `/dev/fd/<N>`.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        requires = "event_stream",
+        help_heading = "Display options"
+    )]
+    pub event_stream_file: Option<PathBuf>,
+
+    /// Export the decoded execution traces of all tests to the given JSON file.
+    ///
+    /// The traces of two runs can be compared with `forge trace-diff`.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        conflicts_with = "json",
+        help_heading = "Display options"
+    )]
+    pub export_traces: Option<PathBuf>,
+
+    /// Run the test suite once per cell of the `test_matrix` config, i.e. against each of the
+    /// configured chains and hardforks.
+    #[clap(long, conflicts_with_all = ["debug", "json", "list", "export_traces"])]
+    pub matrix: bool,
+
+    /// Load an era_test_node state file, as written with `--dump-state`, before deploying the
+    /// test contracts.
+    ///
+    /// The state of a test can be exported with `vm.dumpEraState`.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub load_state: Option<PathBuf>,
+
+    /// Record the inputs of every test suite to the given file, and only run the suites whose
+    /// inputs changed since they last passed.
+    ///
+    /// The inputs of a suite are the artifacts of the contracts it deploys and the files it reads
+    /// with cheatcodes. `forge test --watch` records them in the cache directory.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        conflicts_with_all = ["debug", "list", "matrix"]
+    )]
+    pub incremental: Option<PathBuf>,
+
+    /// Write the failed tests to the given `.tar.gz` bundle, to reproduce them elsewhere with
+    /// `--from-bundle`.
+    ///
+    /// The bundle contains the fuzz seed, the fork block and RPC alias, the decoded traces, the
+    /// compiler versions, the solc and zksolc artifacts of the failed test contracts and a
+    /// snapshot of the `--load-state` state.
+    /// A fuzz seed is generated if none is set, so that the fuzz failures can be replayed.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        conflicts_with_all = ["list", "matrix"]
+    )]
+    pub bundle_failures: Option<PathBuf>,
+
+    /// Re-run the failed tests of a bundle written with `--bundle-failures`, with the same fuzz
+    /// seed, fork and era_test_node state.
+    ///
+    /// The test contracts that changed since the bundle was created are run from their bundled
+    /// artifacts, in both VMs.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        conflicts_with_all = ["matrix", "incremental"]
+    )]
+    pub from_bundle: Option<PathBuf>,
+
+    /// Add the results of the run to the metrics recorded in the given file, served by
+    /// `forge test --watch --metrics-addr`.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH", hide = true)]
+    pub metrics_out: Option<PathBuf>,
+
+    /// The config profile to run the tests with, like the `FOUNDRY_PROFILE` environment variable.
+    ///
+    /// The built-in `ci` profile runs the tests in performance mode, see `perf_mode`.
+    #[clap(long, value_name = "PROFILE")]
+    pub profile: Option<String>,
+
+    /// Resolve the `fork_block_number` tag of the config, e.g. `latest-safe`, again instead of
+    /// using the block pinned in the `fork.lock` file, and update the pin.
+    #[clap(long)]
+    pub refresh_fork_lock: bool,
+
+    /// Record the RPC requests of the run and their responses to the given cassette file, to
+    /// replay them with `--replay-rpc`.
+    ///
+    /// The fork cache is bypassed, so that all the requests of the forks are recorded.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub record_rpc: Option<PathBuf>,
+
+    /// Answer the RPC requests of the run from the given cassette file, written with
+    /// `--record-rpc`, without network access.
+    ///
+    /// Requests that weren't recorded fail.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        conflicts_with = "record_rpc"
+    )]
+    pub replay_rpc: Option<PathBuf>,
+}
+
+/// The format of the gas report printed with `--gas-report`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GasReportFormat {
+    /// Colored tables.
+    #[default]
+    Table,
+    /// Markdown tables, without colors.
+    Markdown,
+    /// One row per function.
+    Csv,
+    /// The full report, which can be compared with `forge gas-report --diff`.
+    Json,
+}
+
+impl GasReportFormat {
+    /// Renders the gas report in this format.
+    pub fn render(self, report: &GasReport) -> Result<String> {
+        Ok(match self {
+            Self::Table => report.to_string(),
+            Self::Markdown => report.to_markdown(),
+            Self::Csv => report.to_csv(),
+            Self::Json => serde_json::to_string_pretty(report)?,
+        })
+    }
+}
+
+/// The format of the event stream emitted with `--event-stream`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EventStreamFormat {
+    /// One JSON object per line.
+    Ndjson,
 }
 
 impl TestArgs {
@@ -129,25 +359,81 @@ impl TestArgs {
 
     pub async fn run(self) -> Result<TestOutcome> {
         trace!(target: "forge::test", "executing test command");
-        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        self.install_shell_and_profile()?;
         self.execute_tests().await
     }
 
+    /// Installs the shell of the command and selects its config profile.
+    pub fn install_shell_and_profile(&self) -> Result<()> {
+        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        if let Some(profile) = &self.profile {
+            Config::select_profile(Profile::new(profile));
+        }
+        Ok(())
+    }
+
     /// Executes all the tests in the project.
     ///
     /// This will trigger the build process first. On success all test contracts that match the
     /// configured filter will be executed
     ///
     /// Returns the test results for all matching tests.
+    ///
+    /// The RPC requests of the run are recorded to or replayed from the `--record-rpc` and
+    /// `--replay-rpc` cassettes.
     pub async fn execute_tests(self) -> Result<TestOutcome> {
+        let record_rpc = self.record_rpc.clone();
+        if let Some(path) = &self.replay_rpc {
+            let cassette = Cassette::read(path)
+                .wrap_err_with(|| format!("failed to read the RPC cassette {}", path.display()))?;
+            cassette::start_replay(cassette);
+        } else if record_rpc.is_some() {
+            cassette::start_recording();
+        }
+
+        let outcome = self.execute_project_tests().await;
+
+        let cassette = cassette::stop();
+        if let (Some(path), Some(cassette)) = (record_rpc, cassette) {
+            cassette.write(&path)?;
+            shell::println(format!(
+                "Recorded {} RPC requests to {}, replay them with `forge test --replay-rpc {}`",
+                cassette.len(),
+                path.display(),
+                path.display()
+            ))?;
+        }
+        outcome
+    }
+
+    /// Builds the project and runs its matching tests.
+    async fn execute_project_tests(mut self) -> Result<TestOutcome> {
+        let bundle = match self.from_bundle.clone() {
+            Some(path) => Some(self.apply_bundle(&path)?),
+            None => None,
+        };
+
         // Merge all configs
         let (mut config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
 
+        // The failures of a bundle are replayed with the seed they were found with
+        if self.bundle_failures.is_some() && config.fuzz.seed.is_none() {
+            config.fuzz.seed = Some(U256::from_be_bytes(B256::random().0));
+        }
+
         // Explicitly enable isolation for gas reports for more correct gas accounting
         if self.gas_report {
             evm_opts.isolate = true;
         }
 
+        // Performance mode only shows the traces of the failing tests
+        if config.perf_mode {
+            evm_opts.verbosity = evm_opts.verbosity.min(PERF_MODE_MAX_VERBOSITY);
+        }
+
+        // Fork from the block the tag of the config is pinned to in the fork lockfile
+        utils::pin_config_fork_block(&config, &mut evm_opts, self.refresh_fork_lock).await?;
+
         // Set up the project.
         let mut project = config.project()?;
 
@@ -160,51 +446,191 @@ impl TestArgs {
             project = config.project()?;
         }
 
+        // The state of a zkSync Era chain is only executable in EraVM
+        if utils::detect_zksync_fork(&mut config, evm_opts.fork_url.as_deref()).await && !self.json
+        {
+            shell::println("Forking a zkSync Era chain, running the tests in EraVM")?;
+        }
+
         let mut filter = self.filter(&config);
         trace!(target: "forge::test", ?filter, "using filter");
 
+        let event_stream = self
+            .event_stream
+            .map(|_| EventStream::create(self.event_stream_file.as_deref()).map(Arc::new))
+            .transpose()?;
+        let emit = |event: TestEvent<'_>| {
+            if let Some(events) = &event_stream {
+                events.emit(event);
+            }
+        };
+
+        let lib_files = self.included_lib_files(&project.paths.root)?;
         let mut compiler = ProjectCompiler::new().quiet_if(self.json || self.opts.silent);
-        if config.sparse_mode {
+        if !lib_files.is_empty() {
+            compiler = compiler
+                .files(project.paths.input_files().into_iter().chain(lib_files.iter().cloned()));
+        } else if config.sparse_mode {
             compiler = compiler.filter(Box::new(filter.clone()));
         }
+        emit(TestEvent::CompileStarted { compiler: "solc" });
+        let timer = Instant::now();
         let output = compiler.compile(&project)?;
+        emit(TestEvent::CompileFinished {
+            compiler: "solc",
+            duration_ms: timer.elapsed().as_millis(),
+        });
 
-        let mut zksolc = ZkSolc::new(
-            config
-                .new_zksolc_config_builder()
-                .and_then(|builder| {
-                    builder
-                        .avoid_contracts(self.opts.compiler.avoid_contracts.clone())
-                        .contracts_to_compile(self.opts.compiler.contracts_to_compile.clone())
-                        .build()
-                })
-                .map_err(|e| eyre::eyre!(e))?,
-            config.zk_project()?,
-        );
-        let (zk_output, _contract_bytecodes) = match zksolc.compile() {
-            Ok(compiled) => compiled,
-            Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
+        // The contracts are only compiled with zksolc to run the tests in EraVM
+        let zk_output = if config.zksync {
+            let mut zksolc = ZkSolc::new(
+                config
+                    .new_zksolc_config_builder()
+                    .and_then(|builder| {
+                        builder
+                            .avoid_contracts(self.opts.compiler.avoid_contracts.clone())
+                            .contracts_to_compile(self.opts.compiler.contracts_to_compile.clone())
+                            .files(lib_files)
+                            .build()
+                    })
+                    .map_err(|e| eyre::eyre!(e))?,
+                config.zk_project()?,
+            );
+            emit(TestEvent::CompileStarted { compiler: "zksolc" });
+            let timer = Instant::now();
+            let (zk_output, _contract_bytecodes) = match zksolc.compile() {
+                Ok(compiled) => compiled,
+                Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
+            };
+            emit(TestEvent::CompileFinished {
+                compiler: "zksolc",
+                duration_ms: timer.elapsed().as_millis(),
+            });
+            Some(zk_output)
+        } else {
+            None
+        };
+
+        // Warn about the functions of the source contracts whose ABI types approach the EraVM
+        // calldata limit, the warnings are reported with the suites importing the contracts.
+        let abi_size_warnings = if config.zksync {
+            self.abi_size_warnings(&project, &output)
+        } else {
+            Default::default()
         };
-        let dual_compiled_contracts = new_dual_compiled_contracts(&output, &zk_output);
+
+        let mut bundled_artifacts = BTreeMap::new();
+        if let Some((bundle, dir)) = &bundle {
+            let artifacts = artifact_hashes(&output, &project.paths.root);
+            let zk_artifacts =
+                zk_output.as_ref().map(|output| artifact_hashes(output, &project.paths.root));
+            let hashes =
+                ArtifactHashes { artifacts: &artifacts, zk_artifacts: zk_artifacts.as_ref() };
+            for difference in bundle.differences(&hashes, &solc_versions(&output)) {
+                shell::println(Paint::yellow(format!("Warning: {difference}")))?;
+            }
+            bundled_artifacts = bundle.changed_artifacts(dir, &hashes);
+            if config.zksync && bundled_artifacts.values().any(|bundled| bundled.zk_path.is_none())
+            {
+                shell::println(Paint::yellow(
+                    "Warning: the bundle wasn't created in EraVM and has no EraVM bytecode, \
+                     replaying the tests with the current artifacts",
+                ))?;
+                bundled_artifacts.clear();
+            }
+        }
 
         // Create test options from general project settings and compiler output.
         let project_root = &project.paths.root;
         let toml = config.get_config_path();
         let profiles = get_available_profiles(toml)?;
 
-        let test_options: TestOptions = TestOptionsBuilder::default()
+        let mut test_options: TestOptions = TestOptionsBuilder::default()
             .fuzz(config.fuzz)
-            .invariant(config.invariant)
+            .invariant(config.invariant.clone())
+            .budget(config.test_budget())
             .profiles(profiles)
             .build(&output, project_root)?;
+        test_options.checkpoint_dir = Some(config.cache_path.join("invariant"));
+        test_options.resume_campaign = self.resume_campaign;
+        // Performance mode runs the passing tests without tracing and drops the other traces of
+        // the passing tests as they finish, unless the gas report or the exported traces need them
+        test_options.drop_passing_traces =
+            config.perf_mode && !self.gas_report && self.export_traces.is_none();
 
         // Determine print verbosity and executor verbosity
         let verbosity = evm_opts.verbosity;
-        if self.gas_report && evm_opts.verbosity < 3 {
+        if (self.gas_report || self.export_traces.is_some() || self.bundle_failures.is_some()) &&
+            evm_opts.verbosity < 3
+        {
             evm_opts.verbosity = 3;
         }
 
-        let env = evm_opts.evm_env().await?;
+        if self.matrix {
+            eyre::ensure!(
+                !config.test_matrix.is_empty(),
+                "`--matrix` requires `test_matrix` to be set in the config"
+            );
+
+            let mut outcome = TestOutcome::empty(self.allow_failure);
+            let mut summaries = Vec::with_capacity(config.test_matrix.len());
+            for cell in &config.test_matrix {
+                let name = cell.name();
+                let cell_config = cell.apply(&config);
+                let mut cell_evm_opts = evm_opts.clone();
+                if let Some(chain) = cell.chain_id {
+                    cell_evm_opts.env.chain_id = Some(chain.id());
+                }
+
+                let header = Paint::cyan(format!("Matrix cell: {name}")).bold();
+                shell::println(format!("\n{header}"))?;
+                let runner = self
+                    .build_runner(
+                        &cell_config,
+                        cell_evm_opts,
+                        project_root,
+                        output.clone(),
+                        zk_output.clone(),
+                        &test_options,
+                        event_stream.clone(),
+                    )
+                    .await?;
+                let cell_outcome = self
+                    .run_tests(runner, cell_config, verbosity, &filter, test_options.clone())
+                    .await?;
+
+                summaries.push(MatrixCellSummary::new(name.clone(), &cell_outcome));
+                let failed = cell_outcome.failed() > 0;
+                outcome.results.extend(
+                    cell_outcome
+                        .results
+                        .into_iter()
+                        .map(|(id, suite)| (format!("{id} [{name}]"), suite)),
+                );
+
+                if self.fail_fast && failed {
+                    break;
+                }
+            }
+
+            shell::println("\n\nTest Matrix Summary:")?;
+            print_matrix_summary(&summaries);
+            return Ok(outcome);
+        }
+
+        // Compare the inputs of the suites of the last run before the output is consumed.
+        let incremental = self.incremental.as_ref().map(|path| {
+            let recorded = TestInputs::read(path);
+            let artifacts = artifact_hashes(&output, project_root);
+            let config_hash = config_hash(&config);
+            let unchanged = recorded.unchanged_suites(config_hash, &artifacts);
+            (path, recorded, artifacts, config_hash, unchanged)
+        });
+
+        let mut bundle_context = self
+            .bundle_failures
+            .as_ref()
+            .map(|_| BundleContext::new(&config, &output, zk_output.as_ref()));
 
         // Prepare the test builder
         let should_debug = self.debug.is_some();
@@ -212,22 +638,51 @@ impl TestArgs {
         // Clone the output only if we actually need it later for the debugger.
         let output_clone = should_debug.then(|| output.clone());
 
-        let mut runner = MultiContractRunnerBuilder::default()
-            .set_debug(should_debug)
-            .initial_balance(evm_opts.initial_balance)
-            .evm_spec(config.evm_spec_id())
-            .sender(evm_opts.sender)
-            .with_fork(evm_opts.get_fork(&config, env.clone()))
-            .with_cheats_config(CheatsConfig::new(
+        let mut runner = self
+            .build_runner(
                 &config,
-                evm_opts.clone(),
-                None,
-                dual_compiled_contracts,
-                config.zksync,
-            ))
-            .with_test_options(test_options.clone())
-            .enable_isolation(evm_opts.isolate)
-            .build(project_root, output, env, evm_opts)?;
+                evm_opts,
+                project_root,
+                output,
+                zk_output,
+                &test_options,
+                event_stream,
+            )
+            .await?;
+
+        for suite in use_bundled_artifacts(&mut runner.contracts, &bundled_artifacts)? {
+            shell::println(format!("Replaying {suite} with the artifact of the bundle"))?;
+        }
+
+        runner.abi_size_warnings = abi_size_warnings;
+
+        if let Some(context) = &mut bundle_context {
+            context.known_contracts = runner.known_contracts.clone();
+            context.state = runner.era_state.clone();
+            context.manifest.fork_block =
+                runner.fork.is_some().then(|| runner.env.block.number.saturating_to());
+        }
+
+        let known_contracts = incremental.is_some().then(|| runner.known_contracts.clone());
+        let mut skipped = 0;
+        if let Some((_, _, _, _, unchanged)) = &incremental {
+            runner.contracts.retain(|id, _| {
+                let skip = unchanged.contains(&id.identifier());
+                if skip && filter.matches_path(&id.source) && filter.matches_contract(&id.name) {
+                    skipped += 1;
+                }
+                !skip
+            });
+            if skipped > 0 {
+                let suites = if skipped > 1 { "suites" } else { "suite" };
+                shell::println(format!("Skipping {skipped} unchanged test {suites}"))?;
+                if runner.matching_test_function_count(&filter) == 0 {
+                    let outcome = TestOutcome::empty(self.allow_failure);
+                    self.record_metrics(&outcome, skipped)?;
+                    return Ok(outcome);
+                }
+            }
+        }
 
         if let Some(debug_test_pattern) = &self.debug {
             let test_pattern = &mut filter.args_mut().test_pattern;
@@ -239,9 +694,41 @@ impl TestArgs {
             }
             *test_pattern = Some(debug_test_pattern.clone());
         }
-        runner.use_zk = config.zksync;
 
         let outcome = self.run_tests(runner, config, verbosity, &filter, test_options).await?;
+        self.record_metrics(&outcome, skipped)?;
+
+        if let (Some((path, mut recorded, artifacts, config_hash, unchanged)), Some(known)) =
+            (incremental, &known_contracts)
+        {
+            // suites that only ran some of their tests are run again next time
+            recorded.suites.retain(|suite, _| unchanged.contains(suite));
+            if filter.is_empty() {
+                let mut identifier = LocalTraceIdentifier::new(known);
+                for (suite, result) in &outcome.results {
+                    let inputs = SuiteInputs::new(suite, result, &mut identifier, &artifacts);
+                    recorded.suites.insert(suite.clone(), inputs);
+                }
+            }
+            recorded.config = config_hash;
+            recorded.write(path)?;
+        }
+
+        if let (Some(path), Some(context)) = (&self.bundle_failures, bundle_context) {
+            if outcome.failed() > 0 {
+                let bundled = context
+                    .write(path, &outcome)
+                    .await
+                    .wrap_err_with(|| format!("failed to write the bundle {}", path.display()))?;
+                let tests = if bundled > 1 { "tests" } else { "test" };
+                shell::println(format!(
+                    "Bundled {bundled} failed {tests} to {}, re-run them with `forge test \
+                     --from-bundle {}`",
+                    path.display(),
+                    path.display()
+                ))?;
+            }
+        }
 
         if should_debug {
             // There is only one test.
@@ -269,6 +756,152 @@ impl TestArgs {
         Ok(outcome)
     }
 
+    /// Adds the results of the run to the metrics recorded at `--metrics-out`, if set.
+    fn record_metrics(&self, outcome: &TestOutcome, suites_cached: usize) -> Result<()> {
+        let Some(path) = &self.metrics_out else { return Ok(()) };
+        let (fork_cache_hits, fork_cache_misses) = forge::fork::cache_stats();
+        let counters = RunCounters {
+            suites_cached,
+            fork_cache_hits,
+            fork_cache_misses,
+            rpc_requests: foundry_common::provider::tower::requests_sent(),
+        };
+        let mut metrics = TestMetrics::read(path);
+        metrics.record(outcome, counters);
+        metrics.write(path)
+    }
+
+    /// Returns the static size warnings of the ABIs of the source contracts, by the test source
+    /// files importing them relative to the root, and prints them unless the output is JSON.
+    fn abi_size_warnings(
+        &self,
+        project: &Project,
+        output: &ProjectCompileOutput,
+    ) -> BTreeMap<PathBuf, Vec<SizeLimitWarning>> {
+        let root = &project.paths.root;
+        let mut warnings = BTreeMap::<PathBuf, Vec<_>>::new();
+        for (id, artifact) in output.artifact_ids() {
+            if !id.source.starts_with(&project.paths.sources) {
+                continue
+            }
+            let Some(abi) = &artifact.abi else { continue };
+            for warning in abi_size_warnings(&id.name, abi) {
+                if !self.json {
+                    eprintln!("{} {warning}", Paint::yellow("Warning:").bold());
+                }
+                warnings.entry(id.source.clone()).or_default().push(warning);
+            }
+        }
+        if warnings.is_empty() {
+            return Default::default()
+        }
+
+        let imports = SolFilesCache::read_joined(&project.paths)
+            .map(|cache| {
+                let imports = |entry: CacheEntry| {
+                    entry.imports.into_iter().map(|import| root.join(import)).collect()
+                };
+                cache.files.into_iter().map(|(file, entry)| (file, imports(entry))).collect()
+            })
+            .unwrap_or_default();
+        output
+            .artifact_ids()
+            .map(|(id, _)| id.source)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|source| {
+                let imported = imported_size_warnings(&source, &warnings, &imports);
+                let source = source.strip_prefix(root).map(Path::to_path_buf).unwrap_or(source);
+                (!imported.is_empty()).then_some((source, imported))
+            })
+            .collect()
+    }
+
+    /// Extracts the bundle at `path` to the cache directory and configures the run to replay its
+    /// failed tests, unless the arguments already override them.
+    fn apply_bundle(&mut self, path: &Path) -> Result<(BundleManifest, PathBuf)> {
+        let config = self.load_config();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let name = name.trim_end_matches(".gz").trim_end_matches(".tar").trim_end_matches(".tgz");
+        let dir = config.cache_path.join("bundles").join(name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        let manifest = BundleManifest::extract(path, &dir)?;
+        eyre::ensure!(!manifest.failures.is_empty(), "the bundle {} is empty", path.display());
+
+        if self.filter.filter_expr.is_none() {
+            let filter = manifest.filter_expr().parse().map_err(|e| eyre::eyre!("{e}"))?;
+            self.filter.filter_expr = Some(filter);
+        }
+        if self.fuzz_seed.is_none() {
+            self.fuzz_seed = manifest.fuzz_seed;
+        }
+        if self.evm_opts.fork_url.is_none() {
+            if let Some(alias) = &manifest.fork_rpc {
+                self.evm_opts.fork_url = Some(alias.clone());
+                self.evm_opts.fork_block_number = manifest.fork_block;
+            } else if manifest.fork_block.is_some() {
+                eyre::bail!(
+                    "the tests of the bundle were forked from an RPC URL that isn't an alias of \
+                     `rpc_endpoints`, pass it with `--fork-url`"
+                )
+            }
+        } else if self.evm_opts.fork_block_number.is_none() {
+            self.evm_opts.fork_block_number = manifest.fork_block;
+        }
+        self.opts.compiler.zksync |= manifest.zksync;
+        if self.load_state.is_none() {
+            self.load_state = manifest.state_path(&dir);
+        }
+
+        let tests = if manifest.failures.len() > 1 { "tests" } else { "test" };
+        shell::println(format!(
+            "Replaying {} failed {tests} of {}",
+            manifest.failures.len(),
+            path.display()
+        ))?;
+        Ok((manifest, dir))
+    }
+
+    /// Builds the runner of the compiled tests, executing them with `config` and `evm_opts`.
+    #[allow(clippy::too_many_arguments)]
+    async fn build_runner(
+        &self,
+        config: &Config,
+        evm_opts: EvmOpts,
+        project_root: &Path,
+        output: ProjectCompileOutput,
+        zk_output: Option<ProjectCompileOutput>,
+        test_options: &TestOptions,
+        event_stream: Option<Arc<EventStream>>,
+    ) -> Result<MultiContractRunner> {
+        let env = evm_opts.evm_env().await?;
+
+        let mut runner = MultiContractRunnerBuilder::default()
+            .set_debug(self.debug.is_some())
+            .initial_balance(evm_opts.initial_balance)
+            .evm_spec(config.evm_spec_id())
+            .sender(evm_opts.sender)
+            .with_fork(evm_opts.get_fork(config, env.clone()))
+            .with_cheats_config(CheatsConfig::new(config, evm_opts.clone(), None, config.zksync))
+            .with_test_options(test_options.clone())
+            .enable_isolation(evm_opts.isolate)
+            .with_event_stream(event_stream)
+            .with_genesis(config.genesis.clone())
+            .with_load_state(self.load_state.clone())
+            .record_inputs(self.incremental.is_some())
+            .full_traces(
+                self.gas_report || self.export_traces.is_some() || self.bundle_failures.is_some(),
+            )
+            .with_zk_output(zk_output)
+            .with_zk_libraries(if config.zksync { config.zk_libraries()? } else { vec![] })
+            .with_trace_sources(self.sources)
+            .build(project_root, output, env, evm_opts)?;
+        runner.use_zk = config.zksync;
+        Ok(runner)
+    }
+
     /// Run all tests that matches the filter predicate from a test runner
     pub async fn run_tests(
         &self,
@@ -314,17 +947,53 @@ impl TestArgs {
             );
         }
 
+        let event_stream = runner.event_stream.clone();
+        let emit_run_finished = |outcome: &TestOutcome, duration: Duration| {
+            if let Some(events) = &event_stream {
+                events.emit(TestEvent::RunFinished {
+                    passed: outcome.passed(),
+                    failed: outcome.failed(),
+                    skipped: outcome.skipped(),
+                    duration_ms: duration.as_millis(),
+                });
+            }
+        };
+
         if self.json {
-            let results = runner.test_collect(filter, test_options).await;
-            println!("{}", serde_json::to_string(&results)?);
-            return Ok(TestOutcome::new(results, self.allow_failure));
+            let timer = Instant::now();
+            let results = runner
+                .test_iter(filter, test_options)
+                .await
+                .map(|(id, suite)| (filter.suite_name(id), suite))
+                .collect::<BTreeMap<_, _>>();
+            shell::println(serde_json::to_string(&results)?)?;
+            let outcome = TestOutcome::new(results, self.allow_failure);
+            emit_run_finished(&outcome, timer.elapsed());
+            return Ok(outcome);
         }
 
         // Set up trace identifiers.
         let known_contracts = runner.known_contracts.clone();
+        let trace_sources = runner.trace_sources.clone();
+        let ffi_log = runner.cheats_config.ffi_log.clone();
+        // the pubdata of the zk deployments, reported next to their ergs
+        let zk_pubdata = self.gas_report.then(|| {
+            runner
+                .cheats_config
+                .dual_compiled_contracts
+                .iter()
+                .map(|contract| {
+                    (contract.name.clone(), pubdata::deployment_pubdata(&contract.factory_deps()))
+                })
+                .collect::<Vec<_>>()
+        });
         let mut local_identifier = LocalTraceIdentifier::new(&known_contracts);
         let remote_chain_id = runner.evm_opts.get_remote_chain_id();
-        let mut etherscan_identifier = EtherscanIdentifier::new(&config, remote_chain_id)?;
+        let mut etherscan_identifier = if config.offline_tests {
+            EtherscanIdentifier::default()
+        } else {
+            EtherscanIdentifier::new(&config, remote_chain_id)?
+        };
 
         // Run tests.
         let (tx, rx) = channel::<(String, SuiteResult)>();
@@ -334,8 +1003,10 @@ impl TestArgs {
             async move { runner.test(&filter, tx, test_options).await }
         });
 
-        let mut gas_report =
-            self.gas_report.then(|| GasReport::new(config.gas_reports, config.gas_reports_ignore));
+        let mut gas_report = self.gas_report.then(|| {
+            GasReport::new(config.gas_reports, config.gas_reports_ignore)
+                .with_zk_pubdata(zk_pubdata.unwrap_or_default())
+        });
 
         // Build the trace decoder.
         let mut builder = CallTraceDecoderBuilder::new()
@@ -345,18 +1016,31 @@ impl TestArgs {
         if !self.gas_report {
             builder = builder.with_signature_identifier(SignaturesIdentifier::new(
                 Config::foundry_cache_dir(),
-                config.offline,
+                config.offline || config.offline_tests,
             )?);
         }
         let mut decoder = builder.build();
 
         // We identify addresses if we're going to print *any* trace or gas report.
-        let identify_addresses = verbosity >= 3 || self.gas_report || self.debug.is_some();
+        let identify_addresses = verbosity >= 3 ||
+            self.gas_report ||
+            self.debug.is_some() ||
+            self.export_traces.is_some();
+
+        let mut exported_traces = self.export_traces.as_ref().map(|_| ExportedTraces::default());
+
+        // The gas report and the exported traces need the traces of the passing tests as well.
+        let mut perf_report = (config.perf_mode && !self.gas_report && exported_traces.is_none())
+            .then(PerfModeReport::default);
 
         let mut outcome = TestOutcome::empty(self.allow_failure);
 
         let mut any_test_failed = false;
         for (contract_name, suite_result) in rx {
+            let contract_name = filter.suite_name(contract_name);
+            if let Some(perf_report) = &mut perf_report {
+                perf_report.record_dropped(&suite_result);
+            }
             let tests = &suite_result.test_results;
 
             // Print suite header.
@@ -364,6 +1048,9 @@ impl TestArgs {
             for warning in suite_result.warnings.iter() {
                 eprintln!("{} {warning}", Paint::yellow("Warning:").bold());
             }
+            for warning in suite_result.size_warnings.iter() {
+                eprintln!("{} {warning}", Paint::yellow("Warning:").bold());
+            }
             if !tests.is_empty() {
                 let len = tests.len();
                 let tests = if len > 1 { "tests" } else { "test" };
@@ -400,33 +1087,54 @@ impl TestArgs {
                 decoder
                     .labels
                     .extend(result.labeled_addresses.iter().map(|(k, v)| (*k, v.clone())));
+                decoder.proxies.extend(result.proxies.iter().map(|(k, v)| (*k, *v)));
 
                 // Identify addresses and decode traces.
+                let decoding = Instant::now();
                 let mut decoded_traces = Vec::with_capacity(result.traces.len());
                 for (kind, arena) in &result.traces {
-                    if identify_addresses {
-                        decoder.identify(arena, &mut local_identifier);
-                        decoder.identify(arena, &mut etherscan_identifier);
-                    }
-
                     // verbosity:
                     // - 0..3: nothing
                     // - 3: only display traces for failed tests
                     // - 4: also display the setup trace for failed tests
                     // - 5..: display all traces for all tests
-                    let should_include = match kind {
-                        TraceKind::Execution => {
-                            (verbosity == 3 && result.status.is_failure()) || verbosity >= 4
-                        }
-                        TraceKind::Setup => {
-                            (verbosity == 4 && result.status.is_failure()) || verbosity >= 5
-                        }
-                        TraceKind::Deployment => false,
+                    //
+                    // The verbosity of the calls after a `vm.setTraceVerbosity` is the one it sets.
+                    let (failure_verbosity, min_verbosity) = match kind {
+                        TraceKind::Execution => (3, 4),
+                        TraceKind::Setup => (4, 5),
+                        TraceKind::Deployment => (u8::MAX, u8::MAX),
                     };
+                    let shown = node_verbosities(arena, verbosity)
+                        .into_iter()
+                        .map(|verbosity| {
+                            verbosity >= min_verbosity ||
+                                (verbosity >= failure_verbosity && result.status.is_failure())
+                        })
+                        .collect::<Vec<_>>();
+                    let should_include = shown.contains(&true);
+
+                    if identify_addresses || should_include {
+                        decoder.identify(arena, &mut local_identifier);
+                        decoder.identify(arena, &mut etherscan_identifier);
+                    }
 
                     if should_include {
-                        decoded_traces.push(render_trace_arena(arena, &decoder).await?);
+                        let sources = trace_sources.as_deref();
+                        let trace = render_trace_arena_filtered(arena, &decoder, sources, &shown);
+                        decoded_traces.push(trace.await?);
                     }
+
+                    if let Some(exported_traces) = &mut exported_traces {
+                        if kind.is_execution() {
+                            exported_traces.insert(&contract_name, name, arena, &decoder).await;
+                        }
+                    }
+                }
+
+                if let Some(perf_report) = &mut perf_report {
+                    let calls = result.traces.iter().map(|(_, arena)| arena.nodes().len()).sum();
+                    perf_report.record_decoding(calls, decoding.elapsed());
                 }
 
                 if !decoded_traces.is_empty() {
@@ -437,7 +1145,7 @@ impl TestArgs {
                 }
 
                 if let Some(gas_report) = &mut gas_report {
-                    gas_report.analyze(&result.traces, &decoder).await;
+                    gas_report.analyze(&result.traces, &decoder, &result.zk_calls).await;
                 }
             }
 
@@ -456,15 +1164,44 @@ impl TestArgs {
 
         trace!(target: "forge::test", len=outcome.results.len(), %any_test_failed, "done with results");
 
+        emit_run_finished(&outcome, duration);
+
         outcome.decoder = Some(decoder);
 
         if let Some(gas_report) = gas_report {
-            shell::println(gas_report.finalize())?;
+            let gas_report = gas_report.finalize();
+            let rendered = self.gas_report_format.render(&gas_report)?;
+            if let Some(path) = &self.gas_report_out {
+                fs::write(path, rendered)?;
+                shell::println(format!("Gas report written to {}", path.display()))?;
+            } else {
+                shell::println(rendered)?;
+            }
+            outcome.gas_report = Some(gas_report);
+        }
+
+        if let (Some(path), Some(exported_traces)) = (&self.export_traces, &exported_traces) {
+            fs::write_json_file(path, exported_traces)?;
+            shell::println(format!("Traces exported to {}", path.display()))?;
         }
 
         if !outcome.results.is_empty() {
             shell::println(outcome.summary(duration))?;
 
+            if let Some(perf_report) =
+                perf_report.filter(|report| report.dropped_tests > 0 || report.untraced_tests > 0)
+            {
+                shell::println(perf_report)?;
+            }
+
+            let ffi_invocations = std::mem::take(&mut *ffi_log.lock());
+            if !ffi_invocations.is_empty() {
+                shell::println("\nFFI invocations:")?;
+                for invocation in ffi_invocations {
+                    shell::println(format!("  {invocation}"))?;
+                }
+            }
+
             if self.summary {
                 let mut summary_table = TestSummaryReporter::new(self.detailed);
                 shell::println("\n\nTest Summary:")?;
@@ -485,7 +1222,22 @@ impl TestArgs {
 
     /// Returns the flattened [`FilterArgs`] arguments merged with [`Config`].
     pub fn filter(&self, config: &Config) -> ProjectPathsAwareFilter {
-        self.filter.clone().merge_with_config(config)
+        self.filter.clone().merge_with_config(config).include_libs(self.include_libs.clone())
+    }
+
+    /// Returns the Solidity files matching `--include-libs`, to compile with the project.
+    fn included_lib_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        if self.include_libs.is_empty() {
+            return Ok(Vec::new())
+        }
+        let files = expand_globs(root, self.include_libs.iter().map(GlobMatcher::as_str))?
+            .into_iter()
+            .filter(|file| file.is_sol())
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            eyre::bail!("no Solidity files match `--include-libs`")
+        }
+        Ok(files)
     }
 
     /// Returns whether `BuildArgs` was configured with `--watch`
@@ -496,10 +1248,8 @@ impl TestArgs {
     /// Returns the [`watchexec::InitConfig`] and [`watchexec::RuntimeConfig`] necessary to
     /// bootstrap a new [`watchexe::Watchexec`] loop.
     pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
-        self.watch.watchexec_config(|| {
-            let config = Config::from(self);
-            vec![config.src, config.test]
-        })
+        let config = Config::from(self);
+        self.watch.watchexec_config(config.get_config_path(), || vec![config.src, config.test])
     }
 }
 
@@ -520,6 +1270,14 @@ impl Provider for TestArgs {
         }
         dict.insert("fuzz".to_string(), fuzz_dict.into());
 
+        if self.offline_tests {
+            dict.insert("offline_tests".to_string(), true.into());
+        }
+
+        if self.record_rpc.is_some() || self.replay_rpc.is_some() {
+            dict.insert("no_storage_caching".to_string(), true.into());
+        }
+
         if let Some(etherscan_api_key) =
             self.etherscan_api_key.as_ref().filter(|s| !s.trim().is_empty())
         {
@@ -555,6 +1313,7 @@ fn list(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use foundry_compilers::FileFilter;
     use foundry_config::Chain;
 
     #[test]
@@ -569,6 +1328,77 @@ mod tests {
         assert!(args.fuzz_seed.is_some());
     }
 
+    #[test]
+    fn filter_expr() {
+        let args: TestArgs = TestArgs::parse_from([
+            "foundry-cli",
+            "--filter-expr",
+            "(contract =~ Token.* and not test =~ Fork) or path =~ zk/",
+        ]);
+        assert!(args.filter.filter_expr.is_some());
+        assert!(!args.filter.is_empty());
+
+        let err = TestArgs::try_parse_from(["foundry-cli", "--filter-expr", "name =~ Foo"]);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn matrix() {
+        let args: TestArgs = TestArgs::parse_from(["foundry-cli", "--matrix", "--fail-fast"]);
+        assert!(args.matrix);
+
+        assert!(TestArgs::try_parse_from(["foundry-cli", "--matrix", "--json"]).is_err());
+        assert!(TestArgs::try_parse_from(["foundry-cli", "--matrix", "--list"]).is_err());
+    }
+
+    #[test]
+    fn include_libs() {
+        let args: TestArgs = TestArgs::parse_from([
+            "foundry-cli",
+            "--include-libs",
+            "lib/protocol/test/**",
+            "--include-libs",
+            "lib/other/test/*.t.sol",
+        ]);
+        assert_eq!(args.include_libs.len(), 2);
+
+        let filter = args.filter(&Config::with_root("/project"));
+        let pool = Path::new("lib/protocol/test/Pool.t.sol");
+        assert_eq!(filter.included_library(pool), Some("protocol".to_string()));
+        assert_eq!(filter.included_library(Path::new("lib/protocol/src/Pool.sol")), None);
+        assert_eq!(filter.included_library(Path::new("test/Pool.t.sol")), None);
+        assert!(filter.matches_path(pool));
+        assert_eq!(
+            filter.suite_name("lib/protocol/test/Pool.t.sol:PoolTest".to_string()),
+            "lib/protocol/test/Pool.t.sol:PoolTest [protocol]"
+        );
+        assert_eq!(
+            filter.suite_name("test/Pool.t.sol:PoolTest".to_string()),
+            "test/Pool.t.sol:PoolTest"
+        );
+
+        let args = ["foundry-cli", "--include-libs", "lib/**", "--bundle-failures", "b.tar.gz"];
+        assert!(TestArgs::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn filters_project_relative_paths() {
+        let args: TestArgs = TestArgs::parse_from([
+            "foundry-cli",
+            "--match-path",
+            "test/*.t.sol",
+            "--filter-expr",
+            "path =~ ^test/",
+        ]);
+        let filter = args.filter(&Config::with_root("/project"));
+        for path in ["test/Pool.t.sol", "/project/test/Pool.t.sol"] {
+            assert!(filter.is_match(Path::new(path)), "{path}");
+            assert!(filter.matches_path(Path::new(path)), "{path}");
+            assert!(filter.matches_qualified_test(Path::new(path), "PoolTest", "test_swap"));
+        }
+        assert!(!filter.matches_path(Path::new("/project/src/test/Pool.t.sol")));
+    }
+
     // <https://github.com/foundry-rs/foundry/issues/5913>
     #[test]
     fn fuzz_seed_exists() {
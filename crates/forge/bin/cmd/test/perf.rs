@@ -0,0 +1,191 @@
+//! The performance mode of `forge test`, enabled with `perf_mode` and by default in the built-in
+//! `ci` profile.
+//!
+//! Recording, retaining and decoding the traces of the passing tests is most of the overhead of a
+//! run at `-vvv` and above, especially in zk mode, while only the traces of the failing tests are
+//! printed. In performance mode the unit tests run without tracing, and only the failing ones run
+//! again with tracing. The traces of the other passing tests are dropped as soon as they finish,
+//! before the other results of their suite are collected, and only the ones of the failing tests
+//! are decoded.
+//!
+//! The first passing unit test of each suite runs again with tracing, and the overhead of tracing
+//! it measures estimates the time saved by the untraced runs.
+
+use forge::result::{SuiteResult, TestStatus};
+use std::{fmt, time::Duration};
+
+/// The maximum verbosity of a run in performance mode, which only shows the traces of the
+/// failing tests.
+pub const PERF_MODE_MAX_VERBOSITY: u8 = 3;
+
+/// The tests run without tracing and the traces dropped and decoded by a run in performance mode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PerfModeReport {
+    /// The passing tests run without tracing.
+    pub untraced_tests: usize,
+    /// The time spent running the tests without tracing.
+    pub untraced: Duration,
+    /// The time spent running the sampled tests without tracing.
+    pub sampled_untraced: Duration,
+    /// The time spent running the sampled tests with tracing.
+    pub sampled_traced: Duration,
+    /// The passing tests whose traces were dropped.
+    pub dropped_tests: usize,
+    /// The calls of the dropped traces.
+    pub dropped_calls: usize,
+    /// The calls of the traces of the failing tests, which were decoded.
+    pub decoded_calls: usize,
+    /// The time spent decoding the traces of the failing tests.
+    pub decoding: Duration,
+}
+
+impl PerfModeReport {
+    /// Records the tests run without tracing and the traces dropped by the runner from the passing
+    /// tests of `suite`.
+    pub fn record_dropped(&mut self, suite: &SuiteResult) {
+        for result in suite.test_results.values() {
+            if result.untraced && result.status == TestStatus::Success {
+                self.untraced_tests += 1;
+                self.untraced += result.duration;
+            }
+            if let Some((untraced, traced)) = result.tracing_sample {
+                self.sampled_untraced += untraced;
+                self.sampled_traced += traced;
+            }
+            if result.dropped_trace_calls > 0 {
+                self.dropped_tests += 1;
+                self.dropped_calls += result.dropped_trace_calls;
+            }
+        }
+    }
+
+    /// Returns the time saved by running the passing tests without tracing, estimated from the
+    /// overhead of tracing the sampled tests, if any was sampled.
+    pub fn saved(&self) -> Option<Duration> {
+        if self.sampled_untraced.is_zero() {
+            return None
+        }
+        let overhead = self.sampled_traced.saturating_sub(self.sampled_untraced);
+        let saved =
+            self.untraced.as_nanos() * overhead.as_nanos() / self.sampled_untraced.as_nanos();
+        Some(Duration::from_nanos(saved.try_into().unwrap_or(u64::MAX)))
+    }
+
+    /// Records the time spent identifying and decoding the `calls` calls of the traces of a
+    /// failing test.
+    pub fn record_decoding(&mut self, calls: usize, duration: Duration) {
+        self.decoded_calls += calls;
+        self.decoding += duration;
+    }
+}
+
+impl fmt::Display for PerfModeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n| if n == 1 { "test" } else { "tests" };
+        f.write_str("Performance mode: ")?;
+        if self.untraced_tests > 0 {
+            write!(
+                f,
+                "ran {} passing {} without tracing",
+                self.untraced_tests,
+                plural(self.untraced_tests)
+            )?;
+            if let Some(saved) = self.saved() {
+                write!(f, " (saved about {saved:.2?})")?;
+            }
+            f.write_str(", ")?;
+        }
+        write!(
+            f,
+            "dropped the traces of {} passing {} ({} calls) without decoding them",
+            self.dropped_tests,
+            plural(self.dropped_tests),
+            self.dropped_calls
+        )?;
+        if self.decoded_calls > 0 {
+            write!(
+                f,
+                ", decoded the {} calls of the failing tests in {:.2?}",
+                self.decoded_calls, self.decoding
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge::result::TestResult;
+
+    #[test]
+    fn reports_the_dropped_and_decoded_traces() {
+        let passed = |calls| TestResult {
+            status: TestStatus::Success,
+            dropped_trace_calls: calls,
+            ..Default::default()
+        };
+        let results = [
+            ("testA()".to_string(), passed(30)),
+            ("testB()".to_string(), passed(10)),
+            ("testC()".to_string(), TestResult::fail("assertion failed".to_string())),
+        ];
+        let suite = SuiteResult::new(Duration::ZERO, results.into(), Vec::new());
+
+        let mut report = PerfModeReport::default();
+        report.record_dropped(&suite);
+        assert_eq!((report.dropped_tests, report.dropped_calls), (2, 40));
+        assert_eq!(
+            report.to_string(),
+            "Performance mode: dropped the traces of 2 passing tests (40 calls) without decoding them"
+        );
+
+        report.record_decoding(10, Duration::from_millis(5));
+        report.record_decoding(10, Duration::from_millis(5));
+        assert!(
+            report.to_string().ends_with(", decoded the 20 calls of the failing tests in 10.00ms"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn reports_the_time_saved_without_tracing() {
+        let untraced = |millis| TestResult {
+            status: TestStatus::Success,
+            duration: Duration::from_millis(millis),
+            untraced: true,
+            ..Default::default()
+        };
+        // the sampled test took 10ms without tracing and 30ms with it
+        let sampled = TestResult {
+            status: TestStatus::Success,
+            duration: Duration::from_millis(30),
+            tracing_sample: Some((Duration::from_millis(10), Duration::from_millis(30))),
+            dropped_trace_calls: 5,
+            ..Default::default()
+        };
+        let failed = TestResult { untraced: true, ..TestResult::fail("revert".to_string()) };
+        let results = [
+            ("testA()".to_string(), untraced(20)),
+            ("testB()".to_string(), untraced(40)),
+            ("testC()".to_string(), sampled),
+            ("testD()".to_string(), failed),
+        ];
+        let suite = SuiteResult::new(Duration::ZERO, results.into(), Vec::new());
+
+        let mut report = PerfModeReport::default();
+        report.record_dropped(&suite);
+        assert_eq!(report.untraced_tests, 2);
+        assert_eq!(report.saved(), Some(Duration::from_millis(120)));
+        assert_eq!(
+            report.to_string(),
+            "Performance mode: ran 2 passing tests without tracing (saved about 120.00ms), dropped \
+             the traces of 1 passing test (5 calls) without decoding them"
+        );
+
+        // nothing is saved while tracing costs nothing
+        report.sampled_traced = report.sampled_untraced;
+        assert_eq!(report.saved(), Some(Duration::ZERO));
+        assert_eq!(PerfModeReport::default().saved(), None);
+    }
+}
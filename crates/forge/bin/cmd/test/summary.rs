@@ -14,6 +14,12 @@ impl TestSummaryReporter {
     pub(crate) fn new(is_detailed: bool) -> Self {
         let mut table = Table::new();
         table.apply_modifier(UTF8_ROUND_CORNERS);
+
+        Self { table, is_detailed }
+    }
+
+    /// Returns the header of the table, with the ZK-VM costs if any test ran on the ZK-VM.
+    fn header(&self, is_zk: bool) -> Row {
         let mut row = Row::from(vec![
             Cell::new("Test Suite")
                 .set_alignment(CellAlignment::Center)
@@ -31,7 +37,16 @@ impl TestSummaryReporter {
                 .add_attribute(Attribute::Bold)
                 .fg(Color::Yellow),
         ]);
-        if is_detailed {
+        if is_zk {
+            for name in ["Ergs", "Pubdata", "Refunded"] {
+                row.add_cell(
+                    Cell::new(name)
+                        .set_alignment(CellAlignment::Center)
+                        .add_attribute(Attribute::Bold),
+                );
+            }
+        }
+        if self.is_detailed {
             row.add_cell(
                 Cell::new("File Path")
                     .set_alignment(CellAlignment::Center)
@@ -43,12 +58,13 @@ impl TestSummaryReporter {
                     .add_attribute(Attribute::Bold),
             );
         }
-        table.set_header(row);
-
-        Self { table, is_detailed }
+        row
     }
 
     pub(crate) fn print_summary(&mut self, outcome: &TestOutcome) {
+        let is_zk = outcome.results.values().any(|suite| suite.zk_ergs().is_some());
+        self.table.set_header(self.header(is_zk));
+
         // Traverse the test_results vector and build the table
         for (contract, suite) in &outcome.results {
             let mut row = Row::new();
@@ -80,6 +96,13 @@ impl TestSummaryReporter {
             }
             row.add_cell(skipped_cell);
 
+            if is_zk {
+                for cost in [suite.zk_ergs(), suite.zk_pubdata(), suite.zk_gas_refunded()] {
+                    let cost = cost.map(|cost| cost.to_string()).unwrap_or_else(|| "-".to_string());
+                    row.add_cell(Cell::new(cost).set_alignment(CellAlignment::Center));
+                }
+            }
+
             if self.is_detailed {
                 row.add_cell(Cell::new(suite_path));
                 row.add_cell(Cell::new(format!("{:.2?}", suite.duration).to_string()));
@@ -91,3 +114,64 @@ impl TestSummaryReporter {
         println!("\n{}", self.table);
     }
 }
+
+/// The test counts of a single cell of a `--matrix` run.
+pub(crate) struct MatrixCellSummary {
+    pub(crate) name: String,
+    pub(crate) passed: usize,
+    pub(crate) failed: usize,
+    pub(crate) skipped: usize,
+}
+
+impl MatrixCellSummary {
+    pub(crate) fn new(name: String, outcome: &TestOutcome) -> Self {
+        Self {
+            name,
+            passed: outcome.passed(),
+            failed: outcome.failed(),
+            skipped: outcome.skipped(),
+        }
+    }
+}
+
+/// Prints the test counts of every cell of a `--matrix` run in a table.
+pub(crate) fn print_matrix_summary(cells: &[MatrixCellSummary]) {
+    let mut table = Table::new();
+    table.apply_modifier(UTF8_ROUND_CORNERS);
+    table.set_header(vec![
+        Cell::new("Matrix Cell")
+            .set_alignment(CellAlignment::Center)
+            .add_attribute(Attribute::Bold),
+        Cell::new("Passed")
+            .set_alignment(CellAlignment::Center)
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Green),
+        Cell::new("Failed")
+            .set_alignment(CellAlignment::Center)
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Red),
+        Cell::new("Skipped")
+            .set_alignment(CellAlignment::Center)
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Yellow),
+    ]);
+
+    let count_cell = |count: usize, color: Color| {
+        let cell = Cell::new(count).set_alignment(CellAlignment::Center);
+        if count > 0 {
+            cell.fg(color)
+        } else {
+            cell
+        }
+    };
+    for cell in cells {
+        table.add_row(vec![
+            Cell::new(&cell.name),
+            count_cell(cell.passed, Color::Green),
+            count_cell(cell.failed, Color::Red),
+            count_cell(cell.skipped, Color::Yellow),
+        ]);
+    }
+
+    println!("\n{table}");
+}
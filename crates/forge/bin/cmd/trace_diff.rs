@@ -0,0 +1,55 @@
+use clap::{Parser, ValueHint};
+use eyre::{Context, Result};
+use forge::trace_diff::{ExportedTraces, TraceDiff};
+use foundry_common::fs;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// CLI arguments for `forge trace-diff`.
+#[derive(Clone, Debug, Parser)]
+pub struct TraceDiffArgs {
+    /// The traces to compare against, as exported with `forge test --export-traces`.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "RUN_A")]
+    a: PathBuf,
+
+    /// The traces to compare, as exported with `forge test --export-traces`.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "RUN_B")]
+    b: PathBuf,
+
+    /// Only compare tests whose `Contract::test` name matches the regex.
+    #[clap(long, short = 'm', value_name = "REGEX")]
+    match_test: Option<Regex>,
+
+    /// Ignore gas differences smaller than this amount.
+    #[clap(long, value_name = "GAS", default_value_t = 0)]
+    gas_threshold: u64,
+
+    /// Exit with code 1 if the traces differ.
+    #[clap(long)]
+    check: bool,
+}
+
+impl TraceDiffArgs {
+    pub fn run(self) -> Result<()> {
+        let a = self.read(&self.a)?;
+        let b = self.read(&self.b)?;
+        let diff = TraceDiff::new(&a, &b, self.gas_threshold);
+        println!("{diff}");
+
+        if self.check && !diff.is_empty() {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> Result<ExportedTraces> {
+        let mut traces: ExportedTraces = fs::read_json_file(path)
+            .wrap_err_with(|| format!("failed to read exported traces from {}", path.display()))?;
+        if let Some(regex) = &self.match_test {
+            for (suite, tests) in &mut traces.tests {
+                tests.retain(|test, _| regex.is_match(&format!("{suite}::{test}")));
+            }
+        }
+        Ok(traces)
+    }
+}
@@ -0,0 +1,700 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt, ResolveSolType};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{b256, Address, Bytes, Selector, B256, U256};
+use clap::Parser;
+use eyre::{Context, Result};
+use forge::{
+    decode::RevertDecoder,
+    executors::{Executor, RawCallResult, TracingExecutor},
+    revm::{primitives::CreateScheme, DatabaseRef},
+};
+use foundry_cli::{
+    opts::CoreBuildArgs,
+    utils::{pin_config_fork_block, LoadConfig},
+};
+use foundry_common::{
+    abi::{encode_function_args, get_func},
+    compile::ProjectCompiler,
+    evm::EvmArgs,
+    fmt::parse_tokens,
+};
+use foundry_compilers::{
+    artifacts::{output_selection::ContractOutputSelection, StorageLayout},
+    info::ContractInfo,
+    utils::canonicalized,
+    ConfigurableContractArtifact, ProjectCompileOutput,
+};
+use foundry_zksync_compiler::{DualCompiledContracts, ZkSolc};
+use foundry_zksync_core::{
+    convert::ConvertH160, encode_create_params, get_balance_key, ZkTransactionMetadata,
+    CONTRACT_DEPLOYER_ADDRESS, L2_ETH_TOKEN_ADDRESS,
+};
+use serde::Serialize;
+use std::fmt;
+use yansi::Paint;
+
+foundry_config::merge_impl_figment_convert!(UpgradeSimArgs, opts, evm_opts);
+
+/// The ERC-1967 implementation slot, `bytes32(uint256(keccak256('eip1967.proxy.implementation')) -
+/// 1)`.
+const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// CLI arguments for `forge upgrade-sim`.
+///
+/// Simulates the upgrade of a live ERC-1967 proxy to a new implementation on a fork, then runs
+/// the given post-upgrade calls and checks against the proxy and reports:
+///
+/// - storage slots of `--previous` that are reused by a different variable in the new
+///   implementation
+/// - functions added or removed compared to `--previous`
+/// - post-upgrade calls that reverted
+/// - checks that fail after the upgrade
+///
+/// The upgrade is simulated by deploying the new implementation and writing its address to the
+/// ERC-1967 implementation slot, admin checks of the proxy are not executed.
+#[derive(Clone, Debug, Parser)]
+pub struct UpgradeSimArgs {
+    /// The address of the proxy to upgrade.
+    #[clap(value_name = "PROXY")]
+    proxy: Address,
+
+    /// The identifier of the new implementation in the form `(<path>:)?<contractname>`.
+    #[clap(value_name = "IMPLEMENTATION")]
+    implementation: ContractInfo,
+
+    /// The identifier of the currently deployed implementation, used to compare storage layouts
+    /// and selectors.
+    #[clap(long, value_name = "CONTRACT")]
+    previous: Option<ContractInfo>,
+
+    /// The constructor arguments of the new implementation.
+    #[clap(long, num_args(1..), value_name = "ARGS")]
+    constructor_args: Vec<String>,
+
+    /// A call to execute on the proxy after the upgrade, in the form `"<sig> [args...]"`.
+    ///
+    /// Calls are executed in order and their state changes are committed. Arguments containing
+    /// spaces must be quoted.
+    ///
+    /// Example: --call "deposit(uint256) 100" --call "setName(string) 'new name'"
+    #[clap(long = "call", value_name = "CALL")]
+    calls: Vec<String>,
+
+    /// A check to run on the proxy before and after the upgrade, in the form `"<sig> [args...]"`.
+    ///
+    /// A check fails if it reverts or returns `false`. Checks are executed after all calls and
+    /// their state changes are discarded.
+    ///
+    /// Example: --check "solvent()(bool)"
+    #[clap(long = "check", value_name = "CHECK")]
+    checks: Vec<String>,
+
+    /// Print the report as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    opts: CoreBuildArgs,
+
+    #[clap(flatten)]
+    evm_opts: EvmArgs,
+}
+
+/// A storage slot of the previous implementation that's used differently by the new one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StorageCollision {
+    pub slot: String,
+    pub offset: i64,
+    /// The previous variable, as `<label> (<type>)`.
+    pub previous: String,
+    /// The new variable at the same position, as `<label> (<type>)`, if any.
+    pub new: Option<String>,
+}
+
+impl fmt::Display for StorageCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { slot, offset, previous, new } = self;
+        let new = new.as_deref().unwrap_or("<removed>");
+        write!(f, "slot {slot} (offset {offset}): {previous} -> {new}")
+    }
+}
+
+/// A function that was added or removed by the upgrade.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SelectorChange {
+    Added { selector: Selector, signature: String },
+    Removed { selector: Selector, signature: String },
+}
+
+impl fmt::Display for SelectorChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { selector, signature } => write!(f, "+ {selector} {signature}"),
+            Self::Removed { selector, signature } => write!(f, "- {selector} {signature}"),
+        }
+    }
+}
+
+/// The outcome of a post-upgrade call.
+#[derive(Clone, Debug, Serialize)]
+pub struct CallOutcome {
+    pub call: String,
+    pub success: bool,
+    pub reason: Option<String>,
+}
+
+/// The outcome of a check, before and after the upgrade.
+#[derive(Clone, Debug, Serialize)]
+pub struct CheckOutcome {
+    pub check: String,
+    pub before: bool,
+    pub after: bool,
+    pub reason: Option<String>,
+}
+
+/// The report of an upgrade simulation.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpgradeReport {
+    pub proxy: Address,
+    pub previous_implementation: Address,
+    pub new_implementation: Address,
+    pub storage_collisions: Vec<StorageCollision>,
+    pub selector_changes: Vec<SelectorChange>,
+    pub calls: Vec<CallOutcome>,
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl UpgradeReport {
+    /// Returns the number of problems found: storage collisions, failed calls and failed checks.
+    pub fn problems(&self) -> usize {
+        self.storage_collisions.len() +
+            self.calls.iter().filter(|call| !call.success).count() +
+            self.checks.iter().filter(|check| !check.after).count()
+    }
+
+    fn print(&self) {
+        println!(
+            "Upgraded proxy {} from {} to {}",
+            self.proxy, self.previous_implementation, self.new_implementation
+        );
+
+        if !self.storage_collisions.is_empty() {
+            println!("{}", Paint::red("\nStorage collisions:"));
+            for collision in &self.storage_collisions {
+                println!("  - {collision}");
+            }
+        }
+
+        if !self.selector_changes.is_empty() {
+            println!("{}", Paint::yellow("\nSelector changes:"));
+            for change in &self.selector_changes {
+                println!("  {change}");
+            }
+        }
+
+        if !self.calls.is_empty() {
+            println!("\nCalls:");
+            for call in &self.calls {
+                if call.success {
+                    println!("  {} {}", Paint::green("[PASS]"), call.call);
+                } else {
+                    let reason = call.reason.as_deref().unwrap_or_default();
+                    println!("  {} {}: {reason}", Paint::red("[FAIL]"), call.call);
+                }
+            }
+        }
+
+        if !self.checks.is_empty() {
+            println!("\nChecks:");
+            for check in &self.checks {
+                let before = if check.before { "held before" } else { "failed before" };
+                if check.after {
+                    println!("  {} {} ({before})", Paint::green("[PASS]"), check.check);
+                } else {
+                    let reason = check.reason.as_deref().unwrap_or_default();
+                    println!("  {} {} ({before}): {reason}", Paint::red("[FAIL]"), check.check);
+                }
+            }
+        }
+    }
+}
+
+impl UpgradeSimArgs {
+    pub async fn run(self) -> Result<()> {
+        let (mut config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        if evm_opts.fork_url.is_none() {
+            eyre::bail!("a fork url is required to simulate an upgrade, pass --fork-url");
+        }
+        pin_config_fork_block(&config, &mut evm_opts, false).await?;
+
+        // we need the storage layouts to detect collisions
+        if !config.extra_output.contains(&ContractOutputSelection::StorageLayout) {
+            config.extra_output.push(ContractOutputSelection::StorageLayout);
+        }
+        let project = config.project()?;
+        let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+
+        let root = project.root();
+        let implementation = find_artifact(&output, &self.implementation, root)?;
+        let previous = self
+            .previous
+            .as_ref()
+            .map(|previous| find_artifact(&output, previous, root))
+            .transpose()?;
+        let abi = implementation.abi.clone().unwrap_or_default();
+
+        let mut code = implementation
+            .bytecode
+            .as_ref()
+            .and_then(|bytecode| bytecode.object.as_bytes())
+            .ok_or_else(|| eyre::eyre!("{} has unlinked or missing bytecode", self.implementation))?
+            .to_vec();
+        let constructor_args = match &abi.constructor {
+            Some(constructor) => {
+                let params = constructor
+                    .inputs
+                    .iter()
+                    .map(|input| input.resolve())
+                    .collect::<Result<Vec<_>, _>>()
+                    .wrap_err("could not resolve constructor args")?;
+                eyre::ensure!(
+                    params.len() == self.constructor_args.len(),
+                    "expected {} constructor arguments, got {}",
+                    params.len(),
+                    self.constructor_args.len()
+                );
+                let values = parse_tokens(
+                    params.iter().zip(self.constructor_args.iter().map(String::as_str)),
+                )?;
+                constructor.abi_encode_input(&values)?
+            }
+            None => vec![],
+        };
+
+        let zk_contract = if self.opts.compiler.zksync {
+            let mut zksolc = ZkSolc::new(
+                config
+                    .new_zksolc_config_builder()
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| eyre::eyre!(e))?,
+                config.zk_project()?,
+            );
+            let (zk_output, _) =
+                zksolc.compile().map_err(|e| eyre::eyre!("Failed to compile with zksolc: {e}"))?;
+            let dual_compiled_contracts = DualCompiledContracts::new(&output, &zk_output);
+            let contract = dual_compiled_contracts.find_by_evm_bytecode(&code).cloned();
+            Some(contract.ok_or_else(|| {
+                eyre::eyre!("Could not find zksolc contract for {}", self.implementation)
+            })?)
+        } else {
+            code.extend_from_slice(&constructor_args);
+            None
+        };
+
+        let env = evm_opts.evm_env().await?;
+        let fork = evm_opts.get_fork(&config, env.clone());
+        let mut executor = TracingExecutor::new(env, fork, Some(config.evm_version), false).await;
+        let sender = evm_opts.sender;
+        executor.set_balance(sender, U256::MAX)?;
+        if zk_contract.is_some() {
+            executor.backend.insert_account_storage(
+                L2_ETH_TOKEN_ADDRESS.to_address(),
+                get_balance_key(sender),
+                U256::MAX,
+            )?;
+        }
+
+        let slot = U256::from_be_bytes(IMPLEMENTATION_SLOT.0);
+        let previous_implementation =
+            Address::from_word(executor.backend.storage_ref(self.proxy, slot)?.into());
+        if previous_implementation.is_zero() {
+            eyre::bail!(
+                "{} is not an ERC-1967 proxy, its implementation slot is empty",
+                self.proxy
+            );
+        }
+
+        let mut decoder = RevertDecoder::new().with_abi(&abi);
+        if let Some(abi) = previous.and_then(|previous| previous.abi.as_ref()) {
+            decoder.extend_from_abi(abi);
+        }
+
+        let checks =
+            self.checks.iter().map(|check| parse_call(check)).collect::<Result<Vec<_>>>()?;
+        let before = checks
+            .iter()
+            .map(|(func, data)| self.check(&mut executor, sender, func, data, &decoder).is_ok())
+            .collect::<Vec<_>>();
+
+        // deploy the new implementation and point the proxy to it
+        let new_implementation = match &zk_contract {
+            Some(contract) => {
+                executor.setup_zk_tx(ZkTransactionMetadata {
+                    factory_deps: contract.factory_deps(),
+                    paymaster: None,
+                });
+                let data = encode_create_params(
+                    &CreateScheme::Create,
+                    contract.zk_bytecode_hash,
+                    constructor_args,
+                );
+                let result = executor.call_raw_committing(
+                    sender,
+                    CONTRACT_DEPLOYER_ADDRESS.to_address(),
+                    data.into(),
+                    U256::ZERO,
+                )?;
+                if result.reverted || result.result.len() < 32 {
+                    let reason = decoder.decode(&result.result, Some(result.exit_reason));
+                    eyre::bail!("failed to deploy {}: {reason}", self.implementation);
+                }
+                Address::from_word(B256::from_slice(&result.result[..32]))
+            }
+            None => {
+                executor
+                    .deploy(sender, code.into(), U256::ZERO, Some(&decoder))
+                    .map_err(|err| eyre::eyre!("failed to deploy {}: {err}", self.implementation))?
+                    .address
+            }
+        };
+        executor.backend.insert_account_storage(
+            self.proxy,
+            slot,
+            U256::from_be_bytes(new_implementation.into_word().0),
+        )?;
+
+        let mut calls = Vec::with_capacity(self.calls.len());
+        for call in &self.calls {
+            let (_, data) = parse_call(call)?;
+            let result = self.execute(&mut executor, sender, data, true)?;
+            calls.push(CallOutcome {
+                call: call.clone(),
+                success: !result.reverted,
+                reason: result
+                    .reverted
+                    .then(|| decoder.decode(&result.result, Some(result.exit_reason))),
+            });
+        }
+
+        let checks = self
+            .checks
+            .iter()
+            .zip(&checks)
+            .zip(before)
+            .map(|((check, (func, data)), before)| {
+                let after = self.check(&mut executor, sender, func, data, &decoder);
+                CheckOutcome {
+                    check: check.clone(),
+                    before,
+                    after: after.is_ok(),
+                    reason: after.err().map(|err| err.to_string()),
+                }
+            })
+            .collect();
+
+        let (storage_collisions, selector_changes) = match previous {
+            Some(previous) => {
+                let storage_collisions =
+                    match (&previous.storage_layout, &implementation.storage_layout) {
+                        (Some(previous), Some(new)) => storage_collisions(previous, new),
+                        _ => eyre::bail!("could not get the storage layouts"),
+                    };
+                let previous_abi = previous.abi.clone().unwrap_or_default();
+                (storage_collisions, selector_changes(&previous_abi, &abi))
+            }
+            None => {
+                if !self.json {
+                    println!(
+                        "{}",
+                        Paint::yellow(
+                            "Pass --previous to compare storage layouts and selectors with the \
+                             current implementation."
+                        )
+                    );
+                }
+                Default::default()
+            }
+        };
+
+        let report = UpgradeReport {
+            proxy: self.proxy,
+            previous_implementation,
+            new_implementation,
+            storage_collisions,
+            selector_changes,
+            calls,
+            checks,
+        };
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            report.print();
+        }
+
+        let problems = report.problems();
+        if problems > 0 {
+            eyre::bail!("found {problems} problems with the upgrade of {}", self.proxy);
+        }
+        Ok(())
+    }
+
+    /// Executes the call on the proxy, on the zkEVM if `--zksync` is set.
+    fn execute(
+        &self,
+        executor: &mut Executor,
+        sender: Address,
+        data: Bytes,
+        commit: bool,
+    ) -> Result<RawCallResult> {
+        if self.opts.compiler.zksync {
+            executor.setup_zk_tx(ZkTransactionMetadata::default());
+        }
+        if commit {
+            executor.call_raw_committing(sender, self.proxy, data, U256::ZERO)
+        } else {
+            executor.call_raw(sender, self.proxy, data, U256::ZERO)
+        }
+    }
+
+    /// Runs a check on the proxy, failing if it reverts or returns `false`.
+    fn check(
+        &self,
+        executor: &mut Executor,
+        sender: Address,
+        func: &Function,
+        data: &Bytes,
+        decoder: &RevertDecoder,
+    ) -> Result<()> {
+        let result = self.execute(executor, sender, data.clone(), false)?;
+        if result.reverted {
+            eyre::bail!("{}", decoder.decode(&result.result, Some(result.exit_reason)));
+        }
+        let output = func.abi_decode_output(&result.result, false)?;
+        if let [DynSolValue::Bool(false)] = output.as_slice() {
+            eyre::bail!("returned false");
+        }
+        Ok(())
+    }
+}
+
+/// Finds the compiled artifact of the given contract.
+fn find_artifact<'a>(
+    output: &'a ProjectCompileOutput,
+    contract: &ContractInfo,
+    root: &std::path::Path,
+) -> Result<&'a ConfigurableContractArtifact> {
+    let mut contract = contract.clone();
+    if let Some(path) = &mut contract.path {
+        // paths are absolute in the project's output
+        *path = canonicalized(root.join(&path)).to_string_lossy().to_string();
+    }
+    output.find_contract(&contract).ok_or_else(|| {
+        eyre::eyre!("Could not find artifact `{contract}` in the compiled artifacts")
+    })
+}
+
+/// Parses a call in the form `"<sig> [args...]"` into its function and calldata.
+///
+/// The signature ends at the parenthesis closing its parameters, so it may contain spaces. The
+/// arguments are split like shell words: an argument containing spaces, like a string or a
+/// tuple, must be quoted.
+fn parse_call(call: &str) -> Result<(Function, Bytes)> {
+    let call = call.trim();
+    if call.is_empty() {
+        eyre::bail!("empty call");
+    }
+    let (sig, args) = split_signature(call);
+    let func = get_func(sig)?;
+    let args = shlex::split(args)
+        .ok_or_else(|| eyre::eyre!("invalid call: unterminated quote in `{call}`"))?;
+    let data =
+        encode_function_args(&func, &args).wrap_err_with(|| format!("invalid call: {call}"))?;
+    Ok((func, data.into()))
+}
+
+/// Splits a call into its signature, up to the parenthesis closing its parameters, and its
+/// arguments.
+fn split_signature(call: &str) -> (&str, &str) {
+    let mut depth = 0usize;
+    for (i, c) in call.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    // the return types are part of the signature: `f(uint256)(bool)`
+                    if !call[i + 1..].starts_with('(') {
+                        return call.split_at(i + 1)
+                    }
+                }
+            }
+            c if c.is_whitespace() && depth == 0 => return call.split_at(i),
+            _ => {}
+        }
+    }
+    (call, "")
+}
+
+/// Returns the variables of `previous` whose position is taken by a different variable, or no
+/// variable at all, in `new`.
+///
+/// Variables are compared by label and type label, as type identifiers contain AST ids.
+pub fn storage_collisions(previous: &StorageLayout, new: &StorageLayout) -> Vec<StorageCollision> {
+    let describe = |layout: &StorageLayout, storage: &foundry_compilers::artifacts::Storage| {
+        let ty = layout.types.get(&storage.storage_type).map_or("?", |ty| ty.label.as_str());
+        format!("{} ({ty})", storage.label)
+    };
+
+    previous
+        .storage
+        .iter()
+        .filter_map(|old| {
+            let previous = describe(previous, old);
+            let new = new
+                .storage
+                .iter()
+                .find(|var| var.slot == old.slot && var.offset == old.offset)
+                .map(|var| describe(new, var));
+            (new.as_ref() != Some(&previous)).then(|| StorageCollision {
+                slot: old.slot.clone(),
+                offset: old.offset,
+                previous,
+                new,
+            })
+        })
+        .collect()
+}
+
+/// Returns the functions added and removed in `new` compared to `previous`.
+pub fn selector_changes(previous: &JsonAbi, new: &JsonAbi) -> Vec<SelectorChange> {
+    let removed = previous
+        .functions()
+        .filter(|func| !new.functions().any(|new| new.selector() == func.selector()))
+        .map(|func| SelectorChange::Removed {
+            selector: func.selector(),
+            signature: func.signature(),
+        });
+    let added = new
+        .functions()
+        .filter(|func| !previous.functions().any(|old| old.selector() == func.selector()))
+        .map(|func| SelectorChange::Added {
+            selector: func.selector(),
+            signature: func.signature(),
+        });
+    removed.chain(added).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(vars: &[(&str, &str, &str, i64)]) -> StorageLayout {
+        let storage = vars
+            .iter()
+            .map(|(label, ty, slot, offset)| {
+                serde_json::json!({
+                    "astId": 1,
+                    "contract": "src/Counter.sol:Counter",
+                    "label": label,
+                    "offset": offset,
+                    "slot": slot,
+                    "type": ty,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "storage": storage,
+            "types": {
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_calls() {
+        let (func, data) = parse_call("deposit(uint256) 100").unwrap();
+        assert_eq!(func.signature(), "deposit(uint256)");
+        assert_eq!(data[4..], U256::from(100).to_be_bytes::<32>());
+
+        // signatures with spaces, quoted strings and tuples
+        let (func, data) =
+            parse_call(r#"setName(string name, (uint256,bool)) "hello world" "(1, true)""#)
+                .unwrap();
+        assert_eq!(func.signature(), "setName(string,(uint256,bool))");
+        let expected = encode_function_args(&func, ["hello world", "(1, true)"]).unwrap();
+        assert_eq!(data, Bytes::from(expected));
+
+        let (func, data) = parse_call("count()").unwrap();
+        assert_eq!(func.signature(), "count()");
+        assert_eq!(data.len(), 4);
+
+        assert!(parse_call("").is_err());
+        assert!(parse_call(r#"setName(string) "unterminated"#).is_err());
+    }
+
+    #[test]
+    fn detects_storage_collisions() {
+        let previous = layout(&[("owner", "t_address", "0", 0), ("count", "t_uint256", "1", 0)]);
+
+        // appending variables is safe
+        let appended = layout(&[
+            ("owner", "t_address", "0", 0),
+            ("count", "t_uint256", "1", 0),
+            ("total", "t_uint256", "2", 0),
+        ]);
+        assert!(storage_collisions(&previous, &appended).is_empty());
+
+        // inserting variables shifts the existing ones
+        let inserted = layout(&[
+            ("owner", "t_address", "0", 0),
+            ("total", "t_uint256", "1", 0),
+            ("count", "t_uint256", "2", 0),
+        ]);
+        assert_eq!(
+            storage_collisions(&previous, &inserted),
+            vec![StorageCollision {
+                slot: "1".to_string(),
+                offset: 0,
+                previous: "count (uint256)".to_string(),
+                new: Some("total (uint256)".to_string()),
+            }]
+        );
+
+        // removing variables leaves the slot unused
+        let removed = layout(&[("owner", "t_address", "0", 0)]);
+        assert_eq!(storage_collisions(&previous, &removed)[0].new, None);
+    }
+
+    fn abi(signatures: &[&str]) -> JsonAbi {
+        let mut abi = JsonAbi::default();
+        for signature in signatures {
+            let func = get_func(signature).unwrap();
+            abi.functions.entry(func.name.clone()).or_default().push(func);
+        }
+        abi
+    }
+
+    #[test]
+    fn detects_selector_changes() {
+        let previous = abi(&["foo()", "bar(uint256)"]);
+        let new = abi(&["foo()", "baz()"]);
+        let changes = selector_changes(&previous, &new);
+        assert_eq!(
+            changes,
+            vec![
+                SelectorChange::Removed {
+                    selector: get_func("bar(uint256)").unwrap().selector(),
+                    signature: "bar(uint256)".to_string(),
+                },
+                SelectorChange::Added {
+                    selector: get_func("baz()").unwrap().selector(),
+                    signature: "baz()".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use super::{VerifierArgs, VerifyArgs};
+use crate::cmd::{
+    retry::RetryArgs,
+    script::{
+        transaction::{AdditionalContract, TransactionWithMetadata},
+        verify::VerifyBundle,
+    },
+};
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::Function;
+use alloy_primitives::{Address, B256};
+use clap::{builder::RangedU64ValueParser, Parser, ValueHint};
+use comfy_table::Table;
+use ethers_core::types::TransactionReceipt;
+use eyre::{Result, WrapErr};
+use foundry_cli::{opts::EtherscanOpts, utils::LoadConfig};
+use foundry_common::{compile::ProjectCompiler, shell, types::ToAlloy, ContractsByArtifact};
+use foundry_config::{figment, impl_figment_convert, Config};
+use foundry_zksync_compiler::{DualCompiledContracts, ZkSolc};
+use futures::StreamExt;
+use serde::Deserialize;
+use std::{fmt, path::PathBuf, time::Duration};
+use tokio::time::Instant;
+
+/// CLI arguments for `forge verify-batch`.
+#[derive(Clone, Debug, Parser)]
+pub struct VerifyBatchArgs {
+    /// The path to the broadcast artifact, e.g. `broadcast/Deploy.s.sol/1/run-latest.json`.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub path: PathBuf,
+
+    /// The maximum number of verifications running at the same time.
+    #[clap(
+        long,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        default_value = "4",
+        value_name = "NUM"
+    )]
+    pub concurrency: usize,
+
+    /// The maximum number of verification requests submitted per second.
+    #[clap(
+        long,
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..),
+        default_value = "2",
+        value_name = "NUM"
+    )]
+    pub rate_limit: u32,
+
+    /// Submit the verification requests without waiting for their results.
+    ///
+    /// Submitted contracts are reported as pending.
+    #[clap(long)]
+    pub no_wait: bool,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    pub root: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    #[clap(flatten)]
+    pub retry: RetryArgs,
+
+    #[clap(flatten)]
+    pub verifier: VerifierArgs,
+}
+
+impl_figment_convert!(VerifyBatchArgs);
+
+impl figment::Provider for VerifyBatchArgs {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Verify Batch Provider")
+    }
+
+    fn data(
+        &self,
+    ) -> Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        let mut dict = self.etherscan.dict();
+        if let Some(root) = self.root.as_ref() {
+            dict.insert("root".to_string(), figment::value::Value::serialize(root)?);
+        }
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+/// The deployments recorded in a broadcast artifact.
+///
+/// This only reads the parts needed for verification, so that the artifact is never written back.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BroadcastArtifact {
+    Multi { deployments: Vec<BroadcastDeployment> },
+    Single(BroadcastDeployment),
+}
+
+#[derive(Deserialize)]
+struct BroadcastDeployment {
+    transactions: Vec<TransactionWithMetadata>,
+    receipts: Vec<TransactionReceipt>,
+    libraries: Vec<String>,
+    chain: u64,
+}
+
+/// The outcome of the verification of a single contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum VerificationStatus {
+    Verified,
+    Pending,
+    Failed(String),
+}
+
+impl fmt::Display for VerificationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Verified => f.write_str("verified"),
+            Self::Pending => f.write_str("pending"),
+            Self::Failed(_) => f.write_str("failed"),
+        }
+    }
+}
+
+/// A contract found in the broadcast artifact.
+struct VerificationJob {
+    chain: u64,
+    address: Address,
+    /// The arguments to verify the contract, `None` if no matching artifact was found.
+    args: Option<VerifyArgs>,
+}
+
+impl VerifyBatchArgs {
+    pub async fn run(self) -> Result<()> {
+        let mut config = self.load_config_emit_warnings();
+
+        let artifact: BroadcastArtifact = foundry_compilers::utils::read_json_file(&self.path)
+            .wrap_err_with(|| format!("failed to read broadcast artifact {:?}", self.path))?;
+        let deployments = match artifact {
+            BroadcastArtifact::Multi { deployments } => deployments,
+            BroadcastArtifact::Single(deployment) => vec![deployment],
+        };
+
+        // linking the libraries at compile time makes the bytecode match the deployed one
+        for deployment in &deployments {
+            for library in &deployment.libraries {
+                if !config.libraries.contains(library) {
+                    config.libraries.push(library.clone());
+                }
+            }
+        }
+
+        let project = config.project()?;
+        let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+        let known_contracts = ContractsByArtifact(
+            output
+                .artifact_ids()
+                .filter_map(|(id, artifact)| {
+                    let code = artifact.bytecode.as_ref()?.object.as_bytes()?.to_vec();
+                    Some((id, (artifact.abi.clone().unwrap_or_default(), code)))
+                })
+                .collect(),
+        );
+
+        let has_zk_transactions =
+            deployments.iter().any(|d| d.transactions.iter().any(|tx| tx.zk.is_some()));
+        let dual_compiled_contracts = if has_zk_transactions {
+            let mut zksolc = ZkSolc::new(
+                config
+                    .new_zksolc_config_builder()
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| eyre::eyre!(e))?,
+                config.zk_project()?,
+            );
+            let (zk_output, _) =
+                zksolc.compile().map_err(|e| eyre::eyre!("Failed to compile with zksolc: {e}"))?;
+            DualCompiledContracts::new(&output, &zk_output)
+        } else {
+            DualCompiledContracts::default()
+        };
+
+        let mut jobs = vec![];
+        for deployment in &deployments {
+            let mut bundle = VerifyBundle::new(
+                &project,
+                &config,
+                known_contracts.clone(),
+                self.retry,
+                self.verifier.clone(),
+            );
+            bundle.etherscan = self.etherscan.clone();
+            bundle.set_chain(&config, deployment.chain.into())?;
+
+            for (address, data) in deployed_contracts(deployment, &dual_compiled_contracts) {
+                let args = data.and_then(|(offset, data)| {
+                    let mut args =
+                        bundle.get_verify_args(address, offset, &data, &deployment.libraries)?;
+                    // verified contracts are skipped, so that failed runs can simply be retried
+                    args.skip_is_verified_check = false;
+                    args.watch = !self.no_wait;
+                    args.root = Some(project.root().clone());
+                    Some(args)
+                });
+                jobs.push(VerificationJob { chain: deployment.chain, address, args });
+            }
+        }
+
+        if jobs.is_empty() {
+            shell::println(format!("No deployed contracts found in {:?}", self.path))?;
+            return Ok(())
+        }
+
+        shell::println(format!("Start verifying {} contracts", jobs.len()))?;
+
+        // stagger the requests to respect the rate limit, the first one starts right away
+        let start = Instant::now();
+        let interval = Duration::from_secs(1) / self.rate_limit;
+        let no_wait = self.no_wait;
+        let mut results = futures::stream::iter(jobs.into_iter().enumerate())
+            .map(|(i, job)| async move {
+                let status = match job.args.clone() {
+                    Some(args) => {
+                        tokio::time::sleep_until(start + interval * i as u32).await;
+                        match args.run().await {
+                            Ok(()) if no_wait => VerificationStatus::Pending,
+                            Ok(()) => VerificationStatus::Verified,
+                            Err(err) => VerificationStatus::Failed(err.to_string()),
+                        }
+                    }
+                    None => VerificationStatus::Failed("no matching artifact found".to_string()),
+                };
+                (i, job, status)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        results.sort_by_key(|(i, ..)| *i);
+
+        let mut table = Table::new();
+        table.set_header(["Chain", "Contract", "Address", "Status", "Details"]);
+        for (_, job, status) in &results {
+            let contract = job.args.as_ref().map(|args| args.contract.name.clone());
+            let details = match status {
+                VerificationStatus::Failed(reason) => reason.lines().next().unwrap_or_default(),
+                _ => "",
+            };
+            table.add_row([
+                job.chain.to_string(),
+                contract.unwrap_or_else(|| "<unknown>".to_string()),
+                job.address.to_string(),
+                status.to_string(),
+                details.to_string(),
+            ]);
+        }
+        shell::println(format!("\n{table}"))?;
+
+        let count = |f: fn(&VerificationStatus) -> bool| {
+            results.iter().filter(|(_, _, status)| f(status)).count()
+        };
+        let verified = count(|status| *status == VerificationStatus::Verified);
+        let pending = count(|status| *status == VerificationStatus::Pending);
+        let failed = count(|status| matches!(status, VerificationStatus::Failed(_)));
+        shell::println(format!("{verified} verified, {pending} pending, {failed} failed"))?;
+
+        if failed > 0 {
+            eyre::bail!(
+                "{failed} contracts failed to verify; run the command again to retry them, \
+                 already verified contracts are skipped"
+            )
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the address of every contract deployed in the deployment, along with the creation data
+/// and the offset of the creation code within it, if the contract could be identified.
+///
+/// For zkSync deployments the creation data is rebuilt from the EVM bytecode of the contract
+/// matching the deployed bytecode hash, so it can be verified from the same sources.
+fn deployed_contracts(
+    deployment: &BroadcastDeployment,
+    dual_compiled_contracts: &DualCompiledContracts,
+) -> Vec<(Address, Option<(usize, Vec<u8>)>)> {
+    let mut receipts = deployment.receipts.iter().collect::<Vec<_>>();
+    receipts.sort_unstable();
+
+    let mut contracts = vec![];
+    for (receipt, tx) in receipts.into_iter().zip(&deployment.transactions) {
+        let Some(data) = tx.typed_tx().data() else { continue };
+
+        if tx.zk.is_some() {
+            let Some(address) = receipt.contract_address.map(|address| address.to_alloy()) else {
+                continue
+            };
+            let data = decode_zk_create(&data.0).and_then(|(bytecode_hash, constructor_args)| {
+                let contract = dual_compiled_contracts.find_by_zk_hash(bytecode_hash.0.into())?;
+                Some((0, [contract.evm_bytecode.clone(), constructor_args].concat()))
+            });
+            contracts.push((address, data));
+            continue
+        }
+
+        // create2 transactions are prefixed with the salt
+        let (address, offset) = if tx.is_create2() {
+            (tx.contract_address, 32)
+        } else {
+            (receipt.contract_address.map(|address| address.to_alloy()), 0)
+        };
+        if let Some(address) = address {
+            contracts.push((address, Some((offset, data.to_vec()))));
+        }
+
+        for AdditionalContract { address, init_code, .. } in &tx.additional_contracts {
+            contracts.push((*address, Some((0, init_code.to_vec()))));
+        }
+    }
+    contracts
+}
+
+/// Decodes a call to the zkSync `ContractDeployer`, returning the bytecode hash and the
+/// constructor arguments of the deployed contract.
+fn decode_zk_create(data: &[u8]) -> Option<(B256, Vec<u8>)> {
+    let selector = data.get(..4)?;
+    let function = ["create(bytes32,bytes32,bytes)", "create2(bytes32,bytes32,bytes)"]
+        .into_iter()
+        .filter_map(|signature| Function::parse(signature).ok())
+        .find(|function| function.selector()[..] == *selector)?;
+    match &function.abi_decode_input(&data[4..], false).ok()?[..] {
+        [_, DynSolValue::FixedBytes(hash, 32), DynSolValue::Bytes(input)] => {
+            Some((*hash, input.clone()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_decode_zk_create() {
+        let hash = B256::repeat_byte(0x11);
+        let function = Function::parse("create2(bytes32,bytes32,bytes)").unwrap();
+        let args = [
+            DynSolValue::FixedBytes(B256::ZERO, 32),
+            DynSolValue::FixedBytes(hash, 32),
+            DynSolValue::Bytes(vec![1, 2, 3]),
+        ];
+        let data = function.abi_encode_input(&args).unwrap();
+        assert_eq!(decode_zk_create(&data), Some((hash, vec![1, 2, 3])));
+        assert_eq!(decode_zk_create(&[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+}
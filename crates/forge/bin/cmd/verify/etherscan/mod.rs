@@ -10,7 +10,7 @@ use foundry_block_explorers::{
     Client,
 };
 use foundry_cli::utils::{get_cached_entry_by_name, read_constructor_args_file, LoadConfig};
-use foundry_common::{abi::encode_function_args, retry::Retry};
+use foundry_common::{abi::encode_function_args, redact::register_secret, retry::Retry, shell};
 use foundry_compilers::{
     artifacts::CompactContract, cache::CacheEntry, info::ContractInfo, Project, Solc,
 };
@@ -63,11 +63,11 @@ impl VerificationProvider for EtherscanVerificationProvider {
         if !args.skip_is_verified_check &&
             self.is_contract_verified(&etherscan, &verify_args).await?
         {
-            println!(
+            shell::println(format!(
                 "\nContract [{}] {:?} is already verified. Skipping verification.",
                 verify_args.contract_name,
                 verify_args.address.to_checksum(None)
-            );
+            ))?;
 
             return Ok(())
         }
@@ -77,10 +77,10 @@ impl VerificationProvider for EtherscanVerificationProvider {
         let retry: Retry = args.retry.into();
         let resp = retry
             .run_async(|| async {
-                println!(
+                shell::println(format!(
                     "\nSubmitting verification for [{}] {}.",
                     verify_args.contract_name, verify_args.address
-                );
+                ))?;
                 let resp = etherscan
                     .submit_contract_verification(&verify_args)
                     .await
@@ -107,10 +107,10 @@ impl VerificationProvider for EtherscanVerificationProvider {
                     }
 
                     warn!("Failed verify submission: {:?}", resp);
-                    eprintln!(
+                    shell::eprintln(format!(
                         "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
                         resp.message, resp.result
-                    );
+                    ))?;
                     std::process::exit(1);
                 }
 
@@ -119,12 +119,12 @@ impl VerificationProvider for EtherscanVerificationProvider {
             .await?;
 
         if let Some(resp) = resp {
-            println!(
+            shell::println(format!(
                 "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`\n\tURL: {}",
                 resp.message,
                 resp.result,
                 etherscan.address_url(args.address)
-            );
+            ))?;
 
             if args.watch {
                 let check_args = VerifyCheckArgs {
@@ -137,7 +137,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
                 return self.check(check_args).await
             }
         } else {
-            println!("Contract source code already verified");
+            shell::println("Contract source code already verified")?;
         }
 
         Ok(())
@@ -163,10 +163,10 @@ impl VerificationProvider for EtherscanVerificationProvider {
 
                     trace!(target: "forge::verify", ?resp, "Received verification response");
 
-                    eprintln!(
+                    shell::eprintln(format!(
                         "Contract verification status:\nResponse: `{}`\nDetails: `{}`",
                         resp.message, resp.result
-                    );
+                    ))?;
 
                     if resp.result == "Pending in queue" {
                         return Err(eyre!("Verification is still pending...",))
@@ -177,17 +177,17 @@ impl VerificationProvider for EtherscanVerificationProvider {
                     }
 
                     if resp.result == "Already Verified" {
-                        println!("Contract source code already verified");
+                        shell::println("Contract source code already verified")?;
                         return Ok(())
                     }
 
                     if resp.status == "0" {
-                        println!("Contract failed to verify.");
+                        shell::println("Contract failed to verify.")?;
                         std::process::exit(1);
                     }
 
                     if resp.result == "Pass - Verified" {
-                        println!("Contract successfully verified");
+                        shell::println("Contract successfully verified")?;
                     }
 
                     Ok(())
@@ -293,6 +293,10 @@ impl EtherscanVerificationProvider {
 
         let etherscan_key =
             etherscan_key.or_else(|| etherscan_config.as_ref().map(|c| c.key.as_str()));
+        if let Some(key) = etherscan_key {
+            // api keys end up in request urls, which are part of many error messages
+            register_secret(key);
+        }
 
         let mut builder = Client::builder();
 
@@ -361,7 +365,11 @@ impl EtherscanVerificationProvider {
 
     /// Get the target contract path. If it wasn't provided, attempt a lookup
     /// in cache. Validate the path indeed exists on disk.
-    fn contract_path(&mut self, args: &VerifyArgs, project: &Project) -> Result<PathBuf> {
+    pub(crate) fn contract_path(
+        &mut self,
+        args: &VerifyArgs,
+        project: &Project,
+    ) -> Result<PathBuf> {
         let path = if let Some(path) = args.contract.path.as_ref() {
             project.root().join(path)
         } else {
@@ -384,7 +392,7 @@ impl EtherscanVerificationProvider {
     ///     1. Through CLI arg `--compiler-version`
     ///     2. `solc` defined in foundry.toml
     ///     3. The version contract was last compiled with.
-    fn compiler_version(
+    pub(crate) fn compiler_version(
         &mut self,
         args: &VerifyArgs,
         config: &Config,
@@ -435,7 +443,11 @@ impl EtherscanVerificationProvider {
     /// Return the optional encoded constructor arguments. If the path to
     /// constructor arguments was provided, read them and encode. Otherwise,
     /// return whatever was set in the [VerifyArgs] args.
-    fn constructor_args(&mut self, args: &VerifyArgs, project: &Project) -> Result<Option<String>> {
+    pub(crate) fn constructor_args(
+        &mut self,
+        args: &VerifyArgs,
+        project: &Project,
+    ) -> Result<Option<String>> {
         if let Some(ref constructor_args_path) = args.constructor_args_path {
             let (_, _, contract) = self.cache_entry(project, &args.contract).wrap_err(
                 "Cache must be enabled in order to use the `--constructor-args-path` option",
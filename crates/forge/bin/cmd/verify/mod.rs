@@ -1,14 +1,20 @@
 use super::retry::RetryArgs;
 use alloy_primitives::Address;
-use clap::{Parser, ValueHint};
+use clap::{builder::TypedValueParser, Parser, ValueHint};
 use eyre::Result;
 use foundry_cli::{opts::EtherscanOpts, utils::LoadConfig};
+use foundry_common::shell;
 use foundry_compilers::{info::ContractInfo, EvmVersion};
-use foundry_config::{figment, impl_figment_convert, impl_figment_convert_cast, Config};
-use provider::VerificationProviderType;
+use foundry_config::{
+    figment, impl_figment_convert, impl_figment_convert_cast, Config, ResolvedEtherscanConfig,
+};
+use provider::{VerificationProviderClient, VerificationProviderType};
 use reqwest::Url;
 use std::path::PathBuf;
 
+mod batch;
+pub use batch::VerifyBatchArgs;
+
 mod etherscan;
 use etherscan::EtherscanVerificationProvider;
 
@@ -17,11 +23,18 @@ use provider::VerificationProvider;
 
 mod sourcify;
 
+mod zksync;
+
 /// Verification provider arguments
 #[derive(Clone, Debug, Parser)]
 pub struct VerifierArgs {
     /// The contract verification provider to use.
-    #[clap(long, help_heading = "Verifier options", default_value = "etherscan", value_enum)]
+    #[clap(
+        long,
+        help_heading = "Verifier options",
+        default_value = "etherscan",
+        value_parser = verifier_parser()
+    )]
     pub verifier: VerificationProviderType,
 
     /// The verifier URL, if using a custom provider
@@ -29,12 +42,36 @@ pub struct VerifierArgs {
     pub verifier_url: Option<String>,
 }
 
+/// Parses a [`VerificationProviderType`], listing the providers in the help.
+fn verifier_parser() -> impl TypedValueParser<Value = VerificationProviderType> {
+    clap::builder::PossibleValuesParser::new(["etherscan", "sourcify", "blockscout", "zksync"])
+        .map(|verifier| verifier.parse().expect("a possible value"))
+}
+
 impl Default for VerifierArgs {
     fn default() -> Self {
         VerifierArgs { verifier: VerificationProviderType::Etherscan, verifier_url: None }
     }
 }
 
+impl VerifierArgs {
+    /// Applies the `verifier` configured in the chain's `[etherscan]` entry.
+    ///
+    /// The entry is only taken into account if neither `--verifier` nor `--verifier-url` were
+    /// changed from their defaults, so the command line always takes precedence.
+    pub fn apply_etherscan_config(&mut self, config: &ResolvedEtherscanConfig) {
+        let Some(verifier) = config.verifier else { return };
+        if self.verifier != VerificationProviderType::Etherscan || self.verifier_url.is_some() {
+            return
+        }
+
+        self.verifier = verifier;
+        if self.verifier != VerificationProviderType::Etherscan {
+            self.verifier_url = Some(config.api_url.clone());
+        }
+    }
+}
+
 /// CLI arguments for `forge verify`.
 #[derive(Clone, Debug, Parser)]
 pub struct VerifyArgs {
@@ -61,6 +98,12 @@ pub struct VerifyArgs {
     #[clap(long, value_name = "VERSION")]
     pub compiler_version: Option<String>,
 
+    /// The `zksolc` version the smart contract was built with, when verifying on zkSync.
+    ///
+    /// Defaults to the `zksolc` version of the config.
+    #[clap(long, value_name = "VERSION")]
+    pub zksolc_version: Option<String>,
+
     /// The number of optimization runs used to build the smart contract.
     #[clap(long, visible_alias = "optimizer-runs", value_name = "NUM")]
     pub num_of_optimizations: Option<usize>,
@@ -156,17 +199,21 @@ impl VerifyArgs {
         let config = self.load_config_emit_warnings();
         let chain = config.chain.unwrap_or_default();
         self.etherscan.chain = Some(chain);
-        self.etherscan.key = config.get_etherscan_config_with_chain(Some(chain))?.map(|c| c.key);
+        let etherscan_config = config.get_etherscan_config_with_chain(Some(chain))?;
+        if let Some(etherscan_config) = &etherscan_config {
+            self.verifier.apply_etherscan_config(etherscan_config);
+        }
+        self.etherscan.key = etherscan_config.map(|c| c.key);
 
         if self.show_standard_json_input {
             let args =
                 EtherscanVerificationProvider::default().create_verify_request(&self, None).await?;
-            println!("{}", args.source);
+            shell::println(&args.source)?;
             return Ok(())
         }
 
         let verifier_url = self.verifier.verifier_url.clone();
-        println!("Start verifying contract `{}` deployed on {chain}", self.address);
+        shell::println(format!("Start verifying contract `{}` deployed on {chain}", self.address))?;
         self.verifier.verifier.client(&self.etherscan.key())?.verify(self).await.map_err(|err| {
             if let Some(verifier_url) = verifier_url {
                  match Url::parse(&verifier_url) {
@@ -203,6 +250,8 @@ pub struct VerifyCheckArgs {
     /// For Etherscan - Submission GUID.
     ///
     /// For Sourcify - Contract Address.
+    ///
+    /// For zkSync - Verification ID.
     id: String,
 
     #[clap(flatten)]
@@ -220,7 +269,10 @@ impl_figment_convert_cast!(VerifyCheckArgs);
 impl VerifyCheckArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
     pub async fn run(self) -> Result<()> {
-        println!("Checking verification status on {}", self.etherscan.chain.unwrap_or_default());
+        shell::println(format!(
+            "Checking verification status on {}",
+            self.etherscan.chain.unwrap_or_default()
+        ))?;
         self.verifier.verifier.client(&self.etherscan.key())?.check(self).await
     }
 }
@@ -266,4 +318,19 @@ mod tests {
         ]);
         assert!(args.via_ir);
     }
+
+    #[test]
+    fn can_parse_zksync_verify_contract() {
+        let args: VerifyArgs = VerifyArgs::parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000000",
+            "src/Domains.sol:Domains",
+            "--verifier",
+            "zksync",
+            "--zksolc-version",
+            "1.4.0",
+        ]);
+        assert_eq!(args.verifier.verifier, VerificationProviderType::ZkSync);
+        assert_eq!(args.zksolc_version.as_deref(), Some("1.4.0"));
+    }
 }
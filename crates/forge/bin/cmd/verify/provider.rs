@@ -1,12 +1,14 @@
 use super::{
-    etherscan::EtherscanVerificationProvider, sourcify::SourcifyVerificationProvider, VerifyArgs,
-    VerifyCheckArgs,
+    etherscan::EtherscanVerificationProvider, sourcify::SourcifyVerificationProvider,
+    zksync::ZkSyncVerificationProvider, VerifyArgs, VerifyCheckArgs,
 };
 use async_trait::async_trait;
 use eyre::Result;
-use std::{fmt, str::FromStr};
 
-/// An abstraction for various verification providers such as etherscan, sourcify, blockscout
+pub use foundry_config::VerificationProviderType;
+
+/// An abstraction for various verification providers such as etherscan, sourcify, blockscout,
+/// zksync
 #[async_trait]
 pub trait VerificationProvider {
     /// This should ensure the verify request can be prepared successfully.
@@ -25,47 +27,14 @@ pub trait VerificationProvider {
     async fn check(&self, args: VerifyCheckArgs) -> Result<()>;
 }
 
-impl FromStr for VerificationProviderType {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "e" | "etherscan" => Ok(VerificationProviderType::Etherscan),
-            "s" | "sourcify" => Ok(VerificationProviderType::Sourcify),
-            "b" | "blockscout" => Ok(VerificationProviderType::Blockscout),
-            _ => Err(format!("Unknown provider: {s}")),
-        }
-    }
-}
-
-impl fmt::Display for VerificationProviderType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            VerificationProviderType::Etherscan => {
-                write!(f, "etherscan")?;
-            }
-            VerificationProviderType::Sourcify => {
-                write!(f, "sourcify")?;
-            }
-            VerificationProviderType::Blockscout => {
-                write!(f, "blockscout")?;
-            }
-        };
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
-pub enum VerificationProviderType {
-    #[default]
-    Etherscan,
-    Sourcify,
-    Blockscout,
+/// Creates the [`VerificationProvider`] of a [`VerificationProviderType`].
+pub trait VerificationProviderClient {
+    /// Returns the corresponding `VerificationProvider` for the key
+    fn client(&self, key: &Option<String>) -> Result<Box<dyn VerificationProvider>>;
 }
 
-impl VerificationProviderType {
-    /// Returns the corresponding `VerificationProvider` for the key
-    pub fn client(&self, key: &Option<String>) -> Result<Box<dyn VerificationProvider>> {
+impl VerificationProviderClient for VerificationProviderType {
+    fn client(&self, key: &Option<String>) -> Result<Box<dyn VerificationProvider>> {
         match self {
             VerificationProviderType::Etherscan => {
                 if key.as_ref().map_or(true, |key| key.is_empty()) {
@@ -79,6 +48,7 @@ impl VerificationProviderType {
             VerificationProviderType::Blockscout => {
                 Ok(Box::<EtherscanVerificationProvider>::default())
             }
+            VerificationProviderType::ZkSync => Ok(Box::<ZkSyncVerificationProvider>::default()),
         }
     }
 }
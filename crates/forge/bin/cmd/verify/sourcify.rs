@@ -2,7 +2,7 @@ use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
 use async_trait::async_trait;
 use eyre::Result;
 use foundry_cli::utils::{get_cached_entry_by_name, LoadConfig};
-use foundry_common::{fs, retry::Retry};
+use foundry_common::{fs, retry::Retry, shell};
 use foundry_compilers::ConfigurableContractArtifact;
 use futures::FutureExt;
 use reqwest::Url;
@@ -34,11 +34,11 @@ impl VerificationProvider for SourcifyVerificationProvider {
         let resp = retry
             .run_async(|| {
                 async {
-                    println!(
+                    shell::println(format!(
                         "\nSubmitting verification for [{}] {:?}.",
                         args.contract.name,
                         args.address.to_string()
-                    );
+                    ))?;
                     let response = client
                         .post(args.verifier.verifier_url.as_deref().unwrap_or(SOURCIFY_URL))
                         .header("Content-Type", "application/json")
@@ -169,15 +169,17 @@ metadata output can be enabled via `extra_output = ["metadata"]` in `foundry.tom
         match response.status.as_str() {
             "perfect" => {
                 if let Some(ts) = &response.storage_timestamp {
-                    println!("Contract source code already verified. Storage Timestamp: {ts}");
+                    shell::println(format!(
+                        "Contract source code already verified. Storage Timestamp: {ts}"
+                    ))?;
                 } else {
-                    println!("Contract successfully verified");
+                    shell::println("Contract successfully verified")?;
                 }
             }
             "partial" => {
-                println!("The recompiled contract partially matches the deployed version");
+                shell::println("The recompiled contract partially matches the deployed version")?;
             }
-            "false" => println!("Contract source code is not verified"),
+            "false" => shell::println("Contract source code is not verified")?,
             s => eyre::bail!("Unknown status from sourcify. Status: {s:?}"),
         }
         Ok(())
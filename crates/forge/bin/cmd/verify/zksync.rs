@@ -0,0 +1,424 @@
+use super::{
+    etherscan::EtherscanVerificationProvider, provider::VerificationProvider, VerifyArgs,
+    VerifyCheckArgs,
+};
+use crate::cmd::retry::RETRY_CHECK_ON_VERIFY;
+use async_trait::async_trait;
+use eyre::{eyre, Context, Result};
+use foundry_cli::utils::LoadConfig;
+use foundry_common::{retry::Retry, shell};
+use foundry_compilers::artifacts::{BytecodeHash, SettingsMetadata, StandardJsonCompilerInput};
+use foundry_config::{Chain, Config};
+use foundry_zksync_compiler::{SettingsBuilder, ZkStandardJsonCompilerInput};
+use futures::FutureExt;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The contract verification API of the zkSync Era mainnet block explorer.
+pub static ZKSYNC_MAINNET_VERIFIER_URL: &str =
+    "https://zksync2-mainnet-explorer.zksync.io/contract_verification";
+
+/// The contract verification API of the zkSync Era Sepolia testnet block explorer.
+pub static ZKSYNC_SEPOLIA_VERIFIER_URL: &str =
+    "https://explorer.sepolia.era.zksync.dev/contract_verification";
+
+/// The body of the `400 Bad Request` response of the verification API to the submission of a
+/// verified contract.
+const ALREADY_VERIFIED_ERROR: &str = "This contract is already verified";
+
+/// The type that can verify a contract on the zkSync Era block explorer
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ZkSyncVerificationProvider {
+    /// Resolves the contract path, solc version and constructor arguments the way etherscan
+    /// verification does
+    etherscan: EtherscanVerificationProvider,
+}
+
+#[async_trait]
+impl VerificationProvider for ZkSyncVerificationProvider {
+    async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()> {
+        let _ = self.prepare_request(&args)?;
+        Ok(())
+    }
+
+    async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
+        let (url, body) = self.prepare_request(&args)?;
+
+        trace!(target: "forge::verify", ?body, "submitting verification request");
+
+        let client = reqwest::Client::new();
+
+        let retry: Retry = args.retry.into();
+        let id = retry
+            .run_async(|| async {
+                shell::println(format!(
+                    "\nSubmitting verification for [{}] {}.",
+                    body.contract_name, args.address
+                ))?;
+                let response = client.post(&url).json(&body).send().await?;
+
+                let status = response.status();
+                let text = response.text().await?;
+                parse_submission(status, &text).wrap_err_with(|| {
+                    format!("zkSync verification request for address ({}) failed", args.address)
+                })
+            })
+            .await?;
+
+        let Some(id) = id else {
+            shell::println("Contract source code already verified")?;
+            return Ok(())
+        };
+
+        shell::println(format!(
+            "Submitted contract for verification:\n\tVerification ID: `{id}`\n\tURL: {}/{id}",
+            url.trim_end_matches('/')
+        ))?;
+
+        if args.watch {
+            let check_args = VerifyCheckArgs {
+                id: id.to_string(),
+                etherscan: args.etherscan,
+                retry: RETRY_CHECK_ON_VERIFY,
+                verifier: args.verifier,
+            };
+            return self.check(check_args).await
+        }
+
+        Ok(())
+    }
+
+    /// Executes the command to check verification status on the zkSync Era block explorer
+    async fn check(&self, args: VerifyCheckArgs) -> Result<()> {
+        let url = verifier_url(
+            args.etherscan.chain.unwrap_or_default(),
+            args.verifier.verifier_url.as_deref(),
+        )?;
+        let url = format!("{}/{}", url.trim_end_matches('/'), args.id);
+
+        let retry: Retry = args.retry.into();
+        let status = retry
+            .run_async(|| {
+                async {
+                    let response = reqwest::get(&url).await?;
+                    if !response.status().is_success() {
+                        eyre::bail!(
+                            "Failed to request verification status with status code {}",
+                            response.status()
+                        );
+                    };
+
+                    let status = response.json::<ZkSyncVerificationStatus>().await?;
+
+                    trace!(target: "forge::verify", ?status, "Received verification status");
+
+                    shell::eprintln(format!(
+                        "Contract verification status:\nResponse: `{}`",
+                        status.status
+                    ))?;
+
+                    if status.is_pending() {
+                        return Err(eyre!("Verification is still pending...",))
+                    }
+
+                    Ok(status)
+                }
+                .boxed()
+            })
+            .await
+            .wrap_err("Checking verification result failed:")?;
+
+        status.into_result()
+    }
+}
+
+impl ZkSyncVerificationProvider {
+    /// Configures the request to the zkSync Era block explorer using the given [`VerifyArgs`].
+    ///
+    /// Returns the URL of the verification API along with the request.
+    fn prepare_request(&mut self, args: &VerifyArgs) -> Result<(String, ZkSyncVerifyRequest)> {
+        let mut config = args.try_load_config_emit_warnings()?;
+        config.libraries.extend(args.libraries.clone());
+
+        let url = verifier_url(
+            args.etherscan.chain.unwrap_or_default(),
+            args.verifier.verifier_url.as_deref(),
+        )?
+        .to_string();
+
+        let compiler_zksolc_version = match &args.zksolc_version {
+            Some(version) => format!("v{}", version.trim_start_matches('v')),
+            None => config.zksolc_version().ok_or_else(|| {
+                eyre!("The version of a local zksolc binary is unknown, set `--zksolc-version`")
+            })?,
+        };
+
+        let project = config.project()?;
+
+        let contract_path = self.etherscan.contract_path(args, &project)?;
+        let version = self.etherscan.compiler_version(args, &config, &project)?;
+
+        let mut input: StandardJsonCompilerInput = project
+            .standard_json_input(&contract_path)
+            .wrap_err("Failed to get standard json input")?
+            .normalize_evm_version(&version);
+
+        input.settings.libraries.libs = input
+            .settings
+            .libraries
+            .libs
+            .into_iter()
+            .map(|(f, libs)| (f.strip_prefix(project.root()).unwrap_or(&f).to_path_buf(), libs))
+            .collect();
+
+        let input = zksolc_input(input, &config, &contract_path)?;
+
+        let contract_name = format!(
+            "{}:{}",
+            contract_path.strip_prefix(project.root()).unwrap_or(&contract_path).display(),
+            args.contract.name
+        );
+        let constructor_args = self.etherscan.constructor_args(args, &project)?.unwrap_or_default();
+
+        let req = ZkSyncVerifyRequest {
+            contract_address: args.address.to_string(),
+            source_code: input,
+            code_format: "solidity-standard-json-input".to_string(),
+            contract_name,
+            compiler_zksolc_version,
+            // the build metadata isn't part of the versions the explorer knows
+            compiler_solc_version: format!("{}.{}.{}", version.major, version.minor, version.patch),
+            optimization_used: input.settings.optimizer.enabled.unwrap_or_default(),
+            constructor_arguments: format!("0x{}", constructor_args.trim_start_matches("0x")),
+        };
+
+        Ok((url, req))
+    }
+}
+
+/// Returns the zksolc standard JSON input compiling the sources of the solc `input` of the contract
+/// at `contract_path` like the project does, with the zksolc settings of `config`.
+fn zksolc_input(
+    input: StandardJsonCompilerInput,
+    config: &Config,
+    contract_path: &Path,
+) -> Result<ZkStandardJsonCompilerInput> {
+    let settings = config.zksolc_settings();
+    let optimizer_details =
+        if settings.optimizer { config.optimizer_details.clone() } else { None };
+    // zksolc appends the keccak256 hash of its metadata by default
+    let metadata = config.strip_metadata.then(|| SettingsMetadata {
+        use_literal_content: None,
+        bytecode_hash: Some(BytecodeHash::None),
+        cbor_metadata: None,
+    });
+    // the contracts of the `is-system` directories are always compiled in system mode
+    let is_system = settings.system_mode || contract_path.to_string_lossy().contains("is-system");
+
+    let settings = SettingsBuilder::new()
+        .remappings(input.settings.remappings)
+        .libraries(input.settings.libraries)
+        .is_system(is_system)
+        .force_evmla(settings.force_evmla)
+        .metadata(metadata)
+        .optimizer(|builder| {
+            builder
+                .enabled(settings.optimizer)
+                .mode(settings.optimizer_mode)
+                .optimize_for_size_fallback(settings.fallback_oz)
+                .disable_system_request_memoization(true)
+                .details(optimizer_details)
+        })
+        .build()
+        .map_err(|err| eyre!("Failed to configure the zksolc input: {err}"))?;
+    Ok(ZkStandardJsonCompilerInput::new(input.sources, settings))
+}
+
+/// Returns the ID of the verification submitted with the response `text` of status `status`, `None`
+/// if the contract is already verified.
+fn parse_submission(status: StatusCode, text: &str) -> Result<Option<u64>> {
+    if !status.is_success() {
+        // the explorer rejects the submissions of verified contracts
+        if status == StatusCode::BAD_REQUEST && text.trim() == ALREADY_VERIFIED_ERROR {
+            return Ok(None)
+        }
+        eyre::bail!("status code {status}\nDetails: {text}");
+    }
+    let id = text
+        .trim()
+        .parse::<u64>()
+        .wrap_err_with(|| format!("Unexpected response to the verification request: {text}"))?;
+    Ok(Some(id))
+}
+
+/// Returns the verification API of the zkSync Era block explorer of `chain`, the `verifier_url` if
+/// set.
+fn verifier_url(chain: Chain, verifier_url: Option<&str>) -> Result<&str> {
+    if let Some(url) = verifier_url {
+        return Ok(url)
+    }
+    match chain.id() {
+        324 => Ok(ZKSYNC_MAINNET_VERIFIER_URL),
+        300 => Ok(ZKSYNC_SEPOLIA_VERIFIER_URL),
+        id => eyre::bail!(
+            "No zkSync block explorer is known for chain {id}, set its verification API with `--verifier-url`"
+        ),
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkSyncVerifyRequest {
+    contract_address: String,
+    source_code: ZkStandardJsonCompilerInput,
+    code_format: String,
+    contract_name: String,
+    compiler_zksolc_version: String,
+    compiler_solc_version: String,
+    optimization_used: bool,
+    constructor_arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkSyncVerificationStatus {
+    status: String,
+    error: Option<String>,
+    compilation_errors: Option<Vec<String>>,
+}
+
+impl ZkSyncVerificationStatus {
+    /// Whether the verification is still to be processed by the explorer.
+    fn is_pending(&self) -> bool {
+        matches!(self.status.as_str(), "queued" | "in_progress")
+    }
+
+    /// Reports the final status of the verification.
+    fn into_result(self) -> Result<()> {
+        match self.status.as_str() {
+            "successful" => {
+                shell::println("Contract successfully verified")?;
+                Ok(())
+            }
+            "failed" => {
+                let mut details = self.error.unwrap_or_default();
+                for error in self.compilation_errors.unwrap_or_default() {
+                    details.push('\n');
+                    details.push_str(&error);
+                }
+                eyre::bail!("Contract failed to verify:\n{details}")
+            }
+            s => eyre::bail!("Unknown status from the zkSync block explorer. Status: {s:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_compilers::artifacts::Source;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_verifier_url() {
+        assert_eq!(verifier_url(Chain::from_id(324), None).unwrap(), ZKSYNC_MAINNET_VERIFIER_URL);
+        assert_eq!(verifier_url(Chain::from_id(300), None).unwrap(), ZKSYNC_SEPOLIA_VERIFIER_URL);
+        assert_eq!(
+            verifier_url(Chain::from_id(1), Some("http://localhost:3020/contract_verification"))
+                .unwrap(),
+            "http://localhost:3020/contract_verification"
+        );
+        assert!(verifier_url(Chain::from_id(1), None).is_err());
+    }
+
+    #[test]
+    fn test_submission_response() {
+        assert_eq!(parse_submission(StatusCode::OK, "42\n").unwrap(), Some(42));
+        assert_eq!(
+            parse_submission(StatusCode::BAD_REQUEST, ALREADY_VERIFIED_ERROR).unwrap(),
+            None
+        );
+        // a contract whose name mentions it isn't verified
+        let err = parse_submission(
+            StatusCode::BAD_REQUEST,
+            "Contract AlreadyVerifiedRegistry failed to compile",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("status code 400"));
+        assert!(parse_submission(StatusCode::OK, "queued").is_err());
+    }
+
+    #[test]
+    fn test_verify_request_body() {
+        let config = Config {
+            zk_optimizer: true,
+            mode: "z".to_string(),
+            optimizer_details: None,
+            strip_metadata: true,
+            ..Default::default()
+        };
+        let source = Source::new("contract Counter {}");
+        let input = StandardJsonCompilerInput::new(
+            vec![(PathBuf::from("src/Counter.sol"), source)],
+            Default::default(),
+        );
+        let input = zksolc_input(input, &config, Path::new("/project/src/Counter.sol")).unwrap();
+        let req = ZkSyncVerifyRequest {
+            contract_address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+            optimization_used: input.settings.optimizer.enabled.unwrap_or_default(),
+            source_code: input,
+            code_format: "solidity-standard-json-input".to_string(),
+            contract_name: "src/Counter.sol:Counter".to_string(),
+            compiler_zksolc_version: "v1.3.22".to_string(),
+            compiler_solc_version: "0.8.23".to_string(),
+            constructor_arguments: "0x".to_string(),
+        };
+
+        let body = serde_json::to_value(&req).unwrap();
+        assert_eq!(body["contractAddress"], "0x5FbDB2315678afecb367f032d93F642f64180aa3");
+        assert_eq!(body["codeFormat"], "solidity-standard-json-input");
+        assert_eq!(body["contractName"], "src/Counter.sol:Counter");
+        assert_eq!(body["compilerZksolcVersion"], "v1.3.22");
+        assert_eq!(body["compilerSolcVersion"], "0.8.23");
+        assert_eq!(body["optimizationUsed"], true);
+        assert_eq!(body["constructorArguments"], "0x");
+
+        let source_code = &body["sourceCode"];
+        assert_eq!(source_code["language"], "Solidity");
+        assert_eq!(source_code["sources"]["src/Counter.sol"]["content"], "contract Counter {}");
+        let settings = &source_code["settings"];
+        assert_eq!(settings["optimizer"]["enabled"], true);
+        assert_eq!(settings["optimizer"]["mode"], "z");
+        assert_eq!(settings["isSystem"], false);
+        assert_eq!(settings["forceEvmla"], false);
+        assert_eq!(settings["metadata"]["bytecodeHash"], "none");
+
+        // the contracts of the `is-system` directories are compiled in system mode
+        let input = StandardJsonCompilerInput::new(vec![], Default::default());
+        let input =
+            zksolc_input(input, &config, Path::new("/project/src/is-system/Account.sol")).unwrap();
+        assert!(input.settings.is_system);
+    }
+
+    #[test]
+    fn test_verification_status() {
+        let status: ZkSyncVerificationStatus =
+            serde_json::from_str(r#"{"status":"in_progress"}"#).unwrap();
+        assert!(status.is_pending());
+
+        let status: ZkSyncVerificationStatus =
+            serde_json::from_str(r#"{"status":"successful","error":null}"#).unwrap();
+        assert!(!status.is_pending());
+        assert!(status.into_result().is_ok());
+
+        let status: ZkSyncVerificationStatus = serde_json::from_str(
+            r#"{"status":"failed","error":"Compilation error","compilationErrors":["DeclarationError: Undeclared identifier."]}"#,
+        )
+        .unwrap();
+        let err = status.into_result().unwrap_err().to_string();
+        assert!(err.contains("Compilation error"));
+        assert!(err.contains("DeclarationError: Undeclared identifier."));
+    }
+}
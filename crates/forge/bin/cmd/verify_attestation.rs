@@ -0,0 +1,63 @@
+use super::build::{BuildArgs, ATTESTATION_FILE};
+use alloy_primitives::Address;
+use clap::{Parser, ValueHint};
+use eyre::{Result, WrapErr};
+use forge::attestation::{Attestation, AttestedArtifacts};
+use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
+use std::path::PathBuf;
+
+/// CLI arguments for `forge verify-attestation`.
+#[derive(Clone, Debug, Parser)]
+pub struct VerifyAttestationArgs {
+    /// The attestation to verify, as written with `forge build --attest`. Defaults to
+    /// `attestation.json` in the artifacts directory.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH")]
+    path: Option<PathBuf>,
+
+    /// The address of the project key the attestation must be signed with.
+    ///
+    /// Defaults to `attestation_signer` in the config, one of them is required.
+    #[clap(long, value_name = "ADDRESS")]
+    signer: Option<Address>,
+
+    #[clap(flatten)]
+    opts: CoreBuildArgs,
+}
+
+impl VerifyAttestationArgs {
+    pub fn run(self) -> Result<()> {
+        let build = BuildArgs { args: self.opts, ..Default::default() };
+        let config = build.try_load_config_emit_warnings()?;
+        let project = config.project()?;
+
+        let path = self.path.unwrap_or_else(|| project.artifacts_path().join(ATTESTATION_FILE));
+        let attestation = Attestation::read(&path)
+            .wrap_err_with(|| format!("failed to read the attestation {}", path.display()))?;
+        let Some(signer) = self.signer.or(config.attestation_signer) else {
+            eyre::bail!(
+                "the signer of the attestation must be set with `--signer` or \
+                 `attestation_signer` in the config"
+            )
+        };
+        attestation.verify_signature(signer)?;
+
+        let output = build.run()?;
+        let zksolc_version = config.zksync.then(|| config.zksolc_version()).flatten();
+        let artifacts = AttestedArtifacts::new(&project, &output, zksolc_version)?;
+        let mismatches = attestation.artifacts.mismatches(&artifacts);
+        if !mismatches.is_empty() {
+            eyre::bail!(
+                "the artifacts differ from the attestation {}:\n  {}",
+                path.display(),
+                mismatches.join("\n  ")
+            )
+        }
+
+        println!(
+            "Verified {} artifacts attested by {}",
+            attestation.artifacts.artifacts.len(),
+            attestation.signer
+        );
+        Ok(())
+    }
+}
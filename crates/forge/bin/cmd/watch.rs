@@ -1,9 +1,19 @@
 use super::{build::BuildArgs, snapshot::SnapshotArgs, test::TestArgs};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
 use clap::Parser;
 use eyre::Result;
-use foundry_cli::utils::{self, FoundryPathExt};
-use foundry_config::Config;
-use std::{collections::HashSet, convert::Infallible, path::PathBuf, sync::Arc};
+use forge::{metrics::TestMetrics, result::TestOutcome};
+use foundry_cli::utils;
+use foundry_common::shell;
+use foundry_config::{Config, FsPermissions, RpcEndpoints};
+use serde::Serialize;
+use std::{convert::Infallible, net::SocketAddr, path::PathBuf, sync::Arc};
+use tokio::sync::{mpsc, oneshot};
 use watchexec::{
     action::{Action, Outcome, PreSpawn},
     command::Command,
@@ -15,6 +25,14 @@ use watchexec::{
     Watchexec,
 };
 
+/// The file in the cache directory the inputs of the test suites are recorded in by `forge test
+/// --watch`.
+pub const TEST_INPUTS_FILE: &str = "test-inputs.json";
+
+/// The file in the cache directory the metrics of the runs of `forge test --watch --metrics-addr`
+/// are recorded in.
+pub const TEST_METRICS_FILE: &str = "test-metrics.json";
+
 #[derive(Clone, Debug, Default, Parser)]
 #[clap(next_help_heading = "Watch options")]
 pub struct WatchArgs {
@@ -35,7 +53,8 @@ pub struct WatchArgs {
 
     /// Explicitly re-run all tests when a change is made.
     ///
-    /// By default, only the tests of the last modified test file are executed.
+    /// By default, only the test suites whose inputs changed since they last passed are executed:
+    /// the artifacts of the contracts they deploy and the files they read with cheatcodes.
     #[clap(long)]
     pub run_all: bool,
 
@@ -54,27 +73,53 @@ pub struct WatchArgs {
     /// overloading disk I/O.
     #[clap(long, value_name = "DELAY")]
     pub watch_delay: Option<String>,
+
+    /// Serve the metrics of the test runs at `http://<ADDR>/metrics`, in the Prometheus text
+    /// format.
+    ///
+    /// Only supported by `forge test`: the metrics include the number of runs and of executed
+    /// tests, their durations, EVM gas and EraVM ergs, the test suites skipped as unchanged, the
+    /// fork cache hits and the RPC requests.
+    #[clap(long, value_name = "ADDR")]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Run as a daemon, running the tests on every `POST http://<ADDR>/run` and responding with
+    /// their results.
+    ///
+    /// Only supported by `forge test`. The config is reloaded before every run, so changes to
+    /// e.g. the cheatcode fs permissions or the rpc endpoints apply to the next run without
+    /// restarting the daemon.
+    #[clap(long, value_name = "ADDR", conflicts_with_all = ["watch", "metrics_addr"])]
+    pub daemon: Option<SocketAddr>,
 }
 
 impl WatchArgs {
     /// Returns new [InitConfig] and [RuntimeConfig] based on the [WatchArgs]
     ///
     /// If paths were provided as arguments the these will be used as the watcher's pathset,
-    /// otherwise the path the closure returns will be used
+    /// otherwise the path the closure returns will be used.
+    ///
+    /// The `config_path` is always watched: every run reloads the config, so changes to e.g. the
+    /// cheatcode fs permissions or the rpc endpoints apply to the next run without restarting.
     pub fn watchexec_config(
         &self,
+        config_path: PathBuf,
         f: impl FnOnce() -> Vec<PathBuf>,
     ) -> Result<(InitConfig, RuntimeConfig)> {
         let init = init()?;
         let mut runtime = runtime(self)?;
 
         // contains all the arguments `--watch p1, p2, p3`
-        let has_paths = self.watch.as_ref().map(|paths| !paths.is_empty()).unwrap_or_default();
-
-        if !has_paths {
+        let mut paths = match &self.watch {
+            Some(paths) if !paths.is_empty() => paths.clone(),
             // use alternative pathset, but only those that exists
-            runtime.pathset(f().into_iter().filter(|p| p.exists()));
+            _ => f().into_iter().filter(|p| p.exists()).collect(),
+        };
+        if config_path.exists() && !paths.contains(&config_path) {
+            paths.push(config_path);
         }
+        runtime.pathset(paths);
+
         Ok((init, runtime))
     }
 }
@@ -86,10 +131,10 @@ pub async fn watch_build(args: BuildArgs) -> Result<()> {
     let cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
 
     trace!("watch build cmd={:?}", cmd);
-    runtime.command(watch_command(cmd.clone()));
+    runtime.command(watch_command(cmd));
 
     let wx = Watchexec::new(init, runtime.clone())?;
-    on_action(args.watch, runtime, Arc::clone(&wx), cmd, (), |_| {});
+    on_action(args.watch, runtime, Arc::clone(&wx));
 
     // start executing the command immediately
     wx.send_event(Event::default(), Priority::default()).await?;
@@ -105,10 +150,10 @@ pub async fn watch_snapshot(args: SnapshotArgs) -> Result<()> {
     let cmd = cmd_args(args.test.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
 
     trace!("watch snapshot cmd={:?}", cmd);
-    runtime.command(watch_command(cmd.clone()));
+    runtime.command(watch_command(cmd));
     let wx = Watchexec::new(init, runtime.clone())?;
 
-    on_action(args.test.watch.clone(), runtime, Arc::clone(&wx), cmd, (), |_| {});
+    on_action(args.test.watch.clone(), runtime, Arc::clone(&wx));
 
     // start executing the command immediately
     wx.send_event(Event::default(), Priority::default()).await?;
@@ -119,29 +164,34 @@ pub async fn watch_snapshot(args: SnapshotArgs) -> Result<()> {
 
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
 /// test`
+///
+/// Unless `--run-all` is set, every run records the inputs of the test suites in the cache
+/// directory, and only the suites whose inputs changed since they last passed are run again.
 pub async fn watch_test(args: TestArgs) -> Result<()> {
     let (init, mut runtime) = args.watchexec_config()?;
-    let cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
+    let mut cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
+    if !args.watch.run_all && args.incremental.is_none() {
+        let config: Config = args.build_args().into();
+        let inputs = config.__root.0.join(&config.cache_path).join(TEST_INPUTS_FILE);
+        cmd.push("--incremental".to_string());
+        cmd.push(inputs.to_string_lossy().into_owned());
+    }
+    if let Some(addr) = args.watch.metrics_addr {
+        let config: Config = args.build_args().into();
+        let metrics = config.__root.0.join(&config.cache_path).join(TEST_METRICS_FILE);
+        // the counters start over with every session
+        if metrics.exists() {
+            std::fs::remove_file(&metrics)?;
+        }
+        cmd.push("--metrics-out".to_string());
+        cmd.push(metrics.to_string_lossy().into_owned());
+        serve_metrics(addr, metrics)?;
+    }
     trace!("watch test cmd={:?}", cmd);
-    runtime.command(watch_command(cmd.clone()));
+    runtime.command(watch_command(cmd));
     let wx = Watchexec::new(init, runtime.clone())?;
 
-    let config: Config = args.build_args().into();
-
-    let filter = args.filter(&config);
-
-    // marker to check whether to override the command
-    let no_reconfigure = filter.args().test_pattern.is_some() ||
-        filter.args().path_pattern.is_some() ||
-        filter.args().contract_pattern.is_some() ||
-        args.watch.run_all;
-
-    let state = WatchTestState {
-        project_root: config.__root.0,
-        no_reconfigure,
-        last_test_files: Default::default(),
-    };
-    on_action(args.watch.clone(), runtime, Arc::clone(&wx), cmd, state, on_test);
+    on_action(args.watch.clone(), runtime, Arc::clone(&wx));
 
     // start executing the command immediately
     wx.send_event(Event::default(), Priority::default()).await?;
@@ -150,102 +200,107 @@ pub async fn watch_test(args: TestArgs) -> Result<()> {
     Ok(())
 }
 
-#[derive(Clone, Debug)]
-struct WatchTestState {
-    /// the root directory of the project
-    project_root: PathBuf,
-    /// marks whether we can reconfigure the watcher command with the `--match-path` arg
-    no_reconfigure: bool,
-    /// Tracks the last changed test files, if any so that if a non-test file was modified we run
-    /// this file instead *Note:* this is a vec, so we can also watch out for changes
-    /// introduced by `forge fmt`
-    last_test_files: HashSet<String>,
+/// The results of a run of `forge test --daemon`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DaemonRun {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    /// The failed tests, as `<path>:<contract> <signature>`.
+    failures: Vec<String>,
+    /// Whether the fs permissions or the rpc endpoints changed since the previous run.
+    config_changed: bool,
 }
 
-/// The `on_action` hook for `forge test --watch`
-fn on_test(action: OnActionState<WatchTestState>) {
-    let OnActionState { args, runtime, action, wx, cmd, other } = action;
-    let WatchTestState { project_root, no_reconfigure, last_test_files } = other;
-
-    if no_reconfigure {
-        // nothing to reconfigure
-        return
-    }
-
-    let mut cmd = cmd.clone();
-
-    let mut changed_sol_test_files: HashSet<_> = action
-        .events
-        .iter()
-        .flat_map(|e| e.paths())
-        .filter(|(path, _)| path.is_sol_test())
-        .filter_map(|(path, _)| path.to_str())
-        .map(str::to_string)
-        .collect();
-
-    // replace `--match-path` | `-mp` argument
-    if let Some(pos) = cmd.iter().position(|arg| arg == "--match-path" || arg == "-mp") {
-        // --match-path requires 1 argument
-        cmd.drain(pos..=(pos + 1));
-    }
-
-    if changed_sol_test_files.len() > 1 ||
-        (changed_sol_test_files.is_empty() && last_test_files.is_empty())
-    {
-        // this could happen if multiple files were changed at once, for example `forge fmt` was
-        // run, or if no test files were changed and no previous test files were modified in which
-        // case we simply run all
-        let mut config = runtime.clone();
-        config.command(watch_command(cmd.clone()));
-        // re-register the action
-        on_action(
-            args.clone(),
-            config,
-            wx,
-            cmd,
-            WatchTestState {
-                project_root,
-                no_reconfigure,
-                last_test_files: changed_sol_test_files,
-            },
-            on_test,
-        );
-        return
-    }
-
-    if changed_sol_test_files.is_empty() {
-        // reuse the old test files if a non-test file was changed
-        changed_sol_test_files = last_test_files;
+impl DaemonRun {
+    fn new(outcome: &TestOutcome, config_changed: bool) -> Self {
+        let failures = outcome
+            .results
+            .iter()
+            .flat_map(|(id, suite)| suite.failures().map(move |(sig, _)| format!("{id} {sig}")))
+            .collect();
+        Self {
+            passed: outcome.passed(),
+            failed: outcome.failed(),
+            skipped: outcome.skipped(),
+            failures,
+            config_changed,
+        }
     }
+}
 
-    // append `--match-path` glob
-    let mut file = changed_sol_test_files.clone().into_iter().next().expect("test file present");
+/// Runs `forge test` as a daemon at `addr`, running the tests on every `POST /run`.
+///
+/// The runs are executed one at a time, in this task, and every run reloads the config and builds
+/// a new `CheatsConfig` from it.
+pub async fn daemon_test(args: TestArgs, addr: SocketAddr) -> Result<()> {
+    args.install_shell_and_profile()?;
+
+    let (runs, mut requests) = mpsc::channel::<oneshot::Sender<Result<DaemonRun, String>>>(16);
+    let app = Router::new().route(
+        "/run",
+        post(move || {
+            let runs = runs.clone();
+            async move {
+                let (reply, run) = oneshot::channel();
+                if runs.send(reply).await.is_err() {
+                    return StatusCode::SERVICE_UNAVAILABLE.into_response()
+                }
+                match run.await {
+                    Ok(Ok(run)) => Json(run).into_response(),
+                    Ok(Err(err)) => (StatusCode::INTERNAL_SERVER_ERROR, err).into_response(),
+                    Err(_) => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+                }
+            }
+        }),
+    );
+    let server = hyper::Server::try_bind(&addr)?.serve(app.into_make_service());
+    shell::println(format!("Running tests on POST http://{}/run", server.local_addr()))?;
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            error!(%err, "test daemon server failed");
+        }
+    });
 
-    // remove the project root dir from the detected file
-    if let Some(root) = project_root.as_os_str().to_str() {
-        if let Some(f) = file.strip_prefix(root) {
-            file = f.trim_start_matches('/').to_string();
+    let mut last_config = None::<(FsPermissions, RpcEndpoints)>;
+    while let Some(reply) = requests.recv().await {
+        let config = Config::from(&args);
+        let reloaded = (config.fs_permissions, config.rpc_endpoints);
+        let config_changed = last_config.as_ref().is_some_and(|last| *last != reloaded);
+        if config_changed {
+            shell::println(
+                "Config changed, running with the new fs permissions and rpc endpoints",
+            )?;
         }
+        last_config = Some(reloaded);
+
+        let run = args.clone().execute_tests().await;
+        let _ = reply.send(
+            run.map(|outcome| DaemonRun::new(&outcome, config_changed))
+                .map_err(|err| format!("{err:?}")),
+        );
     }
+    Ok(())
+}
 
-    let mut new_cmd = cmd.clone();
-    new_cmd.push("--match-path".to_string());
-    new_cmd.push(file);
-    trace!("reconfigure test command {:?}", new_cmd);
-
-    // reconfigure the executor with a new runtime
-    let mut config = runtime.clone();
-    config.command(watch_command(new_cmd));
-
-    // re-register the action
-    on_action(
-        args.clone(),
-        config,
-        wx,
-        cmd,
-        WatchTestState { project_root, no_reconfigure, last_test_files: changed_sol_test_files },
-        on_test,
+/// Serves the metrics recorded at `path` at `http://<addr>/metrics` in the background.
+fn serve_metrics(addr: SocketAddr, path: PathBuf) -> Result<()> {
+    let app = Router::new().route(
+        "/metrics",
+        get(move || {
+            let path = path.clone();
+            async move { TestMetrics::read(&path).render() }
+        }),
     );
+    let server = hyper::Server::try_bind(&addr)?.serve(app.into_make_service());
+    shell::println(format!("Serving test metrics on http://{addr}/metrics"))?;
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            error!(%err, "test metrics server failed");
+        }
+    });
+    Ok(())
 }
 
 /// Converts a list of arguments to a `watchexec::Command`
@@ -310,36 +365,13 @@ pub fn init() -> Result<InitConfig> {
     Ok(config)
 }
 
-/// Contains all necessary context to reconfigure a [`Watchexec`] on the fly
-struct OnActionState<'a, T: Clone> {
-    args: &'a WatchArgs,
-    runtime: &'a RuntimeConfig,
-    action: &'a Action,
-    cmd: &'a Vec<String>,
-    wx: Arc<Watchexec>,
-    // additional context to inject
-    other: T,
-}
-
 /// Registers the `on_action` hook on the `RuntimeConfig` currently in use in the `Watchexec`
 ///
 /// **Note** this is a bit weird since we're installing the hook on the config that's already used
 /// in `Watchexec` but necessary if we want to have access to it in order to
 /// [`Watchexec::reconfigure`]
-fn on_action<F, T>(
-    args: WatchArgs,
-    mut config: RuntimeConfig,
-    wx: Arc<Watchexec>,
-    cmd: Vec<String>,
-    other: T,
-    f: F,
-) where
-    F: for<'a> Fn(OnActionState<'a, T>) + Send + 'static,
-    T: Clone + Send + 'static,
-{
+fn on_action(args: WatchArgs, mut config: RuntimeConfig, wx: Arc<Watchexec>) {
     let on_busy = if args.no_restart { "do-nothing" } else { "restart" };
-    let runtime = config.clone();
-    let w = Arc::clone(&wx);
     config.on_action(move |action: Action| {
         let fut = async { Ok::<(), Infallible>(()) };
         let signals: Vec<MainSignal> = action.events.iter().flat_map(|e| e.signals()).collect();
@@ -386,15 +418,6 @@ fn on_action<F, T>(
             }
         }
 
-        f(OnActionState {
-            args: &args,
-            runtime: &runtime,
-            action: &action,
-            wx: w.clone(),
-            cmd: &cmd,
-            other: other.clone(),
-        });
-
         // mattsse: could be made into flag to never clear the shell
         let clear = false;
         let when_running = match (clear, on_busy) {
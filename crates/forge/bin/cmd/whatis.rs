@@ -0,0 +1,272 @@
+use alloy_primitives::{keccak256, Address, B256};
+use alloy_providers::provider::TempProvider;
+use alloy_rpc_types::BlockId;
+use clap::Parser;
+use eyre::{Result, WrapErr};
+use foundry_cli::{
+    opts::{CoreBuildArgs, RpcOpts},
+    utils::LoadConfig,
+};
+use foundry_common::{compile::ProjectCompiler, provider::alloy::ProviderBuilder};
+use foundry_compilers::{Artifact, ProjectCompileOutput};
+use foundry_zksync_compiler::{DualCompiledContracts, PackedEraBytecode, ZkSolc};
+use foundry_zksync_core::{
+    convert::{ConvertH160, ConvertH256},
+    get_account_code_key, ACCOUNT_CODE_STORAGE_ADDRESS,
+};
+use serde::Serialize;
+use std::{fmt, path::Path};
+
+/// CLI arguments for `forge whatis`.
+///
+/// Finds the local artifacts matching the code deployed at an address, to audit what's actually
+/// deployed. On an Era chain, the bytecode hash kept by the `AccountCodeStorage` system contract
+/// is matched against the zksolc artifacts, and their solc counterparts are found in the
+/// dual-compiled registry. On other chains, the hash of the deployed code is matched against the
+/// solc artifacts, so contracts with immutables don't match.
+#[derive(Clone, Debug, Parser)]
+pub struct WhatisArgs {
+    /// The address of the contract.
+    #[clap(value_name = "ADDRESS")]
+    address: Address,
+
+    /// The block height to query at, the latest block if not set.
+    #[clap(long, short = 'B', value_name = "BLOCK")]
+    block: Option<u64>,
+
+    /// Print the report as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+
+    #[clap(flatten)]
+    opts: CoreBuildArgs,
+}
+
+/// The VM a matching artifact was compiled for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactVm {
+    Evm,
+    EraVm,
+}
+
+impl fmt::Display for ArtifactVm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Evm => "EVM",
+            Self::EraVm => "EraVM",
+        })
+    }
+}
+
+/// A local artifact matching the deployed code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ArtifactMatch {
+    pub vm: ArtifactVm,
+    /// The identifier of the artifact, `<path>:<name>` with a path relative to the project root.
+    pub artifact: String,
+    /// The compilers of the artifact and their versions.
+    pub compiler: String,
+    /// The identifier of the solc artifact of the same contract, for EraVM artifacts in the
+    /// dual-compiled registry.
+    pub evm_counterpart: Option<String>,
+}
+
+impl fmt::Display for ArtifactMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { vm, artifact, compiler, evm_counterpart } = self;
+        write!(f, "[{vm}] {artifact} ({compiler})")?;
+        if let Some(counterpart) = evm_counterpart {
+            write!(f, ", compiled with solc as {counterpart}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The local artifacts matching the code deployed at an address.
+#[derive(Clone, Debug, Serialize)]
+pub struct WhatisReport {
+    pub address: Address,
+    /// The keccak256 hash of the deployed code.
+    pub code_hash: B256,
+    /// The bytecode hash of the contract, if it's deployed on an Era chain.
+    pub era_bytecode_hash: Option<B256>,
+    pub matches: Vec<ArtifactMatch>,
+}
+
+impl WhatisReport {
+    fn print(&self) {
+        match self.era_bytecode_hash {
+            Some(hash) => {
+                println!("{} is an EraVM contract with bytecode hash {hash}", self.address)
+            }
+            None => println!("{} has code hash {}", self.address, self.code_hash),
+        }
+        if self.matches.is_empty() {
+            return
+        }
+        println!("\nMatching artifacts:");
+        for artifact in &self.matches {
+            println!("  {artifact}");
+        }
+    }
+}
+
+impl WhatisArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = self.opts.try_load_config_emit_warnings()?;
+        let url = self
+            .rpc
+            .url(Some(&config))?
+            .ok_or_else(|| eyre::eyre!("an RPC URL is required, pass --rpc-url"))?;
+        let provider = ProviderBuilder::new(&url).build()?;
+        let block = self.block.map(|number| BlockId::Number(number.into()));
+
+        let code = provider.get_code_at(self.address, block).await?;
+        if code.is_empty() {
+            eyre::bail!("{} has no deployed code", self.address);
+        }
+        // the slot is empty on the chains without the `AccountCodeStorage` system contract
+        let account_code = provider
+            .get_storage_at(
+                ACCOUNT_CODE_STORAGE_ADDRESS.to_address(),
+                get_account_code_key(self.address),
+                block,
+            )
+            .await
+            .wrap_err("failed to read the bytecode hash from the `AccountCodeStorage`")?;
+        let era_bytecode_hash =
+            Some(constructed_hash(account_code.into())).filter(|hash| !hash.is_zero());
+
+        let project = self.opts.project()?;
+        let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+        let root = project.root();
+
+        let matches = match era_bytecode_hash {
+            Some(hash) => {
+                let mut zksolc = ZkSolc::new(
+                    config
+                        .new_zksolc_config_builder()
+                        .and_then(|builder| builder.build())
+                        .map_err(|e| eyre::eyre!(e))?,
+                    config.zk_project()?,
+                );
+                let (zk_output, _) = zksolc
+                    .compile()
+                    .map_err(|e| eyre::eyre!("Failed to compile with zksolc: {e}"))?;
+                let dual_compiled_contracts = DualCompiledContracts::new(&output, &zk_output);
+                let zksolc_version = config.zksolc_version().unwrap_or_else(|| "local".to_string());
+
+                let evm_counterpart = dual_compiled_contracts
+                    .iter()
+                    .find(|contract| contract.zk_bytecode_hash.to_b256() == hash)
+                    .and_then(|contract| {
+                        matching_artifacts(&output, root, contract.evm_bytecode_hash, |code| {
+                            keccak256(code)
+                        })
+                        .next()
+                    })
+                    .map(|(artifact, _)| artifact);
+                matching_artifacts(&zk_output, root, hash, era_bytecode_hash_of)
+                    .map(|(artifact, version)| ArtifactMatch {
+                        vm: ArtifactVm::EraVm,
+                        artifact,
+                        compiler: format!("solc {version}, zksolc {zksolc_version}"),
+                        evm_counterpart: evm_counterpart.clone(),
+                    })
+                    .collect()
+            }
+            None => matching_artifacts(&output, root, keccak256(&code), |code| keccak256(code))
+                .map(|(artifact, version)| ArtifactMatch {
+                    vm: ArtifactVm::Evm,
+                    artifact,
+                    compiler: format!("solc {version}"),
+                    evm_counterpart: None,
+                })
+                .collect(),
+        };
+
+        let report = WhatisReport {
+            address: self.address,
+            code_hash: keccak256(&code),
+            era_bytecode_hash,
+            matches,
+        };
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            report.print();
+        }
+
+        if report.matches.is_empty() {
+            eyre::bail!("no local artifact matches the code deployed at {}", self.address);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the identifiers and compiler versions of the artifacts of `output` whose deployed
+/// bytecode hashes to `hash` with `code_hash`.
+fn matching_artifacts<'a>(
+    output: &'a ProjectCompileOutput,
+    root: &'a Path,
+    hash: B256,
+    code_hash: impl Fn(&[u8]) -> B256 + 'a,
+) -> impl Iterator<Item = (String, String)> + 'a {
+    output.artifact_ids().filter_map(move |(id, artifact)| {
+        let code = artifact.get_deployed_bytecode_bytes()?;
+        if code.is_empty() || code_hash(&code) != hash {
+            return None
+        }
+        let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+        Some((format!("{}:{}", source.display(), id.name), id.version.to_string()))
+    })
+}
+
+/// Returns the bytecode hash of the deployed bytecode of a zksolc artifact.
+fn era_bytecode_hash_of(code: &[u8]) -> B256 {
+    PackedEraBytecode::from_vec(code).bytecode_hash().to_b256()
+}
+
+/// Returns the bytecode hash of a constructed contract for a hash kept by the
+/// `AccountCodeStorage`, whose second byte marks the contracts whose constructor is running.
+fn constructed_hash(mut hash: B256) -> B256 {
+    if !hash.is_zero() {
+        hash[1] = 0;
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn clears_the_constructing_marker() {
+        let constructed = b256!("0100000300000000000000000000000000000000000000000000000000000001");
+        let constructing =
+            b256!("0101000300000000000000000000000000000000000000000000000000000001");
+        assert_eq!(constructed_hash(constructing), constructed);
+        assert_eq!(constructed_hash(constructed), constructed);
+        assert_eq!(constructed_hash(B256::ZERO), B256::ZERO);
+    }
+
+    #[test]
+    fn displays_matches() {
+        let artifact = ArtifactMatch {
+            vm: ArtifactVm::EraVm,
+            artifact: "src/Counter.sol:Counter".to_string(),
+            compiler: "solc 0.8.23, zksolc v1.4.0".to_string(),
+            evm_counterpart: Some("src/Counter.sol:Counter".to_string()),
+        };
+        assert_eq!(
+            artifact.to_string(),
+            "[EraVM] src/Counter.sol:Counter (solc 0.8.23, zksolc v1.4.0), compiled with solc as \
+             src/Counter.sol:Counter"
+        );
+    }
+}
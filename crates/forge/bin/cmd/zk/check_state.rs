@@ -0,0 +1,402 @@
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_providers::provider::TempProvider;
+use alloy_rpc_types::{
+    request::{TransactionInput, TransactionRequest},
+    BlockId,
+};
+use clap::Parser;
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_cli::opts::RpcOpts;
+use foundry_common::provider::{alloy::ProviderBuilder, ethers::RetryProvider};
+use foundry_config::{find_project_root_path, Config};
+use foundry_evm::{
+    backend::Backend, executors::TracingExecutor, opts::EvmOpts, utils::configure_tx_env,
+};
+use foundry_zksync_core::is_system_address;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use yansi::Paint;
+
+/// CLI arguments for `forge zk check-state`.
+///
+/// Replays a mined transaction on top of the parent block with the bundled zkEVM and compares the
+/// outcome with the live node: status, return data, emitted events and the final values of all
+/// storage slots written locally.
+///
+/// Only the state of the parent block is used, so transactions executed earlier in the same block
+/// are not taken into account. Slots that are only written by the live node can't be detected.
+#[derive(Clone, Debug, Parser)]
+pub struct CheckStateArgs {
+    /// The hash of the transaction to check.
+    tx_hash: String,
+
+    /// Also compare events and storage of system contracts, e.g. fee transfers and nonces.
+    #[clap(long)]
+    include_system: bool,
+
+    /// Print the mismatches as JSON.
+    #[clap(long, short)]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+/// A divergence between the local execution and the live node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StateMismatch {
+    /// The transaction succeeded on one side and failed on the other.
+    Status { local: bool, remote: bool },
+    /// The transaction returned different data.
+    ReturnData { local: Bytes, remote: Bytes },
+    /// A different number of events was emitted.
+    EventCount { local: usize, remote: usize },
+    /// The event at `index` differs.
+    Event { index: usize, local: Event, remote: Event },
+    /// A storage slot ended up with a different value.
+    Storage { address: Address, slot: U256, local: U256, remote: U256 },
+}
+
+impl fmt::Display for StateMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { local, remote } => {
+                write!(f, "status: local success={local}, remote success={remote}")
+            }
+            Self::ReturnData { local, remote } => {
+                write!(f, "return data: local {local}, remote {remote}")
+            }
+            Self::EventCount { local, remote } => {
+                write!(f, "event count: local {local}, remote {remote}")
+            }
+            Self::Event { index, local, remote } => {
+                write!(f, "event #{index}: local {local}, remote {remote}")
+            }
+            Self::Storage { address, slot, local, remote } => {
+                write!(f, "storage {address} slot {slot:#x}: local {local:#x}, remote {remote:#x}")
+            }
+        }
+    }
+}
+
+/// An emitted event.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Event {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ address: {}, topics: [", self.address)?;
+        for (i, topic) in self.topics.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{topic}")?;
+        }
+        write!(f, "], data: {} }}", self.data)
+    }
+}
+
+/// A transaction of `zks_getRawBlockTransactions`, with its factory dependencies.
+#[derive(Debug, Deserialize)]
+struct RawTransaction {
+    common_data: RawCommonData,
+    execute: RawExecute,
+}
+
+/// The common data of a transaction, keyed by its kind, `L1`, `L2` or `ProtocolUpgrade`.
+#[derive(Debug, Deserialize)]
+struct RawCommonData {
+    #[serde(rename = "L2")]
+    l2: Option<RawL2CommonData>,
+    #[serde(rename = "L1")]
+    l1: Option<RawL1CommonData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawL2CommonData {
+    input: Option<RawInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawInput {
+    hash: B256,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawL1CommonData {
+    canonical_tx_hash: B256,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawExecute {
+    #[serde(default)]
+    factory_deps: Option<Vec<Vec<u8>>>,
+}
+
+/// Returns the factory dependencies of the transaction `tx_hash` mined in the block
+/// `block_number`, which are only returned with the raw transactions of the block.
+async fn factory_deps(
+    provider: &RetryProvider,
+    block_number: u64,
+    tx_hash: B256,
+) -> Result<Option<Vec<Vec<u8>>>> {
+    let txs: Vec<RawTransaction> = provider
+        .request("zks_getRawBlockTransactions", [block_number])
+        .await
+        .wrap_err("failed to get the raw transactions of the block, is the RPC an Era node?")?;
+    find_factory_deps(txs, block_number, tx_hash)
+}
+
+/// Returns the factory dependencies of the transaction `tx_hash` among the raw transactions of
+/// the block `block_number`, matched by their L2 hash or the canonical hash of L1 transactions.
+fn find_factory_deps(
+    txs: Vec<RawTransaction>,
+    block_number: u64,
+    tx_hash: B256,
+) -> Result<Option<Vec<Vec<u8>>>> {
+    let tx = txs.into_iter().find(|raw| {
+        let l2_hash = raw.common_data.l2.as_ref().and_then(|data| data.input.as_ref());
+        let l1_hash = raw.common_data.l1.as_ref().map(|data| data.canonical_tx_hash);
+        l2_hash.map(|input| input.hash) == Some(tx_hash) || l1_hash == Some(tx_hash)
+    });
+    let tx = tx.ok_or_else(|| {
+        eyre::eyre!("tx {tx_hash} not found in the raw transactions of block {block_number}")
+    })?;
+    Ok(tx.execute.factory_deps.filter(|deps| !deps.is_empty()))
+}
+
+/// Compares the events emitted locally with the ones of the live node, in order.
+fn event_mismatches(local: Vec<Event>, remote: Vec<Event>) -> Vec<StateMismatch> {
+    let mut mismatches = vec![];
+    if local.len() != remote.len() {
+        mismatches.push(StateMismatch::EventCount { local: local.len(), remote: remote.len() });
+    }
+    for (index, (local, remote)) in local.into_iter().zip(remote).enumerate() {
+        if local != remote {
+            mismatches.push(StateMismatch::Event { index, local, remote });
+        }
+    }
+    mismatches
+}
+
+impl CheckStateArgs {
+    pub async fn run(self) -> Result<()> {
+        let figment =
+            Config::figment_with_root(find_project_root_path(None).unwrap()).merge(self.rpc);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let mut config = Config::try_from(figment)?.sanitized();
+
+        let rpc_url = config.get_rpc_url_or_localhost_http()?;
+        let provider = ProviderBuilder::new(&rpc_url).build()?;
+        let zk_provider =
+            foundry_common::provider::ethers::ProviderBuilder::new(&rpc_url).build()?;
+
+        let tx_hash: B256 = self.tx_hash.parse().wrap_err("invalid tx hash")?;
+        let tx = provider
+            .get_transaction_by_hash(tx_hash)
+            .await
+            .wrap_err_with(|| format!("tx not found: {tx_hash:?}"))?;
+        let receipt = provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("receipt not found: {tx_hash:?}"))?;
+        let tx_block_number = tx
+            .block_number
+            .ok_or_else(|| eyre::eyre!("tx may still be pending: {tx_hash:?}"))?
+            .to::<u64>();
+        // the transactions of the genesis block have no state to be replayed on
+        let Some(parent_block_number) = tx_block_number.checked_sub(1) else {
+            eyre::bail!("{tx_hash} is a genesis transaction, which can't be replayed")
+        };
+        let factory_deps = factory_deps(&zk_provider, tx_block_number, tx_hash).await?;
+
+        // execute on top of the parent block
+        config.fork_block_number = Some(parent_block_number.into());
+        let (mut env, fork, _) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
+        env.block.number = U256::from(tx_block_number);
+        if let Some(block) = provider.get_block(tx_block_number.into(), false).await? {
+            env.block.timestamp = block.header.timestamp;
+            env.block.coinbase = block.header.miner;
+            env.block.basefee = block.header.base_fee_per_gas.unwrap_or_default();
+            env.block.gas_limit = block.header.gas_limit;
+        }
+        configure_tx_env(&mut env, &tx);
+
+        let mut backend = Backend::spawn(fork).await;
+        backend.is_zk = true;
+        let local = backend.simulate_zk(&mut env, factory_deps)?;
+
+        let mut mismatches = vec![];
+
+        let local_success = local.result.is_success();
+        let remote_success = receipt.status_code.map_or(false, |status| status.to::<u64>() == 1);
+        if local_success != remote_success {
+            mismatches.push(StateMismatch::Status { local: local_success, remote: remote_success });
+        }
+
+        // the live node only returns data for calls, so replay the transaction as one
+        let request = TransactionRequest {
+            from: Some(tx.from),
+            to: tx.to,
+            value: Some(tx.value),
+            gas: Some(tx.gas),
+            input: TransactionInput::new(tx.input.clone()),
+            ..Default::default()
+        };
+        let parent_block = BlockId::Number(parent_block_number.into());
+        if let (Some(local), Ok(remote)) =
+            (local.result.output(), provider.call(request, Some(parent_block)).await)
+        {
+            if *local != remote {
+                mismatches.push(StateMismatch::ReturnData { local: local.clone(), remote });
+            }
+        }
+
+        let keep = |address: &Address| self.include_system || !is_system_address(*address);
+        let local_events = local
+            .result
+            .logs()
+            .into_iter()
+            .filter(|log| keep(&log.address))
+            .map(|log| Event { address: log.address, topics: log.topics, data: log.data })
+            .collect::<Vec<_>>();
+        let remote_events = receipt
+            .logs
+            .into_iter()
+            .filter(|log| keep(&log.address))
+            .map(|log| Event { address: log.address, topics: log.topics, data: log.data })
+            .collect::<Vec<_>>();
+        mismatches.extend(event_mismatches(local_events, remote_events));
+
+        let written_slots = local
+            .state
+            .into_iter()
+            .filter(|(address, _)| keep(address))
+            .flat_map(|(address, account)| {
+                account
+                    .storage
+                    .into_iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(move |(slot, value)| ((address, slot), value.present_value))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let block = BlockId::Number(tx_block_number.into());
+        for ((address, slot), local) in written_slots {
+            let remote = provider.get_storage_at(address, slot, Some(block)).await?;
+            if local != remote {
+                mismatches.push(StateMismatch::Storage { address, slot, local, remote });
+            }
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&mismatches)?);
+        } else if mismatches.is_empty() {
+            println!("{}", Paint::green(format!("No divergences found for {tx_hash}")));
+        } else {
+            println!("{}", Paint::red(format!("Found divergences for {tx_hash}:")));
+            for mismatch in &mismatches {
+                println!("  - {mismatch}");
+            }
+        }
+
+        if !mismatches.is_empty() {
+            let count = mismatches.len();
+            eyre::bail!("local execution diverged from the live node in {count} places");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    const L2_TX: B256 = b256!("1111111111111111111111111111111111111111111111111111111111111111");
+    const L1_TX: B256 = b256!("2222222222222222222222222222222222222222222222222222222222222222");
+
+    fn raw_transactions() -> Vec<RawTransaction> {
+        serde_json::from_value(serde_json::json!([
+            {
+                "common_data": { "L2": { "nonce": 1, "input": { "hash": L2_TX, "data": [] } } },
+                "execute": { "contractAddress": Address::ZERO, "factoryDeps": [[1, 2, 3]] }
+            },
+            {
+                "common_data": { "L1": { "serialId": 7, "canonicalTxHash": L1_TX } },
+                "execute": { "contractAddress": Address::ZERO, "factoryDeps": [] }
+            }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_the_factory_deps_of_raw_transactions() {
+        let deps = find_factory_deps(raw_transactions(), 10, L2_TX).unwrap();
+        assert_eq!(deps, Some(vec![vec![1, 2, 3]]));
+        // L1 transactions are matched by their canonical hash, without factory deps
+        assert_eq!(find_factory_deps(raw_transactions(), 10, L1_TX).unwrap(), None);
+
+        let err = find_factory_deps(raw_transactions(), 10, B256::ZERO).unwrap_err();
+        assert!(err.to_string().contains("not found in the raw transactions of block 10"), "{err}");
+    }
+
+    #[test]
+    fn compares_events_in_order() {
+        let event = |data: &'static [u8]| Event {
+            address: address!("0000000000000000000000000000000000001234"),
+            topics: vec![B256::ZERO],
+            data: Bytes::from_static(data),
+        };
+        assert!(event_mismatches(vec![event(b"a"), event(b"b")], vec![event(b"a"), event(b"b")])
+            .is_empty());
+
+        let mismatches = event_mismatches(vec![event(b"a"), event(b"c")], vec![event(b"a")]);
+        assert_eq!(mismatches, [StateMismatch::EventCount { local: 2, remote: 1 }]);
+
+        let mismatches =
+            event_mismatches(vec![event(b"a"), event(b"c")], vec![event(b"a"), event(b"b")]);
+        assert_eq!(
+            mismatches,
+            [StateMismatch::Event { index: 1, local: event(b"c"), remote: event(b"b") }]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "event #1: local { address: 0x0000000000000000000000000000000000001234, topics: \
+             [0x0000000000000000000000000000000000000000000000000000000000000000], data: 0x63 }, \
+             remote { address: 0x0000000000000000000000000000000000001234, topics: \
+             [0x0000000000000000000000000000000000000000000000000000000000000000], data: 0x62 }"
+        );
+    }
+
+    #[test]
+    fn reports_mismatches_as_json() {
+        let mismatch = StateMismatch::Storage {
+            address: Address::ZERO,
+            slot: U256::from(1),
+            local: U256::from(2),
+            remote: U256::from(3),
+        };
+        assert_eq!(
+            mismatch.to_string(),
+            "storage 0x0000000000000000000000000000000000000000 slot 0x1: local 0x2, remote 0x3"
+        );
+        assert_eq!(
+            serde_json::to_value(&mismatch).unwrap(),
+            serde_json::json!({
+                "kind": "storage",
+                "address": Address::ZERO,
+                "slot": "0x1",
+                "local": "0x2",
+                "remote": "0x3"
+            })
+        );
+    }
+}
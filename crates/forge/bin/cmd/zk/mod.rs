@@ -0,0 +1,21 @@
+use clap::Parser;
+use eyre::Result;
+
+mod check_state;
+pub use check_state::CheckStateArgs;
+
+/// zkSync specific subcommands.
+#[derive(Clone, Debug, Parser)]
+pub enum ZkSubcommands {
+    /// Compares the local zkEVM execution of a transaction against the state of a live Era node.
+    #[clap(visible_alias = "cs")]
+    CheckState(CheckStateArgs),
+}
+
+impl ZkSubcommands {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            ZkSubcommands::CheckState(cmd) => cmd.run().await,
+        }
+    }
+}
@@ -23,6 +23,8 @@ fn main() -> Result<()> {
         ForgeSubcommand::Test(cmd) => {
             if cmd.is_watch() {
                 utils::block_on(watch::watch_test(cmd))
+            } else if let Some(addr) = cmd.watch.daemon {
+                utils::block_on(watch::daemon_test(cmd, addr))
             } else {
                 let outcome = utils::block_on(cmd.run())?;
                 outcome.ensure_ok()
@@ -36,6 +38,7 @@ fn main() -> Result<()> {
             ))?;
             utils::block_on(cmd.run_script())
         }
+        ForgeSubcommand::BroadcastQueue(cmd) => cmd.run(),
         ForgeSubcommand::Coverage(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Bind(cmd) => cmd.run(),
         ForgeSubcommand::Build(cmd) => {
@@ -48,11 +51,15 @@ fn main() -> Result<()> {
         ForgeSubcommand::Debug(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::VerifyContract(args) => utils::block_on(args.run()),
         ForgeSubcommand::VerifyCheck(args) => utils::block_on(args.run()),
+        ForgeSubcommand::VerifyBatch(args) => utils::block_on(args.run()),
+        ForgeSubcommand::VerifyAttestation(args) => args.run(),
         ForgeSubcommand::Cache(cmd) => match cmd.sub {
             CacheSubcommands::Clean(cmd) => cmd.run(),
             CacheSubcommands::Ls(cmd) => cmd.run(),
+            CacheSubcommands::Gc(cmd) => cmd.run(),
         },
         ForgeSubcommand::Create(cmd) => utils::block_on(cmd.run()),
+        ForgeSubcommand::Deploy(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Update(cmd) => cmd.run(),
         ForgeSubcommand::Install(cmd) => cmd.run(),
         ForgeSubcommand::Remove(cmd) => cmd.run(),
@@ -83,11 +90,14 @@ fn main() -> Result<()> {
                 utils::block_on(cmd.run())
             }
         }
+        ForgeSubcommand::GasReport(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Fmt(cmd) => cmd.run(),
         ForgeSubcommand::Config(cmd) => cmd.run(),
         ForgeSubcommand::Flatten(cmd) => cmd.run(),
         ForgeSubcommand::Inspect(cmd) => cmd.run(),
         ForgeSubcommand::Tree(cmd) => cmd.run(),
+        ForgeSubcommand::Callgraph(cmd) => utils::block_on(cmd.run()),
+        ForgeSubcommand::TraceDiff(cmd) => cmd.run(),
         ForgeSubcommand::Geiger(cmd) => {
             let check = cmd.check;
             let n = cmd.run()?;
@@ -100,6 +110,10 @@ fn main() -> Result<()> {
         ForgeSubcommand::Selectors { command } => utils::block_on(command.run()),
         ForgeSubcommand::Generate(cmd) => match cmd.sub {
             GenerateSubcommands::Test(cmd) => cmd.run(),
+            GenerateSubcommands::Cheatcodes(cmd) => cmd.run(),
         },
+        ForgeSubcommand::UpgradeSim(cmd) => utils::block_on(cmd.run()),
+        ForgeSubcommand::Whatis(cmd) => utils::block_on(cmd.run()),
+        ForgeSubcommand::Zk { command } => utils::block_on(command.run()),
     }
 }
@@ -1,13 +1,17 @@
 use crate::cmd::{
     bind::BindArgs,
+    broadcast_queue::BroadcastQueueArgs,
     build::BuildArgs,
     cache::CacheArgs,
+    callgraph::CallgraphArgs,
     config, coverage,
     create::CreateArgs,
     debug::DebugArgs,
+    deploy::DeployArgs,
     doc::DocArgs,
     flatten,
     fmt::FmtArgs,
+    gas_report::GasReportArgs,
     geiger, generate,
     init::InitArgs,
     inspect,
@@ -16,8 +20,14 @@ use crate::cmd::{
     remove::RemoveArgs,
     script::ScriptArgs,
     selectors::SelectorsSubcommands,
-    snapshot, test, tree, update,
-    verify::{VerifyArgs, VerifyCheckArgs},
+    snapshot, test,
+    trace_diff::TraceDiffArgs,
+    tree, update,
+    upgrade_sim::UpgradeSimArgs,
+    verify::{VerifyArgs, VerifyBatchArgs, VerifyCheckArgs},
+    verify_attestation::VerifyAttestationArgs,
+    whatis::WhatisArgs,
+    zk::ZkSubcommands,
 };
 use clap::{Parser, Subcommand, ValueHint};
 use std::path::PathBuf;
@@ -54,6 +64,12 @@ pub enum ForgeSubcommand {
     /// Run a smart contract as a script, building transactions that can be sent onchain.
     Script(ScriptArgs),
 
+    /// Inspect the broadcast queues left behind by interrupted `forge script --broadcast` runs.
+    ///
+    /// Shows which transactions were queued, signed, sent or confirmed, and will be recovered
+    /// by `forge script --resume`.
+    BroadcastQueue(BroadcastQueueArgs),
+
     /// Generate coverage reports.
     Coverage(coverage::CoverageArgs),
 
@@ -97,10 +113,27 @@ pub enum ForgeSubcommand {
     #[clap(visible_alias = "vc")]
     VerifyCheck(VerifyCheckArgs),
 
+    /// Verify all the contracts deployed by a script, from its broadcast artifact.
+    #[clap(visible_alias = "vb")]
+    VerifyBatch(VerifyBatchArgs),
+
+    /// Verify that the compiled artifacts match an attestation written with `forge build
+    /// --attest`.
+    ///
+    /// Recompiles the project and compares the bytecode, standard json input and compiler version
+    /// of every artifact, and checks the signature of the attestation.
+    VerifyAttestation(VerifyAttestationArgs),
+
     /// Deploy a smart contract.
     #[clap(visible_alias = "c")]
     Create(CreateArgs),
 
+    /// Deploy the contracts of a deployment plan to its chains.
+    ///
+    /// The deployed contracts are recorded, so running the plan again only deploys the contracts
+    /// that are missing or whose constructor arguments changed.
+    Deploy(DeployArgs),
+
     /// Create a new Forge project.
     Init(InitArgs),
 
@@ -133,6 +166,12 @@ pub enum ForgeSubcommand {
     #[clap(visible_alias = "s")]
     Snapshot(snapshot::SnapshotArgs),
 
+    /// Run the tests and print a gas report, optionally compared against a previous report.
+    ///
+    /// Prints the deployment and median function cost deltas per contract, in gas or in EraVM
+    /// ergs.
+    GasReport(GasReportArgs),
+
     /// Display the current config.
     #[clap(visible_alias = "co")]
     Config(config::ConfigArgs),
@@ -152,6 +191,18 @@ pub enum ForgeSubcommand {
     #[clap(visible_alias = "tr")]
     Tree(tree::TreeArgs),
 
+    /// Generate a call graph of the project from its ASTs and the calls observed in its tests.
+    ///
+    /// Calls crossing from the EVM into EraVM and calls to zkSync system contracts are marked, to
+    /// help scoping Era specific reviews.
+    Callgraph(CallgraphArgs),
+
+    /// Compare the traces of two test runs, as exported with `forge test --export-traces`.
+    ///
+    /// Prints added and removed calls, changed return data and gas deltas. Calls are matched by
+    /// contract and function, so runs on the EVM and the zkEVM can be compared.
+    TraceDiff(TraceDiffArgs),
+
     /// Detects usage of unsafe cheat codes in a project and its dependencies.
     Geiger(geiger::GeigerArgs),
 
@@ -167,6 +218,21 @@ pub enum ForgeSubcommand {
 
     /// Generate scaffold files.
     Generate(generate::GenerateArgs),
+
+    /// Simulate the upgrade of a live proxy on a fork and report storage collisions, selector
+    /// changes and failed checks.
+    UpgradeSim(UpgradeSimArgs),
+
+    /// Find the local artifacts matching the code deployed at an address.
+    ///
+    /// On Era chains, the bytecode hash of the contract is matched against the zksolc artifacts.
+    Whatis(WhatisArgs),
+
+    /// zkSync utilities
+    Zk {
+        #[clap(subcommand)]
+        command: ZkSubcommands,
+    },
 }
 
 #[cfg(test)]
@@ -0,0 +1,255 @@
+//! Diagnostics for fuzz tests whose inputs are rejected too often by `vm.assume`.
+
+use alloy_json_abi::Function;
+use alloy_primitives::{Address, Bytes, U256};
+use foundry_compilers::sourcemap::SourceElement;
+use foundry_evm::{fuzz::AssumeRejection, revm::primitives::SpecId, utils::PcIcMap};
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf, sync::Arc};
+
+/// The deployed code of a test contract and its source map, used to find the call sites of
+/// `vm.assume` in the sources of the test.
+#[derive(Clone, Debug)]
+pub struct TestSourceMap {
+    /// The deployed code of the test contract.
+    code: Bytes,
+    /// The source map of the deployed code.
+    source_map: Vec<SourceElement>,
+    /// The absolute paths of the sources, by file id.
+    sources: Arc<BTreeMap<u32, PathBuf>>,
+}
+
+/// The source location of an instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The path of the source file.
+    pub path: PathBuf,
+    /// The 1-based line of the start of the instruction's source.
+    pub line: usize,
+    /// The 1-based column of the start of the instruction's source.
+    pub column: usize,
+    /// The source code of the instruction.
+    pub code: String,
+}
+
+impl TestSourceMap {
+    pub fn new(
+        code: Bytes,
+        source_map: Vec<SourceElement>,
+        sources: Arc<BTreeMap<u32, PathBuf>>,
+    ) -> Self {
+        Self { code, source_map, sources }
+    }
+
+    /// Returns the source location of the instruction at `pc` in the deployed code, if it is part
+    /// of the test's sources.
+    pub fn location(&self, pc: usize) -> Option<SourceLocation> {
+        let ic = PcIcMap::new(SpecId::LATEST, &self.code).get(pc)?;
+        let element = self.source_map.get(ic)?;
+        let path = self.sources.get(&element.index?)?;
+        let source = std::fs::read_to_string(path).ok()?;
+        let code = source.get(element.offset..element.offset + element.length)?;
+        let before = &source[..element.offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+        Some(SourceLocation { path: path.clone(), line, column, code: code.to_string() })
+    }
+}
+
+/// Returns a breakdown of the inputs rejected by `vm.assume` by call site, with the `bound()`
+/// calls that can replace the simple range checks of `func`'s parameters.
+///
+/// `address` is the address of the test contract, call sites of other contracts are reported by
+/// their program counter, or by their contract only if they're executed in the ZK-VM.
+pub fn rejections_report(
+    rejections: &[AssumeRejection],
+    func: &Function,
+    address: Address,
+    source_map: Option<&TestSourceMap>,
+) -> String {
+    let mut report = String::from("rejected inputs by `vm.assume` call site:");
+    for rejection in rejections {
+        let location = source_map
+            .filter(|_| rejection.address == address)
+            .zip(rejection.pc)
+            .and_then(|(source_map, pc)| source_map.location(pc));
+        let _ = write!(report, "\n  {} at ", rejection.count);
+        let Some(location) = location else {
+            match rejection.pc {
+                Some(pc) => {
+                    let _ = write!(report, "pc {pc} of {}", rejection.address);
+                }
+                None => {
+                    let _ = write!(report, "a ZK-VM call site of {}", rejection.address);
+                }
+            }
+            continue
+        };
+        let _ = write!(
+            report,
+            "{}:{}:{} `{}`",
+            location.path.display(),
+            location.line,
+            location.column,
+            location.code
+        );
+        if let Some(suggestion) = bound_suggestion(&location.code, func) {
+            let _ = write!(report, ", consider `{suggestion}`");
+        }
+    }
+    report
+}
+
+/// Returns the `bound()` calls equivalent to the condition of a `vm.assume(...)` call, if it only
+/// consists of comparisons of unsigned integer parameters of `func` with literals.
+///
+/// For example `vm.assume(x > 10 && x <= 100)` is equivalent to `x = bound(x, 11, 100);`.
+pub fn bound_suggestion(assume: &str, func: &Function) -> Option<String> {
+    let condition = assume_condition(assume)?;
+
+    // the inclusive range of each parameter, by name
+    let mut ranges: BTreeMap<&str, (U256, U256, &str)> = BTreeMap::new();
+    for clause in condition.split("&&") {
+        let (param, op, value) = parse_comparison(clause.trim())?;
+        let ty = func.inputs.iter().find(|input| input.name == param)?.ty.as_str();
+        let max = uint_max(ty)?;
+        let range = ranges.entry(param).or_insert((U256::ZERO, max, ty));
+        match op {
+            ">" => range.0 = range.0.max(value.checked_add(U256::from(1))?),
+            ">=" => range.0 = range.0.max(value),
+            "<" => range.1 = range.1.min(value.checked_sub(U256::from(1))?),
+            "<=" => range.1 = range.1.min(value),
+            "!=" if value.is_zero() => range.0 = range.0.max(U256::from(1)),
+            _ => return None,
+        }
+    }
+
+    let mut suggestion = Vec::with_capacity(ranges.len());
+    for (param, (low, high, ty)) in ranges {
+        if low > high {
+            return None
+        }
+        let high = if high == uint_max(ty)? { format!("type({ty}).max") } else { high.to_string() };
+        suggestion.push(format!("{param} = bound({param}, {low}, {high});"));
+    }
+    Some(suggestion.join(" "))
+}
+
+/// Returns the condition of a `vm.assume(...)` call.
+fn assume_condition(assume: &str) -> Option<&str> {
+    let start = assume.find("assume(")? + "assume(".len();
+    let end = assume.rfind(')')?;
+    assume.get(start..end).map(str::trim).filter(|condition| !condition.is_empty())
+}
+
+/// Parses a comparison of a parameter with a decimal literal, in either order, and returns it as
+/// `(param, op, literal)` with the parameter on the left.
+fn parse_comparison(clause: &str) -> Option<(&str, &'static str, U256)> {
+    let clause = clause.strip_prefix('(').and_then(|c| c.strip_suffix(')')).unwrap_or(clause);
+    // two character operators first, so `>=` isn't parsed as `>`
+    for (op, flipped) in [(">=", "<="), ("<=", ">="), ("!=", "!="), (">", "<"), ("<", ">")] {
+        let Some((left, right)) = clause.split_once(op) else { continue };
+        let (left, right) = (left.trim(), right.trim());
+        if let Some(value) = parse_literal(right) {
+            return is_identifier(left).then_some((left, op, value))
+        }
+        if let Some(value) = parse_literal(left) {
+            return is_identifier(right).then_some((right, flipped, value))
+        }
+        return None
+    }
+    None
+}
+
+/// Parses a decimal integer literal, which may contain underscores.
+fn parse_literal(literal: &str) -> Option<U256> {
+    if !literal.starts_with(|c: char| c.is_ascii_digit()) {
+        return None
+    }
+    U256::from_str_radix(&literal.replace('_', ""), 10).ok()
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') &&
+        s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Returns the maximum value of an unsigned integer type.
+fn uint_max(ty: &str) -> Option<U256> {
+    let bits: usize = match ty.strip_prefix("uint")? {
+        "" => 256,
+        bits => bits.parse().ok().filter(|bits| (8..=256).contains(bits) && bits % 8 == 0)?,
+    };
+    Some(U256::MAX >> (256 - bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_json_abi::Param;
+
+    fn func(inputs: &[(&str, &str)]) -> Function {
+        Function {
+            name: "testFuzz".to_string(),
+            inputs: inputs
+                .iter()
+                .map(|(ty, name)| Param {
+                    ty: ty.to_string(),
+                    name: name.to_string(),
+                    components: vec![],
+                    internal_type: None,
+                })
+                .collect(),
+            outputs: vec![],
+            state_mutability: Default::default(),
+        }
+    }
+
+    #[test]
+    fn suggests_bounds() {
+        let f = func(&[("uint256", "x"), ("uint8", "y"), ("int256", "z"), ("address", "a")]);
+
+        assert_eq!(
+            bound_suggestion("vm.assume(x > 10)", &f).as_deref(),
+            Some("x = bound(x, 11, type(uint256).max);")
+        );
+        assert_eq!(
+            bound_suggestion("vm.assume(x >= 1 && x < 1_000)", &f).as_deref(),
+            Some("x = bound(x, 1, 999);")
+        );
+        assert_eq!(
+            bound_suggestion("vm.assume(100 >= y && x != 0)", &f).as_deref(),
+            Some("x = bound(x, 1, type(uint256).max); y = bound(y, 0, 100);")
+        );
+        assert_eq!(
+            bound_suggestion("vm.assume((y < 255))", &f).as_deref(),
+            Some("y = bound(y, 0, 254);")
+        );
+
+        // not a simple range check of an unsigned integer parameter
+        assert_eq!(bound_suggestion("vm.assume(x != 5)", &f), None);
+        assert_eq!(bound_suggestion("vm.assume(x > 1 || x < 10)", &f), None);
+        assert_eq!(bound_suggestion("vm.assume(z > 0)", &f), None);
+        assert_eq!(bound_suggestion("vm.assume(a != address(0))", &f), None);
+        assert_eq!(bound_suggestion("vm.assume(w > 0)", &f), None);
+        assert_eq!(bound_suggestion("vm.assume(x < 0)", &f), None);
+        assert_eq!(bound_suggestion("vm.assume(x > 10 && x < 5)", &f), None);
+    }
+
+    #[test]
+    fn reports_call_sites_without_source() {
+        let f = func(&[("uint256", "x")]);
+        let rejections = AssumeRejection::from_counts([
+            ((Address::repeat_byte(1), Some(42)), 3),
+            ((Address::repeat_byte(2), None), 5),
+        ]);
+        assert_eq!(
+            rejections_report(&rejections, &f, Address::ZERO, None),
+            format!(
+                "rejected inputs by `vm.assume` call site:\n  5 at a ZK-VM call site of {}\n  3 at \
+                 pc 42 of {}",
+                Address::repeat_byte(2),
+                Address::repeat_byte(1)
+            )
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Signed attestations of the compiled artifacts of a project.
+//!
+//! An attestation records the hashes of the bytecodes of all artifacts, of the standard json input
+//! of their sources and their compiler versions, signed with a project key. Deployment pipelines
+//! can verify that the artifacts they broadcast are the ones that were built and attested.
+
+use alloy_primitives::{keccak256, Address, Bytes, B256};
+use ethers_core::{types::Signature, utils::hash_message};
+use ethers_signers::{LocalWallet, Signer};
+use eyre::{Context, Result};
+use foundry_common::{fs, types::ToAlloy};
+use foundry_compilers::{
+    artifacts::{BytecodeObject, CompactBytecode, CompactDeployedBytecode},
+    Project, ProjectCompileOutput,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    path::{Path, PathBuf},
+};
+
+/// The version of the attestation format.
+pub const ATTESTATION_VERSION: &str = "1";
+
+/// The hashes of a compiled contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactDigest {
+    /// The source file of the contract, relative to the project root.
+    pub source: PathBuf,
+    /// The version of solc the contract was compiled with.
+    pub compiler_version: String,
+    /// The keccak256 hash of the creation bytecode, or of its hex encoding if it isn't linked.
+    pub bytecode_hash: B256,
+    /// The keccak256 hash of the deployed bytecode, or of its hex encoding if it isn't linked.
+    pub deployed_bytecode_hash: B256,
+    /// The keccak256 hash of the standard json input of the source.
+    pub input_hash: B256,
+}
+
+/// The attested contents of a set of artifacts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestedArtifacts {
+    /// The version of the attestation format.
+    pub version: String,
+    /// The version of zksolc, if the artifacts were compiled for EraVM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zksolc_version: Option<String>,
+    /// The digests of the contracts with bytecode, by `<source>:<name>`.
+    pub artifacts: BTreeMap<String, ArtifactDigest>,
+}
+
+impl AttestedArtifacts {
+    /// Collects the digests of the artifacts of `output` that have bytecode.
+    pub fn new(
+        project: &Project,
+        output: &ProjectCompileOutput,
+        zksolc_version: Option<String>,
+    ) -> Result<Self> {
+        let mut input_hashes = BTreeMap::new();
+        let mut artifacts = BTreeMap::new();
+        for (id, artifact) in output.artifact_ids() {
+            let (Some(bytecode), Some(deployed_bytecode)) =
+                (&artifact.bytecode, &artifact.deployed_bytecode)
+            else {
+                continue
+            };
+            let (Some(bytecode_hash), Some(deployed_bytecode_hash)) =
+                (creation_code_hash(bytecode), deployed_code_hash(deployed_bytecode))
+            else {
+                continue
+            };
+
+            let input_hash = match input_hashes.entry(id.source.clone()) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    let input = project.standard_json_input(&id.source).wrap_err_with(|| {
+                        format!("failed to get the standard json input of {}", id.source.display())
+                    })?;
+                    *entry.insert(keccak256(serde_json::to_vec(&input)?))
+                }
+            };
+
+            let source = id.source.strip_prefix(project.root()).unwrap_or(&id.source);
+            let digest = ArtifactDigest {
+                source: source.to_path_buf(),
+                compiler_version: id.version.to_string(),
+                bytecode_hash,
+                deployed_bytecode_hash,
+                input_hash,
+            };
+            // the same contract compiled with different solc versions
+            let key = format!("{}:{}", source.display(), id.name);
+            let key =
+                if artifacts.contains_key(&key) { format!("{key}@{}", id.version) } else { key };
+            artifacts.insert(key, digest);
+        }
+        Ok(Self { version: ATTESTATION_VERSION.to_string(), zksolc_version, artifacts })
+    }
+
+    /// Returns the hash that is signed, the keccak256 of the JSON encoding.
+    pub fn digest(&self) -> B256 {
+        keccak256(serde_json::to_vec(self).expect("failed to serialize artifacts"))
+    }
+
+    /// Signs the digest of the artifacts as an EIP-191 personal message.
+    pub fn sign(self, key: &LocalWallet) -> Result<Attestation> {
+        let digest = self.digest();
+        let signature = key.sign_hash(hash_message(digest))?;
+        Ok(Attestation {
+            artifacts: self,
+            digest,
+            signer: key.address().to_alloy(),
+            signature: signature.to_vec().into(),
+        })
+    }
+
+    /// Returns the differences of `other` to these artifacts, as one line per artifact.
+    pub fn mismatches(&self, other: &Self) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if self.zksolc_version != other.zksolc_version {
+            mismatches.push(format!(
+                "zksolc version: attested {:?}, got {:?}",
+                self.zksolc_version, other.zksolc_version
+            ));
+        }
+        for (name, attested) in &self.artifacts {
+            match other.artifacts.get(name) {
+                None => mismatches.push(format!("{name}: missing")),
+                Some(digest) if digest != attested => {
+                    let mut fields = Vec::new();
+                    if digest.compiler_version != attested.compiler_version {
+                        fields.push("compiler version");
+                    }
+                    if digest.bytecode_hash != attested.bytecode_hash {
+                        fields.push("bytecode");
+                    }
+                    if digest.deployed_bytecode_hash != attested.deployed_bytecode_hash {
+                        fields.push("deployed bytecode");
+                    }
+                    if digest.input_hash != attested.input_hash {
+                        fields.push("standard json input");
+                    }
+                    if digest.source != attested.source {
+                        fields.push("source");
+                    }
+                    mismatches.push(format!("{name}: different {}", fields.join(", ")));
+                }
+                Some(_) => {}
+            }
+        }
+        for name in other.artifacts.keys().filter(|name| !self.artifacts.contains_key(*name)) {
+            mismatches.push(format!("{name}: not attested"));
+        }
+        mismatches
+    }
+}
+
+/// A signed attestation of a set of artifacts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attestation {
+    /// The attested artifacts.
+    #[serde(flatten)]
+    pub artifacts: AttestedArtifacts,
+    /// The digest of the artifacts.
+    pub digest: B256,
+    /// The address of the key that signed the digest.
+    pub signer: Address,
+    /// The signature of the digest as an EIP-191 personal message.
+    pub signature: Bytes,
+}
+
+impl Attestation {
+    /// Reads an attestation file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let attestation: Self = fs::read_json_file(path)?;
+        if attestation.artifacts.version != ATTESTATION_VERSION {
+            eyre::bail!(
+                "unsupported attestation {}: expected version {ATTESTATION_VERSION}, got {}",
+                path.display(),
+                attestation.artifacts.version
+            )
+        }
+        Ok(attestation)
+    }
+
+    /// Writes the attestation to a file, overwriting it if it exists.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write_json_file(path, self)?;
+        Ok(())
+    }
+
+    /// Verifies that the attestation is signed by `expected`.
+    ///
+    /// The signer recorded in the attestation is only informational: anyone can sign an
+    /// attestation, so it must be checked against the key of the project.
+    ///
+    /// This doesn't compare the artifacts, see [`AttestedArtifacts::mismatches`].
+    pub fn verify_signature(&self, expected: Address) -> Result<()> {
+        if self.artifacts.digest() != self.digest {
+            eyre::bail!("the digest doesn't match the attested artifacts")
+        }
+        let signature = Signature::try_from(self.signature.as_ref())
+            .wrap_err("invalid attestation signature")?;
+        let signer = signature
+            .recover(self.digest.as_slice())
+            .wrap_err("invalid attestation signature")?
+            .to_alloy();
+        if signer != self.signer {
+            eyre::bail!("the attestation is signed by {signer}, not by {}", self.signer)
+        }
+        if signer != expected {
+            eyre::bail!("the attestation is signed by {signer}, expected {expected}")
+        }
+        Ok(())
+    }
+}
+
+fn object_hash(object: &BytecodeObject) -> Option<B256> {
+    match object {
+        BytecodeObject::Bytecode(code) if code.is_empty() => None,
+        BytecodeObject::Bytecode(code) => Some(keccak256(code)),
+        BytecodeObject::Unlinked(code) => Some(keccak256(code.as_bytes())),
+    }
+}
+
+fn creation_code_hash(bytecode: &CompactBytecode) -> Option<B256> {
+    object_hash(&bytecode.object)
+}
+
+fn deployed_code_hash(bytecode: &CompactDeployedBytecode) -> Option<B256> {
+    bytecode.bytecode.as_ref().and_then(|bytecode| object_hash(&bytecode.object))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifacts() -> AttestedArtifacts {
+        let digest = ArtifactDigest {
+            source: "src/Counter.sol".into(),
+            compiler_version: "0.8.23".to_string(),
+            bytecode_hash: B256::repeat_byte(1),
+            deployed_bytecode_hash: B256::repeat_byte(2),
+            input_hash: B256::repeat_byte(3),
+        };
+        AttestedArtifacts {
+            version: ATTESTATION_VERSION.to_string(),
+            zksolc_version: None,
+            artifacts: BTreeMap::from([("src/Counter.sol:Counter".to_string(), digest)]),
+        }
+    }
+
+    #[test]
+    fn signs_and_verifies_attestations() {
+        let key: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let attestation = artifacts().sign(&key).unwrap();
+        let signer = key.address().to_alloy();
+        attestation.verify_signature(signer).unwrap();
+        assert!(attestation.verify_signature(Address::ZERO).is_err());
+
+        // an attestation signed by another key which claims to be signed by the project key
+        let other: LocalWallet =
+            "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a7d8374a8b1ae3e9e4b".parse().unwrap();
+        let mut forged = artifacts().sign(&other).unwrap();
+        forged.signer = signer;
+        assert!(forged.verify_signature(signer).is_err());
+
+        let mut tampered = attestation.clone();
+        tampered.artifacts.artifacts.values_mut().for_each(|digest| {
+            digest.deployed_bytecode_hash = B256::repeat_byte(4);
+        });
+        assert!(tampered.verify_signature(signer).is_err());
+
+        assert_eq!(
+            attestation.artifacts.mismatches(&tampered.artifacts),
+            ["src/Counter.sol:Counter: different deployed bytecode"]
+        );
+        assert!(attestation.artifacts.mismatches(&artifacts()).is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+//! Machine-readable progress events emitted while running tests.
+
+use crate::result::{SuiteResult, TestResult, TestStatus};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A single event of a test run.
+///
+/// Events are serialized as JSON objects tagged with their `event` name.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TestEvent<'a> {
+    /// A compiler (`solc` or `zksolc`) started compiling the project.
+    CompileStarted { compiler: &'a str },
+    /// A compiler finished compiling the project.
+    CompileFinished { compiler: &'a str, duration_ms: u128 },
+    /// Execution of a test suite started.
+    SuiteStarted { suite: &'a str },
+    /// A single test finished.
+    TestFinished {
+        suite: &'a str,
+        test: &'a str,
+        status: TestStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'a str>,
+        gas: u64,
+        duration_ms: u128,
+    },
+    /// Execution of a test suite finished.
+    SuiteFinished {
+        suite: &'a str,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        duration_ms: u128,
+    },
+    /// All test suites finished.
+    RunFinished { passed: usize, failed: usize, skipped: usize, duration_ms: u128 },
+}
+
+impl<'a> TestEvent<'a> {
+    /// Creates a [`TestEvent::TestFinished`] event from a test result.
+    pub fn test_finished(suite: &'a str, test: &'a str, result: &'a TestResult) -> Self {
+        Self::TestFinished {
+            suite,
+            test,
+            status: result.status,
+            reason: result.reason.as_deref(),
+            gas: result.kind.report().gas(),
+            duration_ms: result.duration.as_millis(),
+        }
+    }
+
+    /// Creates a [`TestEvent::SuiteFinished`] event from a suite result.
+    pub fn suite_finished(suite: &'a str, result: &SuiteResult) -> Self {
+        Self::SuiteFinished {
+            suite,
+            passed: result.passed(),
+            failed: result.failed(),
+            skipped: result.skipped(),
+            duration_ms: result.duration.as_millis(),
+        }
+    }
+}
+
+/// Writes [`TestEvent`]s as newline-delimited JSON.
+///
+/// Every event is written on its own line and flushed immediately, so consumers can follow the
+/// progress of a run while it's executing.
+pub struct EventStream {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl fmt::Debug for EventStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventStream").finish_non_exhaustive()
+    }
+}
+
+impl EventStream {
+    /// Creates a new event stream writing to the given writer.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self { writer: Mutex::new(Box::new(writer)) }
+    }
+
+    /// Creates a new event stream writing to the file at `path`, or to stderr if no path is given.
+    ///
+    /// On unix, an already opened file descriptor can be used by passing `/dev/fd/<N>`.
+    pub fn create(path: Option<&Path>) -> io::Result<Self> {
+        match path {
+            Some(path) => Ok(Self::new(BufWriter::new(File::create(path)?))),
+            None => Ok(Self::new(io::stderr())),
+        }
+    }
+
+    /// Writes the event to the stream.
+    ///
+    /// Failing to write an event is not fatal to the test run, so errors are only logged.
+    pub fn emit(&self, event: TestEvent<'_>) {
+        if let Err(err) = self.try_emit(&event) {
+            warn!(target: "forge::events", ?event, %err, "failed to write event");
+        }
+    }
+
+    fn try_emit(&self, event: &TestEvent<'_>) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Envelope<'a> {
+            timestamp_ms: u128,
+            #[serde(flatten)]
+            event: &'a TestEvent<'a>,
+        }
+
+        let timestamp_ms =
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_millis();
+        let mut line = serde_json::to_vec(&Envelope { timestamp_ms, event })?;
+        line.push(b'\n');
+
+        let mut writer = self.writer.lock();
+        writer.write_all(&line)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn emits_ndjson() {
+        let buffer = Buffer::default();
+        let stream = EventStream::new(buffer.clone());
+        stream.emit(TestEvent::SuiteStarted { suite: "test/Counter.t.sol:CounterTest" });
+        stream.emit(TestEvent::test_finished(
+            "test/Counter.t.sol:CounterTest",
+            "testIncrement()",
+            &TestResult { status: TestStatus::Success, ..Default::default() },
+        ));
+
+        let output = String::from_utf8(buffer.0.lock().clone()).unwrap();
+        let events = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], "suite_started");
+        assert_eq!(events[0]["suite"], "test/Counter.t.sol:CounterTest");
+        assert!(events[0]["timestamp_ms"].is_u64());
+        assert_eq!(events[1]["event"], "test_finished");
+        assert_eq!(events[1]["test"], "testIncrement()");
+        assert_eq!(events[1]["status"], "Success");
+        assert!(events[1].get("reason").is_none());
+    }
+}
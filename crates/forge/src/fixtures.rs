@@ -0,0 +1,244 @@
+//! Fixture suites, whose state is shared with the test suites depending on them.
+//!
+//! A test suite declares the fixtures it depends on in the NatSpec of its contract, e.g.
+//! `/// @custom:depends-on ProtocolFixture`. Every fixture is deployed and set up once per run, on
+//! the state of the fixtures it depends on itself, and the suites depending on it run on a copy of
+//! the state after its `setUp`, instead of setting it up again.
+
+use alloy_primitives::Address;
+use eyre::Result;
+use foundry_compilers::{
+    artifacts::{ast::NodeType, Node},
+    ProjectCompileOutput,
+};
+use foundry_evm::{backend::Backend, revm::primitives::Env};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// The NatSpec tag of a test contract declaring the fixtures it depends on.
+pub const DEPENDS_ON_TAG: &str = "@custom:depends-on";
+
+/// The fixtures the test suites depend on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SuiteDependencies {
+    /// The identifiers of the fixtures of the suites with dependencies, in declaration order.
+    pub fixtures: BTreeMap<String, Vec<String>>,
+}
+
+impl SuiteDependencies {
+    /// Reads the dependencies declared by the contracts of `output`, by identifier relative to
+    /// `root`.
+    ///
+    /// Fails if a fixture doesn't exist or its name is ambiguous, or if suites depend on each
+    /// other.
+    pub fn parse(output: &ProjectCompileOutput, root: &Path) -> Result<Self> {
+        let mut identifiers = BTreeSet::new();
+        let mut declared = BTreeMap::new();
+        for (id, artifact) in output.artifact_ids() {
+            let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+            let identifier = format!("{}:{}", source.display(), id.name);
+            let docs = artifact.ast.as_ref().and_then(|ast| contract_docs(&ast.nodes, &id.name));
+            let fixtures = docs.as_deref().map(declared_fixtures).unwrap_or_default();
+            if !fixtures.is_empty() {
+                declared.insert(identifier.clone(), fixtures);
+            }
+            identifiers.insert(identifier);
+        }
+
+        let mut fixtures = BTreeMap::new();
+        for (suite, names) in declared {
+            let resolved = names
+                .iter()
+                .map(|name| resolve(&identifiers, &suite, name))
+                .collect::<Result<Vec<_>>>()?;
+            fixtures.insert(suite, resolved);
+        }
+        let dependencies = Self { fixtures };
+        dependencies.check_cycles()?;
+        Ok(dependencies)
+    }
+
+    /// Returns the fixtures the suite `suite` directly depends on.
+    pub fn of(&self, suite: &str) -> &[String] {
+        self.fixtures.get(suite).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns whether a suite depends on the suite `suite`.
+    pub fn is_fixture(&self, suite: &str) -> bool {
+        self.fixtures.values().flatten().any(|fixture| fixture == suite)
+    }
+
+    /// Returns the fixtures to set up for the suite `suite`, each after the fixtures it depends on.
+    pub fn chain(&self, suite: &str) -> Vec<String> {
+        fn visit(dependencies: &SuiteDependencies, suite: &str, chain: &mut Vec<String>) {
+            for fixture in dependencies.of(suite) {
+                if !chain.contains(fixture) {
+                    visit(dependencies, fixture, chain);
+                    chain.push(fixture.clone());
+                }
+            }
+        }
+
+        let mut chain = Vec::new();
+        visit(self, suite, &mut chain);
+        chain
+    }
+
+    /// Fails if suites depend on each other, directly or through other fixtures.
+    fn check_cycles(&self) -> Result<()> {
+        fn visit<'a>(
+            dependencies: &'a SuiteDependencies,
+            suite: &'a str,
+            path: &mut Vec<&'a str>,
+            done: &mut BTreeSet<&'a str>,
+        ) -> Result<()> {
+            if let Some(start) = path.iter().position(|visited| *visited == suite) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(suite);
+                eyre::bail!("test suites depend on each other: {}", cycle.join(" -> "));
+            }
+            if done.contains(suite) {
+                return Ok(())
+            }
+            path.push(suite);
+            for fixture in dependencies.of(suite) {
+                visit(dependencies, fixture, path, done)?;
+            }
+            path.pop();
+            done.insert(suite);
+            Ok(())
+        }
+
+        let mut done = BTreeSet::new();
+        for suite in self.fixtures.keys() {
+            visit(self, suite, &mut Vec::new(), &mut done)?;
+        }
+        Ok(())
+    }
+}
+
+/// The state of a chain of fixtures after their `setUp`, which the suites depending on them run
+/// on.
+#[derive(Clone, Debug)]
+pub struct FixtureState {
+    /// The backend holding the state of the fixtures.
+    pub backend: Backend,
+    /// The environment after the `setUp` of the fixtures, which can change the block.
+    pub env: Env,
+    /// The addresses of the fixture contracts, by contract name and identifier.
+    pub addresses: BTreeMap<String, Address>,
+    /// The labels of the addresses set up by the fixtures.
+    pub labels: HashMap<Address, String>,
+    /// The paths read by the cheatcodes of the fixtures, if recorded.
+    pub read_paths: BTreeSet<PathBuf>,
+}
+
+/// Returns the NatSpec of the contract `name` among the top level `nodes` of a source.
+fn contract_docs(nodes: &[Node], name: &str) -> Option<String> {
+    let node = nodes.iter().find(|node| {
+        matches!(node.node_type, NodeType::ContractDefinition) &&
+            node.other.get("name").and_then(Value::as_str) == Some(name)
+    })?;
+    match node.other.get("documentation")? {
+        // solc >= 0.6.3 documents contracts with a `StructuredDocumentation` node
+        Value::Object(docs) => docs.get("text").and_then(Value::as_str).map(str::to_string),
+        Value::String(docs) => Some(docs.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the names of the fixtures declared with [DEPENDS_ON_TAG] in the NatSpec `docs`.
+fn declared_fixtures(docs: &str) -> Vec<String> {
+    docs.lines()
+        .filter_map(|line| line.split_once(DEPENDS_ON_TAG).map(|(_, names)| names))
+        .flat_map(|names| names.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Resolves the fixture `name` of the suite `suite`, a contract name or identifier.
+fn resolve(identifiers: &BTreeSet<String>, suite: &str, name: &str) -> Result<String> {
+    let matching = identifiers
+        .iter()
+        .filter(|id| {
+            if name.contains(':') {
+                *id == name
+            } else {
+                id.rsplit_once(':').map(|(_, contract)| contract) == Some(name)
+            }
+        })
+        .collect::<Vec<_>>();
+    match matching[..] {
+        [id] => Ok(id.clone()),
+        [] => eyre::bail!("the fixture {name} of {suite} doesn't exist"),
+        _ => eyre::bail!(
+            "the fixture {name} of {suite} is ambiguous, use one of {}",
+            matching.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependencies(fixtures: &[(&str, &[&str])]) -> SuiteDependencies {
+        SuiteDependencies {
+            fixtures: fixtures
+                .iter()
+                .map(|(suite, fixtures)| {
+                    (suite.to_string(), fixtures.iter().map(|f| f.to_string()).collect())
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_declared_fixtures() {
+        assert_eq!(
+            declared_fixtures(" @notice Uses the protocol\n @custom:depends-on Protocol, Tokens"),
+            vec!["Protocol", "Tokens"]
+        );
+        assert!(declared_fixtures("@notice no fixtures").is_empty());
+
+        let identifiers = BTreeSet::from([
+            "test/A.t.sol:Protocol".to_string(),
+            "test/B.t.sol:Protocol".to_string(),
+            "test/B.t.sol:Tokens".to_string(),
+        ]);
+        assert_eq!(resolve(&identifiers, "S", "Tokens").unwrap(), "test/B.t.sol:Tokens");
+        assert_eq!(
+            resolve(&identifiers, "S", "test/A.t.sol:Protocol").unwrap(),
+            "test/A.t.sol:Protocol"
+        );
+        let err = resolve(&identifiers, "S", "Protocol").unwrap_err().to_string();
+        assert!(err.contains("ambiguous"), "{err}");
+        assert!(resolve(&identifiers, "S", "Missing").is_err());
+    }
+
+    #[test]
+    fn orders_fixtures() {
+        let dependencies =
+            dependencies(&[("Suite", &["Tokens", "Protocol"]), ("Protocol", &["Tokens"])]);
+        dependencies.check_cycles().unwrap();
+        assert_eq!(dependencies.chain("Suite"), vec!["Tokens", "Protocol"]);
+        assert_eq!(dependencies.chain("Protocol"), vec!["Tokens"]);
+        assert!(dependencies.chain("Tokens").is_empty());
+        assert!(dependencies.is_fixture("Protocol"));
+        assert!(!dependencies.is_fixture("Suite"));
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let dependencies =
+            dependencies(&[("A", &["B"]), ("B", &["C"]), ("C", &["A"]), ("D", &["A"])]);
+        let err = dependencies.check_cycles().unwrap_err().to_string();
+        assert!(err.contains("A -> B -> C -> A"), "{err}");
+
+        assert!(dependencies(&[("A", &["A"])]).check_cycles().is_err());
+    }
+}
@@ -2,17 +2,21 @@
 
 use crate::{
     constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
-    hashbrown::HashSet,
+    hashbrown::{HashMap, HashSet},
     traces::{CallTraceArena, CallTraceDecoder, CallTraceNode, DecodedCallData, TraceKind},
 };
+use alloy_primitives::Address;
 use comfy_table::{presets::ASCII_MARKDOWN, *};
 use foundry_common::{calc, TestFunctionExt};
 use foundry_evm::traces::CallKind;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Write},
+};
 
 /// Represents the gas report for a set of contracts.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct GasReport {
     /// Whether to report any contracts.
     report_any: bool,
@@ -23,6 +27,9 @@ pub struct GasReport {
     /// All contracts that were analyzed grouped by their identifier
     /// ``test/Counter.t.sol:CounterTest
     contracts: BTreeMap<String, ContractInfo>,
+    /// The pubdata of a deployment in EraVM of the contracts, by name.
+    #[serde(skip)]
+    zk_pubdata: HashMap<String, usize>,
 }
 
 impl GasReport {
@@ -36,6 +43,16 @@ impl GasReport {
         Self { report_any, report_for, ignore, ..Default::default() }
     }
 
+    /// Sets the bytes of pubdata of deploying the contracts in EraVM, by contract name, reported
+    /// next to the ergs of their deployments.
+    pub fn with_zk_pubdata(
+        mut self,
+        zk_pubdata: impl IntoIterator<Item = (String, usize)>,
+    ) -> Self {
+        self.zk_pubdata = zk_pubdata.into_iter().collect();
+        self
+    }
+
     /// Whether the given contract should be reported.
     #[instrument(level = "trace", skip(self), ret)]
     fn should_report(&self, contract_name: &str) -> bool {
@@ -59,17 +76,22 @@ impl GasReport {
     }
 
     /// Analyzes the given traces and generates a gas report.
+    ///
+    /// The costs of the calls and deployments in `zk_calls`, by `(caller, callee)`, were executed
+    /// in EraVM and are reported in ergs.
     pub async fn analyze(
         &mut self,
         traces: &[(TraceKind, CallTraceArena)],
         decoder: &CallTraceDecoder,
+        zk_calls: &std::collections::HashSet<(Address, Address)>,
     ) {
         for node in traces.iter().flat_map(|(_, arena)| arena.nodes()) {
-            self.analyze_node(node, decoder).await;
+            let zk = zk_calls.contains(&(node.trace.caller, node.trace.address));
+            self.analyze_node(node, decoder, zk).await;
         }
     }
 
-    async fn analyze_node(&mut self, node: &CallTraceNode, decoder: &CallTraceDecoder) {
+    async fn analyze_node(&mut self, node: &CallTraceNode, decoder: &CallTraceDecoder, zk: bool) {
         let trace = &node.trace;
 
         if trace.address == CHEATCODE_ADDRESS || trace.address == HARDHAT_CONSOLE_ADDRESS {
@@ -97,22 +119,28 @@ impl GasReport {
 
         let contract_info = self.contracts.entry(name.to_string()).or_default();
         if trace.kind.is_any_create() {
-            trace!(contract_name, "adding create gas info");
-            contract_info.gas = trace.gas_used;
+            trace!(contract_name, zk, "adding create gas info");
+            if zk {
+                contract_info.ergs = Some(trace.gas_used);
+                contract_info.pubdata = self.zk_pubdata.get(contract_name).copied();
+            } else {
+                contract_info.gas = trace.gas_used;
+            }
             contract_info.size = trace.data.len();
         } else if let Some(DecodedCallData { signature, .. }) = decoded.func {
             let name = signature.split('(').next().unwrap();
             // ignore any test/setup functions
             let should_include = !(name.is_test() || name.is_invariant_test() || name.is_setup());
             if should_include {
-                trace!(contract_name, signature, "adding gas info");
+                trace!(contract_name, signature, zk, "adding gas info");
                 let gas_info = contract_info
                     .functions
                     .entry(name.to_string())
                     .or_default()
                     .entry(signature.clone())
                     .or_default();
-                gas_info.calls.push(trace.gas_used);
+                let calls = if zk { &mut gas_info.ergs.calls } else { &mut gas_info.calls };
+                calls.push(trace.gas_used);
             }
         }
     }
@@ -124,11 +152,9 @@ impl GasReport {
         for contract in self.contracts.values_mut() {
             for sigs in contract.functions.values_mut() {
                 for func in sigs.values_mut() {
-                    func.calls.sort_unstable();
-                    func.min = func.calls.first().copied().unwrap_or_default();
-                    func.max = func.calls.last().copied().unwrap_or_default();
-                    func.mean = calc::mean(&func.calls);
-                    func.median = calc::median_sorted(&func.calls);
+                    (func.min, func.mean, func.median, func.max) = stats(&mut func.calls);
+                    let ergs = &mut func.ergs;
+                    (ergs.min, ergs.mean, ergs.median, ergs.max) = stats(&mut ergs.calls);
                 }
             }
         }
@@ -136,69 +162,482 @@ impl GasReport {
     }
 }
 
-impl Display for GasReport {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        for (name, contract) in &self.contracts {
+impl GasReport {
+    /// Returns the gas report as markdown tables, without colors.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for (name, contract) in self.reported_contracts() {
+            let mut table = contract.table(name);
+            table.force_no_tty();
+            let _ = writeln!(out, "{table}\n");
+        }
+        out
+    }
+
+    /// Returns the gas report as CSV, with one row per function.
+    ///
+    /// The ergs and pubdata columns are empty for the contracts and functions without EraVM
+    /// executions.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "contract,deployment_cost,deployment_size,deployment_ergs,deployment_pubdata,function,\
+             min,avg,median,max,calls,min_ergs,avg_ergs,median_ergs,max_ergs,ergs_calls\n",
+        );
+        for (name, contract) in self.reported_contracts() {
+            let deployment_ergs = contract.ergs.map(|ergs| ergs.to_string()).unwrap_or_default();
+            let deployment_pubdata =
+                contract.pubdata.map(|pubdata| pubdata.to_string()).unwrap_or_default();
+            for (sig, gas_info) in contract.functions.values().flat_map(|sigs| sigs.iter()) {
+                let ergs = &gas_info.ergs;
+                let ergs = if ergs.calls.is_empty() {
+                    ",,,,".to_string()
+                } else {
+                    format!(
+                        "{},{},{},{},{}",
+                        ergs.min,
+                        ergs.mean,
+                        ergs.median,
+                        ergs.max,
+                        ergs.calls.len()
+                    )
+                };
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{},{},{}",
+                    csv_field(name),
+                    contract.gas,
+                    contract.size,
+                    deployment_ergs,
+                    deployment_pubdata,
+                    csv_field(sig),
+                    gas_info.min,
+                    gas_info.mean,
+                    gas_info.median,
+                    gas_info.max,
+                    gas_info.calls.len(),
+                    ergs
+                );
+            }
+        }
+        out
+    }
+
+    /// Returns the contracts with at least one reported function.
+    fn reported_contracts(&self) -> impl Iterator<Item = (&String, &ContractInfo)> {
+        self.contracts.iter().filter(|(name, contract)| {
             if contract.functions.is_empty() {
                 trace!(name, "gas report contract without functions");
-                continue
+                return false
             }
+            true
+        })
+    }
+}
 
-            let mut table = Table::new();
-            table.load_preset(ASCII_MARKDOWN);
-            table.set_header([Cell::new(format!("{name} contract"))
-                .add_attribute(Attribute::Bold)
-                .fg(Color::Green)]);
-            table.add_row([
-                Cell::new("Deployment Cost").add_attribute(Attribute::Bold).fg(Color::Cyan),
-                Cell::new("Deployment Size").add_attribute(Attribute::Bold).fg(Color::Cyan),
-            ]);
-            table.add_row([contract.gas.to_string(), contract.size.to_string()]);
-
-            table.add_row([
-                Cell::new("Function Name").add_attribute(Attribute::Bold).fg(Color::Magenta),
-                Cell::new("min").add_attribute(Attribute::Bold).fg(Color::Green),
-                Cell::new("avg").add_attribute(Attribute::Bold).fg(Color::Yellow),
-                Cell::new("median").add_attribute(Attribute::Bold).fg(Color::Yellow),
-                Cell::new("max").add_attribute(Attribute::Bold).fg(Color::Red),
-                Cell::new("# calls").add_attribute(Attribute::Bold),
-            ]);
-            contract.functions.iter().for_each(|(fname, sigs)| {
-                sigs.iter().for_each(|(sig, gas_info)| {
-                    // show function signature if overloaded else name
-                    let fn_display =
-                        if sigs.len() == 1 { fname.clone() } else { sig.replace(':', "") };
-
-                    table.add_row([
-                        Cell::new(fn_display).add_attribute(Attribute::Bold),
-                        Cell::new(gas_info.min.to_string()).fg(Color::Green),
-                        Cell::new(gas_info.mean.to_string()).fg(Color::Yellow),
-                        Cell::new(gas_info.median.to_string()).fg(Color::Yellow),
-                        Cell::new(gas_info.max.to_string()).fg(Color::Red),
-                        Cell::new(gas_info.calls.len().to_string()),
-                    ]);
-                })
-            });
-            writeln!(f, "{table}")?;
+impl Display for GasReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for (name, contract) in self.reported_contracts() {
+            writeln!(f, "{}", contract.table(name))?;
             writeln!(f, "\n")?;
         }
         Ok(())
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ContractInfo {
     pub gas: u64,
     pub size: usize,
+    /// The deployment cost in ergs, if the contract was deployed in EraVM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ergs: Option<u64>,
+    /// The bytes of pubdata of the deployment in EraVM, if the contract was deployed in EraVM
+    /// and its bytecodes are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pubdata: Option<usize>,
     /// Function name -> Function signature -> GasInfo
     pub functions: BTreeMap<String, BTreeMap<String, GasInfo>>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+impl ContractInfo {
+    /// Returns whether the contract was deployed or called in EraVM.
+    pub fn has_ergs(&self) -> bool {
+        self.ergs.is_some() ||
+            self.functions
+                .values()
+                .flat_map(|sigs| sigs.values())
+                .any(|gas_info| !gas_info.ergs.calls.is_empty())
+    }
+
+    /// Returns the median gas, or ergs, of each function called in the EVM, or in EraVM, by
+    /// signature.
+    fn medians(&self, ergs: bool) -> BTreeMap<&str, u64> {
+        self.functions
+            .values()
+            .flat_map(|sigs| sigs.iter())
+            .filter_map(|(sig, gas_info)| {
+                let median = if ergs {
+                    (!gas_info.ergs.calls.is_empty()).then_some(gas_info.ergs.median)
+                } else {
+                    (!gas_info.calls.is_empty()).then_some(gas_info.median)
+                };
+                Some((sig.as_str(), median?))
+            })
+            .collect()
+    }
+
+    /// Returns the table of the contract, with the ergs columns next to the gas ones if the
+    /// contract was deployed or called in EraVM.
+    fn table(&self, name: &str) -> Table {
+        let has_ergs = self.has_ergs();
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header([Cell::new(format!("{name} contract"))
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Green)]);
+
+        let mut header = vec![
+            Cell::new("Deployment Cost").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            Cell::new("Deployment Size").add_attribute(Attribute::Bold).fg(Color::Cyan),
+        ];
+        let mut row = vec![Cell::new(self.gas), Cell::new(self.size)];
+        if has_ergs {
+            header.push(
+                Cell::new("Deployment Cost (ergs)").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            );
+            row.push(Cell::new(self.ergs.map_or_else(|| "-".to_string(), |e| e.to_string())));
+            header.push(
+                Cell::new("Deployment Pubdata (bytes)")
+                    .add_attribute(Attribute::Bold)
+                    .fg(Color::Cyan),
+            );
+            row.push(Cell::new(self.pubdata.map_or_else(|| "-".to_string(), |p| p.to_string())));
+        }
+        table.add_row(header);
+        table.add_row(row);
+
+        let mut header = vec![
+            Cell::new("Function Name").add_attribute(Attribute::Bold).fg(Color::Magenta),
+            Cell::new("min").add_attribute(Attribute::Bold).fg(Color::Green),
+            Cell::new("avg").add_attribute(Attribute::Bold).fg(Color::Yellow),
+            Cell::new("median").add_attribute(Attribute::Bold).fg(Color::Yellow),
+            Cell::new("max").add_attribute(Attribute::Bold).fg(Color::Red),
+            Cell::new("# calls").add_attribute(Attribute::Bold),
+        ];
+        if has_ergs {
+            header.extend([
+                Cell::new("min (ergs)").add_attribute(Attribute::Bold).fg(Color::Green),
+                Cell::new("avg (ergs)").add_attribute(Attribute::Bold).fg(Color::Yellow),
+                Cell::new("median (ergs)").add_attribute(Attribute::Bold).fg(Color::Yellow),
+                Cell::new("max (ergs)").add_attribute(Attribute::Bold).fg(Color::Red),
+                Cell::new("# EraVM calls").add_attribute(Attribute::Bold),
+            ]);
+        }
+        table.add_row(header);
+
+        self.functions.iter().for_each(|(fname, sigs)| {
+            sigs.iter().for_each(|(sig, gas_info)| {
+                // show function signature if overloaded else name
+                let fn_display = if sigs.len() == 1 { fname.clone() } else { sig.replace(':', "") };
+
+                let mut row = vec![Cell::new(fn_display).add_attribute(Attribute::Bold)];
+                row.extend(cost_cells(
+                    gas_info.min,
+                    gas_info.mean,
+                    gas_info.median,
+                    gas_info.max,
+                    &gas_info.calls,
+                ));
+                if has_ergs {
+                    let ergs = &gas_info.ergs;
+                    row.extend(cost_cells(ergs.min, ergs.mean, ergs.median, ergs.max, &ergs.calls));
+                }
+                table.add_row(row);
+            })
+        });
+        table
+    }
+}
+
+/// Returns the cells of the min, mean, median and max costs and the number of `calls`, with
+/// dashes for the costs if there are no calls.
+fn cost_cells(min: u64, mean: u64, median: u64, max: u64, calls: &[u64]) -> [Cell; 5] {
+    let cost = |cost: u64| if calls.is_empty() { "-".to_string() } else { cost.to_string() };
+    [
+        Cell::new(cost(min)).fg(Color::Green),
+        Cell::new(cost(mean)).fg(Color::Yellow),
+        Cell::new(cost(median)).fg(Color::Yellow),
+        Cell::new(cost(max)).fg(Color::Red),
+        Cell::new(calls.len()),
+    ]
+}
+
+/// Sorts the costs of the `calls` and returns their min, mean, median and max.
+fn stats(calls: &mut [u64]) -> (u64, u64, u64, u64) {
+    calls.sort_unstable();
+    let min = calls.first().copied().unwrap_or_default();
+    let max = calls.last().copied().unwrap_or_default();
+    (min, calc::mean(calls), calc::median_sorted(calls), max)
+}
+
+/// The EVM gas costs of a function.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct GasInfo {
     pub calls: Vec<u64>,
     pub min: u64,
     pub mean: u64,
     pub median: u64,
     pub max: u64,
+    /// The costs of the calls executed in EraVM.
+    #[serde(default, skip_serializing_if = "ErgsInfo::is_empty")]
+    pub ergs: ErgsInfo,
+}
+
+/// The EraVM costs of a function, in ergs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ErgsInfo {
+    pub calls: Vec<u64>,
+    pub min: u64,
+    pub mean: u64,
+    pub median: u64,
+    pub max: u64,
+}
+
+impl ErgsInfo {
+    /// Returns whether the function wasn't called in EraVM.
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+}
+
+/// Quotes a CSV field if it contains separators.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The change of the deployment cost or the median cost of a function between two gas reports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasChange {
+    /// The identifier of the contract.
+    pub contract: String,
+    /// The signature of the function, `None` for the deployment cost.
+    pub function: Option<String>,
+    /// The unit of the costs, `gas` or `ergs`.
+    pub unit: &'static str,
+    /// The cost in the old report, `None` if it was added.
+    pub old: Option<u64>,
+    /// The cost in the new report, `None` if it was removed.
+    pub new: Option<u64>,
+}
+
+impl GasChange {
+    /// Returns the relative change in percent, if the cost is in both reports.
+    pub fn percent(&self) -> Option<f64> {
+        let (old, new) = (self.old?, self.new?);
+        if old == 0 {
+            return None
+        }
+        Some((new as f64 - old as f64) / old as f64 * 100.0)
+    }
+
+    /// Returns whether the cost increased by more than `tolerance` percent.
+    pub fn is_regression(&self, tolerance: f64) -> bool {
+        self.percent().is_some_and(|percent| percent > tolerance)
+    }
+}
+
+/// The differences between two gas reports, comparing the deployment costs and the median costs
+/// of the functions of each contract.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GasReportDiff {
+    pub changes: Vec<GasChange>,
+}
+
+impl GasReportDiff {
+    /// Compares `new` against `old`, ignoring changes smaller than `threshold` percent.
+    ///
+    /// Added and removed contracts and functions are always included.
+    pub fn new(old: &GasReport, new: &GasReport, threshold: f64) -> Self {
+        let mut changes = Vec::new();
+        let names = old.contracts.keys().chain(new.contracts.keys()).collect::<BTreeSet<_>>();
+        for name in names {
+            let (old, new) = (old.contracts.get(name), new.contracts.get(name));
+            let mut push = |unit, function: Option<&str>, old: Option<u64>, new: Option<u64>| {
+                let change = GasChange {
+                    contract: name.clone(),
+                    function: function.map(str::to_string),
+                    unit,
+                    old,
+                    new,
+                };
+                let ignored =
+                    old == new || change.percent().is_some_and(|percent| percent.abs() < threshold);
+                if !ignored {
+                    changes.push(change);
+                }
+            };
+
+            // a contract deployed in EraVM has no EVM deployment cost
+            let gas = |contract: &ContractInfo| (contract.ergs.is_none()).then_some(contract.gas);
+            push("gas", None, old.and_then(gas), new.and_then(gas));
+            push("ergs", None, old.and_then(|c| c.ergs), new.and_then(|c| c.ergs));
+
+            for (unit, ergs) in [("gas", false), ("ergs", true)] {
+                let old = old.map(|contract| contract.medians(ergs)).unwrap_or_default();
+                let new = new.map(|contract| contract.medians(ergs)).unwrap_or_default();
+                for sig in old.keys().chain(new.keys()).collect::<BTreeSet<_>>() {
+                    push(unit, Some(sig), old.get(sig).copied(), new.get(sig).copied());
+                }
+            }
+        }
+        Self { changes }
+    }
+
+    /// Returns whether the reports have no differences.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the costs that increased by more than `tolerance` percent.
+    pub fn regressions(&self, tolerance: f64) -> impl Iterator<Item = &GasChange> {
+        self.changes.iter().filter(move |change| change.is_regression(tolerance))
+    }
+}
+
+impl Display for GasReportDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No gas changes")
+        }
+
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(["Contract", "Function", "Unit", "Old", "New", "Change", "%"]);
+        let cost = |cost: Option<u64>| cost.map_or_else(|| "-".to_string(), |c| c.to_string());
+        for change in &self.changes {
+            let delta = match (change.old, change.new) {
+                (Some(old), Some(new)) => {
+                    let delta = new as i128 - old as i128;
+                    let color = if delta > 0 { Color::Red } else { Color::Green };
+                    Cell::new(format!("{delta:+}")).fg(color)
+                }
+                (None, _) => Cell::new("added"),
+                (_, None) => Cell::new("removed"),
+            };
+            let percent = change.percent().map_or_else(String::new, |p| format!("{p:+.2}%"));
+            table.add_row([
+                Cell::new(&change.contract),
+                Cell::new(change.function.as_deref().unwrap_or("(deployment)")),
+                Cell::new(change.unit),
+                Cell::new(cost(change.old)),
+                Cell::new(cost(change.new)),
+                delta,
+                Cell::new(percent),
+            ]);
+        }
+        write!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(gas: u64, functions: &[(&str, u64)]) -> GasReport {
+        let mut contract = ContractInfo { gas, size: 100, ..Default::default() };
+        for (sig, median) in functions {
+            let name = sig.split('(').next().unwrap().to_string();
+            let gas_info = GasInfo { calls: vec![*median], median: *median, ..Default::default() };
+            contract.functions.entry(name).or_default().insert(sig.to_string(), gas_info);
+        }
+        let mut report = GasReport::default();
+        report.contracts.insert("src/Counter.sol:Counter".to_string(), contract);
+        report
+    }
+
+    #[test]
+    fn diffs_reports() {
+        let old = report(1000, &[("increment()", 100), ("reset()", 50), ("set(uint256)", 200)]);
+        let new = report(1000, &[("increment()", 110), ("set(uint256)", 201), ("get()", 20)]);
+
+        let diff = GasReportDiff::new(&old, &new, 1.0);
+        let changes = diff
+            .changes
+            .iter()
+            .map(|change| (change.function.as_deref(), change.old, change.new))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                (Some("get()"), None, Some(20)),
+                (Some("increment()"), Some(100), Some(110)),
+                (Some("reset()"), Some(50), None),
+            ]
+        );
+
+        assert_eq!(diff.changes[1].percent(), Some(10.0));
+        assert_eq!(diff.regressions(5.0).count(), 1);
+        assert_eq!(diff.regressions(10.0).count(), 0);
+        assert!(GasReportDiff::new(&old, &old, 0.0).is_empty());
+    }
+
+    #[test]
+    fn writes_csv() {
+        let report = report(1000, &[("set(uint256,address)", 200)]);
+        assert_eq!(
+            report.to_csv(),
+            "contract,deployment_cost,deployment_size,deployment_ergs,deployment_pubdata,function,\
+             min,avg,median,max,calls,min_ergs,avg_ergs,median_ergs,max_ergs,ergs_calls\n\
+             src/Counter.sol:Counter,1000,100,,,\"set(uint256,address)\",0,0,200,0,1,,,,,\n"
+        );
+    }
+
+    #[test]
+    fn reports_gas_and_ergs() {
+        let mut contract =
+            ContractInfo { size: 100, ergs: Some(5000), pubdata: Some(1234), ..Default::default() };
+        let gas_info = GasInfo {
+            calls: vec![300, 100],
+            ergs: ErgsInfo { calls: vec![2000, 4000, 3000], ..Default::default() },
+            ..Default::default()
+        };
+        contract.functions.entry("set".to_string()).or_default().insert("set()".into(), gas_info);
+        let mut report = GasReport::default();
+        report.contracts.insert("src/Counter.sol:Counter".to_string(), contract);
+        let report = report.finalize();
+
+        let gas_info = &report.contracts["src/Counter.sol:Counter"].functions["set"]["set()"];
+        assert_eq!((gas_info.min, gas_info.median, gas_info.max), (100, 200, 300));
+        let ergs = &gas_info.ergs;
+        assert_eq!((ergs.min, ergs.mean, ergs.median, ergs.max), (2000, 3000, 3000, 4000));
+
+        let table = report.to_markdown();
+        assert!(table.contains("Deployment Cost (ergs)"));
+        assert!(table.contains("Deployment Pubdata (bytes)"));
+        assert!(table.contains("1234"));
+        assert!(table.contains("median (ergs)"));
+        assert!(report
+            .to_csv()
+            .ends_with(",0,100,5000,1234,set(),100,200,200,300,2,2000,3000,3000,4000,3\n"));
+
+        // the diff compares the ergs of the calls in EraVM separately
+        let mut new = report.clone();
+        let contract = new.contracts.get_mut("src/Counter.sol:Counter").unwrap();
+        contract.functions.get_mut("set").unwrap().get_mut("set()").unwrap().ergs.median = 3300;
+        let diff = GasReportDiff::new(&report, &new, 1.0);
+        assert_eq!(
+            diff.changes,
+            [GasChange {
+                contract: "src/Counter.sol:Counter".to_string(),
+                function: Some("set()".to_string()),
+                unit: "ergs",
+                old: Some(3000),
+                new: Some(3300),
+            }]
+        );
+    }
 }
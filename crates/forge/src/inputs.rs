@@ -0,0 +1,205 @@
+//! The inputs test suites consumed during a run.
+//!
+//! A suite consumes the artifacts of the contracts it deploys and the files it reads with
+//! cheatcodes. Watch mode records them after every run, and the next run only executes the suites
+//! whose inputs changed, or that failed.
+
+use crate::{
+    result::SuiteResult,
+    traces::identifier::{LocalTraceIdentifier, TraceIdentifier},
+};
+use alloy_primitives::{keccak256, B256};
+use foundry_common::fs;
+use foundry_compilers::ProjectCompileOutput;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// The inputs of the test suites of the last run, by suite identifier.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestInputs {
+    /// The hash of the config of the run.
+    pub config: B256,
+    /// The inputs of every suite.
+    pub suites: BTreeMap<String, SuiteInputs>,
+}
+
+/// The inputs one test suite consumed during its last run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuiteInputs {
+    /// Whether all tests of the suite passed.
+    pub passed: bool,
+    /// The hashes of the artifacts deployed by the suite, by identifier.
+    pub artifacts: BTreeMap<String, B256>,
+    /// The hashes of the paths read by cheatcodes.
+    pub paths: BTreeMap<PathBuf, B256>,
+}
+
+impl TestInputs {
+    /// Reads the inputs recorded at `path`, which are empty if the file doesn't exist or is
+    /// invalid.
+    pub fn read(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default()
+        }
+        fs::read_json_file(path)
+            .map_err(|err| warn!(%err, "failed to read the recorded test inputs"))
+            .unwrap_or_default()
+    }
+
+    /// Writes the inputs to `path`.
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Returns the suites whose inputs didn't change since they passed, given the current hash of
+    /// the config and of the artifacts.
+    pub fn unchanged_suites(
+        &self,
+        config: B256,
+        artifacts: &BTreeMap<String, B256>,
+    ) -> BTreeSet<String> {
+        if self.config != config {
+            return BTreeSet::new()
+        }
+        self.suites
+            .iter()
+            .filter(|(_, inputs)| inputs.is_unchanged(artifacts))
+            .map(|(suite, _)| suite.clone())
+            .collect()
+    }
+}
+
+impl SuiteInputs {
+    /// Collects the inputs the suite `suite` consumed: its own artifact and the ones of its
+    /// fixtures, the artifacts of the contracts created in its traces, identified with
+    /// `identifier`, and the paths that were read by cheatcodes.
+    pub fn new(
+        suite: &str,
+        result: &SuiteResult,
+        identifier: &mut LocalTraceIdentifier<'_>,
+        artifacts: &BTreeMap<String, B256>,
+    ) -> Self {
+        let created = result
+            .test_results
+            .values()
+            .flat_map(|test| test.traces.iter())
+            .flat_map(|(_, arena)| arena.nodes())
+            .filter(|node| node.trace.kind.is_any_create())
+            .map(|node| (&node.trace.address, Some(&node.trace.output[..])));
+        let consumed = identifier
+            .identify_addresses(created)
+            .into_iter()
+            .filter_map(|identity| identity.contract)
+            .chain(std::iter::once(suite.to_string()))
+            .chain(result.fixtures.iter().cloned())
+            .filter_map(|id| {
+                let hash = *artifacts.get(&id)?;
+                Some((id, hash))
+            })
+            .collect();
+
+        Self {
+            passed: result.failed() == 0,
+            artifacts: consumed,
+            paths: result.read_paths.iter().map(|path| (path.clone(), path_hash(path))).collect(),
+        }
+    }
+
+    /// Returns whether the suite passed and none of its inputs changed.
+    pub fn is_unchanged(&self, artifacts: &BTreeMap<String, B256>) -> bool {
+        self.passed &&
+            !self.artifacts.is_empty() &&
+            self.artifacts.iter().all(|(id, hash)| artifacts.get(id) == Some(hash)) &&
+            self.paths.iter().all(|(path, hash)| path_hash(path) == *hash)
+    }
+}
+
+/// Returns the hashes of the bytecodes of all artifacts of `output`, by identifier relative to
+/// `root`.
+pub fn artifact_hashes(output: &ProjectCompileOutput, root: &Path) -> BTreeMap<String, B256> {
+    output
+        .artifact_ids()
+        .filter_map(|(id, artifact)| {
+            let code = serde_json::to_vec(&(&artifact.bytecode, &artifact.deployed_bytecode));
+            let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+            Some((format!("{}:{}", source.display(), id.name), keccak256(code.ok()?)))
+        })
+        .collect()
+}
+
+/// Returns the hash of the config of a run, independent of the order of its maps.
+pub fn config_hash(config: &impl Serialize) -> B256 {
+    fn sorted(value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries =
+                    map.into_iter().map(|(key, value)| (key, sorted(value))).collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Value::Object(entries.into_iter().collect())
+            }
+            Value::Array(values) => Value::Array(values.into_iter().map(sorted).collect()),
+            value => value,
+        }
+    }
+    serde_json::to_value(config)
+        .and_then(|config| serde_json::to_vec(&sorted(config)))
+        .map(keccak256)
+        .unwrap_or_default()
+}
+
+/// Returns the hash of the contents of a file, or of the entries of a directory, and zero if the
+/// path doesn't exist.
+fn path_hash(path: &Path) -> B256 {
+    if path.is_dir() {
+        let entries = std::fs::read_dir(path).map(|entries| {
+            let mut names = entries
+                .filter_map(|entry| Some(entry.ok()?.file_name().to_string_lossy().into_owned()))
+                .collect::<Vec<_>>();
+            names.sort();
+            names.join("\n")
+        });
+        return entries.map(keccak256).unwrap_or_default()
+    }
+    std::fs::read(path).map(keccak256).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_changed_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("input.json");
+        std::fs::write(&file, "{}").unwrap();
+
+        let artifacts = BTreeMap::from([("src/A.sol:A".to_string(), B256::repeat_byte(1))]);
+        let inputs = SuiteInputs {
+            passed: true,
+            artifacts: artifacts.clone(),
+            paths: BTreeMap::from([(file.clone(), path_hash(&file))]),
+        };
+        let recorded = TestInputs {
+            config: B256::ZERO,
+            suites: BTreeMap::from([("test/A.t.sol:ATest".to_string(), inputs.clone())]),
+        };
+        assert!(inputs.is_unchanged(&artifacts));
+        assert_eq!(recorded.unchanged_suites(B256::ZERO, &artifacts).len(), 1);
+        assert!(recorded.unchanged_suites(B256::repeat_byte(1), &artifacts).is_empty());
+
+        let changed = BTreeMap::from([("src/A.sol:A".to_string(), B256::repeat_byte(2))]);
+        assert!(!inputs.is_unchanged(&changed));
+
+        std::fs::write(&file, "{\"a\": 1}").unwrap();
+        assert!(!inputs.is_unchanged(&artifacts));
+
+        assert!(!SuiteInputs { passed: false, ..Default::default() }.is_unchanged(&artifacts));
+    }
+}
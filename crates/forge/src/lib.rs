@@ -1,30 +1,54 @@
 #[macro_use]
 extern crate tracing;
 
-use alloy_primitives::B256;
-use foundry_compilers::ProjectCompileOutput;
+use alloy_primitives::{B256, U256};
+use foundry_compilers::{ArtifactId, ConfigurableContractArtifact, ProjectCompileOutput};
 use foundry_config::{
     validate_profiles, Config, FuzzConfig, InlineConfig, InlineConfigError, InlineConfigParser,
-    InvariantConfig, NatSpec,
+    InvariantConfig, NatSpec, TestBudgetConfig,
 };
 
+use foundry_evm::fuzz::invariant::CheckpointConfig;
 use proptest::test_runner::{RngAlgorithm, TestRng, TestRunner};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+pub mod assume;
+
+pub mod attestation;
 
 pub mod coverage;
 
+pub mod events;
+
+pub mod fixtures;
+
 pub mod gas_report;
 
+pub mod inputs;
+
+pub mod metrics;
+
+pub mod size_limits;
+
+pub mod suite_code;
+
+pub mod trace_diff;
+
 pub mod link;
 
 mod multi_runner;
-pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder, ProjectCompileDualOutput};
+pub use multi_runner::{
+    DeployableContracts, MultiContractRunner, MultiContractRunnerBuilder, ProjectCompileDualOutput,
+    TestEnvOverrides,
+};
 
 mod runner;
 pub use runner::ContractRunner;
 
 pub mod result;
 
+pub mod script;
+
 // TODO: remove
 pub use foundry_common::traits::TestFilter;
 pub use foundry_evm::*;
@@ -42,6 +66,23 @@ pub struct TestOptions {
     pub inline_fuzz: InlineConfig<FuzzConfig>,
     /// Contains per-test specific "invariant" configurations.
     pub inline_invariant: InlineConfig<InvariantConfig>,
+    /// The base execution time budget of a test. To be used as a fallback in case
+    /// no more specific budgets are found for a given run.
+    pub budget: TestBudgetConfig,
+    /// Contains per-test specific execution time budgets.
+    pub inline_budget: InlineConfig<TestBudgetConfig>,
+    /// The profiles the in-line configurations are validated against.
+    pub profiles: Vec<String>,
+    /// The natspecs declaring in-line configurations, to merge them again when the base
+    /// configurations change.
+    pub natspecs: Vec<NatSpec>,
+    /// The directory the invariant campaigns are checkpointed to.
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Whether the invariant campaigns are resumed from their last checkpoint.
+    pub resume_campaign: bool,
+    /// Whether the unit tests run without tracing, only the failing ones running again with
+    /// tracing, and the traces of the other passing tests are dropped as soon as they finish.
+    pub drop_passing_traces: bool,
 }
 
 impl TestOptions {
@@ -53,37 +94,113 @@ impl TestOptions {
         profiles: Vec<String>,
         base_fuzz: FuzzConfig,
         base_invariant: InvariantConfig,
+        base_budget: TestBudgetConfig,
     ) -> Result<Self, InlineConfigError> {
-        let natspecs: Vec<NatSpec> = NatSpec::parse(output, root);
-        let mut inline_invariant = InlineConfig::<InvariantConfig>::default();
-        let mut inline_fuzz = InlineConfig::<FuzzConfig>::default();
+        let mut options = Self {
+            fuzz: base_fuzz,
+            invariant: base_invariant,
+            budget: base_budget,
+            profiles,
+            ..Default::default()
+        };
+        for natspec in NatSpec::parse(output, root) {
+            options.add_natspec(natspec)?;
+        }
+        Ok(options)
+    }
+
+    /// Sets the number of runs of the fuzz tests. The tests whose in-line configuration sets
+    /// their runs keep them.
+    pub fn set_fuzz_runs(&mut self, runs: u32) -> Result<(), InlineConfigError> {
+        self.fuzz.runs = runs;
+        self.merge_inline()
+    }
 
+    /// Sets the seed of the fuzz tests, a new random one for every run if `None`.
+    pub fn reseed(&mut self, seed: Option<U256>) -> Result<(), InlineConfigError> {
+        self.fuzz.seed = seed;
+        self.merge_inline()
+    }
+
+    /// Replaces the in-line configurations of the contract of an artifact with the ones it
+    /// declares now, e.g. after the test contract is recompiled, without rebuilding the options
+    /// of the other contracts.
+    pub fn reresolve_inline(
+        &mut self,
+        id: &ArtifactId,
+        artifact: &ConfigurableContractArtifact,
+        root: &Path,
+    ) -> Result<(), InlineConfigError> {
+        let contract = NatSpec::contract_id(id, root);
+        let natspecs = NatSpec::parse_artifact(id, artifact, root);
+        // validate all of them first, to keep the current ones on error
+        for natspec in &natspecs {
+            validate_natspec(natspec, &self.profiles)?;
+        }
+
+        self.natspecs.retain(|natspec| natspec.contract != contract);
+        self.inline_fuzz.remove_contract(&contract);
+        self.inline_invariant.remove_contract(&contract);
+        self.inline_budget.remove_contract(&contract);
         for natspec in natspecs {
-            // Perform general validation
-            validate_profiles(&natspec, &profiles)?;
-            FuzzConfig::validate_configs(&natspec)?;
-            InvariantConfig::validate_configs(&natspec)?;
-
-            // Apply in-line configurations for the current profile
-            let configs: Vec<String> = natspec.current_profile_configs().collect();
-            let c: &str = &natspec.contract;
-            let f: &str = &natspec.function;
-            let line: String = natspec.debug_context();
-
-            match base_fuzz.try_merge(&configs) {
-                Ok(Some(conf)) => inline_fuzz.insert(c, f, conf),
-                Ok(None) => { /* No inline config found, do nothing */ }
-                Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
-            }
+            self.add_natspec(natspec)?;
+        }
+        Ok(())
+    }
 
-            match base_invariant.try_merge(&configs) {
-                Ok(Some(conf)) => inline_invariant.insert(c, f, conf),
-                Ok(None) => { /* No inline config found, do nothing */ }
-                Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+    /// Validates the in-line configurations of a natspec and merges them with the base ones.
+    fn add_natspec(&mut self, natspec: NatSpec) -> Result<(), InlineConfigError> {
+        validate_natspec(&natspec, &self.profiles)?;
+        if natspec.config_lines().next().is_none() {
+            return Ok(())
+        }
+        self.merge_natspec(&natspec)?;
+        self.natspecs.push(natspec);
+        Ok(())
+    }
+
+    /// Merges the in-line configurations of all the natspecs with the base ones again.
+    ///
+    /// All the natspecs are merged and kept, the first one failing to merge is returned.
+    fn merge_inline(&mut self) -> Result<(), InlineConfigError> {
+        self.inline_fuzz = Default::default();
+        self.inline_invariant = Default::default();
+        self.inline_budget = Default::default();
+        let mut merged = Ok(());
+        for natspec in std::mem::take(&mut self.natspecs) {
+            if let Err(err) = self.merge_natspec(&natspec) {
+                merged = merged.and(Err(err));
             }
+            self.natspecs.push(natspec);
         }
+        merged
+    }
+
+    /// Applies the in-line configurations of a natspec for the current profile.
+    fn merge_natspec(&mut self, natspec: &NatSpec) -> Result<(), InlineConfigError> {
+        let configs: Vec<String> = natspec.current_profile_configs().collect();
+        let c: &str = &natspec.contract;
+        let f: &str = &natspec.function;
+        let line: String = natspec.debug_context();
 
-        Ok(Self { fuzz: base_fuzz, invariant: base_invariant, inline_fuzz, inline_invariant })
+        match self.fuzz.try_merge(&configs) {
+            Ok(Some(conf)) => self.inline_fuzz.insert(c, f, conf),
+            Ok(None) => { /* No inline config found, do nothing */ }
+            Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+        }
+
+        match self.invariant.try_merge(&configs) {
+            Ok(Some(conf)) => self.inline_invariant.insert(c, f, conf),
+            Ok(None) => { /* No inline config found, do nothing */ }
+            Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+        }
+
+        match self.budget.try_merge(&configs) {
+            Ok(Some(conf)) => self.inline_budget.insert(c, f, conf),
+            Ok(None) => { /* No inline config found, do nothing */ }
+            Err(e) => Err(InlineConfigError { line, source: e })?,
+        }
+        Ok(())
     }
 
     /// Returns a "fuzz" test runner instance. Parameters are used to select tight scoped fuzz
@@ -144,6 +261,37 @@ impl TestOptions {
         self.inline_invariant.get(contract_id, test_fn).unwrap_or(&self.invariant)
     }
 
+    /// Returns where and how often the campaign of an invariant test is checkpointed, `None` if
+    /// its `checkpoint_interval` is `0` or no checkpoint directory is set.
+    ///
+    /// The checkpoint of a test is at `<checkpoint_dir>/<path>/<contract>/<test>.json`.
+    pub fn invariant_checkpoint(
+        &self,
+        contract_id: &str,
+        test_fn: &str,
+    ) -> Option<CheckpointConfig> {
+        let interval = self.invariant_config(contract_id, test_fn).checkpoint_interval;
+        let dir = self.checkpoint_dir.as_ref().filter(|_| interval > 0)?;
+        Some(CheckpointConfig {
+            path: dir.join(contract_id.replace(':', "/")).join(format!("{test_fn}.json")),
+            interval,
+            resume: self.resume_campaign,
+        })
+    }
+
+    /// Returns the execution time budget of a test. A fallback budget is applied if no specific
+    /// one is found for the given contract-function pair.
+    ///
+    /// - `contract_id` is the id of the test contract, expressed as a relative path from the
+    ///   project root.
+    /// - `test_fn` is the name of the test function declared inside the test contract.
+    pub fn budget_config<S>(&self, contract_id: S, test_fn: S) -> &TestBudgetConfig
+    where
+        S: Into<String>,
+    {
+        self.inline_budget.get(contract_id, test_fn).unwrap_or(&self.budget)
+    }
+
     pub fn fuzzer_with_cases(&self, cases: u32) -> TestRunner {
         // TODO: Add Options to modify the persistence
         let cfg = proptest::test_runner::Config {
@@ -164,12 +312,22 @@ impl TestOptions {
     }
 }
 
+/// Performs the general validation of the in-line configurations of a natspec.
+fn validate_natspec(natspec: &NatSpec, profiles: &[String]) -> Result<(), InlineConfigError> {
+    validate_profiles(natspec, profiles)?;
+    FuzzConfig::validate_configs(natspec)?;
+    InvariantConfig::validate_configs(natspec)?;
+    TestBudgetConfig::validate_configs(natspec)?;
+    Ok(())
+}
+
 /// Builder utility to create a [`TestOptions`] instance.
 #[derive(Default)]
 #[must_use = "builders do nothing unless you call `build` on them"]
 pub struct TestOptionsBuilder {
     fuzz: Option<FuzzConfig>,
     invariant: Option<InvariantConfig>,
+    budget: Option<TestBudgetConfig>,
     profiles: Option<Vec<String>>,
 }
 
@@ -186,6 +344,12 @@ impl TestOptionsBuilder {
         self
     }
 
+    /// Sets a [`TestBudgetConfig`] to be used as base execution time budget.
+    pub fn budget(mut self, conf: TestBudgetConfig) -> Self {
+        self.budget = Some(conf);
+        self
+    }
+
     /// Sets available configuration profiles. Profiles are useful to validate existing in-line
     /// configurations. This argument is necessary in case a `compile_output`is provided.
     pub fn profiles(mut self, p: Vec<String>) -> Self {
@@ -208,7 +372,8 @@ impl TestOptionsBuilder {
             self.profiles.unwrap_or_else(|| vec![Config::selected_profile().into()]);
         let base_fuzz = self.fuzz.unwrap_or_default();
         let base_invariant = self.invariant.unwrap_or_default();
-        TestOptions::new(output, root, profiles, base_fuzz, base_invariant)
+        let base_budget = self.budget.unwrap_or_default();
+        TestOptions::new(output, root, profiles, base_fuzz, base_invariant, base_budget)
     }
 }
 
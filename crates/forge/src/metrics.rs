@@ -0,0 +1,269 @@
+//! Metrics of the test runs of a long-lived `forge test --watch` session.
+//!
+//! Every run adds its results to the metrics recorded in a file, which the watch session serves
+//! in the Prometheus text format.
+
+use crate::result::{TestOutcome, TestStatus};
+use foundry_common::fs;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+/// The metrics of all the test runs of a session.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TestMetrics {
+    /// The number of test runs.
+    pub runs: u64,
+    /// The number of executed tests, by status.
+    pub tests: BTreeMap<String, u64>,
+    /// The total duration of the executed tests, in seconds.
+    pub test_seconds: f64,
+    /// The total EVM gas consumed by the executed tests.
+    pub gas: u64,
+    /// The total ergs used by the EraVM executions of the executed tests.
+    #[serde(default)]
+    pub ergs: u64,
+    /// The number of executed test suites.
+    pub suites_run: u64,
+    /// The number of test suites skipped because their inputs didn't change.
+    pub suites_cached: u64,
+    /// The number of fork requests answered from the cache.
+    pub fork_cache_hits: u64,
+    /// The number of fork requests fetched from the RPC endpoint.
+    pub fork_cache_misses: u64,
+    /// The number of RPC requests.
+    pub rpc_requests: u64,
+    /// The last result of every test, by suite and test.
+    pub last: BTreeMap<String, BTreeMap<String, TestSample>>,
+}
+
+/// The result of the last run of a test.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TestSample {
+    /// Whether the test passed.
+    pub passed: bool,
+    /// The duration of the test, in seconds.
+    pub seconds: f64,
+    /// The EVM gas consumed by the test, the median of the runs of fuzz tests.
+    pub gas: u64,
+    /// The ergs used by the EraVM executions of the test, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ergs: Option<u64>,
+}
+
+/// The process-wide counters of a single run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunCounters {
+    /// The number of test suites skipped because their inputs didn't change.
+    pub suites_cached: usize,
+    /// The number of fork requests answered from the cache.
+    pub fork_cache_hits: u64,
+    /// The number of fork requests fetched from the RPC endpoint.
+    pub fork_cache_misses: u64,
+    /// The number of RPC requests.
+    pub rpc_requests: u64,
+}
+
+impl TestMetrics {
+    /// Reads the metrics recorded at `path`, which are empty if the file doesn't exist or is
+    /// invalid.
+    pub fn read(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default()
+        }
+        fs::read_json_file(path)
+            .map_err(|err| warn!(%err, "failed to read the recorded test metrics"))
+            .unwrap_or_default()
+    }
+
+    /// Writes the metrics to `path`.
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Adds the results of a run to the metrics.
+    pub fn record(&mut self, outcome: &TestOutcome, counters: RunCounters) {
+        self.runs += 1;
+        self.suites_run += outcome.results.len() as u64;
+        self.suites_cached += counters.suites_cached as u64;
+        self.fork_cache_hits += counters.fork_cache_hits;
+        self.fork_cache_misses += counters.fork_cache_misses;
+        self.rpc_requests += counters.rpc_requests;
+
+        for (suite, result) in &outcome.results {
+            let mut samples = BTreeMap::new();
+            for (test, result) in &result.test_results {
+                let status = match result.status {
+                    TestStatus::Success => "passed",
+                    TestStatus::Failure => "failed",
+                    TestStatus::Skipped => "skipped",
+                };
+                *self.tests.entry(status.to_string()).or_default() += 1;
+                let sample = TestSample {
+                    passed: result.status == TestStatus::Success,
+                    seconds: result.duration.as_secs_f64(),
+                    gas: result.kind.report().gas(),
+                    ergs: result.zk_ergs,
+                };
+                self.test_seconds += sample.seconds;
+                self.gas += sample.gas;
+                self.ergs += sample.ergs.unwrap_or_default();
+                samples.insert(test.clone(), sample);
+            }
+            self.last.insert(suite.clone(), samples);
+        }
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{name}{labels} {value}");
+            }
+        };
+        let value = |value: &dyn ToString| vec![(String::new(), value.to_string())];
+
+        metric("forge_test_runs_total", "counter", "Number of test runs.", &value(&self.runs));
+        let tests = self
+            .tests
+            .iter()
+            .map(|(status, count)| (format!("{{status=\"{status}\"}}"), count.to_string()))
+            .collect::<Vec<_>>();
+        metric("forge_tests_total", "counter", "Number of executed tests, by status.", &tests);
+        metric(
+            "forge_test_duration_seconds_total",
+            "counter",
+            "Total duration of the executed tests.",
+            &value(&self.test_seconds),
+        );
+        metric(
+            "forge_test_gas_total",
+            "counter",
+            "Total EVM gas consumed by the executed tests.",
+            &value(&self.gas),
+        );
+        metric(
+            "forge_test_ergs_total",
+            "counter",
+            "Total ergs used by the EraVM executions of the executed tests.",
+            &value(&self.ergs),
+        );
+        metric(
+            "forge_test_suites_total",
+            "counter",
+            "Number of test suites, by whether they were executed or skipped as unchanged.",
+            &[
+                ("{result=\"run\"}".to_string(), self.suites_run.to_string()),
+                ("{result=\"cached\"}".to_string(), self.suites_cached.to_string()),
+            ],
+        );
+        metric(
+            "forge_fork_cache_requests_total",
+            "counter",
+            "Number of fork requests, by whether they were answered from the cache.",
+            &[
+                ("{result=\"hit\"}".to_string(), self.fork_cache_hits.to_string()),
+                ("{result=\"miss\"}".to_string(), self.fork_cache_misses.to_string()),
+            ],
+        );
+        metric(
+            "forge_rpc_requests_total",
+            "counter",
+            "Number of RPC requests.",
+            &value(&self.rpc_requests),
+        );
+
+        let last = |f: fn(&TestSample) -> Option<String>| {
+            self.last
+                .iter()
+                .flat_map(|(suite, tests)| {
+                    tests.iter().filter_map(move |(test, sample)| {
+                        let labels =
+                            format!("{{suite=\"{}\",test=\"{}\"}}", escape(suite), escape(test));
+                        Some((labels, f(sample)?))
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        metric(
+            "forge_test_passed",
+            "gauge",
+            "Whether the last run of the test passed.",
+            &last(|sample| Some(u8::from(sample.passed).to_string())),
+        );
+        metric(
+            "forge_test_duration_seconds",
+            "gauge",
+            "Duration of the last run of the test.",
+            &last(|sample| Some(sample.seconds.to_string())),
+        );
+        metric(
+            "forge_test_gas",
+            "gauge",
+            "EVM gas consumed by the last run of the test.",
+            &last(|sample| Some(sample.gas.to_string())),
+        );
+        metric(
+            "forge_test_ergs",
+            "gauge",
+            "Ergs used by the EraVM executions of the last run of the test, if any.",
+            &last(|sample| sample.ergs.map(|ergs| ergs.to_string())),
+        );
+        out
+    }
+}
+
+/// Escapes a Prometheus label value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::{SuiteResult, TestResult};
+    use std::time::Duration;
+
+    #[test]
+    fn renders_recorded_runs() {
+        let results = BTreeMap::from([
+            (
+                "test/A.t.sol:ATest".to_string(),
+                TestResult {
+                    status: TestStatus::Success,
+                    zk_ergs: Some(1000),
+                    ..Default::default()
+                },
+            ),
+            ("test/B.t.sol:BTest".to_string(), TestResult::fail("reverted".to_string())),
+        ])
+        .into_iter()
+        .map(|(suite, result)| {
+            let tests = BTreeMap::from([("test_a()".to_string(), result)]);
+            (suite, SuiteResult::new(Duration::ZERO, tests, vec![]))
+        })
+        .collect();
+        let outcome = TestOutcome::new(results, false);
+
+        let mut metrics = TestMetrics::default();
+        let counters = RunCounters { suites_cached: 1, rpc_requests: 3, ..Default::default() };
+        metrics.record(&outcome, counters);
+        metrics.record(&outcome, counters);
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("forge_test_runs_total 2\n"), "{rendered}");
+        assert!(rendered.contains("forge_tests_total{status=\"failed\"} 2\n"), "{rendered}");
+        assert!(rendered.contains("forge_test_suites_total{result=\"cached\"} 2\n"), "{rendered}");
+        assert!(rendered.contains("forge_rpc_requests_total 6\n"), "{rendered}");
+        assert!(rendered.contains("forge_test_ergs_total 2000\n"), "{rendered}");
+        assert!(rendered
+            .contains("forge_test_ergs{suite=\"test/A.t.sol:ATest\",test=\"test_a()\"} 1000\n"));
+        assert!(!rendered.contains("forge_test_ergs{suite=\"test/B.t.sol:BTest\""), "{rendered}");
+        assert!(rendered
+            .contains("forge_test_passed{suite=\"test/B.t.sol:BTest\",test=\"test_a()\"} 0\n"));
+    }
+}
@@ -1,44 +1,63 @@
 //! Forge test runner for multiple contracts.
 
 use crate::{
+    assume::TestSourceMap,
+    events::{EventStream, TestEvent},
+    fixtures::{FixtureState, SuiteDependencies},
     link::{LinkOutput, Linker},
-    result::SuiteResult,
+    result::{SuiteResult, TestResult},
+    suite_code::{CodeCache, SuiteCode},
     ContractRunner, TestFilter, TestOptions,
 };
+use alloy_dyn_abi::JsonAbiExt;
 use alloy_json_abi::{Function, JsonAbi};
-use alloy_primitives::{Address, Bytes, U256};
-use eyre::Result;
+use alloy_primitives::{hex, keccak256, Address, Bytes, U256};
+use eyre::{Result, WrapErr};
 use foundry_common::{get_contract_name, ContractsByArtifact, TestFunctionExt};
 use foundry_compilers::{
     contracts::ArtifactContracts, Artifact, ArtifactId, ArtifactOutput, ConfigurableArtifacts,
     ProjectCompileOutput,
 };
+use foundry_config::GenesisContract;
 use foundry_evm::{
     backend::Backend,
     decode::RevertDecoder,
     executors::{Executor, ExecutorBuilder},
     fork::CreateFork,
-    inspectors::CheatsConfig,
+    inspectors::{cheatcodes::FfiInvocation, CheatsConfig},
     opts::EvmOpts,
     revm,
+    traces::{sets_trace_verbosity, TraceSources},
 };
+use foundry_zksync_compiler::{
+    order_libraries, DualCompiledContract, DualCompiledContracts, ZkLibrary,
+};
+use foundry_zksync_core::{era_state::EraState, hash_bytecode, vm::SizeLimitWarning};
+use parking_lot::Mutex;
 use rayon::prelude::*;
 use revm::primitives::SpecId;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{mpsc, Arc},
+    time::Duration,
 };
 
-pub type DeployableContracts = BTreeMap<ArtifactId, (JsonAbi, Bytes, Vec<Bytes>)>;
+pub type DeployableContracts = BTreeMap<ArtifactId, (JsonAbi, SuiteCode, Vec<Bytes>)>;
 
 /// A multi contract runner receives a set of contracts deployed in an EVM instance and proceeds
 /// to run all test functions in these contracts.
 pub struct MultiContractRunner {
     /// Mapping of contract name to JsonAbi, creation bytecode and library bytecode which
     /// needs to be deployed & linked against
+    ///
+    /// The creation bytecode is loaded from the artifact when the suite runs.
     pub contracts: DeployableContracts,
+    /// The root of the project, the artifact paths are relative to
+    pub root: PathBuf,
+    /// The most recently loaded creation bytecodes
+    pub code_cache: CodeCache,
     /// Compiled contracts by name that have an JsonAbi and runtime bytecode
     pub known_contracts: ContractsByArtifact,
     /// The EVM instance used in the test runner
@@ -53,6 +72,8 @@ pub struct MultiContractRunner {
     pub sender: Option<Address>,
     /// A map of contract names to absolute source file paths
     pub source_paths: BTreeMap<String, String>,
+    /// The source maps of the deployed code of the test contracts, by identifier
+    pub test_source_maps: BTreeMap<String, TestSourceMap>,
     /// The fork to use at launch
     pub fork: Option<CreateFork>,
     /// Additional cheatcode inspector related settings derived from the `Config`
@@ -66,6 +87,26 @@ pub struct MultiContractRunner {
     /// Whether to enable call isolation
     pub isolation: bool,
     pub use_zk: bool,
+    /// Stream to report the progress of the run to
+    pub event_stream: Option<Arc<EventStream>>,
+    /// Deployed code to set at the given addresses before running `setUp()`
+    pub genesis: Vec<(Address, Bytes)>,
+    /// The era_test_node state to load before deploying the test contracts
+    pub era_state: Option<EraState>,
+    /// Whether to record the inputs of every suite, its traces and the paths read by cheatcodes
+    pub record_inputs: bool,
+    /// Whether to record the traces of every call, whatever the verbosity set by
+    /// `vm.setTraceVerbosity`
+    pub full_traces: bool,
+    /// The sources of the deployed contracts, to show the source lines of the traces
+    pub trace_sources: Option<Arc<TraceSources>>,
+    /// The fixtures the test suites depend on
+    pub dependencies: SuiteDependencies,
+    /// The fixture suites, kept when their own tests don't run
+    pub fixtures: DeployableContracts,
+    /// The static size warnings of the ABIs of the contracts each test source file imports, by
+    /// source file
+    pub abi_size_warnings: BTreeMap<PathBuf, Vec<SizeLimitWarning>>,
 }
 
 impl MultiContractRunner {
@@ -82,8 +123,10 @@ impl MultiContractRunner {
         self.contracts
             .iter()
             .filter(|(id, _)| filter.matches_path(&id.source) && filter.matches_contract(&id.name))
-            .flat_map(|(_, (abi, _, _))| {
-                abi.functions().filter(|func| filter.matches_test(&func.signature()))
+            .flat_map(|(id, (abi, _, _))| {
+                abi.functions().filter(|func| {
+                    filter.matches_qualified_test(&id.source, &id.name, &func.signature())
+                })
             })
     }
 
@@ -116,7 +159,9 @@ impl MultiContractRunner {
                 let tests = abi
                     .functions()
                     .filter(|func| func.name.is_test())
-                    .filter(|func| filter.matches_test(&func.signature()))
+                    .filter(|func| {
+                        filter.matches_qualified_test(&id.source, &id.name, &func.signature())
+                    })
                     .map(|func| func.name.clone())
                     .collect::<Vec<_>>();
 
@@ -174,40 +219,337 @@ impl MultiContractRunner {
         let mut db = Backend::spawn(self.fork.take()).await;
         db.is_zk = self.cheats_config.use_zk;
 
-        self.contracts
-            .par_iter()
+        let suites = self
+            .contracts
+            .iter()
             .filter(|(id, _)| filter.matches_path(&id.source) && filter.matches_contract(&id.name))
             .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
-            .for_each_with(stream_result, |stream_result, (id, (abi, deploy_code, libs))| {
-                let executor = ExecutorBuilder::new()
-                    .inspectors(|stack| {
-                        stack
-                            .cheatcodes(self.cheats_config.clone())
-                            .trace(self.evm_opts.verbosity >= 3 || self.debug)
-                            .debug(self.debug)
-                            .coverage(self.coverage)
-                            .enable_isolation(self.isolation)
-                    })
-                    .spec(self.evm_spec)
-                    .gas_limit(self.evm_opts.gas_limit())
-                    .use_zk_vm(self.use_zk)
-                    .build(self.env.clone(), db.clone());
+            .collect::<Vec<_>>();
+
+        // the fixtures are set up once, before the suites depending on them run
+        let fixtures = self.setup_fixtures(&db, suites.iter().map(|(id, _)| id.identifier()));
+
+        suites.into_par_iter().for_each_with(
+            stream_result,
+            |stream_result, (id, (abi, deploy_code, libs))| {
                 let identifier = id.identifier();
                 trace!(contract=%identifier, "start executing all tests in contract");
+                if let Some(events) = &self.event_stream {
+                    events.emit(TestEvent::SuiteStarted { suite: &identifier });
+                }
 
-                let result = self.run_tests(
-                    &identifier,
-                    abi,
-                    executor,
-                    deploy_code.clone(),
-                    libs,
-                    filter,
-                    test_options.clone(),
-                );
+                let chain = self.dependencies.chain(&identifier);
+                // the creation code is only held while the suite runs
+                let setup = match fixtures.get(&chain) {
+                    Some(Err(reason)) => Err(reason.clone()),
+                    fixture => self
+                        .code_cache
+                        .load(&self.root, id, deploy_code)
+                        .map(|code| (fixture.and_then(|fixture| fixture.as_ref().ok()), code))
+                        .map_err(|err| format!("{err:#}")),
+                };
+                let mut result = match setup {
+                    Err(reason) => {
+                        let result = TestResult::fail(format!("setup failed: {reason}"));
+                        if let Some(events) = &self.event_stream {
+                            events.emit(TestEvent::test_finished(&identifier, "setUp()", &result));
+                        }
+                        SuiteResult::new(
+                            Duration::ZERO,
+                            [("setUp()".to_string(), result)].into(),
+                            Vec::new(),
+                        )
+                    }
+                    Ok((fixture, deploy_code)) => {
+                        let read_paths = self.record_inputs.then(Arc::<Mutex<_>>::default);
+                        let size_warnings = self.use_zk.then(Arc::<Mutex<_>>::default);
+                        let ffi_log = self.cheats_config.ffi.then(Arc::<Mutex<_>>::default);
+                        let cheats_config = self.suite_cheats_config(
+                            read_paths.clone(),
+                            size_warnings.clone(),
+                            ffi_log.clone(),
+                            fixture,
+                        );
+                        let (env, db) = match fixture {
+                            Some(fixture) => (fixture.env.clone(), fixture.backend.clone()),
+                            None => (self.env.clone(), db.clone()),
+                        };
+                        let executor = self.executor(cheats_config, &deploy_code, env, db);
+
+                        let mut result = self.run_tests(
+                            &identifier,
+                            abi,
+                            executor,
+                            deploy_code,
+                            libs,
+                            filter,
+                            test_options.clone(),
+                            fixture.is_some(),
+                        );
+                        if let Some(read_paths) = read_paths {
+                            result.read_paths = std::mem::take(&mut *read_paths.lock());
+                            if let Some(fixture) = fixture {
+                                result.read_paths.extend(fixture.read_paths.iter().cloned());
+                            }
+                        }
+                        if let Some(size_warnings) = size_warnings {
+                            result.size_warnings = std::mem::take(&mut *size_warnings.lock());
+                        }
+                        if let Some(ffi_log) = ffi_log {
+                            result.ffi_invocations = std::mem::take(&mut *ffi_log.lock());
+                            self.cheats_config
+                                .ffi_log
+                                .lock()
+                                .extend(result.ffi_invocations.iter().cloned());
+                        }
+                        result
+                    }
+                };
+                result.fixtures = chain;
+                if let Some(warnings) = self.abi_size_warnings.get(&id.source) {
+                    result.size_warnings.extend(warnings.iter().cloned());
+                }
                 trace!(contract=?identifier, "executed all tests in contract");
+                // the results of the tests were emitted as soon as they finished
+                if let Some(events) = &self.event_stream {
+                    events.emit(TestEvent::suite_finished(&identifier, &result));
+                }
 
                 let _ = stream_result.send((identifier, result));
+            },
+        )
+    }
+
+    /// Runs the single test `fn_name`, a function name or signature, of the test contract
+    /// `artifact_id` with the ABI-encoded arguments `calldata`, optionally prefixed by the
+    /// selector of the function.
+    ///
+    /// The test contract is deployed and set up as in [`test`](Self::test), on the state of the
+    /// fixtures it depends on, and the environment of the test is changed by `env_overrides`. The
+    /// test always records its traces.
+    pub async fn run_test(
+        &self,
+        artifact_id: &str,
+        fn_name: &str,
+        calldata: Bytes,
+        env_overrides: TestEnvOverrides,
+    ) -> Result<TestResult> {
+        let Some((id, (abi, deploy_code, libs))) =
+            self.contracts.iter().find(|(id, _)| id.identifier() == artifact_id)
+        else {
+            eyre::bail!("the test contract {artifact_id} doesn't exist")
+        };
+        let func = abi
+            .functions()
+            .find(|func| func.name == fn_name || func.signature() == fn_name)
+            .ok_or_else(|| eyre::eyre!("{artifact_id} has no function {fn_name}"))?;
+        if !func.is_test() {
+            eyre::bail!("{} of {artifact_id} is not a test", func.signature());
+        }
+        let args = match calldata.strip_prefix(func.selector().as_slice()) {
+            Some(args) if calldata.len() % 32 == 4 => args,
+            _ => &calldata[..],
+        };
+        let args = func
+            .abi_decode_input(args, false)
+            .wrap_err_with(|| format!("invalid arguments for {}", func.signature()))?;
+
+        let identifier = id.identifier();
+        trace!(contract=%identifier, test=%func.signature(), "running single test");
+        let mut db = Backend::spawn(self.fork.clone()).await;
+        db.is_zk = self.cheats_config.use_zk;
+
+        let chain = self.dependencies.chain(&identifier);
+        let mut fixtures = self.setup_fixtures(&db, std::iter::once(identifier.clone()));
+        let fixture = match fixtures.remove(&chain) {
+            Some(Err(reason)) => return Ok(TestResult::fail(format!("setup failed: {reason}"))),
+            fixture => fixture.and_then(Result::ok),
+        };
+
+        let cheats_config = self.suite_cheats_config(None, None, None, fixture.as_ref());
+        let (mut env, db) = match fixture {
+            Some(ref fixture) => (fixture.env.clone(), fixture.backend.clone()),
+            None => (self.env.clone(), db),
+        };
+        env_overrides.apply(&mut env);
+        let deploy_code = self.code_cache.load(&self.root, id, deploy_code)?;
+        let mut executor = self.executor(cheats_config, &deploy_code, env, db);
+        executor.set_tracing(true);
+
+        let mut runner = ContractRunner::new(
+            &identifier,
+            executor,
+            abi,
+            deploy_code,
+            self.evm_opts.initial_balance,
+            env_overrides.sender.or(self.sender),
+            &self.revert_decoder,
+            libs,
+            &self.genesis,
+            self.debug,
+        );
+        runner.source_map = self.test_source_maps.get(&identifier);
+        runner.era_state = self.era_state.as_ref().filter(|_| fixture.is_none());
+        runner.on_fixtures = fixture.is_some();
+        Ok(runner.run_single_test(func, args))
+    }
+
+    /// Sets up the fixtures the suites `suites` depend on, by chain of fixtures set up one after
+    /// the other.
+    ///
+    /// Chains sharing their first fixtures reuse their state, so every fixture is only set up once
+    /// on the state of the same fixtures.
+    fn setup_fixtures(
+        &self,
+        db: &Backend,
+        suites: impl Iterator<Item = String>,
+    ) -> BTreeMap<Vec<String>, Result<FixtureState, String>> {
+        let mut states = BTreeMap::<Vec<String>, Result<FixtureState, String>>::new();
+        for suite in suites {
+            let chain = self.dependencies.chain(&suite);
+            for len in 1..=chain.len() {
+                if states.contains_key(&chain[..len]) {
+                    continue
+                }
+                let state = match states.get(&chain[..len - 1]) {
+                    Some(Ok(base)) => self.setup_fixture(db, &chain[len - 1], Some(base)),
+                    Some(Err(reason)) => Err(reason.clone()),
+                    None => self.setup_fixture(db, &chain[len - 1], None),
+                };
+                states.insert(chain[..len].to_vec(), state);
+            }
+        }
+        states
+    }
+
+    /// Deploys and sets up the fixture `fixture`, on the state of the fixtures `base` if any.
+    fn setup_fixture(
+        &self,
+        db: &Backend,
+        fixture: &str,
+        base: Option<&FixtureState>,
+    ) -> Result<FixtureState, String> {
+        let Some((id, (abi, deploy_code, libs))) =
+            self.fixtures.iter().find(|(id, _)| id.identifier() == fixture)
+        else {
+            return Err(format!("the fixture {fixture} is not a deployable contract"))
+        };
+        trace!(%fixture, "setting up fixture");
+        let deploy_code = self
+            .code_cache
+            .load(&self.root, id, deploy_code)
+            .map_err(|err| format!("fixture {fixture} failed: {err:#}"))?;
+
+        let read_paths = self.record_inputs.then(Arc::<Mutex<_>>::default);
+        let cheats_config = self.suite_cheats_config(read_paths.clone(), None, None, base);
+        let (env, db) = match base {
+            Some(base) => (base.env.clone(), base.backend.clone()),
+            None => (self.env.clone(), db.clone()),
+        };
+        let executor = self.executor(cheats_config, &deploy_code, env, db);
+        let mut runner = ContractRunner::new(
+            fixture,
+            executor,
+            abi,
+            deploy_code,
+            self.evm_opts.initial_balance,
+            self.sender,
+            &self.revert_decoder,
+            libs,
+            &self.genesis,
+            false,
+        );
+        runner.on_fixtures = base.is_some();
+        runner.era_state = self.era_state.as_ref().filter(|_| base.is_none());
+        let setup = runner.setup(abi.functions().any(|func| func.name == "setUp"));
+        if let Some(reason) = setup.reason {
+            return Err(format!("fixture {fixture} failed: {reason}"))
+        }
+
+        let mut state = match base {
+            Some(base) => FixtureState {
+                backend: runner.executor.backend,
+                env: runner.executor.env,
+                ..base.clone()
+            },
+            None => FixtureState {
+                backend: runner.executor.backend,
+                env: runner.executor.env,
+                addresses: BTreeMap::new(),
+                labels: HashMap::new(),
+                read_paths: BTreeSet::new(),
+            },
+        };
+        state.addresses.insert(fixture.to_string(), setup.address);
+        state.addresses.insert(id.name.clone(), setup.address);
+        state.labels.extend(setup.labeled_addresses);
+        state.labels.entry(setup.address).or_insert_with(|| id.name.clone());
+        if let Some(read_paths) = read_paths {
+            state.read_paths.extend(std::mem::take(&mut *read_paths.lock()));
+        }
+        Ok(state)
+    }
+
+    /// Returns the cheatcodes config of a suite, recording the paths read by its cheatcodes, the
+    /// sizes of its ZK-VM calls and the invocations of its FFI cheatcodes if set, with the
+    /// fixtures it runs on.
+    fn suite_cheats_config(
+        &self,
+        read_paths: Option<Arc<Mutex<BTreeSet<PathBuf>>>>,
+        size_warnings: Option<Arc<Mutex<Vec<SizeLimitWarning>>>>,
+        ffi_log: Option<Arc<Mutex<Vec<FfiInvocation>>>>,
+        fixture: Option<&FixtureState>,
+    ) -> Arc<CheatsConfig> {
+        if read_paths.is_none() && size_warnings.is_none() && ffi_log.is_none() && fixture.is_none()
+        {
+            return self.cheats_config.clone()
+        }
+        let mut cheats_config = CheatsConfig {
+            read_paths,
+            zk_size_warnings: size_warnings,
+            ..(*self.cheats_config).clone()
+        };
+        if let Some(ffi_log) = ffi_log {
+            cheats_config.ffi_log = ffi_log;
+        }
+        if let Some(fixture) = fixture {
+            cheats_config.fixtures = fixture.addresses.clone();
+            cheats_config.labels.extend(fixture.labels.clone());
+        }
+        Arc::new(cheats_config)
+    }
+
+    /// Returns the executor of a suite deploying `deploy_code`, with the environment `env` and the
+    /// database `db`.
+    fn executor(
+        &self,
+        cheats_config: Arc<CheatsConfig>,
+        deploy_code: &Bytes,
+        env: revm::primitives::Env,
+        db: Backend,
+    ) -> Executor {
+        let full_traces = self.debug || self.record_inputs || self.full_traces;
+        let trace = self.evm_opts.verbosity >= 3 || full_traces;
+        // the tracer only records the calls of a suite changing its trace verbosity at the
+        // verbosities they are displayed at, unless full traces are needed
+        let trace_verbosity =
+            (!full_traces && sets_trace_verbosity(deploy_code)).then_some(self.evm_opts.verbosity);
+        ExecutorBuilder::new()
+            .inspectors(|stack| {
+                stack
+                    .trace_return_data_limit(cheats_config.trace_return_data_limit)
+                    .cheatcodes(cheats_config)
+                    .trace(trace || trace_verbosity.is_some())
+                    .trace_verbosity(trace_verbosity)
+                    .trace_steps(self.trace_sources.is_some())
+                    .debug(self.debug)
+                    .coverage(self.coverage)
+                    .enable_isolation(self.isolation)
             })
+            .spec(self.evm_spec)
+            .gas_limit(self.evm_opts.gas_limit())
+            .use_zk_vm(self.use_zk)
+            .build(env, db)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -220,6 +562,7 @@ impl MultiContractRunner {
         libs: &[Bytes],
         filter: &dyn TestFilter,
         test_options: TestOptions,
+        on_fixtures: bool,
     ) -> SuiteResult {
         let span = info_span!("run_tests");
         if !span.is_disabled() {
@@ -231,7 +574,7 @@ impl MultiContractRunner {
         }
         let _guard = span.enter();
 
-        let runner = ContractRunner::new(
+        let mut runner = ContractRunner::new(
             name,
             executor,
             contract,
@@ -240,12 +583,64 @@ impl MultiContractRunner {
             self.sender,
             &self.revert_decoder,
             libs,
+            &self.genesis,
             self.debug,
         );
+        runner.source_map = self.test_source_maps.get(name);
+        // the state was loaded before the fixtures were set up
+        runner.era_state = self.era_state.as_ref().filter(|_| !on_fixtures);
+        runner.on_fixtures = on_fixtures;
+        runner.event_stream = self.event_stream.as_deref();
         runner.run_tests(filter, test_options, Some(&self.known_contracts))
     }
 }
 
+/// Changes to the environment of a test run by [`MultiContractRunner::run_test`].
+#[derive(Clone, Debug, Default)]
+pub struct TestEnvOverrides {
+    /// The number of the block
+    pub block_number: Option<U256>,
+    /// The timestamp of the block
+    pub block_timestamp: Option<U256>,
+    /// The chain ID
+    pub chain_id: Option<u64>,
+    /// The address deploying the test contract and calling the test, also used as `tx.origin`
+    pub sender: Option<Address>,
+    /// The gas price of the transactions
+    pub gas_price: Option<U256>,
+    /// The base fee of the block
+    pub base_fee: Option<U256>,
+    /// The coinbase of the block
+    pub coinbase: Option<Address>,
+}
+
+impl TestEnvOverrides {
+    /// Applies the overrides to `env`.
+    pub fn apply(&self, env: &mut revm::primitives::Env) {
+        if let Some(number) = self.block_number {
+            env.block.number = number;
+        }
+        if let Some(timestamp) = self.block_timestamp {
+            env.block.timestamp = timestamp;
+        }
+        if let Some(chain_id) = self.chain_id {
+            env.cfg.chain_id = chain_id;
+        }
+        if let Some(sender) = self.sender {
+            env.tx.caller = sender;
+        }
+        if let Some(gas_price) = self.gas_price {
+            env.tx.gas_price = gas_price;
+        }
+        if let Some(base_fee) = self.base_fee {
+            env.block.basefee = base_fee;
+        }
+        if let Some(coinbase) = self.coinbase {
+            env.block.coinbase = coinbase;
+        }
+    }
+}
+
 /// Builder used for instantiating the multi-contract runner
 #[derive(Clone, Debug, Default)]
 #[must_use = "builders do nothing unless you call `build` on them"]
@@ -269,6 +664,23 @@ pub struct MultiContractRunnerBuilder {
     pub isolation: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: Option<TestOptions>,
+    /// Stream to report the progress of the run to
+    pub event_stream: Option<Arc<EventStream>>,
+    /// Contracts to deploy at the given addresses before running `setUp()`
+    pub genesis: BTreeMap<Address, GenesisContract>,
+    /// The era_test_node state file to load before deploying the test contracts
+    pub load_state: Option<PathBuf>,
+    /// Whether to record the inputs of every suite
+    pub record_inputs: bool,
+    /// Whether to record the traces of every call, e.g. for the gas report
+    pub full_traces: bool,
+    /// The zksolc output of the project, whose contracts are matched with the solc ones
+    pub zk_output: Option<ProjectCompileOutput>,
+    /// The libraries of the `libraries` config to deploy before running `setUp()`, in
+    /// deployment order
+    pub zk_libraries: Vec<ZkLibrary>,
+    /// Whether to record the steps of the traces and the sources to show their source lines
+    pub trace_sources: bool,
 }
 
 #[derive(Debug)]
@@ -332,6 +744,53 @@ impl MultiContractRunnerBuilder {
         self
     }
 
+    pub fn with_event_stream(mut self, event_stream: Option<Arc<EventStream>>) -> Self {
+        self.event_stream = event_stream;
+        self
+    }
+
+    pub fn with_genesis(mut self, genesis: BTreeMap<Address, GenesisContract>) -> Self {
+        self.genesis = genesis;
+        self
+    }
+
+    pub fn with_load_state(mut self, load_state: Option<PathBuf>) -> Self {
+        self.load_state = load_state;
+        self
+    }
+
+    pub fn record_inputs(mut self, enable: bool) -> Self {
+        self.record_inputs = enable;
+        self
+    }
+
+    /// Records the traces of every call, whatever the verbosity set by `vm.setTraceVerbosity`.
+    pub fn full_traces(mut self, enable: bool) -> Self {
+        self.full_traces = enable;
+        self
+    }
+
+    /// Sets the zksolc output of the project. Its contracts are matched with the solc ones by
+    /// name and registered as dual compiled contracts in the cheatcodes config, to migrate them
+    /// to the zkEVM. Without it, the tests only run with the solc output.
+    pub fn with_zk_output(mut self, zk_output: Option<ProjectCompileOutput>) -> Self {
+        self.zk_output = zk_output;
+        self
+    }
+
+    /// Sets the libraries of the `libraries` config, which zksolc links the contracts with at
+    /// compile time. Their code is set at their addresses before running `setUp()`, like the
+    /// genesis contracts, every library after the ones it links.
+    pub fn with_zk_libraries(mut self, libraries: Vec<ZkLibrary>) -> Self {
+        self.zk_libraries = libraries;
+        self
+    }
+
+    pub fn with_trace_sources(mut self, enable: bool) -> Self {
+        self.trace_sources = enable;
+        self
+    }
+
     /// Given an EVM, proceeds to return a runner which is able to execute all tests
     /// against that evm
     pub fn build(
@@ -341,13 +800,33 @@ impl MultiContractRunnerBuilder {
         env: revm::primitives::Env,
         evm_opts: EvmOpts,
     ) -> Result<MultiContractRunner> {
+        let dependencies = SuiteDependencies::parse(&output, root)?;
+        let mut cheats_config = self.cheats_config.unwrap_or_default();
+        // the zksolc artifact files of the contracts, by source and name
+        let mut zk_artifacts = BTreeMap::new();
+        if let Some(zk_output) = &self.zk_output {
+            Arc::make_mut(&mut cheats_config.dual_compiled_contracts)
+                .extend(DualCompiledContracts::new(&output, zk_output));
+            for (id, _) in zk_output.artifact_ids() {
+                let source = id.source.strip_prefix(root).unwrap_or(&id.source).to_path_buf();
+                zk_artifacts.insert((source, id.name), root.join(id.path));
+            }
+        }
+
         // This is just the contracts compiled, but we need to merge this with the read cached
         // artifacts.
+        let mut sources = BTreeMap::<u32, PathBuf>::new();
         let contracts = output
             .with_stripped_file_prefixes(root)
             .into_artifacts()
-            .map(|(i, c)| (i, c.into_contract_bytecode()))
+            .map(|(i, c)| {
+                if let Some(file_id) = c.id {
+                    sources.insert(file_id, root.join(&i.source));
+                }
+                (i, c.into_contract_bytecode())
+            })
             .collect::<ArtifactContracts>();
+        let sources = Arc::new(sources);
 
         let source_paths = contracts
             .iter()
@@ -358,9 +837,14 @@ impl MultiContractRunnerBuilder {
 
         // Create a mapping of name => (abi, deployment code, Vec<library deployment code>)
         let mut deployable_contracts = DeployableContracts::default();
+        let mut fixtures = DeployableContracts::default();
 
         let mut known_contracts = ContractsByArtifact::default();
 
+        let mut test_source_maps = BTreeMap::new();
+
+        let mut trace_sources = self.trace_sources.then(TraceSources::default);
+
         for (id, contract) in &linker.contracts.0 {
             let Some(abi) = contract.abi.as_ref() else {
                 continue;
@@ -384,11 +868,47 @@ impl MultiContractRunnerBuilder {
                 continue;
             };
 
+            let no_constructor_args =
+                abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true);
+            // fixtures don't need tests of their own
+            // the creation code is read from the artifact again when the suite runs
+            let zk_path = zk_artifacts.get(&(id.source.clone(), id.name.clone())).cloned();
+            let bytecode = SuiteCode::new(root, id, bytecode, &libraries, zk_path);
+            if no_constructor_args && dependencies.is_fixture(&id.identifier()) {
+                fixtures
+                    .insert(id.clone(), (abi.clone(), bytecode.clone(), libs_to_deploy.clone()));
+            }
+
             // if it's a test, add it to deployable contracts
-            if abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true) &&
+            if no_constructor_args &&
                 abi.functions().any(|func| func.name.is_test() || func.name.is_invariant_test())
             {
                 deployable_contracts.insert(id.clone(), (abi.clone(), bytecode, libs_to_deploy));
+
+                let source_map = contract
+                    .deployed_bytecode
+                    .as_ref()
+                    .and_then(|code| code.bytecode.as_ref()?.source_map()?.ok());
+                if let (Some(code), Some(source_map)) =
+                    (linked_contract.get_deployed_bytecode_bytes(), source_map)
+                {
+                    test_source_maps.insert(
+                        id.identifier(),
+                        TestSourceMap::new(code.into_owned(), source_map, sources.clone()),
+                    );
+                }
+            }
+
+            if let Some(trace_sources) = &mut trace_sources {
+                let source_map = contract
+                    .deployed_bytecode
+                    .as_ref()
+                    .and_then(|code| code.bytecode.as_ref()?.source_map()?.ok());
+                if let (Some(code), Some(source_map)) =
+                    (linked_contract.get_deployed_bytecode_bytes(), source_map)
+                {
+                    trace_sources.insert_contract(id.identifier(), &code, source_map);
+                }
             }
 
             if let Some(bytes) = linked_contract.get_deployed_bytecode_bytes() {
@@ -396,10 +916,97 @@ impl MultiContractRunnerBuilder {
             }
         }
 
+        if let Some(trace_sources) = &mut trace_sources {
+            for (file_id, path) in sources.iter() {
+                let Ok(content) = foundry_common::fs::read_to_string(path) else { continue };
+                let display_path = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+                trace_sources.insert_file(*file_id, display_path, content);
+            }
+        }
+
+        let mut genesis = Vec::with_capacity(self.genesis.len());
+        for (address, contract) in &self.genesis {
+            let code = match contract {
+                GenesisContract::Artifact(id) => {
+                    let (_, (_, code)) = known_contracts
+                        .find_by_name_or_identifier(id)?
+                        .ok_or_else(|| eyre::eyre!("genesis contract {id} not found"))?;
+                    if code.is_empty() {
+                        eyre::bail!("genesis contract {id} has no deployed bytecode");
+                    }
+                    // the code is migrated to EraVM with the zksolc bytecode of the artifact
+                    if cheats_config.use_zk &&
+                        cheats_config
+                            .dual_compiled_contracts
+                            .find_by_evm_hash(keccak256(code))
+                            .is_none()
+                    {
+                        eyre::bail!("genesis contract {id} has no zksolc bytecode to run in EraVM");
+                    }
+                    Bytes::from(code.clone())
+                }
+                GenesisContract::Bytecode { bytecode, zk_bytecode } => {
+                    let code = read_bytecode(bytecode)?;
+                    if let Some(zk_bytecode) = zk_bytecode {
+                        // register the pair so the code is migrated when switching to the zkEVM
+                        let zk_code = read_bytecode(zk_bytecode)?;
+                        eyre::ensure!(
+                            zk_code.len() % 64 == 32,
+                            "invalid EraVM bytecode in {}: length must be an odd number of words",
+                            zk_bytecode.display()
+                        );
+                        Arc::make_mut(&mut cheats_config.dual_compiled_contracts).insert(
+                            DualCompiledContract {
+                                name: format!("genesis {address}"),
+                                zk_bytecode_hash: hash_bytecode(&zk_code),
+                                zk_deployed_bytecode: zk_code.to_vec(),
+                                evm_bytecode_hash: keccak256(&code),
+                                evm_deployed_bytecode: code.to_vec(),
+                                evm_bytecode: vec![],
+                                zk_factory_deps: vec![],
+                                constructor_inputs: None,
+                            },
+                        );
+                    } else if cheats_config.use_zk {
+                        eyre::bail!(
+                            "genesis contract {address} has no `zk_bytecode` to run in EraVM"
+                        );
+                    }
+                    code
+                }
+            };
+            genesis.push((*address, code));
+        }
+        let mut zk_libraries = Vec::new();
+        for library in &self.zk_libraries {
+            // a genesis contract set at the address takes precedence
+            if self.genesis.contains_key(&library.address) {
+                continue
+            }
+            let id = library.identifier();
+            let (_, (_, code)) = known_contracts
+                .find_by_name_or_identifier(&id)?
+                .ok_or_else(|| eyre::eyre!("library {id} not found"))?;
+            if code.is_empty() {
+                eyre::bail!("library {id} has no deployed bytecode");
+            }
+            zk_libraries.push((library.clone(), Bytes::from(code.clone())));
+        }
+        // every library is set after the ones it links in this compilation
+        genesis.extend(
+            order_libraries(zk_libraries)?
+                .into_iter()
+                .map(|(library, code)| (library.address, code)),
+        );
+
+        let era_state = self.load_state.as_deref().map(EraState::read).transpose()?;
+
         let revert_decoder =
             RevertDecoder::new().with_abis(known_contracts.values().map(|(abi, _)| abi));
         Ok(MultiContractRunner {
             contracts: deployable_contracts,
+            root: root.to_path_buf(),
+            code_cache: CodeCache::default(),
             known_contracts,
             evm_opts,
             env,
@@ -407,13 +1014,31 @@ impl MultiContractRunnerBuilder {
             sender: self.sender,
             revert_decoder,
             source_paths,
+            test_source_maps,
             fork: self.fork,
-            cheats_config: self.cheats_config.unwrap_or_default().into(),
+            cheats_config: cheats_config.into(),
             coverage: self.coverage,
             debug: self.debug,
             test_options: self.test_options.unwrap_or_default(),
             isolation: self.isolation,
             use_zk: false,
+            event_stream: self.event_stream,
+            genesis,
+            era_state,
+            record_inputs: self.record_inputs,
+            full_traces: self.full_traces,
+            trace_sources: trace_sources.map(Arc::new),
+            dependencies,
+            fixtures,
+            abi_size_warnings: Default::default(),
         })
     }
 }
+
+/// Reads hex encoded bytecode from the file at `path`.
+fn read_bytecode(path: &Path) -> Result<Bytes> {
+    let content = foundry_common::fs::read_to_string(path)?;
+    hex::decode(content.trim())
+        .map(Into::into)
+        .map_err(|err| eyre::eyre!("invalid bytecode in {}: {err}", path.display()))
+}
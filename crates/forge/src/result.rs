@@ -1,5 +1,6 @@
 //! Test outcomes.
 
+use crate::gas_report::GasReport;
 use alloy_primitives::{Address, Log};
 use foundry_common::{evm::Breakpoints, get_contract_name, get_file_name, shell};
 use foundry_evm::{
@@ -7,12 +8,15 @@ use foundry_evm::{
     debug::DebugArena,
     executors::EvmError,
     fuzz::{CounterExample, FuzzCase},
+    inspectors::cheatcodes::FfiInvocation,
     traces::{CallTraceDecoder, TraceKind, Traces},
 };
+use foundry_zksync_core::vm::{SizeLimitWarning, ZkResourceLimitExceeded};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::{self, Write},
+    path::PathBuf,
     time::Duration,
 };
 use yansi::Paint;
@@ -32,12 +36,14 @@ pub struct TestOutcome {
     ///
     /// Note that `Address` fields only contain the last executed test case's data.
     pub decoder: Option<CallTraceDecoder>,
+    /// The finalized gas report, if one was requested.
+    pub gas_report: Option<GasReport>,
 }
 
 impl TestOutcome {
     /// Creates a new test outcome with the given results.
     pub fn new(results: BTreeMap<String, SuiteResult>, allow_failure: bool) -> Self {
-        Self { results, allow_failure, decoder: None }
+        Self { results, allow_failure, decoder: None, gas_report: None }
     }
 
     /// Creates a new empty test outcome.
@@ -189,6 +195,19 @@ pub struct SuiteResult {
     pub test_results: BTreeMap<String, TestResult>,
     /// Generated warnings.
     pub warnings: Vec<String>,
+    /// The calldata sizes approaching the EraVM limit, in ZK-VM calls and in the ABIs of the
+    /// contracts the suite imports.
+    #[serde(default)]
+    pub size_warnings: Vec<SizeLimitWarning>,
+    /// The invocations of the FFI cheatcodes by the tests of the suite, for the audit log.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ffi_invocations: Vec<FfiInvocation>,
+    /// The paths read by cheatcodes, if recorded.
+    #[serde(skip)]
+    pub read_paths: BTreeSet<PathBuf>,
+    /// The identifiers of the fixtures the suite ran on.
+    #[serde(skip)]
+    pub fixtures: Vec<String>,
 }
 
 impl SuiteResult {
@@ -197,7 +216,15 @@ impl SuiteResult {
         test_results: BTreeMap<String, TestResult>,
         warnings: Vec<String>,
     ) -> Self {
-        Self { duration, test_results, warnings }
+        Self {
+            duration,
+            test_results,
+            warnings,
+            size_warnings: Vec::new(),
+            ffi_invocations: Vec::new(),
+            read_paths: BTreeSet::new(),
+            fixtures: Vec::new(),
+        }
     }
 
     /// Returns an iterator over all individual succeeding tests and their names.
@@ -252,6 +279,21 @@ impl SuiteResult {
         self.test_results.values().map(|result| result.duration).sum()
     }
 
+    /// Sums up the ergs used by the tests executed on the ZK-VM, if any.
+    pub fn zk_ergs(&self) -> Option<u64> {
+        self.test_results.values().filter_map(|result| result.zk_ergs).reduce(|a, b| a + b)
+    }
+
+    /// Sums up the bytes of pubdata published by the tests executed on the ZK-VM, if any.
+    pub fn zk_pubdata(&self) -> Option<u64> {
+        self.test_results.values().filter_map(|result| result.zk_pubdata).reduce(|a, b| a + b)
+    }
+
+    /// Sums up the gas refunded to the tests executed on the ZK-VM, if any.
+    pub fn zk_gas_refunded(&self) -> Option<u64> {
+        self.test_results.values().filter_map(|result| result.zk_gas_refunded).reduce(|a, b| a + b)
+    }
+
     /// Returns the summary of a single test suite.
     pub fn summary(&self) -> String {
         let failed = self.failed();
@@ -288,6 +330,18 @@ impl SuiteTestResult {
         self.result.kind.report().gas()
     }
 
+    /// Returns the report of the test, with the ergs and pubdata of the ZK-VM executions of a
+    /// standard test instead of its EVM gas.
+    pub fn zk_report(&self) -> TestKindReport {
+        match (&self.result.kind, self.result.zk_ergs) {
+            (TestKind::Standard(_), Some(ergs)) => TestKindReport::ZkStandard {
+                ergs,
+                pubdata: self.result.zk_pubdata.unwrap_or_default(),
+            },
+            (kind, _) => kind.report(),
+        }
+    }
+
     /// Returns the contract name of the artifact ID.
     pub fn contract_name(&self) -> &str {
         get_contract_name(&self.artifact_id)
@@ -328,6 +382,22 @@ impl TestStatus {
     }
 }
 
+/// Why a test failed, when it isn't one of its own reverts or assertions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TestFailureKind {
+    /// A ZK-VM execution of the test was aborted for exceeding a configured resource limit.
+    ZkResourceLimit(ZkResourceLimitExceeded),
+}
+
+impl fmt::Display for TestFailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZkResourceLimit(_) => f.write_str("zk vm resource limit exceeded"),
+        }
+    }
+}
+
 /// The result of an executed test.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct TestResult {
@@ -341,6 +411,10 @@ pub struct TestResult {
     /// still be successful (i.e self.success == true) when it's expected to fail.
     pub reason: Option<String>,
 
+    /// Why a standard test failed, when it isn't one of its own reverts or assertions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_kind: Option<TestFailureKind>,
+
     /// Minimal reproduction test case for failing test
     pub counterexample: Option<CounterExample>,
 
@@ -365,6 +439,14 @@ pub struct TestResult {
     /// Labeled addresses
     pub labeled_addresses: HashMap<Address, String>,
 
+    /// The EIP-1967 proxies called while tracing, mapped to their implementation
+    #[serde(skip)]
+    pub proxies: HashMap<Address, Address>,
+
+    /// The calls made from the EVM to the ZK-VM, as `(caller, callee)` pairs
+    #[serde(skip)]
+    pub zk_calls: HashSet<(Address, Address)>,
+
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
 
@@ -372,6 +454,32 @@ pub struct TestResult {
 
     /// pc breakpoint char map
     pub breakpoints: Breakpoints,
+
+    /// The ergs used by the ZK-VM executions of a standard test, or the median ergs of the calls
+    /// of a fuzz or invariant test, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zk_ergs: Option<u64>,
+
+    /// The bytes of pubdata published by the ZK-VM executions of a standard test, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zk_pubdata: Option<u64>,
+
+    /// The gas refunded to the ZK-VM executions of a standard test, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zk_gas_refunded: Option<u64>,
+
+    /// The calls of the traces dropped once the test passed, in performance mode
+    #[serde(skip)]
+    pub dropped_trace_calls: usize,
+
+    /// Whether the test ran without tracing, in performance mode
+    #[serde(skip)]
+    pub untraced: bool,
+
+    /// The durations of the test run without and with tracing, if it was run again with tracing
+    /// to sample the overhead of tracing, in performance mode
+    #[serde(skip)]
+    pub tracing_sample: Option<(Duration, Duration)>,
 }
 
 impl fmt::Display for TestResult {
@@ -380,7 +488,11 @@ impl fmt::Display for TestResult {
             TestStatus::Success => Paint::green("[PASS]").fmt(f),
             TestStatus::Skipped => Paint::yellow("[SKIP]").fmt(f),
             TestStatus::Failure => {
-                let mut s = String::from("[FAIL. Reason: ");
+                let mut s = String::from("[FAIL. ");
+                if let Some(kind) = &self.failure_kind {
+                    write!(s, "{kind}. ").unwrap();
+                }
+                s.push_str("Reason: ");
 
                 let reason = self.reason.as_deref().unwrap_or("assertion failed");
                 s.push_str(reason);
@@ -412,6 +524,15 @@ impl TestResult {
         Self { status: TestStatus::Failure, reason: Some(reason), ..Default::default() }
     }
 
+    /// Drops the traces of a passing test, keeping the number of their calls.
+    pub fn drop_passing_traces(&mut self) {
+        if self.status != TestStatus::Success || self.traces.is_empty() {
+            return
+        }
+        self.dropped_trace_calls = self.traces.iter().map(|(_, arena)| arena.nodes().len()).sum();
+        self.traces = Vec::new();
+    }
+
     /// Returns `true` if this is the result of a fuzz test
     pub fn is_fuzz(&self) -> bool {
         matches!(self.kind, TestKind::Fuzz { .. })
@@ -419,16 +540,30 @@ impl TestResult {
 
     /// Formats the test result into a string (for printing).
     pub fn short_result(&self, name: &str) -> String {
-        format!("{self} {name} {}", self.kind.report())
+        let mut result = format!("{self} {name} {}", self.kind.report());
+        if let Some(ergs) = self.zk_ergs {
+            write!(result, " (ergs: {ergs}").unwrap();
+            if let Some(pubdata) = self.zk_pubdata {
+                write!(result, ", pubdata: {pubdata}").unwrap();
+            }
+            if let Some(refunded) = self.zk_gas_refunded {
+                write!(result, ", refunded: {refunded}").unwrap();
+            }
+            result.push(')');
+        }
+        result
     }
 }
 
 /// Data report by a test.
+///
+/// `ZkStandard` reports the ergs and pubdata of the ZK-VM executions of a standard test.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TestKindReport {
     Standard { gas: u64 },
     Fuzz { runs: usize, mean_gas: u64, median_gas: u64 },
     Invariant { runs: usize, calls: usize, reverts: usize },
+    ZkStandard { ergs: u64, pubdata: u64 },
 }
 
 impl fmt::Display for TestKindReport {
@@ -443,6 +578,9 @@ impl fmt::Display for TestKindReport {
             TestKindReport::Invariant { runs, calls, reverts } => {
                 write!(f, "(runs: {runs}, calls: {calls}, reverts: {reverts})")
             }
+            TestKindReport::ZkStandard { ergs, pubdata } => {
+                write!(f, "(ergs: {ergs}, pubdata: {pubdata})")
+            }
         }
     }
 }
@@ -456,6 +594,8 @@ impl TestKindReport {
             TestKindReport::Fuzz { median_gas, .. } => *median_gas,
             // We return 0 since it's not applicable
             TestKindReport::Invariant { .. } => 0,
+            // The ergs are the gas of the ZK-VM
+            TestKindReport::ZkStandard { ergs, .. } => *ergs,
         }
     }
 }
@@ -510,6 +650,10 @@ pub struct TestSetup {
     pub traces: Traces,
     /// Addresses labeled during setup
     pub labeled_addresses: HashMap<Address, String>,
+    /// The EIP-1967 proxies called during setup, mapped to their implementation
+    pub proxies: HashMap<Address, Address>,
+    /// The calls made from the EVM to the ZK-VM during setup, as `(caller, callee)` pairs
+    pub zk_calls: HashSet<(Address, Address)>,
     /// The reason the setup failed, if it did
     pub reason: Option<String>,
     /// Coverage info during setup
@@ -529,7 +673,12 @@ impl TestSetup {
                 traces.extend(err.traces.map(|traces| (TraceKind::Setup, traces)));
                 logs.extend(err.logs);
                 labeled_addresses.extend(err.labels);
-                Self::failed_with(logs, traces, labeled_addresses, err.reason)
+                let (proxies, zk_calls) = (err.proxies, err.zk_calls);
+                Self {
+                    proxies,
+                    zk_calls,
+                    ..Self::failed_with(logs, traces, labeled_addresses, err.reason)
+                }
             }
             e => Self::failed_with(
                 logs,
@@ -547,7 +696,16 @@ impl TestSetup {
         labeled_addresses: HashMap<Address, String>,
         coverage: Option<HitMaps>,
     ) -> Self {
-        Self { address, logs, traces, labeled_addresses, reason: None, coverage }
+        Self {
+            address,
+            logs,
+            traces,
+            labeled_addresses,
+            proxies: Default::default(),
+            zk_calls: Default::default(),
+            reason: None,
+            coverage,
+        }
     }
 
     pub fn failed_with(
@@ -561,6 +719,8 @@ impl TestSetup {
             logs,
             traces,
             labeled_addresses,
+            proxies: Default::default(),
+            zk_calls: Default::default(),
             reason: Some(reason),
             coverage: None,
         }
@@ -1,9 +1,12 @@
 //! The Forge test runner.
 
 use crate::{
-    result::{SuiteResult, TestKind, TestResult, TestSetup, TestStatus},
+    assume::{rejections_report, TestSourceMap},
+    events::{EventStream, TestEvent},
+    result::{SuiteResult, TestFailureKind, TestKind, TestResult, TestSetup, TestStatus},
     TestFilter, TestOptions,
 };
+use alloy_dyn_abi::DynSolValue;
 use alloy_json_abi::{Function, JsonAbi};
 use alloy_primitives::{Address, Bytes, U256};
 use eyre::Result;
@@ -11,23 +14,31 @@ use foundry_common::{
     contracts::{ContractsByAddress, ContractsByArtifact},
     TestFunctionExt,
 };
-use foundry_config::{FuzzConfig, InvariantConfig};
+use foundry_config::{FuzzConfig, InvariantConfig, TestBudgetConfig};
 use foundry_evm::{
+    backend::DatabaseExt,
     constants::CALLER,
-    coverage::HitMaps,
+    coverage::merge_coverages,
     decode::{decode_console_logs, RevertDecoder},
     executors::{
         fuzz::{CaseOutcome, CounterExampleOutcome, FuzzOutcome, FuzzedExecutor},
         invariant::{replay_run, InvariantExecutor, InvariantFuzzError, InvariantFuzzTestResult},
         CallResult, EvmError, ExecutionErr, Executor,
     },
-    fuzz::{invariant::InvariantContract, CounterExample},
+    fuzz::{
+        invariant::{CheckpointConfig, InvariantContract},
+        median_zk_ergs, CounterExample, FuzzError,
+    },
+    revm::primitives::Bytecode,
     traces::{load_contracts, TraceKind},
 };
+use foundry_zksync_core::era_state::EraState;
 use proptest::test_runner::{TestError, TestRunner};
 use rayon::prelude::*;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
     time::Instant,
 };
 
@@ -39,6 +50,8 @@ pub struct ContractRunner<'a> {
     pub executor: Executor,
     /// Library contracts to be deployed before the test contract
     pub predeploy_libs: &'a [Bytes],
+    /// Deployed code to set at the given addresses before the libraries are deployed
+    pub genesis: &'a [(Address, Bytes)],
     /// The deployed contract's code
     pub code: Bytes,
     /// The test contract's ABI
@@ -51,6 +64,15 @@ pub struct ContractRunner<'a> {
     pub sender: Address,
     /// Should generate debug traces
     pub debug: bool,
+    /// The source map of the deployed contract, used to locate the call sites of `vm.assume`
+    pub source_map: Option<&'a TestSourceMap>,
+    /// The era_test_node state to load before the test contract is deployed
+    pub era_state: Option<&'a EraState>,
+    /// Whether the test contract is deployed on the state of fixtures, which already used the
+    /// nonces of the sender
+    pub on_fixtures: bool,
+    /// The stream the results of the tests are emitted to as soon as they finish
+    pub event_stream: Option<&'a EventStream>,
 }
 
 impl<'a> ContractRunner<'a> {
@@ -64,6 +86,7 @@ impl<'a> ContractRunner<'a> {
         sender: Option<Address>,
         revert_decoder: &'a RevertDecoder,
         predeploy_libs: &'a [Bytes],
+        genesis: &'a [(Address, Bytes)],
         debug: bool,
     ) -> Self {
         Self {
@@ -75,12 +98,24 @@ impl<'a> ContractRunner<'a> {
             sender: sender.unwrap_or_default(),
             revert_decoder,
             predeploy_libs,
+            genesis,
             debug,
+            source_map: None,
+            era_state: None,
+            on_fixtures: false,
+            event_stream: None,
         }
     }
 }
 
 impl<'a> ContractRunner<'a> {
+    /// Emits the result of the test `sig` to the event stream, if any.
+    fn emit_finished(&self, sig: &str, result: &TestResult) {
+        if let Some(events) = self.event_stream {
+            events.emit(TestEvent::test_finished(self.name, sig, result));
+        }
+    }
+
     /// Deploys the test contract inside the runner from the sending account, and optionally runs
     /// the `setUp` function on the test contract.
     pub fn setup(&mut self, setup: bool) -> TestSetup {
@@ -93,12 +128,30 @@ impl<'a> ContractRunner<'a> {
     fn _setup(&mut self, setup: bool) -> Result<TestSetup> {
         info!(?setup, name = self.name, "Setting test contract");
 
+        // Load the state first, so it doesn't override the accounts set up for the test
+        if let Some(state) = self.era_state {
+            self.executor.load_era_state(state)?;
+        }
+
         // We max out their balance so that they can deploy and make calls.
         self.executor.set_balance(self.sender, U256::MAX)?;
         self.executor.set_balance(CALLER, U256::MAX)?;
 
-        // We set the nonce of the deployer accounts to 1 to get the same addresses as DappTools
-        self.executor.set_nonce(self.sender, 1)?;
+        // We set the nonce of the deployer accounts to 1 to get the same addresses as DappTools,
+        // unless the fixtures deployed from it
+        if !self.on_fixtures {
+            self.executor.set_nonce(self.sender, 1)?;
+        } else if !self.predeploy_libs.is_empty() {
+            // the libraries are linked at the addresses of the first nonces of the sender
+            eyre::bail!("{} depends on fixtures and can't deploy libraries", self.name);
+        }
+
+        // Set the code of the genesis contracts, they're persistent so they survive forks and get
+        // migrated to the zkEVM
+        for (address, code) in self.genesis {
+            self.executor.set_code(*address, Bytecode::new_raw(code.clone()).to_checked())?;
+            self.executor.backend.add_persistent_account(*address);
+        }
 
         // Deploy libraries
         let mut logs = Vec::new();
@@ -143,6 +196,10 @@ impl<'a> ContractRunner<'a> {
             }
         };
 
+        // The calls to the test contract are executed in the EVM in zk mode, and the fuzzed calls
+        // of the invariant tests to the target contracts in the ZK-VM
+        self.executor.inspector.set_zk_test_contract(address);
+
         // Reset `self.sender`s and `CALLER`s balance to the initial balance we want
         self.executor.set_balance(self.sender, self.initial_balance)?;
         self.executor.set_balance(CALLER, self.initial_balance)?;
@@ -152,16 +209,21 @@ impl<'a> ContractRunner<'a> {
         // Optionally call the `setUp` function
         let setup = if setup {
             trace!("setting up");
-            let (setup_logs, setup_traces, labeled_addresses, reason, coverage) =
+            let (setup_logs, setup_traces, labeled_addresses, proxies, zk_calls, reason, coverage) =
                 match self.executor.setup(None, address) {
-                    Ok(CallResult { traces, labels, logs, coverage, .. }) => {
+                    Ok(CallResult {
+                        traces, labels, proxies, zk_calls, logs, coverage, ..
+                    }) => {
                         trace!(contract=%address, "successfully setUp test");
-                        (logs, traces, labels, None, coverage)
+                        (logs, traces, labels, proxies, zk_calls, None, coverage)
                     }
                     Err(EvmError::Execution(err)) => {
-                        let ExecutionErr { traces, labels, logs, reason, .. } = *err;
+                        let ExecutionErr {
+                            traces, labels, proxies, zk_calls, logs, reason, ..
+                        } = *err;
                         error!(reason=%reason, contract=%address, "setUp failed {}", self.name);
-                        (logs, traces, labels, Some(format!("setup failed: {reason}")), None)
+                        let reason = Some(format!("setup failed: {reason}"));
+                        (logs, traces, labels, proxies, zk_calls, reason, None)
                     }
                     Err(err) => {
                         error!(reason=%err, contract=%address, "setUp failed");
@@ -169,6 +231,8 @@ impl<'a> ContractRunner<'a> {
                             Vec::new(),
                             None,
                             HashMap::new(),
+                            HashMap::new(),
+                            HashSet::new(),
                             Some(format!("setup failed {}: {err}", self.name)),
                             None,
                         )
@@ -177,7 +241,16 @@ impl<'a> ContractRunner<'a> {
             traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)));
             logs.extend(setup_logs);
 
-            TestSetup { address, logs, traces, labeled_addresses, reason, coverage }
+            TestSetup {
+                address,
+                logs,
+                traces,
+                labeled_addresses,
+                proxies,
+                zk_calls,
+                reason,
+                coverage,
+            }
         } else {
             TestSetup::success(address, logs, traces, Default::default(), None)
         };
@@ -213,10 +286,11 @@ impl<'a> ContractRunner<'a> {
 
         // There are multiple setUp function, so we return a single test result for `setUp`
         if setup_fns.len() > 1 {
+            let result = TestResult::fail("multiple setUp functions".to_string());
+            self.emit_finished("setUp()", &result);
             return SuiteResult::new(
                 start.elapsed(),
-                [("setUp()".to_string(), TestResult::fail("multiple setUp functions".to_string()))]
-                    .into(),
+                [("setUp()".to_string(), result)].into(),
                 warnings,
             )
         }
@@ -235,32 +309,38 @@ impl<'a> ContractRunner<'a> {
 
         if setup.reason.is_some() {
             // The setup failed, so we return a single test result for `setUp`
+            let result = failed_setup_result(setup);
+            self.emit_finished("setUp()", &result);
             return SuiteResult::new(
                 start.elapsed(),
-                [(
-                    "setUp()".to_string(),
-                    TestResult {
-                        status: TestStatus::Failure,
-                        reason: setup.reason,
-                        counterexample: None,
-                        decoded_logs: decode_console_logs(&setup.logs),
-                        logs: setup.logs,
-                        kind: TestKind::Standard(0),
-                        traces: setup.traces,
-                        coverage: setup.coverage,
-                        labeled_addresses: setup.labeled_addresses,
-                        ..Default::default()
-                    },
-                )]
-                .into(),
+                [("setUp()".to_string(), result)].into(),
                 warnings,
             )
         }
 
+        // `name` is the contract's identifier, `<path>:<contract>`
+        let (path, contract) = self.name.rsplit_once(':').unwrap_or(("", self.name));
+        let matches_test = |func: &Function| {
+            filter.matches_qualified_test(Path::new(path), contract, &func.signature())
+        };
+
+        // In performance mode the unit tests run without tracing, and only the failing ones run
+        // again with tracing for their traces to be printed. The first passing one runs again too,
+        // to sample the overhead of tracing. Fuzz tests are always traced, as running them again
+        // may not find their counterexample.
+        let untraced = (test_options.drop_passing_traces &&
+            self.executor.inspector.tracer.is_some())
+        .then(|| {
+            let mut runner = self.clone();
+            runner.executor.set_tracing(false);
+            runner
+        });
+        let sampled = AtomicBool::new(false);
+
         let functions: Vec<_> = self.contract.functions().collect();
-        let mut test_results = functions
+        let mut finished: Vec<_> = functions
             .par_iter()
-            .filter(|&&func| func.is_test() && filter.matches_test(&func.signature()))
+            .filter(|&&func| func.is_test() && matches_test(func))
             .map(|&func| {
                 let should_fail = func.is_test_fail();
                 let res = if func.is_fuzz_test() {
@@ -268,34 +348,61 @@ impl<'a> ContractRunner<'a> {
                     let fuzz_config = test_options.fuzz_config(self.name, &func.name);
                     info!(name = func.name, "run fuzz test");
                     self.run_fuzz_test(func, should_fail, runner, setup.clone(), *fuzz_config)
+                } else if let Some(untraced) = &untraced {
+                    info!(name = func.name, "run test without tracing");
+                    let mut res = untraced.run_test(func, should_fail, setup.clone());
+                    res.untraced = true;
+                    let sample =
+                        res.status == TestStatus::Success && !sampled.swap(true, Ordering::Relaxed);
+                    if res.status == TestStatus::Failure || sample {
+                        info!(name = func.name, "run test");
+                        let mut traced = self.run_test(func, should_fail, setup.clone());
+                        if sample && traced.status == TestStatus::Success {
+                            traced.tracing_sample = Some((res.duration, traced.duration));
+                        }
+                        res = traced;
+                    }
+                    res
                 } else {
                     info!(name = func.name, "run test");
                     self.run_test(func, should_fail, setup.clone())
                 };
-                (func.signature(), res)
+                let finished = finish_test(&test_options, self.name, func.signature(), res);
+                self.emit_finished(&finished.0, &finished.1);
+                finished
             })
-            .collect::<BTreeMap<_, _>>();
+            .collect();
 
         if has_invariants {
             let identified_contracts = load_contracts(setup.traces.clone(), known_contracts);
             let results: Vec<_> = functions
                 .par_iter()
-                .filter(|&&func| func.is_invariant_test() && filter.matches_test(&func.signature()))
+                .filter(|&&func| func.is_invariant_test() && matches_test(func))
                 .map(|&func| {
                     let runner = test_options.invariant_runner(self.name, &func.name);
                     let invariant_config = test_options.invariant_config(self.name, &func.name);
+                    let checkpoint = test_options.invariant_checkpoint(self.name, &func.name);
                     let res = self.run_invariant_test(
                         runner,
                         setup.clone(),
-                        *invariant_config,
+                        invariant_config.clone(),
+                        checkpoint,
                         func,
                         known_contracts,
                         &identified_contracts,
                     );
-                    (func.signature(), res)
+                    let finished = finish_test(&test_options, self.name, func.signature(), res);
+                    self.emit_finished(&finished.0, &finished.1);
+                    finished
                 })
                 .collect();
-            test_results.extend(results);
+            finished.extend(results);
+        }
+
+        let mut test_results = BTreeMap::new();
+        for (sig, result, warning) in finished {
+            warnings.extend(warning);
+            test_results.insert(sig, result);
         }
 
         let duration = start.elapsed();
@@ -313,6 +420,17 @@ impl<'a> ContractRunner<'a> {
         SuiteResult::new(duration, test_results, warnings)
     }
 
+    /// Deploys the test contract, runs its `setUp` function if any, and then the test `func` once
+    /// with the arguments `args`
+    pub fn run_single_test(&mut self, func: &Function, args: Vec<DynSolValue>) -> TestResult {
+        let needs_setup = self.contract.functions().any(|func| func.name == "setUp");
+        let setup = self.setup(needs_setup);
+        if setup.reason.is_some() {
+            return failed_setup_result(setup)
+        }
+        self.run_test_with_args(func, func.is_test_fail(), setup, args)
+    }
+
     /// Runs a single test
     ///
     /// Calls the given functions and returns the `TestResult`.
@@ -320,6 +438,19 @@ impl<'a> ContractRunner<'a> {
     /// State modifications are not committed to the evm database but discarded after the call,
     /// similar to `eth_call`.
     pub fn run_test(&self, func: &Function, should_fail: bool, setup: TestSetup) -> TestResult {
+        self.run_test_with_args(func, should_fail, setup, vec![])
+    }
+
+    /// Runs a single test with the arguments `args`
+    ///
+    /// The same as [`run_test`](Self::run_test), for test functions with parameters.
+    pub fn run_test_with_args(
+        &self,
+        func: &Function,
+        should_fail: bool,
+        setup: TestSetup,
+        args: Vec<DynSolValue>,
+    ) -> TestResult {
         let span = info_span!("test", %should_fail);
         if !span.is_disabled() {
             let sig = &func.signature()[..];
@@ -332,83 +463,123 @@ impl<'a> ContractRunner<'a> {
         let _guard = span.enter();
 
         let TestSetup {
-            address, mut logs, mut traces, mut labeled_addresses, mut coverage, ..
+            address,
+            mut logs,
+            mut traces,
+            mut labeled_addresses,
+            mut proxies,
+            mut zk_calls,
+            mut coverage,
+            ..
         } = setup;
 
         // Run unit test
         let mut executor = self.executor.clone();
         let start = Instant::now();
         let debug_arena;
-        let (reverted, reason, gas, stipend, coverage, state_changeset, breakpoints) =
-            match executor.execute_test::<_, _>(
-                self.sender,
-                address,
-                func.clone(),
-                vec![],
-                U256::ZERO,
-                Some(self.revert_decoder),
-            ) {
-                Ok(CallResult {
+        let (
+            reverted,
+            reason,
+            gas,
+            stipend,
+            coverage,
+            state_changeset,
+            breakpoints,
+            zk_ergs,
+            zk_metrics,
+            zk_limit_exceeded,
+        ) = match executor.execute_test::<_, _>(
+            self.sender,
+            address,
+            func.clone(),
+            args,
+            U256::ZERO,
+            Some(self.revert_decoder),
+        ) {
+            Ok(CallResult {
+                reverted,
+                gas_used: gas,
+                stipend,
+                logs: execution_logs,
+                traces: execution_trace,
+                coverage: execution_coverage,
+                labels: new_labels,
+                proxies: new_proxies,
+                zk_calls: new_zk_calls,
+                state_changeset,
+                debug,
+                breakpoints,
+                zk_ergs_used,
+                zk_metrics,
+                zk_limit_exceeded,
+                ..
+            }) => {
+                traces.extend(execution_trace.map(|traces| (TraceKind::Execution, traces)));
+                labeled_addresses.extend(new_labels);
+                proxies.extend(new_proxies);
+                zk_calls.extend(new_zk_calls);
+                logs.extend(execution_logs);
+                debug_arena = debug;
+                coverage = merge_coverages(coverage, execution_coverage);
+
+                (
                     reverted,
-                    gas_used: gas,
+                    None,
+                    gas,
                     stipend,
-                    logs: execution_logs,
-                    traces: execution_trace,
-                    coverage: execution_coverage,
-                    labels: new_labels,
+                    coverage,
                     state_changeset,
-                    debug,
                     breakpoints,
-                    ..
-                }) => {
-                    traces.extend(execution_trace.map(|traces| (TraceKind::Execution, traces)));
-                    labeled_addresses.extend(new_labels);
-                    logs.extend(execution_logs);
-                    debug_arena = debug;
-                    coverage = merge_coverages(coverage, execution_coverage);
-
-                    (reverted, None, gas, stipend, coverage, state_changeset, breakpoints)
-                }
-                Err(EvmError::Execution(err)) => {
-                    traces.extend(err.traces.map(|traces| (TraceKind::Execution, traces)));
-                    labeled_addresses.extend(err.labels);
-                    logs.extend(err.logs);
-                    debug_arena = err.debug;
-                    (
-                        err.reverted,
-                        Some(err.reason),
-                        err.gas_used,
-                        err.stipend,
-                        None,
-                        err.state_changeset,
-                        HashMap::new(),
-                    )
-                }
-                Err(EvmError::SkipError) => {
-                    return TestResult {
-                        status: TestStatus::Skipped,
-                        reason: None,
-                        decoded_logs: decode_console_logs(&logs),
-                        traces,
-                        labeled_addresses,
-                        kind: TestKind::Standard(0),
-                        duration: start.elapsed(),
-                        ..Default::default()
-                    }
+                    zk_ergs_used,
+                    zk_metrics,
+                    zk_limit_exceeded,
+                )
+            }
+            Err(EvmError::Execution(err)) => {
+                traces.extend(err.traces.map(|traces| (TraceKind::Execution, traces)));
+                labeled_addresses.extend(err.labels);
+                proxies.extend(err.proxies);
+                zk_calls.extend(err.zk_calls);
+                logs.extend(err.logs);
+                debug_arena = err.debug;
+                (
+                    err.reverted,
+                    Some(err.reason),
+                    err.gas_used,
+                    err.stipend,
+                    None,
+                    err.state_changeset,
+                    HashMap::new(),
+                    None,
+                    None,
+                    err.zk_limit_exceeded,
+                )
+            }
+            Err(EvmError::SkipError) => {
+                return TestResult {
+                    status: TestStatus::Skipped,
+                    reason: None,
+                    decoded_logs: decode_console_logs(&logs),
+                    traces,
+                    labeled_addresses,
+                    kind: TestKind::Standard(0),
+                    duration: start.elapsed(),
+                    ..Default::default()
                 }
-                Err(err) => {
-                    return TestResult {
-                        status: TestStatus::Failure,
-                        reason: Some(err.to_string()),
-                        decoded_logs: decode_console_logs(&logs),
-                        traces,
-                        labeled_addresses,
-                        kind: TestKind::Standard(0),
-                        duration: start.elapsed(),
-                        ..Default::default()
-                    }
+            }
+            Err(err) => {
+                return TestResult {
+                    status: TestStatus::Failure,
+                    reason: Some(err.to_string()),
+                    decoded_logs: decode_console_logs(&logs),
+                    traces,
+                    labeled_addresses,
+                    kind: TestKind::Standard(0),
+                    duration: start.elapsed(),
+                    ..Default::default()
                 }
-            };
+            }
+        };
 
         let success = executor.is_success(
             setup.address,
@@ -427,6 +598,9 @@ impl<'a> ContractRunner<'a> {
                 false => TestStatus::Failure,
             },
             reason,
+            failure_kind: zk_limit_exceeded
+                .filter(|_| !success)
+                .map(TestFailureKind::ZkResourceLimit),
             counterexample: None,
             decoded_logs: decode_console_logs(&logs),
             logs,
@@ -434,9 +608,17 @@ impl<'a> ContractRunner<'a> {
             traces,
             coverage,
             labeled_addresses,
+            proxies,
+            zk_calls,
             debug: debug_arena,
             breakpoints,
             duration,
+            zk_ergs,
+            zk_pubdata: zk_metrics.map(|metrics| metrics.pubdata_published),
+            zk_gas_refunded: zk_metrics.map(|metrics| metrics.gas_refunded),
+            dropped_trace_calls: 0,
+            untraced: false,
+            tracing_sample: None,
         }
     }
 
@@ -446,6 +628,7 @@ impl<'a> ContractRunner<'a> {
         runner: TestRunner,
         setup: TestSetup,
         invariant_config: InvariantConfig,
+        checkpoint: Option<CheckpointConfig>,
         func: &Function,
         known_contracts: Option<&ContractsByArtifact>,
         identified_contracts: &ContractsByAddress,
@@ -478,20 +661,28 @@ impl<'a> ContractRunner<'a> {
             }
         };
 
+        let checkpointed = checkpoint.is_some();
         let mut evm = InvariantExecutor::new(
             self.executor.clone(),
             runner,
             invariant_config,
             identified_contracts,
             project_contracts,
-        );
+        )
+        .with_checkpoint(checkpoint);
 
         let invariant_contract =
             InvariantContract { address, invariant_function: func, abi: self.contract };
 
-        let InvariantFuzzTestResult { error, cases, reverts, last_run_inputs } = match evm
-            .invariant_fuzz(invariant_contract.clone())
-        {
+        let InvariantFuzzTestResult {
+            error,
+            cases,
+            reverts,
+            last_run_inputs,
+            coverage: campaign_coverage,
+            resumed_runs,
+            resumed_calls,
+        } = match evm.invariant_fuzz(invariant_contract.clone()) {
             Ok(x) => x,
             Err(e) => {
                 return TestResult {
@@ -514,7 +705,9 @@ impl<'a> ContractRunner<'a> {
         let reason = error
             .as_ref()
             .and_then(|err| (!err.revert_reason.is_empty()).then(|| err.revert_reason.clone()));
-        let mut coverage = coverage.clone();
+        // The coverage of a checkpointed campaign already includes its last run
+        let mut coverage = merge_coverages(coverage.clone(), campaign_coverage);
+        let mut last_run_coverage = None;
         match error {
             // If invariants were broken, replay the error to collect logs and traces
             Some(error @ InvariantFuzzError { test_error: TestError::Fail(_, _), .. }) => {
@@ -542,12 +735,15 @@ impl<'a> ContractRunner<'a> {
                     identified_contracts.clone(),
                     &mut logs,
                     &mut traces,
-                    &mut coverage,
+                    &mut last_run_coverage,
                     func.clone(),
                     last_run_inputs.clone(),
                 );
             }
         }
+        if !checkpointed {
+            coverage = merge_coverages(coverage, last_run_coverage);
+        }
 
         TestResult {
             status: match success {
@@ -559,14 +755,18 @@ impl<'a> ContractRunner<'a> {
             decoded_logs: decode_console_logs(&logs),
             logs,
             kind: TestKind::Invariant {
-                runs: cases.len(),
-                calls: cases.iter().map(|sequence| sequence.cases().len()).sum(),
+                runs: resumed_runs + cases.len(),
+                calls: resumed_calls +
+                    cases.iter().map(|sequence| sequence.cases().len()).sum::<usize>(),
                 reverts,
             },
             coverage,
             traces,
             labeled_addresses: labeled_addresses.clone(),
             duration: start.elapsed(),
+            zk_ergs: median_zk_ergs(
+                cases.iter().flat_map(|sequence| sequence.cases()).map(|case| case.zk_ergs),
+            ),
             ..Default::default() // TODO collect debug traces on the last run or error
         }
     }
@@ -592,7 +792,14 @@ impl<'a> ContractRunner<'a> {
         let _guard = span.enter();
 
         let TestSetup {
-            address, mut logs, mut traces, mut labeled_addresses, mut coverage, ..
+            address,
+            mut logs,
+            mut traces,
+            mut labeled_addresses,
+            mut proxies,
+            mut zk_calls,
+            mut coverage,
+            ..
         } = setup;
 
         // Run fuzz test
@@ -600,7 +807,17 @@ impl<'a> ContractRunner<'a> {
         let fuzzed_executor =
             FuzzedExecutor::new(self.executor.clone(), runner.clone(), self.sender, fuzz_config);
         let state = fuzzed_executor.build_fuzz_state();
-        let result = fuzzed_executor.fuzz(func, address, should_fail, self.revert_decoder);
+        let mut result = fuzzed_executor.fuzz(func, address, should_fail, self.revert_decoder);
+
+        // Break down the rejections by call site if `vm.assume` rejected too many inputs
+        let too_many_rejects =
+            FuzzError::TooManyRejects(runner.config().max_global_rejects).to_string();
+        if result.reason.as_ref() == Some(&too_many_rejects) && !result.assume_rejections.is_empty()
+        {
+            let report =
+                rejections_report(&result.assume_rejections, func, address, self.source_map);
+            result.reason = Some(format!("{too_many_rejects}\n{report}"));
+        }
 
         let mut debug = Default::default();
         let mut breakpoints = Default::default();
@@ -661,6 +878,7 @@ impl<'a> ContractRunner<'a> {
             };
         }
 
+        let zk_ergs = result.median_zk_ergs();
         let kind = TestKind::Fuzz {
             median_gas: result.median_gas(false),
             mean_gas: result.mean_gas(false),
@@ -671,6 +889,8 @@ impl<'a> ContractRunner<'a> {
         // Record logs, labels and traces
         logs.extend(result.logs);
         labeled_addresses.extend(result.labeled_addresses);
+        proxies.extend(result.proxies);
+        zk_calls.extend(result.zk_calls);
         traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)));
         coverage = merge_coverages(coverage, result.coverage);
 
@@ -684,6 +904,7 @@ impl<'a> ContractRunner<'a> {
                 false => TestStatus::Failure,
             },
             reason: result.reason,
+            failure_kind: None,
             counterexample: result.counterexample,
             decoded_logs: decode_console_logs(&logs),
             logs,
@@ -691,20 +912,72 @@ impl<'a> ContractRunner<'a> {
             traces,
             coverage,
             labeled_addresses,
+            proxies,
+            zk_calls,
             debug,
             breakpoints,
             duration,
+            zk_ergs,
+            zk_pubdata: None,
+            zk_gas_refunded: None,
+            dropped_trace_calls: 0,
+            untraced: false,
+            tracing_sample: None,
         }
     }
 }
 
-/// Utility function to merge coverage options
-fn merge_coverages(mut coverage: Option<HitMaps>, other: Option<HitMaps>) -> Option<HitMaps> {
-    let old_coverage = std::mem::take(&mut coverage);
-    match (old_coverage, other) {
-        (Some(old_coverage), Some(other)) => Some(old_coverage.merge(other)),
-        (None, Some(other)) => Some(other),
-        (Some(old_coverage), None) => Some(old_coverage),
-        (None, None) => None,
+/// Finishes the test `sig` of the contract `name` as soon as it ran, before the other tests of
+/// its suite are collected: enforces its execution time budget, whole fuzz and invariant
+/// campaigns included, then drops its traces if it passed and `drop_passing_traces` is set.
+fn finish_test(
+    test_options: &TestOptions,
+    name: &str,
+    sig: String,
+    mut result: TestResult,
+) -> (String, TestResult, Option<String>) {
+    let func = sig.split('(').next().unwrap_or(&sig);
+    let warning = enforce_budget(test_options.budget_config(name, func), &sig, &mut result);
+    if test_options.drop_passing_traces {
+        result.drop_passing_traces();
+    }
+    (sig, result, warning)
+}
+
+/// Checks the wall time of a test against its budget.
+///
+/// Successful tests exceeding a failing budget are marked as failed, while a warning is returned
+/// for tests exceeding a warn-only budget.
+fn enforce_budget(budget: &TestBudgetConfig, sig: &str, result: &mut TestResult) -> Option<String> {
+    if result.status == TestStatus::Skipped {
+        return None
+    }
+    let max = budget.exceeded_by(result.duration)?;
+    let took = result.duration;
+    if budget.warn_only {
+        return Some(format!("{sig} exceeded its max duration of {max} (took {took:.2?})"))
+    }
+    if result.status == TestStatus::Success {
+        result.status = TestStatus::Failure;
+        result.reason = Some(format!("test exceeded its max duration of {max} (took {took:.2?})"));
+    }
+    None
+}
+
+/// Returns the result of the `setUp` function of a test contract that failed.
+fn failed_setup_result(setup: TestSetup) -> TestResult {
+    TestResult {
+        status: TestStatus::Failure,
+        reason: setup.reason,
+        counterexample: None,
+        decoded_logs: decode_console_logs(&setup.logs),
+        logs: setup.logs,
+        kind: TestKind::Standard(0),
+        traces: setup.traces,
+        coverage: setup.coverage,
+        labeled_addresses: setup.labeled_addresses,
+        proxies: setup.proxies,
+        zk_calls: setup.zk_calls,
+        ..Default::default()
     }
 }
@@ -0,0 +1,522 @@
+//! Programmatic execution of Solidity scripts.
+//!
+//! [ScriptExecutor] compiles a project, executes one of its scripts in a local EVM, or a fork of
+//! `fork_url`, and returns the transactions the script broadcasts without sending them, so
+//! deployment tooling can sign and send them on its own terms.
+//!
+//! ```no_run
+//! use forge::{opts::EvmOpts, script::ScriptExecutor};
+//! use foundry_config::Config;
+//!
+//! # async fn run() -> eyre::Result<()> {
+//! let figment = Config::figment();
+//! let config = Config::from_provider(&figment);
+//! let evm_opts = figment.extract::<EvmOpts>()?;
+//! let output = ScriptExecutor::new(config, evm_opts, "script/Deploy.s.sol:Deploy")
+//!     .sig("run(uint256)")
+//!     .args(["42"])
+//!     .execute()
+//!     .await?;
+//! for tx in output.transactions {
+//!     println!("{:?}", tx.request);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::link::{LinkOutput, Linker};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{hex, Address, Bytes, Log};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
+    TransactionRequest as EthersTransactionRequest,
+};
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    abi::{encode_function_args, get_func},
+    compile::ProjectCompiler,
+    evm::Breakpoints,
+    provider::ethers::RpcUrl,
+    types::ToEthers,
+    SELECTOR_LEN,
+};
+use foundry_compilers::{contracts::ArtifactContracts, Artifact, ArtifactId};
+use foundry_config::{Chain, Config};
+use foundry_evm::{
+    backend::Backend,
+    debug::DebugArena,
+    executors::ExecutorBuilder,
+    inspectors::{
+        cheatcodes::{
+            BroadcastableTransaction, BroadcastableTransactions, BroadcastableUserOperation,
+            FfiInvocation,
+        },
+        CheatsConfig,
+    },
+    opts::EvmOpts,
+    traces::Traces,
+};
+use foundry_zksync_compiler::{order_libraries, DualCompiledContracts, ZkSolc};
+use foundry_zksync_core::eip712_meta;
+use std::{collections::HashMap, path::Path};
+use zksync_web3_rs::{eip712::Eip712TransactionRequest, zks_utils::EIP712_TX_TYPE};
+
+mod runner;
+pub use runner::{ScriptRunner, SimulationStage};
+
+/// The result of executing a script, or one of the transactions it broadcasts.
+#[derive(Default)]
+pub struct ScriptResult {
+    /// Whether the execution succeeded.
+    pub success: bool,
+    /// The logs emitted during the execution.
+    pub logs: Vec<Log>,
+    /// The traces of the execution.
+    pub traces: Traces,
+    /// The debug nodes of the execution, if debugging was enabled.
+    pub debug: Option<Vec<DebugArena>>,
+    /// The gas used by the execution.
+    pub gas_used: u64,
+    /// The labels assigned to addresses with `vm.label`.
+    pub labeled_addresses: HashMap<Address, String>,
+    /// The EIP-1967 proxies called during the execution, mapped to their implementation.
+    pub proxies: HashMap<Address, Address>,
+    /// The transactions broadcast during the execution.
+    pub transactions: Option<BroadcastableTransactions>,
+    /// The user operations recorded with `vm.broadcastUserOp`.
+    pub user_operations: Vec<BroadcastableUserOperation>,
+    /// The data returned by the execution.
+    pub returned: Bytes,
+    /// The address of the created contract, if the execution was a deployment.
+    pub address: Option<Address>,
+    /// The breakpoints set with `vm.breakpoint`.
+    pub breakpoints: Breakpoints,
+    /// The invocations of the FFI cheatcodes, for the audit log.
+    pub ffi_invocations: Vec<FfiInvocation>,
+    /// The addresses of the configured zkSync libraries that weren't deployed, in deployment
+    /// order. Their code is set for the execution, and they're deployed ahead of the
+    /// transactions when broadcasting.
+    pub zk_libraries: Vec<Address>,
+}
+
+/// A transaction request broadcast by a script.
+///
+/// Gas limits, fees and chain IDs are left unset, to be filled in by the sender.
+#[derive(Clone, Debug)]
+pub enum ScriptTransactionRequest {
+    /// A transaction sent to an EVM chain.
+    Evm(TypedTransaction),
+    /// A zkSync Era EIP-712 transaction (type `0x71`), carrying the factory deps of the
+    /// contracts it deploys.
+    Eip712(Eip712TransactionRequest),
+}
+
+/// A transaction broadcast by a script, along with the fork it's broadcast to.
+#[derive(Clone, Debug)]
+pub struct ScriptTransaction {
+    /// The RPC URL of the fork the transaction was broadcast on, if any.
+    pub rpc: Option<RpcUrl>,
+    /// The transaction request.
+    pub request: ScriptTransactionRequest,
+}
+
+impl ScriptTransaction {
+    /// Converts a transaction broadcast by a script into an EIP-1559 transaction, a legacy one if
+    /// `legacy` is set, or an EIP-712 transaction if it was broadcast in zkSync mode.
+    pub fn new(tx: BroadcastableTransaction, legacy: bool) -> Self {
+        let BroadcastableTransaction { rpc, transaction, zk_tx, .. } = tx;
+        let from = transaction.from.map(ToEthers::to_ethers);
+        let to = transaction.to.map(ToEthers::to_ethers);
+        let value = transaction.value.map(ToEthers::to_ethers);
+        let data = transaction.input.into_input().map(ToEthers::to_ethers);
+        let nonce = transaction.nonce.map(|n| n.to::<u64>());
+        let gas = transaction.gas.map(ToEthers::to_ethers);
+
+        let request = match zk_tx {
+            Some(zk_tx) => {
+                let mut request = Eip712TransactionRequest::new()
+                    .r#type(EIP712_TX_TYPE)
+                    .custom_data(eip712_meta(&zk_tx));
+                if let Some(from) = from {
+                    request = request.from(from);
+                }
+                if let Some(to) = to {
+                    request = request.to(to);
+                }
+                if let Some(value) = value {
+                    request = request.value(value);
+                }
+                if let Some(data) = data {
+                    request = request.data(data);
+                }
+                if let Some(nonce) = nonce {
+                    request = request.nonce(nonce);
+                }
+                if let Some(gas) = gas {
+                    request = request.gas_limit(gas);
+                }
+                ScriptTransactionRequest::Eip712(request)
+            }
+            None if legacy => {
+                ScriptTransactionRequest::Evm(TypedTransaction::Legacy(EthersTransactionRequest {
+                    from,
+                    to: to.map(Into::into),
+                    value,
+                    data,
+                    nonce: nonce.map(Into::into),
+                    gas,
+                    ..Default::default()
+                }))
+            }
+            None => ScriptTransactionRequest::Evm(TypedTransaction::Eip1559(
+                Eip1559TransactionRequest {
+                    from,
+                    to: to.map(Into::into),
+                    value,
+                    data,
+                    nonce: nonce.map(Into::into),
+                    gas,
+                    ..Default::default()
+                },
+            )),
+        };
+
+        Self { rpc, request }
+    }
+}
+
+/// The outcome of a script executed with [ScriptExecutor].
+#[derive(Default)]
+pub struct ScriptOutput {
+    /// Whether `setUp()` and the script function succeeded.
+    pub success: bool,
+    /// The data returned by the script function.
+    pub returned: Bytes,
+    /// The gas used by the script function.
+    pub gas_used: u64,
+    /// The logs emitted by the script.
+    pub logs: Vec<Log>,
+    /// The labels assigned to addresses with `vm.label`.
+    pub labeled_addresses: HashMap<Address, String>,
+    /// The transactions broadcast by the script, in order.
+    pub transactions: Vec<ScriptTransaction>,
+    /// The user operations recorded with `vm.broadcastUserOp`.
+    pub user_operations: Vec<BroadcastableUserOperation>,
+    /// The invocations of the FFI cheatcodes by `setUp()` and the script function.
+    pub ffi_invocations: Vec<FfiInvocation>,
+    /// The addresses of the configured zkSync libraries that aren't deployed yet, in deployment
+    /// order. Their code is set for the execution, and they must be deployed ahead of the
+    /// transactions.
+    pub zk_libraries: Vec<Address>,
+}
+
+/// Compiles a project and executes one of its scripts, collecting the transactions it broadcasts.
+///
+/// This is the library counterpart of `forge script` without `--broadcast`: nothing is signed or
+/// sent, and the transactions are not simulated on-chain. Both encode the call with
+/// [encode_calldata] and deploy and call the script with [ScriptRunner::run].
+#[derive(Clone, Debug)]
+#[must_use = "executors do nothing unless you call `execute` on them"]
+pub struct ScriptExecutor {
+    config: Config,
+    evm_opts: EvmOpts,
+    target: String,
+    sig: String,
+    args: Vec<String>,
+    legacy: bool,
+}
+
+impl ScriptExecutor {
+    /// Creates an executor for the script `target` of the project configured by `config`.
+    ///
+    /// The target is either the path of the script relative to the project root, optionally
+    /// followed by `:<ContractName>`, or the name of the script contract.
+    pub fn new(config: Config, evm_opts: EvmOpts, target: impl Into<String>) -> Self {
+        Self {
+            config,
+            evm_opts,
+            target: target.into(),
+            sig: "run()".to_string(),
+            args: vec![],
+            legacy: false,
+        }
+    }
+
+    /// Sets the signature of the function to call, or raw hex encoded calldata. Defaults to
+    /// `run()`.
+    pub fn sig(mut self, sig: impl Into<String>) -> Self {
+        self.sig = sig.into();
+        self
+    }
+
+    /// Sets the arguments of the function to call.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns legacy transactions instead of EIP-1559 ones. Transactions broadcast to chains
+    /// which don't support EIP-1559 are always legacy ones.
+    pub fn legacy(mut self, legacy: bool) -> Self {
+        self.legacy = legacy;
+        self
+    }
+
+    /// Compiles the project, deploys the script and calls it.
+    pub async fn execute(self) -> Result<ScriptOutput> {
+        let Self { mut config, evm_opts, target, sig, args, legacy } = self;
+        let sender = evm_opts.sender;
+
+        let sender_nonce = match &evm_opts.fork_url {
+            Some(fork_url) => crate::next_nonce(sender, fork_url, None).await?,
+            None => {
+                // without a fork, pre-deployed library addresses don't exist
+                config.libraries = Default::default();
+                // dapptools compatibility
+                1
+            }
+        };
+
+        let project = config.project()?;
+        let output = ProjectCompiler::new().quiet(true).compile(&project)?;
+        let root = project.root();
+        let output = output.with_stripped_file_prefixes(root);
+
+        let dual_compiled_contracts = if config.zksync {
+            let mut zksolc = ZkSolc::new(
+                config
+                    .new_zksolc_config_builder()
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| eyre::eyre!(e))?,
+                config.zk_project()?,
+            );
+            let (zk_output, _) =
+                zksolc.compile().map_err(|e| eyre::eyre!("Failed to compile with zksolc: {e}"))?;
+            DualCompiledContracts::new(&output, &zk_output)
+        } else {
+            DualCompiledContracts::default()
+        };
+
+        // The configured libraries zksolc linked the script with, set if they aren't deployed
+        let mut zk_libraries = Vec::new();
+        if config.zksync {
+            for library in config.zk_libraries()? {
+                let id = library.identifier();
+                let code = output
+                    .find(&library.path.to_string_lossy(), &library.name)
+                    .and_then(|artifact| artifact.get_deployed_bytecode_bytes())
+                    .filter(|code| !code.is_empty())
+                    .ok_or_else(|| eyre::eyre!("library {id} has no deployed bytecode"))?
+                    .into_owned();
+                zk_libraries.push((library, code));
+            }
+        }
+        let zk_libraries = order_libraries(zk_libraries)?
+            .into_iter()
+            .map(|(library, code)| (library.address, code))
+            .collect::<Vec<_>>();
+
+        let contracts: ArtifactContracts = output.into_artifacts().collect();
+        let target = find_target(&contracts, &target, root)?.clone();
+
+        let linker = Linker::new(root, contracts);
+        let LinkOutput { libs_to_deploy, libraries } = linker.link_with_nonce_or_address(
+            config.libraries_with_remappings()?,
+            sender,
+            sender_nonce,
+            &target,
+        )?;
+        let contract = linker.link(&target, &libraries)?;
+        let abi = contract.abi.clone().unwrap_or_default();
+        let bytecode = contract.get_bytecode_bytes().map(|b| b.into_owned()).ok_or_else(|| {
+            eyre::eyre!("expected fully linked bytecode, found unlinked bytecode")
+        })?;
+
+        if abi.constructor.as_ref().is_some_and(|constructor| !constructor.inputs.is_empty()) {
+            eyre::bail!("script constructors can't have arguments, pass them to `{sig}` instead")
+        }
+        let (_, calldata) = encode_calldata(&abi, &sig, &args)?;
+
+        let env = evm_opts.evm_env().await?;
+        let legacy =
+            legacy || Chain::from(env.cfg.chain_id).named().is_some_and(|chain| chain.is_legacy());
+        let db = Backend::spawn(evm_opts.get_fork(&config, env.clone())).await;
+        let mut executor = ExecutorBuilder::new()
+            .inspectors(|stack| {
+                stack
+                    .trace(true)
+                    .trace_return_data_limit(config.trace_return_data_limit)
+                    .cheatcodes(
+                        CheatsConfig::new(&config, evm_opts.clone(), None, config.zksync)
+                            .with_dual_compiled_contracts(dual_compiled_contracts)
+                            .into(),
+                    )
+                    .enable_isolation(evm_opts.isolate)
+            })
+            .spec(config.evm_spec_id())
+            .gas_limit(evm_opts.gas_limit())
+            .build(env, db);
+        executor.use_zk = config.zksync;
+
+        let mut runner = ScriptRunner::new(executor, evm_opts.initial_balance, sender);
+        let has_setup = abi.functions().any(|func| func.name == "setUp");
+        let (_, result) = runner.run(
+            &libs_to_deploy,
+            &zk_libraries,
+            bytecode,
+            has_setup,
+            sender_nonce,
+            false,
+            evm_opts.fork_url.is_none(),
+            calldata,
+        )?;
+
+        let output = ScriptOutput {
+            success: result.success,
+            returned: result.returned,
+            gas_used: result.gas_used,
+            logs: result.logs,
+            labeled_addresses: result.labeled_addresses,
+            transactions: result
+                .transactions
+                .into_iter()
+                .flatten()
+                .map(|tx| ScriptTransaction::new(tx, legacy))
+                .collect(),
+            user_operations: result.user_operations,
+            ffi_invocations: result.ffi_invocations,
+            zk_libraries: result.zk_libraries,
+        };
+
+        Ok(output)
+    }
+}
+
+/// Finds the artifact of the script `target`, see [ScriptExecutor::new].
+fn find_target<'a>(
+    contracts: &'a ArtifactContracts,
+    target: &str,
+    root: &Path,
+) -> Result<&'a ArtifactId> {
+    let (path, name) = match target.rsplit_once(':') {
+        Some((path, name)) => (Some(path), Some(name)),
+        None if target.ends_with(".sol") => (Some(target), None),
+        None => (None, Some(target)),
+    };
+    let path = path.map(|path| Path::new(path).strip_prefix(root).unwrap_or(Path::new(path)));
+
+    let is_target = |id: &ArtifactId| {
+        if let Some(path) = path {
+            if id.source != path {
+                return false
+            }
+        }
+        // artifacts compiled with multiple solc versions are named `<name>.<version>`
+        let id_name = id.name.split('.').next().unwrap_or_default();
+        name.is_none() || name == Some(id_name)
+    };
+
+    let mut candidates = contracts
+        .iter()
+        .filter(|(id, contract)| {
+            is_target(id) && contract.bytecode.as_ref().is_some_and(|b| b.object.bytes_len() > 0)
+        })
+        .map(|(id, _)| id);
+    let target_id =
+        candidates.next().ok_or_else(|| eyre::eyre!("could not find script `{target}`"))?;
+
+    let target_name = target_id.name.split('.').next();
+    if candidates.any(|id| id.name.split('.').next() != target_name) {
+        eyre::bail!("multiple contracts found in `{target}`, specify one with `<path>:<name>`")
+    }
+    Ok(target_id)
+}
+
+/// Returns the function of the script `abi` called with `sig` and its calldata.
+///
+/// If `sig` is a function signature, the call is encoded with `args`, otherwise `sig` is the hex
+/// encoded calldata, and the function is found by its selector.
+pub fn encode_calldata(abi: &JsonAbi, sig: &str, args: &[String]) -> Result<(Function, Bytes)> {
+    if let Ok(func) = get_func(sig) {
+        let abi_func = abi
+            .functions()
+            .find(|f| f.selector() == func.selector())
+            .ok_or_else(|| eyre::eyre!("Function `{sig}` is not implemented in your script."))?;
+        return Ok((abi_func.clone(), encode_function_args(&func, args)?.into()))
+    }
+
+    let calldata = hex::decode(sig).wrap_err("Invalid hex calldata")?;
+    eyre::ensure!(calldata.len() >= SELECTOR_LEN, "Calldata is shorter than a selector");
+    let selector = &calldata[..SELECTOR_LEN];
+    let func = abi.functions().find(|f| f.selector()[..] == *selector).ok_or_else(|| {
+        eyre::eyre!("Function selector `{}` not found in the ABI", hex::encode(selector))
+    })?;
+    Ok((func.clone(), calldata.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_zksync_core::ZkTransactionMetadata;
+
+    #[test]
+    fn encodes_script_calldata() {
+        let abi = JsonAbi::parse(["function run(uint256)"]).unwrap();
+        let args = vec!["1".to_string()];
+
+        let (func, calldata) = encode_calldata(&abi, "run(uint256)", &args).unwrap();
+        assert_eq!(func.name, "run");
+        assert_eq!(calldata.len(), 4 + 32);
+        let raw = hex::encode(&calldata);
+        assert_eq!(encode_calldata(&abi, &raw, &[]).unwrap(), (func, calldata));
+
+        assert!(encode_calldata(&abi, "run()", &[]).is_err());
+        assert!(encode_calldata(&abi, "0x12345678", &[]).is_err());
+    }
+
+    #[test]
+    fn converts_script_transactions() {
+        let tx = |zk_tx| BroadcastableTransaction {
+            rpc: None,
+            transaction: alloy_rpc_types::TransactionRequest {
+                from: Some(Address::with_last_byte(1)),
+                to: Some(Address::with_last_byte(2)),
+                nonce: Some(alloy_primitives::U64::from(3)),
+                gas: Some(alloy_primitives::U256::from(21_000)),
+                ..Default::default()
+            },
+            zk_tx,
+            ..Default::default()
+        };
+
+        let ScriptTransactionRequest::Evm(TypedTransaction::Eip1559(request)) =
+            ScriptTransaction::new(tx(None), false).request
+        else {
+            panic!("expected an EIP-1559 transaction")
+        };
+        assert_eq!(request.from, Some(Address::with_last_byte(1).to_ethers()));
+        assert_eq!(request.nonce, Some(3.into()));
+        assert_eq!(request.gas, Some(21_000.into()));
+
+        let ScriptTransactionRequest::Evm(TypedTransaction::Legacy(request)) =
+            ScriptTransaction::new(tx(None), true).request
+        else {
+            panic!("expected a legacy transaction")
+        };
+        assert_eq!(request.to, Some(Address::with_last_byte(2).to_ethers().into()));
+
+        let zk_tx = ZkTransactionMetadata { factory_deps: vec![vec![1, 2]], paymaster: None };
+        let ScriptTransactionRequest::Eip712(request) =
+            ScriptTransaction::new(tx(Some(zk_tx)), false).request
+        else {
+            panic!("expected an EIP-712 transaction")
+        };
+        assert_eq!(request.r#type, EIP712_TX_TYPE.into());
+        assert_eq!(request.nonce, 3.into());
+        assert_eq!(request.custom_data.factory_deps, vec![vec![1, 2]]);
+    }
+}
@@ -1,39 +1,55 @@
 use super::ScriptResult;
 use alloy_primitives::{Address, Bytes, U256};
 use eyre::Result;
-use forge::{
+use foundry_config::Config;
+use foundry_evm::{
     constants::CALLER,
     executors::{CallResult, DeployResult, EvmError, ExecutionErr, Executor, RawCallResult},
-    revm::interpreter::{return_ok, InstructionResult},
+    revm::{
+        interpreter::{return_ok, InstructionResult},
+        primitives::Bytecode,
+        DatabaseRef,
+    },
     traces::{TraceKind, Traces},
 };
-use foundry_config::Config;
 use foundry_zksync_core::ZkTransactionMetadata;
 use yansi::Paint;
 
 /// Represents which simulation stage is the script execution at.
 pub enum SimulationStage {
+    /// The script is executed locally to collect its transactions.
     Local,
+    /// The collected transactions are executed against the forks they are broadcast to.
     OnChain,
 }
 
 /// Drives script execution
 #[derive(Debug)]
 pub struct ScriptRunner {
+    /// The executor the script is deployed and called in.
     pub executor: Executor,
+    /// The balance of the script contract after its deployment.
     pub initial_balance: U256,
+    /// The sender of the script calls.
     pub sender: Address,
 }
 
 impl ScriptRunner {
+    /// Creates a new runner driving `executor`.
     pub fn new(executor: Executor, initial_balance: U256, sender: Address) -> Self {
         Self { executor, initial_balance, sender }
     }
 
     /// Deploys the libraries and broadcast contract. Calls setUp method if requested.
+    ///
+    /// The code of the zkSync libraries, linked at compile time, is set at their addresses if
+    /// they aren't deployed. They're deployed from the sender after the other libraries when
+    /// broadcasting, so the sender's nonce is increased for each one.
+    #[allow(clippy::too_many_arguments)]
     pub fn setup(
         &mut self,
         libraries: &[Bytes],
+        zk_libraries: &[(Address, Bytes)],
         code: Bytes,
         setup: bool,
         sender_nonce: u64,
@@ -72,6 +88,27 @@ impl ScriptRunner {
             .map(|traces| (TraceKind::Deployment, traces))
             .collect();
 
+        // The zkSync libraries that aren't deployed are persistent, to be migrated to the zkEVM
+        let mut zk_libraries_to_deploy = Vec::new();
+        for (address, library_code) in zk_libraries {
+            let deployed = self
+                .executor
+                .backend
+                .basic_ref(*address)?
+                .and_then(|account| account.code)
+                .map_or(false, |deployed_code| !deployed_code.is_empty());
+            if !deployed {
+                let library_code = Bytecode::new_raw(library_code.clone()).to_checked();
+                self.executor.set_code(*address, library_code)?;
+                self.executor.backend.add_persistent_account(*address);
+                zk_libraries_to_deploy.push(*address);
+            }
+        }
+        let libraries_len = libraries.len() + zk_libraries_to_deploy.len();
+        if !zk_libraries_to_deploy.is_empty() {
+            self.executor.set_nonce(self.sender, sender_nonce + libraries_len as u64)?;
+        }
+
         let address = CALLER.create(self.executor.get_nonce(CALLER)?);
 
         // Set the contracts initial balance before deployment, so it is available during the
@@ -93,15 +130,23 @@ impl ScriptRunner {
         traces.extend(constructor_traces.map(|traces| (TraceKind::Deployment, traces)));
 
         // Optionally call the `setUp` function
-        let (success, gas_used, labeled_addresses, transactions, debug) = if !setup {
+        let (success, gas_used, labeled_addresses, proxies, transactions, debug) = if !setup {
             self.executor.backend.set_test_contract(address);
-            (true, 0, Default::default(), None, vec![constructor_debug].into_iter().collect())
+            (
+                true,
+                0,
+                Default::default(),
+                Default::default(),
+                None,
+                vec![constructor_debug].into_iter().collect(),
+            )
         } else {
             match self.executor.setup(Some(self.sender), address) {
                 Ok(CallResult {
                     reverted,
                     traces: setup_traces,
                     labels,
+                    proxies,
                     logs: setup_logs,
                     debug,
                     gas_used,
@@ -111,12 +156,13 @@ impl ScriptRunner {
                     traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)));
                     logs.extend_from_slice(&setup_logs);
 
-                    self.maybe_correct_nonce(sender_nonce, libraries.len())?;
+                    self.maybe_correct_nonce(sender_nonce, libraries_len)?;
 
                     (
                         !reverted,
                         gas_used,
                         labels,
+                        proxies,
                         transactions,
                         vec![constructor_debug, debug].into_iter().collect(),
                     )
@@ -126,6 +172,7 @@ impl ScriptRunner {
                         reverted,
                         traces: setup_traces,
                         labels,
+                        proxies,
                         logs: setup_logs,
                         debug,
                         gas_used,
@@ -135,12 +182,13 @@ impl ScriptRunner {
                     traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)));
                     logs.extend_from_slice(&setup_logs);
 
-                    self.maybe_correct_nonce(sender_nonce, libraries.len())?;
+                    self.maybe_correct_nonce(sender_nonce, libraries_len)?;
 
                     (
                         !reverted,
                         gas_used,
                         labels,
+                        proxies,
                         transactions,
                         vec![constructor_debug, debug].into_iter().collect(),
                     )
@@ -156,11 +204,13 @@ impl ScriptRunner {
                 success,
                 gas_used,
                 labeled_addresses,
+                proxies,
                 transactions,
                 logs,
                 traces,
                 debug,
                 address: None,
+                zk_libraries: zk_libraries_to_deploy,
                 ..Default::default()
             },
         ))
@@ -189,6 +239,62 @@ impl ScriptRunner {
         self.call(self.sender, address, calldata, U256::ZERO, false)
     }
 
+    /// Deploys the script with [Self::setup] and, if `setUp()` succeeded, calls it with
+    /// `calldata`.
+    ///
+    /// Returns the address of the script and the result of both calls, merged.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run(
+        &mut self,
+        libraries: &[Bytes],
+        zk_libraries: &[(Address, Bytes)],
+        code: Bytes,
+        setup: bool,
+        sender_nonce: u64,
+        is_broadcast: bool,
+        need_create2_deployer: bool,
+        calldata: Bytes,
+    ) -> Result<(Address, ScriptResult)> {
+        let (address, mut result) = self.setup(
+            libraries,
+            zk_libraries,
+            code,
+            setup,
+            sender_nonce,
+            is_broadcast,
+            need_create2_deployer,
+        )?;
+
+        // Only call the method if `setUp()` succeeded.
+        if result.success {
+            let script_result = self.script(address, calldata)?;
+
+            result.success &= script_result.success;
+            result.gas_used = script_result.gas_used;
+            result.logs.extend(script_result.logs);
+            result.traces.extend(script_result.traces);
+            result.debug = script_result.debug;
+            result.labeled_addresses.extend(script_result.labeled_addresses);
+            result.proxies.extend(script_result.proxies);
+            result.returned = script_result.returned;
+            result.breakpoints = script_result.breakpoints;
+            result.ffi_invocations.extend(script_result.ffi_invocations);
+            result.user_operations.extend(script_result.user_operations);
+
+            match (&mut result.transactions, script_result.transactions) {
+                (Some(txs), Some(new_txs)) => {
+                    txs.extend(new_txs);
+                }
+                (None, Some(new_txs)) => {
+                    result.transactions = Some(new_txs);
+                }
+                _ => {}
+            }
+        }
+
+        Ok((address, result))
+    }
+
     /// Runs a broadcastable transaction locally and persists its state.
     pub fn simulate(
         &mut self,
@@ -272,8 +378,24 @@ impl ScriptRunner {
             res = self.executor.call_raw_committing(from, to, calldata, value)?;
         }
 
-        let RawCallResult { result, reverted, logs, traces, labels, debug, transactions, .. } = res;
-        let breakpoints = res.cheatcodes.map(|cheats| cheats.breakpoints).unwrap_or_default();
+        let RawCallResult {
+            result,
+            reverted,
+            logs,
+            traces,
+            labels,
+            proxies,
+            debug,
+            transactions,
+            ..
+        } = res;
+        let (breakpoints, user_operations, ffi_invocations) = res
+            .cheatcodes
+            .map(|cheats| {
+                let ffi_invocations = std::mem::take(&mut *cheats.config.ffi_log.lock());
+                (cheats.breakpoints, cheats.broadcastable_user_operations, ffi_invocations)
+            })
+            .unwrap_or_default();
 
         Ok(ScriptResult {
             returned: result,
@@ -289,9 +411,13 @@ impl ScriptRunner {
                 .unwrap_or_default(),
             debug: debug.map(|d| vec![d]),
             labeled_addresses: labels,
+            proxies,
             transactions,
+            user_operations,
             address: None,
             breakpoints,
+            ffi_invocations,
+            zk_libraries: vec![],
         })
     }
 
@@ -0,0 +1,95 @@
+//! Static detection of the functions whose ABI types approach the EraVM size limits.
+
+use alloy_dyn_abi::DynSolType;
+use alloy_json_abi::{JsonAbi, Param};
+use foundry_zksync_core::vm::{SizeLimitKind, SizeLimitWarning};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// Returns the functions of the contract `name` whose arguments have a minimum ABI-encoded size
+/// approaching the EraVM calldata limit.
+pub fn abi_size_warnings(name: &str, abi: &JsonAbi) -> Vec<SizeLimitWarning> {
+    abi.functions()
+        .filter_map(|func| {
+            let context = format!("{name}.{}", func.signature());
+            let size = 4 + min_params_size(&func.inputs);
+            SizeLimitWarning::check(SizeLimitKind::Calldata, context, size, true)
+        })
+        .collect()
+}
+
+/// Returns the warnings, by the source file of their contract, of the contracts of `source` and
+/// of the files it imports, directly or not.
+pub fn imported_size_warnings(
+    source: &Path,
+    warnings: &BTreeMap<PathBuf, Vec<SizeLimitWarning>>,
+    imports: &BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+) -> Vec<SizeLimitWarning> {
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![source];
+    while let Some(file) = pending.pop() {
+        if visited.insert(file) {
+            pending.extend(imports.get(file).into_iter().flatten().map(PathBuf::as_path));
+        }
+    }
+    visited.into_iter().filter_map(|file| warnings.get(file)).flatten().cloned().collect()
+}
+
+fn min_params_size(params: &[Param]) -> usize {
+    params
+        .iter()
+        .filter_map(|param| param.selector_type().parse::<DynSolType>().ok())
+        .map(|ty| min_encoded_size(&ty))
+        .fold(0, usize::saturating_add)
+}
+
+/// Returns the minimum size of the ABI encoding of a value of type `ty`, where dynamic values
+/// take at least their offset and their length.
+fn min_encoded_size(ty: &DynSolType) -> usize {
+    match ty {
+        DynSolType::Bytes | DynSolType::String | DynSolType::Array(_) => 64,
+        DynSolType::FixedArray(ty, len) => min_encoded_size(ty).saturating_mul(*len),
+        DynSolType::Tuple(types) => {
+            types.iter().map(min_encoded_size).fold(0, usize::saturating_add)
+        }
+        _ => 32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_large_abi_types() {
+        let abi = JsonAbi::parse([
+            "function small(uint256[10] values, bytes data) returns (string)",
+            "function large(uint256[30000] values)",
+            "function output() returns ((address,uint256)[600000][2] values)",
+        ])
+        .unwrap();
+        let warnings = abi_size_warnings("C", &abi);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].kind, SizeLimitKind::Calldata);
+        assert_eq!(warnings[0].context, "C.large(uint256[30000])");
+        assert_eq!(warnings[0].size, 4 + 30_000 * 32);
+        assert!(warnings[0].is_exceeded());
+    }
+
+    #[test]
+    fn collects_imported_warnings() {
+        let abi = JsonAbi::parse(["function large(uint256[30000] values)"]).unwrap();
+        let warnings = BTreeMap::from([(PathBuf::from("src/C.sol"), abi_size_warnings("C", &abi))]);
+        let imports = BTreeMap::from([
+            (PathBuf::from("test/C.t.sol"), BTreeSet::from([PathBuf::from("test/Base.sol")])),
+            (PathBuf::from("test/Base.sol"), BTreeSet::from([PathBuf::from("src/C.sol")])),
+            (PathBuf::from("src/C.sol"), BTreeSet::from([PathBuf::from("test/C.t.sol")])),
+        ]);
+        let imported = imported_size_warnings(Path::new("test/C.t.sol"), &warnings, &imports);
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].context, "C.large(uint256[30000])");
+        assert!(imported_size_warnings(Path::new("test/D.t.sol"), &warnings, &imports).is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+//! Lazily loaded creation code of the test and fixture contracts.
+//!
+//! The runner doesn't keep the creation code of every suite in memory for the whole run: a suite
+//! only keeps the path of its artifact and the libraries it's linked with, and its code is read
+//! and linked when the suite runs, then dropped once it's done. The most recently loaded codes
+//! are kept in a bounded [`CodeCache`], since a fixture is deployed once per chain of fixtures.
+//!
+//! In zk mode a suite also keeps the path of its zksolc artifact, whose EraVM bytecode is loaded
+//! the same way and cached next to the EVM codes, keyed by the VM they run in.
+
+use crate::link::Linker;
+use alloy_primitives::Bytes;
+use eyre::{Result, WrapErr};
+use foundry_compilers::{
+    artifacts::Libraries, contracts::ArtifactContracts, Artifact, ArtifactId,
+    ConfigurableContractArtifact,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+/// The number of loaded creation codes kept by the runner.
+pub const CODE_CACHE_SIZE: usize = 32;
+
+/// The creation code of a test or fixture contract.
+#[derive(Clone, Debug)]
+pub enum SuiteCode {
+    /// The linked creation code, kept in memory when the contract has no artifact file
+    Loaded(Bytes),
+    /// The artifact file of the contract, linked with `libraries` when the suite runs
+    Artifact {
+        /// The path of the artifact file
+        path: PathBuf,
+        /// The libraries the contract is linked with, with their paths stripped of the root
+        libraries: Libraries,
+        /// The path of the zksolc artifact file of the contract, if it was compiled with zksolc
+        zk_path: Option<PathBuf>,
+    },
+}
+
+/// The VM a cached code runs in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeVm {
+    /// The solc creation code, run in the EVM
+    Evm,
+    /// The zksolc bytecode, run in EraVM
+    Zk,
+}
+
+impl SuiteCode {
+    /// Returns the code of the contract `id`, read from its artifact file if it was written to
+    /// `root`, held in memory otherwise, with the path of its zksolc artifact file if any.
+    pub fn new(
+        root: &Path,
+        id: &ArtifactId,
+        code: Bytes,
+        libraries: &Libraries,
+        zk_path: Option<PathBuf>,
+    ) -> Self {
+        let path = root.join(&id.path);
+        if path.is_file() {
+            let zk_path = zk_path.filter(|path| path.is_file());
+            Self::Artifact { path, libraries: libraries.clone(), zk_path }
+        } else {
+            Self::Loaded(code)
+        }
+    }
+
+    /// Reads the zksolc bytecode of the contract `id` from its zksolc artifact file, if it has
+    /// one.
+    pub fn load_zk(&self, id: &ArtifactId) -> Result<Option<Bytes>> {
+        let Self::Artifact { zk_path: Some(path), .. } = self else { return Ok(None) };
+        let artifact: ConfigurableContractArtifact = foundry_common::fs::read_json_file(path)
+            .wrap_err_with(|| {
+                format!("failed to load the zksolc artifact of {}", id.identifier())
+            })?;
+        let code = artifact
+            .get_deployed_bytecode_bytes()
+            .map(|code| code.into_owned())
+            .filter(|code| !code.is_empty())
+            .ok_or_else(|| {
+                eyre::eyre!("the zksolc artifact of {} has no bytecode", id.identifier())
+            })?;
+        Ok(Some(code))
+    }
+
+    /// Reads and links the creation code of the contract `id`.
+    pub fn load(&self, root: &Path, id: &ArtifactId) -> Result<Bytes> {
+        let (path, libraries) = match self {
+            Self::Loaded(code) => return Ok(code.clone()),
+            Self::Artifact { path, libraries, .. } => (path, libraries),
+        };
+        let artifact: ConfigurableContractArtifact = foundry_common::fs::read_json_file(path)
+            .wrap_err_with(|| format!("failed to load the artifact of {}", id.identifier()))?;
+
+        // the link references of the artifact file aren't stripped of the root
+        let mut libraries = libraries.clone();
+        let absolute = libraries
+            .libs
+            .iter()
+            .map(|(file, libs)| (root.join(file), libs.clone()))
+            .collect::<Vec<_>>();
+        libraries.libs.extend(absolute);
+
+        let linker = Linker::new(
+            root,
+            ArtifactContracts::from_iter([(id.clone(), artifact.into_contract_bytecode())]),
+        );
+        let code = linker
+            .link(id, &libraries)?
+            .get_bytecode_bytes()
+            .map(|code| code.into_owned())
+            .filter(|code| !code.is_empty())
+            .ok_or_else(|| eyre::eyre!("the artifact of {} has no bytecode", id.identifier()))?;
+        Ok(code)
+    }
+}
+
+/// A bounded cache of the most recently loaded codes, by VM and artifact path.
+#[derive(Debug)]
+pub struct CodeCache {
+    codes: Mutex<LruCache<(CodeVm, PathBuf), Bytes>>,
+}
+
+impl Default for CodeCache {
+    fn default() -> Self {
+        Self::new(CODE_CACHE_SIZE)
+    }
+}
+
+impl CodeCache {
+    /// Creates a cache keeping at most `capacity` codes.
+    pub fn new(capacity: usize) -> Self {
+        Self { codes: Mutex::new(LruCache::new(capacity)) }
+    }
+
+    /// Returns the creation code of the contract `id`, loading it if it isn't cached.
+    pub fn load(&self, root: &Path, id: &ArtifactId, code: &SuiteCode) -> Result<Bytes> {
+        let SuiteCode::Artifact { path, .. } = code else { return code.load(root, id) };
+        let key = (CodeVm::Evm, path.clone());
+        if let Some(code) = self.codes.lock().get(&key) {
+            return Ok(code.clone())
+        }
+        let loaded = code.load(root, id)?;
+        self.codes.lock().insert(key, loaded.clone());
+        Ok(loaded)
+    }
+
+    /// Returns the zksolc bytecode of the contract `id`, loading it if it isn't cached, if it was
+    /// compiled with zksolc.
+    pub fn load_zk(&self, id: &ArtifactId, code: &SuiteCode) -> Result<Option<Bytes>> {
+        let SuiteCode::Artifact { zk_path: Some(path), .. } = code else { return Ok(None) };
+        let key = (CodeVm::Zk, path.clone());
+        if let Some(code) = self.codes.lock().get(&key) {
+            return Ok(Some(code.clone()))
+        }
+        let loaded = code.load_zk(id)?;
+        if let Some(loaded) = &loaded {
+            self.codes.lock().insert(key, loaded.clone());
+        }
+        Ok(loaded)
+    }
+}
+
+/// A cache of at most `capacity` entries, evicting the least recently used one when full.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    /// The entries, from the least to the most recently used
+    entries: VecDeque<(K, V)>,
+}
+
+impl<K: PartialEq, V> LruCache<K, V> {
+    /// Creates an empty cache keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: VecDeque::with_capacity(capacity) }
+    }
+
+    /// Returns the value of `key` and marks it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let position = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(position)?;
+        self.entries.push_back(entry);
+        self.entries.back().map(|(_, value)| value)
+    }
+
+    /// Inserts the value of `key`, evicting the least recently used entry if the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return
+        }
+        if let Some(position) = self.entries.iter().position(|(k, _)| *k == key) {
+            self.entries.remove(position);
+        } else if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, value));
+    }
+
+    /// Returns the number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get(&"a"), Some(&1));
+
+        // `b` is the least recently used
+        cache.insert("c", 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"c"), Some(&3));
+
+        cache.insert("a", 4);
+        cache.insert("d", 5);
+        assert_eq!(cache.get(&"c"), None);
+        assert_eq!(cache.get(&"a"), Some(&4));
+
+        let mut empty = LruCache::new(0);
+        empty.insert("a", 1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn keeps_code_without_artifact_in_memory() {
+        let id = ArtifactId {
+            path: PathBuf::from("out/Missing.sol/Missing.json"),
+            name: "Missing".to_string(),
+            source: PathBuf::from("src/Missing.sol"),
+            version: semver::Version::new(0, 8, 24),
+        };
+        let root = Path::new("/nonexistent-root");
+        let code = SuiteCode::new(
+            root,
+            &id,
+            Bytes::from_static(&[0x60, 0x80]),
+            &Libraries::default(),
+            Some(root.join("zkout/Missing.sol/Missing.json")),
+        );
+        assert!(matches!(code, SuiteCode::Loaded(_)));
+
+        let cache = CodeCache::default();
+        assert_eq!(cache.load(root, &id, &code).unwrap(), Bytes::from_static(&[0x60, 0x80]));
+        assert_eq!(cache.load_zk(&id, &code).unwrap(), None);
+    }
+
+    #[test]
+    fn caches_codes_by_vm() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = ArtifactId {
+            path: PathBuf::from("out/Counter.sol/Counter.json"),
+            name: "Counter".to_string(),
+            source: PathBuf::from("src/Counter.sol"),
+            version: semver::Version::new(0, 8, 24),
+        };
+        let artifact = |code: &str| {
+            serde_json::json!({
+                "abi": [],
+                "bytecode": { "object": code, "linkReferences": {} },
+                "deployedBytecode": { "bytecode": { "object": code, "linkReferences": {} } },
+            })
+        };
+        let (path, zk_path) = (dir.path().join(&id.path), dir.path().join("zkout/Counter.json"));
+        for (path, code) in [(&path, "0x6080"), (&zk_path, "0x0000008003000039")] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, artifact(code).to_string()).unwrap();
+        }
+        let code =
+            SuiteCode::new(dir.path(), &id, Bytes::new(), &Libraries::default(), Some(zk_path));
+
+        let cache = CodeCache::new(2);
+        let evm = cache.load(dir.path(), &id, &code).unwrap();
+        let zk = cache.load_zk(&id, &code).unwrap().unwrap();
+        assert_eq!(evm, Bytes::from_static(&[0x60, 0x80]));
+        assert_eq!(zk, Bytes::from_static(&[0, 0, 0, 0x80, 0x03, 0, 0, 0x39]));
+
+        // both codes are served from the cache once their artifacts are gone
+        std::fs::remove_dir_all(dir.path().join("out")).unwrap();
+        std::fs::remove_dir_all(dir.path().join("zkout")).unwrap();
+        assert_eq!(cache.codes.lock().len(), 2);
+        assert_eq!(cache.load(dir.path(), &id, &code).unwrap(), evm);
+        assert_eq!(cache.load_zk(&id, &code).unwrap().unwrap(), zk);
+    }
+}
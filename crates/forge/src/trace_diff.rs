@@ -0,0 +1,460 @@
+//! Exporting and diffing of decoded test traces.
+//!
+//! `forge test --export-traces <PATH>` writes the decoded execution traces of every test as
+//! [`ExportedTraces`], and `forge trace-diff` compares two such exports structurally, e.g. to
+//! review the effect of a change across commits or between the EVM and zkEVM.
+
+use crate::traces::{CallKind, CallTraceArena, CallTraceDecoder, DecodedCallData};
+use alloy_primitives::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Write},
+};
+use yansi::Paint;
+
+/// The decoded execution traces of a test run: `suite -> test -> traces`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTraces {
+    pub tests: BTreeMap<String, BTreeMap<String, Vec<ExportedCall>>>,
+}
+
+impl ExportedTraces {
+    /// Decodes `arena` and adds it to the traces of `test` in `suite`.
+    pub async fn insert(
+        &mut self,
+        suite: &str,
+        test: &str,
+        arena: &CallTraceArena,
+        decoder: &CallTraceDecoder,
+    ) {
+        let call = ExportedCall::from_arena(arena, decoder).await;
+        self.tests
+            .entry(suite.to_string())
+            .or_default()
+            .entry(test.to_string())
+            .or_default()
+            .extend(call);
+    }
+}
+
+/// A single decoded call of an exported trace, including its subcalls.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedCall {
+    pub kind: ExportedCallKind,
+    pub address: Address,
+    /// The label or contract name of the callee, if known.
+    pub label: Option<String>,
+    /// The decoded function signature, if known.
+    pub signature: Option<String>,
+    /// The decoded arguments, if the function is known.
+    pub args: Option<Vec<String>>,
+    /// The decoded return data, if known.
+    pub decoded_output: Option<String>,
+    pub data: Bytes,
+    pub output: Bytes,
+    pub gas_used: u64,
+    pub success: bool,
+    pub children: Vec<ExportedCall>,
+}
+
+/// The kind of an [`ExportedCall`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportedCallKind {
+    Call,
+    StaticCall,
+    CallCode,
+    DelegateCall,
+    Create,
+    Create2,
+}
+
+impl From<CallKind> for ExportedCallKind {
+    fn from(kind: CallKind) -> Self {
+        match kind {
+            CallKind::Call => Self::Call,
+            CallKind::StaticCall => Self::StaticCall,
+            CallKind::CallCode => Self::CallCode,
+            CallKind::DelegateCall => Self::DelegateCall,
+            CallKind::Create => Self::Create,
+            CallKind::Create2 => Self::Create2,
+        }
+    }
+}
+
+impl ExportedCallKind {
+    pub fn is_any_create(self) -> bool {
+        matches!(self, Self::Create | Self::Create2)
+    }
+}
+
+impl ExportedCall {
+    /// Decodes the root call of `arena` with all of its subcalls.
+    pub async fn from_arena(arena: &CallTraceArena, decoder: &CallTraceDecoder) -> Option<Self> {
+        let nodes = arena.nodes();
+        decoder.prefetch_signatures(nodes).await;
+
+        let mut calls = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let trace = &node.trace;
+            let decoded = decoder.decode_function(trace).await;
+            let (signature, args) = match decoded.func {
+                Some(DecodedCallData { signature, args }) => (Some(signature), Some(args)),
+                None => (None, None),
+            };
+            calls.push(Some(Self {
+                kind: trace.kind.into(),
+                address: trace.address,
+                label: decoded.label,
+                signature,
+                args,
+                decoded_output: decoded.return_data,
+                data: trace.data.clone(),
+                output: trace.output.clone(),
+                gas_used: trace.gas_used,
+                success: trace.success,
+                children: Vec::new(),
+            }));
+        }
+
+        fn build(
+            idx: usize,
+            arena: &CallTraceArena,
+            calls: &mut [Option<ExportedCall>],
+        ) -> Option<ExportedCall> {
+            let mut call = calls[idx].take()?;
+            call.children = arena.nodes()[idx]
+                .children
+                .iter()
+                .filter_map(|&child| build(child, arena, calls))
+                .collect();
+            Some(call)
+        }
+        build(0, arena, &mut calls)
+    }
+
+    /// The key used to match calls of two traces with each other.
+    ///
+    /// Calls are matched by callee and function rather than address, since addresses of
+    /// deployed contracts differ between runs on the EVM and the zkEVM.
+    fn key(&self) -> (bool, String, Option<&[u8]>) {
+        let callee = self.label.clone().unwrap_or_else(|| self.address.to_string());
+        let selector = (!self.kind.is_any_create()).then(|| self.data.get(..4)).flatten();
+        (self.kind.is_any_create(), callee, selector)
+    }
+
+    /// Returns whether `other` calls the same function as `self` with the same arguments.
+    ///
+    /// The decoded signatures and arguments are compared when both calls are decoded, and the
+    /// calldata otherwise. The calldata of creations is their init code, which differs between
+    /// the EVM and the zkEVM, so only their constructor arguments are compared, if decoded.
+    fn same_input(&self, other: &Self) -> bool {
+        match (&self.signature, &other.signature) {
+            (Some(a), Some(b)) => a == b && self.args == other.args,
+            _ if self.kind.is_any_create() => true,
+            _ => self.data == other.data,
+        }
+    }
+
+    fn args_str(&self) -> String {
+        match (&self.signature, &self.args) {
+            (Some(signature), Some(args)) => format!("{signature} ({})", args.join(", ")),
+            _ => self.data.to_string(),
+        }
+    }
+
+    /// Returns the name of the call, e.g. `Counter::increment(1)`.
+    fn name(&self) -> String {
+        let callee = self.label.clone().unwrap_or_else(|| self.address.to_checksum(None));
+        if self.kind.is_any_create() {
+            return format!("new {callee}")
+        }
+        match (&self.signature, &self.args) {
+            (Some(signature), Some(args)) => {
+                let func = signature.split('(').next().unwrap_or(signature);
+                format!("{callee}::{func}({})", args.join(", "))
+            }
+            _ if self.data.len() < 4 => format!("{callee}::fallback()"),
+            _ => format!("{callee}::{}", hex::encode(&self.data[..4])),
+        }
+    }
+
+    fn output_str(&self) -> String {
+        match &self.decoded_output {
+            Some(output) => output.clone(),
+            None if self.kind.is_any_create() => format!("{} bytes of code", self.output.len()),
+            None if self.output.is_empty() => "()".to_string(),
+            None => self.output.to_string(),
+        }
+    }
+}
+
+/// The structural difference between two [`ExportedTraces`].
+#[derive(Clone, Debug, Default)]
+pub struct TraceDiff {
+    pub tests: Vec<TestTraceDiff>,
+}
+
+/// The difference between the traces of a single test.
+#[derive(Clone, Debug)]
+pub struct TestTraceDiff {
+    pub suite: String,
+    pub test: String,
+    pub kind: TestTraceDiffKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum TestTraceDiffKind {
+    /// The test only exists in the first export.
+    Removed,
+    /// The test only exists in the second export.
+    Added,
+    /// The test exists in both exports, with differing traces.
+    Changed(Vec<CallDiff>),
+}
+
+/// The difference between two calls, or a call that only exists in one of the traces.
+#[derive(Clone, Debug)]
+pub enum CallDiff {
+    Removed(ExportedCall),
+    Added(ExportedCall),
+    /// A call found in both traces, that differs itself or in any of its subcalls.
+    Changed {
+        a: Box<ExportedCall>,
+        b: Box<ExportedCall>,
+        children: Vec<CallDiff>,
+    },
+}
+
+impl TraceDiff {
+    /// Compares the traces of all tests in `a` with the traces in `b`.
+    ///
+    /// Gas differences smaller than `gas_threshold` are ignored.
+    pub fn new(a: &ExportedTraces, b: &ExportedTraces, gas_threshold: u64) -> Self {
+        let mut tests = Vec::new();
+        let empty = BTreeMap::new();
+        let suites = a.tests.keys().chain(b.tests.keys()).collect::<BTreeSet<_>>();
+        for suite in suites {
+            let a_tests = a.tests.get(suite).unwrap_or(&empty);
+            let b_tests = b.tests.get(suite).unwrap_or(&empty);
+            let names = a_tests.keys().chain(b_tests.keys()).collect::<BTreeSet<_>>();
+            for test in names {
+                let kind = match (a_tests.get(test), b_tests.get(test)) {
+                    (Some(_), None) => TestTraceDiffKind::Removed,
+                    (None, Some(_)) => TestTraceDiffKind::Added,
+                    (Some(a), Some(b)) => {
+                        let diffs = diff_calls(a, b, gas_threshold);
+                        if diffs.is_empty() {
+                            continue
+                        }
+                        TestTraceDiffKind::Changed(diffs)
+                    }
+                    (None, None) => unreachable!(),
+                };
+                tests.push(TestTraceDiff { suite: suite.clone(), test: test.clone(), kind });
+            }
+        }
+        Self { tests }
+    }
+
+    /// Returns `true` if the traces are equal.
+    pub fn is_empty(&self) -> bool {
+        self.tests.is_empty()
+    }
+}
+
+/// Diffs two lists of sibling calls, matching them by their longest common subsequence.
+fn diff_calls(a: &[ExportedCall], b: &[ExportedCall], gas_threshold: u64) -> Vec<CallDiff> {
+    let (n, m) = (a.len(), b.len());
+    // lcs[i][j] is the length of the longest common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i].key() == b[j].key() {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diffs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i].key() == b[j].key() {
+            let children = diff_calls(&a[i].children, &b[j].children, gas_threshold);
+            let gas_delta = a[i].gas_used.abs_diff(b[j].gas_used);
+            let changed = !a[i].same_input(&b[j]) ||
+                a[i].output != b[j].output ||
+                a[i].success != b[j].success ||
+                (gas_delta > 0 && gas_delta >= gas_threshold);
+            if changed || !children.is_empty() {
+                diffs.push(CallDiff::Changed {
+                    a: Box::new(a[i].clone()),
+                    b: Box::new(b[j].clone()),
+                    children,
+                });
+            }
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            diffs.push(CallDiff::Added(b[j].clone()));
+            j += 1;
+        } else {
+            diffs.push(CallDiff::Removed(a[i].clone()));
+            i += 1;
+        }
+    }
+    diffs
+}
+
+impl fmt::Display for TraceDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences found.")
+        }
+        for test in &self.tests {
+            let name = format!("{}::{}", test.suite, test.test);
+            match &test.kind {
+                TestTraceDiffKind::Removed => writeln!(f, "{} {name}", Paint::red("-"))?,
+                TestTraceDiffKind::Added => writeln!(f, "{} {name}", Paint::green("+"))?,
+                TestTraceDiffKind::Changed(diffs) => {
+                    writeln!(f, "{} {name}", Paint::yellow("~"))?;
+                    let mut s = String::new();
+                    for diff in diffs {
+                        write_call_diff(&mut s, diff, 1)?;
+                    }
+                    f.write_str(&s)?;
+                }
+            }
+        }
+        let (added, removed, changed) =
+            self.tests.iter().fold((0, 0, 0), |acc, test| match test.kind {
+                TestTraceDiffKind::Added => (acc.0 + 1, acc.1, acc.2),
+                TestTraceDiffKind::Removed => (acc.0, acc.1 + 1, acc.2),
+                TestTraceDiffKind::Changed(_) => (acc.0, acc.1, acc.2 + 1),
+            });
+        write!(f, "\n{changed} test(s) changed, {added} added, {removed} removed")
+    }
+}
+
+fn write_call_diff(s: &mut String, diff: &CallDiff, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    match diff {
+        CallDiff::Removed(call) => write_call(s, call, &indent, Paint::red("-").to_string()),
+        CallDiff::Added(call) => write_call(s, call, &indent, Paint::green("+").to_string()),
+        CallDiff::Changed { a, b, children } => {
+            let delta = b.gas_used as i128 - a.gas_used as i128;
+            let gas = if delta == 0 {
+                format!("[{}]", a.gas_used)
+            } else {
+                let delta = format!("{delta:+}");
+                let delta =
+                    if b.gas_used > a.gas_used { Paint::red(delta) } else { Paint::green(delta) };
+                format!("[{} → {} ({delta})]", a.gas_used, b.gas_used)
+            };
+            writeln!(s, "{indent}{} {gas} {}", Paint::yellow("~"), a.name())?;
+            if !a.same_input(b) {
+                writeln!(s, "{indent}    args: {} → {}", a.args_str(), b.args_str())?;
+            }
+            if a.success != b.success {
+                let status = |success| if success { "success" } else { "revert" };
+                writeln!(s, "{indent}    status: {} → {}", status(a.success), status(b.success))?;
+            }
+            if a.output != b.output {
+                writeln!(s, "{indent}    returns: {} → {}", a.output_str(), b.output_str())?;
+            }
+            for child in children {
+                write_call_diff(s, child, depth + 1)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Writes `call` and all of its subcalls, prefixed with `marker`.
+fn write_call(s: &mut String, call: &ExportedCall, indent: &str, marker: String) -> fmt::Result {
+    writeln!(s, "{indent}{marker} [{}] {} ← {}", call.gas_used, call.name(), call.output_str())?;
+    let indent = format!("{indent}  ");
+    for child in &call.children {
+        write_call(s, child, &indent, marker.clone())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(label: &str, selector: u8, gas_used: u64, children: Vec<ExportedCall>) -> ExportedCall {
+        ExportedCall {
+            kind: ExportedCallKind::Call,
+            address: Address::with_last_byte(selector),
+            label: Some(label.to_string()),
+            signature: None,
+            args: None,
+            decoded_output: None,
+            data: Bytes::from(vec![0, 0, 0, selector]),
+            output: Bytes::new(),
+            gas_used,
+            success: true,
+            children,
+        }
+    }
+
+    fn export(calls: Vec<ExportedCall>) -> ExportedTraces {
+        let tests = BTreeMap::from([("testFoo()".to_string(), calls)]);
+        ExportedTraces { tests: BTreeMap::from([("FooTest".to_string(), tests)]) }
+    }
+
+    #[test]
+    fn can_diff_traces() {
+        let children = vec![call("Foo", 2, 10, vec![]), call("Bar", 3, 20, vec![])];
+        let a = export(vec![call("FooTest", 1, 100, children)]);
+        let mut changed = call("Bar", 3, 20, vec![]);
+        changed.output = Bytes::from(vec![1]);
+        let b = export(vec![call("FooTest", 1, 120, vec![changed, call("Baz", 4, 30, vec![])])]);
+
+        assert!(TraceDiff::new(&a, &a, 0).is_empty());
+
+        let diff = TraceDiff::new(&a, &b, 0);
+        assert_eq!(diff.tests.len(), 1);
+        let TestTraceDiffKind::Changed(diffs) = &diff.tests[0].kind else { panic!("{diff:?}") };
+        let [CallDiff::Changed { children, .. }] = &diffs[..] else { panic!("{diffs:?}") };
+        assert!(matches!(
+            &children[..],
+            [CallDiff::Removed(foo), CallDiff::Changed { .. }, CallDiff::Added(baz)]
+                if foo.label.as_deref() == Some("Foo") && baz.label.as_deref() == Some("Baz")
+        ));
+    }
+
+    #[test]
+    fn detects_changed_arguments() {
+        let mut a = call("FooTest", 1, 100, vec![]);
+        let mut b = a.clone();
+        b.data = Bytes::from(vec![0, 0, 0, 1, 2]);
+        let diff = TraceDiff::new(&export(vec![a.clone()]), &export(vec![b.clone()]), 0);
+        let TestTraceDiffKind::Changed(diffs) = &diff.tests[0].kind else { panic!("{diff:?}") };
+        assert!(matches!(&diffs[..], [CallDiff::Changed { .. }]), "{diffs:?}");
+        assert!(diff.to_string().contains("args: 0x00000001 → 0x0000000102"), "{diff}");
+
+        // decoded calls are compared by their signature and arguments
+        a.signature = Some("set(uint256)".to_string());
+        a.args = Some(vec!["1".to_string()]);
+        b.signature = a.signature.clone();
+        b.args = Some(vec!["2".to_string()]);
+        assert!(!TraceDiff::new(&export(vec![a.clone()]), &export(vec![b.clone()]), 0).is_empty());
+        b.args = a.args.clone();
+        assert!(TraceDiff::new(&export(vec![a]), &export(vec![b]), 0).is_empty());
+    }
+
+    #[test]
+    fn ignores_gas_below_threshold() {
+        let a = export(vec![call("FooTest", 1, 100, vec![])]);
+        let b = export(vec![call("FooTest", 1, 105, vec![])]);
+        assert!(!TraceDiff::new(&a, &b, 0).is_empty());
+        assert!(TraceDiff::new(&a, &b, 10).is_empty());
+        assert_eq!(TraceDiff::new(&a, &ExportedTraces::default(), 10).tests.len(), 1);
+    }
+}
@@ -1,3 +1,5 @@
+use foundry_compilers::artifacts::BytecodeHash;
+use foundry_config::Config;
 use foundry_test_utils::{forgetest, util::OutputExt};
 use std::path::PathBuf;
 
@@ -45,3 +47,174 @@ Compiler run successful!
         assert_eq!(expected, output, "expected: {}, output: {}", expected, output);
     }
 });
+
+// tests that bytecode pins ignoring the metadata match the bytecode compiled without metadata
+forgetest!(can_pin_bytecode_without_metadata, |prj, cmd| {
+    prj.add_source(
+        "Dummy",
+        r"
+contract Dummy {
+    function number() public pure returns (uint256) {
+        return 1;
+    }
+}
+",
+    )
+    .unwrap();
+    let wrong_pin = format!("Dummy={}", alloy_primitives::B256::ZERO);
+
+    // get the hash of the bytecode compiled without metadata from the mismatch
+    prj.write_config(Config { strip_metadata: true, ..Default::default() });
+    cmd.args(["build", "--force", "--assert-bytecode", &wrong_pin]);
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    let stripped_hash = stderr
+        .split("got ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_else(|| panic!("no bytecode hash mismatch: {stderr}"))
+        .to_string();
+
+    // the bytecode with metadata has a different hash, unless the metadata is ignored
+    prj.write_config(Config::default());
+    let pin = format!("Dummy={stripped_hash}");
+    cmd.forge_fuse().args(["build", "--force", "--assert-bytecode", &pin]);
+    cmd.assert_err();
+
+    cmd.forge_fuse().args(["build", "--force", "--ignore-metadata", "--assert-bytecode", &pin]);
+    cmd.assert_non_empty_stdout();
+});
+
+// tests that all the bytecode pins differing from the compiled bytecode are reported, and that
+// pins of unknown contracts are rejected
+forgetest!(reports_mismatched_bytecode_pins, |prj, cmd| {
+    prj.add_source(
+        "Pinned",
+        r"
+contract One {
+    function number() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract Two {
+    function number() public pure returns (uint256) {
+        return 2;
+    }
+}
+",
+    )
+    .unwrap();
+    let zero = alloy_primitives::B256::ZERO;
+    let got = |stderr: &str, contract: &str| {
+        let prefix = format!("{contract}: expected {zero}, got ");
+        stderr
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(&prefix))
+            .unwrap_or_else(|| panic!("no bytecode hash mismatch of {contract}: {stderr}"))
+            .to_string()
+    };
+
+    cmd.args(["build", "--force"])
+        .args(["--assert-bytecode", &format!("One={zero}")])
+        .args(["--assert-bytecode", &format!("Two={zero}")]);
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("bytecode hashes differ from the pinned values"), "{stderr}");
+    let one = got(&stderr, "One");
+    let two = got(&stderr, "Two");
+    assert_ne!(one, two);
+
+    // only the pins that differ are reported
+    cmd.forge_fuse()
+        .args(["build", "--force"])
+        .args(["--assert-bytecode", &format!("One={one}")])
+        .args(["--assert-bytecode", &format!("Two={zero}")]);
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(!stderr.contains("One: expected"), "{stderr}");
+    assert_eq!(got(&stderr, "Two"), two);
+
+    cmd.forge_fuse()
+        .args(["build", "--force"])
+        .args(["--assert-bytecode", &format!("One={one}")])
+        .args(["--assert-bytecode", &format!("Two={two}")]);
+    cmd.assert_non_empty_stdout();
+
+    cmd.forge_fuse().args(["build", "--force", "--assert-bytecode", &format!("Three={zero}")]);
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains("could not find artifact `Three` of bytecode pin"));
+});
+
+// tests that an attestation of the artifacts is verified until a contract changes
+forgetest!(can_attest_artifacts, |prj, cmd| {
+    let contract = |number: u32| {
+        format!(
+            r"
+contract Dummy {{
+    function number() public pure returns (uint256) {{
+        return {number};
+    }}
+}}
+"
+        )
+    };
+    prj.add_source("Dummy", &contract(1)).unwrap();
+    let key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    let signer = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    cmd.args(["build", "--attest", "--attestation-key", key]);
+    assert!(cmd.stdout_lossy().contains(&format!("signed by {signer}")));
+    assert!(prj.paths().artifacts.join("attestation.json").exists());
+
+    cmd.forge_fuse().args(["verify-attestation", "--signer", signer]);
+    assert!(cmd.stdout_lossy().contains("Verified 1 artifacts"));
+
+    // signed by another key
+    let other = format!("{:?}", alloy_primitives::Address::ZERO);
+    cmd.forge_fuse().args(["verify-attestation", "--signer", &other]);
+    cmd.assert_err();
+
+    // the signer is required
+    cmd.forge_fuse().arg("verify-attestation");
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("the signer of the attestation must be set"), "{stderr}");
+
+    // or pinned in the config
+    let config = Config { attestation_signer: Some(signer.parse().unwrap()), ..Default::default() };
+    prj.write_config(config);
+    cmd.forge_fuse().arg("verify-attestation");
+    assert!(cmd.stdout_lossy().contains("Verified 1 artifacts"));
+
+    prj.add_source("Dummy", &contract(2)).unwrap();
+    cmd.forge_fuse().arg("verify-attestation");
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("Dummy: different bytecode, deployed bytecode"), "{stderr}");
+});
+
+// tests that ignoring the metadata doesn't change the bytecode hash without a metadata hash
+forgetest!(ignores_no_metadata_hash, |prj, cmd| {
+    prj.add_source(
+        "Dummy",
+        r"
+contract Dummy {
+    function number() public pure returns (uint256) {
+        return 1;
+    }
+}
+",
+    )
+    .unwrap();
+    let wrong_pin = format!("Dummy={}", alloy_primitives::B256::ZERO);
+
+    prj.write_config(Config { bytecode_hash: BytecodeHash::None, ..Default::default() });
+    cmd.args(["build", "--force", "--assert-bytecode", &wrong_pin]);
+    let (_, stderr) = cmd.unchecked_output_lossy();
+    let hash = stderr
+        .split("got ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_else(|| panic!("no bytecode hash mismatch: {stderr}"))
+        .to_string();
+
+    let pin = format!("Dummy={hash}");
+    cmd.forge_fuse().args(["build", "--force", "--ignore-metadata", "--assert-bytecode", &pin]);
+    cmd.assert_non_empty_stdout();
+});
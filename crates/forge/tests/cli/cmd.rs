@@ -306,6 +306,65 @@ forgetest!(can_init_with_dir_and_template_and_branch, |prj, cmd| {
     assert!(prj.root().join("foobar/scripts").exists());
 });
 
+// `forge init --template zksync-aa` generates the built-in template, offline
+forgetest!(can_init_with_zksync_template, |prj, cmd| {
+    prj.wipe();
+
+    cmd.args(["init", "--template", "zksync-aa", "--offline"]).arg(prj.root());
+    cmd.assert_non_empty_stdout();
+    prj.assert_config_exists();
+
+    for path in [
+        "src/Counter.sol",
+        "src/ZkAccount.sol",
+        "src/interfaces/IZkSync.sol",
+        "test/ZkAccount.t.sol",
+        "test/utils/ZkVm.sol",
+        "script/ZkAccount.s.sol",
+    ] {
+        assert!(prj.root().join(path).exists(), "{path} was not generated");
+    }
+    assert!(!prj.root().join("lib/forge-std").exists());
+
+    let config = Config::load_with_root(prj.root());
+    assert!(config.zksync);
+    assert_eq!(config.zksync_settings.system_mode, Some(true));
+});
+
+// the projects generated from the built-in zkSync templates build and pass their tests
+forgetest!(can_build_and_test_zksync_templates, |prj, cmd| {
+    prj.wipe();
+
+    for template in ["zksync-aa", "zksync-paymaster", "zksync-bridge"] {
+        let root = prj.root().join(template);
+        cmd.forge_fuse().args(["init", "--template", template]).arg(&root);
+        cmd.assert_non_empty_stdout();
+
+        cmd.forge_fuse().args(["build", "--root"]).arg(&root);
+        cmd.assert_non_empty_stdout();
+
+        cmd.forge_fuse().args(["test", "--zksync", "--root"]).arg(&root);
+        let output = cmd.stdout_lossy();
+        assert!(!output.contains("[FAIL"), "{template}: {output}");
+        if template == "zksync-bridge" {
+            // the greetings to L1 are proven with the batch, index and proof of their messages
+            for test in ["test_GreetL1()", "test_UnsentGreetingIsNotProven()"] {
+                assert!(output.contains(&format!("[PASS] {test}")), "{template}: {output}");
+            }
+        }
+    }
+});
+
+// built-in templates can't be fetched from a branch, and repository templates can't be offline
+forgetest!(can_not_init_with_invalid_template_args, |prj, cmd| {
+    cmd.args(["init", "--template", "zksync-bridge", "--branch", "main"]).arg(prj.root());
+    cmd.assert_err();
+
+    cmd.forge_fuse().args(["init", "--template", "foundry-rs/forge-template", "--offline"]);
+    cmd.arg(prj.root());
+    cmd.assert_err();
+});
+
 // `forge init --force` works on non-empty dirs
 forgetest!(can_init_non_empty, |prj, cmd| {
     prj.create_file("README.md", "non-empty dir");
@@ -640,6 +699,33 @@ contract ATest is DSTest {
     let _ = cmd.output();
 });
 
+// test that `forge snapshot --zksync --check --tolerance` accepts the tests publishing no pubdata
+forgetest!(can_check_zk_snapshot_without_pubdata, |prj, cmd| {
+    prj.insert_ds_test();
+
+    prj.add_source(
+        "ATest.t.sol",
+        r#"
+import "./test.sol";
+contract ATest is DSTest {
+    function testExample() public {
+        assertTrue(true);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["snapshot", "--zksync"]);
+    cmd.assert_non_empty_stdout();
+    let snapshot = std::fs::read_to_string(prj.root().join(".zk-gas-snapshot")).unwrap();
+    assert!(snapshot.contains("ATest:testExample() (ergs: "), "{snapshot}");
+    assert!(snapshot.contains("pubdata: 0)"), "{snapshot}");
+
+    cmd.forge_fuse().args(["snapshot", "--zksync", "--check", "--tolerance", "5"]);
+    cmd.assert_non_empty_stdout();
+});
+
 // test that `forge build` does not print `(with warnings)` if file path is ignored
 forgetest!(can_compile_without_warnings_ignored_file_paths, |prj, cmd| {
     // Ignoring path and setting empty error_codes as default would set would set some error codes
@@ -1425,6 +1511,56 @@ contract ContractThreeTest is DSTest {
     assert!(third_out.contains("foo") && third_out.contains("bar") && third_out.contains("baz"));
 });
 
+forgetest!(gas_report_export_and_diff, |prj, cmd| {
+    prj.insert_ds_test();
+    let contract = |loops: u32| {
+        format!(
+            r#"
+import "./test.sol";
+
+contract Counter {{
+    uint256 public i;
+
+    function foo() public {{
+        while (i < {loops}) {{
+            i++;
+        }}
+    }}
+}}
+
+contract CounterTest is DSTest {{
+    function testFoo() public {{
+        new Counter().foo();
+    }}
+}}
+"#
+        )
+    };
+    prj.add_source("Counter.sol", &contract(5)).unwrap();
+
+    let report = prj.root().join("gas-report.json");
+    cmd.args(["test", "--gas-report", "--gas-report-format", "json", "--gas-report-out"])
+        .arg(&report);
+    assert!(cmd.stdout_lossy().contains("Gas report written to"));
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert!(json["contracts"]["src/Counter.sol:Counter"]["functions"]["foo"].is_object());
+
+    cmd.forge_fuse().args(["test", "--gas-report", "--gas-report-format", "csv"]);
+    assert!(cmd.stdout_lossy().contains(
+        "contract,deployment_cost,deployment_size,deployment_ergs,function,min,avg,median,max,calls"
+    ));
+
+    cmd.forge_fuse().args(["gas-report", "--diff"]).arg(&report);
+    assert!(cmd.stdout_lossy().contains("No gas changes"));
+
+    prj.add_source("Counter.sol", &contract(50)).unwrap();
+    cmd.forge_fuse().args(["gas-report", "--tolerance", "10", "--diff"]).arg(&report);
+    let err = cmd.unchecked_output();
+    assert!(!err.status.success());
+    assert!(String::from_utf8_lossy(&err.stdout).contains("foo()"));
+});
+
 forgetest_init!(can_use_absolute_imports, |prj, cmd| {
     let remapping = prj.paths().libraries[0].join("myDependency");
     let config = Config {
@@ -1637,3 +1773,66 @@ forgetest_init!(can_inspect_counter_pretty, |prj, cmd| {
 }"
     );
 });
+
+// checks that forge callgraph finds the calls of the default project
+forgetest_init!(can_generate_callgraph, |prj, cmd| {
+    cmd.args(["callgraph", "--static-only", "--format", "json"]);
+    let output: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let edges = output["edges"].as_array().unwrap();
+    assert!(edges.iter().any(|edge| edge["from"] == "CounterTest.test_Increment" &&
+        edge["to"] == "Counter.increment" &&
+        edge["kind"] == "external"));
+
+    // the observed calls are merged into the graph, the test output isn't mixed into the JSON
+    cmd.forge_fuse().args(["callgraph", "--format", "json"]);
+    let output: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let edges = output["edges"].as_array().unwrap();
+    assert!(edges.iter().any(|edge| edge["from"] == "CounterTest.test_Increment" &&
+        edge["to"] == "Counter.increment" &&
+        edge["observed"].as_u64().unwrap() > 0 &&
+        edge["cross_vm"] == false));
+
+    cmd.forge_fuse().args(["callgraph", "--export"]).arg(prj.root().join("callgraph.dot"));
+    cmd.assert_success();
+    let dot = std::fs::read_to_string(prj.root().join("callgraph.dot")).unwrap();
+    assert!(dot.starts_with("digraph callgraph {"));
+    assert!(dot.contains(r#""CounterTest.setUp" -> "Counter.setNumber""#));
+});
+
+// checks that forge trace-diff compares the exported traces of two test runs
+forgetest_init!(can_diff_exported_traces, |prj, cmd| {
+    let a = prj.root().join("a.json");
+    let b = prj.root().join("b.json");
+    cmd.args(["test", "--export-traces"]).arg(&a);
+    cmd.assert_success();
+
+    cmd.forge_fuse().args(["trace-diff"]).arg(&a).arg(&a);
+    assert!(cmd.stdout_lossy().contains("No differences found."));
+
+    prj.add_source(
+        "Counter.sol",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function setNumber(uint256 newNumber) public {
+        number = newNumber;
+    }
+
+    function increment() public {
+        number = number + 1;
+        number = number * 1;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    cmd.forge_fuse().args(["test", "--export-traces"]).arg(&b);
+    cmd.assert_success();
+
+    cmd.forge_fuse().args(["trace-diff", "--check"]).arg(&a).arg(&b);
+    let output = cmd.execute();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Counter::increment()"), "{stdout}");
+});
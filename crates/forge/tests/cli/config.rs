@@ -68,13 +68,21 @@ forgetest!(can_extract_config_values, |prj, cmd| {
             ..Default::default()
         },
         invariant: InvariantConfig { runs: 256, ..Default::default() },
+        max_duration: Some("5s".parse().unwrap()),
+        max_duration_warn_only: true,
+        test_matrix: vec![],
+        offline_tests: true,
+        offline_tests_allow_urls: vec!["https://mainnet.era.zksync.io".to_string()],
+        perf_mode: true,
         ffi: true,
+        prompt_timeout: 0,
+        prompt_answers: Some("prompt-answers.json".into()),
         always_use_create_2_factory: false,
         sender: "00a329c0648769A73afAc7F9381D08FB43dBEA72".parse().unwrap(),
         tx_origin: "00a329c0648769A73afAc7F9F81E08FB43dBEA72".parse().unwrap(),
         initial_balance: U256::from(0xffffffffffffffffffffffffu128),
         block_number: 10,
-        fork_block_number: Some(200),
+        fork_block_number: Some(200u64.into()),
         chain: Some(9999.into()),
         gas_limit: 99_000_000u64.into(),
         code_size_limit: Some(100000),
@@ -88,6 +96,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         memory_limit: 1 << 27,
         eth_rpc_url: Some("localhost".to_string()),
         eth_rpc_jwt: None,
+        bundler_url: None,
         etherscan_api_key: None,
         etherscan: Default::default(),
         verbosity: 4,
@@ -98,6 +107,9 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         ignored_error_codes: vec![],
         ignored_file_paths: vec![],
         deny_warnings: false,
+        assert_bytecode: vec![],
+        assert_bytecode_ignore_metadata: false,
+        attestation_signer: None,
         via_ir: true,
         ast: false,
         rpc_storage_caching: StorageCachingConfig {
@@ -109,6 +121,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         use_literal_content: false,
         bytecode_hash: Default::default(),
         cbor_metadata: true,
+        strip_metadata: false,
         revert_strings: Some(RevertStrings::Strip),
         sparse_mode: true,
         allow_paths: vec![],
@@ -120,11 +133,15 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         doc: Default::default(),
         fs_permissions: Default::default(),
         labels: Default::default(),
+        genesis: Default::default(),
         cancun: true,
         isolate: true,
+        trace_return_data_limit: Some(1024),
         __non_exhaustive: (),
         __warnings: vec![],
         compiler_path: Default::default(),
+        zksolc: None,
+        zk_out: "zkout-test".into(),
         zk_optimizer: Default::default(),
         mode: Default::default(),
         zksync: false,
@@ -133,6 +150,16 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         is_system: Default::default(),
         force_evmla: Default::default(),
         detect_missing_libraries: Default::default(),
+        zksync_settings: Default::default(),
+        zk_max_cycles: Default::default(),
+        zk_max_memory: Default::default(),
+        zk_skip: vec![],
+        zk_deny_warnings: vec![],
+        zk_allow_warnings: vec![],
+        zk_warnings_overrides: vec![],
+        zk_overrides: Default::default(),
+        zk_combined_artifacts: false,
+        zk_messaging: None,
     };
     prj.write_config(input.clone());
     let config = cmd.config();
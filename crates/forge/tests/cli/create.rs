@@ -308,3 +308,126 @@ contract UniswapV2Swap {
     let (stdout, _) = cmd.output_lossy();
     assert!(stdout.contains("Deployed to: 0x5FbDB2315678afecb367f032d93F642f64180aa3"));
 });
+
+// tests that `forge deploy` compiles the project once and deploys the contracts of a plan in order
+forgetest_async!(can_deploy_plan, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let pk = hex::encode(wallet.signer().to_bytes());
+
+    let config = Config { bytecode_hash: BytecodeHash::None, ..Default::default() };
+    prj.write_config(config);
+
+    prj.add_source(
+        "Registry",
+        r#"
+contract Registry {}
+
+contract Token {
+    address public registry;
+
+    constructor(address _registry) {
+        registry = _registry;
+    }
+}
+"#,
+    )
+    .unwrap();
+    let plan = prj.root().join("deploy.toml");
+    std::fs::write(
+        &plan,
+        format!(
+            r#"
+[chains.local]
+rpc_url = "{rpc}"
+
+[contracts.token]
+contract = "src/Registry.sol:Token"
+args = ["${{registry}}"]
+
+[contracts.registry]
+contract = "src/Registry.sol:Registry"
+"#
+        ),
+    )
+    .unwrap();
+
+    cmd.forge_fuse().arg("deploy").arg(&plan).args(["--private-key", pk.as_str()]);
+    let stdout = cmd.stdout_lossy();
+    assert_eq!(stdout.matches("Compiler run successful").count(), 1, "{stdout}");
+    assert!(!stdout.contains("No files changed"), "{stdout}");
+    let registry = stdout.find("local/registry: deploying").unwrap();
+    let token = stdout.find("local/token: deploying").unwrap();
+    assert!(registry < token, "{stdout}");
+    assert!(stdout.contains("Deployed to: 0x5FbDB2315678afecb367f032d93F642f64180aa3"));
+    assert!(stdout.contains("Deployed to: 0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512"));
+
+    // the deployments are recorded and skipped on the next run
+    let state = prj.root().join("broadcast/deploy/deploy.json");
+    assert!(state.exists());
+    cmd.forge_fuse().arg("deploy").arg(&plan).args(["--private-key", pk.as_str()]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("local/registry: already deployed at"), "{stdout}");
+    assert!(stdout.contains("local/token: already deployed at"), "{stdout}");
+});
+
+// tests that `--estimate-only` prints the estimated cost of a deployment without sending it
+forgetest_async!(can_estimate_create_only, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let rpc = handle.http_endpoint();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let pk = hex::encode(wallet.signer().to_bytes());
+
+    let config = Config { bytecode_hash: BytecodeHash::None, ..Default::default() };
+    prj.write_config(config);
+
+    let args = [
+        "create",
+        format!("./src/{TEMPLATE_CONTRACT}.sol:{TEMPLATE_CONTRACT}"),
+        "--rpc-url".to_string(),
+        rpc,
+        "--private-key".to_string(),
+        pk,
+    ];
+    cmd.forge_fuse().args(&args).arg("--estimate-only");
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Address: 0x5FbDB2315678afecb367f032d93F642f64180aa3"), "{stdout}");
+    assert!(stdout.contains("Estimated gas: "), "{stdout}");
+    assert!(stdout.contains("Estimated fee: "), "{stdout}");
+    assert!(!stdout.contains("Estimated pubdata"), "{stdout}");
+    assert!(!stdout.contains("Transaction hash"), "{stdout}");
+
+    // nothing was sent, so the contract is deployed at the estimated address
+    cmd.forge_fuse().args(&args);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Deployed to: 0x5FbDB2315678afecb367f032d93F642f64180aa3"), "{stdout}");
+});
+
+// tests that `--estimate-only` prints the pubdata of a deployment in EraVM
+forgetest_async!(can_estimate_zk_create_only, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+
+    let (_handler, handle) = anvil::zksync::spawn(NodeConfig::test()).await;
+
+    cmd.forge_fuse().args([
+        "create",
+        format!("./src/{TEMPLATE_CONTRACT}.sol:{TEMPLATE_CONTRACT}").as_str(),
+        "--zksync",
+        "--rpc-url",
+        handle.http_endpoint().as_str(),
+        "--private-key",
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "--estimate-only",
+        "--json",
+    ]);
+    let stdout = cmd.stdout_lossy();
+    let estimate: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    // the bytecode of the contract and the initial write of its code hash, at least
+    assert!(estimate["pubdata"].as_u64().unwrap() > 65, "{stdout}");
+    assert_ne!(estimate["gas"], "0", "{stdout}");
+});
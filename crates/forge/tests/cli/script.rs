@@ -1,9 +1,13 @@
 //! Contains various tests related to `forge script`.
 
-use crate::constants::TEMPLATE_CONTRACT;
+use crate::{
+    constants::TEMPLATE_CONTRACT,
+    utils::{add_linked_zk_libraries, LIB_ADDRESS, MATH_ADDRESS},
+};
 use alloy_primitives::{Address, Bytes};
 use anvil::{spawn, NodeConfig};
 use foundry_common::rpc;
+use foundry_config::{Config, ForkBlock};
 use foundry_test_utils::{util::OutputExt, ScriptOutcome, ScriptTester};
 use regex::Regex;
 use serde_json::Value;
@@ -302,6 +306,34 @@ contract Demo {
     );
 });
 
+// Tests that private keys passed on the command line are redacted from logs, traces and json output
+forgetest!(redacts_private_key_from_script_output, |prj, cmd| {
+    let script = prj
+        .add_source(
+            "Foo",
+            r#"
+contract Demo {
+    event log_bytes32(bytes32);
+    function run(bytes32 key) external returns (bytes32) {
+        emit log_bytes32(key);
+        require(key == bytes32(0), "unexpected key");
+        return key;
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let key = "0xAC0974BEC39A17E36BA4A6B4D238FF944BACB478CBED5EFCAEE0BA3D4D8FD3E1";
+    let args = ["--sig", "run(bytes32)", key, "--private-key", key];
+    cmd.arg("script").arg(&script).args(args).arg("-vvvv");
+    let out = cmd.assert_no_secrets(&[key]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("<redacted>"));
+
+    cmd.forge_fuse().arg("script").arg(&script).args(args).arg("--json");
+    cmd.assert_no_secrets(&[key]);
+});
+
 forgetest_async!(can_broadcast_script_skipping_simulation, |prj, cmd| {
     foundry_test_utils::util::initialize(prj.root());
     // This example script would fail in on-chain simulation
@@ -652,6 +684,31 @@ forgetest_async!(fail_broadcast_staticcall, |prj, cmd| {
         .simulate(ScriptOutcome::StaticCallNotAllowed);
 });
 
+forgetest_async!(can_broadcast_with_expected_events, |prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+    tester
+        .load_private_keys(&[0])
+        .await
+        .add_sig("BroadcastExpectEvent", "expectEmitted()")
+        .simulate(ScriptOutcome::OkSimulation)
+        .broadcast(ScriptOutcome::OkBroadcast)
+        .assert_nonce_increment(&[(0, 2)])
+        .await;
+});
+
+forgetest_async!(fail_broadcast_expected_event_not_emitted, |prj, cmd| {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+    tester
+        .load_private_keys(&[0])
+        .await
+        .add_sig("BroadcastExpectEvent", "expectNotEmitted()")
+        .simulate(ScriptOutcome::BroadcastEventNotEmitted);
+});
+
 forgetest_async!(check_broadcast_log, |prj, cmd| {
     let (api, handle) = spawn(NodeConfig::test()).await;
     let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
@@ -1243,3 +1300,492 @@ contract DeployScript is Script {
         assert_eq!(transactions.len(), 3);
     }
 );
+
+// Tests that the library API executes a script and returns the transactions it broadcasts
+forgetest_async!(can_execute_script_with_library_api, |prj, _cmd| {
+    use ethers_core::types::transaction::eip2718::TypedTransaction;
+    use forge::{
+        opts::EvmOpts,
+        script::{ScriptExecutor, ScriptTransactionRequest},
+    };
+    use foundry_config::Config;
+
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "Counter",
+            r#"
+import "forge-std/Script.sol";
+
+contract Counter {
+    uint256 public number;
+
+    function setNumber(uint256 newNumber) public {
+        number = newNumber;
+    }
+}
+
+contract CounterScript is Script {
+    function run(uint256 number) public {
+        vm.startBroadcast();
+        Counter counter = new Counter();
+        counter.setNumber(number);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let figment = Config::figment_with_root(prj.root());
+    let config = Config::from_provider(&figment);
+    let evm_opts = figment.extract::<EvmOpts>().unwrap();
+    let executor =
+        ScriptExecutor::new(config, evm_opts, format!("{}:CounterScript", script.display()))
+            .sig("run(uint256)")
+            .args(["42"]);
+
+    let output = executor.clone().execute().await.unwrap();
+    assert!(output.success);
+    assert_eq!(output.transactions.len(), 2);
+    let requests = output
+        .transactions
+        .iter()
+        .map(|tx| match &tx.request {
+            ScriptTransactionRequest::Evm(request) => request.clone(),
+            ScriptTransactionRequest::Eip712(_) => panic!("unexpected EIP-712 transaction"),
+        })
+        .collect::<Vec<_>>();
+    assert!(requests.iter().all(|request| matches!(request, TypedTransaction::Eip1559(_))));
+    assert!(requests[0].to().is_none());
+    let data = requests[1].data().unwrap();
+    assert_eq!(data[..4], alloy_primitives::keccak256("setNumber(uint256)")[..4]);
+    assert_eq!(requests[1].nonce(), requests[0].nonce().map(|nonce| nonce + 1).as_ref());
+
+    let output = executor.legacy(true).execute().await.unwrap();
+    assert!(output.transactions.iter().all(|tx| matches!(
+        &tx.request,
+        ScriptTransactionRequest::Evm(TypedTransaction::Legacy(_))
+    )));
+});
+
+// Tests that a sender which can't cover the fees of its transactions fails the script before
+// broadcasting
+forgetest_async!(can_check_sender_funds, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "Foo",
+            r#"
+import "forge-std/Script.sol";
+
+contract Empty {}
+
+contract DeployScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        new Empty();
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let sender = Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap();
+    api.anvil_set_balance(sender, alloy_primitives::U256::from(1)).await.unwrap();
+
+    cmd.set_current_dir(prj.root());
+    cmd.args([
+        "script",
+        &format!("{}:DeployScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--private-key",
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    ]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("1 sender(s) have insufficient funds on chain"), "{stdout}\n{stderr}");
+    assert!(
+        stderr.contains(&format!("{sender} can't cover the estimated fees of")),
+        "{stdout}\n{stderr}"
+    );
+});
+
+// Tests that the zkSync transactions of underfunded senders, including publishing their factory
+// dependencies, fail the script before broadcasting, with the shortfall of every sender
+forgetest_async!(can_check_zk_sender_funds, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "Foo",
+            r#"
+import "forge-std/Script.sol";
+
+contract Empty {}
+
+contract DeployScript is Script {
+    function run() external {
+        vm.broadcast(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80);
+        new Empty();
+        vm.broadcast(0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d);
+        new Empty();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_handler, handle) =
+        anvil::zksync::spawn(NodeConfig::test().with_genesis_balance(1u64)).await;
+
+    cmd.set_current_dir(prj.root());
+    cmd.args([
+        "script",
+        &format!("{}:DeployScript", script.display()),
+        "--zksync",
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--broadcast",
+    ]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("2 sender(s) have insufficient funds on chain"), "{stdout}\n{stderr}");
+    for sender in
+        ["0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266", "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"]
+    {
+        assert!(
+            stderr.contains(&format!("{sender} can't cover the estimated fees of")),
+            "{stdout}\n{stderr}"
+        );
+    }
+    assert!(!stdout.contains("ONCHAIN EXECUTION COMPLETE"), "{stdout}");
+});
+
+// Tests that a `fork_block_number` tag is pinned to the block it first resolves to
+forgetest_async!(can_pin_fork_block_tag_in_script, |prj, cmd| {
+    let script = prj
+        .add_source(
+            "PinScript",
+            r#"
+contract PinScript {
+    function run() external view {
+        require(block.number == 3, "not forked from the pinned block");
+    }
+}
+   "#,
+        )
+        .unwrap();
+    prj.write_config(Config { fork_block_number: Some(ForkBlock::Latest), ..Default::default() });
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    api.anvil_mine(Some(alloy_primitives::U256::from(3)), None).await.unwrap();
+
+    cmd.args([
+        "script",
+        &format!("{}:PinScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+    ]);
+    assert!(cmd.stdout_lossy().contains("Pinned the `latest` fork block of chain 31337 to 3"));
+    assert!(prj.root().join("fork.lock").exists());
+
+    // later blocks don't change the fork until the pin is refreshed
+    api.anvil_mine(Some(alloy_primitives::U256::from(2)), None).await.unwrap();
+    cmd.assert_non_empty_stdout();
+});
+
+// Tests that the transactions sent by a Safe are written as a Safe Transaction Builder bundle
+forgetest_async!(can_write_safe_bundle, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "SafeScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract SafeScript is Script {
+    function run() external {
+        vm.startBroadcast(address(0x5AFE));
+        payable(address(0x1111)).transfer(1);
+        payable(address(0x2222)).transfer(2);
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let safe = Address::from_str("0x0000000000000000000000000000000000005AFE").unwrap();
+    api.anvil_set_balance(safe, alloy_primitives::U256::from(10)).await.unwrap();
+
+    cmd.set_current_dir(prj.root());
+    cmd.args([
+        "script",
+        &format!("{}:SafeScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--safe",
+        &safe.to_string(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(
+        output.contains(&format!("Wrote the 2 transaction(s) for the Safe {safe} on chain 31337"))
+    );
+    // the Safe pays for nothing itself
+    assert!(!output.contains("might not be able to cover"));
+
+    let bundle: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            prj.root().join("broadcast/SafeScript.sol/31337/dry-run/run-safe.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bundle["chainId"], "31337");
+    assert_eq!(bundle["meta"]["createdFromSafeAddress"], safe.to_string());
+    let transactions = bundle["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(
+        transactions[0]["to"].as_str().unwrap().to_lowercase(),
+        "0x0000000000000000000000000000000000001111"
+    );
+    assert_eq!(transactions[1]["value"], "2");
+});
+
+// Tests that a script forking a zkSync Era chain runs in EraVM
+forgetest_async!(can_detect_zksync_fork_in_script, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "EraScript",
+            r#"
+import "forge-std/Script.sol";
+
+contract EraScript is Script {
+    function run() external view returns (uint256) {
+        return block.number;
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_handler, handle) = anvil::zksync::spawn(NodeConfig::test()).await;
+
+    cmd.set_current_dir(prj.root());
+    cmd.args([
+        "script",
+        &format!("{}:EraScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Forking a zkSync Era chain, running the script in EraVM"), "{output}");
+    assert!(output.contains("Script ran successfully."), "{output}");
+
+    // an Ethereum RPC is left to the EVM
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    cmd.forge_fuse().args([
+        "script",
+        &format!("{}:EraScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(!output.contains("Forking a zkSync Era chain"), "{output}");
+});
+
+// Tests that a zkSync script deploys the libraries after the ones they link in the current
+// compilation, not in the order of the cached missing libraries report
+forgetest_init!(deploys_linked_zk_libraries_in_order_in_script, |prj, cmd| {
+    prj.wipe_contracts();
+    add_linked_zk_libraries(&prj, LIB_ADDRESS, MATH_ADDRESS);
+    let script = prj
+        .add_source(
+            "MathScript",
+            r#"
+import "forge-std/Script.sol";
+import {Math} from "./Math.sol";
+
+contract MathScript is Script {
+    function run() external pure returns (uint256) {
+        return Math.two();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    // the `CREATE2` address of a library, from the error of a deployment at another address
+    let create2_address = |stderr: &str, id: &str| {
+        let re = Regex::new(&format!("set `{id}:(0x[0-9a-fA-F]{{40}})`")).unwrap();
+        re.captures(stderr).unwrap_or_else(|| panic!("{stderr}"))[1].to_string()
+    };
+
+    // the configured addresses aren't the `CREATE2` ones, so the first deployment fails: the one
+    // of `Lib`, which `Math` links
+    cmd.args(["script", &format!("{}:MathScript", script.display()), "--zksync"]);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("library src/Lib.sol:Lib is configured at"), "{stdout}\n{stderr}");
+    assert!(!stderr.contains("cycle"), "{stderr}");
+
+    // with `Lib` at its address, `Math` is deployed after it, at an address depending on the
+    // address of `Lib` it links
+    let lib = create2_address(&stderr, "src/Lib.sol:Lib");
+    add_linked_zk_libraries(&prj, &lib, MATH_ADDRESS);
+    let (stdout, stderr) = cmd.unchecked_output_lossy();
+    assert!(stderr.contains("library src/Math.sol:Math is configured at"), "{stdout}\n{stderr}");
+
+    let math = create2_address(&stderr, "src/Math.sol:Math");
+    add_linked_zk_libraries(&prj, &lib, &math);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Script ran successfully."), "{stdout}");
+});
+
+// Tests that the user operations of a script are submitted through the bundler and saved with the
+// transactions of the script
+forgetest_async!(can_broadcast_user_operations, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let script = prj
+        .add_source(
+            "UserOpScript",
+            r#"
+import "forge-std/Script.sol";
+
+interface UserOpVm {
+    function broadcastUserOp(address entryPoint, address account, bytes calldata callData) external;
+}
+
+contract UserOpScript is Script {
+    function run() external {
+        vm.startBroadcast(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80);
+        UserOpVm(address(vm)).broadcastUserOp(
+            0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789,
+            address(0xAC),
+            hex"b61d27f6"
+        );
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    // an entry point whose `getNonce` returns 0
+    let entry_point = Address::from_str("0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789").unwrap();
+    api.anvil_set_code(
+        entry_point,
+        Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]),
+    )
+    .await
+    .unwrap();
+
+    let user_op_hash = format!("0x{}", "ab".repeat(32));
+    let bundler = mock_bundler(user_op_hash.clone());
+
+    prj.write_config(Config {
+        bundler_url: Some(format!("http://{bundler}/{{chain_id}}")),
+        ..Default::default()
+    });
+
+    cmd.set_current_dir(prj.root());
+    cmd.args([
+        "script",
+        &format!("{}:UserOpScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--broadcast",
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains(&format!("[Success] UserOpHash: {user_op_hash}")), "{output}");
+
+    let sequence: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            prj.root().join("broadcast/UserOpScript.sol/31337/run-user-ops-latest.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(sequence["chain"], 31337);
+    let user_operations = sequence["userOperations"].as_array().unwrap();
+    assert_eq!(user_operations.len(), 1);
+    assert_eq!(user_operations[0]["hash"], user_op_hash);
+    assert_eq!(user_operations[0]["sender"], "0x00000000000000000000000000000000000000ac");
+    assert_eq!(user_operations[0]["success"], true);
+});
+
+/// Spawns a bundler answering the requests of `forge script` on the chain 31337, returning its
+/// address.
+fn mock_bundler(user_op_hash: String) -> std::net::SocketAddr {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+
+            // only the bundler of the chain of the fork is used
+            assert!(request_line.contains("/31337"), "{request_line}");
+            let result = match request["method"].as_str().unwrap() {
+                "eth_estimateUserOperationGas" => serde_json::json!({
+                    "preVerificationGas": "0xc350",
+                    "verificationGasLimit": "0x186a0",
+                    "callGasLimit": "0x186a0",
+                }),
+                "eth_sendUserOperation" => serde_json::json!(user_op_hash),
+                "eth_getUserOperationReceipt" => serde_json::json!({
+                    "userOpHash": user_op_hash,
+                    "success": true,
+                    "actualGasCost": "0x1",
+                    "actualGasUsed": "0x1",
+                    "receipt": {
+                        "transactionHash": format!("0x{}", "cd".repeat(32)),
+                        "transactionIndex": "0x0",
+                        "from": "0x0000000000000000000000000000000000004337",
+                        "cumulativeGasUsed": "0x1",
+                        "logs": [],
+                        "logsBloom": format!("0x{}", "00".repeat(256)),
+                    },
+                }),
+                method => panic!("unexpected bundler request {method}"),
+            };
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": result,
+            })
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+        }
+    });
+    addr
+}
@@ -1,7 +1,13 @@
 //! Contains various tests for checking `forge test`
+use crate::utils::{add_linked_zk_libraries, LIB_ADDRESS, MATH_ADDRESS};
 use foundry_common::rpc;
-use foundry_config::Config;
-use foundry_test_utils::util::{OutputExt, OTHER_SOLC_VERSION, SOLC_VERSION};
+use foundry_config::{
+    fs_permissions::PathPermission, Config, FsPermissions, GenesisContract, InvariantConfig,
+};
+use foundry_test_utils::{
+    util::{OutputExt, OTHER_SOLC_VERSION, SOLC_VERSION},
+    TestCommand, TestProject,
+};
 use std::{path::PathBuf, str::FromStr};
 
 // tests that test filters are handled correctly
@@ -490,3 +496,875 @@ contract TransientTest is Test {
 
     cmd.args(["test", "-vvvv", "--isolate", "--evm-version", "cancun"]).assert_success();
 });
+
+// only runs the suites whose inputs changed since they last passed
+forgetest_init!(can_run_incremental_tests, |prj, cmd| {
+    prj.add_test(
+        "Other.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract OtherTest is Test {
+    function test_other() public {
+        assertEq(uint256(1), 1);
+    }
+}
+   "#,
+    )
+    .unwrap();
+    let inputs = prj.root().join("cache").join("inputs.json");
+    let inputs = inputs.to_str().unwrap();
+
+    cmd.args(["test", "--incremental", inputs]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("CounterTest") && out.contains("OtherTest"), "{out}");
+
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Skipping 2 unchanged test suites"), "{out}");
+
+    let counter = prj.root().join("src").join("Counter.sol");
+    let source = std::fs::read_to_string(&counter).unwrap();
+    let source = source.replacen("number++;", "number += 1;", 1);
+    std::fs::write(&counter, source).unwrap();
+
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Skipping 1 unchanged test suite"), "{out}");
+    assert!(out.contains("CounterTest") && !out.contains("OtherTest"), "{out}");
+});
+
+forgetest_init!(can_bundle_failures, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "Failing.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract FailingTest is Test {
+    function test_passes() public {
+        assertEq(uint256(1), 1);
+    }
+
+    function testFuzz_fails(uint256 x) public {
+        assertLt(x, 10);
+    }
+}
+   "#,
+    )
+    .unwrap();
+    let bundle = prj.root().join("failures.tar.gz");
+    let bundle = bundle.to_str().unwrap();
+
+    cmd.args(["test", "--fuzz-seed", "1", "--bundle-failures", bundle]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("Bundled 1 failed test"), "{out}");
+
+    cmd.forge_fuse().args(["test", "--from-bundle", bundle]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("Replaying 1 failed test"), "{out}");
+    assert!(out.contains("testFuzz_fails") && !out.contains("test_passes"), "{out}");
+});
+
+// tests that a bundle records the generated fuzz seed and replays the bundled artifacts
+forgetest_init!(can_replay_bundled_artifacts, |prj, cmd| {
+    prj.wipe_contracts();
+    let failing = |assertion: &str| {
+        format!(
+            r#"
+import {{Test}} from "forge-std/Test.sol";
+
+contract FailingTest is Test {{
+    function testFuzz_fails(uint256 x) public {{
+        {assertion};
+    }}
+}}
+   "#
+        )
+    };
+    prj.add_test("Failing.t.sol", &failing("assertLt(x, 10)")).unwrap();
+    let bundle = prj.root().join("failures.tar.gz");
+    let bundle = bundle.to_str().unwrap();
+
+    cmd.args(["test", "--bundle-failures", bundle]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("Bundled 1 failed test"), "{out}");
+
+    // the test passes with the changed source, the bundled artifact still fails
+    prj.add_test("Failing.t.sol", &failing("assertGe(x, 0)")).unwrap();
+    cmd.forge_fuse().args(["test", "--from-bundle", bundle]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("test/Failing.t.sol:FailingTest changed since the bundle was created"));
+    assert!(
+        out.contains("Replaying test/Failing.t.sol:FailingTest with the artifact of the bundle"),
+        "{out}"
+    );
+    assert!(out.contains("[FAIL."), "{out}");
+
+    let manifest = prj.root().join("cache/bundles/failures/bundle.json");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(manifest).unwrap()).unwrap();
+    assert!(manifest["fuzzSeed"].is_string(), "{manifest}");
+});
+
+forgetest_init!(can_forbid_network_access_with_offline_tests, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "Offline.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract OfflineTest is Test {
+    function test_createFork() public {
+        vm.createFork("https://eth.llamarpc.com");
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--offline-tests"]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("[FAIL"), "{out}");
+    assert!(out.contains("is forbidden with `offline_tests`"), "{out}");
+});
+
+// tests that `offline_tests` gates every cheatcode accessing the network, and only allows the
+// fork URL
+forgetest_async!(can_gate_network_cheatcodes_with_offline_tests, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let (_api, fork) = anvil::spawn(anvil::NodeConfig::test()).await;
+    let (_api, other) = anvil::spawn(anvil::NodeConfig::test()).await;
+
+    prj.wipe_contracts();
+    prj.add_test(
+        "Offline.t.sol",
+        &format!(
+            r#"
+import {{Test}} from "forge-std/Test.sol";
+
+contract OfflineTest is Test {{
+    function test_ffi() public {{
+        string[] memory inputs = new string[](1);
+        inputs[0] = "true";
+        vm.ffi(inputs);
+    }}
+
+    function test_createSelectFork() public {{
+        vm.createSelectFork("{other}");
+    }}
+
+    function test_rpc() public {{
+        vm.rpc("eth_chainId", "[]");
+    }}
+
+    function test_getLogs() public {{
+        vm.eth_getLogs(0, 0, address(0), new bytes32[](0));
+    }}
+}}
+   "#,
+            other = other.http_endpoint()
+        ),
+    )
+    .unwrap();
+
+    cmd.args(["test", "--offline-tests", "--ffi", "--fork-url", &fork.http_endpoint()]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("`vm.ffi` is forbidden with `offline_tests`"), "{out}");
+    assert!(
+        out.contains(&format!("network access to `{}` is forbidden", other.http_endpoint())),
+        "{out}"
+    );
+    assert!(out.contains("[PASS] test_rpc()"), "{out}");
+    assert!(out.contains("[PASS] test_getLogs()"), "{out}");
+});
+
+// tests that the static ABI size warnings are reported with the suites importing the contracts
+forgetest_init!(can_report_abi_size_warnings_in_json, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_source(
+        "Large.sol",
+        r#"
+contract Large {
+    function large(uint256[30000] calldata values) external pure returns (uint256) {
+        return values[0];
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.add_test(
+        "Large.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Large} from "../src/Large.sol";
+
+contract LargeTest is Test {
+    function test_deploy() public {
+        new Large();
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--zksync", "--json"]);
+    let out = cmd.stdout_lossy();
+    let results: serde_json::Value = serde_json::from_str(out.lines().last().unwrap()).unwrap();
+    let warnings = &results["test/Large.t.sol:LargeTest"]["size_warnings"];
+    assert_eq!(warnings[0]["context"], "Large.large(uint256[30000])", "{results}");
+    assert_eq!(warnings[0]["isStatic"], true, "{results}");
+});
+
+// tests that the zkSync libraries are deployed in the order they link each other in the current
+// compilation, not in the one of the cached missing libraries report
+forgetest_init!(can_deploy_linked_zk_libraries_in_tests, |prj, cmd| {
+    prj.wipe_contracts();
+    add_linked_zk_libraries(&prj, LIB_ADDRESS, MATH_ADDRESS);
+    prj.add_test(
+        "Math.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Math} from "../src/Math.sol";
+
+contract MathTest is Test {
+    function test_two() public {
+        assertEq(Math.two(), 2);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--zksync"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("[PASS] test_two()"), "{output}");
+});
+
+forgetest_init!(can_record_test_metrics, |prj, cmd| {
+    let metrics = prj.root().join("test-metrics.json");
+    let metrics_out = metrics.to_str().unwrap();
+
+    cmd.args(["test", "--metrics-out", metrics_out]);
+    cmd.assert_non_empty_stdout();
+    cmd.assert_non_empty_stdout();
+
+    let metrics = forge::metrics::TestMetrics::read(&metrics);
+    assert_eq!(metrics.runs, 2);
+    assert_eq!(metrics.tests.get("passed"), Some(&4));
+    let rendered = metrics.render();
+    assert!(rendered.contains("forge_test_runs_total 2\n"), "{rendered}");
+    assert!(rendered.contains("test=\"testFuzz_SetNumber(uint256)\""), "{rendered}");
+});
+
+forgetest_init!(can_record_and_replay_rpc, |prj, cmd| {
+    prj.wipe_contracts();
+
+    let endpoint = rpc::next_http_archive_rpc_endpoint();
+    let test = r#"
+import {Test} from "forge-std/Test.sol";
+
+interface IERC20 {
+    function name() external view returns (string memory);
+}
+
+contract CassetteTest is Test {
+    function test_name() public {
+        vm.createSelectFork("<url>", <block>);
+        assertEq(IERC20(0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48).name(), "USD Coin");
+    }
+}
+   "#
+    .replace("<url>", &endpoint);
+    prj.add_test("Cassette.t.sol", &test.replace("<block>", "19000000")).unwrap();
+
+    let cassette = prj.root().join("cassette.json");
+    let cassette_path = cassette.to_str().unwrap();
+    cmd.args(["test", "--record-rpc", cassette_path]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("[PASS] test_name()"), "{out}");
+    assert!(out.contains("Recorded"), "{out}");
+    let recorded = std::fs::read_to_string(&cassette).unwrap();
+    assert!(recorded.contains("eth_getCode"), "{recorded}");
+
+    cmd.forge_fuse().args(["test", "--replay-rpc", cassette_path]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("[PASS] test_name()"), "{out}");
+
+    // requests of another block weren't recorded
+    prj.add_test("Cassette.t.sol", &test.replace("<block>", "19000001")).unwrap();
+    cmd.forge_fuse().args(["test", "--replay-rpc", cassette_path]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(out.contains("[FAIL"), "{out}");
+    assert!(out.contains("Could not instantiate forked environment"), "{out}");
+});
+
+forgetest_init!(can_set_trace_verbosity, |prj, cmd| {
+    prj.add_test(
+        "TraceVerbosity.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Counter} from "../src/Counter.sol";
+
+interface TraceVm {
+    function setTraceVerbosity(uint8 verbosity) external;
+}
+
+contract TraceVerbosityTest is Test {
+    Counter counter;
+
+    function setUp() public {
+        counter = new Counter();
+    }
+
+    function test_traced() public {
+        counter.setNumber(1);
+        TraceVm(address(vm)).setTraceVerbosity(4);
+        counter.increment();
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--match-contract", "TraceVerbosityTest"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Traces:") && out.contains("Counter::increment()"), "{out}");
+    assert!(!out.contains("Counter::setNumber(1)"), "{out}");
+
+    cmd.forge_fuse().args(["test", "--match-contract", "TraceVerbosityTest", "-vvvv"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Counter::setNumber(1)"), "{out}");
+});
+
+// tests that `--profile ci` runs the tests in performance mode, dropping the passing traces
+forgetest_init!(can_run_in_perf_mode_with_profile, |prj, cmd| {
+    prj.add_test(
+        "Perf.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Counter} from "../src/Counter.sol";
+
+contract PerfTest is Test {
+    Counter counter;
+
+    function setUp() public {
+        counter = new Counter();
+    }
+
+    function test_passing() public {
+        counter.setNumber(1);
+    }
+
+    function test_failing() public {
+        counter.setNumber(2);
+        assertEq(counter.number(), 3);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--match-contract", "PerfTest", "--profile", "ci", "-vvvv"]);
+    let (out, _) = cmd.unchecked_output_lossy();
+    assert!(
+        out.contains("Performance mode: dropped the traces of 1 passing test") &&
+            out.contains("calls of the failing tests in"),
+        "{out}"
+    );
+    assert!(
+        out.contains("Counter::setNumber(2)") && !out.contains("Counter::setNumber(1)"),
+        "{out}"
+    );
+});
+
+// tests that the invocations of `vm.ffi` are listed in the `--json` output of their suite
+forgetest_init!(lists_ffi_invocations_in_json, |prj, cmd| {
+    prj.add_test(
+        "Ffi.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract FfiTest is Test {
+    function test_ffi() public {
+        string[] memory inputs = new string[](2);
+        inputs[0] = "echo";
+        inputs[1] = "gm";
+        vm.ffi(inputs);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--match-contract", "FfiTest", "--ffi", "--json"]);
+    let out = cmd.stdout_lossy();
+    let out: serde_json::Value = serde_json::from_str(out.lines().last().unwrap()).unwrap();
+    let invocations = &out["test/Ffi.t.sol:FfiTest"]["ffi_invocations"];
+    assert_eq!(
+        invocations,
+        &serde_json::json!([{ "command": ["echo", "gm"], "allowed": true, "exit_code": 0 }]),
+        "{out}"
+    );
+});
+
+// tests that an invariant campaign interrupted after a checkpoint is resumed from it, and that
+// the checkpoint is discarded once the invariant configuration changed. The campaign is
+// interrupted by the invariant itself, killing forge as soon as the first checkpoint is written.
+fn resume_interrupted_campaign(prj: TestProject, mut cmd: TestCommand, zk_args: &[&str]) {
+    prj.wipe_contracts();
+    prj.add_source(
+        "Counter.sol",
+        r#"
+contract Counter {
+    uint256 public number;
+
+    function increment(uint256 amount) public {
+        number += amount % 100;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.add_test(
+        "CounterInvariant.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Counter} from "../src/Counter.sol";
+
+contract CounterInvariantTest is Test {
+    Counter counter;
+
+    function setUp() public {
+        counter = new Counter();
+    }
+
+    function invariant_bounded() public {
+        assertLt(counter.number(), 100 * 5);
+
+        string[] memory inputs = new string[](3);
+        inputs[0] = "sh";
+        inputs[1] = "-c";
+        inputs[2] = "[ -n \"$INTERRUPT_CAMPAIGN\" ] && [ -f \"$INTERRUPT_CAMPAIGN\" ] && kill -9 $PPID; true";
+        vm.ffi(inputs);
+    }
+}
+   "#,
+    )
+    .unwrap();
+    let invariant =
+        InvariantConfig { runs: 10, depth: 5, checkpoint_interval: 2, ..Default::default() };
+    prj.write_config(Config { invariant: invariant.clone(), ffi: true, ..Default::default() });
+    cmd.arg("build").args(zk_args);
+    cmd.assert_non_empty_stdout();
+
+    // the campaign is interrupted once it's checkpointed after its first 2 runs
+    let checkpoint = prj
+        .root()
+        .join("cache/invariant/test/CounterInvariant.t.sol/CounterInvariantTest")
+        .join("invariant_bounded.json");
+    cmd.forge_fuse().arg("test").args(zk_args);
+    let output = cmd.cmd().env("INTERRUPT_CAMPAIGN", &checkpoint).output().unwrap();
+    assert!(!output.status.success(), "the campaign wasn't interrupted");
+    assert!(checkpoint.exists(), "the campaign wasn't checkpointed");
+
+    // the calls of the checkpoint are marked, to tell a resumed campaign from a new one
+    let mut state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&checkpoint).unwrap()).unwrap();
+    let runs = state["runs"].as_u64().unwrap() as u32;
+    assert_eq!(runs, 2);
+    state["calls"] = 1_000_000.into();
+    let state = state.to_string();
+    std::fs::write(&checkpoint, &state).unwrap();
+
+    prj.write_config(Config {
+        invariant: InvariantConfig { runs: runs + 2, ..invariant.clone() },
+        ffi: true,
+        ..Default::default()
+    });
+    cmd.forge_fuse().args(["test", "--resume-campaign"]).args(zk_args);
+    let output = cmd.stdout_lossy();
+    // the initial assertion of the invariant is reported as a run
+    assert!(output.contains(&format!("(runs: {}, calls: 1000", runs + 3)), "{output}");
+    assert!(!checkpoint.exists());
+
+    // the checkpoint of a campaign of another depth is discarded
+    std::fs::write(&checkpoint, &state).unwrap();
+    prj.write_config(Config {
+        invariant: InvariantConfig { runs: runs + 2, depth: 4, ..invariant },
+        ffi: true,
+        ..Default::default()
+    });
+    cmd.forge_fuse().args(["test", "--resume-campaign"]).args(zk_args);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains(&format!("(runs: {}, calls: ", runs + 3)), "{output}");
+    assert!(!output.contains("calls: 1000"), "{output}");
+    assert!(!checkpoint.exists());
+}
+
+forgetest_init!(can_resume_interrupted_invariant_campaign, |prj, cmd| {
+    resume_interrupted_campaign(prj, cmd, &[]);
+});
+
+forgetest_init!(can_resume_interrupted_zk_invariant_campaign, |prj, cmd| {
+    resume_interrupted_campaign(prj, cmd, &["--zksync"]);
+});
+
+// tests that the contracts are only compiled with zksolc to run the tests in EraVM
+forgetest_init!(compiles_with_zksolc_only_with_zksync, |prj, cmd| {
+    cmd.arg("test");
+    cmd.assert_non_empty_stdout();
+    assert!(!prj.root().join("zkout").exists());
+
+    cmd.forge_fuse().args(["test", "--zksync"]);
+    cmd.assert_non_empty_stdout();
+    assert!(prj.root().join("zkout").exists());
+});
+
+// tests that the results of the tests are streamed as soon as they finish, before the other
+// tests of their suite
+forgetest_init!(streams_test_results_as_they_finish, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "Stream.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract StreamTest is Test {
+    function testFast() public pure {}
+
+    function testSlow() public pure {
+        uint256 sum;
+        for (uint256 i; i < 2_000_000; i++) {
+            sum += i;
+        }
+        assert(sum > 0);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    let events = prj.root().join("events.ndjson");
+    cmd.args(["test", "--event-stream", "ndjson", "--event-stream-file"]).arg(&events);
+    cmd.assert_non_empty_stdout();
+
+    let events = std::fs::read_to_string(events).unwrap();
+    let events = events
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| event["suite"] == "test/Stream.t.sol:StreamTest")
+        .collect::<Vec<_>>();
+    let names = events
+        .iter()
+        .map(|event| event["test"].as_str().unwrap_or(event["event"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["suite_started", "testFast()", "testSlow()", "suite_finished"]);
+    // the fast test was emitted while the slow one was still running
+    assert!(events[1]["timestamp_ms"].as_u64() < events[2]["timestamp_ms"].as_u64(), "{events:?}");
+});
+
+// tests that `vm.multicallStatic` batches the calls on the fork block when the chain has no
+// Multicall3, and that the batch is recorded and replayed with the RPC cassette
+forgetest_async!(can_batch_static_calls_on_the_fork_block, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    let (api, fork) = anvil::spawn(anvil::NodeConfig::test()).await;
+    // a contract returning 1, and one reverting
+    let one = alloy_primitives::address!("0000000000000000000000000000000000001001");
+    let reverting = alloy_primitives::address!("0000000000000000000000000000000000001002");
+    let code =
+        |code: &str| alloy_primitives::Bytes::from(alloy_primitives::hex::decode(code).unwrap());
+    api.anvil_set_code(one, code("600160005260206000f3")).await.unwrap();
+    api.anvil_set_code(reverting, code("60006000fd")).await.unwrap();
+
+    prj.wipe_contracts();
+    prj.add_test(
+        "Multicall.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+interface MulticallVm {
+    struct StaticCall {
+        address target;
+        bytes callData;
+    }
+
+    struct StaticCallResult {
+        bool success;
+        bytes returnData;
+    }
+
+    function multicallStatic(StaticCall[] calldata calls) external returns (StaticCallResult[] memory results);
+}
+
+contract MulticallTest is Test {
+    function test_multicallStatic() public {
+        // the calls are executed on the fork block, not the rolled one
+        vm.roll(block.number + 100);
+
+        MulticallVm.StaticCall[] memory calls = new MulticallVm.StaticCall[](2);
+        calls[0] = MulticallVm.StaticCall(address(0x1001), "");
+        calls[1] = MulticallVm.StaticCall(address(0x1002), "");
+        MulticallVm.StaticCallResult[] memory results = MulticallVm(address(vm)).multicallStatic(calls);
+
+        assertEq(results.length, 2);
+        assertTrue(results[0].success);
+        assertEq(abi.decode(results[0].returnData, (uint256)), 1);
+        assertTrue(!results[1].success);
+        assertEq(results[1].returnData.length, 0);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    let cassette = prj.root().join("cassette.json");
+    let cassette_path = cassette.to_str().unwrap();
+    cmd.args(["test", "--fork-url", &fork.http_endpoint(), "--record-rpc", cassette_path]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("[PASS] test_multicallStatic()"), "{out}");
+    let recorded = std::fs::read_to_string(&cassette).unwrap();
+    assert!(recorded.contains("eth_call"), "{recorded}");
+
+    // the replayed calls return the recorded results
+    api.anvil_set_code(one, code("60006000fd")).await.unwrap();
+    cmd.forge_fuse().args([
+        "test",
+        "--fork-url",
+        &fork.http_endpoint(),
+        "--replay-rpc",
+        cassette_path,
+    ]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("[PASS] test_multicallStatic()"), "{out}");
+});
+
+// tests that the genesis contracts are set before `setUp()`, in EraVM as well with `--zksync`,
+// and that a genesis bytecode without its EraVM counterpart is rejected in zkEVM mode
+forgetest_init!(can_set_genesis_contracts_in_both_vms, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_source(
+        "Token.sol",
+        r#"
+contract Token {
+    function value() public pure returns (uint256) {
+        return 42;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.add_test(
+        "Genesis.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Token} from "../src/Token.sol";
+
+contract GenesisTest is Test {
+    Token constant TOKEN = Token(0x1000000000000000000000000000000000000001);
+
+    function test_genesisToken() public {
+        assertEq(TOKEN.value(), 42);
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    let token = alloy_primitives::address!("1000000000000000000000000000000000000001");
+    prj.write_config(Config {
+        genesis: [(token, GenesisContract::Artifact("src/Token.sol:Token".to_string()))].into(),
+        ..Default::default()
+    });
+    for args in [&[][..], &["--zksync"]] {
+        cmd.forge_fuse().arg("test").args(args);
+        let output = cmd.stdout_lossy();
+        assert!(output.contains("[PASS] test_genesisToken()"), "{args:?}: {output}");
+    }
+
+    // `PUSH1 42 PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`
+    prj.create_file("genesis/Token.hex", "602a60005260206000f3");
+    prj.write_config(Config {
+        genesis: [(
+            token,
+            GenesisContract::Bytecode { bytecode: "genesis/Token.hex".into(), zk_bytecode: None },
+        )]
+        .into(),
+        ..Default::default()
+    });
+    cmd.forge_fuse().arg("test");
+    assert!(cmd.stdout_lossy().contains("[PASS] test_genesisToken()"));
+    cmd.forge_fuse().args(["test", "--zksync"]);
+    cmd.assert_err();
+    assert!(cmd.stderr_lossy().contains(
+        "genesis contract 0x1000000000000000000000000000000000000001 has no `zk_bytecode`"
+    ));
+});
+
+// tests that the state committed in `setUp` is dumped, and loaded back with `--load-state`
+forgetest_init!(can_dump_and_load_era_state, |prj, cmd| {
+    prj.wipe_contracts();
+    prj.add_test(
+        "EraState.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+interface EraStateVm {
+    function dumpEraState(string calldata pathToStateJson) external;
+}
+
+contract EraStateTest is Test {
+    address constant TARGET = address(0x1001);
+
+    function setUp() public {
+        if (vm.envOr("DUMP_ERA_STATE", false)) {
+            vm.store(TARGET, bytes32(uint256(0x20)), bytes32(uint256(0x40)));
+        }
+    }
+
+    function test_dumpEraState() public {
+        EraStateVm(address(vm)).dumpEraState(vm.envString("ERA_STATE"));
+    }
+
+    function test_loadedEraState() public {
+        assertEq(vm.load(TARGET, bytes32(uint256(0x20))), bytes32(uint256(0x40)));
+    }
+}
+   "#,
+    )
+    .unwrap();
+
+    let state = prj.root().join("state.json");
+    cmd.args(["test", "--mt", "test_dumpEraState"]);
+    cmd.env("DUMP_ERA_STATE", "true");
+    cmd.env("ERA_STATE", &state);
+    assert!(cmd.stdout_lossy().contains("[PASS] test_dumpEraState()"));
+
+    let dumped = std::fs::read_to_string(&state).unwrap();
+    assert!(dumped.contains("0000000000000000000000000000000000001001"), "{dumped}");
+
+    cmd.forge_fuse().args(["test", "--mt", "test_loadedEraState"]);
+    cmd.assert_err();
+
+    cmd.forge_fuse().args(["test", "--mt", "test_loadedEraState", "--load-state"]).arg(&state);
+    assert!(cmd.stdout_lossy().contains("[PASS] test_loadedEraState()"));
+});
+
+// tests that `forge test --daemon` reloads the fs permissions of the config between runs
+forgetest_init!(daemon_reloads_fs_permissions, |prj, cmd| {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpStream,
+        process::Stdio,
+    };
+
+    prj.wipe_contracts();
+    prj.create_file("data.txt", "hello");
+    prj.add_test(
+        "ReadFile.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+
+contract ReadFileTest is Test {
+    function test_readFile() public {
+        assertEq(vm.readFile("data.txt"), "hello");
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.write_config(Config { fs_permissions: FsPermissions::new([]), ..Default::default() });
+
+    cmd.args(["test", "--daemon", "127.0.0.1:0"]);
+    let mut daemon = cmd.cmd().stdout(Stdio::piped()).spawn().unwrap();
+    let mut lines = BufReader::new(daemon.stdout.take().unwrap()).lines();
+    let addr = lines
+        .by_ref()
+        .map(Result::unwrap)
+        .find_map(|line| {
+            line.strip_prefix("Running tests on POST http://")?
+                .strip_suffix("/run")
+                .map(str::to_string)
+        })
+        .expect("the daemon didn't start");
+    std::thread::spawn(move || lines.for_each(drop));
+
+    let run = || {
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        write!(
+            stream,
+            "POST /run HTTP/1.1\r\nHost: {addr}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str::<serde_json::Value>(body).unwrap()
+    };
+
+    let first = run();
+    assert_eq!(first["failed"], 1, "{first}");
+    assert_eq!(first["configChanged"], false, "{first}");
+
+    prj.write_config(Config {
+        fs_permissions: FsPermissions::new([PathPermission::read("./")]),
+        ..Default::default()
+    });
+    let second = run();
+    assert_eq!(second["passed"], 1, "{second}");
+    assert_eq!(second["failed"], 0, "{second}");
+    assert_eq!(second["configChanged"], true, "{second}");
+
+    daemon.kill().unwrap();
+});
+
+// tests that `--include-libs` runs the tests of library sources, namespaced with their library
+forgetest_init!(can_run_included_lib_tests, |prj, cmd| {
+    prj.create_file(
+        "lib/protocol/src/Pool.sol",
+        r#"
+contract Pool {
+    function swap(uint256 amount) public pure returns (uint256) {
+        return amount * 2;
+    }
+}
+   "#,
+    );
+    prj.create_file(
+        "lib/protocol/test/Pool.t.sol",
+        r#"
+import {Test} from "forge-std/Test.sol";
+import {Pool} from "../src/Pool.sol";
+
+contract PoolTest is Test {
+    function test_swap() public {
+        assertEq(new Pool().swap(2), 4);
+    }
+}
+   "#,
+    );
+
+    cmd.arg("test");
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("CounterTest"), "{out}");
+    assert!(!out.contains("PoolTest"), "{out}");
+
+    cmd.forge_fuse().args(["test", "--include-libs", "lib/protocol/test/**"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("CounterTest"), "{out}");
+    assert!(out.contains("lib/protocol/test/Pool.t.sol:PoolTest [protocol]"), "{out}");
+    assert!(out.contains("[PASS] test_swap()"), "{out}");
+
+    // the library tests can be filtered like the project ones
+    cmd.forge_fuse().args([
+        "test",
+        "--include-libs",
+        "lib/protocol/test/**",
+        "--match-path",
+        "lib/protocol/test/*",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("[PASS] test_swap()"), "{out}");
+    assert!(!out.contains("CounterTest"), "{out}");
+
+    cmd.forge_fuse().args(["test", "--include-libs", "lib/missing/**"]);
+    cmd.assert_err();
+});
@@ -2,6 +2,14 @@
 
 use ethers_core::types::{Address, Chain};
 use ethers_signers::{LocalWallet, Signer};
+use foundry_config::Config;
+use foundry_test_utils::TestProject;
+
+/// The address `Lib` is configured at by [`add_linked_zk_libraries`] in the tests.
+pub const LIB_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+/// The address `Math` is configured at by [`add_linked_zk_libraries`] in the tests.
+pub const MATH_ADDRESS: &str = "0x2222222222222222222222222222222222222222";
 
 /// Returns the current millis since unix epoch.
 ///
@@ -122,6 +130,53 @@ impl EnvExternalities {
     }
 }
 
+/// Adds the `Lib` library and the `Math` library linking it to the sources of `prj`, configured at
+/// `lib` and `math`.
+///
+/// `Math` is configured first, and a stale missing libraries report has the libraries link each
+/// other in a cycle, so the libraries are only deployed in order if they're ordered by the code
+/// of the current compilation.
+pub fn add_linked_zk_libraries(prj: &TestProject, lib: &str, math: &str) {
+    prj.add_source(
+        "Lib.sol",
+        r#"
+library Lib {
+    function one() public pure returns (uint256) {
+        return 1;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.add_source(
+        "Math.sol",
+        r#"
+import {Lib} from "./Lib.sol";
+
+library Math {
+    function two() public pure returns (uint256) {
+        return Lib.one() + 1;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.write_config(Config {
+        libraries: vec![format!("src/Math.sol:Math:{math}"), format!("src/Lib.sol:Lib:{lib}")],
+        ..Default::default()
+    });
+    let report = prj.root().join("cache").join("zksolc-missing-libraries.json");
+    std::fs::create_dir_all(report.parent().unwrap()).unwrap();
+    std::fs::write(
+        report,
+        r#"[
+    { "contractPath": "src/Lib.sol", "contractName": "Lib", "missingLibraries": ["src/Math.sol:Math"] },
+    { "contractPath": "src/Math.sol", "contractName": "Math", "missingLibraries": ["src/Lib.sol:Lib"] }
+]"#,
+    )
+    .unwrap();
+}
+
 /// Parses the address the contract was deployed to
 pub fn parse_deployed_address(out: &str) -> Option<String> {
     for line in out.lines() {
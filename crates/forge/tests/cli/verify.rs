@@ -2,12 +2,19 @@
 //! and Sourcify.
 
 use crate::utils::{self, EnvExternalities};
+use anvil::{spawn, NodeConfig};
 use foundry_common::retry::Retry;
 use foundry_test_utils::{
     forgetest,
     util::{TestCommand, TestProject},
 };
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 /// Adds a `Unique` contract to the source directory of the project that can be imported as
 /// `import {Unique} from "./unique.sol";`
@@ -174,3 +181,171 @@ forgetest!(can_verify_random_contract_sepolia, |prj, cmd| {
 forgetest!(can_create_verify_random_contract_sepolia, |prj, cmd| {
     create_verify_on_chain(EnvExternalities::sepolia(), prj, cmd);
 });
+
+// tests that `forge verify-batch` verifies every contract deployed by a script, and skips the
+// verified ones when run again
+forgetest_async!(can_verify_batch_of_broadcast, |prj, cmd| {
+    foundry_test_utils::util::initialize(prj.root());
+    prj.add_source(
+        "Token.sol",
+        r#"
+contract Token {
+    uint256 public supply;
+
+    constructor(uint256 _supply) {
+        supply = _supply;
+    }
+}
+   "#,
+    )
+    .unwrap();
+    prj.add_source(
+        "Registry.sol",
+        r#"
+contract Registry {
+    mapping(address => bool) public registered;
+}
+   "#,
+    )
+    .unwrap();
+    let script = prj
+        .add_source(
+            "Deploy.sol",
+            r#"
+import "forge-std/Script.sol";
+import {Token} from "./Token.sol";
+import {Registry} from "./Registry.sol";
+
+contract DeployScript is Script {
+    function run() external {
+        vm.startBroadcast(0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80);
+        new Token(1000);
+        new Registry();
+        vm.stopBroadcast();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    cmd.args([
+        "script",
+        &format!("{}:DeployScript", script.display()),
+        "--fork-url",
+        &handle.http_endpoint(),
+        "--broadcast",
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"), "{output}");
+
+    let (explorer, submitted) = mock_explorer();
+    let broadcast = prj.root().join("broadcast/Deploy.sol/31337/run-latest.json");
+    let verify_batch = |cmd: &mut TestCommand| {
+        cmd.forge_fuse().arg("verify-batch").arg(&broadcast).args([
+            "--verifier",
+            "blockscout",
+            "--verifier-url",
+            &format!("http://{explorer}/api"),
+            "--rate-limit",
+            "10",
+        ]);
+        cmd.stdout_lossy()
+    };
+
+    let output = verify_batch(&mut cmd);
+    assert!(output.contains("2 verified, 0 pending, 0 failed"), "{output}");
+    assert!(output.contains("Token") && output.contains("Registry"), "{output}");
+    let mut addresses = submitted.lock().unwrap().clone();
+    addresses.sort();
+    assert_eq!(
+        addresses,
+        [
+            "0x5fbdb2315678afecb367f032d93f642f64180aa3",
+            "0xe7f1725e7734ce288f8367e1bb143e90bb3f0512"
+        ]
+    );
+
+    // the contracts are verified now, so retrying doesn't submit them again
+    let output = verify_batch(&mut cmd);
+    assert!(output.contains("2 verified, 0 pending, 0 failed"), "{output}");
+    assert!(output.contains("already verified"), "{output}");
+    assert_eq!(submitted.lock().unwrap().len(), 2);
+});
+
+/// Spawns a block explorer answering the verification requests of the Etherscan API, returning
+/// its address and the addresses of the contracts submitted for verification.
+///
+/// The submitted contracts are verified right away.
+fn mock_explorer() -> (SocketAddr, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let verified = Arc::clone(&submitted);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            // the parameters are sent in the query of GET requests and the form of POST ones
+            let query = request_line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|target| target.split_once('?'))
+                .map(|(_, query)| query.to_string())
+                .unwrap_or_default();
+            let form = String::from_utf8_lossy(&body).into_owned();
+            let params = format!("{query}&{form}")
+                .split('&')
+                .filter_map(|param| param.split_once('='))
+                .map(|(key, value)| (key.to_lowercase(), value.to_lowercase()))
+                .collect::<HashMap<_, _>>();
+
+            let mut verified = verified.lock().unwrap();
+            let response = match params["action"].as_str() {
+                "getabi" if verified.contains(&params["address"]) => {
+                    serde_json::json!({ "status": "1", "message": "OK", "result": "[]" })
+                }
+                "getabi" => serde_json::json!({
+                    "status": "0",
+                    "message": "NOTOK",
+                    "result": "Contract source code not verified",
+                }),
+                "verifysourcecode" => {
+                    verified.push(params["contractaddress"].clone());
+                    serde_json::json!({ "status": "1", "message": "OK", "result": "guid" })
+                }
+                "checkverifystatus" => {
+                    serde_json::json!({ "status": "1", "message": "OK", "result": "Pass - Verified" })
+                }
+                action => panic!("unexpected explorer request {action}"),
+            }
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+        }
+    });
+    (addr, submitted)
+}
@@ -1,30 +1,25 @@
 //! Test config.
 
 use crate::test_helpers::{COMPILED, COMPILED_ZK, EVM_OPTS, PROJECT};
-use alloy_primitives::keccak256;
 use forge::{
     result::{SuiteResult, TestStatus},
     MultiContractRunner, MultiContractRunnerBuilder, TestOptions, TestOptionsBuilder,
 };
-use foundry_compilers::Artifact;
 use foundry_config::{
     fs_permissions::PathPermission, Config, FsPermissions, FuzzConfig, FuzzDictionaryConfig,
     InvariantConfig, RpcEndpoint, RpcEndpoints,
 };
 use foundry_evm::{
+    backend::ForkType,
     decode::decode_console_logs,
     inspectors::CheatsConfig,
     revm::primitives::SpecId,
     traces::{render_trace_arena, CallTraceDecoderBuilder},
 };
 use foundry_test_utils::{init_tracing, Filter};
-use foundry_zksync_compiler::{DualCompiledContract, PackedEraBytecode};
 use futures::future::join_all;
 use itertools::Itertools;
-use std::{
-    collections::{BTreeMap, HashMap},
-    path::Path,
-};
+use std::{collections::BTreeMap, path::Path};
 
 /// How to execute a test run.
 pub struct TestConfig {
@@ -123,9 +118,11 @@ pub fn test_opts() -> TestOptions {
             runs: 256,
             max_test_rejects: 65536,
             seed: None,
+            parallel: 1,
             dictionary: FuzzDictionaryConfig {
                 include_storage: true,
                 include_push_bytes: true,
+                include_zk_return_data: true,
                 dictionary_weight: 40,
                 max_fuzz_dictionary_addresses: 10_000,
                 max_fuzz_dictionary_values: 10_000,
@@ -140,11 +137,14 @@ pub fn test_opts() -> TestOptions {
                 dictionary_weight: 80,
                 include_storage: true,
                 include_push_bytes: true,
+                include_zk_return_data: true,
                 max_fuzz_dictionary_addresses: 10_000,
                 max_fuzz_dictionary_values: 10_000,
             },
             shrink_sequence: true,
             shrink_run_limit: 2usize.pow(18u32),
+            selector_weights: Default::default(),
+            checkpoint_interval: 0,
         })
         .build(&COMPILED, &PROJECT.paths.root)
         .expect("Config loaded")
@@ -184,13 +184,7 @@ pub async fn runner_with_config(mut config: Config) -> MultiContractRunner {
     let output = COMPILED.clone();
     base_runner()
         .with_test_options(test_opts())
-        .with_cheats_config(CheatsConfig::new(
-            &config,
-            opts.clone(),
-            None,
-            Default::default(),
-            false,
-        ))
+        .with_cheats_config(CheatsConfig::new(&config, opts.clone(), None, false))
         .sender(config.sender)
         .build(root, output, env, opts.clone())
         .unwrap()
@@ -207,57 +201,11 @@ pub async fn runner_with_config_and_zk(mut config: Config) -> MultiContractRunne
     let output = COMPILED.clone();
     let zk_output = COMPILED_ZK.clone();
 
-    // Dual compiled contracts
-    let mut dual_compiled_contracts = vec![];
-    let mut solc_bytecodes = HashMap::new();
-    for (contract_name, artifact) in output.artifacts() {
-        let contract_name =
-            contract_name.split('.').next().expect("name cannot be empty").to_string();
-        let deployed_bytecode = artifact.get_deployed_bytecode();
-        let deployed_bytecode = deployed_bytecode
-            .as_ref()
-            .and_then(|d| d.bytecode.as_ref().and_then(|b| b.object.as_bytes()));
-        let bytecode = artifact.get_bytecode().and_then(|b| b.object.as_bytes().cloned());
-        if let Some(bytecode) = bytecode {
-            if let Some(deployed_bytecode) = deployed_bytecode {
-                solc_bytecodes.insert(contract_name.clone(), (bytecode, deployed_bytecode.clone()));
-            }
-        }
-    }
-
-    // TODO make zk optional and solc default
-    for (contract_name, artifact) in zk_output.artifacts() {
-        let deployed_bytecode = artifact.get_deployed_bytecode();
-        let deployed_bytecode = deployed_bytecode
-            .as_ref()
-            .and_then(|d| d.bytecode.as_ref().and_then(|b| b.object.as_bytes()));
-        if let Some(deployed_bytecode) = deployed_bytecode {
-            let packed_bytecode = PackedEraBytecode::from_vec(deployed_bytecode);
-            if let Some((solc_bytecode, solc_deployed_bytecode)) =
-                solc_bytecodes.get(&contract_name)
-            {
-                dual_compiled_contracts.push(DualCompiledContract {
-                    name: contract_name,
-                    zk_bytecode_hash: packed_bytecode.bytecode_hash(),
-                    zk_deployed_bytecode: packed_bytecode.bytecode(),
-                    evm_bytecode_hash: keccak256(solc_deployed_bytecode),
-                    evm_bytecode: solc_bytecode.to_vec(),
-                    evm_deployed_bytecode: solc_deployed_bytecode.to_vec(),
-                });
-            }
-        }
-    }
-
     base_runner()
         .with_test_options(test_opts())
-        .with_cheats_config(CheatsConfig::new(
-            &config,
-            opts.clone(),
-            None,
-            dual_compiled_contracts,
-            false,
-        ))
+        .with_cheats_config(CheatsConfig::new(&config, opts.clone(), None, false))
         .sender(config.sender)
+        .with_zk_output(Some(zk_output))
         .build(root, output, env, opts.clone())
         .unwrap()
 }
@@ -276,7 +224,7 @@ pub async fn tracing_runner() -> MultiContractRunner {
         .unwrap()
 }
 
-// Builds a runner that runs against forked state
+// Builds a runner that runs against forked state, in EraVM if forking a zkSync Era chain
 pub async fn forked_runner(rpc: &str) -> MultiContractRunner {
     let mut opts = EVM_OPTS.clone();
 
@@ -286,10 +234,16 @@ pub async fn forked_runner(rpc: &str) -> MultiContractRunner {
     let env = opts.evm_env().await.expect("Could not instantiate fork environment");
     let fork = opts.get_fork(&Default::default(), env.clone());
 
-    base_runner()
-        .with_fork(fork)
-        .build(&PROJECT.paths.root, (*COMPILED).clone(), env, opts)
-        .unwrap()
+    let mut builder = base_runner().with_fork(fork);
+    let use_zk = ForkType::detect(rpc).await.is_zk();
+    if use_zk {
+        builder = builder
+            .with_cheats_config(CheatsConfig::new(&Default::default(), opts.clone(), None, true))
+            .with_zk_output(Some(COMPILED_ZK.clone()));
+    }
+    let mut runner = builder.build(&PROJECT.paths.root, (*COMPILED).clone(), env, opts).unwrap();
+    runner.use_zk = use_zk;
+    runner
 }
 
 /// the RPC endpoints used during tests
@@ -1,8 +1,23 @@
 //! Forge tests for core functionality.
 
-use crate::config::*;
-use forge::result::SuiteResult;
-use foundry_evm::traces::TraceKind;
+use crate::{
+    config::*,
+    test_helpers::{COMPILED, EVM_OPTS, PROJECT},
+};
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{keccak256, Bytes, U256};
+use forge::{
+    result::{SuiteResult, TestStatus},
+    TestEnvOverrides,
+};
+use foundry_config::Config;
+use foundry_evm::{
+    inspectors::CheatsConfig,
+    traces::{
+        identifier::LocalTraceIdentifier, render_trace_arena_with_sources, CallTraceDecoderBuilder,
+        TraceKind,
+    },
+};
 use foundry_test_utils::Filter;
 use std::{collections::BTreeMap, env};
 
@@ -727,3 +742,189 @@ async fn test_trace() {
         }
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_sources() {
+    let mut opts = EVM_OPTS.clone();
+    opts.verbosity = 5;
+    let env = opts.evm_env().await.expect("Could not instantiate fork environment");
+    let mut runner = base_runner()
+        .with_trace_sources(true)
+        .build(&PROJECT.paths.root, (*COMPILED).clone(), env, opts)
+        .unwrap();
+    let results =
+        runner.test_collect(&Filter::new(".*", "FailingSetupTest", ".*core"), test_opts()).await;
+    let result = &results["core/FailingSetup.t.sol:FailingSetupTest"].test_results["setUp()"];
+
+    let mut identifier = LocalTraceIdentifier::new(&runner.known_contracts);
+    let mut decoder = CallTraceDecoderBuilder::new().build();
+    let mut rendered = String::new();
+    for (_, arena) in &result.traces {
+        decoder.identify(arena, &mut identifier);
+        let sources = runner.trace_sources.as_deref();
+        let trace = render_trace_arena_with_sources(arena, &decoder, sources).await.unwrap();
+        rendered.push_str(&trace);
+    }
+    assert!(
+        rendered.contains(
+            "reverted at core/FailingSetup.t.sol:11: require(false, \"setup failed predictably\");"
+        ),
+        "{rendered}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_return_data_limit() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.trace_return_data_limit = Some(32);
+    let mut opts = EVM_OPTS.clone();
+    opts.verbosity = 3;
+    let env = opts.evm_env().await.expect("Could not instantiate fork environment");
+    let mut runner = base_runner()
+        .with_cheats_config(CheatsConfig::new(&config, opts.clone(), None, false))
+        .build(&PROJECT.paths.root, (*COMPILED).clone(), env, opts)
+        .unwrap();
+    let results = runner
+        .test_collect(&Filter::new(".*", "ReturnDataLimitTest", ".*trace"), test_opts())
+        .await;
+    let suite = &results["trace/ReturnDataLimit.t.sol:ReturnDataLimitTest"];
+
+    // The return data of the call to the returner, recorded by the execution trace
+    let returned = |test: &str, status: TestStatus| {
+        let result = &suite.test_results[test];
+        assert_eq!(result.status, status, "{test}: {:?}", result.reason);
+        result
+            .traces
+            .iter()
+            .filter(|(kind, _)| *kind == TraceKind::Execution)
+            .flat_map(|(_, arena)| arena.nodes())
+            .find(|node| node.trace.depth == 1)
+            .map(|node| node.trace.output.clone())
+            .unwrap()
+    };
+    assert_eq!(returned("testLargeReturn()", TestStatus::Success).len(), 32);
+    assert!(returned("testLargeRevert()", TestStatus::Success).len() > 1024);
+
+    // the reverts the failing tests are decoded from are recorded in full
+    assert!(returned("testLargeRevertFailing()", TestStatus::Failure).len() > 1024);
+    assert!(returned("testFuzzLargeRevertFailing(uint256)", TestStatus::Failure).len() > 1024);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_verbosity_filters_recorded_calls() {
+    let mut opts = EVM_OPTS.clone();
+    opts.verbosity = 0;
+    let env = opts.evm_env().await.expect("Could not instantiate fork environment");
+    let mut runner =
+        base_runner().build(&PROJECT.paths.root, (*COMPILED).clone(), env, opts).unwrap();
+    let results =
+        runner.test_collect(&Filter::new(".*", "TraceVerbosityTest", ".*trace"), test_opts()).await;
+    let result = &results["trace/TraceVerbosity.t.sol:TraceVerbosityTest"].test_results
+        ["testRaiseTraceVerbosity()"];
+    assert_eq!(result.status, TestStatus::Success, "{:?}", result.reason);
+
+    // Only the test call, the calls to `vm.setTraceVerbosity` and the call made at verbosity 4
+    // are recorded
+    let (_, arena) = result.traces.iter().find(|(kind, _)| *kind == TraceKind::Execution).unwrap();
+    let selectors =
+        arena.nodes().iter().skip(1).map(|node| node.trace.data[..4].to_vec()).collect::<Vec<_>>();
+    let set_trace_verbosity = keccak256("setTraceVerbosity(uint8)")[..4].to_vec();
+    assert_eq!(
+        selectors,
+        vec![set_trace_verbosity.clone(), keccak256("traced()")[..4].to_vec(), set_trace_verbosity]
+    );
+    assert!(arena.nodes().iter().skip(1).all(|node| node.trace.depth == 1));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fixture_dependencies() {
+    let mut runner = runner().await;
+    assert_eq!(
+        runner.dependencies.chain("dependencies/Fixtures.t.sol:FixtureDependentTest"),
+        vec![
+            "dependencies/Fixtures.t.sol:ProtocolFixture".to_string(),
+            "dependencies/Fixtures.t.sol:LiquidityFixture".to_string()
+        ]
+    );
+    let results =
+        runner.test_collect(&Filter::new(".*", ".*", ".*dependencies"), test_opts()).await;
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([
+            (
+                "dependencies/Fixtures.t.sol:FixtureDependentTest",
+                vec![
+                    ("testRunsOnFixtureState()", true, None, None, None),
+                    ("testChangesAreNotShared()", true, None, None, None),
+                ],
+            ),
+            (
+                "dependencies/Fixtures.t.sol:FixtureSiblingTest",
+                vec![
+                    ("testRunsOnFirstFixture()", true, None, None, None),
+                    ("testUnknownFixture()", true, None, None, None),
+                ],
+            ),
+        ]),
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_single_test() {
+    let runner = runner().await;
+
+    let result = runner
+        .run_test(
+            "core/SetupConsistency.t.sol:SetupConsistencyCheck",
+            "testAdd",
+            Bytes::new(),
+            TestEnvOverrides::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, TestStatus::Success, "{:?}", result.reason);
+    assert!(result.traces.iter().any(|(kind, _)| *kind == TraceKind::Execution));
+
+    // the arguments are decoded with or without the selector of the test
+    let args = |x: u64| -> Bytes { DynSolValue::Uint(U256::from(x), 8).abi_encode().into() };
+    let result = runner
+        .run_test("fuzz/Fuzz.t.sol:FuzzTest", "testFailFuzz", args(1), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(result.status, TestStatus::Success, "{:?}", result.reason);
+    let calldata = [&keccak256("testFailFuzz(uint8)")[..4], &args(200)].concat();
+    let result = runner
+        .run_test(
+            "fuzz/Fuzz.t.sol:FuzzTest",
+            "testFailFuzz(uint8)",
+            calldata.into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, TestStatus::Failure);
+
+    let overrides = TestEnvOverrides { block_timestamp: Some(U256::from(2)), ..Default::default() };
+    let result = runner
+        .run_test(
+            "core/ContractEnvironment.t.sol:ContractEnvironmentTest",
+            "testEnvironment",
+            Bytes::new(),
+            overrides,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, TestStatus::Failure);
+
+    let err = runner
+        .run_test("core/Missing.t.sol:Missing", "testAdd", Bytes::new(), Default::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("doesn't exist"), "{err}");
+    let err = runner
+        .run_test("fuzz/Fuzz.t.sol:FuzzTest", "setUp", Bytes::new(), Default::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("is not a test"), "{err}");
+}
@@ -2,7 +2,8 @@
 
 use crate::config::*;
 use alloy_primitives::U256;
-use forge::result::{SuiteResult, TestStatus};
+use forge::result::{SuiteResult, TestKind, TestStatus};
+use foundry_evm::fuzz::CounterExample;
 use foundry_test_utils::Filter;
 use std::collections::BTreeMap;
 
@@ -13,7 +14,7 @@ async fn test_fuzz() {
     let suite_result = runner
         .test_collect(
             &Filter::new(".*", ".*", ".*fuzz/")
-                .exclude_tests(r"invariantCounter|testIncrement\(address\)|testNeedle\(uint256\)|testSuccessChecker\(uint256\)|testSuccessChecker2\(int256\)|testSuccessChecker3\(uint32\)")
+                .exclude_tests(r"invariantCounter|testIncrement\(address\)|testNeedle\(uint256\)|testSuccessChecker\(uint256\)|testSuccessChecker2\(int256\)|testSuccessChecker3\(uint32\)|testAssumeRejections\(uint256\)")
                 .exclude_paths("invariant"),
             test_opts(),
         )
@@ -114,3 +115,74 @@ async fn test_fuzz_collection() {
         )]),
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_parallel_fuzz_is_deterministic() {
+    let mut runner = runner().await;
+    let filter = Filter::new(".*", ".*", ".*fuzz/")
+        .exclude_tests(
+            r"invariantCounter|testIncrement\(address\)|testNeedle\(uint256\)|testAssumeRejections\(uint256\)",
+        )
+        .exclude_paths("invariant");
+
+    let mut opts = test_opts();
+    opts.fuzz.seed = Some(U256::from(100u32));
+    opts.fuzz.parallel = 4;
+    runner.test_options = opts.clone();
+
+    let mut outcomes = Vec::new();
+    for _ in 0..2 {
+        let results = runner.test_collect(&filter, opts.clone()).await;
+        assert!(!results.is_empty());
+
+        let outcome = results
+            .into_iter()
+            .flat_map(|(suite, SuiteResult { test_results, .. })| {
+                test_results.into_iter().map(move |(test, result)| {
+                    let counterexample = match result.counterexample {
+                        Some(CounterExample::Single(counterexample)) => {
+                            Some(counterexample.calldata)
+                        }
+                        _ => None,
+                    };
+                    let runs = match result.kind {
+                        TestKind::Fuzz { runs, .. } => Some(runs),
+                        _ => None,
+                    };
+                    let outcome = (result.status, result.reason, counterexample, runs);
+                    (format!("{suite}:{test}"), outcome)
+                })
+            })
+            .collect::<BTreeMap<_, _>>();
+        outcomes.push(outcome);
+    }
+
+    assert_eq!(outcomes[0], outcomes[1]);
+    assert_eq!(
+        outcomes[0].get("fuzz/FuzzPositive.t.sol:FuzzPositive:testSuccessChecker(uint256)"),
+        Some(&(TestStatus::Success, None, None, Some(256)))
+    );
+}
+
+/// Tests that the inputs rejected by `vm.assume` are broken down by call site when a fuzz test
+/// rejects too many inputs.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fuzz_assume_rejections() {
+    let filter = Filter::new("testAssumeRejections", ".*", ".*fuzz/FuzzAssume");
+    let mut test = TestConfig::filter(filter).await;
+    test.opts.fuzz.max_test_rejects = 100;
+    let results = test.test().await;
+
+    let result = &results["fuzz/FuzzAssume.t.sol:FuzzAssumeTest"].test_results
+        ["testAssumeRejections(uint256)"];
+    assert_eq!(result.status, TestStatus::Failure);
+    let reason = result.reason.as_deref().unwrap();
+    assert!(reason.starts_with("The `vm.assume` cheatcode rejected too many inputs"), "{reason}");
+    assert!(reason.contains("rejected inputs by `vm.assume` call site:"), "{reason}");
+    assert!(
+        reason.contains(
+            "FuzzAssume.t.sol:11:9 `vm.assume(x > 10 && x < 12)`, consider `x = bound(x, 11, 11);`"
+        ),
+        "{reason}"
+    );
+}
@@ -4,6 +4,7 @@ use crate::{
     config::runner,
     test_helpers::{COMPILED, PROJECT},
 };
+use alloy_primitives::U256;
 use forge::{
     result::{SuiteResult, TestKind, TestResult},
     TestOptions, TestOptionsBuilder,
@@ -72,6 +73,24 @@ async fn inline_config_run_invariant() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_config_run_budget() {
+    let opts = default_test_options();
+
+    let filter = Filter::new(".*", ".*", ".*inline/BudgetInlineConf.t.sol");
+
+    let mut runner = runner().await;
+    runner.test_options = opts.clone();
+
+    let result = runner.test_collect(&filter, opts).await;
+    let suite_result: &SuiteResult =
+        result.get("inline/BudgetInlineConf.t.sol:BudgetInlineConf").unwrap();
+    assert!(suite_result.failures().next().is_none());
+    assert_eq!(suite_result.warnings.len(), 1, "{:?}", suite_result.warnings);
+    assert!(suite_result.warnings[0]
+        .starts_with("testInlineConfBudgetWarn(uint8) exceeded its max duration of 0ns"));
+}
+
 #[test]
 fn build_test_options() {
     let root = &PROJECT.paths.root;
@@ -100,6 +119,35 @@ fn build_test_options_just_one_valid_profile() {
     assert!(build_result.is_err());
 }
 
+#[test]
+fn update_test_options() {
+    const CONTRACT: &str = "inline/FuzzInlineConf.t.sol:FuzzInlineConf";
+    let root = &PROJECT.paths.root;
+    let mut opts = default_test_options();
+    opts.set_fuzz_runs(7).unwrap();
+
+    // the in-line runs are kept, the base runs apply to the other tests
+    assert_eq!(opts.fuzz_config(CONTRACT, "testInlineConfFuzz").runs, 1024);
+    assert_eq!(opts.fuzz_config(CONTRACT, "testOther").runs, 7);
+
+    // the new seed is merged into the in-line configurations
+    opts.reseed(Some(U256::from(42))).unwrap();
+    let fuzz = opts.fuzz_config(CONTRACT, "testInlineConfFuzz");
+    assert_eq!((fuzz.runs, fuzz.max_test_rejects, fuzz.seed), (1024, 500, Some(U256::from(42))));
+
+    let (id, artifact) =
+        COMPILED.artifact_ids().find(|(id, _)| id.name == "FuzzInlineConf").unwrap();
+    let natspecs = opts.natspecs.len();
+    opts.reresolve_inline(&id, artifact, root).unwrap();
+    assert_eq!(opts.natspecs.len(), natspecs);
+    assert_eq!(opts.fuzz_config(CONTRACT, "testInlineConfFuzz").runs, 1024);
+
+    // the profiles are validated again
+    opts.profiles = vec!["profile-sheldon-cooper".to_string()];
+    assert!(opts.reresolve_inline(&id, artifact, root).is_err());
+    assert_eq!(opts.fuzz_config(CONTRACT, "testInlineConfFuzz").runs, 1024);
+}
+
 /// Returns the [TestOptions] for the testing [PROJECT].
 pub fn default_test_options() -> TestOptions {
     let root = &PROJECT.paths.root;
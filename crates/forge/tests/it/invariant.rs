@@ -1,8 +1,11 @@
 //! Invariant tests.
 
 use crate::config::*;
-use alloy_primitives::U256;
-use forge::fuzz::CounterExample;
+use alloy_primitives::{B256, U256};
+use forge::{
+    fuzz::{invariant::InvariantCheckpoint, CounterExample},
+    result::TestKind,
+};
 use foundry_test_utils::Filter;
 use std::collections::BTreeMap;
 
@@ -87,6 +90,10 @@ async fn test_invariant() {
                 "fuzz/invariant/target/TargetSelectors.t.sol:TargetSelectors",
                 vec![("invariantTrueWorld()", true, None, None, None)],
             ),
+            (
+                "fuzz/invariant/target/TargetSelectorWeights.t.sol:TargetSelectorWeights",
+                vec![("invariantTrueWorld()", true, None, None, None)],
+            ),
             (
                 "fuzz/invariant/targetAbi/ExcludeArtifacts.t.sol:ExcludeArtifacts",
                 vec![("invariantShouldPass()", true, None, None, None)],
@@ -259,3 +266,46 @@ async fn test_invariant_shrink() {
         }
     };
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invariant_discards_stale_checkpoint() {
+    let mut runner = runner().await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut opts = test_opts();
+    opts.invariant.runs = 20;
+    opts.invariant.checkpoint_interval = 5;
+    opts.checkpoint_dir = Some(dir.path().to_path_buf());
+    opts.resume_campaign = true;
+    runner.test_options = opts.clone();
+
+    // the checkpoint was written by a campaign of other contracts
+    let contract = "fuzz/invariant/target/ExcludeContracts.t.sol:ExcludeContracts";
+    let checkpoint = opts.invariant_checkpoint(contract, "invariantTrueWorld").unwrap();
+    InvariantCheckpoint {
+        code_hash: B256::repeat_byte(1),
+        config: opts.invariant.clone(),
+        runs: 15,
+        calls: 1_000_000,
+        ..Default::default()
+    }
+    .write(&checkpoint.path)
+    .unwrap();
+
+    let results = runner
+        .test_collect(
+            &Filter::new(".*", ".*", ".*fuzz/invariant/target/ExcludeContracts.t.sol"),
+            opts,
+        )
+        .await;
+
+    let result = &results[contract].test_results["invariantTrueWorld()"];
+    assert!(result.status.is_success());
+    // the initial assertion of the invariant is reported as a run
+    let TestKind::Invariant { runs, calls, .. } = &result.kind else { panic!("not an invariant") };
+    assert_eq!(*runs, 21);
+    assert!(*calls < 1_000_000);
+
+    // the campaign is over, so its checkpoint is removed
+    assert!(!checkpoint.path.exists());
+}
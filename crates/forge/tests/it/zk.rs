@@ -6,9 +6,16 @@ use crate::{
     config::*,
     test_helpers::{PROJECT, RE_PATH_SEPARATOR},
 };
-use forge::revm::primitives::SpecId;
+use alloy_primitives::address;
+use forge::{
+    fuzz::CounterExample,
+    result::{TestFailureKind, TestKind, TestStatus},
+    revm::primitives::SpecId,
+};
 use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
+use foundry_evm::traces::TraceKind;
 use foundry_test_utils::Filter;
+use foundry_zksync_core::vm::{SizeLimitKind, ZkResourceLimitExceeded};
 
 /// Executes all zk basic tests
 #[tokio::test(flavor = "multi_thread")]
@@ -21,6 +28,33 @@ async fn test_zk_basic() {
     TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
 }
 
+/// Matches the zksolc contracts with the solc ones only when the zk output is given
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_output_optional() {
+    let solc_runner = runner_with_config(Config::with_root(PROJECT.root())).await;
+    assert!(solc_runner.cheats_config.dual_compiled_contracts.is_empty());
+
+    let zk_runner = runner_with_config_and_zk(Config::with_root(PROJECT.root())).await;
+    let dual_compiled_contracts = &zk_runner.cheats_config.dual_compiled_contracts;
+    assert!(dual_compiled_contracts.iter().any(|contract| contract.name == "Greeter"));
+
+    // contracts with the same bytecodes are registered once
+    let hashes = dual_compiled_contracts
+        .iter()
+        .map(|contract| (contract.evm_bytecode_hash, contract.zk_bytecode_hash))
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(hashes.len(), dual_compiled_contracts.len());
+}
+
+/// Executes the zk tests mixing zkSync Era and L1 forks
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_multi_fork() {
+    let runner = runner_with_config_and_zk(Config::with_root(PROJECT.root()));
+    let filter = Filter::new(".*", "ZkMultiForkTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
 /// Executes all zk contract tests
 #[tokio::test(flavor = "multi_thread")]
 async fn test_zk_contracts() {
@@ -32,6 +66,53 @@ async fn test_zk_contracts() {
     TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
 }
 
+/// Reports the ergs, pubdata and refunds of the zk calls of a test alongside its EVM gas
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_ergs_reported() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(
+        "testZkContractsInlineDeployedContractNoArgs",
+        "ZkContractsTest",
+        &format!(".*zk{RE_PATH_SEPARATOR}*"),
+    );
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+    let suite = results.values().next().unwrap();
+    let result = suite.test_results.values().next().unwrap();
+    assert!(result.zk_ergs.is_some_and(|ergs| ergs > 0), "{:?}", result.zk_ergs);
+    assert!(result.zk_pubdata.is_some_and(|pubdata| pubdata > 0), "{:?}", result.zk_pubdata);
+    assert!(result.zk_gas_refunded.is_some());
+    assert_eq!(suite.zk_ergs(), result.zk_ergs);
+    assert!(result.short_result("test()").contains(", pubdata: "));
+    assert!(result.short_result("test()").contains("(ergs: "));
+}
+
+/// Records the calls switching from the EVM to EraVM, which `forge callgraph` marks as cross VM
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_calls_recorded() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(
+        "testZkContractsInlineDeployedContractNoArgs",
+        "ZkContractsTest",
+        &format!(".*zk{RE_PATH_SEPARATOR}*"),
+    );
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+    let result = results.values().next().unwrap().test_results.values().next().unwrap();
+    let (_, arena) = result.traces.last().unwrap();
+    let nodes = arena.nodes();
+    // the test contract runs in the EVM, the contract it deploys in EraVM
+    let create = nodes.iter().find(|node| node.trace.kind.is_any_create()).unwrap();
+    let caller = nodes[create.parent.unwrap()].trace.address;
+    assert!(result.zk_calls.contains(&(caller, create.trace.address)), "{:?}", result.zk_calls);
+}
+
 /// Executes all zk cheatcode tests
 #[tokio::test(flavor = "multi_thread")]
 async fn test_zk_cheats() {
@@ -43,6 +124,134 @@ async fn test_zk_cheats() {
     TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
 }
 
+/// Publishes a bytecode marked unknown again when it's deployed, unlike one marked known
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_bytecode_known_published() {
+    let runner = runner_with_config_and_zk(Config::with_root(PROJECT.root()));
+    let filter = Filter::new(".*", "ZkBytecodeKnownTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+    let suite = results.values().next().unwrap();
+    let pubdata = |test: &str| {
+        let result = &suite.test_results[test];
+        assert!(result.status.is_success(), "{test}: {:?}", result.reason);
+        result.zk_pubdata.unwrap()
+    };
+    let kept = pubdata("testZkBytecodeKnownKept()");
+    let republished = pubdata("testZkBytecodeKnownRepublished()");
+    let marked = pubdata("testZkBytecodeKnownMarked()");
+    assert!(republished > kept, "{republished} <= {kept}");
+    assert!(marked < republished, "{marked} >= {republished}");
+}
+
+/// Executes all zk vm switch tests
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_vm_switch() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkVmSwitchTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Executes all zk messaging tests
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_messaging() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkMessagingTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Executes the zk messaging tests relying on the `zk_messaging` config
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_configured_messaging() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    config.zk_messaging = Some(address!("32400084C286CF3E17e7B677ea9583e60a000324"));
+    let runner = runner_with_config_and_zk(config);
+    let filter =
+        Filter::new(".*", "ZkConfiguredMessagingTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Executes all zk system call tests
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_system_calls() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkSystemCallTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Executes all zk paymaster tests
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_paymaster() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkPaymasterTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Executes all zk account abstraction tests
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_accounts() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkAccountTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Reports the sizes of the zk calls and deployments approaching the EraVM limits
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_size_limits() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkSizeLimitsTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+    let suite = results.values().next().unwrap();
+    assert_eq!(suite.failed(), 0);
+    assert_eq!(suite.size_warnings.len(), 2, "{:?}", suite.size_warnings);
+    for warning in &suite.size_warnings {
+        assert_eq!(warning.kind, SizeLimitKind::Calldata);
+        assert!(warning.size > 900_000, "{warning}");
+    }
+    assert!(suite.size_warnings.iter().any(|w| w.context.starts_with("call to ")));
+    assert!(suite.size_warnings.iter().any(|w| w.context == "deployment of Holder"));
+}
+
+/// Records the EIP-1967 proxies called in EraVM with their implementation
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_proxies() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkProxyTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+    let suite = results.values().next().unwrap();
+    let result = &suite.test_results["testCallThroughProxy()"];
+    assert_eq!(result.status, TestStatus::Success, "{:?}", result.reason);
+    assert_eq!(result.proxies.len(), 1, "{:?}", result.proxies);
+    let (proxy, implementation) = result.proxies.iter().next().unwrap();
+    assert_ne!(proxy, implementation);
+}
+
 /// Executes all zk console tests
 #[tokio::test(flavor = "multi_thread")]
 async fn test_zk_logs() {
@@ -75,3 +284,205 @@ async fn test_zk_logs() {
         )]),
     );
 }
+
+/// Executes the zk resource limit tests, which pass without limits
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_resource_limits() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkResourceLimitsTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Executes the zk resource limit tests with a cycle limit, which aborts the executions
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_resource_limits_exceeded() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+    config.zk_max_cycles = Some(1);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkResourceLimitsTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+
+    let reason = Some("zk vm execution aborted: cycle limit of 1 exceeded".to_string());
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "zk/ResourceLimits.t.sol:ZkResourceLimitsTest",
+            vec![
+                ("testZkResourceLimitsCall()", false, reason.clone(), None, None),
+                ("testZkResourceLimitsCaughtRevert()", false, reason, None, None),
+            ],
+        )]),
+    );
+
+    // the failures are reported as resource limit failures, not as reverts of the tests
+    let suite = &results["zk/ResourceLimits.t.sol:ZkResourceLimitsTest"];
+    for result in suite.test_results.values() {
+        assert_eq!(
+            result.failure_kind,
+            Some(TestFailureKind::ZkResourceLimit(ZkResourceLimitExceeded::Cycles { limit: 1 }))
+        );
+        assert!(result.to_string().contains("[FAIL. zk vm resource limit exceeded. Reason: "));
+        let json = serde_json::to_value(result).unwrap();
+        assert_eq!(
+            json["failure_kind"],
+            serde_json::json!({ "zkResourceLimit": { "cycles": { "limit": 1 } } })
+        );
+    }
+}
+
+/// Executes the invariant tests with the fuzzed calls in the ZK-VM, shrinking the sequences
+/// breaking them
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_invariant() {
+    let runner = runner_with_config_and_zk(Config::with_root(PROJECT.root()));
+    let filter = Filter::new(".*", "ZkInvariant.*", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([
+            (
+                "zk/Invariant.t.sol:ZkInvariantTest",
+                vec![("invariantZkSenderNonces()", true, None, None, None)],
+            ),
+            (
+                "zk/Invariant.t.sol:ZkInvariantShrinkTest",
+                vec![("invariantZkNotBroken()", false, Some("revert: broken".into()), None, None)],
+            ),
+        ]),
+    );
+
+    let result =
+        &results["zk/Invariant.t.sol:ZkInvariantShrinkTest"].test_results["invariantZkNotBroken()"];
+    match result.counterexample.as_ref().expect("no counterexample") {
+        CounterExample::Single(_) => panic!("CounterExample should be a sequence."),
+        CounterExample::Sequence(sequence) => {
+            let calls = sequence
+                .iter()
+                .map(|call| call.signature.clone().unwrap_or_default())
+                .collect::<Vec<_>>();
+            assert_eq!(calls, ["arm()", "fire()"]);
+        }
+    }
+}
+
+/// Samples the functions of the handlers executed in the ZK-VM with the weights of
+/// `targetSelectorWeights()`
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_invariant_selector_weights() {
+    let runner = runner_with_config_and_zk(Config::with_root(PROJECT.root()));
+    let filter = Filter::new(".*", "ZkSelectorWeightsTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "zk/Invariant.t.sol:ZkSelectorWeightsTest",
+            vec![("invariantZkNeverCalled()", true, None, None, None)],
+        )]),
+    );
+    let result = &results["zk/Invariant.t.sol:ZkSelectorWeightsTest"].test_results
+        ["invariantZkNeverCalled()"];
+    assert!(matches!(result.kind, TestKind::Invariant { calls, reverts: 0, .. } if calls > 0));
+}
+
+/// Reports the median ergs of the calls of the fuzz and invariant tests executed in the ZK-VM,
+/// apart from their EVM gas
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_fuzz_ergs_reported() {
+    let runner = runner_with_config_and_zk(Config::with_root(PROJECT.root()));
+    let filter = Filter::new(
+        "testZkFuzzErgs|invariantZkSenderNonces",
+        "ZkFuzzTest|ZkInvariantTest",
+        &format!(".*zk{RE_PATH_SEPARATOR}*"),
+    );
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+
+    for (suite, test) in [
+        ("zk/Fuzz.t.sol:ZkFuzzTest", "testZkFuzzErgs(uint8)"),
+        ("zk/Invariant.t.sol:ZkInvariantTest", "invariantZkSenderNonces()"),
+    ] {
+        let result = &results[suite].test_results[test];
+        assert_eq!(result.status, TestStatus::Success, "{test}: {:?}", result.reason);
+        assert!(result.zk_ergs.is_some_and(|ergs| ergs > 0), "{test}: {:?}", result.zk_ergs);
+        assert!(result.short_result(test).contains("(ergs: "), "{test}");
+    }
+}
+
+/// Cuts the return data the traces record of the successful calls executed in the ZK-VM, and
+/// records the return data of the reverted calls in full
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_trace_return_data_limit() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.trace_return_data_limit = Some(32);
+    let runner = runner_with_config_and_zk(config);
+    let filter = Filter::new(".*", "ZkReturnDataLimitTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    let results =
+        TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI).test().await;
+    let suite = &results["zk/ReturnDataLimit.t.sol:ZkReturnDataLimitTest"];
+
+    // the return data of the call to the returner, recorded by the execution trace
+    let returned = |test: &str, status: TestStatus| {
+        let result = &suite.test_results[test];
+        assert_eq!(result.status, status, "{test}: {:?}", result.reason);
+        result
+            .traces
+            .iter()
+            .filter(|(kind, _)| *kind == TraceKind::Execution)
+            .flat_map(|(_, arena)| arena.nodes())
+            .find(|node| node.trace.depth == 1)
+            .map(|node| node.trace.output.clone())
+            .unwrap()
+    };
+    assert_eq!(returned("testZkLargeReturn()", TestStatus::Success).len(), 32);
+    assert!(returned("testZkLargeRevertFailing()", TestStatus::Failure).len() > 1024);
+}
+
+/// Executes the tests in EraVM on the state of a zkSync Era chain when launched forking one
+///
+/// This is a network test forking zkSync Era mainnet, so it's ignored by default.
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn test_zk_launch_fork() {
+    let runner = forked_runner("https://mainnet.era.zksync.io").await;
+    assert!(runner.use_zk);
+    let filter = Filter::new(".*", "ZkLaunchForkTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+
+    TestConfig::with_filter(runner, filter).evm_spec(SpecId::SHANGHAI).run().await;
+}
+
+/// Tests that the `vm.assume` call sites of the test contract are located in zk mode, as the test
+/// contract is executed in the EVM.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_zk_assume_rejections() {
+    let runner = runner_with_config_and_zk(Config::with_root(PROJECT.root()));
+    let filter = Filter::new(".*", "ZkAssumeTest", &format!(".*zk{RE_PATH_SEPARATOR}*"));
+    let mut test = TestConfig::with_filter(runner.await, filter).evm_spec(SpecId::SHANGHAI);
+    test.opts.fuzz.max_test_rejects = 100;
+    let results = test.test().await;
+
+    let result =
+        &results["zk/Assume.t.sol:ZkAssumeTest"].test_results["testZkAssumeRejections(uint256)"];
+    assert_eq!(result.status, TestStatus::Failure);
+    let reason = result.reason.as_deref().unwrap();
+    assert!(
+        reason.contains(
+            "Assume.t.sol:22:9 `vm.assume(x > 10 && x < 12)`, consider `x = bound(x, 11, 11);`"
+        ),
+        "{reason}"
+    );
+    assert!(!reason.contains(" pc "), "{reason}");
+}
@@ -258,6 +258,7 @@ pub enum ScriptOutcome {
     ScriptFailed,
     UnsupportedLibraries,
     ErrorSelectForkOnBroadcast,
+    BroadcastEventNotEmitted,
 }
 
 impl ScriptOutcome {
@@ -273,6 +274,7 @@ impl ScriptOutcome {
             Self::ScriptFailed => "script failed: ",
             Self::UnsupportedLibraries => "Multi chain deployment does not support library linking at the moment.",
             Self::ErrorSelectForkOnBroadcast => "cannot select forks during a broadcast",
+            Self::BroadcastEventNotEmitted => "didn't satisfy the expected broadcast events",
         }
     }
 
@@ -287,6 +289,7 @@ impl ScriptOutcome {
             ScriptOutcome::StaticCallNotAllowed |
             ScriptOutcome::UnsupportedLibraries |
             ScriptOutcome::ErrorSelectForkOnBroadcast |
+            ScriptOutcome::BroadcastEventNotEmitted |
             ScriptOutcome::ScriptFailed => true,
         }
     }
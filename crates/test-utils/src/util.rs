@@ -978,6 +978,22 @@ impl TestCommand {
         }
     }
 
+    /// Runs the command and asserts that none of the given secrets show up in its stdout or
+    /// stderr, regardless of whether the command succeeded.
+    #[track_caller]
+    pub fn assert_no_secrets(&mut self, secrets: &[&str]) -> Output {
+        let out = self.execute();
+        let (stdout, stderr) = (lossy_string(&out.stdout), lossy_string(&out.stderr));
+        for secret in secrets {
+            let unprefixed = secret.strip_prefix("0x").unwrap_or(secret);
+            let leaked = [&stdout, &stderr].into_iter().any(|output| {
+                output.contains(unprefixed) || output.contains(&unprefixed.to_ascii_lowercase())
+            });
+            assert!(!leaked, "secret `{secret}` leaked:\n\nstdout:\n{stdout}\n\nstderr:\n{stderr}");
+        }
+        out
+    }
+
     #[track_caller]
     pub fn ensure_success(&self, out: &Output) -> Result<()> {
         if out.status.success() {
@@ -1,6 +1,7 @@
 use crate::{error::PrivateKeyError, PendingSigner, WalletSigner};
 use ethers_signers::{HDPath as LedgerHDPath, LocalWallet, TrezorHDPath, WalletError};
 use eyre::{Context, Result};
+use foundry_common::redact::register_secret;
 use foundry_config::Config;
 use std::{
     fs,
@@ -10,6 +11,7 @@ use std::{
 
 /// Validates and sanitizes user inputs, returning configured [WalletSigner].
 pub fn create_private_key_signer(private_key: &str) -> Result<WalletSigner> {
+    register_secret(private_key);
     let privk = private_key.trim().strip_prefix("0x").unwrap_or(private_key);
     match LocalWallet::from_str(privk) {
         Ok(pk) => Ok(WalletSigner::Local(pk)),
@@ -53,6 +55,10 @@ pub fn create_mnemonic_signer(
     } else {
         mnemonic.to_owned()
     };
+    register_secret(&mnemonic);
+    if let Some(passphrase) = passphrase {
+        register_secret(passphrase);
+    }
 
     Ok(WalletSigner::from_mnemonic(&mnemonic, passphrase, hd_path, index)?)
 }
@@ -147,6 +153,7 @@ pub fn create_keystore_signer(
     }?;
 
     if let Some(password) = password {
+        register_secret(&password);
         let wallet = LocalWallet::decrypt_keystore(path, password)
             .wrap_err_with(|| format!("Failed to decrypt keystore {path:?}"))?;
         Ok((Some(WalletSigner::Local(wallet)), None))
@@ -9,6 +9,7 @@ use ethers_signers::{
     coins_bip39::English, AwsSigner, HDPath as LedgerHDPath, Ledger, LocalWallet, MnemonicBuilder,
     Signer, Trezor, TrezorHDPath,
 };
+use foundry_common::redact::register_secret;
 use rusoto_core::{
     credential::ChainProvider as AwsChainProvider, region::Region as AwsRegion,
     request::HttpClient as AwsHttpClient, Client as AwsClient,
@@ -223,10 +224,12 @@ impl PendingSigner {
         match self {
             Self::Keystore(path) => {
                 let password = rpassword::prompt_password("Enter keystore password:")?;
+                register_secret(&password);
                 Ok(WalletSigner::Local(LocalWallet::decrypt_keystore(path, password)?))
             }
             Self::Interactive => {
                 let private_key = rpassword::prompt_password("Enter private key:")?;
+                register_secret(&private_key);
                 Ok(WalletSigner::from_private_key(hex::decode(private_key)?)?)
             }
         }
@@ -0,0 +1,338 @@
+//! Combined EVM and EraVM artifacts.
+//!
+//! `forge build --zksync` writes the solc and zksolc artifacts of a contract to separate
+//! directories. Downstream tooling, like hardhat plugins and frontends, usually needs both, so a
+//! [CombinedArtifact] with the ABI and the bytecodes of both compilers can be written per
+//! contract, along with the EraVM bytecode hash and the factory dependencies deploying it needs.
+
+use super::{transitive_factory_deps, PackedEraBytecode};
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::{keccak256, Bytes, B256};
+use eyre::{Context, Result};
+use foundry_compilers::{Artifact, ArtifactId, ProjectCompileOutput};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+use zksync_utils::bytecode::hash_bytecode;
+
+/// The format of the combined artifacts.
+pub const COMBINED_ARTIFACT_FORMAT: &str = "foundry-zksync-combined-1";
+
+/// The directory of the combined artifacts, in the zksolc artifacts directory.
+pub const COMBINED_ARTIFACTS_DIR: &str = "combined";
+
+/// The solc and zksolc outputs of a contract, in a single artifact.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CombinedArtifact {
+    /// The format of the artifact, [COMBINED_ARTIFACT_FORMAT].
+    pub format: String,
+    /// The name of the contract.
+    pub contract_name: String,
+    /// The path of the source of the contract, relative to the project root.
+    pub source_name: String,
+    /// The version of solc the contract was compiled with, by solc and by zksolc.
+    #[serde(default)]
+    pub solc_version: String,
+    /// The ABI of the contract.
+    pub abi: JsonAbi,
+    /// The solc output.
+    pub evm: EvmBytecodes,
+    /// The zksolc output.
+    pub era: EraBytecodes,
+}
+
+/// The solc bytecodes of a contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmBytecodes {
+    /// The creation code.
+    pub bytecode: Bytes,
+    /// The deployed code.
+    pub deployed_bytecode: Bytes,
+    /// The keccak256 hash of the deployed code.
+    pub deployed_bytecode_hash: B256,
+}
+
+/// The zksolc bytecode of a contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EraBytecodes {
+    /// The bytecode, which is both deployed and run as the constructor on EraVM.
+    pub bytecode: Bytes,
+    /// The bytecode hash, identifying the contract on an Era chain.
+    pub bytecode_hash: B256,
+    /// The bytecodes of the contracts the contract deploys, directly or through the contracts it
+    /// deploys, by bytecode hash. They are the factory dependencies of its deployment.
+    pub factory_deps: BTreeMap<B256, Bytes>,
+}
+
+impl CombinedArtifact {
+    /// Returns the path of the artifact, `<source name>/<contract name>.json`.
+    pub fn path(&self) -> PathBuf {
+        Path::new(&self.source_name).join(format!("{}.json", self.contract_name))
+    }
+
+    /// Returns the path of the artifact of a contract compiled with several solc versions,
+    /// `<source name>/<contract name>.<solc version>.json`.
+    pub fn versioned_path(&self) -> PathBuf {
+        Path::new(&self.source_name)
+            .join(format!("{}.{}.json", self.contract_name, self.solc_version))
+    }
+}
+
+/// Returns the paths the combined artifacts are written to, their [versioned
+/// path](CombinedArtifact::versioned_path) for the contracts compiled with several solc versions
+/// and their [path](CombinedArtifact::path) otherwise.
+pub fn combined_artifact_paths(artifacts: &[CombinedArtifact]) -> Vec<PathBuf> {
+    let mut counts = HashMap::<_, usize>::new();
+    for artifact in artifacts {
+        *counts.entry(artifact.path()).or_default() += 1;
+    }
+    artifacts
+        .iter()
+        .map(|artifact| {
+            let path = artifact.path();
+            if counts[&path] > 1 {
+                artifact.versioned_path()
+            } else {
+                path
+            }
+        })
+        .collect()
+}
+
+/// The ABI, creation code and deployed code of a solc artifact.
+type SolcArtifact = (JsonAbi, Bytes, Bytes);
+
+/// Returns the combined artifacts of the contracts of the zksolc output with a solc counterpart,
+/// the contract of the same name from the same source compiled with the same solc version, with
+/// bytecode.
+///
+/// Contracts without bytecode, like interfaces, and contracts whose solc bytecode is unlinked are
+/// left out.
+pub fn combined_artifacts(
+    output: &ProjectCompileOutput,
+    zk_output: &ProjectCompileOutput,
+    root: &Path,
+) -> Vec<CombinedArtifact> {
+    let solc_artifacts = output.artifact_ids().filter_map(|(id, artifact)| {
+        let (Some(abi), Some(bytecode), Some(deployed_bytecode)) = (
+            artifact.get_abi(),
+            artifact.get_bytecode_bytes(),
+            artifact.get_deployed_bytecode_bytes(),
+        ) else {
+            return None
+        };
+        if deployed_bytecode.is_empty() {
+            return None
+        }
+        Some((id, (abi.into_owned(), bytecode.into_owned(), deployed_bytecode.into_owned())))
+    });
+    let zk_bytecodes = zk_output.artifact_ids().filter_map(|(id, artifact)| {
+        let deployed_bytecode = artifact.get_deployed_bytecode_bytes()?;
+        if deployed_bytecode.is_empty() {
+            return None
+        }
+        Some((id, PackedEraBytecode::from_vec(&deployed_bytecode)))
+    });
+    pair_artifacts(solc_artifacts, zk_bytecodes, root)
+}
+
+/// Pairs the zksolc bytecodes with their solc artifacts, sorted by path.
+fn pair_artifacts(
+    solc_artifacts: impl IntoIterator<Item = (ArtifactId, SolcArtifact)>,
+    zk_bytecodes: impl IntoIterator<Item = (ArtifactId, PackedEraBytecode)>,
+    root: &Path,
+) -> Vec<CombinedArtifact> {
+    let key = |id: &ArtifactId| {
+        let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+        (source.to_string_lossy().into_owned(), id.name.clone(), id.version.to_string())
+    };
+    let solc_artifacts = solc_artifacts
+        .into_iter()
+        .map(|(id, artifact)| (key(&id), artifact))
+        .collect::<HashMap<_, _>>();
+
+    let zk_bytecodes = zk_bytecodes.into_iter().collect::<Vec<_>>();
+    // the direct factory deps of every zksolc contract, to resolve the nested ones
+    let direct_factory_deps = zk_bytecodes
+        .iter()
+        .map(|(_, packed_bytecode)| {
+            (packed_bytecode.bytecode_hash(), packed_bytecode.dependencies())
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut artifacts = zk_bytecodes
+        .into_iter()
+        .filter_map(|(id, packed_bytecode)| {
+            let key = key(&id);
+            let (abi, bytecode, deployed_bytecode) = solc_artifacts.get(&key)?;
+            let factory_deps =
+                transitive_factory_deps(&direct_factory_deps, packed_bytecode.dependencies());
+            let (source_name, contract_name, solc_version) = key;
+            Some(CombinedArtifact {
+                format: COMBINED_ARTIFACT_FORMAT.to_string(),
+                contract_name,
+                source_name,
+                solc_version,
+                abi: abi.clone(),
+                evm: EvmBytecodes {
+                    bytecode: bytecode.clone(),
+                    deployed_bytecode: deployed_bytecode.clone(),
+                    deployed_bytecode_hash: keccak256(deployed_bytecode),
+                },
+                era: era_bytecodes(&packed_bytecode.bytecode(), factory_deps),
+            })
+        })
+        .collect::<Vec<_>>();
+    artifacts.sort_by(|a, b| a.versioned_path().cmp(&b.versioned_path()));
+    artifacts
+}
+
+/// Writes the combined artifacts of the contracts of the zksolc output to `dir`, at their
+/// [paths](combined_artifact_paths).
+///
+/// Returns the number of written artifacts.
+pub fn write_combined_artifacts(
+    output: &ProjectCompileOutput,
+    zk_output: &ProjectCompileOutput,
+    root: &Path,
+    dir: &Path,
+) -> Result<usize> {
+    let artifacts = combined_artifacts(output, zk_output, root);
+    for (artifact, path) in artifacts.iter().zip(combined_artifact_paths(&artifacts)) {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(artifact)?)
+            .wrap_err_with(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(artifacts.len())
+}
+
+fn era_bytecodes(bytecode: &[u8], factory_deps: Vec<Vec<u8>>) -> EraBytecodes {
+    EraBytecodes {
+        bytecode: Bytes::copy_from_slice(bytecode),
+        bytecode_hash: B256::from(hash_bytecode(bytecode).0),
+        factory_deps: factory_deps
+            .into_iter()
+            .map(|dep| (B256::from(hash_bytecode(&dep).0), Bytes::from(dep)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_combined_artifacts() {
+        let (bytecode, dep) = (vec![1; 32], vec![2; 32]);
+        let artifact = CombinedArtifact {
+            format: COMBINED_ARTIFACT_FORMAT.to_string(),
+            contract_name: "Factory".to_string(),
+            source_name: "src/Factory.sol".to_string(),
+            solc_version: "0.8.24".to_string(),
+            abi: JsonAbi::default(),
+            evm: EvmBytecodes {
+                bytecode: Bytes::from_static(&[0x60, 0x80]),
+                deployed_bytecode: Bytes::from_static(&[0x60]),
+                deployed_bytecode_hash: keccak256([0x60]),
+            },
+            era: era_bytecodes(&bytecode, vec![dep.clone()]),
+        };
+        assert_eq!(artifact.path(), Path::new("src/Factory.sol/Factory.json"));
+        assert_eq!(artifact.versioned_path(), Path::new("src/Factory.sol/Factory.0.8.24.json"));
+        assert_eq!(artifact.era.bytecode_hash, B256::from(hash_bytecode(&bytecode).0));
+        assert_eq!(
+            artifact.era.factory_deps,
+            BTreeMap::from([(B256::from(hash_bytecode(&dep).0), Bytes::from(dep))])
+        );
+
+        let json = serde_json::to_value(&artifact).unwrap();
+        assert_eq!(json["contractName"], "Factory");
+        assert_eq!(json["evm"]["deployedBytecode"], "0x60");
+        assert!(json["era"]["bytecodeHash"].is_string());
+        assert_eq!(serde_json::from_value::<CombinedArtifact>(json).unwrap(), artifact);
+    }
+
+    fn id(root: &Path, name: &str, version: &str) -> ArtifactId {
+        ArtifactId {
+            path: root.join(format!("out/Counter.sol/{name}.{version}.json")),
+            name: name.to_string(),
+            source: root.join("src/Counter.sol"),
+            version: semver::Version::parse(version).unwrap(),
+        }
+    }
+
+    fn packed(bytecode: &[u8], deps: &[&[u8]]) -> PackedEraBytecode {
+        PackedEraBytecode::new(
+            hex::encode(hash_bytecode(bytecode)),
+            hex::encode(bytecode),
+            deps.iter().map(hex::encode).collect(),
+        )
+    }
+
+    #[test]
+    fn pairs_artifacts_by_source_name_and_solc_version() {
+        let root = Path::new("/project");
+        let solc =
+            |code: u8| (JsonAbi::default(), Bytes::from(vec![code, 0x80]), Bytes::from(vec![code]));
+        let solc_artifacts = [
+            (id(root, "Counter", "0.8.20"), solc(0x20)),
+            (id(root, "Counter", "0.8.24"), solc(0x24)),
+            (id(root, "Factory", "0.8.24"), solc(0xfa)),
+        ];
+        let (counter_20, counter_24, child) = (vec![0x20; 32], vec![0x24; 32], vec![0xc; 32]);
+        let zk_bytecodes = [
+            (id(root, "Counter", "0.8.20"), packed(&counter_20, &[])),
+            (id(root, "Counter", "0.8.24"), packed(&counter_24, &[])),
+            (id(root, "Factory", "0.8.24"), packed(&[0xfa; 32], &[child.as_slice()])),
+            // no solc counterpart compiled with the same version
+            (id(root, "Factory", "0.8.20"), packed(&[0xfb; 32], &[])),
+            (id(root, "Child", "0.8.24"), packed(&child, &[])),
+        ];
+
+        let artifacts = pair_artifacts(solc_artifacts, zk_bytecodes, root);
+        let paired = artifacts
+            .iter()
+            .map(|artifact| {
+                (
+                    artifact.contract_name.as_str(),
+                    artifact.solc_version.as_str(),
+                    artifact.evm.deployed_bytecode[0],
+                    artifact.era.bytecode[0],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paired,
+            [
+                ("Counter", "0.8.20", 0x20, 0x20),
+                ("Counter", "0.8.24", 0x24, 0x24),
+                ("Factory", "0.8.24", 0xfa, 0xfa)
+            ]
+        );
+        assert_eq!(artifacts[0].source_name, "src/Counter.sol");
+        assert_eq!(
+            artifacts[2].era.factory_deps,
+            BTreeMap::from([(B256::from(hash_bytecode(&child).0), Bytes::from(child))])
+        );
+
+        // the contract compiled with both versions gets versioned paths
+        assert_eq!(
+            combined_artifact_paths(&artifacts),
+            [
+                PathBuf::from("src/Counter.sol/Counter.0.8.20.json"),
+                PathBuf::from("src/Counter.sol/Counter.0.8.24.json"),
+                PathBuf::from("src/Counter.sol/Factory.json"),
+            ]
+        );
+    }
+}
@@ -1,6 +1,9 @@
 #![allow(missing_docs)]
 //! This module provides the implementation of the ZkSolc compiler for Solidity contracts.
-use crate::zksolc::config::{Settings, ZkSolcConfig, ZkStandardJsonCompilerInput};
+use crate::zksolc::{
+    config::{Settings, ZkSolcConfig, ZkStandardJsonCompilerInput},
+    skip_reason, SkipReason, SkippedSource, ZkMissingLibrary, ZkWarningsPolicy,
+};
 /// ZkSolc is a specialized compiler that supports zero-knowledge (ZK) proofs for smart
 /// contracts.
 ///
@@ -160,6 +163,10 @@ pub struct ZkSolc {
     config: ZkSolcConfig,
     project: Project,
     standard_json: Option<ZkStandardJsonCompilerInput>,
+    /// Sources skipped during the last [`ZkSolc::compile`] run.
+    skipped: Vec<SkippedSource>,
+    /// Contracts linking missing libraries, detected during the last [`ZkSolc::compile`] run.
+    missing_libraries: Vec<ZkMissingLibrary>,
 }
 
 impl fmt::Display for ZkSolc {
@@ -204,7 +211,25 @@ pub fn compile_smart_contracts(
 
 impl ZkSolc {
     pub fn new(config: ZkSolcConfig, project: Project) -> Self {
-        Self { config, project, standard_json: None }
+        Self {
+            config,
+            project,
+            standard_json: None,
+            skipped: Vec::new(),
+            missing_libraries: Vec::new(),
+        }
+    }
+
+    /// Returns the sources that were skipped during the last compilation, because they match a
+    /// `zk_skip` pattern or use instructions zksolc doesn't support.
+    pub fn skipped_sources(&self) -> &[SkippedSource] {
+        &self.skipped
+    }
+
+    /// Returns the contracts linking libraries that aren't set in the `libraries` config,
+    /// detected during the last compilation if zksolc detects missing libraries.
+    pub fn missing_libraries(&self) -> &[ZkMissingLibrary] {
+        &self.missing_libraries
     }
 
     /// Compiles the Solidity contracts in the project's 'sources' directory and its subdirectories
@@ -263,7 +288,8 @@ impl ZkSolc {
     ///
     /// 5. Run Compiler and Handle Output:
     ///    - It runs the Solidity compiler for each source file with the corresponding compiler
-    ///      arguments.
+    ///      arguments, unless the project is cached and the artifacts of the source were compiled
+    ///      from the same sources, settings, arguments and zksolc version.
     ///    - The output of the compiler, including errors and warnings, is captured.
     ///
     /// 6. Handle Output (Errors and Warnings):
@@ -286,6 +312,11 @@ impl ZkSolc {
         // Step 1: Collect Source Files
         let sources = self.get_versioned_sources().wrap_err("Cannot get source files")?;
         let mut contract_bytecodes = BTreeMap::new();
+        let mut unsupported = HashMap::new();
+        self.skipped.clear();
+        self.missing_libraries.clear();
+        let detect_missing_libraries = self.config.settings.detect_missing_libraries;
+        let compiler_version = self.compiler_version();
 
         // Step 2: Compile Contracts for Each Source
         for (_solc, version) in sources {
@@ -301,12 +332,12 @@ impl ZkSolc {
                 // Step 4: Build Compiler Arguments
                 let comp_args = self.build_compiler_args(&contract_path, &self.project.solc);
 
-                // Hash the input contract to allow caching
-                let mut contract_file = File::open(&contract_path)?;
-                let mut buffer = Vec::new();
-                contract_file.read_to_end(&mut buffer)?;
-                let contract_hash =
-                    hex::encode(xxhash_rust::const_xxh3::xxh3_64(&buffer).to_be_bytes());
+                // Hash the compiler input, the sources and settings, to allow caching
+                let contract_hash = cache_key(
+                    self.standard_json.as_ref().wrap_err("Missing compiler input")?,
+                    &comp_args,
+                    &compiler_version,
+                )?;
 
                 let filename = contract_path
                     .file_name()
@@ -340,76 +371,224 @@ impl ZkSolc {
                     continue
                 }
 
+                if let Some(reason) = self.skip_reason(relative_path, &mut unsupported) {
+                    self.skipped.push(SkippedSource { path: relative_path.to_path_buf(), reason });
+                    continue
+                }
+
                 let artifact_paths =
                     ZkSolcArtifactPaths::new(self.project.paths.artifacts.join(&filename));
 
                 info!("\nCompiling {:?}...", contract_path);
-                let (output, maybe_artifact_paths) = match self
-                    .check_cache(&artifact_paths, &contract_hash)
-                {
+                // zksolc doesn't produce bytecode when it detects missing libraries, so neither
+                // cached artifacts are used nor artifacts written
+                let cached = if self.project.cached && !detect_missing_libraries {
+                    self.check_cache(&artifact_paths, &contract_hash)
+                } else {
+                    None
+                };
+                let (output, maybe_artifact_paths) = match cached {
                     Some(output) => {
                         info!("Using hashed artifact ({}) for {:?}", contract_hash, filename);
                         (output, None)
                     }
                     None => {
-                        let mut cmd = Command::new(&self.config.compiler_path);
-                        let mut child = cmd
-                            .args(&comp_args)
-                            .stdin(Stdio::piped())
-                            .stderr(Stdio::piped())
-                            .stdout(Stdio::piped())
-                            .spawn()
-                            .wrap_err("Failed to start the compiler")?;
-
-                        let stdin = child.stdin.take().expect("Stdin exists.");
-
-                        let stdjson = serde_json::to_value(&self.standard_json.clone().unwrap())
-                            .wrap_err("Could not serialize JSON input")?;
-
-                        serde_json::to_writer(stdin, &stdjson)
-                            .wrap_err("Could not assign standard_json to writer")?;
-
-                        let output =
-                            child.wait_with_output().wrap_err("Could not run compiler cmd")?;
-
-                        if !output.status.success() {
-                            // Skip this file if the compiler output is empty
-                            // currently zksolc returns false for success if output is empty
-                            // when output is empty, it has a length of 3, `[]\n`
-                            // solc returns true for success if output is empty
-                            if output.stderr.len() <= 3 {
-                                continue
+                        let input =
+                            self.standard_json.as_ref().wrap_err("Missing compiler input")?;
+                        match self.run_compiler(&comp_args, input, &contract_path)? {
+                            Some(output) => {
+                                (output, Some(artifact_paths).filter(|_| !detect_missing_libraries))
                             }
-                            eyre::bail!(
-                                    "Compilation failed with {:?}. Using compiler: {:?}, with args {:?} {:?}",
-                                    String::from_utf8(output.stderr).unwrap_or_default(),
-                                    self.config.compiler_path,
-                                    contract_path,
-                                    &comp_args
-                                );
+                            None => continue,
                         }
-
-                        (output.stdout, Some(artifact_paths))
                     }
                 };
+                if detect_missing_libraries {
+                    self.missing_libraries.extend(ZkMissingLibrary::from_output(
+                        &output,
+                        &filename,
+                        self.project.root(),
+                    ));
+                }
 
                 // Step 6: Handle Output (Errors and Warnings)
-                let (artifacts, bytecodes) = ZkSolc::handle_output(
+                let (mut artifacts, mut bytecodes) = ZkSolc::handle_output(
                     output,
                     &filename,
                     &mut displayed_warnings,
+                    (&self.config.warnings, self.project.root()),
                     &contract_hash,
                     maybe_artifact_paths,
                 );
+
+                // Step 7: Replace the artifacts of the contracts compiled with their overrides
+                let overridden = if detect_missing_libraries {
+                    Vec::new()
+                } else {
+                    self.compile_overrides(
+                        &contract_path,
+                        &comp_args,
+                        &compiler_version,
+                        &mut displayed_warnings,
+                    )?
+                };
+                for (name, artifact, overridden_bytecodes) in overridden {
+                    bytecodes.retain(|_, contract| *contract != name);
+                    bytecodes.extend(overridden_bytecodes);
+                    artifacts.insert(name, artifact);
+                }
                 data.insert(filename.clone(), artifacts);
                 contract_bytecodes.extend(bytecodes);
             }
         }
+        if !self.skipped.is_empty() {
+            println!(
+                "{}",
+                Yellow.paint(format!(
+                    "Skipped {} source(s) that can't be compiled with zksolc, they're only \
+                     available to EVM tests:",
+                    self.skipped.len()
+                ))
+            );
+            for skipped in &self.skipped {
+                println!("  {skipped}");
+            }
+        }
+
         let mut result = ProjectCompileOutput::default();
         result.set_compiled_artifacts(Artifacts(data));
         Ok((result, contract_bytecodes))
     }
 
+    /// Compiles the contracts of the source at `contract_path` that have `zk_overrides`, each with
+    /// a separate zksolc invocation on the current compiler input with its optimizer settings
+    /// replaced.
+    ///
+    /// Returns the name, artifact and bytecode hashes of every overridden contract. Overridden
+    /// outputs are cached like the ones of the profile, in the `overrides` dir of the source's
+    /// artifacts.
+    #[allow(clippy::type_complexity)]
+    fn compile_overrides(
+        &self,
+        contract_path: &Path,
+        comp_args: &[String],
+        compiler_version: &str,
+        displayed_warnings: &mut HashSet<String>,
+    ) -> Result<Vec<(String, Vec<ArtifactFile<ConfigurableContractArtifact>>, ContractBytecodes)>>
+    {
+        let relative_path = contract_path.strip_prefix(self.project.root())?;
+        let filename = contract_path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let mut overridden = Vec::new();
+        for (name, entry) in self.config.overrides.of_source(relative_path) {
+            let mut input = self.standard_json.clone().wrap_err("Missing compiler input")?;
+            input.settings.optimizer = entry.apply(&input.settings.optimizer);
+            let contract_hash = cache_key(&input, comp_args, compiler_version)?;
+
+            let artifact_dir =
+                self.build_artifacts_path(contract_path)?.join("overrides").join(name);
+            fs::create_dir_all(&artifact_dir).wrap_err("Could not create artifacts directory")?;
+            let artifact_paths = ZkSolcArtifactPaths::new(artifact_dir);
+
+            info!("\nCompiling {} of {:?} with its overrides...", name, contract_path);
+            let cached = if self.project.cached {
+                self.check_cache(&artifact_paths, &contract_hash)
+            } else {
+                None
+            };
+            let (output, maybe_artifact_paths) = match cached {
+                Some(output) => (output, None),
+                None => match self.run_compiler(comp_args, &input, contract_path)? {
+                    Some(output) => (output, Some(artifact_paths)),
+                    None => continue,
+                },
+            };
+
+            let (mut artifacts, bytecodes) = ZkSolc::handle_output(
+                output,
+                filename,
+                displayed_warnings,
+                (&self.config.warnings, self.project.root()),
+                &contract_hash,
+                maybe_artifact_paths,
+            );
+            let artifact = artifacts.remove(name).wrap_err_with(|| {
+                format!("The zk_overrides contract {name} is not in {}", relative_path.display())
+            })?;
+            let bytecodes =
+                bytecodes.into_iter().filter(|(_, contract)| contract == name).collect();
+            overridden.push((name.to_string(), artifact, bytecodes));
+        }
+        Ok(overridden)
+    }
+
+    /// Runs zksolc with `comp_args` on the compiler `input` of the source at `contract_path`.
+    ///
+    /// Returns `None` if the compiler output is empty, as zksolc fails when there is nothing to
+    /// compile.
+    fn run_compiler(
+        &self,
+        comp_args: &[String],
+        input: &ZkStandardJsonCompilerInput,
+        contract_path: &Path,
+    ) -> Result<Option<Vec<u8>>> {
+        let mut cmd = Command::new(&self.config.compiler_path);
+        let mut child = cmd
+            .args(comp_args)
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .wrap_err("Failed to start the compiler")?;
+
+        let stdin = child.stdin.take().expect("Stdin exists.");
+
+        let stdjson = serde_json::to_value(input).wrap_err("Could not serialize JSON input")?;
+
+        serde_json::to_writer(stdin, &stdjson)
+            .wrap_err("Could not assign standard_json to writer")?;
+
+        let output = child.wait_with_output().wrap_err("Could not run compiler cmd")?;
+
+        if !output.status.success() {
+            // Skip this file if the compiler output is empty
+            // currently zksolc returns false for success if output is empty
+            // when output is empty, it has a length of 3, `[]\n`
+            // solc returns true for success if output is empty
+            if output.stderr.len() <= 3 {
+                return Ok(None)
+            }
+            eyre::bail!(
+                "Compilation failed with {:?}. Using compiler: {:?}, with args {:?} {:?}",
+                String::from_utf8(output.stderr).unwrap_or_default(),
+                self.config.compiler_path,
+                contract_path,
+                comp_args
+            );
+        }
+
+        Ok(Some(output.stdout))
+    }
+
+    /// Returns why the source at `relative_path` must not be compiled with zksolc, if it or any of
+    /// the sources in its compiler input, which it imports, match a `zk_skip` pattern or use an
+    /// unsupported instruction, see [`skip_reason`].
+    fn skip_reason(
+        &self,
+        relative_path: &Path,
+        unsupported: &mut HashMap<PathBuf, Option<&'static str>>,
+    ) -> Option<SkipReason> {
+        let root = self.project.root();
+        let sources = self.standard_json.as_ref().map(|input| &input.sources).into_iter().flatten();
+        skip_reason(
+            relative_path,
+            sources.map(|(path, source)| {
+                (path.strip_prefix(root).unwrap_or(path), source.content.as_str())
+            }),
+            &self.config.skip_contracts,
+            unsupported,
+        )
+    }
+
     /// Checks if the contract has already been compiled for the given input contract hash.
     /// If yes, returns the pre-compiled data.
     fn check_cache(
@@ -443,6 +622,18 @@ impl ZkSolc {
         }
     }
 
+    /// Returns the version of the `zksolc` binary, as printed by `zksolc --version`, or its path
+    /// if it can't be run.
+    fn compiler_version(&self) -> String {
+        Command::new(&self.config.compiler_path)
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_else(|| self.config.compiler_path.display().to_string())
+    }
+
     /// Builds the compiler arguments for the Solidity compiler based on the provided versioned
     /// source and solc instance. The compiler arguments specify options and settings for the
     /// compiler's execution.
@@ -472,6 +663,11 @@ impl ZkSolc {
         if self.config.settings.force_evmla {
             comp_args.push("--force-evmla".to_string());
         }
+
+        // Check if only the missing libraries are detected
+        if self.config.settings.detect_missing_libraries {
+            comp_args.push("--detect-missing-libraries".to_string());
+        }
         comp_args
     }
 
@@ -485,6 +681,7 @@ impl ZkSolc {
     /// * `source` - The path of the contract source file that was compiled.
     /// * `displayed_warnings` - A mutable set that keeps track of displayed warnings to avoid
     ///   duplicates.
+    /// * `warnings_policy` - The policy of the warnings promoted to errors, with the project root.
     ///
     /// # Output Handling
     ///
@@ -496,7 +693,8 @@ impl ZkSolc {
     /// - The function checks for errors and warnings in the compiler output and handles them
     ///   accordingly.
     /// - Errors are printed in red color.
-    /// - Warnings are printed in yellow color.
+    /// - Warnings are printed in yellow color, unless they are denied by the policy, then they are
+    ///   errors.
     /// - If an error is encountered, the function exits with a non-zero status code.
     /// - If only warnings are present, a message indicating the presence of warnings is printed.
     ///
@@ -512,7 +710,7 @@ impl ZkSolc {
     /// let output = std::process::Output { ... };
     /// let source = "/path/to/contract.sol".to_string();
     /// let mut displayed_warnings = HashSet::new();
-    /// ZkSolc::handle_output(output, source, &mut displayed_warnings);
+    /// ZkSolc::handle_output(output, source, &mut displayed_warnings, (&policy, root), "", None);
     /// ```
     ///
     /// In this example, the `handle_output` function is called with the compiler output, contract
@@ -522,6 +720,7 @@ impl ZkSolc {
         output: Vec<u8>,
         source: &str,
         displayed_warnings: &mut HashSet<String>,
+        warnings_policy: (&ZkWarningsPolicy, &Path),
         contract_hash: &str,
         write_artifacts: Option<ZkSolcArtifactPaths>,
     ) -> (BTreeMap<String, Vec<ArtifactFile<ConfigurableContractArtifact>>>, ContractBytecodes)
@@ -553,7 +752,7 @@ impl ZkSolc {
         };
 
         // Handle warnings in the output
-        ZkSolc::handle_output_warnings(&compiler_output, displayed_warnings);
+        ZkSolc::handle_output_warnings(&compiler_output, displayed_warnings, warnings_policy);
 
         // First - let's get all the bytecodes.
         let mut all_bytecodes: HashMap<String, String> = Default::default();
@@ -576,6 +775,13 @@ impl ZkSolc {
             if key.contains(source) {
                 let contracts_in_file = compiler_output.contracts.get(key).unwrap();
                 for (contract_name, contract) in contracts_in_file {
+                    if !contract.missing_libraries.is_empty() {
+                        warn!(
+                            "{} links missing libraries: {}",
+                            contract_name,
+                            contract.missing_libraries.join(", ")
+                        );
+                    }
                     // if contract hash is empty, skip
                     if contract.hash.is_none() {
                         trace!("{} -> empty contract.hash", contract_name);
@@ -679,6 +885,8 @@ impl ZkSolc {
     ///   from the `serde_json` crate.
     /// * `displayed_warnings` - A mutable reference to a `HashSet` that tracks displayed warnings
     ///   to avoid duplicates.
+    /// * `warnings_policy` - The policy of the warnings promoted to errors, with the project root
+    ///   the sources of the warnings are relative to.
     ///
     /// # Behavior
     ///
@@ -688,8 +896,8 @@ impl ZkSolc {
     /// warning message has been displayed before to avoid duplicates. If the warning message
     /// has not been displayed before, it adds the message to the `displayed_warnings` set,
     /// prints the formatted warning message in yellow, and sets the `has_warning` flag to true.
-    /// If the severity is not "warning", it prints the formatted error message in red and sets
-    /// the `has_error` flag to true.
+    /// If the severity is not "warning", or the warning is denied by the policy, it prints the
+    /// formatted error message in red and sets the `has_error` flag to true.
     ///
     /// If any errors are encountered, the function calls `exit(1)` to terminate the program. If
     /// only warnings are encountered, it prints a message indicating that the compiler run
@@ -697,11 +905,13 @@ impl ZkSolc {
     pub fn handle_output_warnings(
         output_json: &ZkSolcCompilerOutput,
         displayed_warnings: &mut HashSet<String>,
+        (warnings_policy, root): (&ZkWarningsPolicy, &Path),
     ) {
         let errors = &output_json.errors;
 
         let mut has_error = false;
         let mut has_warning = false;
+        let mut has_denied_warning = false;
 
         for error in errors {
             let severity = error.get("severity").and_then(|v| v.as_str()).unwrap_or("Unknown");
@@ -710,6 +920,17 @@ impl ZkSolc {
 
             let is_warning = severity.eq_ignore_ascii_case("warning");
             if is_warning {
+                let file = error.pointer("/sourceLocation/file").and_then(|v| v.as_str());
+                let code = error.get("errorCode").and_then(|v| v.as_str());
+                let message =
+                    error.get("message").and_then(|v| v.as_str()).unwrap_or(formatted_message);
+                if warnings_policy.is_denied(root, file, code, message) {
+                    println!("{}", Red.paint(formatted_message));
+                    has_error = true;
+                    has_denied_warning = true;
+                    continue
+                }
+
                 let main_message = formatted_message.lines().next().unwrap_or("").to_string();
                 if !displayed_warnings.contains(&main_message) {
                     displayed_warnings.insert(main_message);
@@ -723,6 +944,12 @@ impl ZkSolc {
         }
 
         if has_error {
+            if has_denied_warning {
+                println!(
+                    "{}",
+                    Red.paint("Compiler run failed: warnings are denied by `zk_deny_warnings`")
+                );
+            }
             exit(1);
         } else if has_warning {
             warn!("Compiler run completed with warnings");
@@ -861,11 +1088,12 @@ impl ZkSolc {
             settings: Settings {
                 remappings: input.settings.remappings,
                 optimizer: self.config.settings.optimizer.clone(),
-                metadata: input.settings.metadata,
+                metadata: self.config.settings.metadata.clone().or(input.settings.metadata),
                 output_selection: input.settings.output_selection,
                 libraries: input.settings.libraries,
                 is_system: self.config.settings.is_system,
                 force_evmla: self.config.settings.force_evmla,
+                detect_missing_libraries: self.config.settings.detect_missing_libraries,
                 missing_libraries_path: self.config.settings.missing_libraries_path.clone(),
                 are_libraries_missing: self.config.settings.are_libraries_missing,
                 contracts_to_compile: self.config.settings.contracts_to_compile.clone(),
@@ -881,6 +1109,7 @@ impl ZkSolc {
     /// 1. Retrieve Project Sources:
     ///    - The function calls the `sources` method of the `Project` instance to obtain the
     ///      Solidity contract sources for the project.
+    ///    - The extra files of the config, e.g. library tests, are added to the sources.
     ///    - If the retrieval of project sources fails, an error is returned.
     ///
     /// 2. Resolve Graph of Sources and Versions:
@@ -945,8 +1174,11 @@ impl ZkSolc {
     /// struct to obtain the necessary versioned sources for contract compilation.
     /// The versioned sources can then be used for further processing or analysis.
     fn get_versioned_sources(&mut self) -> Result<BTreeMap<Solc, SolidityVersionSources>> {
-        // Step 1: Retrieve Project Sources
-        let sources = self.project.paths.read_input_files()?;
+        // Step 1: Retrieve Project Sources, and the extra files of the config
+        let mut sources = self.project.paths.read_input_files()?;
+        if !self.config.files.is_empty() {
+            sources.extend(Source::read_all_files(self.config.files.clone())?);
+        }
 
         // Step 2: Resolve Graph of Sources and Versions
         let graph = Graph::resolve_sources(&self.project.paths, sources)
@@ -1005,6 +1237,26 @@ impl ZkSolc {
     }
 }
 
+/// Returns the cache key of the artifacts compiled from `input` with `comp_args` and the
+/// `compiler_version` of zksolc.
+///
+/// The input holds the content of the compiled source and of all the sources it imports, and the
+/// compiler settings, so the artifacts are recompiled when any of them changes. The arguments
+/// hold the path of solc, which is specific to its version.
+fn cache_key(
+    input: &ZkStandardJsonCompilerInput,
+    comp_args: &[String],
+    compiler_version: &str,
+) -> Result<String> {
+    let mut buffer = serde_json::to_vec(input).wrap_err("Could not serialize JSON input")?;
+    for arg in comp_args {
+        buffer.extend(arg.as_bytes());
+        buffer.push(0);
+    }
+    buffer.extend(compiler_version.as_bytes());
+    Ok(hex::encode(xxhash_rust::const_xxh3::xxh3_64(&buffer).to_be_bytes()))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ZkSolcCompilerOutput {
     // Map from file name -> (Contract name -> Contract)
@@ -1022,6 +1274,9 @@ pub struct ZkContract {
     // Hashmap from hash to filename:contract_name string.
     #[serde(rename = "factoryDependencies", default)]
     pub factory_dependencies: HashMap<String, String>,
+    // The libraries the contract links that aren't deployed, in missing libraries detection mode.
+    #[serde(rename = "missingLibraries", default)]
+    pub missing_libraries: Vec<String>,
     pub evm: Evm,
     pub abi: Option<JsonAbi>,
 }
@@ -1043,9 +1298,11 @@ pub struct ZkSourceFile {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::{collections::HashSet, path::Path};
 
-    use super::{ZkSolc, ZkSolcCompilerOutput};
+    use super::{cache_key, ZkSolc, ZkSolcCompilerOutput, ZkWarningsPolicy};
+    use crate::zksolc::config::{Settings, ZkStandardJsonCompilerInput};
+    use foundry_compilers::artifacts::Source;
 
     /// Basic test to analyze the single Counter.sol artifact.
     #[test]
@@ -1055,7 +1312,15 @@ mod tests {
             .to_vec();
         let mut displayed_warnings = HashSet::new();
         let source = "src/Counter.sol".to_owned();
-        let (result, _) = ZkSolc::handle_output(data, &source, &mut displayed_warnings, "", None);
+        let policy = ZkWarningsPolicy::default();
+        let (result, _) = ZkSolc::handle_output(
+            data,
+            &source,
+            &mut displayed_warnings,
+            (&policy, Path::new("")),
+            "",
+            None,
+        );
 
         let artifacts = result.get("Counter").unwrap();
         assert_eq!(artifacts.len(), 1);
@@ -1080,4 +1345,23 @@ mod tests {
         let _parsed_empty: ZkSolcCompilerOutput =
             serde_json::from_slice(&almost_empty_data).unwrap();
     }
+
+    #[test]
+    fn cache_key_changes_with_sources_settings_and_compiler() {
+        let input = |content: &str, is_system: bool| {
+            let counter = Source::new("import \"./Lib.sol\"; contract Counter {}");
+            let lib = Source::new(content);
+            let sources = vec![("src/Counter.sol".into(), counter), ("src/Lib.sol".into(), lib)];
+            let settings = Settings { is_system, ..Default::default() };
+            ZkStandardJsonCompilerInput::new(sources, settings)
+        };
+        let args = vec!["--standard-json".to_string()];
+        let key = cache_key(&input("library Lib {}", false), &args, "v1.4.0").unwrap();
+
+        assert_eq!(cache_key(&input("library Lib {}", false), &args, "v1.4.0").unwrap(), key);
+        assert_ne!(cache_key(&input("library Lib { }", false), &args, "v1.4.0").unwrap(), key);
+        assert_ne!(cache_key(&input("library Lib {}", true), &args, "v1.4.0").unwrap(), key);
+        assert_ne!(cache_key(&input("library Lib {}", false), &[], "v1.4.0").unwrap(), key);
+        assert_ne!(cache_key(&input("library Lib {}", false), &args, "v1.3.23").unwrap(), key);
+    }
 }
@@ -18,9 +18,12 @@ use foundry_compilers::{
     remappings::Remapping,
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
-use crate::setup_zksolc_manager;
+use crate::{
+    setup_zksolc_manager, ZkContractOverride, ZkContractOverrides, ZkWarningsOverride,
+    ZkWarningsPolicy,
+};
 
 const SOLIDITY: &str = "Solidity";
 /// Configuration for the zkSolc compiler.
@@ -40,6 +43,18 @@ pub struct ZkSolcConfig {
 
     /// contracts to avoid compiling
     pub avoid_contracts: Option<Vec<globset::GlobMatcher>>,
+
+    /// sources that are skipped and reported, as they can't be compiled with zksolc
+    pub skip_contracts: Vec<globset::GlobMatcher>,
+
+    /// warnings that fail the compilation
+    pub warnings: ZkWarningsPolicy,
+
+    /// extra sources to compile, that are not necessarily in the project's source dirs
+    pub files: Vec<PathBuf>,
+
+    /// contracts compiled with different optimizer settings than the rest
+    pub overrides: ZkContractOverrides,
 }
 
 /// Compiler settings for zkSolc.
@@ -75,6 +90,10 @@ pub struct Settings {
     pub is_system: bool,
     /// A flag indicating whether to forcibly switch to the EVM legacy assembly pipeline.
     pub force_evmla: bool,
+    /// A flag indicating whether to only detect the missing deployable libraries, passed to
+    /// zksolc as `--detect-missing-libraries`.
+    #[serde(skip)]
+    pub detect_missing_libraries: bool,
     /// Path to cache missing library dependencies, used for compiling and deploying libraries.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub missing_libraries_path: Option<String>,
@@ -97,6 +116,7 @@ impl Default for Settings {
             libraries: Default::default(),
             is_system: false,
             force_evmla: false,
+            detect_missing_libraries: false,
             missing_libraries_path: None,
             are_libraries_missing: false,
             contracts_to_compile: Default::default(),
@@ -114,6 +134,7 @@ pub struct SettingsBuilder {
     libraries: Libraries,
     is_system: bool,
     force_evmla: bool,
+    detect_missing_libraries: bool,
     missing_libraries_path: Option<String>,
     are_libraries_missing: bool,
     contracts_to_compile: Vec<String>,
@@ -170,6 +191,12 @@ impl SettingsBuilder {
         self
     }
 
+    /// Sets detect_missing_libraries.
+    pub fn detect_missing_libraries(mut self, value: bool) -> Self {
+        self.detect_missing_libraries = value;
+        self
+    }
+
     /// Sets missing_libraries_path.
     pub fn missing_libraries_path(mut self, value: Option<String>) -> Self {
         self.missing_libraries_path = value;
@@ -198,6 +225,7 @@ impl SettingsBuilder {
             libraries: self.libraries,
             is_system: self.is_system,
             force_evmla: self.force_evmla,
+            detect_missing_libraries: self.detect_missing_libraries,
             missing_libraries_path: self.missing_libraries_path.take(),
             are_libraries_missing: self.are_libraries_missing,
             contracts_to_compile: self.contracts_to_compile,
@@ -290,6 +318,12 @@ pub struct ZkSolcConfigBuilder {
     compiler_path: Option<PathBuf>,
     contracts_to_compile: Option<Vec<String>>,
     avoid_contracts: Option<Vec<String>>,
+    skip_contracts: Vec<String>,
+    deny_warnings: Vec<String>,
+    allow_warnings: Vec<String>,
+    warnings_overrides: Vec<ZkWarningsOverride>,
+    files: Vec<PathBuf>,
+    overrides: BTreeMap<String, ZkContractOverride>,
     settings: SettingsBuilder,
 }
 
@@ -332,9 +366,60 @@ impl ZkSolcConfigBuilder {
         self
     }
 
+    /// Sets skip_contracts.
+    pub fn skip_contracts(mut self, value: Vec<String>) -> Self {
+        self.skip_contracts = value;
+        self
+    }
+
+    /// Sets deny_warnings.
+    pub fn deny_warnings(mut self, value: Vec<String>) -> Self {
+        self.deny_warnings = value;
+        self
+    }
+
+    /// Sets allow_warnings.
+    pub fn allow_warnings(mut self, value: Vec<String>) -> Self {
+        self.allow_warnings = value;
+        self
+    }
+
+    /// Sets warnings_overrides.
+    pub fn warnings_overrides(mut self, value: Vec<ZkWarningsOverride>) -> Self {
+        self.warnings_overrides = value;
+        self
+    }
+
+    /// Sets extra files to compile, that are not necessarily in the project's source dirs.
+    pub fn files(mut self, value: Vec<PathBuf>) -> Self {
+        self.files = value;
+        self
+    }
+
+    /// Sets the overrides of contracts, by `<path>:<contract>` identifier.
+    pub fn overrides(mut self, value: BTreeMap<String, ZkContractOverride>) -> Self {
+        self.overrides = value;
+        self
+    }
+
     /// Builds the `ZkSolcConfig`.
     pub fn build(self) -> Result<ZkSolcConfig, String> {
         let settings = self.settings.build()?;
+        let skip_contracts = self
+            .skip_contracts
+            .iter()
+            .map(|pat| {
+                globset::Glob::new(pat)
+                    .map(|glob| glob.compile_matcher())
+                    .map_err(|err| format!("invalid zk_skip pattern {pat:?}: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let warnings = ZkWarningsPolicy::new(
+            &self.deny_warnings,
+            &self.allow_warnings,
+            &self.warnings_overrides,
+        )?;
+        let overrides = ZkContractOverrides::new(&self.overrides)?;
         let compiler_path = if let Some(compiler_path) = self.compiler_path {
             compiler_path
         } else if let Some(compiler_version) = self.compiler_version {
@@ -374,6 +459,10 @@ impl ZkSolcConfigBuilder {
                     .map(|pat| globset::Glob::new(&pat).expect("invalid pattern").compile_matcher())
                     .collect::<Vec<_>>()
             }),
+            skip_contracts,
+            warnings,
+            files: self.files,
+            overrides,
         })
     }
 }
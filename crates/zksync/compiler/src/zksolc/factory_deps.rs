@@ -55,6 +55,11 @@ impl PackedEraBytecode {
             .map(|entry| hex::decode(entry).unwrap())
             .collect()
     }
+
+    /// Get the factory deps, without the bytecode of the contract.
+    pub fn dependencies(&self) -> Vec<Vec<u8>> {
+        self.factory_deps.iter().map(|entry| hex::decode(entry).unwrap()).collect()
+    }
 }
 
 fn ensure_chunkable(bytes: &[u8]) {
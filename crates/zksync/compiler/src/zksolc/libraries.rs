@@ -0,0 +1,303 @@
+//! Libraries linked by zksolc contracts.
+//!
+//! zksolc links libraries by address at compile time, so the ones a contract links must be
+//! deployed, and set in the `libraries` config, before the contract is compiled. With
+//! `--detect-missing-libraries`, zksolc reports the libraries each contract links that aren't
+//! set instead of producing bytecode. The report orders their deployment, libraries linking
+//! other libraries being deployed after them, and is cached in the cache directory.
+//!
+//! Tests and scripts order the deployment of the configured libraries with the code they were
+//! just compiled to instead, since the cached report may be stale: a library links the libraries
+//! whose addresses are in its code.
+
+use super::ZkSolcCompilerOutput;
+use alloy_primitives::Address;
+use eyre::{Context, Result};
+use foundry_compilers::artifacts::Libraries;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// The file of the last missing libraries report, in the cache directory.
+pub const ZKSOLC_MISSING_LIBRARIES_FILE: &str = "zksolc-missing-libraries.json";
+
+/// The libraries a contract links that aren't set in the `libraries` config.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkMissingLibrary {
+    /// The path of the source of the contract, relative to the project root.
+    pub contract_path: String,
+    /// The name of the contract.
+    pub contract_name: String,
+    /// The missing libraries, as `<path>:<name>` identifiers.
+    pub missing_libraries: Vec<String>,
+}
+
+impl ZkMissingLibrary {
+    /// Returns the `<path>:<name>` identifier of the contract.
+    pub fn identifier(&self) -> String {
+        format!("{}:{}", self.contract_path, self.contract_name)
+    }
+
+    /// Returns the contracts of `source` linking missing libraries in a zksolc output, with their
+    /// paths relative to `root`.
+    pub fn from_output(output: &[u8], source: &str, root: &Path) -> Vec<Self> {
+        let Ok(output) = serde_json::from_slice::<ZkSolcCompilerOutput>(output) else {
+            return Vec::new()
+        };
+        let mut missing = output
+            .contracts
+            .iter()
+            .filter(|(path, _)| path.contains(source))
+            .flat_map(|(path, contracts)| {
+                contracts.iter().filter(|(_, contract)| !contract.missing_libraries.is_empty()).map(
+                    |(name, contract)| Self {
+                        contract_path: relative_path(path, root),
+                        contract_name: name.clone(),
+                        missing_libraries: contract
+                            .missing_libraries
+                            .iter()
+                            .map(|library| relative_identifier(library, root))
+                            .collect(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        missing.sort_by_key(|contract| contract.identifier());
+        missing
+    }
+}
+
+impl fmt::Display for ZkMissingLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} links {}", self.identifier(), self.missing_libraries.join(", "))
+    }
+}
+
+/// A library of the `libraries` config, linked at a fixed address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkLibrary {
+    /// The path of the source of the library.
+    pub path: PathBuf,
+    /// The name of the library.
+    pub name: String,
+    /// The address the library is linked at.
+    pub address: Address,
+}
+
+impl ZkLibrary {
+    /// Returns the `<path>:<name>` identifier of the library.
+    pub fn identifier(&self) -> String {
+        format!("{}:{}", self.path.display(), self.name)
+    }
+
+    /// Returns the libraries of the `libraries` config, with their paths relative to `root`.
+    pub fn configured(libraries: &Libraries, root: &Path) -> Result<Vec<Self>> {
+        let mut configured = Vec::new();
+        for (path, names) in &libraries.libs {
+            let path = path.strip_prefix(root).unwrap_or(path);
+            for (name, address) in names {
+                let address = address.parse().wrap_err_with(|| {
+                    format!("invalid address of library {}:{name}: {address}", path.display())
+                })?;
+                configured.push(Self { path: path.to_path_buf(), name: name.clone(), address });
+            }
+        }
+        Ok(configured)
+    }
+}
+
+/// Returns the missing libraries of `report` in deployment order, every library after the
+/// libraries it links.
+///
+/// Fails if libraries link each other in a cycle, when none of them can be deployed first.
+pub fn missing_libraries_deployment_order(report: &[ZkMissingLibrary]) -> Result<Vec<String>> {
+    let mut libraries = Vec::new();
+    for library in report.iter().flat_map(|contract| &contract.missing_libraries) {
+        if !libraries.contains(library) {
+            libraries.push(library.clone());
+        }
+    }
+    dependency_order(libraries, &linked_libraries(report))
+}
+
+/// Orders the deployment of the configured `libraries`, with the code they were compiled to,
+/// every library after the libraries it links. The libraries are linked at their addresses at
+/// compile time, so a library links the ones whose address is in its code. The libraries that
+/// don't link each other keep their order.
+///
+/// Fails if libraries link each other in a cycle.
+pub fn order_libraries<T: AsRef<[u8]>>(
+    libraries: Vec<(ZkLibrary, T)>,
+) -> Result<Vec<(ZkLibrary, T)>> {
+    let links: HashMap<_, Vec<_>> = libraries
+        .iter()
+        .map(|(library, code)| {
+            let linked = libraries
+                .iter()
+                .filter(|(other, _)| {
+                    other.address != library.address &&
+                        code.as_ref().windows(20).any(|word| word == other.address.as_slice())
+                })
+                .map(|(other, _)| other.identifier())
+                .collect();
+            (library.identifier(), linked)
+        })
+        .collect();
+    let order = dependency_order(
+        libraries.iter().map(|(library, _)| library.identifier()).collect(),
+        &links,
+    )?;
+    let mut libraries = libraries;
+    libraries.sort_by_key(|(library, _)| order.iter().position(|id| *id == library.identifier()));
+    Ok(libraries)
+}
+
+/// Writes the missing libraries `report` to `path`.
+pub fn write_missing_libraries(path: &Path, report: &[ZkMissingLibrary]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .wrap_err_with(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(report)?)
+        .wrap_err_with(|| format!("failed to write {}", path.display()))
+}
+
+/// Returns the libraries linked by every contract of the report, by identifier.
+fn linked_libraries(report: &[ZkMissingLibrary]) -> HashMap<String, Vec<String>> {
+    report
+        .iter()
+        .map(|contract| (contract.identifier(), contract.missing_libraries.clone()))
+        .collect()
+}
+
+/// Orders `ids` so that every one of them comes after the ones of `ids` it links in `links`,
+/// keeping their order otherwise.
+fn dependency_order(ids: Vec<String>, links: &HashMap<String, Vec<String>>) -> Result<Vec<String>> {
+    fn visit(
+        id: &str,
+        ids: &HashSet<&str>,
+        links: &HashMap<String, Vec<String>>,
+        visiting: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if order.iter().any(|ordered| ordered == id) {
+            return Ok(())
+        }
+        if let Some(start) = visiting.iter().position(|visited| visited == id) {
+            let mut cycle = visiting[start..].to_vec();
+            cycle.push(id.to_string());
+            eyre::bail!("libraries link each other in a cycle: {}", cycle.join(" -> "));
+        }
+        visiting.push(id.to_string());
+        for linked in
+            links.get(id).into_iter().flatten().filter(|linked| ids.contains(linked.as_str()))
+        {
+            visit(linked, ids, links, visiting, order)?;
+        }
+        visiting.pop();
+        order.push(id.to_string());
+        Ok(())
+    }
+
+    let id_set = ids.iter().map(String::as_str).collect::<HashSet<_>>();
+    let mut order = Vec::with_capacity(ids.len());
+    for id in &ids {
+        visit(id, &id_set, links, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+fn relative_path(path: &str, root: &Path) -> String {
+    let path = Path::new(path);
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().into_owned()
+}
+
+fn relative_identifier(identifier: &str, root: &Path) -> String {
+    match identifier.rsplit_once(':') {
+        Some((path, name)) => format!("{}:{name}", relative_path(path, root)),
+        None => identifier.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn missing(contract: &str, libraries: &[&str]) -> ZkMissingLibrary {
+        let (contract_path, contract_name) = contract.rsplit_once(':').unwrap();
+        ZkMissingLibrary {
+            contract_path: contract_path.to_string(),
+            contract_name: contract_name.to_string(),
+            missing_libraries: libraries.iter().map(|library| library.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn orders_libraries_after_the_ones_they_link() {
+        let report = [
+            missing("src/Counter.sol:Counter", &["src/Math.sol:Math", "src/Lib.sol:Lib"]),
+            missing("src/Math.sol:Math", &["src/Lib.sol:Lib"]),
+        ];
+        assert_eq!(
+            missing_libraries_deployment_order(&report).unwrap(),
+            vec!["src/Lib.sol:Lib", "src/Math.sol:Math"]
+        );
+
+        let library = |path: &str, name: &str, byte: u8| ZkLibrary {
+            path: PathBuf::from(path),
+            name: name.to_string(),
+            address: Address::repeat_byte(byte),
+        };
+        let lib = library("src/Lib.sol", "Lib", 0x11);
+        // the code of `Math` delegates to the address of `Lib`
+        let math_code = [&[0x73][..], lib.address.as_slice(), &[0xf4]].concat();
+        let ordered = order_libraries(vec![
+            (library("src/Other.sol", "Other", 0x33), vec![0x60, 0x80]),
+            (library("src/Math.sol", "Math", 0x22), math_code),
+            (lib, vec![0x60, 0x80]),
+        ])
+        .unwrap();
+        assert_eq!(
+            ordered.iter().map(|(library, _)| library.identifier()).collect::<Vec<_>>(),
+            vec!["src/Other.sol:Other", "src/Lib.sol:Lib", "src/Math.sol:Math"]
+        );
+    }
+
+    #[test]
+    fn fails_on_cycles() {
+        let report =
+            [missing("src/A.sol:A", &["src/B.sol:B"]), missing("src/B.sol:B", &["src/A.sol:A"])];
+        let err = missing_libraries_deployment_order(&report).unwrap_err();
+        assert!(err.to_string().contains("cycle"), "{err}");
+
+        let a = ZkLibrary {
+            path: "src/A.sol".into(),
+            name: "A".into(),
+            address: Address::repeat_byte(0xaa),
+        };
+        let b = ZkLibrary {
+            path: "src/B.sol".into(),
+            name: "B".into(),
+            address: Address::repeat_byte(0xbb),
+        };
+        let err = order_libraries(vec![(a.clone(), b.address.to_vec()), (b, a.address.to_vec())])
+            .unwrap_err();
+        assert!(err.to_string().contains("src/A.sol:A -> src/B.sol:B -> src/A.sol:A"), "{err}");
+    }
+
+    #[test]
+    fn parses_configured_libraries() {
+        let libraries = Libraries::parse(&[
+            "/project/src/Lib.sol:Lib:0x00000000000000000000000000000000000000aa".to_string(),
+        ])
+        .unwrap();
+        let configured = ZkLibrary::configured(&libraries, Path::new("/project")).unwrap();
+        assert_eq!(configured.len(), 1);
+        assert_eq!(configured[0].identifier(), "src/Lib.sol:Lib");
+        assert_eq!(configured[0].address, Address::with_last_byte(0xaa));
+    }
+}
@@ -24,20 +24,27 @@
 ///   includes functions to get the full compiler name, check if the compiler exists, setup the
 ///   compilers directory, and download the compiler if necessary.
 ///
+/// Compilers are installed to `~/.foundry/zksolc/`, and a downloaded binary is only installed
+/// if its SHA-256 checksum matches the one listed for it in the `zksolc-bin` repository.
+///
 /// This module abstracts the details of managing the zksolc compiler, making it easier for
 /// developers to use different versions of the compiler without dealing with the details of
 /// downloading, setting up, and switching between versions. It is part of a larger framework
 /// for managing and interacting with zkSync contracts.
 use anyhow::{anyhow, Context, Error, Result};
 use reqwest::Client;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{fmt, fs, os::unix::prelude::PermissionsExt, path::PathBuf};
-use tokio::{fs::File, io::copy};
 use url::Url;
 
 const ZKSOLC_DOWNLOAD_BASE_URL: &str =
     "https://github.com/matter-labs/zksolc-bin/releases/download/";
 
+/// The base URL of the build lists of the `zksolc` binaries, with their checksums.
+const ZKSOLC_BUILD_LIST_BASE_URL: &str =
+    "https://raw.githubusercontent.com/matter-labs/zksolc-bin/main/";
+
 /// `ZkSolcVersion` is an enumeration of the supported versions of the `zksolc` compiler.
 ///
 /// Each variant in this enum represents a specific version of the `zksolc` compiler:
@@ -77,14 +84,15 @@ pub const DEFAULT_ZKSOLC_VERSION: &str = "v1.4.0";
 ///
 /// # Arguments
 ///
-/// * `version`: A string slice of the `zksolc` version to parse.
+/// * `version`: A string slice of the `zksolc` version to parse, with or without the leading `v`,
+///   e.g. `v1.4.0` or `1.4.0`.
 ///
 /// # Returns
 ///
 /// A `Result` with the `ZkSolcVersion` variant for the parsed version, or an `Err`
 /// if the version isn't supported.
 fn parse_version(version: &str) -> Result<ZkSolcVersion> {
-    match version {
+    match format!("v{}", version.trim_start_matches('v')).as_str() {
         "v1.3.5" => Ok(ZkSolcVersion::V135),
         "v1.3.6" => Ok(ZkSolcVersion::V136),
         "v1.3.7" => Ok(ZkSolcVersion::V137),
@@ -201,6 +209,55 @@ impl ZkSolcOS {
             ZkSolcOS::MacARM => "macosx-arm64",
         }
     }
+
+    /// `get_build_list_dir` provides the directory of the `zksolc-bin` repository listing the
+    /// compiler binaries of the current operating system.
+    ///
+    /// # Returns
+    ///
+    /// A string representing the directory, depending on the operating system.
+    fn get_build_list_dir(&self) -> &str {
+        match self {
+            ZkSolcOS::Linux => "linux-amd64",
+            ZkSolcOS::MacAMD => "macosx-amd64",
+            ZkSolcOS::MacARM => "macosx-arm64",
+        }
+    }
+}
+
+/// The `list.json` of a platform directory of the `zksolc-bin` repository, listing the released
+/// compiler binaries of the platform.
+#[derive(Debug, Deserialize)]
+struct ZkSolcBuildList {
+    builds: Vec<ZkSolcBuild>,
+}
+
+/// A released compiler binary of a [ZkSolcBuildList].
+#[derive(Debug, Deserialize)]
+struct ZkSolcBuild {
+    /// The file name of the binary, e.g. `zksolc-linux-amd64-musl-v1.4.0`.
+    path: String,
+    /// The hex encoded SHA-256 checksum of the binary.
+    sha256: String,
+}
+
+impl ZkSolcBuildList {
+    /// Returns the SHA-256 checksum of the binary named `compiler`, if it's listed.
+    fn checksum(&self, compiler: &str) -> Option<&str> {
+        self.builds
+            .iter()
+            .find(|build| build.path == compiler)
+            .map(|build| build.sha256.trim_start_matches("0x"))
+    }
+}
+
+/// Checks that the SHA-256 checksum of the downloaded compiler binary `content` is `expected`.
+fn verify_checksum(content: &[u8], expected: &str) -> Result<()> {
+    let checksum = hex::encode(Sha256::digest(content));
+    if !checksum.eq_ignore_ascii_case(expected) {
+        return Err(anyhow!("checksum mismatch: expected {expected}, got {checksum}"))
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -311,7 +368,7 @@ impl ZkSolcManagerBuilder {
     /// the manager instance.
     ///
     /// The function performs the following steps:
-    /// 1. Obtains the home directory path and appends the `.zksync` directory to it, which
+    /// 1. Obtains the home directory path and appends the `.foundry/zksolc` directory to it, which
     ///    represents the compilers directory.
     /// 2. Parses the provided version string and verifies if it matches one of the supported
     ///    `ZkSolcVersion` variants.
@@ -336,7 +393,8 @@ impl ZkSolcManagerBuilder {
         // else at the end)
         let mut home_path =
             dirs::home_dir().ok_or(anyhow!("Could not build SolcManager - homedir not found"))?;
-        home_path.push(".zksync");
+        home_path.push(".foundry");
+        home_path.push("zksolc");
         let version = self.version.to_string();
         let download_url = self.download_url.to_owned();
         let compiler = self.get_compiler()?;
@@ -461,7 +519,7 @@ pub async fn setup_zksolc_manager(zksolc_version: String) -> eyre::Result<PathBu
             .unwrap_or_else(|_| "unknown URL".to_string());
 
         println!(
-            "zksolc not found in `.zksync` directory. Downloading zksolc compiler from {}",
+            "zksolc not found in `~/.foundry/zksolc`. Downloading zksolc compiler from {}",
             download_url
         );
         zksolc_manager.download().await.map_err(|err| {
@@ -555,6 +613,21 @@ impl ZkSolcManager {
             .map_err(|err| anyhow!("Could not parse URL for binary download: {}", err))
     }
 
+    /// Returns the URL of the list of the compiler binaries released for the current operating
+    /// system, with their checksums.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Err` if the operating system is not supported.
+    pub fn get_build_list_url(&self) -> Result<Url> {
+        let zk_solc_os = get_operating_system()
+            .map_err(|err| anyhow!("Failed to determine OS to select the binary: {}", err))?;
+
+        Url::parse(ZKSOLC_BUILD_LIST_BASE_URL)
+            .and_then(|url| url.join(&format!("{}/list.json", zk_solc_os.get_build_list_dir())))
+            .map_err(|err| anyhow!("Could not parse URL for build list: {}", err))
+    }
+
     pub fn get_full_compiler_path(&self) -> PathBuf {
         self.compilers_path.join(self.clone().get_full_compiler())
     }
@@ -610,11 +683,15 @@ impl ZkSolcManager {
     /// 1. Checks if the compiler binary already exists in the compilers directory using the
     ///    `exists` function.
     /// 2. If the binary exists, the function returns early without performing any download.
-    /// 3. If the binary doesn't exist, it sends a HTTP GET request to the download URL to retrieve
-    ///    the binary.
-    /// 4. If the download is successful, it creates the output file in the compilers directory and
-    ///    writes the binary data to it.
-    /// 5. Finally, it sets the appropriate permissions for the downloaded compiler binary.
+    /// 3. If the binary doesn't exist, it fetches the SHA-256 checksum of the binary from the build
+    ///    list of the current operating system.
+    /// 4. It sends a HTTP GET request to the download URL to retrieve the binary, and verifies its
+    ///    checksum.
+    /// 5. If the download is successful, it writes the binary data to a temporary file in the
+    ///    compilers directory, so that an interrupted download is never taken for an installed
+    ///    compiler.
+    /// 6. Finally, it sets the appropriate permissions for the downloaded compiler binary and moves
+    ///    it to the compiler path.
     ///
     /// # Returns
     ///
@@ -626,7 +703,9 @@ impl ZkSolcManager {
     /// This function can return an `Err` if any errors occur during the download or setup process,
     /// including:
     /// * If the download URL cannot be obtained using `get_full_download_url`.
+    /// * If the checksum of the binary cannot be fetched, or it isn't listed.
     /// * If the HTTP GET request to the download URL fails.
+    /// * If the checksum of the downloaded binary doesn't match.
     /// * If the output file cannot be created or written to.
     /// * If the permissions for the downloaded compiler binary cannot be set.
     pub async fn download(&self) -> Result<()> {
@@ -640,6 +719,7 @@ impl ZkSolcManager {
             .map_err(|e| Error::msg(format!("Could not get full download url: {}", e)))?;
 
         let client = Client::new();
+        let checksum = self.fetch_checksum(&client).await?;
         let response = client
             .get(url)
             .send()
@@ -647,23 +727,25 @@ impl ZkSolcManager {
             .map_err(|e| Error::msg(format!("Failed to download file: {}", e)))?;
 
         if response.status().is_success() {
-            let mut output_file = File::create(self.get_full_compiler_path())
-                .await
-                .map_err(|e| Error::msg(format!("Failed to create output file: {}", e)))?;
-
             let content = response
                 .bytes()
                 .await
                 .map_err(|e| Error::msg(format!("failed to download file: {}", e)))?;
 
-            copy(&mut content.as_ref(), &mut output_file)
+            verify_checksum(&content, &checksum)
+                .with_context(|| format!("Failed to verify {}", self.get_full_compiler()))?;
+
+            let download_path =
+                self.compilers_path.join(format!("{}.download", self.get_full_compiler()));
+            tokio::fs::write(&download_path, &content)
                 .await
                 .map_err(|e| Error::msg(format!("Failed to write the downloaded file: {}", e)))?;
 
-            let compiler_path = self.compilers_path.join(self.get_full_compiler());
-            fs::set_permissions(compiler_path, PermissionsExt::from_mode(0o755)).map_err(|e| {
+            fs::set_permissions(&download_path, PermissionsExt::from_mode(0o755)).map_err(|e| {
                 Error::msg(format!("Failed to set zksync compiler permissions: {e}"))
             })?;
+            fs::rename(&download_path, self.get_full_compiler_path())
+                .map_err(|e| Error::msg(format!("Failed to install the zksync compiler: {e}")))?;
         } else {
             return Err(Error::msg(format!(
                 "Failed to download file: status code {}",
@@ -672,4 +754,66 @@ impl ZkSolcManager {
         }
         Ok(())
     }
+
+    /// Fetches the SHA-256 checksum of the compiler binary from the build list of the current
+    /// operating system.
+    ///
+    /// # Errors
+    ///
+    /// This function can return an `Err` if the build list cannot be fetched or parsed, or if the
+    /// compiler binary isn't listed in it.
+    async fn fetch_checksum(&self, client: &Client) -> Result<String> {
+        let url = self.get_build_list_url()?;
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::msg(format!("Failed to fetch the build list {url}: {e}")))?;
+        let content = response
+            .bytes()
+            .await
+            .map_err(|e| Error::msg(format!("Failed to fetch the build list {url}: {e}")))?;
+        let list: ZkSolcBuildList = serde_json::from_slice(&content)
+            .map_err(|e| Error::msg(format!("Failed to parse the build list {url}: {e}")))?;
+
+        let compiler = self.get_full_compiler();
+        list.checksum(&compiler)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("{compiler} is not listed in the build list {url}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions_with_and_without_prefix() {
+        assert_eq!(parse_version("v1.4.0").unwrap().get_version(), "v1.4.0");
+        assert_eq!(parse_version("1.3.23").unwrap().get_version(), "v1.3.23");
+        assert!(parse_version("1.2.0").is_err());
+    }
+
+    #[test]
+    fn verifies_listed_checksums() {
+        let content = b"zksolc";
+        let checksum = hex::encode(Sha256::digest(content));
+        let list: ZkSolcBuildList = serde_json::from_value(serde_json::json!({
+            "builds": [
+                { "path": "zksolc-linux-amd64-gnu-v1.4.0", "version": "1.4.0", "sha256": "0x00" },
+                {
+                    "path": "zksolc-linux-amd64-musl-v1.4.0",
+                    "version": "1.4.0",
+                    "sha256": format!("0x{checksum}"),
+                },
+            ]
+        }))
+        .unwrap();
+
+        let listed = list.checksum("zksolc-linux-amd64-musl-v1.4.0").unwrap();
+        assert!(verify_checksum(content, listed).is_ok());
+        assert!(verify_checksum(b"tampered", listed).is_err());
+        assert!(list.checksum("zksolc-linux-amd64-musl-v1.3.23").is_none());
+    }
 }
@@ -1,20 +1,33 @@
 //! ZKSolc module.
 
+mod combined;
 mod compile;
 mod config;
 mod factory_deps;
+mod libraries;
 mod manager;
+mod overrides;
+mod skip;
+mod warnings;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+pub use combined::*;
 pub use compile::*;
 pub use config::*;
 pub use factory_deps::*;
 use foundry_compilers::{Artifact, ProjectCompileOutput};
+pub use libraries::*;
 pub use manager::*;
+pub use overrides::*;
+pub use skip::*;
+pub use warnings::*;
 
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::{Constructor, Param, StateMutability};
 use alloy_primitives::{keccak256, B256};
 use zksync_types::H256;
+use zksync_utils::bytecode::hash_bytecode;
 
 /// Defines a contract that has been dual compiled with both zksolc and solc
 #[derive(Debug, Default, Clone)]
@@ -31,69 +44,363 @@ pub struct DualCompiledContract {
     pub evm_deployed_bytecode: Vec<u8>,
     /// Bytecode with solc
     pub evm_bytecode: Vec<u8>,
+    /// Bytecodes with zksolc of the contracts the contract deploys, directly or through the
+    /// contracts it deploys
+    pub zk_factory_deps: Vec<Vec<u8>>,
+    /// Parameters of the constructor in the zksolc ABI, `None` if the ABI is unknown
+    pub constructor_inputs: Option<Vec<Param>>,
 }
 
-/// Creates a list of [DualCompiledContract]s from the provided solc and zksolc output.
-pub fn new_dual_compiled_contracts(
-    output: &ProjectCompileOutput,
-    zk_output: &ProjectCompileOutput,
-) -> Vec<DualCompiledContract> {
-    let mut dual_compiled_contracts = vec![];
-    let mut solc_bytecodes = HashMap::new();
-    for (contract_name, artifact) in output.artifacts() {
-        let contract_name =
-            contract_name.split('.').next().expect("name cannot be empty").to_string();
-        let deployed_bytecode = artifact.get_deployed_bytecode();
-        let deployed_bytecode = deployed_bytecode
-            .as_ref()
-            .and_then(|d| d.bytecode.as_ref().and_then(|b| b.object.as_bytes()));
-        let bytecode = artifact.get_bytecode().and_then(|b| b.object.as_bytes().cloned());
-        if let Some(bytecode) = bytecode {
+impl DualCompiledContract {
+    /// Returns the factory dependencies of a deployment of, or a call to, the contract on the
+    /// zkEVM: the bytecodes of the contracts it can deploy, and its own bytecode last.
+    pub fn factory_deps(&self) -> Vec<Vec<u8>> {
+        self.zk_factory_deps.iter().chain([&self.zk_deployed_bytecode]).cloned().collect()
+    }
+
+    /// Checks that `input`, the constructor input of a deployment of the contract on the zkEVM, is
+    /// exactly the ABI encoding of the arguments of its constructor.
+    ///
+    /// The input is passed to the constructor as the calldata of `ContractDeployer::create`, and
+    /// the immutables are set by the constructor itself, so unlike the init code of the EVM
+    /// nothing else may follow the arguments: a contract deployed with trailing, missing or
+    /// non-canonically encoded arguments would misparse them. The error describes the words
+    /// that differ from the encoding of the decoded arguments.
+    pub fn check_constructor_input(&self, input: &[u8]) -> eyre::Result<()> {
+        let Some(inputs) = &self.constructor_inputs else { return Ok(()) };
+        let signature = format!(
+            "constructor({})",
+            inputs.iter().map(|param| param.selector_type()).collect::<Vec<_>>().join(",")
+        );
+        if inputs.is_empty() {
+            eyre::ensure!(
+                input.is_empty(),
+                "{} has no constructor arguments, but its deployment passes {} bytes of \
+                 constructor input",
+                self.name,
+                input.len()
+            );
+            return Ok(())
+        }
+
+        let constructor =
+            Constructor { inputs: inputs.clone(), state_mutability: StateMutability::NonPayable };
+        let args = constructor.abi_decode_input(input, false).map_err(|err| {
+            eyre::eyre!(
+                "the constructor input of {} ({} bytes) doesn't decode as the arguments of \
+                 `{signature}`: {err}",
+                self.name,
+                input.len()
+            )
+        })?;
+        let expected = constructor.abi_encode_input(&args)?;
+        if expected == input {
+            return Ok(())
+        }
+
+        let word = |data: &[u8], i: usize| {
+            data.get(i * 32..).filter(|word| !word.is_empty()).map_or_else(
+                || "<none>".to_string(),
+                |word| format!("0x{}", hex::encode(&word[..word.len().min(32)])),
+            )
+        };
+        let words = expected.len().max(input.len()).div_ceil(32);
+        let diff = (0..words)
+            .map(|i| (i, word(&expected, i), word(input, i)))
+            .filter(|(_, expected, actual)| expected != actual)
+            .map(|(i, expected, actual)| format!("\n  word {i}: expected {expected}, got {actual}"))
+            .collect::<String>();
+        eyre::bail!(
+            "the constructor input of {} isn't the ABI encoding of the arguments of `{signature}`: \
+             expected {} bytes, got {}{diff}",
+            self.name,
+            expected.len(),
+            input.len()
+        )
+    }
+}
+
+/// The registry of the contracts compiled with both zksolc and solc, to find the EraVM
+/// counterpart of EVM code and vice versa.
+#[derive(Debug, Default, Clone)]
+pub struct DualCompiledContracts {
+    contracts: Vec<DualCompiledContract>,
+}
+
+impl DualCompiledContracts {
+    /// Creates the registry of the contracts of the solc output with a zksolc counterpart of the
+    /// same name.
+    ///
+    /// Contracts with the same bytecodes, e.g. compiled from several sources, are registered once,
+    /// see [`Self::insert`].
+    pub fn new(output: &ProjectCompileOutput, zk_output: &ProjectCompileOutput) -> Self {
+        let mut dual_compiled_contracts = Self::default();
+        let mut solc_bytecodes = HashMap::new();
+        for (contract_name, artifact) in output.artifacts() {
+            let contract_name =
+                contract_name.split('.').next().expect("name cannot be empty").to_string();
+            let deployed_bytecode = artifact.get_deployed_bytecode();
+            let deployed_bytecode = deployed_bytecode
+                .as_ref()
+                .and_then(|d| d.bytecode.as_ref().and_then(|b| b.object.as_bytes()));
+            let bytecode = artifact.get_bytecode().and_then(|b| b.object.as_bytes().cloned());
+            if let Some(bytecode) = bytecode {
+                if let Some(deployed_bytecode) = deployed_bytecode {
+                    solc_bytecodes
+                        .insert(contract_name.clone(), (bytecode, deployed_bytecode.clone()));
+                }
+            }
+        }
+        let mut zk_bytecodes = Vec::new();
+        for (contract_name, artifact) in zk_output.artifacts() {
+            let deployed_bytecode = artifact.get_deployed_bytecode();
+            let deployed_bytecode = deployed_bytecode
+                .as_ref()
+                .and_then(|d| d.bytecode.as_ref().and_then(|b| b.object.as_bytes()));
             if let Some(deployed_bytecode) = deployed_bytecode {
-                solc_bytecodes.insert(contract_name.clone(), (bytecode, deployed_bytecode.clone()));
+                let constructor_inputs = artifact.get_abi().map(|abi| {
+                    abi.constructor.as_ref().map(|c| c.inputs.clone()).unwrap_or_default()
+                });
+                zk_bytecodes.push((
+                    contract_name,
+                    PackedEraBytecode::from_vec(deployed_bytecode),
+                    constructor_inputs,
+                ));
             }
         }
-    }
-    for (contract_name, artifact) in zk_output.artifacts() {
-        let deployed_bytecode = artifact.get_deployed_bytecode();
-        let deployed_bytecode = deployed_bytecode
-            .as_ref()
-            .and_then(|d| d.bytecode.as_ref().and_then(|b| b.object.as_bytes()));
-        if let Some(deployed_bytecode) = deployed_bytecode {
-            let packed_bytecode = PackedEraBytecode::from_vec(deployed_bytecode);
+        // the direct factory deps of every zksolc contract, to resolve the nested ones
+        let direct_factory_deps = zk_bytecodes
+            .iter()
+            .map(|(_, packed_bytecode, _)| {
+                (packed_bytecode.bytecode_hash(), packed_bytecode.dependencies())
+            })
+            .collect::<HashMap<_, _>>();
+        for (contract_name, packed_bytecode, constructor_inputs) in zk_bytecodes {
             if let Some((solc_bytecode, solc_deployed_bytecode)) =
                 solc_bytecodes.get(&contract_name)
             {
-                dual_compiled_contracts.push(DualCompiledContract {
+                dual_compiled_contracts.insert(DualCompiledContract {
                     name: contract_name,
                     zk_bytecode_hash: packed_bytecode.bytecode_hash(),
                     zk_deployed_bytecode: packed_bytecode.bytecode(),
                     evm_bytecode_hash: keccak256(solc_deployed_bytecode),
                     evm_bytecode: solc_bytecode.to_vec(),
                     evm_deployed_bytecode: solc_deployed_bytecode.to_vec(),
+                    zk_factory_deps: transitive_factory_deps(
+                        &direct_factory_deps,
+                        packed_bytecode.dependencies(),
+                    ),
+                    constructor_inputs,
                 });
             }
         }
+
+        dual_compiled_contracts
+    }
+
+    /// Finds the contract whose solc creation code `bytecode` starts with, i.e. which `bytecode`
+    /// deploys with its constructor arguments appended.
+    ///
+    /// Contracts registered without creation code, like the genesis contracts, are never matched.
+    pub fn find_by_evm_bytecode(&self, bytecode: &[u8]) -> Option<&DualCompiledContract> {
+        self.contracts.iter().find(|contract| {
+            !contract.evm_bytecode.is_empty() && bytecode.starts_with(&contract.evm_bytecode)
+        })
+    }
+
+    /// Finds the contract with the given hash of its solc deployed code.
+    pub fn find_by_evm_hash(&self, code_hash: B256) -> Option<&DualCompiledContract> {
+        self.contracts.iter().find(|contract| code_hash == contract.evm_bytecode_hash)
+    }
+
+    /// Finds the contract with the given hash of its zksolc bytecode.
+    pub fn find_by_zk_hash(&self, code_hash: H256) -> Option<&DualCompiledContract> {
+        self.contracts.iter().find(|contract| code_hash == contract.zk_bytecode_hash)
     }
 
-    dual_compiled_contracts
+    /// Finds the contract with the given hashes of its solc deployed code and zksolc bytecode.
+    pub fn find_by_hashes(
+        &self,
+        evm_bytecode_hash: B256,
+        zk_bytecode_hash: H256,
+    ) -> Option<&DualCompiledContract> {
+        self.contracts.iter().find(|contract| {
+            contract.evm_bytecode_hash == evm_bytecode_hash &&
+                contract.zk_bytecode_hash == zk_bytecode_hash
+        })
+    }
+
+    /// Registers a contract, unless one with the same bytecode hashes already is.
+    ///
+    /// Returns whether the contract was registered.
+    pub fn insert(&mut self, contract: DualCompiledContract) -> bool {
+        if self.find_by_hashes(contract.evm_bytecode_hash, contract.zk_bytecode_hash).is_some() {
+            return false
+        }
+        self.contracts.push(contract);
+        true
+    }
+
+    /// Returns an iterator over the registered contracts.
+    pub fn iter(&self) -> std::slice::Iter<'_, DualCompiledContract> {
+        self.contracts.iter()
+    }
+
+    /// Returns the number of registered contracts.
+    pub fn len(&self) -> usize {
+        self.contracts.len()
+    }
+
+    /// Returns whether no contract is registered.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+}
+
+impl Extend<DualCompiledContract> for DualCompiledContracts {
+    fn extend<I: IntoIterator<Item = DualCompiledContract>>(&mut self, contracts: I) {
+        for contract in contracts {
+            self.insert(contract);
+        }
+    }
 }
 
-/// Implements methods to look for contracts
-pub trait FindContract {
-    /// Finds a contract matching the EVM bytecode
-    fn find_evm_bytecode(&self, bytecode: &[u8]) -> Option<&DualCompiledContract>;
+impl FromIterator<DualCompiledContract> for DualCompiledContracts {
+    fn from_iter<I: IntoIterator<Item = DualCompiledContract>>(contracts: I) -> Self {
+        let mut dual_compiled_contracts = Self::default();
+        dual_compiled_contracts.extend(contracts);
+        dual_compiled_contracts
+    }
+}
+
+impl<'a> IntoIterator for &'a DualCompiledContracts {
+    type Item = &'a DualCompiledContract;
+    type IntoIter = std::slice::Iter<'a, DualCompiledContract>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for DualCompiledContracts {
+    type Item = DualCompiledContract;
+    type IntoIter = std::vec::IntoIter<DualCompiledContract>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contracts.into_iter()
+    }
+}
 
-    /// Finds a contract matching the ZK bytecode hash
-    fn find_zk_bytecode_hash(&self, code_hash: H256) -> Option<&DualCompiledContract>;
+/// Returns the bytecodes `deps`, and the ones of the contracts they deploy in turn, deduplicated.
+fn transitive_factory_deps(
+    direct_factory_deps: &HashMap<H256, Vec<Vec<u8>>>,
+    deps: Vec<Vec<u8>>,
+) -> Vec<Vec<u8>> {
+    let mut seen = HashSet::new();
+    let mut resolved = Vec::new();
+    let mut pending = deps;
+    while let Some(dep) = pending.pop() {
+        let hash = hash_bytecode(&dep);
+        if !seen.insert(hash) {
+            continue
+        }
+        if let Some(nested) = direct_factory_deps.get(&hash) {
+            pending.extend(nested.iter().cloned());
+        }
+        resolved.push(dep);
+    }
+    resolved
 }
 
-impl FindContract for Vec<DualCompiledContract> {
-    fn find_evm_bytecode(&self, bytecode: &[u8]) -> Option<&DualCompiledContract> {
-        self.iter().find(|contract| bytecode.starts_with(&contract.evm_bytecode))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_dual_compiled_contracts() {
+        let contract = DualCompiledContract {
+            name: "Counter".to_string(),
+            zk_bytecode_hash: H256::repeat_byte(1),
+            evm_bytecode_hash: B256::repeat_byte(2),
+            evm_bytecode: vec![0x60, 0x80],
+            ..Default::default()
+        };
+        let mut contracts = DualCompiledContracts::default();
+        assert!(contracts.insert(contract.clone()));
+        assert!(!contracts.insert(DualCompiledContract { name: "Copy".to_string(), ..contract }));
+        assert_eq!(contracts.len(), 1);
+
+        let found = |contract: Option<&DualCompiledContract>| contract.map(|c| c.name.as_str());
+        assert_eq!(found(contracts.find_by_zk_hash(H256::repeat_byte(1))), Some("Counter"));
+        assert_eq!(found(contracts.find_by_evm_hash(B256::repeat_byte(2))), Some("Counter"));
+        assert_eq!(found(contracts.find_by_evm_bytecode(&[0x60, 0x80, 0x01])), Some("Counter"));
+        assert_eq!(found(contracts.find_by_evm_bytecode(&[0x60])), None);
+
+        // a contract without creation code doesn't match every bytecode
+        let mut contracts = DualCompiledContracts::default();
+        contracts.insert(DualCompiledContract {
+            name: "genesis".to_string(),
+            zk_bytecode_hash: H256::repeat_byte(3),
+            evm_bytecode_hash: B256::repeat_byte(4),
+            ..Default::default()
+        });
+        assert_eq!(found(contracts.find_by_evm_bytecode(&[0x60, 0x80])), None);
     }
 
-    fn find_zk_bytecode_hash(&self, code_hash: H256) -> Option<&DualCompiledContract> {
-        self.iter().find(|contract| code_hash == contract.zk_bytecode_hash)
+    #[test]
+    fn resolves_nested_factory_deps() {
+        let (factory, child, grandchild) = (vec![1; 32], vec![2; 32], vec![3; 32]);
+        let direct_factory_deps = HashMap::from([
+            (hash_bytecode(&factory), vec![child.clone()]),
+            (hash_bytecode(&child), vec![grandchild.clone(), grandchild.clone()]),
+        ]);
+
+        let mut deps = transitive_factory_deps(&direct_factory_deps, vec![child.clone()]);
+        deps.sort();
+        assert_eq!(deps, vec![child.clone(), grandchild.clone()]);
+
+        let contract = DualCompiledContract {
+            zk_deployed_bytecode: factory.clone(),
+            zk_factory_deps: deps,
+            ..Default::default()
+        };
+        assert_eq!(contract.factory_deps(), vec![child, grandchild, factory]);
+    }
+
+    #[test]
+    fn checks_constructor_input() {
+        let param = |ty: &str| Param {
+            ty: ty.to_string(),
+            name: String::new(),
+            components: vec![],
+            internal_type: None,
+        };
+        let contract = DualCompiledContract {
+            name: "Custom".to_string(),
+            constructor_inputs: Some(vec![param("uint8"), param("string")]),
+            ..Default::default()
+        };
+        let mut input = [[0u8; 32]; 4];
+        input[0][31] = 20;
+        input[1][31] = 0x40;
+        input[2][31] = 2;
+        input[3][..2].copy_from_slice(b"hi");
+        let input = input.concat();
+        contract.check_constructor_input(&input).unwrap();
+
+        let trailing = [input.as_slice(), &[1; 32]].concat();
+        let err = contract.check_constructor_input(&trailing).unwrap_err().to_string();
+        assert!(err.contains("`constructor(uint8,string)`: expected 128 bytes, got 160"), "{err}");
+        assert!(err.contains(&format!("word 4: expected <none>, got 0x{}", "01".repeat(32))));
+
+        let err = contract.check_constructor_input(&input[..64]).unwrap_err().to_string();
+        assert!(err.contains("(64 bytes) doesn't decode as the arguments"), "{err}");
+
+        let no_args = DualCompiledContract { constructor_inputs: Some(vec![]), ..contract.clone() };
+        no_args.check_constructor_input(&[]).unwrap();
+        assert!(no_args.check_constructor_input(&input).is_err());
+
+        let unknown = DualCompiledContract { constructor_inputs: None, ..contract };
+        unknown.check_constructor_input(&[1, 2, 3]).unwrap();
     }
 }
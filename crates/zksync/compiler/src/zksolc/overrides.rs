@@ -0,0 +1,122 @@
+//! Per-contract zksolc compilation overrides.
+//!
+//! zksolc applies its optimizer settings to a whole compiler input, but a project may need a
+//! single contract compiled differently than the rest, e.g. optimized for size to fit the
+//! bytecode limit. A [ZkContractOverride] changes the optimizer settings of one contract, which is
+//! then compiled by a separate zksolc invocation whose artifact replaces the one of the profile.
+
+use crate::zksolc::config::Optimizer;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// A `zk_overrides` entry, the optimizer settings of a single contract, replacing the ones of the
+/// profile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZkContractOverride {
+    /// Whether the optimizer is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimizer: Option<bool>,
+    /// The optimization mode string, e.g. `"z"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimizer_mode: Option<String>,
+    /// Whether to try to recompile with -Oz if the bytecode is too large.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_oz: Option<bool>,
+}
+
+impl ZkContractOverride {
+    /// Returns the `optimizer` settings with the ones set by the override replaced.
+    pub fn apply(&self, optimizer: &Optimizer) -> Optimizer {
+        let mut optimizer = optimizer.clone();
+        if let Some(enabled) = self.optimizer {
+            optimizer.enabled = Some(enabled);
+        }
+        if let Some(mode) = &self.optimizer_mode {
+            optimizer.mode = Some(mode.clone());
+        }
+        if let Some(fallback_oz) = self.fallback_oz {
+            optimizer.fallback_to_optimizing_for_size = Some(fallback_oz);
+        }
+        optimizer
+    }
+}
+
+/// The overrides of the contracts, by source path relative to the project root and contract name.
+#[derive(Clone, Debug, Default)]
+pub struct ZkContractOverrides(BTreeMap<(String, String), ZkContractOverride>);
+
+impl ZkContractOverrides {
+    /// Parses the `zk_overrides` config, keyed by `<path>:<contract>` identifiers.
+    pub fn new(overrides: &BTreeMap<String, ZkContractOverride>) -> Result<Self, String> {
+        overrides
+            .iter()
+            .map(|(id, entry)| {
+                let (path, name) = id
+                    .rsplit_once(':')
+                    .filter(|(path, name)| !path.is_empty() && !name.is_empty())
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid zk_overrides contract {id:?}, expected `<path>:<contract>`"
+                        )
+                    })?;
+                Ok(((path.trim_start_matches("./").to_string(), name.to_string()), entry.clone()))
+            })
+            .collect::<Result<_, String>>()
+            .map(Self)
+    }
+
+    /// Returns whether there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the overridden contracts of the source at `relative_path`, with their overrides.
+    pub fn of_source<'a>(
+        &'a self,
+        relative_path: &'a Path,
+    ) -> impl Iterator<Item = (&'a str, &'a ZkContractOverride)> + 'a {
+        self.0
+            .iter()
+            .filter(move |((path, _), _)| Path::new(path) == relative_path)
+            .map(|((_, name), entry)| (name.as_str(), entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_the_optimizer_of_contracts() {
+        let hot =
+            ZkContractOverride { optimizer_mode: Some("z".to_string()), ..Default::default() };
+        let cold = ZkContractOverride { optimizer: Some(false), ..Default::default() };
+        let overrides = ZkContractOverrides::new(&BTreeMap::from([
+            ("./src/Hot.sol:Hot".to_string(), hot.clone()),
+            ("src/Hot.sol:Cold".to_string(), cold.clone()),
+            ("src/Other.sol:Other".to_string(), Default::default()),
+        ]))
+        .unwrap();
+
+        let contracts = overrides.of_source(Path::new("src/Hot.sol")).collect::<Vec<_>>();
+        assert_eq!(contracts, vec![("Cold", &cold), ("Hot", &hot)]);
+        assert_eq!(overrides.of_source(Path::new("src/Cold.sol")).count(), 0);
+
+        let optimizer = Optimizer {
+            enabled: Some(true),
+            mode: Some("3".to_string()),
+            fallback_to_optimizing_for_size: Some(false),
+            ..Default::default()
+        };
+        let optimizer = hot.apply(&optimizer);
+        assert_eq!(optimizer.enabled, Some(true));
+        assert_eq!(optimizer.mode.as_deref(), Some("z"));
+        assert_eq!(optimizer.fallback_to_optimizing_for_size, Some(false));
+
+        for id in ["src/Hot.sol", "src/Hot.sol:", ":Hot"] {
+            let overrides = BTreeMap::from([(id.to_string(), ZkContractOverride::default())]);
+            assert!(ZkContractOverrides::new(&overrides).is_err());
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Detection of sources that can't be compiled with zksolc.
+//!
+//! Test-only contracts commonly use instructions EraVM has no equivalent for. Instead of failing
+//! the whole zksolc build, such sources are skipped and reported, while remaining available to
+//! EVM-only tests through the regular solc build.
+//!
+//! A source is compiled with the sources it imports, so the sources importing a skipped one,
+//! directly or through other imports, are skipped too.
+
+use globset::GlobMatcher;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Instructions zksolc rejects at compile time, with whether they are only matched as a
+/// call without arguments.
+const UNSUPPORTED_INSTRUCTIONS: &[(&str, bool)] =
+    &[("selfdestruct", false), ("callcode", false), ("extcodecopy", false), ("pc", true)];
+
+/// A source that was excluded from zksolc compilation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedSource {
+    /// The path of the source, relative to the project root.
+    pub path: PathBuf,
+    /// Why the source was skipped.
+    pub reason: SkipReason,
+}
+
+impl fmt::Display for SkippedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.path.display(), self.reason)
+    }
+}
+
+/// The reason a source was excluded from zksolc compilation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The source matches a `zk_skip` pattern.
+    Pattern(String),
+    /// The source imports, directly or transitively, a source matching a `zk_skip` pattern.
+    Imports {
+        /// The imported source matching the pattern.
+        source: PathBuf,
+        /// The matched pattern.
+        pattern: String,
+    },
+    /// The source, or one of the sources it imports, uses an instruction zksolc doesn't support.
+    Unsupported {
+        /// The source using the instruction.
+        source: PathBuf,
+        /// The unsupported instruction.
+        instruction: &'static str,
+    },
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pattern(pattern) => write!(f, "matches zk_skip pattern `{pattern}`"),
+            Self::Imports { source, pattern } => {
+                write!(f, "imports {}, which matches zk_skip pattern `{pattern}`", source.display())
+            }
+            Self::Unsupported { source, instruction } => {
+                write!(f, "`{instruction}` is not supported, used in {}", source.display())
+            }
+        }
+    }
+}
+
+/// Returns why the source at `path` can't be compiled with zksolc, if it can't.
+///
+/// `sources` are the sources of its compiler input: itself and the ones it imports, directly or
+/// transitively, with their paths relative to the project root. The source is skipped if it or
+/// one of them matches a pattern of `skip`, or uses an unsupported instruction. `unsupported`
+/// caches the detection result of every source seen so far, as sources are shared between the
+/// inputs of all files importing them.
+pub fn skip_reason<'a>(
+    path: &Path,
+    sources: impl IntoIterator<Item = (&'a Path, &'a str)>,
+    skip: &[GlobMatcher],
+    unsupported: &mut HashMap<PathBuf, Option<&'static str>>,
+) -> Option<SkipReason> {
+    let matching =
+        |path: &Path| skip.iter().find(|m| m.is_match(path)).map(|m| m.glob().to_string());
+    if let Some(pattern) = matching(path) {
+        return Some(SkipReason::Pattern(pattern))
+    }
+    let mut reason = None;
+    for (source, content) in sources {
+        if let Some(pattern) = matching(source) {
+            return Some(SkipReason::Imports { source: source.to_path_buf(), pattern })
+        }
+        if reason.is_none() {
+            let instruction = *unsupported
+                .entry(source.to_path_buf())
+                .or_insert_with(|| find_unsupported_instruction(content));
+            reason = instruction.map(|instruction| SkipReason::Unsupported {
+                source: source.to_path_buf(),
+                instruction,
+            });
+        }
+    }
+    reason
+}
+
+/// Returns the first instruction used in `source` that zksolc doesn't support, if any.
+///
+/// This is a lexical check, comments and string literals are ignored.
+pub fn find_unsupported_instruction(source: &str) -> Option<&'static str> {
+    let code = strip_comments_and_strings(source);
+    let bytes = code.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if !is_ident(bytes[i]) {
+            i += 1;
+            continue
+        }
+        let start = i;
+        while i < bytes.len() && is_ident(bytes[i]) {
+            i += 1;
+        }
+        let ident = &code[start..i];
+        let Some(&(instruction, no_args)) =
+            UNSUPPORTED_INSTRUCTIONS.iter().find(|(name, _)| *name == ident)
+        else {
+            continue
+        };
+        let rest = code[i..].trim_start();
+        let Some(args) = rest.strip_prefix('(') else { continue };
+        if !no_args || args.trim_start().starts_with(')') {
+            return Some(instruction)
+        }
+    }
+    None
+}
+
+/// Replaces comments and string literals with whitespace, keeping everything else intact.
+fn strip_comments_and_strings(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = None;
+                for c in chars.by_ref() {
+                    if prev == Some('*') && c == '/' {
+                        break
+                    }
+                    prev = Some(c);
+                }
+                out.push(' ');
+            }
+            '"' | '\'' => {
+                let mut escaped = false;
+                for ch in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                    } else if ch == '\\' {
+                        escaped = true;
+                    } else if ch == c {
+                        break
+                    }
+                }
+                out.push(' ');
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_unsupported_instructions() {
+        assert_eq!(
+            find_unsupported_instruction("function kill() public { selfdestruct(payable(a)); }"),
+            Some("selfdestruct")
+        );
+        assert_eq!(
+            find_unsupported_instruction("assembly { let p := pc() extcodecopy(a, 0, 0, 32) }"),
+            Some("pc")
+        );
+        assert_eq!(
+            find_unsupported_instruction("assembly { extcodecopy(a, 0, 0, 32) }"),
+            Some("extcodecopy")
+        );
+        assert_eq!(find_unsupported_instruction("function pc(uint256 x) public {} pc(1);"), None);
+        assert_eq!(find_unsupported_instruction("uint256 selfdestructs; mySelfdestruct();"), None);
+    }
+
+    #[test]
+    fn ignores_comments_and_strings() {
+        let source = r#"
+            // selfdestruct(addr);
+            /* callcode(g, a, v, in, insize, out, outsize) */
+            string constant DOC = "uses selfdestruct() internally";
+            string constant ESCAPED = "\"callcode(\"";
+        "#;
+        assert_eq!(find_unsupported_instruction(source), None);
+    }
+
+    #[test]
+    fn skips_importers_of_skipped_sources() {
+        let skip = [globset::Glob::new("test/mocks/**").unwrap().compile_matcher()];
+        let mut unsupported = HashMap::new();
+        let mock = (Path::new("test/mocks/Mock.sol"), "contract Mock {}");
+        let helper = (Path::new("test/Helper.sol"), "import './mocks/Mock.sol';");
+        let test = (Path::new("test/Counter.t.sol"), "import './Helper.sol';");
+        let killer = (
+            Path::new("src/Killer.sol"),
+            "contract K { function k() public { selfdestruct(payable(0)); } }",
+        );
+
+        assert_eq!(
+            skip_reason(mock.0, [mock], &skip, &mut unsupported),
+            Some(SkipReason::Pattern("test/mocks/**".to_string()))
+        );
+        // the test imports the mock through the helper
+        let reason = skip_reason(test.0, [test, helper, mock], &skip, &mut unsupported).unwrap();
+        assert_eq!(
+            reason,
+            SkipReason::Imports {
+                source: PathBuf::from("test/mocks/Mock.sol"),
+                pattern: "test/mocks/**".to_string()
+            }
+        );
+        assert_eq!(
+            reason.to_string(),
+            "imports test/mocks/Mock.sol, which matches zk_skip pattern `test/mocks/**`"
+        );
+        // a skipped import is reported over an unsupported instruction
+        assert!(matches!(
+            skip_reason(test.0, [killer, test, mock], &skip, &mut unsupported),
+            Some(SkipReason::Imports { .. })
+        ));
+        assert_eq!(
+            skip_reason(test.0, [test, killer], &skip, &mut unsupported),
+            Some(SkipReason::Unsupported {
+                source: PathBuf::from("src/Killer.sol"),
+                instruction: "selfdestruct"
+            })
+        );
+        assert_eq!(skip_reason(helper.0, [helper], &skip, &mut unsupported), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Promotion of zksolc warnings to errors.
+//!
+//! Some warnings zksolc emits are specific to EraVM, e.g. the use of instructions that are
+//! replaced by stubs, and a project may want them to fail its build in CI while still allowing
+//! benign warnings. A [ZkWarningsPolicy] denies warnings by error code or message, allows some of
+//! them back, and can be overridden for the sources matching a glob.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A `zk_warnings_overrides` entry, the denied and allowed warnings of the sources matching
+/// `paths`, replacing the ones of the profile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkWarningsOverride {
+    /// The glob pattern of the sources the override applies to, relative to the project root.
+    pub paths: String,
+    /// The warnings promoted to errors.
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// The warnings never promoted to errors.
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+/// Which zksolc warnings fail the compilation.
+///
+/// A rule matches a warning with that error code, or whose message the rule matches as a regex,
+/// so `".*"` matches all warnings. A warning is denied if it matches a deny rule and no allow
+/// rule. The rules of the last override matching the source of a warning replace the ones of the
+/// policy.
+#[derive(Clone, Debug, Default)]
+pub struct ZkWarningsPolicy {
+    rules: WarningRules,
+    overrides: Vec<(globset::GlobMatcher, WarningRules)>,
+}
+
+impl ZkWarningsPolicy {
+    /// Compiles the policy from the `zk_deny_warnings`, `zk_allow_warnings` and
+    /// `zk_warnings_overrides` config.
+    pub fn new(
+        deny: &[String],
+        allow: &[String],
+        overrides: &[ZkWarningsOverride],
+    ) -> Result<Self, String> {
+        let rules = WarningRules::new(deny, allow)?;
+        let overrides = overrides
+            .iter()
+            .map(|entry| {
+                let glob = globset::Glob::new(&entry.paths)
+                    .map_err(|err| format!("invalid zk_warnings_overrides paths: {err}"))?;
+                Ok((glob.compile_matcher(), WarningRules::new(&entry.deny, &entry.allow)?))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules, overrides })
+    }
+
+    /// Returns whether the warning with `code` and `message`, in the source at `file`, fails the
+    /// compilation. `file` is stripped of the project `root` before matching the overrides.
+    pub fn is_denied(
+        &self,
+        root: &Path,
+        file: Option<&str>,
+        code: Option<&str>,
+        message: &str,
+    ) -> bool {
+        let file = file.map(|file| Path::new(file).strip_prefix(root).unwrap_or(Path::new(file)));
+        let rules = file
+            .and_then(|file| {
+                self.overrides.iter().rev().find(|(glob, _)| glob.is_match(file)).map(|(_, r)| r)
+            })
+            .unwrap_or(&self.rules);
+        rules.is_denied(code, message)
+    }
+}
+
+/// The deny and allow rules of a [ZkWarningsPolicy].
+#[derive(Clone, Debug, Default)]
+struct WarningRules {
+    deny: Vec<WarningRule>,
+    allow: Vec<WarningRule>,
+}
+
+impl WarningRules {
+    fn new(deny: &[String], allow: &[String]) -> Result<Self, String> {
+        let rules = |rules: &[String]| -> Result<Vec<_>, String> {
+            rules.iter().map(|rule| WarningRule::new(rule)).collect()
+        };
+        Ok(Self { deny: rules(deny)?, allow: rules(allow)? })
+    }
+
+    fn is_denied(&self, code: Option<&str>, message: &str) -> bool {
+        self.deny.iter().any(|rule| rule.matches(code, message)) &&
+            !self.allow.iter().any(|rule| rule.matches(code, message))
+    }
+}
+
+/// A rule matching warnings by error code or message.
+#[derive(Clone, Debug)]
+struct WarningRule {
+    rule: String,
+    message: Regex,
+}
+
+impl WarningRule {
+    fn new(rule: &str) -> Result<Self, String> {
+        let message = Regex::new(rule)
+            .map_err(|err| format!("invalid zksolc warning rule {rule:?}: {err}"))?;
+        Ok(Self { rule: rule.to_string(), message })
+    }
+
+    fn matches(&self, code: Option<&str>, message: &str) -> bool {
+        code == Some(self.rule.as_str()) || self.message.is_match(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn denies_warnings_by_code_and_message() {
+        let policy = ZkWarningsPolicy::new(
+            &["5667".to_string(), "(?i)ecrecover".to_string()],
+            &["unused".to_string()],
+            &[],
+        )
+        .unwrap();
+        let root = Path::new("/project");
+        let file = Some("/project/src/Counter.sol");
+
+        assert!(policy.is_denied(root, file, Some("5667"), "Unused function parameter."));
+        assert!(policy.is_denied(root, file, None, "It looks like you are using 'ecrecover'"));
+        assert!(!policy.is_denied(root, file, Some("5667"), "unused variable"));
+        assert!(!policy.is_denied(root, file, Some("2072"), "Unused local variable."));
+        assert!(!ZkWarningsPolicy::default().is_denied(root, file, Some("5667"), ""));
+    }
+
+    #[test]
+    fn applies_the_last_matching_override() {
+        let overrides = vec![
+            ZkWarningsOverride { paths: "test/**".to_string(), deny: vec![], allow: vec![] },
+            ZkWarningsOverride {
+                paths: "test/era/**".to_string(),
+                deny: vec!["txorigin".to_string()],
+                allow: vec![],
+            },
+        ];
+        let policy = ZkWarningsPolicy::new(&[".*".to_string()], &[], &overrides).unwrap();
+        let root = Path::new("/project");
+        let message = "You are checking for 'tx.origin', see txorigin";
+
+        assert!(policy.is_denied(root, Some("/project/src/Counter.sol"), None, message));
+        assert!(!policy.is_denied(root, Some("/project/test/Counter.t.sol"), None, message));
+        assert!(!policy.is_denied(root, Some("/project/test/era/Era.t.sol"), None, "other"));
+        assert!(policy.is_denied(root, Some("test/era/Era.t.sol"), None, message));
+        assert!(policy.is_denied(root, None, None, message));
+        assert!(ZkWarningsPolicy::new(&["(".to_string()], &[], &[]).is_err());
+    }
+}
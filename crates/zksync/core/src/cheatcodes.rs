@@ -12,10 +12,15 @@ use zksync_types::{
     utils::storage_key_for_eth_balance,
     ACCOUNT_CODE_STORAGE_ADDRESS, CURRENT_VIRTUAL_BLOCK_INFO_POSITION, KNOWN_CODES_STORAGE_ADDRESS,
     L2_ETH_TOKEN_ADDRESS, NONCE_HOLDER_ADDRESS, SYSTEM_CONTEXT_ADDRESS,
+    SYSTEM_CONTEXT_BLOCK_INFO_POSITION, SYSTEM_CONTEXT_CURRENT_L2_BLOCK_HASHES_POSITION,
+    SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION, SYSTEM_CONTEXT_STORED_L2_BLOCK_HASHES,
 };
 use zksync_utils::bytecode::hash_bytecode;
 
-use crate::convert::{ConvertAddress, ConvertH160, ConvertH256, ConvertRU256, ConvertU256};
+use crate::{
+    convert::{ConvertAddress, ConvertH160, ConvertH256, ConvertRU256, ConvertU256},
+    era_state::{EraState, EraStorageKey},
+};
 
 /// Sets `block.timestamp`.
 pub fn warp<'a, DB>(
@@ -71,6 +76,109 @@ pub fn roll<'a, DB>(
     env.block.number = number;
 }
 
+/// Sets the L2 block the next executions run in, `number` at `timestamp`, in the `SystemContext`.
+///
+/// Every execution runs in a new batch with a single L2 block, following the last one stored, so
+/// the previous block is stored. The batch timestamp is lowered if needed, as a batch can't start
+/// after its first L2 block.
+pub fn set_l2_block<'a, DB>(
+    number: u64,
+    timestamp: u64,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+) where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    info!(number, timestamp, "cheatcode setL2Block");
+
+    let l2_block_info_key = SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION.to_ru256();
+    let batch_info_key = SYSTEM_CONTEXT_BLOCK_INFO_POSITION.to_ru256();
+    let (batch_number, batch_timestamp) = load_block_info(batch_info_key, db, journaled_state);
+    store_block_info(l2_block_info_key, number - 1, timestamp - 1, db, journaled_state);
+    if batch_timestamp >= timestamp {
+        store_block_info(batch_info_key, batch_number, timestamp - 1, db, journaled_state);
+    }
+}
+
+/// Sets the batch the next executions run in, `number` at `timestamp`, in the `SystemContext`.
+///
+/// Like for [set_l2_block], the previous batch is stored, and the timestamp of the previous L2
+/// block is lowered if needed, as a batch can't start before it.
+pub fn set_batch_info<'a, DB>(
+    number: u64,
+    timestamp: u64,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+) where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    info!(number, timestamp, "cheatcode setBatchInfo");
+
+    let l2_block_info_key = SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION.to_ru256();
+    let batch_info_key = SYSTEM_CONTEXT_BLOCK_INFO_POSITION.to_ru256();
+    let (l2_block_number, l2_block_timestamp) =
+        load_block_info(l2_block_info_key, db, journaled_state);
+    store_block_info(batch_info_key, number - 1, timestamp - 1, db, journaled_state);
+    if l2_block_timestamp >= timestamp {
+        store_block_info(l2_block_info_key, l2_block_number, timestamp - 1, db, journaled_state);
+    }
+}
+
+/// Sets the hash the `SystemContext` keeps for the L2 block `number`, in its ring buffer of the
+/// latest L2 block hashes.
+pub fn set_l2_block_hash<'a, DB>(
+    number: u64,
+    hash: B256,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+) where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    info!(number, ?hash, "cheatcode setL2BlockHash");
+
+    let system_account = SYSTEM_CONTEXT_ADDRESS.to_address();
+    journaled_state.load_account(system_account, db).expect("account could not be loaded");
+    let hash_key = SYSTEM_CONTEXT_CURRENT_L2_BLOCK_HASHES_POSITION.to_ru256() +
+        rU256::from(number % u64::from(SYSTEM_CONTEXT_STORED_L2_BLOCK_HASHES));
+    journaled_state.touch(&system_account);
+    journaled_state
+        .sstore(system_account, hash_key, hash.into(), db)
+        .expect("failed storing value");
+}
+
+/// Reads a packed block number and timestamp from the `SystemContext` storage.
+fn load_block_info<DB>(key: rU256, db: &mut DB, journaled_state: &mut JournaledState) -> (u64, u64)
+where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    let system_account = SYSTEM_CONTEXT_ADDRESS.to_address();
+    journaled_state.load_account(system_account, db).expect("account could not be loaded");
+    let (block_info, _) = journaled_state.sload(system_account, key, db).unwrap_or_default();
+    unpack_block_info(block_info.to_u256())
+}
+
+/// Writes a packed block number and timestamp to the `SystemContext` storage.
+fn store_block_info<DB>(
+    key: rU256,
+    number: u64,
+    timestamp: u64,
+    db: &mut DB,
+    journaled_state: &mut JournaledState,
+) where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    let system_account = SYSTEM_CONTEXT_ADDRESS.to_address();
+    journaled_state.load_account(system_account, db).expect("account could not be loaded");
+    journaled_state.touch(&system_account);
+    let block_info = pack_block_info(number, timestamp).to_ru256();
+    journaled_state.sstore(system_account, key, block_info, db).expect("failed storing value");
+}
+
 /// Sets balance for a specific address.
 pub fn deal<'a, DB>(
     address: Address,
@@ -174,6 +282,48 @@ pub fn etch<'a, DB>(
     account.info.code = Some(bytecode.clone());
 }
 
+/// Sets the marker of a bytecode hash in the `KnownCodesStorage` system contract, which the
+/// bootloader reads to skip publishing the factory dependencies already known to the chain.
+pub fn set_bytecode_known<'a, DB>(
+    bytecode_hash: B256,
+    known: bool,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+) where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    info!(?bytecode_hash, known, "cheatcode setBytecodeKnown");
+
+    let known_codes_addr = KNOWN_CODES_STORAGE_ADDRESS.to_address();
+    journaled_state.load_account(known_codes_addr, db).expect("account could not be loaded");
+    journaled_state.touch(&known_codes_addr);
+    let marker = rU256::from(u8::from(known));
+    journaled_state
+        .sstore(known_codes_addr, bytecode_hash.into(), marker, db)
+        .expect("failed storing value");
+}
+
+/// Gets whether a bytecode hash is marked as known in the `KnownCodesStorage` system contract.
+pub fn is_bytecode_known<'a, DB>(
+    bytecode_hash: B256,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+) -> bool
+where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    info!(?bytecode_hash, "cheatcode isBytecodeKnown");
+
+    let known_codes_addr = KNOWN_CODES_STORAGE_ADDRESS.to_address();
+    journaled_state.load_account(known_codes_addr, db).expect("account could not be loaded");
+    let (marker, _) =
+        journaled_state.sload(known_codes_addr, bytecode_hash.into(), db).unwrap_or_default();
+
+    !marker.is_zero()
+}
+
 /// Represents an empty code
 pub const EMPTY_CODE: [u8; 32] = [0; 32];
 
@@ -211,3 +361,61 @@ pub fn set_mocked_account<'a, DB>(
         }
     }
 }
+
+/// Loads the storage of an era_test_node state, and sets the code of its accounts.
+pub fn load_era_state<'a, DB>(
+    state: &EraState,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+) where
+    DB: Database,
+    <DB as Database>::Error: Debug,
+{
+    info!(slots = state.fork_storage.storage.len(), "cheatcode loadEraState");
+
+    for (slot, value) in &state.fork_storage.storage {
+        journaled_state.load_account(slot.address, db).expect("account could not be loaded");
+        journaled_state.touch(&slot.address);
+        let (key, value) = (rU256::from_be_bytes(slot.key.0), rU256::from_be_bytes(value.0));
+        journaled_state.sstore(slot.address, key, value, db).expect("failed storing value");
+    }
+
+    for (address, hash, code) in state.account_codes() {
+        journaled_state.load_account(address, db).expect("account could not be loaded");
+        journaled_state.touch(&address);
+        let account = journaled_state.state.get_mut(&address).expect("failed loading account");
+        account.info.code_hash = hash;
+        account.info.code = Some(Bytecode::new_raw(code.clone()).to_checked());
+    }
+}
+
+/// Returns the era_test_node state of the accounts of the journaled state.
+///
+/// The state contains the non-zero storage of the accounts, and the code of the accounts deployed
+/// in the zkEVM.
+pub fn dump_era_state(journaled_state: &JournaledState) -> EraState {
+    info!("cheatcode dumpEraState");
+
+    let mut state = EraState::default();
+    for (address, account) in &journaled_state.state {
+        for (key, slot) in &account.storage {
+            if slot.present_value.is_zero() {
+                continue
+            }
+            state.fork_storage.storage.insert(
+                EraStorageKey { address: *address, key: B256::from(*key) },
+                B256::from(slot.present_value),
+            );
+        }
+    }
+
+    let code_hashes = state.account_code_hashes().collect::<Vec<_>>();
+    for (address, hash) in code_hashes {
+        let code =
+            journaled_state.state.get(&address).and_then(|account| account.info.code.as_ref());
+        if let Some(code) = code.filter(|code| !code.is_empty()) {
+            state.fork_storage.factory_deps.insert(hash, code.original_bytes());
+        }
+    }
+    state
+}
@@ -0,0 +1,175 @@
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+
+use alloy_primitives::{Address, Bytes, B256};
+use eyre::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zksync_types::ACCOUNT_CODE_STORAGE_ADDRESS;
+
+use crate::convert::ConvertH160;
+
+/// The version of the era_test_node state files that can be read and written.
+pub const ERA_STATE_VERSION: &str = "1";
+
+/// A storage slot of an account, serialized as `<address>:<key>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EraStorageKey {
+    /// The account.
+    pub address: Address,
+    /// The key of the slot.
+    pub key: B256,
+}
+
+impl fmt::Display for EraStorageKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}:{:?}", self.address, self.key)
+    }
+}
+
+impl FromStr for EraStorageKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, key) =
+            s.split_once(':').ok_or_else(|| format!("invalid storage key `{s}`"))?;
+        Ok(Self {
+            address: address
+                .parse()
+                .map_err(|err| format!("invalid address `{address}`: {err}"))?,
+            key: key.parse().map_err(|err| format!("invalid slot `{key}`: {err}"))?,
+        })
+    }
+}
+
+impl Serialize for EraStorageKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EraStorageKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The raw storage and the bytecodes of an Era chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EraForkStorage {
+    /// The non-zero storage slots, which include the balances, nonces and code hashes of the
+    /// accounts kept by the system contracts.
+    #[serde(default)]
+    pub storage: BTreeMap<EraStorageKey, B256>,
+    /// The bytecodes known to the chain, by bytecode hash.
+    #[serde(default)]
+    pub factory_deps: BTreeMap<B256, Bytes>,
+}
+
+/// The state of an Era chain, in the format of the state files of era_test_node, as written with
+/// `--dump-state` and read with `--load-state`.
+///
+/// Only the storage is used to load the state, the blocks and transactions are kept as is.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EraState {
+    /// The blocks of the chain.
+    #[serde(default)]
+    pub blocks: Vec<serde_json::Value>,
+    /// The transactions of the chain.
+    #[serde(default)]
+    pub transactions: Vec<serde_json::Value>,
+    /// The state of the chain.
+    #[serde(default)]
+    pub fork_storage: EraForkStorage,
+}
+
+impl EraState {
+    /// Reads a state file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let mut state: serde_json::Value = foundry_common::fs::read_json_file(path)?;
+        let version = state.as_object_mut().and_then(|state| state.remove("version"));
+        match version.as_ref().and_then(|version| version.as_str()) {
+            Some(ERA_STATE_VERSION) => {}
+            _ => eyre::bail!(
+                "unsupported state file {}: expected version {ERA_STATE_VERSION}, got {}",
+                path.display(),
+                version.unwrap_or_default()
+            ),
+        }
+        serde_json::from_value(state)
+            .wrap_err_with(|| format!("invalid state file {}", path.display()))
+    }
+
+    /// Writes the state to a file, overwriting it if it exists.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        foundry_common::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Returns the contents of the state file of the state.
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        let mut state = serde_json::to_value(self)?;
+        if let Some(state) = state.as_object_mut() {
+            state.insert("version".to_string(), ERA_STATE_VERSION.into());
+        }
+        Ok(serde_json::to_vec(&state)?)
+    }
+
+    /// Returns the accounts with code, with the hash of their bytecode as kept by the account
+    /// code storage system contract.
+    pub fn account_code_hashes(&self) -> impl Iterator<Item = (Address, B256)> + '_ {
+        let account_code_storage = ACCOUNT_CODE_STORAGE_ADDRESS.to_address();
+        self.fork_storage
+            .storage
+            .iter()
+            .filter(move |(slot, hash)| slot.address == account_code_storage && !hash.is_zero())
+            .map(|(slot, hash)| (Address::from_word(slot.key), *hash))
+    }
+
+    /// Returns the accounts with code and their bytecode, if it is a factory dependency of the
+    /// state.
+    pub fn account_codes(&self) -> impl Iterator<Item = (Address, B256, &Bytes)> + '_ {
+        self.account_code_hashes().filter_map(|(address, hash)| {
+            self.fork_storage.factory_deps.get(&hash).map(|code| (address, hash, code))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn roundtrips_state_file() {
+        let account = address!("1111111111111111111111111111111111111111");
+        let hash = b256!("0100000300000000000000000000000000000000000000000000000000000001");
+        let mut state = EraState::default();
+        state.fork_storage.storage.insert(
+            EraStorageKey {
+                address: ACCOUNT_CODE_STORAGE_ADDRESS.to_address(),
+                key: account.into_word(),
+            },
+            hash,
+        );
+        let slot = EraStorageKey { address: account, key: B256::ZERO };
+        state.fork_storage.storage.insert(slot, hash);
+        state.fork_storage.factory_deps.insert(hash, Bytes::from(vec![0; 96]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        state.write(&path).unwrap();
+        let json: serde_json::Value = foundry_common::fs::read_json_file(&path).unwrap();
+        assert_eq!(json["version"], "1");
+        assert_eq!(EraState::read(&path).unwrap(), state);
+
+        let codes = state.account_codes().map(|(address, hash, _)| (address, hash));
+        assert_eq!(codes.collect::<Vec<_>>(), vec![(account, hash)]);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::fs::write(&path, r#"{"version": "2", "fork_storage": {}}"#).unwrap();
+        assert!(EraState::read(&path).is_err());
+    }
+}
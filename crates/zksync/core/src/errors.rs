@@ -0,0 +1,39 @@
+//! Custom errors of the Era system contracts.
+
+/// Signatures of the custom errors that can be returned by the Era system contracts and the
+/// bootloader, used to decode revert data of zkSync transactions.
+pub const SYSTEM_CONTRACT_ERRORS: &[&str] = &[
+    "Unauthorized(address)",
+    "InvalidCodeHash(uint8)",
+    "UnknownCodeHash(bytes32)",
+    "EmptyVirtualBlocks()",
+    "HashIsNonZero(bytes32)",
+    "NonEmptyAccount()",
+    "EmptyBytes32()",
+    "NotAllowedToDeployInKernelSpace()",
+    "NonceIncreaseError(uint256,uint256)",
+    "ZeroNonceError()",
+    "NonceJumpError()",
+    "NonceAlreadySet(address,uint256)",
+    "NonceNotUsed(address,uint256)",
+    "InvalidNonceOrderingChange()",
+    "ValueMismatch(uint256,uint256)",
+    "InsufficientFunds(uint256,uint256)",
+    "FailedToPayOperator()",
+    "FailedToChargeGas()",
+    "CallerMustBeSystemContract()",
+    "CallerMustBeBootloader()",
+    "CallerMustBeForceDeployer()",
+    "CallerMustBeEvmContract()",
+    "InvalidCall()",
+    "InvalidInput()",
+    "Overflow()",
+    "UnsupportedOperation()",
+    "IndexOutOfBounds()",
+    "HashMismatch(bytes32,uint256)",
+    "MalformedBytecode(uint8)",
+    "UpgradeMustBeNonZero()",
+    "TimestampsShouldBeIncremental()",
+    "ProvidedBatchNumberIsNotCorrect(uint256,uint256)",
+    "CompressionValueTransformError()",
+];
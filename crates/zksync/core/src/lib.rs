@@ -9,6 +9,18 @@ pub mod cheatcodes;
 /// Contains conversion utils for revm primitives.
 pub mod convert;
 
+/// Contains the custom errors of the system contracts.
+pub mod errors;
+
+/// Contains the state file format of era_test_node.
+pub mod era_state;
+
+/// Contains the compression model of the pubdata published on L1 to deploy a contract.
+pub mod pubdata;
+
+/// Contains the verification of the Merkle proofs of L2 to L1 logs and storage slots.
+pub mod proof;
+
 /// Contains zksync utils.
 pub mod utils;
 
@@ -18,7 +30,7 @@ pub mod vm;
 /// ZKSync Era State implementation.
 pub mod state;
 
-use alloy_primitives::{Address, Bytes, U256 as rU256};
+use alloy_primitives::{b256, keccak256, Address, Bytes, B256, U256 as rU256};
 use convert::{ConvertAddress, ConvertH256, ConvertU256};
 use eyre::{eyre, OptionExt};
 pub use utils::{fix_l2_gas_limit, fix_l2_gas_price};
@@ -29,11 +41,12 @@ pub use zksync_types::{
     ACCOUNT_CODE_STORAGE_ADDRESS, CONTRACT_DEPLOYER_ADDRESS, L2_ETH_TOKEN_ADDRESS,
     NONCE_HOLDER_ADDRESS,
 };
+pub use zksync_utils::bytecode::hash_bytecode;
 use zksync_web3_rs::{
-    eip712::{Eip712Meta, Eip712Transaction, Eip712TransactionRequest},
+    eip712::{Eip712Meta, Eip712Transaction, Eip712TransactionRequest, PaymasterParams},
     providers::Middleware,
     signers::Signer,
-    types::transaction::eip2718::TypedTransaction,
+    types::{transaction::eip2718::TypedTransaction, Eip1559TransactionRequest},
     zks_provider::types::Fee,
     zks_utils::EIP712_TX_TYPE,
 };
@@ -45,20 +58,34 @@ pub fn get_balance_key(address: Address) -> rU256 {
     storage_key_for_eth_balance(&address.to_h160()).key().to_ru256()
 }
 
+/// Returns the nonce key for a provided account address.
+pub fn get_nonce_key(address: Address) -> rU256 {
+    zksync_types::get_nonce_key(&address.to_h160()).key().to_ru256()
+}
+
+/// Returns the code hash key for a provided account address.
+pub fn get_account_code_key(address: Address) -> rU256 {
+    zksync_types::get_code_key(&address.to_h160()).key().to_ru256()
+}
+
 /// Represents additional data for ZK transactions.
 #[derive(Clone, Debug, Default)]
 pub struct ZkTransactionMetadata {
     /// Factory Deps for ZK transactions.
     pub factory_deps: Vec<Vec<u8>>,
+    /// The paymaster paying the fees of the transaction, set by `zkUsePaymaster`.
+    pub paymaster: Option<vm::ZkPaymasterData>,
 }
 
-/// Creates a new signed EIP-712 transaction with the provided factory deps.
-pub async fn new_eip712_transaction<M: Middleware, S: Signer>(
-    legacy_or_1559: TypedTransaction,
-    factory_deps: Vec<Vec<u8>>,
-    provider: M,
-    signer: S,
-) -> Result<Bytes> {
+/// Builds the EIP-712 (type `0x71`) request of the `legacy_or_1559` transaction, with the factory
+/// deps and the paymaster of `zk_tx`.
+///
+/// The fees of the request are the ones of `legacy_or_1559` until set with
+/// [`set_eip712_fee`], e.g. to the estimate of [`estimate_eip712_fee`].
+pub fn new_eip712_request(
+    legacy_or_1559: &TypedTransaction,
+    zk_tx: &ZkTransactionMetadata,
+) -> Result<Eip712TransactionRequest> {
     let from = legacy_or_1559.from().cloned().ok_or_eyre("`from` cannot be empty")?;
     let to = legacy_or_1559
         .to()
@@ -70,9 +97,8 @@ pub async fn new_eip712_transaction<M: Middleware, S: Signer>(
     let gas_price = legacy_or_1559.gas_price().ok_or_eyre("`gas_price` cannot be empty")?;
     let max_cost = legacy_or_1559.max_cost().ok_or_eyre("`max_cost` cannot be empty")?;
     let data = legacy_or_1559.data().cloned().ok_or_eyre("`data` cannot be empty")?;
-    let custom_data = Eip712Meta::new().factory_deps(factory_deps);
 
-    let mut deploy_request = Eip712TransactionRequest::new()
+    Ok(Eip712TransactionRequest::new()
         .r#type(EIP712_TX_TYPE)
         .from(from)
         .to(to)
@@ -81,36 +107,85 @@ pub async fn new_eip712_transaction<M: Middleware, S: Signer>(
         .gas_price(gas_price)
         .max_fee_per_gas(max_cost)
         .data(data)
-        .custom_data(custom_data);
+        .value(legacy_or_1559.value().copied().unwrap_or_default())
+        .custom_data(eip712_meta(zk_tx)))
+}
 
-    let gas_price = provider
-        .get_gas_price()
-        .await
-        .map_err(|err| eyre!("failed retrieving gas_price {:?}", err))?;
-    let fee: Fee = provider
+/// Estimates the fee of an EIP-712 request with `zks_estimateFee`: its gas limit, fees and gas
+/// per pubdata, which account for the factory deps it publishes and the validation of its
+/// paymaster.
+pub async fn estimate_eip712_fee<M: Middleware>(
+    request: &Eip712TransactionRequest,
+    provider: M,
+) -> Result<Fee> {
+    provider
         .provider()
-        .request("zks_estimateFee", [deploy_request.clone()])
+        .request("zks_estimateFee", [request.clone()])
         .await
-        .map_err(|err| eyre!("failed estimating fee {:?}", err))?;
-    deploy_request = deploy_request
+        .map_err(|err| eyre!("failed estimating fee {:?}", err))
+}
+
+/// Sets the gas limit, fees and gas per pubdata of an EIP-712 request with the factory deps and
+/// the paymaster of `zk_tx` to `fee`.
+pub fn set_eip712_fee(
+    request: Eip712TransactionRequest,
+    zk_tx: &ZkTransactionMetadata,
+    fee: &Fee,
+) -> Eip712TransactionRequest {
+    request
         .gas_limit(fee.gas_limit)
         .max_fee_per_gas(fee.max_fee_per_gas)
         .max_priority_fee_per_gas(fee.max_priority_fee_per_gas)
-        .gas_price(gas_price);
+        .gas_price(fee.max_fee_per_gas)
+        .custom_data(eip712_meta(zk_tx).gas_per_pubdata(fee.gas_per_pubdata_limit))
+}
 
-    let signable: Eip712Transaction = deploy_request
+/// Signs an EIP-712 request, returning the raw transaction to send with
+/// `eth_sendRawTransaction`.
+pub async fn sign_eip712_request<S: Signer>(
+    request: Eip712TransactionRequest,
+    signer: &S,
+) -> Result<Bytes> {
+    let signable: Eip712Transaction = request
         .clone()
         .try_into()
         .map_err(|err| eyre!("failed converting deploy request to eip-712 tx {:?}", err))?;
 
-    let signature = signer.sign_typed_data(&signable).await.expect("Failed to sign typed data");
-    let encoded_rlp = deploy_request
+    let signature = signer
+        .sign_typed_data(&signable)
+        .await
+        .map_err(|err| eyre!("failed signing typed data {:?}", err))?;
+    let encoded_rlp = request
         .rlp_signed(signature)
         .map_err(|err| eyre!("failed encoding deployment request {:?}", err))?;
 
-    let tx = [&[EIP712_TX_TYPE], encoded_rlp.to_vec().as_slice()].concat().into();
+    Ok([&[EIP712_TX_TYPE], encoded_rlp.to_vec().as_slice()].concat().into())
+}
+
+/// Creates a new signed EIP-712 transaction with the provided factory deps and paymaster, and
+/// the fee estimated by the network.
+pub async fn new_eip712_transaction<M: Middleware, S: Signer>(
+    legacy_or_1559: TypedTransaction,
+    zk_tx: &ZkTransactionMetadata,
+    provider: M,
+    signer: S,
+) -> Result<Bytes> {
+    let request = new_eip712_request(&legacy_or_1559, zk_tx)?;
+    let fee = estimate_eip712_fee(&request, provider).await?;
+    sign_eip712_request(set_eip712_fee(request, zk_tx, &fee), &signer).await
+}
 
-    Ok(tx)
+/// Returns the EIP-712 fields of a transaction with the factory deps and the paymaster of
+/// `zk_tx`.
+pub fn eip712_meta(zk_tx: &ZkTransactionMetadata) -> Eip712Meta {
+    let meta = Eip712Meta::new().factory_deps(zk_tx.factory_deps.clone());
+    match &zk_tx.paymaster {
+        Some(paymaster) => meta.paymaster_params(PaymasterParams {
+            paymaster: paymaster.address.to_h160(),
+            paymaster_input: paymaster.input.to_vec(),
+        }),
+        None => meta,
+    }
 }
 
 /// Estimated gas from a ZK network.
@@ -160,8 +235,135 @@ pub async fn estimate_gas<M: Middleware>(
     Ok(EstimatedGas { price: gas_price.to_ru256(), limit: fee.gas_limit.to_ru256() })
 }
 
+/// The prefix of the preimage of `CREATE2` addresses in EraVM, `keccak256("zksyncCreate2")`.
+pub const CREATE2_PREFIX: B256 =
+    b256!("2020dba91b30cc0006188af794c2fb30dd8520db7e2c088b7fc7c103c00ca494");
+
+/// Returns the address of a contract deployed by `sender` with `CREATE2` in EraVM.
+///
+/// Unlike the EVM, the address is derived from the bytecode hash of the contract and the hash of
+/// its constructor input, `input_hash`, separately.
+pub fn compute_create2_address(
+    sender: Address,
+    bytecode_hash: B256,
+    salt: B256,
+    input_hash: B256,
+) -> Address {
+    let mut preimage = [0u8; 160];
+    preimage[..32].copy_from_slice(CREATE2_PREFIX.as_slice());
+    preimage[44..64].copy_from_slice(sender.as_slice());
+    preimage[64..96].copy_from_slice(salt.as_slice());
+    preimage[96..128].copy_from_slice(bytecode_hash.as_slice());
+    preimage[128..].copy_from_slice(input_hash.as_slice());
+    Address::from_word(keccak256(preimage))
+}
+
+/// The prefix of the preimage of `CREATE` addresses in EraVM, `keccak256("zksyncCreate")`.
+pub const CREATE_PREFIX: B256 =
+    b256!("63bae3a9951d38e8a3fbb7b70909afc1200610fc5bc55ade242f815974674f23");
+
+/// Returns the address of a contract deployed by `sender` with `CREATE` in EraVM.
+///
+/// Unlike the EVM, the address is derived from the deployment nonce of the sender, the number of
+/// contracts it deployed, instead of its transaction nonce.
+pub fn compute_create_address(sender: Address, deployment_nonce: rU256) -> Address {
+    let mut preimage = [0u8; 96];
+    preimage[..32].copy_from_slice(CREATE_PREFIX.as_slice());
+    preimage[44..64].copy_from_slice(sender.as_slice());
+    preimage[64..].copy_from_slice(&deployment_nonce.to_be_bytes::<32>());
+    Address::from_word(keccak256(preimage))
+}
+
+/// The selector of `getDeploymentNonce(address)` of the NonceHolder system contract.
+const GET_DEPLOYMENT_NONCE_SELECTOR: [u8; 4] = [0xfb, 0x1a, 0x9a, 0x57];
+
+/// Returns the deployment nonce of `address` on the network of `provider`, which the address of
+/// the next contract it deploys with `CREATE` is derived from.
+pub async fn get_deployment_nonce<M: Middleware>(address: Address, provider: M) -> Result<rU256> {
+    let data = [GET_DEPLOYMENT_NONCE_SELECTOR.as_slice(), address.into_word().as_slice()].concat();
+    let call: TypedTransaction =
+        Eip1559TransactionRequest::new().to(NONCE_HOLDER_ADDRESS).data(data).into();
+    let nonce = provider
+        .call(&call, None)
+        .await
+        .map_err(|err| eyre!("failed retrieving the deployment nonce of {address}: {err:?}"))?;
+    rU256::try_from_be_slice(&nonce).ok_or_eyre("invalid deployment nonce")
+}
+
 /// Returns true if the provided address is a reserved zkSync system address
 /// All addresses less than 2^16 are considered reserved addresses.
 pub fn is_system_address(address: Address) -> bool {
     address.to_h256().to_ru256().lt(&rU256::from(2u128.pow(16)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn builds_eip712_requests_with_value() {
+        use zksync_web3_rs::types::{H160, U256};
+
+        let tx = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .from(H160::from_low_u64_be(1))
+                .to(H160::from_low_u64_be(2))
+                .chain_id(324)
+                .nonce(3)
+                .max_fee_per_gas(100)
+                .data(vec![0xd0, 0xe3, 0x0d, 0xb0])
+                .value(U256::exp10(18)),
+        );
+        let zk_tx = ZkTransactionMetadata { factory_deps: vec![vec![0; 32]], paymaster: None };
+
+        // values are transferred in the base token of the chain, be it ETH or a custom token
+        let request = new_eip712_request(&tx, &zk_tx).unwrap();
+        assert_eq!(request.value, U256::exp10(18));
+        assert_eq!(request.to, Some(H160::from_low_u64_be(2)));
+        assert_eq!(request.custom_data.factory_deps, vec![vec![0; 32]]);
+
+        let without_value = TypedTransaction::Eip1559(match tx {
+            TypedTransaction::Eip1559(tx) => Eip1559TransactionRequest { value: None, ..tx },
+            _ => unreachable!(),
+        });
+        assert_eq!(new_eip712_request(&without_value, &zk_tx).unwrap().value, U256::zero());
+    }
+
+    #[test]
+    fn computes_create2_addresses() {
+        assert_eq!(keccak256("zksyncCreate2"), CREATE2_PREFIX);
+
+        // the addresses zksync-ethers' `utils.create2Address` returns for the sender and bytecode
+        // hash of its test vectors
+        let sender = address!("36615Cf349d7F6344891B1e7CA7C72883F5dc049");
+        let bytecode_hash =
+            b256!("010001cb6a6e8d5f6829522f19fa9568660e0a9cd53b2e8be4deb0a679452e41");
+        assert_eq!(
+            compute_create2_address(sender, bytecode_hash, B256::ZERO, keccak256(b"")),
+            address!("3cfd6d969361fd475d81ba3e3306263ec88e1c61")
+        );
+        assert_eq!(
+            compute_create2_address(
+                sender,
+                bytecode_hash,
+                B256::with_last_byte(1),
+                keccak256([0x01])
+            ),
+            address!("78ee9dea03a39f5cc04c80a575517ff5de02ec4c")
+        );
+    }
+
+    #[test]
+    fn computes_create_addresses() {
+        assert_eq!(keccak256("zksyncCreate"), CREATE_PREFIX);
+        assert_eq!(keccak256("getDeploymentNonce(address)")[..4], GET_DEPLOYMENT_NONCE_SELECTOR);
+
+        // the test vector of zksync-ethers' `utils.createAddress`
+        let sender = address!("36615Cf349d7F6344891B1e7CA7C72883F5dc049");
+        assert_eq!(
+            compute_create_address(sender, rU256::from(1)),
+            address!("4B5DF730c2e6b28E17013A1485E5d9BC41Efe021")
+        );
+    }
+}
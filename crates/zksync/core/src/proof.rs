@@ -0,0 +1,297 @@
+use alloy_primitives::{keccak256, Address, B256, U256};
+use blake2::{Blake2s256, Digest};
+
+/// The depth of the sparse Merkle tree of the storage of an Era chain.
+pub const STORAGE_TREE_DEPTH: usize = 256;
+
+/// The height of the Merkle tree of the L2 -> L1 logs of a batch, which has `2^14` leaves.
+pub const L2_LOGS_TREE_HEIGHT: usize = 14;
+
+/// A log sent from L2 to L1, as it's included in the L2 logs tree of its batch and verified by
+/// `proveL2LogInclusion` of the chain's L1 contract.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct L2ToL1Log {
+    /// The shard of the log.
+    pub shard_id: u8,
+    /// Whether the log is sent by a system contract.
+    pub is_service: bool,
+    /// The index of the transaction in its batch.
+    pub tx_number_in_batch: u16,
+    /// The sender of the log.
+    pub sender: Address,
+    /// The key of the log.
+    pub key: B256,
+    /// The value of the log.
+    pub value: B256,
+}
+
+impl L2ToL1Log {
+    /// Returns the leaf of the log in the L2 logs tree, the keccak256 hash of its packed encoding.
+    pub fn leaf_hash(&self) -> B256 {
+        let mut leaf = Vec::with_capacity(88);
+        leaf.push(self.shard_id);
+        leaf.push(self.is_service as u8);
+        leaf.extend_from_slice(&self.tx_number_in_batch.to_be_bytes());
+        leaf.extend_from_slice(self.sender.as_slice());
+        leaf.extend_from_slice(self.key.as_slice());
+        leaf.extend_from_slice(self.value.as_slice());
+        keccak256(leaf)
+    }
+}
+
+/// Returns the root of the L2 logs tree given the leaf at `index` and its Merkle path, ordered
+/// from the leaf to the root.
+pub fn l2_logs_root(leaf: B256, index: u32, proof: &[B256]) -> B256 {
+    proof.iter().enumerate().fold(leaf, |hash, (depth, sibling)| {
+        if index.checked_shr(depth as u32).unwrap_or_default() & 1 == 1 {
+            keccak256([sibling.as_slice(), hash.as_slice()].concat())
+        } else {
+            keccak256([hash.as_slice(), sibling.as_slice()].concat())
+        }
+    })
+}
+
+fn keccak_pair(left: &B256, right: &B256) -> B256 {
+    keccak256([left.as_slice(), right.as_slice()].concat())
+}
+
+/// Returns the root hashes of the empty subtrees of the L2 logs tree, by height.
+///
+/// An empty leaf is the hash of a log whose fields are all zero.
+pub fn empty_l2_logs_subtrees() -> Vec<B256> {
+    let mut hashes = Vec::with_capacity(L2_LOGS_TREE_HEIGHT + 1);
+    let mut hash = L2ToL1Log::default().leaf_hash();
+    hashes.push(hash);
+    for _ in 0..L2_LOGS_TREE_HEIGHT {
+        hash = keccak_pair(&hash, &hash);
+        hashes.push(hash);
+    }
+    hashes
+}
+
+/// Returns the parent level of a level of the L2 logs tree at `height`, padded with the empty
+/// subtrees.
+fn l2_logs_parents(level: &[B256], height: usize, empty: &[B256]) -> Vec<B256> {
+    level
+        .chunks(2)
+        .map(|pair| keccak_pair(&pair[0], pair.get(1).unwrap_or(&empty[height])))
+        .collect()
+}
+
+/// Returns the root of the L2 logs tree of a batch whose logs have the given leaf hashes.
+///
+/// # Panics
+///
+/// Panics if there are more leaves than the tree has.
+pub fn l2_logs_tree_root(leaves: &[B256]) -> B256 {
+    assert!(leaves.len() <= 1 << L2_LOGS_TREE_HEIGHT, "too many L2 -> L1 logs in the batch");
+    let empty = empty_l2_logs_subtrees();
+    let mut level = leaves.to_vec();
+    for height in 0..L2_LOGS_TREE_HEIGHT {
+        if level.is_empty() {
+            return empty[L2_LOGS_TREE_HEIGHT]
+        }
+        level = l2_logs_parents(&level, height, &empty);
+    }
+    level.first().copied().unwrap_or(empty[L2_LOGS_TREE_HEIGHT])
+}
+
+/// Returns the Merkle path of the leaf at `index` in the L2 logs tree of a batch whose logs have
+/// the given leaf hashes, ordered from the leaf to the root as expected by [l2_logs_root].
+///
+/// # Panics
+///
+/// Panics if there are more leaves than the tree has.
+pub fn l2_logs_proof(leaves: &[B256], index: usize) -> Vec<B256> {
+    assert!(leaves.len() <= 1 << L2_LOGS_TREE_HEIGHT, "too many L2 -> L1 logs in the batch");
+    let empty = empty_l2_logs_subtrees();
+    let mut level = leaves.to_vec();
+    let mut proof = Vec::with_capacity(L2_LOGS_TREE_HEIGHT);
+    for height in 0..L2_LOGS_TREE_HEIGHT {
+        proof.push(level.get((index >> height) ^ 1).copied().unwrap_or(empty[height]));
+        level = l2_logs_parents(&level, height, &empty);
+    }
+    proof
+}
+
+fn blake2s(data: &[u8]) -> B256 {
+    B256::from_slice(&Blake2s256::digest(data))
+}
+
+/// Returns the key of a storage slot in the storage tree, the blake2s hash of the padded
+/// address followed by the slot.
+pub fn storage_tree_key(address: Address, slot: B256) -> B256 {
+    blake2s(&[address.into_word().as_slice(), slot.as_slice()].concat())
+}
+
+/// Returns the hash of a leaf of the storage tree, with `index` being the enumeration index of
+/// the slot.
+pub fn storage_leaf_hash(index: u64, value: B256) -> B256 {
+    blake2s(&[&index.to_be_bytes()[..], value.as_slice()].concat())
+}
+
+/// Returns the root hashes of the empty subtrees of the storage tree, by height.
+pub fn empty_storage_subtrees() -> Vec<B256> {
+    let mut hashes = Vec::with_capacity(STORAGE_TREE_DEPTH + 1);
+    let mut hash = storage_leaf_hash(0, B256::ZERO);
+    hashes.push(hash);
+    for _ in 0..STORAGE_TREE_DEPTH {
+        hash = blake2s(&[hash.as_slice(), hash.as_slice()].concat());
+        hashes.push(hash);
+    }
+    hashes
+}
+
+/// Returns the full Merkle path of a storage proof, ordered from the root to the leaf.
+///
+/// `zks_getProof` omits the hashes of the empty subtrees at the end of the path.
+pub fn padded_storage_proof(proof: &[B256]) -> Vec<B256> {
+    let empty = empty_storage_subtrees();
+    let mut path = proof.to_vec();
+    path.extend(
+        (path.len()..STORAGE_TREE_DEPTH).map(|depth| empty[STORAGE_TREE_DEPTH - depth - 1]),
+    );
+    path
+}
+
+/// Returns the root of the storage tree given the value of a slot, its enumeration index and its
+/// Merkle path as returned by `zks_getProof`.
+///
+/// Slots that were never written have the index 0 and the value 0.
+pub fn storage_root(address: Address, slot: B256, value: B256, index: u64, proof: &[B256]) -> B256 {
+    // the key is read as a little-endian number, whose most significant bit selects the first
+    // branch from the root
+    let key = U256::from_le_bytes(storage_tree_key(address, slot).0);
+    let path = padded_storage_proof(proof);
+    let leaf = storage_leaf_hash(index, value);
+    path.iter().rev().enumerate().fold(leaf, |hash, (height, sibling)| {
+        if key.bit(height) {
+            blake2s(&[sibling.as_slice(), hash.as_slice()].concat())
+        } else {
+            blake2s(&[hash.as_slice(), sibling.as_slice()].concat())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn hashes_like_the_network() {
+        // `L2_L1_LOGS_TREE_DEFAULT_LEAF_HASH` of the L1 contracts
+        assert_eq!(
+            L2ToL1Log::default().leaf_hash(),
+            b256!("72abee45b59e344af8a6e520241c4744aff26ed411f4c4b00f8af09adada43ba")
+        );
+        assert_eq!(
+            empty_l2_logs_subtrees()[L2_LOGS_TREE_HEIGHT],
+            b256!("375a5bf909cb02143e3695ca658e0641e739aa590f0004dba93572c44cdb9d2d")
+        );
+        assert_eq!(
+            empty_storage_subtrees()[STORAGE_TREE_DEPTH],
+            b256!("98a48e4ed1736188384ae8a79dd21c4d6687e5fd22ca18148906d78736c0d86a")
+        );
+    }
+
+    #[test]
+    fn proves_known_l2_logs() {
+        // messages sent through the `L1Messenger` by transactions 3 to 5 of a batch
+        let log = |tx_number_in_batch: u16, message: &[u8]| L2ToL1Log {
+            shard_id: 0,
+            is_service: true,
+            tx_number_in_batch,
+            sender: address!("0000000000000000000000000000000000008008"),
+            key: address!("0000000000000000000000000000000000001234").into_word(),
+            value: keccak256(message),
+        };
+        let leaves = [log(3, b"hello"), log(4, b"world"), log(5, b"!")].map(|log| log.leaf_hash());
+        assert_eq!(
+            leaves[0],
+            b256!("e8fd18e1f376207ff8326a1cedbd1d3b79c9a7ce793d49cb3b24b973b6b57adb")
+        );
+
+        let root = b256!("8a449431cb0196263598e1985ee1658e58f82100c46b23feb596cb3145fc3996");
+        assert_eq!(l2_logs_tree_root(&leaves), root);
+        let proof = l2_logs_proof(&leaves, 2);
+        assert_eq!(proof[0], L2ToL1Log::default().leaf_hash());
+        assert_eq!(
+            proof[1],
+            b256!("0fb1cc6e2528e8e53db75f77d1e8dd4a32310ba28851ba6032b852f707648447")
+        );
+        assert_eq!(l2_logs_root(leaves[2], 2, &proof), root);
+    }
+
+    #[test]
+    fn proves_known_storage() {
+        // the first slot of the `NonceHolder`, the only slot of the tree
+        let (address, slot) = (address!("0000000000000000000000000000000000008003"), B256::ZERO);
+        assert_eq!(
+            storage_tree_key(address, slot),
+            b256!("c1198a72505f46d722ed8e0ca0f9bfe42ba3a2d868a618d9d55a80ba9255f761")
+        );
+        assert_eq!(
+            storage_root(address, slot, B256::with_last_byte(1), 1, &[]),
+            b256!("7204cd71696123b183ae5025504a664e9efcb478a71e08322ca7fad817efa12c")
+        );
+    }
+
+    #[test]
+    fn computes_l2_logs_root() {
+        let leaf = L2ToL1Log { sender: Address::repeat_byte(1), ..Default::default() }.leaf_hash();
+        let sibling = B256::repeat_byte(2);
+        let uncle = B256::repeat_byte(3);
+
+        let parent = keccak256([sibling.as_slice(), leaf.as_slice()].concat());
+        let root = keccak256([parent.as_slice(), uncle.as_slice()].concat());
+        assert_eq!(l2_logs_root(leaf, 1, &[sibling, uncle]), root);
+        assert_ne!(l2_logs_root(leaf, 0, &[sibling, uncle]), root);
+    }
+
+    #[test]
+    fn proves_l2_logs() {
+        let leaves = (1..=5u8)
+            .map(|byte| {
+                L2ToL1Log { value: B256::repeat_byte(byte), ..Default::default() }.leaf_hash()
+            })
+            .collect::<Vec<_>>();
+        let root = l2_logs_tree_root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = l2_logs_proof(&leaves, index);
+            assert_eq!(proof.len(), L2_LOGS_TREE_HEIGHT);
+            assert_eq!(l2_logs_root(*leaf, index as u32, &proof), root);
+            assert_ne!(l2_logs_root(*leaf, index as u32 ^ 1, &proof), root);
+        }
+
+        // the tree of a single log is the log and the empty subtrees
+        let empty = empty_l2_logs_subtrees();
+        assert_eq!(l2_logs_proof(&leaves[..1], 0), empty[..L2_LOGS_TREE_HEIGHT]);
+        assert_eq!(l2_logs_tree_root(&[]), empty[L2_LOGS_TREE_HEIGHT]);
+        assert_eq!(l2_logs_tree_root(&leaves[..0]), l2_logs_tree_root(&[empty[0]]));
+    }
+
+    #[test]
+    fn computes_storage_root() {
+        let empty = empty_storage_subtrees();
+        let (address, slot) = (Address::repeat_byte(1), B256::ZERO);
+
+        // a slot that was never written in an empty tree
+        assert_eq!(storage_root(address, slot, B256::ZERO, 0, &[]), empty[STORAGE_TREE_DEPTH]);
+
+        // the only slot of the tree
+        let value = B256::repeat_byte(2);
+        let root = storage_root(address, slot, value, 1, &[]);
+        let key = U256::from_le_bytes(storage_tree_key(address, slot).0);
+        let mut hash = storage_leaf_hash(1, value);
+        for (height, sibling) in empty.iter().take(STORAGE_TREE_DEPTH).enumerate() {
+            hash = if key.bit(height) {
+                blake2s(&[sibling.as_slice(), hash.as_slice()].concat())
+            } else {
+                blake2s(&[hash.as_slice(), sibling.as_slice()].concat())
+            };
+        }
+        assert_eq!(root, hash);
+        assert_eq!(padded_storage_proof(&[]).len(), STORAGE_TREE_DEPTH);
+    }
+}
@@ -0,0 +1,124 @@
+//! The pubdata zkSync Era publishes on L1 to deploy a contract, for local fee estimates.
+//!
+//! Era doesn't publish the raw bytecodes and storage slots a deployment changes: the bytecodes
+//! are compressed by the operator when it's shorter, and the storage writes marking them as known
+//! and setting the code hash of the contract are published as state diffs whose key and value are
+//! compressed. Pricing the raw sizes overestimates the cost, so the deployment costs of
+//! `cast zk code-deploy-cost`, `forge create --estimate-only`, the gas reports and the script fee
+//! projections use the compression of the network, from `zksync_types` and `zksync_utils`.
+//!
+//! The model only covers the writes of the deployment itself, not the ones of the constructor.
+//! The fees of transactions are estimated by the node with `zks_estimateFee` when it's reachable,
+//! and the pubdata of the transactions executed in EraVM by tests and scripts is the one measured
+//! by the VM.
+
+use alloy_primitives::U256;
+use zksync_types::writes::StateDiffRecord;
+use zksync_utils::bytecode::{compress_bytecode, hash_bytecode};
+
+use crate::convert::{ConvertH256, ConvertRU256};
+
+/// The gas per pubdata byte assumed when it's neither given nor quoted by the chain.
+pub const DEFAULT_GAS_PER_PUBDATA: u64 = 800;
+
+/// The bytes of the length prefixing a bytecode published by the `L1Messenger`.
+const BYTECODE_LENGTH_BYTES: usize = 4;
+
+/// Returns the bytes of pubdata publishing a bytecode unknown to the chain, including marking it
+/// as known.
+///
+/// The bytecode is published compressed if it compresses, as the operator prefers the shorter
+/// of the two.
+pub fn bytecode_pubdata(code: &[u8]) -> usize {
+    let published =
+        compress_bytecode(code).map_or(code.len(), |compressed| compressed.len().min(code.len()));
+    // marking the bytecode as known is an initial write of the `KnownCodesStorage` marker
+    BYTECODE_LENGTH_BYTES + published + state_diff_pubdata(true, U256::ZERO, U256::from(1))
+}
+
+/// Returns the bytes of pubdata publishing the factory dependencies of a transaction, assuming
+/// none of them is known to the chain.
+pub fn factory_deps_pubdata(deps: &[Vec<u8>]) -> usize {
+    deps.iter().map(|dep| bytecode_pubdata(dep)).sum()
+}
+
+/// Returns the bytes of pubdata deploying a contract with the bytecodes `deps`, the one of the
+/// contract last: publishing the bytecodes unknown to the chain and the initial write of the
+/// code hash of the contract in the `AccountCodeStorage`.
+pub fn deployment_pubdata(deps: &[Vec<u8>]) -> usize {
+    let code_hash = deps.last().map_or(U256::ZERO, |code| hash_bytecode(code).to_ru256());
+    factory_deps_pubdata(deps) + state_diff_pubdata(true, U256::ZERO, code_hash)
+}
+
+/// Returns the bytes of pubdata of a storage write changing the slot from `previous` to `value`.
+///
+/// An initial write is published with its full derived key, a repeated one with the enumeration
+/// index of the key. The value is published with the shortest of the compression strategies.
+pub fn state_diff_pubdata(initial: bool, previous: U256, value: U256) -> usize {
+    state_diff(initial, previous, value).compress().len()
+}
+
+/// Returns the state diff of a storage write, whose key only matters for its length.
+fn state_diff(initial: bool, previous: U256, value: U256) -> StateDiffRecord {
+    StateDiffRecord {
+        address: Default::default(),
+        key: Default::default(),
+        derived_key: [0; 32],
+        enumeration_index: if initial { 0 } else { 1 },
+        initial_value: previous.to_u256(),
+        final_value: value.to_u256(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, hex};
+
+    #[test]
+    fn compresses_state_diffs() {
+        assert_eq!(state_diff_pubdata(true, U256::ZERO, U256::from(1)), 34);
+        assert_eq!(state_diff_pubdata(false, U256::from(1000), U256::from(1001)), 6);
+        assert_eq!(state_diff_pubdata(false, U256::from(1000), U256::from(999)), 6);
+        assert_eq!(state_diff_pubdata(false, U256::MAX, U256::from(1)), 6);
+        assert_eq!(state_diff_pubdata(false, U256::MAX, U256::MAX - U256::from(0x100)), 7);
+    }
+
+    // The state diffs of the compression spec of the Era pubdata: an enumeration index of 4
+    // bytes, then a metadata byte of the length of the value shifted by 3 and the strategy
+    // (1 add, 2 sub, 3 transform), then the value
+    #[test]
+    fn encodes_state_diffs_like_the_network() {
+        let compressed = |previous: u64, value: u64| {
+            state_diff(false, U256::from(previous), U256::from(value)).compress()
+        };
+        // a counter incremented, added
+        assert_eq!(compressed(0x1234_5678, 0x1234_5679), hex!("00000001" "09" "01"));
+        // a balance decreased by a fee, subtracted
+        assert_eq!(compressed(0x1_0000_0000, 0xffff_ff00), hex!("00000001" "12" "0100"));
+        // a slot set to a small value, transformed
+        assert_eq!(compressed(0xabcdef, 0x02), hex!("00000001" "0b" "02"));
+    }
+
+    #[test]
+    fn publishes_the_shorter_bytecode() {
+        // a bytecode of identical 8-byte chunks compresses to a single chunk and its indices
+        let code = vec![0x11; 32 * 32];
+        let pubdata = bytecode_pubdata(&code);
+        assert!(pubdata < BYTECODE_LENGTH_BYTES + code.len() + 34);
+        assert!(pubdata > BYTECODE_LENGTH_BYTES + 34);
+        assert_eq!(factory_deps_pubdata(&[code.clone(), code]), 2 * pubdata);
+    }
+
+    // The code hash of a deployment is hashed by `zksync_utils` like the network does: the
+    // version, the length in words and the SHA-256 of the bytecode
+    #[test]
+    fn prices_the_code_hash_of_deployments() {
+        let code = vec![0; 32];
+        assert_eq!(
+            hash_bytecode(&code).to_b256(),
+            b256!("01000001f862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925")
+        );
+        assert_eq!(deployment_pubdata(&[code.clone()]), bytecode_pubdata(&code) + 65);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The maximum size of the calldata of a transaction accepted by zkSync Era nodes, their default
+/// `max_tx_size`.
+pub const MAX_CALLDATA_SIZE: usize = 1_000_000;
+
+/// Sizes reaching this percentage of their limit are reported.
+pub const SIZE_WARNING_PERCENT: usize = 80;
+
+/// The data whose size is limited on EraVM.
+///
+/// Only the transactions entering EraVM are limited in size: the calls between contracts, their
+/// returndata and their heap have no limit of their own and are bounded by the ergs of the call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SizeLimitKind {
+    /// The data of a transaction: the calldata of a call, or the constructor input and factory
+    /// dependencies of a deployment.
+    Calldata,
+}
+
+impl SizeLimitKind {
+    /// Returns the limit of the size in bytes.
+    pub fn limit(self) -> usize {
+        match self {
+            Self::Calldata => MAX_CALLDATA_SIZE,
+        }
+    }
+}
+
+impl fmt::Display for SizeLimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Calldata => f.write_str("calldata"),
+        }
+    }
+}
+
+/// A transaction size approaching, or exceeding, its EraVM limit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeLimitWarning {
+    /// The limited data.
+    pub kind: SizeLimitKind,
+    /// The function, the called contract or the deployed contract the data belongs to.
+    pub context: String,
+    /// The size of the data in bytes, the minimum ABI-encoded size if detected statically.
+    pub size: usize,
+    /// The limit of the size in bytes.
+    pub limit: usize,
+    /// Whether the size was detected from the ABI instead of during an execution.
+    pub is_static: bool,
+}
+
+impl SizeLimitWarning {
+    /// Returns a warning if `size` reaches [`SIZE_WARNING_PERCENT`] of the limit of `kind`.
+    pub fn check(
+        kind: SizeLimitKind,
+        context: impl Into<String>,
+        size: usize,
+        is_static: bool,
+    ) -> Option<Self> {
+        let limit = kind.limit();
+        (size.saturating_mul(100) >= limit * SIZE_WARNING_PERCENT).then(|| Self {
+            kind,
+            context: context.into(),
+            size,
+            limit,
+            is_static,
+        })
+    }
+
+    /// Returns whether the size exceeds the limit.
+    pub fn is_exceeded(&self) -> bool {
+        self.size > self.limit
+    }
+
+    /// Records the warning in `warnings`, keeping only the largest size of the same data.
+    pub fn record(self, warnings: &mut Vec<Self>) {
+        match warnings.iter_mut().find(|w| w.kind == self.kind && w.context == self.context) {
+            Some(warning) => warning.size = warning.size.max(self.size),
+            None => warnings.push(self),
+        }
+    }
+}
+
+impl fmt::Display for SizeLimitWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { kind, context, size, limit, is_static } = self;
+        let size = if *is_static { format!("at least {size}") } else { size.to_string() };
+        let state = if self.is_exceeded() { "exceeds" } else { "approaches" };
+        write!(f, "{kind} of {context} ({size} bytes) {state} the EraVM limit of {limit} bytes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_size_limits() {
+        assert!(SizeLimitWarning::check(SizeLimitKind::Calldata, "f()", 1_000, false).is_none());
+
+        let warning =
+            SizeLimitWarning::check(SizeLimitKind::Calldata, "f()", 900_000, true).unwrap();
+        assert!(!warning.is_exceeded());
+        assert_eq!(
+            warning.to_string(),
+            "calldata of f() (at least 900000 bytes) approaches the EraVM limit of 1000000 bytes"
+        );
+        let deployment =
+            SizeLimitWarning::check(SizeLimitKind::Calldata, "deployment of C", 1_200_000, false)
+                .unwrap();
+        assert!(deployment.is_exceeded());
+        assert_eq!(
+            deployment.to_string(),
+            "calldata of deployment of C (1200000 bytes) exceeds the EraVM limit of 1000000 bytes"
+        );
+
+        let mut warnings = vec![warning];
+        SizeLimitWarning::check(SizeLimitKind::Calldata, "f()", 2_000_000, true)
+            .unwrap()
+            .record(&mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_exceeded());
+    }
+}
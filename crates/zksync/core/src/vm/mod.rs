@@ -1,9 +1,17 @@
 mod db;
 mod env;
 mod farcall;
+mod limits;
 mod runner;
 mod storage_view;
 mod tracer;
 
-pub use runner::{balance, call, code_hash, create, encode_create_params, nonce, transact};
-pub use tracer::CheatcodeTracerContext;
+pub use limits::{SizeLimitKind, SizeLimitWarning, MAX_CALLDATA_SIZE, SIZE_WARNING_PERCENT};
+pub use runner::{
+    balance, call, call_with_factory_deps, code_hash, create, encode_create_params, nonce,
+    simulate, transact,
+};
+pub use tracer::{
+    CheatcodeTracerContext, ZkExecutionMetrics, ZkFuzzValues, ZkPaymasterData,
+    ZkResourceLimitExceeded, ZkResourceLimits, ZkStorageWrite,
+};
@@ -45,9 +45,9 @@ use zksync_types::{
     fee::Fee,
     l2::L2Tx,
     transaction_request::PaymasterParams,
-    vm_trace::Call,
-    PackedEthSignature, StorageKey, Transaction, VmEvent, ACCOUNT_CODE_STORAGE_ADDRESS,
-    CONTRACT_DEPLOYER_ADDRESS, H160, U256,
+    vm_trace::{Call, CallType},
+    FarCallOpcode, PackedEthSignature, StorageKey, Transaction, VmEvent,
+    ACCOUNT_CODE_STORAGE_ADDRESS, CONTRACT_DEPLOYER_ADDRESS, H160, U256,
 };
 use zksync_utils::{h256_to_account_address, h256_to_u256, u256_to_h256};
 
@@ -56,7 +56,10 @@ use crate::vm::{
     env::{create_l1_batch_env, create_system_env},
 };
 
-use super::{storage_view::StorageView, tracer::CheatcodeTracerContext};
+use super::{
+    storage_view::StorageView,
+    tracer::{CheatcodeTracerContext, ZkFuzzValues, ZkStorageWrite},
+};
 
 type ZKVMResult<E> = EVMResultGeneric<rExecutionResult, E>;
 
@@ -71,25 +74,72 @@ where
     <DB as Database>::Error: Debug,
 {
     tracing::debug!("zk transact");
-    let mut journaled_state = JournaledState::new(
+    let mut journaled_state = new_journaled_state(env);
+    let tx = env_to_l2_tx(factory_deps, env, db, &mut journaled_state);
+
+    let (state, _) = journaled_state.finalize();
+    match inspect::<_, DB::Error>(tx, env, db, &mut journaled_state, Default::default()) {
+        Ok(result) => Ok(ResultAndState { result, state }),
+        Err(err) => eyre::bail!("zk backend: failed while inspecting: {err:?}"),
+    }
+}
+
+/// Executes the transaction of the `env` and returns its result together with the state it
+/// modified, without committing it to the `db`.
+pub fn simulate<'a, DB>(
+    factory_deps: Option<Vec<Vec<u8>>>,
+    env: &'a mut Env,
+    db: &'a mut DB,
+) -> eyre::Result<ResultAndState>
+where
+    DB: Database + Send,
+    <DB as Database>::Error: Debug,
+{
+    tracing::debug!("zk simulate");
+    let mut journaled_state = new_journaled_state(env);
+    let tx = env_to_l2_tx(factory_deps, env, db, &mut journaled_state);
+
+    match inspect::<_, DB::Error>(tx, env, db, &mut journaled_state, Default::default()) {
+        Ok(result) => {
+            let (state, _) = journaled_state.finalize();
+            Ok(ResultAndState { result, state })
+        }
+        Err(err) => eyre::bail!("zk backend: failed while inspecting: {err:?}"),
+    }
+}
+
+fn new_journaled_state(env: &Env) -> JournaledState {
+    JournaledState::new(
         env.cfg.spec_id,
         Precompiles::new(to_precompile_id(env.cfg.spec_id))
             .addresses()
             .into_iter()
             .copied()
             .collect(),
-    );
+    )
+}
 
+/// Builds the [L2Tx] for the transaction configured in the `env`.
+fn env_to_l2_tx<DB>(
+    factory_deps: Option<Vec<Vec<u8>>>,
+    env: &mut Env,
+    db: &mut DB,
+    journaled_state: &mut JournaledState,
+) -> L2Tx
+where
+    DB: Database + Send,
+    <DB as Database>::Error: Debug,
+{
     let caller = env.tx.caller;
-    let nonce = ZKVMData::new(db, &mut journaled_state).get_tx_nonce(caller);
+    let nonce = ZKVMData::new(db, journaled_state).get_tx_nonce(caller);
     let transact_to = match env.tx.transact_to {
         TransactTo::Call(to) => to.to_h160(),
         TransactTo::Create(CreateScheme::Create) |
         TransactTo::Create(CreateScheme::Create2 { .. }) => CONTRACT_DEPLOYER_ADDRESS,
     };
 
-    let (gas_limit, max_fee_per_gas) = gas_params(env, db, &mut journaled_state, caller);
-    let tx = L2Tx::new(
+    let (gas_limit, max_fee_per_gas) = gas_params(env, db, journaled_state, caller, None);
+    L2Tx::new(
         transact_to,
         env.tx.data.to_vec(),
         nonce,
@@ -103,13 +153,7 @@ where
         env.tx.value.to_u256(),
         factory_deps,
         PaymasterParams::default(),
-    );
-
-    let (state, _) = journaled_state.finalize();
-    match inspect::<_, DB::Error>(tx, env, db, &mut journaled_state, Default::default()) {
-        Ok(result) => Ok(ResultAndState { result, state }),
-        Err(err) => eyre::bail!("zk backend: failed while inspecting: {err:?}"),
-    }
+    )
 }
 
 /// Retrieves L2 ETH balance for a given address.
@@ -170,10 +214,11 @@ where
     let constructor_input = call.init_code[contract.evm_bytecode.len()..].to_vec();
     let caller = call.caller;
     let calldata = encode_create_params(&call.scheme, contract.zk_bytecode_hash, constructor_input);
-    let factory_deps = vec![contract.zk_deployed_bytecode.clone()];
+    let factory_deps = contract.factory_deps();
     let nonce = ZKVMData::new(db, journaled_state).get_tx_nonce(caller);
 
-    let (gas_limit, max_fee_per_gas) = gas_params(env, db, journaled_state, caller);
+    let paymaster = ccx.paymaster.as_ref().map(|paymaster| paymaster.address);
+    let (gas_limit, max_fee_per_gas) = gas_params(env, db, journaled_state, caller, paymaster);
     let tx = L2Tx::new(
         CONTRACT_DEPLOYER_ADDRESS,
         calldata,
@@ -187,7 +232,7 @@ where
         caller.to_h160(),
         call.value.to_u256(),
         Some(factory_deps),
-        PaymasterParams::default(),
+        paymaster_params(&ccx),
     );
     inspect(tx, env, db, journaled_state, ccx)
 }
@@ -201,16 +246,33 @@ pub fn call<'a, DB, E>(
     journaled_state: &'a mut JournaledState,
     ccx: CheatcodeTracerContext,
 ) -> ZKVMResult<E>
+where
+    DB: Database + Send,
+    <DB as Database>::Error: Debug,
+{
+    let factory_deps = contract.map(DualCompiledContract::factory_deps);
+    call_with_factory_deps(call, factory_deps, env, db, journaled_state, ccx)
+}
+
+/// Executes a CALL opcode on the ZK-VM, publishing the given factory deps with the transaction.
+pub fn call_with_factory_deps<'a, DB, E>(
+    call: &CallInputs,
+    factory_deps: Option<Vec<Vec<u8>>>,
+    env: &'a mut Env,
+    db: &'a mut DB,
+    journaled_state: &'a mut JournaledState,
+    ccx: CheatcodeTracerContext,
+) -> ZKVMResult<E>
 where
     DB: Database + Send,
     <DB as Database>::Error: Debug,
 {
     info!(?call, "call tx {}", hex::encode(&call.input));
     let caller = call.context.caller;
-    let factory_deps = contract.map(|contract| vec![contract.zk_deployed_bytecode.clone()]);
     let nonce: zksync_types::Nonce = ZKVMData::new(db, journaled_state).get_tx_nonce(caller);
 
-    let (gas_limit, max_fee_per_gas) = gas_params(env, db, journaled_state, caller);
+    let paymaster = ccx.paymaster.as_ref().map(|paymaster| paymaster.address);
+    let (gas_limit, max_fee_per_gas) = gas_params(env, db, journaled_state, caller, paymaster);
     let tx = L2Tx::new(
         call.contract.to_h160(),
         call.input.to_vec(),
@@ -224,24 +286,40 @@ where
         caller.to_h160(),
         call.transfer.value.to_u256(),
         factory_deps,
-        PaymasterParams::default(),
+        paymaster_params(&ccx),
     );
     inspect(tx, env, db, journaled_state, ccx)
 }
 
+/// Returns the paymaster parameters of a transaction paid by the paymaster of `ccx`, if any.
+fn paymaster_params(ccx: &CheatcodeTracerContext) -> PaymasterParams {
+    ccx.paymaster.as_ref().map_or_else(PaymasterParams::default, |paymaster| PaymasterParams {
+        paymaster: paymaster.address.to_h160(),
+        paymaster_input: paymaster.input.to_vec(),
+    })
+}
+
 /// Assign gas parameters that satisfy zkSync's fee model.
+///
+/// The gas limit is capped by the balance of the `paymaster` if set, as it pays the fees instead
+/// of the `caller`.
 fn gas_params<'a, DB>(
     env: &'a mut Env,
     db: &'a mut DB,
     journaled_state: &'a mut JournaledState,
     caller: Address,
+    paymaster: Option<Address>,
 ) -> (U256, U256)
 where
     DB: Database + Send,
     <DB as Database>::Error: Debug,
 {
-    let value = env.tx.value.to_u256();
-    let balance = ZKVMData::new(db, journaled_state).get_balance(caller);
+    let (value, balance) = match paymaster {
+        Some(paymaster) => {
+            (U256::zero(), ZKVMData::new(db, journaled_state).get_balance(paymaster))
+        }
+        None => (env.tx.value.to_u256(), ZKVMData::new(db, journaled_state).get_balance(caller)),
+    };
     let max_fee_per_gas = fix_l2_gas_price(env.tx.gas_price.to_u256());
     let gas_limit = fix_l2_gas_limit(env.tx.gas_limit.into(), max_fee_per_gas, value, balance);
 
@@ -253,7 +331,7 @@ fn inspect<'a, DB, E>(
     env: &'a mut Env,
     db: &'a mut DB,
     journaled_state: &'a mut JournaledState,
-    ccx: CheatcodeTracerContext,
+    mut ccx: CheatcodeTracerContext,
 ) -> ZKVMResult<E>
 where
     DB: Database + Send,
@@ -270,23 +348,36 @@ where
         DEFAULT_CHAIN_ID
     };
 
+    // A registered account validates the transaction with its configured signature
+    if let Some(signature) = &ccx.custom_account {
+        tx.common_data.signature = signature.to_vec();
+    }
     if tx.common_data.signature.is_empty() {
         // FIXME: This is a hack to make sure that the signature is not empty.
         // Fails without a signature here: https://github.com/matter-labs/zksync-era/blob/73a1e8ff564025d06e02c2689da238ae47bb10c3/core/lib/types/src/transaction_request.rs#L381
         tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
     }
 
+    // The code of a registered account is kept, so it validates and executes the transaction
     let modified_storage_keys = era_db.override_keys.clone();
-    let storage_ptr =
-        StorageView::new(&mut era_db, modified_storage_keys, tx.common_data.initiator_address)
-            .into_rc_ptr();
-    let (tx_result, bytecodes, modified_storage) = inspect_inner(
+    let caller = ccx.custom_account.is_none().then_some(tx.common_data.initiator_address);
+    let storage_ptr = StorageView::new(&mut era_db, modified_storage_keys, caller).into_rc_ptr();
+    let metrics = ccx.metrics.take();
+    let storage_writes = ccx.storage_writes.take();
+    let fuzz_values = ccx.fuzz_values.take();
+    let delegators = ccx.delegators.take();
+    let (tx_result, bytecodes, modified_storage, call_traces) = inspect_inner(
         tx,
         storage_ptr,
         L2ChainId::from(chain_id_u32),
         u64::max(env.block.basefee.to::<u64>(), 1000),
         ccx,
     );
+    if let Some(metrics) = metrics {
+        metrics.ergs_used += tx_result.statistics.computational_gas_used as u64;
+        metrics.pubdata_published += tx_result.statistics.pubdata_published as u64;
+        metrics.gas_refunded += tx_result.refunds.gas_refunded as u64;
+    }
 
     let execution_result = match tx_result.result {
         ExecutionResult::Success { output, .. } => {
@@ -353,6 +444,25 @@ where
         }
     };
 
+    if let Some(storage_writes) = storage_writes {
+        if matches!(execution_result, rExecutionResult::Success { .. }) {
+            storage_writes.extend(modified_storage.iter().map(|(k, v)| ZkStorageWrite {
+                address: k.address().to_address(),
+                slot: k.key().to_ru256(),
+                value: v.to_ru256(),
+            }));
+        }
+    }
+
+    if let Some(fuzz_values) = fuzz_values {
+        let success = matches!(execution_result, rExecutionResult::Success { .. });
+        collect_fuzz_values(fuzz_values, &call_traces, success.then_some(&modified_storage));
+    }
+
+    if let Some(delegators) = delegators {
+        collect_delegators(delegators, &call_traces);
+    }
+
     let mut storage: rHashMap<Address, rHashMap<rU256, StorageSlot>> = Default::default();
     let mut codes: rHashMap<Address, (B256, Bytecode)> = Default::default();
     for (k, v) in &modified_storage {
@@ -412,13 +522,17 @@ where
     Ok(execution_result)
 }
 
+/// The result of a transaction, the bytecodes it published, the storage it modified and its calls.
+type InspectResult =
+    (VmExecutionResultAndLogs, HashMap<U256, Vec<U256>>, HashMap<StorageKey, H256>, Vec<Call>);
+
 fn inspect_inner<S: ReadStorage + Send>(
     l2_tx: L2Tx,
     storage: StoragePtr<StorageView<S>>,
     chain_id: L2ChainId,
     l1_gas_price: u64,
     mut ccx: CheatcodeTracerContext,
-) -> (VmExecutionResultAndLogs, HashMap<U256, Vec<U256>>, HashMap<StorageKey, H256>) {
+) -> InspectResult {
     let batch_env = create_l1_batch_env(storage.clone(), l1_gas_price);
 
     let system_contracts = SystemContracts::from_options(&Options::BuiltInWithoutSecurity);
@@ -445,6 +559,11 @@ fn inspect_inner<S: ReadStorage + Send>(
             expected_calls,
             result: cheatcode_tracer_result.clone(),
             caller: l2_tx.common_data.initiator_address.to_address(),
+            limits: ccx.limits,
+            system_call_target: ccx
+                .system_call
+                .then_some(l2_tx.execute.contract_address.to_address()),
+            ..Default::default()
         }
         .into_tracer_pointer(),
     ];
@@ -471,6 +590,11 @@ fn inspect_inner<S: ReadStorage + Send>(
     if let Some(expected_calls) = ccx.expected_calls.as_mut() {
         expected_calls.extend(cheatcode_result.expected_calls);
     }
+    if let (Some(limit_exceeded), Some(exceeded)) =
+        (ccx.limit_exceeded.as_mut(), cheatcode_result.limit_exceeded)
+    {
+        **limit_exceeded = Some(exceeded);
+    }
 
     formatter::print_vm_details(&tx_result);
 
@@ -504,7 +628,59 @@ fn inspect_inner<S: ReadStorage + Send>(
         .map(|b| bytecode_to_factory_dep(b.original.clone()))
         .collect();
     let modified_keys = storage.borrow().modified_storage_keys().clone();
-    (tx_result, bytecodes, modified_keys)
+    (tx_result, bytecodes, modified_keys, call_traces)
+}
+
+/// Collects the data the system contracts returned to the calls of `call_traces`, and the storage
+/// written by the contracts and the bytecode hashes deployed, if the execution succeeded.
+fn collect_fuzz_values(
+    fuzz_values: &mut ZkFuzzValues,
+    call_traces: &[Call],
+    modified_storage: Option<&HashMap<StorageKey, H256>>,
+) {
+    fn visit(fuzz_values: &mut ZkFuzzValues, call: &Call) {
+        if is_system_address(call.to.to_address()) && !call.output.is_empty() {
+            fuzz_values.system_return_data.push(Bytes::from(call.output.clone()));
+        }
+        for call in &call.calls {
+            visit(fuzz_values, call);
+        }
+    }
+
+    for call in call_traces {
+        visit(fuzz_values, call);
+    }
+
+    for (key, value) in modified_storage.into_iter().flatten() {
+        if key.address() == &ACCOUNT_CODE_STORAGE_ADDRESS {
+            if !is_system_address(key.key().to_h160().to_address()) && !value.is_zero() {
+                fuzz_values.bytecode_hashes.push(B256::from(value.0));
+            }
+        } else if !is_system_address(key.address().to_address()) {
+            fuzz_values.storage.push((key.key().to_ru256(), value.to_ru256()));
+        }
+    }
+}
+
+/// Collects the contracts of `call_traces` which delegated a call, except the system contracts.
+///
+/// A delegate call keeps the address of the contract delegating it, whose storage it runs on.
+fn collect_delegators(delegators: &mut Vec<Address>, call_traces: &[Call]) {
+    fn visit(delegators: &mut Vec<Address>, call: &Call) {
+        if matches!(call.r#type, CallType::Call(FarCallOpcode::Delegate)) {
+            let delegator = call.to.to_address();
+            if !is_system_address(delegator) && !delegators.contains(&delegator) {
+                delegators.push(delegator);
+            }
+        }
+        for call in &call.calls {
+            visit(delegators, call);
+        }
+    }
+
+    for call in call_traces {
+        visit(delegators, call);
+    }
 }
 
 struct ConsoleLogParser {
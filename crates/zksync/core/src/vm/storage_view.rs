@@ -26,7 +26,8 @@ pub(crate) struct StorageView<S> {
     // Cache for `contains_key()` checks. The cache is only valid within one L1 batch execution.
     initial_writes_cache: HashMap<StorageKey, bool>,
 
-    caller: H160,
+    /// The caller whose account code is hidden, so it's treated as an EOA, if any.
+    caller: Option<H160>,
 }
 
 impl<S: ReadStorage + fmt::Debug> StorageView<S> {
@@ -34,7 +35,7 @@ impl<S: ReadStorage + fmt::Debug> StorageView<S> {
     pub(crate) fn new(
         storage_handle: S,
         modified_storage_keys: HashMap<StorageKey, StorageValue>,
-        caller: H160,
+        caller: Option<H160>,
     ) -> Self {
         Self {
             storage_handle,
@@ -73,7 +74,9 @@ impl<S: ReadStorage + fmt::Debug> ReadStorage for StorageView<S> {
         let value = self.get_value_no_log(key);
 
         // We override the caller's account code storage to allow for calls
-        if key.address() == &ACCOUNT_CODE_STORAGE_ADDRESS && key.key() == &self.caller.to_h256() {
+        if key.address() == &ACCOUNT_CODE_STORAGE_ADDRESS &&
+            self.caller.is_some_and(|caller| key.key() == &caller.to_h256())
+        {
             let value = StorageValue::zero();
             tracing::trace!(
                 "override read value {:?} {:?} ({:?}/{:?})",
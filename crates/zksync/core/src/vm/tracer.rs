@@ -1,19 +1,27 @@
 use std::{
     collections::{BTreeMap, HashMap},
+    fmt,
     sync::Arc,
 };
 
-use alloy_primitives::{hex, Address, Bytes, U256 as rU256};
+use alloy_primitives::{hex, Address, Bytes, B256, U256 as rU256};
 use foundry_cheatcodes_common::{
     expect::ExpectedCallTracker,
     mock::{MockCallDataContext, MockCallReturnData},
 };
 use multivm::{
-    interface::{dyn_tracers::vm_1_4_1::DynTracer, tracer::TracerExecutionStatus},
+    interface::{
+        dyn_tracers::vm_1_4_1::DynTracer,
+        tracer::{TracerExecutionStatus, TracerExecutionStopReason},
+        Halt,
+    },
     vm_latest::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
     zk_evm_latest::{
         tracing::{AfterDecodingData, AfterExecutionData, BeforeExecutionData, VmLocalStateData},
-        zkevm_opcode_defs::{FatPointer, Opcode, CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER},
+        zkevm_opcode_defs::{
+            FatPointer, Opcode, CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER,
+            CALL_IMPLICIT_CONSTRUCTOR_MARKER_REGISTER,
+        },
     },
 };
 use once_cell::sync::OnceCell;
@@ -27,6 +35,13 @@ use super::farcall::FarCallHandler;
 /// extendedAccountVersion(address)
 const SELECTOR_ACCOUNT_VERSION: [u8; 4] = hex!("bb0fd610");
 
+/// How many cycles to execute between checks of the memory limit, as measuring the memory
+/// isn't free.
+const MEMORY_CHECK_INTERVAL: u64 = 1024;
+
+/// The bit of the call flags, passed to a new frame in `r2`, that marks a system call.
+const SYSTEM_CALL_FLAG: u64 = 1 << 1;
+
 /// Represents the context for [CheatcodeContext]
 #[derive(Debug, Default)]
 pub struct CheatcodeTracerContext<'a> {
@@ -34,11 +49,116 @@ pub struct CheatcodeTracerContext<'a> {
     pub mocked_calls: HashMap<Address, BTreeMap<MockCallDataContext, MockCallReturnData>>,
     /// Expected calls recorder.
     pub expected_calls: Option<&'a mut ExpectedCallTracker>,
+    /// Resource limits of the execution.
+    pub limits: ZkResourceLimits,
+    /// Records the resource limit the execution was aborted for, if any.
+    pub limit_exceeded: Option<&'a mut Option<ZkResourceLimitExceeded>>,
+    /// Whether the target of the transaction is called with the system call flag, like system
+    /// contracts call each other.
+    pub system_call: bool,
+    /// The paymaster paying for the transaction, if any.
+    pub paymaster: Option<ZkPaymasterData>,
+    /// The signature of the transaction, if the caller is a registered account validating and
+    /// executing it with its own code instead of the default account's.
+    pub custom_account: Option<Bytes>,
+    /// Accumulates the pubdata and refunds of the execution, if recorded.
+    pub metrics: Option<&'a mut ZkExecutionMetrics>,
+    /// Records the storage slots written by a successful execution, if recorded.
+    pub storage_writes: Option<&'a mut Vec<ZkStorageWrite>>,
+    /// Collects the values observed by the execution for the fuzz dictionary, if recorded.
+    pub fuzz_values: Option<&'a mut ZkFuzzValues>,
+    /// Collects the contracts which delegated a call during the execution, the proxies among
+    /// them, if recorded.
+    pub delegators: Option<&'a mut Vec<Address>>,
+}
+
+/// The L2 costs of ZK-VM executions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkExecutionMetrics {
+    /// The ergs spent by the computation, without the gas of the transaction charged for its
+    /// pubdata and overhead.
+    pub ergs_used: u64,
+    /// The bytes of pubdata published on L1.
+    pub pubdata_published: u64,
+    /// The gas refunded by the operator.
+    pub gas_refunded: u64,
+}
+
+/// A storage slot written by a ZK-VM execution, with its value at the end of the execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZkStorageWrite {
+    /// The account whose storage was written.
+    pub address: Address,
+    /// The written slot.
+    pub slot: rU256,
+    /// The value of the slot at the end of the execution.
+    pub value: rU256,
+}
+
+/// The values observed by ZK-VM executions, feeding the fuzz dictionary like the storage and the
+/// logs of the EVM calls.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZkFuzzValues {
+    /// The slots of the contracts written by successful executions, and their values.
+    pub storage: Vec<(rU256, rU256)>,
+    /// The bytecode hashes of the contracts deployed by successful executions.
+    pub bytecode_hashes: Vec<B256>,
+    /// The data returned by the system contracts to the calls of the executions.
+    pub system_return_data: Vec<Bytes>,
+}
+
+impl ZkFuzzValues {
+    /// Returns whether no values were observed.
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty() &&
+            self.bytecode_hashes.is_empty() &&
+            self.system_return_data.is_empty()
+    }
+}
+
+/// A paymaster paying the fees of ZK-VM transactions, and the input it validates them with.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZkPaymasterData {
+    /// The address of the paymaster.
+    pub address: Address,
+    /// The input passed to the paymaster, e.g. an encoded `IPaymasterFlow` call.
+    pub input: Bytes,
+}
+
+/// Resource limits of a single execution on the ZK-VM.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkResourceLimits {
+    /// Maximum number of VM cycles, unlimited if `None`.
+    pub max_cycles: Option<u64>,
+    /// Maximum size of the VM memory in bytes, unlimited if `None`.
+    pub max_memory: Option<u64>,
+}
+
+/// A resource limit an execution on the ZK-VM was aborted for.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ZkResourceLimitExceeded {
+    /// The execution ran for more cycles than allowed.
+    Cycles { limit: u64 },
+    /// The execution used more memory than allowed.
+    Memory { used: u64, limit: u64 },
+}
+
+impl fmt::Display for ZkResourceLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycles { limit } => write!(f, "cycle limit of {limit} exceeded"),
+            Self::Memory { used, limit } => {
+                write!(f, "memory limit of {limit} bytes exceeded (used {used} bytes)")
+            }
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct CheatcodeTracerResult {
     pub expected_calls: ExpectedCallTracker,
+    pub limit_exceeded: Option<ZkResourceLimitExceeded>,
 }
 
 #[derive(Debug, Default)]
@@ -47,9 +167,39 @@ pub struct CheatcodeTracer {
     pub mocked_calls: HashMap<Address, BTreeMap<MockCallDataContext, MockCallReturnData>>,
     pub expected_calls: ExpectedCallTracker,
     pub caller: Address,
+    pub limits: ZkResourceLimits,
+    pub cycles: u64,
+    pub limit_exceeded: Option<ZkResourceLimitExceeded>,
+    /// The target of the transaction, whose frame is entered with the system call flag.
+    pub system_call_target: Option<Address>,
+    /// Whether to set the system call flag of the frame entered in the current cycle.
+    pub set_system_call_flag: bool,
     pub result: Arc<OnceCell<CheatcodeTracerResult>>,
 }
 
+impl CheatcodeTracer {
+    /// Returns the resource limit exceeded by the execution so far, if any.
+    fn check_limits<S: WriteStorage, H: HistoryMode>(
+        &self,
+        state: &ZkSyncVmState<S, H>,
+    ) -> Option<ZkResourceLimitExceeded> {
+        if let Some(limit) = self.limits.max_cycles {
+            if self.cycles > limit {
+                return Some(ZkResourceLimitExceeded::Cycles { limit })
+            }
+        }
+        if let Some(limit) = self.limits.max_memory {
+            if self.cycles % MEMORY_CHECK_INTERVAL == 0 {
+                let used = state.memory.get_size() as u64;
+                if used > limit {
+                    return Some(ZkResourceLimitExceeded::Memory { used, limit })
+                }
+            }
+        }
+        None
+    }
+}
+
 impl<S: Send, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for CheatcodeTracer {
     fn before_decoding(&mut self, _state: VmLocalStateData<'_>, _memory: &SimpleMemory<H>) {}
 
@@ -97,6 +247,16 @@ impl<S: Send, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for CheatcodeTracer
             }
         }
 
+        // Marks the first call to the target of a system call transaction, i.e. the one made by
+        // the account of the caller
+        if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
+            let current = state.vm_local_state.callstack.current;
+            if self.system_call_target == Some(current.code_address.to_address()) {
+                self.system_call_target = None;
+                self.set_system_call_flag = true;
+            }
+        }
+
         // Checks contract calls for expectCall cheatcode
         if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
             let current = state.vm_local_state.callstack.current;
@@ -162,6 +322,20 @@ impl<S: WriteStorage + Send, H: HistoryMode> VmTracer<S, H> for CheatcodeTracer
     ) -> TracerExecutionStatus {
         self.farcall_handler.maybe_return_early(state, bootloader_state);
 
+        if std::mem::take(&mut self.set_system_call_flag) {
+            let register = CALL_IMPLICIT_CONSTRUCTOR_MARKER_REGISTER as usize;
+            let flags = &mut state.local_state.registers[register];
+            flags.value = flags.value | U256::from(SYSTEM_CALL_FLAG);
+        }
+
+        self.cycles += 1;
+        if let Some(exceeded) = self.check_limits(state) {
+            tracing::warn!(%exceeded, "aborting zk vm execution");
+            let reason = Halt::TracerCustom(exceeded.to_string());
+            self.limit_exceeded = Some(exceeded);
+            return TracerExecutionStatus::Stop(TracerExecutionStopReason::Abort(reason))
+        }
+
         TracerExecutionStatus::Continue
     }
 
@@ -172,7 +346,11 @@ impl<S: WriteStorage + Send, H: HistoryMode> VmTracer<S, H> for CheatcodeTracer
         _stop_reason: multivm::interface::tracer::VmExecutionStopReason,
     ) {
         let cell = self.result.as_ref();
-        cell.set(CheatcodeTracerResult { expected_calls: self.expected_calls.clone() }).unwrap();
+        cell.set(CheatcodeTracerResult {
+            expected_calls: self.expected_calls.clone(),
+            limit_exceeded: self.limit_exceeded.clone(),
+        })
+        .unwrap();
     }
 }
 
@@ -528,3 +528,40 @@ contract ScriptAdditionalContracts is DSTest {
         new Parent();
     }
 }
+
+contract EventCounter {
+    event Incremented(uint256 value);
+
+    uint256 public value;
+
+    function increment() public {
+        value += 1;
+        emit Incremented(value);
+    }
+}
+
+contract BroadcastExpectEvent is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function expectEmitted() public {
+        vm.startBroadcast();
+        EventCounter counter = new EventCounter();
+
+        bytes32[] memory topics = new bytes32[](1);
+        topics[0] = keccak256("Incremented(uint256)");
+        vm.expectBroadcastEvent(address(counter), topics, abi.encode(uint256(1)));
+        counter.increment();
+        vm.stopBroadcast();
+    }
+
+    function expectNotEmitted() public {
+        vm.startBroadcast();
+        EventCounter counter = new EventCounter();
+
+        bytes32[] memory topics = new bytes32[](1);
+        topics[0] = keccak256("Decremented(uint256)");
+        vm.expectBroadcastEvent(address(counter), topics);
+        counter.increment();
+        vm.stopBroadcast();
+    }
+}
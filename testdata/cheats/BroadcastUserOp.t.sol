@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract UserOpTarget {
+    uint256 public value;
+
+    function setValue(uint256 _value) public {
+        value = _value;
+    }
+}
+
+contract BroadcastUserOpTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    address constant ENTRY_POINT = 0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789;
+
+    UserOpTarget target;
+
+    function setUp() public {
+        target = new UserOpTarget();
+    }
+
+    function testBroadcastUserOpIsNotExecuted() public {
+        bytes memory callData = abi.encodeWithSignature(
+            "execute(address,uint256,bytes)",
+            address(target),
+            uint256(0),
+            abi.encodeCall(UserOpTarget.setValue, (1))
+        );
+
+        vm.broadcastUserOp(ENTRY_POINT, address(0x4337), callData);
+        vm.broadcastUserOp(ENTRY_POINT, address(0x4337), callData, address(this));
+
+        assertEq(target.value(), 0);
+    }
+}
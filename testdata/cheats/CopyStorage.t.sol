@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract Slots {
+    uint256 public a;
+    uint256 public b;
+
+    function setA(uint256 value) public {
+        a = value;
+    }
+
+    function setB(uint256 value) public {
+        b = value;
+    }
+}
+
+contract CopyStorageTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+    Slots source;
+    Slots target;
+
+    function setUp() public {
+        source = new Slots();
+        source.setA(1);
+        target = new Slots();
+        target.setB(2);
+    }
+
+    function testCopyStorage() public {
+        vm.copyStorage(address(source), address(target));
+        assertEq(target.a(), 1, "slot of the source not copied");
+        assertEq(target.b(), 0, "slot of the target not cleared");
+        assertEq(source.a(), 1, "source changed");
+    }
+
+    function testCopyStorageChangedInTest() public {
+        source.setB(3);
+        target.setA(4);
+        vm.copyStorage(address(source), address(target));
+        assertEq(target.a(), 1, "slot of the source not copied");
+        assertEq(target.b(), 3, "slot changed in the test not copied");
+
+        target.setA(5);
+        assertEq(source.a(), 1, "storage not copied");
+    }
+
+    function testCopyStorageNotAvailableOnPrecompiles() public {
+        vm.expectRevert(bytes("cannot call `copyStorage` on precompile 0x0000000000000000000000000000000000000001"));
+        this._copyStorage(address(source), address(1));
+    }
+
+    function _copyStorage(address from, address to) public {
+        vm.copyStorage(from, to);
+    }
+}
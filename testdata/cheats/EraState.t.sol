@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract EraStateTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testLoadEraState() public {
+        string memory path = string.concat(vm.projectRoot(), "/fixtures/EraState/state.json");
+
+        vm.loadEraState(path);
+
+        assertEq(vm.load(address(0x420), bytes32(uint256(1))), bytes32(uint256(0xbeef)));
+    }
+
+    function testDumpAndLoadEraState() public {
+        // Path to temporary file that is deleted after the test
+        string memory path = string.concat(vm.projectRoot(), "/fixtures/Json/test_dump_era_state.json");
+
+        address target = address(1001);
+        uint256 snapshotId = vm.snapshot();
+        vm.store(target, bytes32(uint256(0x20)), bytes32(uint256(0x40)));
+
+        vm.dumpEraState(path);
+        string memory json = vm.readFile(path);
+        assertEq(vm.parseJsonString(json, ".version"), "1");
+
+        vm.revertTo(snapshotId);
+        assertEq(vm.load(target, bytes32(uint256(0x20))), bytes32(0));
+
+        vm.loadEraState(path);
+        assertEq(vm.load(target, bytes32(uint256(0x20))), bytes32(uint256(0x40)));
+
+        vm.removeFile(path);
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract BroadcastEmitter {
+    event Something(uint256 value);
+
+    function emitSomething(uint256 value) public {
+        emit Something(value);
+    }
+}
+
+contract ExpectBroadcastEventTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    BroadcastEmitter emitter;
+
+    function setUp() public {
+        emitter = new BroadcastEmitter();
+    }
+
+    function testExpectBroadcastEvent() public {
+        bytes32[] memory topics = new bytes32[](1);
+        topics[0] = keccak256("Something(uint256)");
+        vm.expectBroadcastEvent(address(emitter), topics, abi.encode(uint256(1)));
+
+        vm.broadcast();
+        emitter.emitSomething(1);
+    }
+
+    function testFailExpectBroadcastEventWithoutBroadcast() public {
+        bytes32[] memory topics = new bytes32[](1);
+        topics[0] = keccak256("Something(uint256)");
+        vm.expectBroadcastEvent(address(emitter), topics);
+
+        emitter.emitSomething(1);
+    }
+
+    function testFailExpectBroadcastEventTooManyTopics() public {
+        bytes32[] memory topics = new bytes32[](5);
+        vm.expectBroadcastEvent(address(emitter), topics);
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract Store {
+    uint256 public a;
+    uint256 public b;
+
+    function setA(uint256 value) public {
+        a = value;
+    }
+
+    function setBoth(uint256 valueA, uint256 valueB) public {
+        a = valueA;
+        b = valueB;
+    }
+}
+
+contract StoreCaller {
+    function setA(Store store, uint256 value) public {
+        store.setA(value);
+    }
+}
+
+contract ExpectStorageWriteTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    Store store;
+
+    function setUp() public {
+        store = new Store();
+    }
+
+    function exposed_setA(uint256 expected, uint256 value) public {
+        vm.expectStorageWrite(address(store), bytes32(0), bytes32(expected));
+        store.setA(value);
+    }
+
+    function testExpectStorageWrite() public {
+        vm.expectStorageWrite(address(store), bytes32(0), bytes32(uint256(7)));
+        store.setA(7);
+    }
+
+    function testExpectStorageWrites() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(1)), bytes32(uint256(2)));
+        vm.expectStorageWrite(address(store), bytes32(0), bytes32(uint256(1)));
+        store.setBoth(1, 2);
+    }
+
+    function testExpectStorageWriteNested() public {
+        StoreCaller caller = new StoreCaller();
+        vm.expectStorageWrite(address(store), bytes32(0), bytes32(uint256(7)));
+        caller.setA(store, 7);
+    }
+
+    function testExpectStorageWriteIgnoresStaticCalls() public {
+        vm.expectStorageWrite(address(store), bytes32(0), bytes32(uint256(7)));
+        require(store.a() == 0, "unexpected value");
+        store.setA(7);
+    }
+
+    function testExpectStorageWriteWrongValue() public {
+        string memory storeAddress = vm.toString(address(store));
+        vm.expectRevert(
+            bytes(
+                string.concat(
+                    "expected a write of ",
+                    vm.toString(bytes32(uint256(7))),
+                    " to slot ",
+                    vm.toString(bytes32(0)),
+                    " of ",
+                    storeAddress,
+                    ", but the writes to ",
+                    storeAddress,
+                    " were: ",
+                    vm.toString(bytes32(0)),
+                    " = ",
+                    vm.toString(bytes32(uint256(8)))
+                )
+            )
+        );
+        this.exposed_setA(7, 8);
+    }
+
+    function testFailExpectStorageWriteNotWritten() public {
+        vm.expectStorageWrite(address(store), bytes32(uint256(1)), bytes32(uint256(7)));
+        store.setA(7);
+    }
+
+    function testFailExpectStorageWriteWithoutCall() public {
+        vm.expectStorageWrite(address(store), bytes32(0), bytes32(uint256(7)));
+    }
+}
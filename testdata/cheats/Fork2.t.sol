@@ -220,6 +220,22 @@ contract ForkTest is DSTest {
         }
     }
 
+    function testMulticallStatic() public {
+        vm.selectFork(mainnetFork);
+        address weth = address(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2);
+
+        Vm.StaticCall[] memory calls = new Vm.StaticCall[](2);
+        calls[0] = Vm.StaticCall(weth, abi.encodeWithSignature("decimals()"));
+        // Multicall3 has no WETH to withdraw
+        calls[1] = Vm.StaticCall(weth, abi.encodeWithSignature("withdraw(uint256)", 1));
+        Vm.StaticCallResult[] memory results = vm.multicallStatic(calls);
+
+        assertEq(results.length, 2);
+        assertTrue(results[0].success);
+        assertEq(abi.decode(results[0].returnData, (uint8)), 18);
+        assertTrue(!results[1].success);
+    }
+
     function testRpc() public {
         // balance at block <https://etherscan.io/block/18332681>
         vm.selectFork(mainnetFork);
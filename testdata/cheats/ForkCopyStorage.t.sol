@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract ForkSlots {
+    uint256 public a;
+
+    function setA(uint256 value) public {
+        a = value;
+    }
+}
+
+contract ForkCopyStorageTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+    address constant WETH = 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2;
+
+    function setUp() public {
+        vm.createSelectFork("rpcAlias", 19_000_000);
+    }
+
+    function testCopyStorageOfLocalContractsOnFork() public {
+        ForkSlots source = new ForkSlots();
+        source.setA(1);
+        ForkSlots target = new ForkSlots();
+        vm.copyStorage(address(source), address(target));
+        assertEq(target.a(), 1, "slot of the source not copied");
+    }
+
+    function testCopyStorageFromForkContractFails() public {
+        ForkSlots target = new ForkSlots();
+        vm.expectRevert(
+            bytes(
+                "cannot copy the storage of 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2, a contract of the fork: its full storage can't be read over RPC"
+            )
+        );
+        this._copyStorage(WETH, address(target));
+    }
+
+    function testCopyStorageToForkContractFails() public {
+        ForkSlots source = new ForkSlots();
+        vm.expectRevert(
+            bytes(
+                "cannot copy the storage to 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2, a contract of the fork: its slots can't be cleared without reading its full storage over RPC"
+            )
+        );
+        this._copyStorage(address(source), WETH);
+    }
+
+    function _copyStorage(address from, address to) public {
+        vm.copyStorage(from, to);
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract GetChainTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testGetEraChain() public {
+        Vm.Chain memory chain = vm.getChain("zksync");
+        assertEq(chain.name, "zkSync Era");
+        assertEq(chain.chainId, 324);
+        assertEq(chain.chainAlias, "zksync");
+        assertEq(chain.rpcUrl, "https://mainnet.era.zksync.io");
+        assertEq(chain.explorerApiUrl, "https://block-explorer-api.mainnet.zksync.io/api");
+        assertEq(chain.baseToken.symbol, "ETH");
+        assertEq(chain.baseToken.decimals, 18);
+        assertTrue(chain.isEra);
+        assertTrue(!chain.isValidium);
+        assertEq(chain.settlementChainId, 1);
+    }
+
+    function testGetChainById() public {
+        Vm.Chain memory chain = vm.getChain(50104);
+        assertEq(chain.chainAlias, "sophon");
+        assertEq(chain.baseToken.symbol, "SOPH");
+        assertTrue(chain.isEra);
+        assertTrue(chain.isValidium);
+
+        chain = vm.getChain(1);
+        assertEq(chain.chainAlias, "mainnet");
+        assertTrue(!chain.isEra);
+        assertEq(chain.settlementChainId, 0);
+    }
+
+    function testRevertsOnUnknownChain() public {
+        vm._expectCheatcodeRevert("unknown chain alias: unknown");
+        vm.getChain("unknown");
+
+        vm._expectCheatcodeRevert("unknown chain ID: 42424242");
+        vm.getChain(42424242);
+    }
+}
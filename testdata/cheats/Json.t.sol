@@ -174,6 +174,54 @@ contract ParseJsonTest is DSTest {
         vm._expectCheatcodeRevert("key \".*\" must return exactly one JSON object");
         vm.parseJsonKeys(jsonString, ".*");
     }
+
+    struct Module {
+        string name;
+        bool enabled;
+    }
+
+    struct Deployment {
+        address owner;
+        uint256 amount;
+        Module[] modules;
+    }
+
+    bytes constant DEPLOYMENT_SCHEMA = "(address owner,uint256 amount,(string name,bool enabled)[] modules)";
+
+    function test_parseJsonAs() public {
+        string memory jsonString =
+            '{"modules": [{"enabled": true, "name": "vault"}], "owner": "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D", "amount": "0x10"}';
+
+        bytes memory data = vm.parseJsonAs(DEPLOYMENT_SCHEMA, jsonString);
+        Deployment memory deployment = abi.decode(data, (Deployment));
+        assertEq(deployment.owner, 0x7109709ECfa91a80626fF3989D68f67F5b1DD12D);
+        assertEq(deployment.amount, 16);
+        assertEq(deployment.modules.length, 1);
+        assertEq(deployment.modules[0].name, "vault");
+        assertTrue(deployment.modules[0].enabled);
+    }
+
+    function test_parseJsonAsRevert() public {
+        vm._expectCheatcodeRevert("missing key \"$.modules\"");
+        vm.parseJsonAs(DEPLOYMENT_SCHEMA, '{"owner": "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D", "amount": 1}');
+
+        vm._expectCheatcodeRevert("missing key \"$.modules[0].enabled\"");
+        vm.parseJsonAs(
+            DEPLOYMENT_SCHEMA,
+            '{"owner": "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D", "amount": 1, "modules": [{"name": "vault"}]}'
+        );
+
+        vm._expectCheatcodeRevert("unexpected key \"$.extra\" not in schema");
+        vm.parseJsonAs(
+            DEPLOYMENT_SCHEMA,
+            '{"owner": "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D", "amount": 1, "modules": [], "extra": 1}'
+        );
+
+        vm._expectCheatcodeRevert("expected `uint256` at \"$.amount\", found an object");
+        vm.parseJsonAs(
+            DEPLOYMENT_SCHEMA, '{"owner": "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D", "amount": {}, "modules": []}'
+        );
+    }
 }
 
 contract WriteJsonTest is DSTest {
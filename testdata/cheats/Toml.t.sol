@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract WriteTomlTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function test_writeToml() public {
+        string memory obj = "toml";
+        string memory path = "fixtures/Toml/write_test.toml";
+        vm.serializeUint(obj, "a", uint256(123));
+        string memory json = vm.serializeString(obj, "b", "test");
+        vm.writeToml(json, path);
+        assertEq(vm.readFile(path), 'a = 123\nb = "test"\n');
+
+        // replace a single value at key b
+        vm.writeToml(vm.toString(address(0xBEEF)), path, ".b");
+        assertEq(vm.readFile(path), 'a = 123\nb = "0x000000000000000000000000000000000000bEEF"\n');
+    }
+
+    function test_writeTomlLargeUint() public {
+        string memory path = "fixtures/Toml/write_uint_test.toml";
+        string memory json = vm.serializeUint("tomlUint", "max", type(uint256).max);
+        vm.writeToml(json, path);
+        string memory toml = vm.readFile(path);
+        assertEq(
+            toml, 'max = "115792089237316195423570985008687907853269984665640564039457584007913129639935"\n'
+        );
+    }
+
+    function test_writeTomlRevert() public {
+        vm._expectCheatcodeRevert("only JSON objects can be written as TOML documents");
+        vm.writeToml("[1, 2]", "fixtures/Toml/write_test.toml");
+
+        vm._expectCheatcodeRevert("TOML has no null value, found null at \"$.a\"");
+        vm.writeToml('{"a": null}', "fixtures/Toml/write_test.toml");
+    }
+}
@@ -86,6 +86,24 @@ interface Vm {
         uint256 chainId;
     }
 
+    struct BaseToken {
+        string name;
+        string symbol;
+        uint8 decimals;
+    }
+
+    struct Chain {
+        string name;
+        uint256 chainId;
+        string chainAlias;
+        string rpcUrl;
+        string explorerApiUrl;
+        BaseToken baseToken;
+        bool isEra;
+        bool isValidium;
+        uint256 settlementChainId;
+    }
+
     struct AccountAccess {
         ChainInfo chainInfo;
         AccountAccessKind kind;
@@ -111,6 +129,40 @@ interface Vm {
         bool reverted;
     }
 
+    struct L2ToL1Message {
+        address sender;
+        bytes data;
+        uint256 batchNumber;
+        uint256 index;
+        uint16 txNumberInBatch;
+        bytes32[] proof;
+    }
+
+    struct L2ToL1Log {
+        uint8 l2ShardId;
+        bool isService;
+        uint16 txNumberInBatch;
+        address sender;
+        bytes32 key;
+        bytes32 value;
+    }
+
+    struct PriorityRequestResult {
+        bytes32 txHash;
+        bool success;
+        bytes returnData;
+    }
+
+    struct StaticCall {
+        address target;
+        bytes callData;
+    }
+
+    struct StaticCallResult {
+        bool success;
+        bytes returnData;
+    }
+
     function _expectCheatcodeRevert() external;
     function _expectCheatcodeRevert(bytes4 revertData) external;
     function _expectCheatcodeRevert(bytes calldata revertData) external;
@@ -269,6 +321,8 @@ interface Vm {
     function assume(bool condition) external pure;
     function breakpoint(string calldata char) external;
     function breakpoint(string calldata char, bool value) external;
+    function broadcastUserOp(address entryPoint, address account, bytes calldata callData) external;
+    function broadcastUserOp(address entryPoint, address account, bytes calldata callData, address signer) external;
     function broadcast() external;
     function broadcast(address signer) external;
     function broadcast(uint256 privateKey) external;
@@ -284,6 +338,7 @@ interface Vm {
     function computeCreateAddress(address deployer, uint256 nonce) external pure returns (address);
     function cool(address target) external;
     function copyFile(string calldata from, string calldata to) external returns (uint64 copied);
+    function copyStorage(address from, address to) external;
     function createDir(string calldata path, bool recursive) external;
     function createFork(string calldata urlOrAlias) external returns (uint256 forkId);
     function createFork(string calldata urlOrAlias, uint256 blockNumber) external returns (uint256 forkId);
@@ -311,6 +366,7 @@ interface Vm {
         pure
         returns (uint256 privateKey);
     function difficulty(uint256 newDifficulty) external;
+    function dumpEraState(string calldata pathToStateJson) external;
     function dumpState(string calldata pathToStateJson) external;
     function envAddress(string calldata name) external view returns (address value);
     function envAddress(string calldata name, string calldata delim) external view returns (address[] memory value);
@@ -366,6 +422,8 @@ interface Vm {
         external
         returns (EthGetLogs[] memory logs);
     function exists(string calldata path) external returns (bool result);
+    function expectBroadcastEvent(address emitter, bytes32[] calldata topics) external;
+    function expectBroadcastEvent(address emitter, bytes32[] calldata topics, bytes calldata data) external;
     function expectCallMinGas(address callee, uint256 msgValue, uint64 minGas, bytes calldata data) external;
     function expectCallMinGas(address callee, uint256 msgValue, uint64 minGas, bytes calldata data, uint64 count)
         external;
@@ -385,13 +443,17 @@ interface Vm {
     function expectRevert(bytes calldata revertData) external;
     function expectSafeMemory(uint64 min, uint64 max) external;
     function expectSafeMemoryCall(uint64 min, uint64 max) external;
+    function expectStorageWrite(address target, bytes32 slot, bytes32 value) external;
     function fee(uint256 newBasefee) external;
     function ffi(string[] calldata commandInput) external returns (bytes memory result);
     function fsMetadata(string calldata path) external view returns (FsMetadata memory metadata);
     function getBlockNumber() external view returns (uint256 height);
     function getBlockTimestamp() external view returns (uint256 timestamp);
+    function getChain(string calldata chainAlias) external view returns (Chain memory chain);
+    function getChain(uint256 chainId) external view returns (Chain memory chain);
     function getCode(string calldata artifactPath) external view returns (bytes memory creationBytecode);
     function getDeployedCode(string calldata artifactPath) external view returns (bytes memory runtimeBytecode);
+    function getFixture(string calldata fixture) external view returns (address fixtureAddress);
     function getLabel(address account) external view returns (string memory currentLabel);
     function getMappingKeyAndParentOf(address target, bytes32 elementSlot)
         external
@@ -408,6 +470,7 @@ interface Vm {
     function label(address account, string calldata newLabel) external;
     function load(address target, bytes32 slot) external view returns (bytes32 data);
     function loadAllocs(string calldata pathToAllocsJson) external;
+    function loadEraState(string calldata pathToStateJson) external;
     function makePersistent(address account) external;
     function makePersistent(address account0, address account1) external;
     function makePersistent(address account0, address account1, address account2) external;
@@ -417,6 +480,7 @@ interface Vm {
         external;
     function mockCall(address callee, bytes calldata data, bytes calldata returnData) external;
     function mockCall(address callee, uint256 msgValue, bytes calldata data, bytes calldata returnData) external;
+    function multicallStatic(StaticCall[] calldata calls) external returns (StaticCallResult[] memory results);
     function parseAddress(string calldata stringifiedValue) external pure returns (address parsedValue);
     function parseBool(string calldata stringifiedValue) external pure returns (bool parsedValue);
     function parseBytes(string calldata stringifiedValue) external pure returns (bytes memory parsedValue);
@@ -427,6 +491,10 @@ interface Vm {
         external
         pure
         returns (address[] memory);
+    function parseJsonAs(bytes calldata schemaId, string calldata json)
+        external
+        pure
+        returns (bytes memory abiEncodedData);
     function parseJsonBool(string calldata json, string calldata key) external pure returns (bool);
     function parseJsonBoolArray(string calldata json, string calldata key) external pure returns (bool[] memory);
     function parseJsonBytes(string calldata json, string calldata key) external pure returns (bytes memory);
@@ -451,6 +519,10 @@ interface Vm {
     function prank(address msgSender, address txOrigin) external;
     function prevrandao(bytes32 newPrevrandao) external;
     function projectRoot() external view returns (string memory path);
+    function prompt(string calldata promptText) external returns (string memory input);
+    function promptAddress(string calldata promptText) external returns (address);
+    function promptSecret(string calldata promptText) external returns (string memory input);
+    function promptUint(string calldata promptText) external returns (uint256);
     function readCallers() external returns (CallerMode callerMode, address msgSender, address txOrigin);
     function readDir(string calldata path) external view returns (DirEntry[] memory entries);
     function readDir(string calldata path, uint64 maxDepth) external view returns (DirEntry[] memory entries);
@@ -533,6 +605,7 @@ interface Vm {
     function setEnv(string calldata name, string calldata value) external;
     function setNonce(address account, uint64 newNonce) external;
     function setNonceUnsafe(address account, uint64 newNonce) external;
+    function setTraceVerbosity(uint8 verbosity) external;
     function signP256(uint256 privateKey, bytes32 digest) external pure returns (bytes32 r, bytes32 s);
     function sign(uint256 privateKey, bytes32 digest) external pure returns (uint8 v, bytes32 r, bytes32 s);
     function sign(Wallet calldata wallet, bytes32 digest) external returns (uint8 v, bytes32 r, bytes32 s);
@@ -577,6 +650,15 @@ interface Vm {
     function writeJson(string calldata json, string calldata path) external;
     function writeJson(string calldata json, string calldata path, string calldata valueKey) external;
     function writeLine(string calldata path, string calldata data) external;
+    function writeToml(string calldata json, string calldata path) external;
+    function writeToml(string calldata json, string calldata path, string calldata valueKey) external;
+    function zkGetL2ToL1Logs() external view returns (L2ToL1Log[] memory logs);
+    function zkGetL2ToL1Messages() external view returns (L2ToL1Message[] memory messages);
+    function zkIsBytecodeKnown(bytes32 bytecodeHash) external view returns (bool known);
+    function zkMarkBytecodeKnown(bytes32 bytecodeHash) external;
+    function zkMarkBytecodeUnknown(bytes32 bytecodeHash) external;
+    function zkRegisterAccount(address account) external;
+    function zkRegisterAccount(address account, bytes calldata signature) external;
     function zkRegisterContract(
         string calldata name,
         bytes32 evmBytecodeHash,
@@ -585,5 +667,15 @@ interface Vm {
         bytes32 zkBytecodeHash,
         bytes calldata zkDeployedBytecode
     ) external pure;
+    function zkRelayPriorityRequests() external returns (PriorityRequestResult[] memory results);
+    function zkSendL1Message(address sender, bytes calldata data) external;
+    function zkSetBatchInfo(uint256 number, uint256 timestamp) external;
+    function zkSetL2Block(uint256 number, uint256 timestamp) external;
+    function zkSetL2BlockHash(uint256 number, bytes32 hash) external;
+    function zkStartMessaging(address mailbox) external;
+    function zkStopMessaging() external;
+    function zkSystemCall(address target, bytes calldata data) external returns (bytes memory returnData);
+    function zkSystemMode(bool enable) external;
+    function zkUsePaymaster(address paymaster, bytes calldata paymasterInput) external;
     function zkVm(bool enable) external pure;
 }
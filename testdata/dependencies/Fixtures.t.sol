@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract DependencyToken {
+    uint256 public totalSupply;
+
+    function mint(uint256 amount) external {
+        totalSupply += amount;
+    }
+}
+
+contract ProtocolFixture {
+    Vm constant vm = Vm(address(uint160(uint256(keccak256("hevm cheat code")))));
+
+    DependencyToken public token;
+
+    function setUp() public {
+        token = new DependencyToken();
+        token.mint(100);
+        vm.label(address(token), "DependencyToken");
+        vm.warp(1000);
+    }
+}
+
+/// @custom:depends-on ProtocolFixture
+contract LiquidityFixture {
+    Vm constant vm = Vm(address(uint160(uint256(keccak256("hevm cheat code")))));
+
+    function setUp() public {
+        ProtocolFixture(vm.getFixture("ProtocolFixture")).token().mint(10);
+    }
+}
+
+/// @custom:depends-on LiquidityFixture
+contract FixtureDependentTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    DependencyToken token;
+
+    function setUp() public {
+        token = ProtocolFixture(vm.getFixture("ProtocolFixture")).token();
+    }
+
+    function testRunsOnFixtureState() public {
+        assertEq(token.totalSupply(), 110);
+        assertEq(block.timestamp, 1000);
+        assertEq(vm.getLabel(address(token)), "DependencyToken");
+    }
+
+    function testChangesAreNotShared() public {
+        token.mint(1);
+        assertEq(token.totalSupply(), 111);
+    }
+}
+
+/// @custom:depends-on dependencies/Fixtures.t.sol:ProtocolFixture
+contract FixtureSiblingTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testRunsOnFirstFixture() public {
+        DependencyToken token = ProtocolFixture(vm.getFixture("ProtocolFixture")).token();
+        assertEq(token.totalSupply(), 100);
+    }
+
+    function testUnknownFixture() public {
+        vm.expectRevert(bytes("the test suite doesn't depend on the fixture LiquidityFixture"));
+        vm.getFixture("LiquidityFixture");
+    }
+}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract FuzzAssumeTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testAssumeRejections(uint256 x) public {
+        vm.assume(x > 10 && x < 12);
+    }
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+struct FuzzSelectorWeight {
+    bytes4 selector;
+    uint256 weight;
+}
+
+contract WeightedHello {
+    bool public world = true;
+
+    function change() public {
+        world = true;
+    }
+
+    function real_change() public {
+        world = false;
+    }
+}
+
+contract TargetSelectorWeights is DSTest {
+    WeightedHello hello;
+
+    function setUp() public {
+        hello = new WeightedHello();
+    }
+
+    function targetSelectorWeights() public returns (FuzzSelectorWeight[] memory) {
+        FuzzSelectorWeight[] memory weights = new FuzzSelectorWeight[](2);
+        weights[0] = FuzzSelectorWeight(WeightedHello.change.selector, 10);
+        // a weight of 0 never selects the function
+        weights[1] = FuzzSelectorWeight(WeightedHello.real_change.selector, 0);
+        return weights;
+    }
+
+    function invariantTrueWorld() public {
+        require(hello.world() == true, "false world");
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract BudgetInlineConf is DSTest {
+    /// forge-config: default.max_duration = 1h
+    function testWithinBudget() public {
+        require(true, "this is not going to revert");
+    }
+
+    /**
+     * forge-config: default.max_duration = 0
+     * forge-config: default.max_duration_warn_only = true
+     */
+    function testInlineConfBudgetWarn(uint8 x) public {
+        require(true, "this is not going to revert");
+    }
+}
@@ -0,0 +1,41 @@
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+
+contract LargeReturner {
+    function large() public pure returns (bytes memory) {
+        return new bytes(1024);
+    }
+
+    function largeRevert() public pure {
+        revert(string(new bytes(1024)));
+    }
+}
+
+contract ReturnDataLimitTest is DSTest {
+    LargeReturner returner;
+
+    function setUp() public {
+        returner = new LargeReturner();
+    }
+
+    function testLargeReturn() public {
+        assertEq(returner.large().length, 1024);
+    }
+
+    function testLargeRevert() public {
+        try returner.largeRevert() {
+            fail();
+        } catch (bytes memory reason) {
+            assertGt(reason.length, 1024);
+        }
+    }
+
+    function testLargeRevertFailing() public {
+        returner.largeRevert();
+    }
+
+    function testFuzzLargeRevertFailing(uint256) public {
+        returner.largeRevert();
+    }
+}
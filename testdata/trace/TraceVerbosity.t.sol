@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract TracedCounter {
+    uint256 public number;
+
+    function hidden() public {
+        number += 1;
+    }
+
+    function traced() public {
+        number += 2;
+    }
+}
+
+contract TraceVerbosityTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+    TracedCounter counter;
+
+    function setUp() public {
+        counter = new TracedCounter();
+    }
+
+    function testRaiseTraceVerbosity() public {
+        counter.hidden();
+        vm.setTraceVerbosity(4);
+        counter.traced();
+        vm.setTraceVerbosity(0);
+        counter.hidden();
+    }
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract AssumedCounter {
+    uint256 public count;
+
+    function increment() public {
+        count++;
+    }
+}
+
+contract ZkAssumeTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testZkAssumeRejections(uint256 x) public {
+        vm.zkVm(true);
+        AssumedCounter counter = new AssumedCounter();
+        counter.increment();
+        vm.assume(x > 10 && x < 12);
+    }
+}
@@ -4,6 +4,42 @@ pragma solidity ^0.8.18;
 import "ds-test/test.sol";
 import "../cheats/Vm.sol";
 
+contract ZkSlots {
+    uint256 public a;
+    uint256 public b;
+
+    function set(uint256 newA, uint256 newB) public {
+        a = newA;
+        b = newB;
+    }
+}
+
+contract ZkSlot {
+    uint256 public value;
+
+    function set(uint256 newValue) public {
+        value = newValue;
+    }
+}
+
+interface ISystemContext {
+    function getL2BlockNumberAndTimestamp() external view returns (uint128 number, uint128 timestamp);
+
+    function getBatchNumberAndTimestamp() external view returns (uint128 number, uint128 timestamp);
+}
+
+contract ZkSystemContextReader {
+    ISystemContext constant SYSTEM_CONTEXT = ISystemContext(address(0x800b));
+
+    function l2Block() public view returns (uint128, uint128) {
+        return SYSTEM_CONTEXT.getL2BlockNumberAndTimestamp();
+    }
+
+    function batch() public view returns (uint128, uint128) {
+        return SYSTEM_CONTEXT.getBatchNumberAndTimestamp();
+    }
+}
+
 contract ZkCheatcodesTest is DSTest {
     Vm constant vm = Vm(HEVM_ADDRESS);
 
@@ -76,4 +112,146 @@ contract ZkCheatcodesTest is DSTest {
         uint8 number = abi.decode(output, (uint8));
         require(number == 10, "era etched code incorrect");
     }
+
+    function testZkCheatcodesSetL2BlockAndBatchInfo() public {
+        vm.zkVm(true);
+        ZkSystemContextReader reader = new ZkSystemContextReader();
+        vm.roll(7);
+        vm.warp(70);
+
+        vm.zkSetL2Block(100, 5000);
+        (uint128 number, uint128 timestamp) = reader.l2Block();
+        require(number == 100 && timestamp == 5000, "L2 block not set");
+        (number, timestamp) = reader.l2Block();
+        require(number == 101 && timestamp == 5001, "L2 block not advanced");
+
+        vm.zkSetBatchInfo(40, 6000);
+        (number, timestamp) = reader.batch();
+        require(number == 40 && timestamp == 6000, "batch not set");
+        (number, timestamp) = reader.l2Block();
+        require(number == 103 && timestamp == 6001, "L2 block not advanced with the batch");
+        require(block.number == 7 && block.timestamp == 70, "roll and warp changed");
+    }
+
+    function testZkCheatcodesCopyStorage() public {
+        vm.zkVm(true);
+        ZkSlots source = new ZkSlots();
+        source.set(1, 0);
+        ZkSlots target = new ZkSlots();
+        target.set(0, 2);
+
+        vm.copyStorage(address(source), address(target));
+        require(target.a() == 1, "era slot of the source not copied");
+        require(target.b() == 0, "era slot of the target not cleared");
+
+        vm.zkVm(false);
+        require(target.a() == 1, "evm slot of the source not copied");
+    }
+
+    function testZkCheatcodesComputeCreate2Address() public {
+        bytes32 salt = bytes32(uint256(42));
+        bytes32 initCodeHash = keccak256(type(ZkSlot).creationCode);
+        address evmAddress = vm.computeCreate2Address(salt, initCodeHash, address(this));
+
+        vm.zkVm(true);
+        address eraAddress = vm.computeCreate2Address(salt, initCodeHash, address(this));
+        require(eraAddress != evmAddress, "era address derived as in the evm");
+
+        ZkSlot slot = new ZkSlot{salt: salt}();
+        require(address(slot) == eraAddress, "era create2 address mismatch");
+
+        // the constructor arguments can't be recovered from the hash of the init code
+        bytes32 withArgsHash = keccak256(abi.encodePacked(type(ZkSlot).creationCode, uint256(1)));
+        try vm.computeCreate2Address(salt, withArgsHash, address(this)) returns (address) {
+            revert("era address derived from an unknown init code hash");
+        } catch {}
+    }
+
+    function exposed_zkSet(ZkSlot slot, uint256 expected, uint256 value) public {
+        vm.expectStorageWrite(address(slot), bytes32(0), bytes32(expected));
+        slot.set(value);
+    }
+
+    function testZkCheatcodesExpectStorageWrite() public {
+        vm.zkVm(true);
+        ZkSlots slots = new ZkSlots();
+
+        vm.expectStorageWrite(address(slots), bytes32(0), bytes32(uint256(1)));
+        vm.expectStorageWrite(address(slots), bytes32(uint256(1)), bytes32(uint256(2)));
+        slots.set(1, 2);
+    }
+
+    function testZkCheatcodesExpectStorageWriteWrongValue() public {
+        vm.zkVm(true);
+        ZkSlot slot = new ZkSlot();
+
+        string memory slotAddress = vm.toString(address(slot));
+        vm.expectRevert(
+            bytes(
+                string.concat(
+                    "expected a write of ",
+                    vm.toString(bytes32(uint256(2))),
+                    " to slot ",
+                    vm.toString(bytes32(0)),
+                    " of ",
+                    slotAddress,
+                    ", but the writes to ",
+                    slotAddress,
+                    " were: ",
+                    vm.toString(bytes32(0)),
+                    " = ",
+                    vm.toString(bytes32(uint256(3)))
+                )
+            )
+        );
+        this.exposed_zkSet(slot, 2, 3);
+    }
+}
+
+interface IAccountCodeStorage {
+    function getRawCodeHash(address account) external view returns (bytes32 codeHash);
+}
+
+contract ZkCodeHashReader {
+    IAccountCodeStorage constant ACCOUNT_CODE_STORAGE = IAccountCodeStorage(address(0x8002));
+
+    function codeHash(address account) public view returns (bytes32) {
+        return ACCOUNT_CODE_STORAGE.getRawCodeHash(account);
+    }
+}
+
+/// The pubdata published by these tests is compared by `test_zk_bytecode_known_published`: a
+/// bytecode marked unknown is published again by its next deployment, one marked known isn't.
+contract ZkBytecodeKnownTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function deployedHash() internal returns (bytes32 zkBytecodeHash) {
+        vm.zkVm(true);
+        ZkCodeHashReader reader = new ZkCodeHashReader();
+        zkBytecodeHash = reader.codeHash(address(new ZkSlot()));
+        require(vm.zkIsBytecodeKnown(zkBytecodeHash), "deployed bytecode unknown");
+    }
+
+    function testZkBytecodeKnownKept() public {
+        deployedHash();
+        new ZkSlot();
+    }
+
+    function testZkBytecodeKnownRepublished() public {
+        bytes32 zkBytecodeHash = deployedHash();
+
+        vm.zkMarkBytecodeUnknown(zkBytecodeHash);
+        require(!vm.zkIsBytecodeKnown(zkBytecodeHash), "bytecode known after marking unknown");
+        new ZkSlot();
+        require(vm.zkIsBytecodeKnown(zkBytecodeHash), "bytecode unknown after publishing it again");
+    }
+
+    function testZkBytecodeKnownMarked() public {
+        bytes32 zkBytecodeHash = deployedHash();
+
+        vm.zkMarkBytecodeUnknown(zkBytecodeHash);
+        vm.zkMarkBytecodeKnown(zkBytecodeHash);
+        require(vm.zkIsBytecodeKnown(zkBytecodeHash), "bytecode unknown after marking known");
+        new ZkSlot();
+    }
 }
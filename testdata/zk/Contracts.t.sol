@@ -84,6 +84,24 @@ contract CustomStorage {
     }
 }
 
+contract NumberFactory {
+    Number public number;
+
+    constructor() {
+        number = new Number();
+    }
+
+    function deployCustomNumber(uint8 _value) public returns (CustomNumber) {
+        return new CustomNumber(_value);
+    }
+}
+
+contract NestedNumberFactory {
+    function deployFactory() public returns (NumberFactory) {
+        return new NumberFactory();
+    }
+}
+
 contract ZkContractsTest is DSTest {
     Vm constant vm = Vm(HEVM_ADDRESS);
 
@@ -191,6 +209,39 @@ contract ZkContractsTest is DSTest {
         assertEq(60, greeter.getAge());
     }
 
+    function testZkContractsFactoryDeps() public {
+        vm.zkVm(true);
+        NumberFactory factory = new NumberFactory();
+        require(factory.number().ten() == 10, "contract deployed in constructor value mismatch");
+
+        CustomNumber custom = factory.deployCustomNumber(20);
+        require(custom.number() == 20, "contract deployed in call value mismatch");
+    }
+
+    function testZkContractsNestedFactoryDeps() public {
+        vm.zkVm(true);
+        NestedNumberFactory nested = new NestedNumberFactory();
+        NumberFactory factory = nested.deployFactory();
+        require(factory.number().ten() == 10, "contract deployed by nested factory value mismatch");
+    }
+
+    function testZkContractsMalformedConstructorInput() public {
+        vm.zkVm(true);
+        bytes memory trailing = bytes.concat(type(CustomNumber).creationCode, abi.encode(uint8(20), uint256(1)));
+        require(_create(trailing) == address(0), "deployed with trailing constructor input");
+
+        require(_create(type(CustomNumber).creationCode) == address(0), "deployed without constructor input");
+
+        bytes memory exact = bytes.concat(type(CustomNumber).creationCode, abi.encode(uint8(20)));
+        require(CustomNumber(_create(exact)).number() == 20, "deployed contract value mismatch");
+    }
+
+    function _create(bytes memory initCode) private returns (address deployed) {
+        assembly {
+            deployed := create(0, add(initCode, 0x20), mload(initCode))
+        }
+    }
+
     function _computeCreate2Address(
         address sender,
         bytes32 salt,
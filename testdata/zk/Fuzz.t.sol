@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract FuzzedCounter {
+    uint256 public count;
+
+    function add(uint8 x) public {
+        count += x;
+    }
+}
+
+contract ZkFuzzTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+    FuzzedCounter counter;
+
+    function setUp() public {
+        vm.zkVm(true);
+        counter = new FuzzedCounter();
+    }
+
+    function testZkFuzzErgs(uint8 x) public {
+        counter.add(x);
+        assertEq(counter.count(), x);
+    }
+}
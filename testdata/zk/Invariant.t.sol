@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+
+interface INonceHolder {
+    function getMinNonce(address _address) external view returns (uint256);
+}
+
+INonceHolder constant NONCE_HOLDER = INonceHolder(address(0x8003));
+
+// Only executes in EraVM, where the bootloader increases the nonce of the sender of each transaction.
+contract ZkInvariantHandler {
+    mapping(address => uint256) public calls;
+    address[] public senders;
+
+    function call() public {
+        if (calls[msg.sender] == 0) {
+            senders.push(msg.sender);
+        }
+        calls[msg.sender] += 1;
+        require(NONCE_HOLDER.getMinNonce(msg.sender) >= calls[msg.sender], "nonce not increased");
+    }
+
+    function sendersCount() public view returns (uint256) {
+        return senders.length;
+    }
+}
+
+contract ZkInvariantTest is DSTest {
+    ZkInvariantHandler handler;
+
+    function setUp() public {
+        handler = new ZkInvariantHandler();
+    }
+
+    /// forge-config: default.invariant.runs = 10
+    /// forge-config: default.invariant.depth = 10
+    /// forge-config: default.invariant.fail-on-revert = true
+    function invariantZkSenderNonces() public {
+        for (uint256 i = 0; i < handler.sendersCount(); i++) {
+            address sender = handler.senders(i);
+            require(NONCE_HOLDER.getMinNonce(sender) >= handler.calls(sender), "nonce mismatch");
+        }
+    }
+}
+
+contract ZkInvariantBreakable {
+    bool public armed;
+    bool public broken;
+
+    function arm() public {
+        armed = true;
+    }
+
+    function idle() public {}
+
+    function fire() public {
+        if (armed) {
+            broken = true;
+        }
+    }
+}
+
+contract ZkInvariantShrinkTest is DSTest {
+    ZkInvariantBreakable breakable;
+
+    function setUp() public {
+        breakable = new ZkInvariantBreakable();
+    }
+
+    /// forge-config: default.invariant.runs = 10
+    /// forge-config: default.invariant.depth = 10
+    function invariantZkNotBroken() public {
+        require(!breakable.broken(), "broken");
+    }
+}
+
+struct FuzzSelectorWeight {
+    bytes4 selector;
+    uint256 weight;
+}
+
+// Counts its calls in EraVM, where the bootloader increases the nonce of their sender.
+contract ZkWeightedHandler {
+    uint256 public weighted;
+    bool public broken;
+
+    function weightedCall() public {
+        require(NONCE_HOLDER.getMinNonce(msg.sender) > 0, "not called in EraVM");
+        weighted += 1;
+    }
+
+    function neverCalled() public {
+        broken = true;
+    }
+}
+
+contract ZkSelectorWeightsTest is DSTest {
+    ZkWeightedHandler handler;
+
+    function setUp() public {
+        handler = new ZkWeightedHandler();
+    }
+
+    function targetSelectorWeights() public returns (FuzzSelectorWeight[] memory) {
+        FuzzSelectorWeight[] memory weights = new FuzzSelectorWeight[](2);
+        weights[0] = FuzzSelectorWeight(ZkWeightedHandler.weightedCall.selector, 10);
+        // a weight of 0 never selects the function
+        weights[1] = FuzzSelectorWeight(ZkWeightedHandler.neverCalled.selector, 0);
+        return weights;
+    }
+
+    /// forge-config: default.invariant.runs = 10
+    /// forge-config: default.invariant.depth = 10
+    /// forge-config: default.invariant.fail-on-revert = true
+    function invariantZkNeverCalled() public {
+        require(!handler.broken(), "never called function was called");
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+interface IERC20 {
+    function totalSupply() external view returns (uint256);
+    function balanceOf(address account) external view returns (uint256);
+}
+
+// Launched forking zkSync Era mainnet, with the calls executed in EraVM.
+contract ZkLaunchForkTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    uint256 constant ERA_CHAIN_ID = 324;
+
+    // The bridged WETH of zkSync Era mainnet
+    IERC20 constant WETH = IERC20(0x5AEa5775959fBC2557Cc8789bC1bf90A239D9a91);
+
+    function testZkLaunchForkChain() public {
+        require(block.chainid == ERA_CHAIN_ID, "not forking zkSync Era");
+        require(block.number > 0, "no fork block");
+    }
+
+    function testZkLaunchForkContractCall() public {
+        // the EraVM bytecode of the fork contract only executes in EraVM
+        require(WETH.totalSupply() > 0, "no WETH supply");
+        require(WETH.balanceOf(address(this)) == 0, "unexpected WETH balance");
+    }
+
+    function testZkLaunchForkMulticallStatic() public {
+        // zkSync Era has no Multicall3, the calls are batched JSON-RPC calls on the fork block
+        Vm.StaticCall[] memory calls = new Vm.StaticCall[](2);
+        calls[0] = Vm.StaticCall(address(WETH), abi.encodeCall(IERC20.totalSupply, ()));
+        calls[1] = Vm.StaticCall(address(WETH), abi.encodeWithSignature("withdraw(uint256)", 1));
+        Vm.StaticCallResult[] memory results = vm.multicallStatic(calls);
+
+        require(results.length == 2, "missing results");
+        require(results[0].success, "totalSupply failed");
+        require(abi.decode(results[0].returnData, (uint256)) == WETH.totalSupply(), "not the fork state");
+        require(!results[1].success, "withdraw succeeded");
+    }
+}
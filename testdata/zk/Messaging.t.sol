@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+interface IMailbox {
+    struct L2Message {
+        uint16 txNumberInBatch;
+        address sender;
+        bytes data;
+    }
+
+    function requestL2Transaction(
+        address _contractL2,
+        uint256 _l2Value,
+        bytes calldata _calldata,
+        uint256 _l2GasLimit,
+        uint256 _l2GasPerPubdataByteLimit,
+        bytes[] calldata _factoryDeps,
+        address _refundRecipient
+    ) external payable returns (bytes32 canonicalTxHash);
+
+    function proveL2MessageInclusion(
+        uint256 _batchNumber,
+        uint256 _index,
+        L2Message calldata _message,
+        bytes32[] calldata _proof
+    ) external view returns (bool);
+}
+
+/// The L1 -> L2 transaction of a priority request, as hashed by the `Mailbox`.
+struct L2CanonicalTransaction {
+    uint256 txType;
+    uint256 from;
+    uint256 to;
+    uint256 gasLimit;
+    uint256 gasPerPubdataByteLimit;
+    uint256 maxFeePerGas;
+    uint256 maxPriorityFeePerGas;
+    uint256 paymaster;
+    uint256 nonce;
+    uint256 value;
+    uint256[4] reserved;
+    bytes data;
+    bytes signature;
+    uint256[] factoryDeps;
+    bytes paymasterInput;
+    bytes reservedDynamic;
+}
+
+interface IL1Messenger {
+    function sendToL1(bytes calldata _message) external returns (bytes32);
+}
+
+contract L2Receiver {
+    address public lastSender;
+    uint256 public lastValue;
+
+    function receiveFromL1(uint256 value) public {
+        lastSender = msg.sender;
+        lastValue = value;
+    }
+
+    function sendToL1(bytes memory message) public {
+        IL1Messenger(address(0x8008)).sendToL1(message);
+    }
+}
+
+contract ZkMessagingTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    IMailbox constant MAILBOX = IMailbox(0x32400084C286CF3E17e7B677ea9583e60a000324);
+    address constant ALIAS_OFFSET = 0x1111000000000000000000000000000000001111;
+
+    L2Receiver receiver;
+
+    function setUp() public {
+        vm.zkStartMessaging(address(MAILBOX));
+
+        vm.zkVm(true);
+        receiver = new L2Receiver();
+        vm.zkVm(false);
+    }
+
+    function testZkMessagingL1ToL2() public {
+        bytes32 txHash = MAILBOX.requestL2Transaction(
+            address(receiver),
+            0,
+            abi.encodeWithSignature("receiveFromL1(uint256)", 42),
+            1000000,
+            800,
+            new bytes[](0),
+            address(this)
+        );
+        address expectedSender;
+        unchecked {
+            expectedSender = address(uint160(address(this)) + uint160(ALIAS_OFFSET));
+        }
+        uint256 l2GasPrice = (17 * tx.gasprice + 799) / 800;
+        if (l2GasPrice < 500_000_000) {
+            l2GasPrice = 500_000_000;
+        }
+        L2CanonicalTransaction memory transaction = L2CanonicalTransaction({
+            txType: 255,
+            from: uint256(uint160(expectedSender)),
+            to: uint256(uint160(address(receiver))),
+            gasLimit: 1000000,
+            gasPerPubdataByteLimit: 800,
+            maxFeePerGas: l2GasPrice,
+            maxPriorityFeePerGas: 0,
+            paymaster: 0,
+            nonce: 0,
+            value: 0,
+            reserved: [uint256(0), uint256(uint160(expectedSender)), 0, 0],
+            data: abi.encodeWithSignature("receiveFromL1(uint256)", 42),
+            signature: new bytes(0),
+            factoryDeps: new uint256[](0),
+            paymasterInput: new bytes(0),
+            reservedDynamic: new bytes(0)
+        });
+        require(txHash == keccak256(abi.encode(transaction)), "non canonical tx hash");
+
+        vm.zkVm(true);
+        require(receiver.lastSender() == expectedSender, "sender was not aliased");
+        require(receiver.lastValue() == 42, "priority request was not relayed");
+    }
+
+    function testZkMessagingL2ToL1() public {
+        vm.zkVm(true);
+        receiver.sendToL1("hello L1");
+        vm.zkVm(false);
+
+        Vm.L2ToL1Message[] memory messages = vm.zkGetL2ToL1Messages();
+        require(messages.length == 1, "message was not collected");
+        require(messages[0].sender == address(receiver), "message sender mismatch");
+        require(keccak256(messages[0].data) == keccak256("hello L1"), "message data mismatch");
+
+        require(messages[0].batchNumber == 1, "message batch mismatch");
+        require(messages[0].proof.length == 14, "message proof is not a path of the logs tree");
+
+        IMailbox.L2Message memory message =
+            IMailbox.L2Message(messages[0].txNumberInBatch, address(receiver), "hello L1");
+        require(
+            MAILBOX.proveL2MessageInclusion(messages[0].batchNumber, messages[0].index, message, messages[0].proof),
+            "message not provable"
+        );
+        require(
+            !MAILBOX.proveL2MessageInclusion(messages[0].batchNumber, messages[0].index + 1, message, messages[0].proof),
+            "message provable at another index"
+        );
+
+        message.data = "unknown";
+        require(
+            !MAILBOX.proveL2MessageInclusion(messages[0].batchNumber, messages[0].index, message, messages[0].proof),
+            "unknown message provable"
+        );
+    }
+
+    function testZkMessagingProveUnknownBatch() public {
+        vm.zkSendL1Message(address(receiver), "sent by cheatcode");
+        Vm.L2ToL1Message[] memory messages = vm.zkGetL2ToL1Messages();
+
+        IMailbox.L2Message memory message = IMailbox.L2Message(0, address(receiver), "sent by cheatcode");
+        vm.expectRevert("batch 2 is not executed");
+        MAILBOX.proveL2MessageInclusion(2, 0, message, messages[0].proof);
+    }
+
+    function testZkMessagingL2ToL1Logs() public {
+        vm.zkVm(true);
+        receiver.sendToL1("hello L1");
+        vm.zkVm(false);
+
+        Vm.L2ToL1Log[] memory logs = vm.zkGetL2ToL1Logs();
+        require(logs.length == 1, "log was not collected");
+        require(logs[0].isService, "message log is not a service log");
+        require(logs[0].sender == address(0x8008), "log sender is not the messenger");
+        require(logs[0].key == bytes32(uint256(uint160(address(receiver)))), "log key mismatch");
+        require(logs[0].value == keccak256("hello L1"), "log value mismatch");
+    }
+
+    function testZkMessagingSendL1Message() public {
+        vm.zkSendL1Message(address(receiver), "sent by cheatcode");
+
+        Vm.L2ToL1Message[] memory messages = vm.zkGetL2ToL1Messages();
+        require(messages.length == 1, "message was not sent");
+        require(messages[0].sender == address(receiver), "message sender mismatch");
+
+        Vm.L2ToL1Log[] memory logs = vm.zkGetL2ToL1Logs();
+        require(logs.length == 1, "log was not sent");
+        require(logs[0].value == keccak256("sent by cheatcode"), "log value mismatch");
+
+        IMailbox.L2Message memory message = IMailbox.L2Message(0, address(receiver), "sent by cheatcode");
+        require(MAILBOX.proveL2MessageInclusion(1, 0, message, messages[0].proof), "message not provable");
+    }
+
+    function testZkMessagingRelayPriorityRequests() public {
+        bytes32 txHash = MAILBOX.requestL2Transaction(
+            address(receiver),
+            0,
+            abi.encodeWithSignature("receiveFromL1(uint256)", 42),
+            1000000,
+            800,
+            new bytes[](0),
+            address(this)
+        );
+        bytes32 failingTxHash = MAILBOX.requestL2Transaction(
+            address(receiver),
+            0,
+            abi.encodeWithSignature("unknown()"),
+            1000000,
+            800,
+            new bytes[](0),
+            address(this)
+        );
+
+        Vm.PriorityRequestResult[] memory results = vm.zkRelayPriorityRequests();
+        require(results.length == 2, "priority requests were not relayed");
+        require(results[0].txHash == txHash && results[0].success, "priority request failed");
+        require(results[1].txHash == failingTxHash && !results[1].success, "unknown call succeeded");
+        require(vm.zkRelayPriorityRequests().length == 0, "priority requests relayed twice");
+
+        vm.zkVm(true);
+        require(receiver.lastValue() == 42, "priority request was not relayed");
+    }
+}
+
+/// Relies on the `zk_messaging` config instead of `zkStartMessaging`.
+contract ZkConfiguredMessagingTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    IMailbox constant MAILBOX = IMailbox(0x32400084C286CF3E17e7B677ea9583e60a000324);
+
+    L2Receiver receiver;
+
+    function setUp() public {
+        vm.zkVm(true);
+        receiver = new L2Receiver();
+        vm.zkVm(false);
+    }
+
+    function testZkConfiguredMessagingRoundTrip() public {
+        MAILBOX.requestL2Transaction(
+            address(receiver),
+            0,
+            abi.encodeWithSignature("sendToL1(bytes)", bytes("relayed")),
+            1000000,
+            800,
+            new bytes[](0),
+            address(0)
+        );
+        Vm.PriorityRequestResult[] memory results = vm.zkRelayPriorityRequests();
+        require(results.length == 1 && results[0].success, "priority request was not relayed");
+
+        Vm.L2ToL1Message[] memory messages = vm.zkGetL2ToL1Messages();
+        require(messages.length == 1, "message was not collected");
+        IMailbox.L2Message memory message =
+            IMailbox.L2Message(messages[0].txNumberInBatch, address(receiver), "relayed");
+        require(
+            MAILBOX.proveL2MessageInclusion(messages[0].batchNumber, messages[0].index, message, messages[0].proof),
+            "message not provable"
+        );
+    }
+}
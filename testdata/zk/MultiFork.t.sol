@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+interface IERC20 {
+    function totalSupply() external view returns (uint256);
+}
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public {
+        count += 1;
+    }
+}
+
+// Mixes zkSync Era and L1 forks in the same tests, as cross-chain tests do.
+contract ZkMultiForkTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    uint256 constant ERA_FORK_BLOCK = 19579636;
+    uint256 constant ETH_FORK_BLOCK = 19225195;
+
+    // The WETH of Ethereum mainnet, and the one bridged to zkSync Era mainnet
+    IERC20 constant ETH_WETH = IERC20(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2);
+    IERC20 constant ERA_WETH = IERC20(0x5AEa5775959fBC2557Cc8789bC1bf90A239D9a91);
+
+    address constant TEST_ADDRESS = 0x6Eb28604685b1F182dAB800A1Bfa4BaFdBA8a79a;
+
+    uint256 forkEra;
+    uint256 forkEraNext;
+    uint256 forkEth;
+
+    function setUp() public {
+        forkEra = vm.createFork("https://mainnet.era.zksync.io", ERA_FORK_BLOCK);
+        forkEraNext = vm.createFork("https://mainnet.era.zksync.io", ERA_FORK_BLOCK + 1);
+        forkEth = vm.createFork("https://eth-mainnet.alchemyapi.io/v2/Lc7oIGYeL_QvInzI0Wiu_pOZZDEKBrdf", ETH_FORK_BLOCK);
+    }
+
+    function testZkMultiForkL1AndL2State() public {
+        vm.selectFork(forkEth);
+        require(block.chainid == 1, "eth chain id mismatch");
+        uint256 l1Supply = ETH_WETH.totalSupply();
+        require(l1Supply > 0, "no eth WETH supply");
+
+        vm.selectFork(forkEra);
+        require(block.chainid == 324, "era chain id mismatch");
+        require(ERA_WETH.totalSupply() > 0, "no era WETH supply");
+
+        vm.selectFork(forkEth);
+        require(ETH_WETH.totalSupply() == l1Supply, "eth WETH supply mismatch");
+    }
+
+    function testZkMultiForkCreateSelectFork() public {
+        vm.createSelectFork("https://mainnet.era.zksync.io", ERA_FORK_BLOCK);
+        require(ERA_WETH.totalSupply() > 0, "no era WETH supply");
+
+        vm.createSelectFork("https://eth-mainnet.alchemyapi.io/v2/Lc7oIGYeL_QvInzI0Wiu_pOZZDEKBrdf", ETH_FORK_BLOCK);
+        require(ETH_WETH.totalSupply() > 0, "no eth WETH supply");
+    }
+
+    function testZkMultiForkPersistedNonceBetweenEraForks() public {
+        vm.makePersistent(TEST_ADDRESS);
+
+        vm.selectFork(forkEra);
+        vm.setNonce(TEST_ADDRESS, 10);
+
+        vm.selectFork(forkEraNext);
+        require(vm.getNonce(TEST_ADDRESS) == 10, "era nonce mismatch");
+
+        vm.selectFork(forkEth);
+        require(vm.getNonce(TEST_ADDRESS) == 10, "eth nonce mismatch");
+    }
+
+    function testZkMultiForkPersistedContractBetweenEraForks() public {
+        vm.selectFork(forkEra);
+        Counter counter = new Counter();
+        counter.increment();
+        vm.makePersistent(address(counter));
+
+        vm.selectFork(forkEraNext);
+        require(counter.count() == 1, "era contract value mismatch");
+        counter.increment();
+        require(counter.count() == 2, "era contract value mismatch");
+    }
+}
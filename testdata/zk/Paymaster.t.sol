@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+struct Transaction {
+    uint256 txType;
+    uint256 from;
+    uint256 to;
+    uint256 gasLimit;
+    uint256 gasPerPubdataByteLimit;
+    uint256 maxFeePerGas;
+    uint256 maxPriorityFeePerGas;
+    uint256 paymaster;
+    uint256 nonce;
+    uint256 value;
+    uint256[4] reserved;
+    bytes data;
+    bytes signature;
+    bytes32[] factoryDeps;
+    bytes paymasterInput;
+    bytes reservedDynamic;
+}
+
+enum ExecutionResult {
+    Revert,
+    Success
+}
+
+interface IPaymasterFlow {
+    function general(bytes calldata input) external;
+}
+
+contract ZkPaymaster {
+    address constant BOOTLOADER = address(0x8001);
+
+    uint256 public validations;
+
+    function validateAndPayForPaymasterTransaction(bytes32, bytes32, Transaction calldata transaction)
+        external
+        payable
+        returns (bytes4 magic, bytes memory context)
+    {
+        require(msg.sender == BOOTLOADER, "only the bootloader can validate");
+        require(bytes4(transaction.paymasterInput[0:4]) == IPaymasterFlow.general.selector, "unsupported flow");
+        validations += 1;
+
+        (bool success,) = BOOTLOADER.call{value: transaction.gasLimit * transaction.maxFeePerGas}("");
+        require(success, "failed paying the bootloader");
+        return (this.validateAndPayForPaymasterTransaction.selector, "");
+    }
+
+    function postTransaction(bytes calldata, Transaction calldata, bytes32, bytes32, ExecutionResult, uint256)
+        external
+        payable
+    {
+        require(msg.sender == BOOTLOADER, "only the bootloader can call postTransaction");
+    }
+
+    receive() external payable {}
+}
+
+contract ZkPaymasterCounter {
+    uint256 public number;
+
+    function increment() public {
+        number += 1;
+    }
+}
+
+contract ZkPaymasterTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    ZkPaymaster paymaster;
+    ZkPaymasterCounter counter;
+
+    function setUp() public {
+        vm.zkVm(true);
+        paymaster = new ZkPaymaster();
+        counter = new ZkPaymasterCounter();
+        vm.deal(address(paymaster), 10 ether);
+    }
+
+    function testZkPaymasterPaysFees() public {
+        uint256 balance = address(this).balance;
+
+        vm.zkUsePaymaster(address(paymaster), abi.encodeCall(IPaymasterFlow.general, ("")));
+        counter.increment();
+        counter.increment();
+        vm.zkUsePaymaster(address(0), "");
+
+        assertEq(counter.number(), 2);
+        assertEq(paymaster.validations(), 2);
+        assertEq(address(this).balance, balance);
+        assertLt(address(paymaster).balance, 10 ether);
+    }
+
+    function testZkPaymasterPaysDeployments() public {
+        vm.zkUsePaymaster(address(paymaster), abi.encodeCall(IPaymasterFlow.general, ("")));
+        ZkPaymasterCounter deployed = new ZkPaymasterCounter();
+        vm.zkUsePaymaster(address(0), "");
+
+        assertEq(paymaster.validations(), 1);
+        deployed.increment();
+        assertEq(deployed.number(), 1);
+        assertEq(paymaster.validations(), 1);
+    }
+
+    function testZkPaymasterValidationFails() public {
+        vm.zkUsePaymaster(address(paymaster), hex"12345678");
+        vm.expectRevert();
+        counter.increment();
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+
+contract ProxiedCounter {
+    uint256 public number;
+
+    function increment() public {
+        number += 1;
+    }
+}
+
+contract Eip1967Proxy {
+    bytes32 constant IMPLEMENTATION_SLOT = 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+
+    constructor(address implementation) {
+        assembly {
+            sstore(IMPLEMENTATION_SLOT, implementation)
+        }
+    }
+
+    fallback() external payable {
+        assembly {
+            let implementation := sload(IMPLEMENTATION_SLOT)
+            calldatacopy(0, 0, calldatasize())
+            let result := delegatecall(gas(), implementation, 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            switch result
+            case 0 { revert(0, returndatasize()) }
+            default { return(0, returndatasize()) }
+        }
+    }
+}
+
+contract ZkProxyTest is DSTest {
+    function testCallThroughProxy() public {
+        ProxiedCounter implementation = new ProxiedCounter();
+        ProxiedCounter proxy = ProxiedCounter(address(new Eip1967Proxy(address(implementation))));
+        proxy.increment();
+        assertEq(proxy.number(), 1);
+    }
+}
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract LimitedCounter {
+    uint256 public count;
+
+    function increment(uint256 times) public {
+        for (uint256 i = 0; i < times; i++) {
+            count++;
+        }
+    }
+}
+
+contract ZkResourceLimitsTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testZkResourceLimitsCall() public {
+        vm.zkVm(true);
+        LimitedCounter counter = new LimitedCounter();
+        counter.increment(100);
+        require(counter.count() == 100, "count mismatch");
+    }
+
+    function testZkResourceLimitsCaughtRevert() public {
+        vm.zkVm(true);
+        LimitedCounter counter = new LimitedCounter();
+        // even if the abort is caught, the test must fail
+        try counter.increment(100) {} catch {}
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract ZkLargeReturner {
+    function large() public pure returns (bytes memory) {
+        return new bytes(1024);
+    }
+
+    function largeRevert() public pure {
+        revert(string(new bytes(1024)));
+    }
+}
+
+contract ZkReturnDataLimitTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    ZkLargeReturner returner;
+
+    function setUp() public {
+        vm.zkVm(true);
+        returner = new ZkLargeReturner();
+    }
+
+    function testZkLargeReturn() public {
+        assertEq(returner.large().length, 1024);
+    }
+
+    function testZkLargeRevertFailing() public {
+        returner.largeRevert();
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract Consumer {
+    function consume(bytes calldata data) external pure returns (uint256) {
+        return data.length;
+    }
+}
+
+contract Holder {
+    uint256 public length;
+
+    constructor(bytes memory data) {
+        length = data.length;
+    }
+}
+
+contract ZkSizeLimitsTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testLargeCalldata() public {
+        Consumer consumer = new Consumer();
+        bytes memory data = new bytes(900_000);
+        assertEq(consumer.consume(data), 900_000);
+    }
+
+    function testLargeConstructorInput() public {
+        Holder holder = new Holder(new bytes(900_000));
+        assertEq(holder.length(), 900_000);
+    }
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+interface INonceHolder {
+    function getMinNonce(address _address) external view returns (uint256);
+
+    function increaseMinNonce(uint256 _value) external returns (uint256);
+}
+
+contract ZkSystemCallTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    INonceHolder constant NONCE_HOLDER = INonceHolder(address(0x8003));
+
+    function setUp() public {
+        vm.zkVm(true);
+    }
+
+    function testRequiresSystemCallFlag() public {
+        vm.expectRevert();
+        NONCE_HOLDER.increaseMinNonce(1);
+    }
+
+    function testSystemCall() public {
+        uint256 nonce = NONCE_HOLDER.getMinNonce(address(this));
+
+        bytes memory data =
+            vm.zkSystemCall(address(NONCE_HOLDER), abi.encodeCall(INonceHolder.increaseMinNonce, (1)));
+
+        assertEq(abi.decode(data, (uint256)), nonce + 1);
+        assertEq(NONCE_HOLDER.getMinNonce(address(this)), nonce + 1);
+    }
+
+    function testSystemCallRevert() public {
+        vm._expectCheatcodeRevert();
+        vm.zkSystemCall(address(NONCE_HOLDER), abi.encodeCall(INonceHolder.increaseMinNonce, (1 << 33)));
+    }
+
+    function testSystemMode() public {
+        uint256 nonce = NONCE_HOLDER.getMinNonce(address(this));
+
+        vm.zkSystemMode(true);
+        NONCE_HOLDER.increaseMinNonce(1);
+        vm.zkSystemMode(false);
+
+        assertEq(NONCE_HOLDER.getMinNonce(address(this)), nonce + 1);
+
+        vm.expectRevert();
+        NONCE_HOLDER.increaseMinNonce(1);
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../cheats/Vm.sol";
+
+contract Counter {
+    uint256 public number;
+
+    function increment() public {
+        number += 1;
+    }
+}
+
+contract ZkVmSwitchTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testZkVmSwitchKeepsDeployedContracts() public {
+        vm.zkVm(false);
+        Counter counter = new Counter();
+        counter.increment();
+
+        vm.zkVm(true);
+        counter.increment();
+        require(counter.number() == 2, "zk vm counter mismatch");
+
+        vm.zkVm(false);
+        counter.increment();
+        require(counter.number() == 3, "evm counter mismatch");
+    }
+
+    function testZkVmSwitchKeepsContractsDeployedOnZkVm() public {
+        vm.zkVm(true);
+        Counter counter = new Counter();
+        counter.increment();
+
+        vm.zkVm(false);
+        counter.increment();
+        require(counter.number() == 2, "evm counter mismatch");
+    }
+
+    function testZkVmSwitchKeepsBalances() public {
+        address alice = address(0xa11ce);
+        vm.zkVm(false);
+        vm.deal(alice, 1 ether);
+
+        vm.zkVm(true);
+        require(alice.balance == 1 ether, "zk vm balance mismatch");
+        vm.deal(alice, 2 ether);
+
+        vm.zkVm(false);
+        require(alice.balance == 2 ether, "evm balance mismatch");
+    }
+
+    function testZkVmSwitchComparesVms() public {
+        vm.zkVm(false);
+        Counter evmCounter = new Counter();
+        evmCounter.increment();
+
+        vm.zkVm(true);
+        Counter zkCounter = new Counter();
+        zkCounter.increment();
+
+        require(evmCounter.number() == zkCounter.number(), "vm results mismatch");
+    }
+}
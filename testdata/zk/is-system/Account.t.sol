@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.18;
+
+import "ds-test/test.sol";
+import "../../cheats/Vm.sol";
+
+struct Transaction {
+    uint256 txType;
+    uint256 from;
+    uint256 to;
+    uint256 gasLimit;
+    uint256 gasPerPubdataByteLimit;
+    uint256 maxFeePerGas;
+    uint256 maxPriorityFeePerGas;
+    uint256 paymaster;
+    uint256 nonce;
+    uint256 value;
+    uint256[4] reserved;
+    bytes data;
+    bytes signature;
+    bytes32[] factoryDeps;
+    bytes paymasterInput;
+    bytes reservedDynamic;
+}
+
+interface IAccount {
+    function validateTransaction(bytes32 txHash, bytes32 suggestedSignedHash, Transaction calldata transaction)
+        external
+        payable
+        returns (bytes4 magic);
+}
+
+interface INonceHolder {
+    function incrementMinNonceIfEquals(uint256 expectedNonce) external;
+}
+
+// Compiled in system mode, from the `is-system` path, to call the nonce holder with the system call
+// flag like `SystemContractsCaller` of the system contracts
+library SystemCall {
+    address constant SYSTEM_CALL_CALL_ADDRESS = address((1 << 16) - 11);
+
+    function call(address to, bytes memory data) internal returns (bool success) {
+        address callAddr = SYSTEM_CALL_CALL_ADDRESS;
+        uint256 dataStart;
+        assembly {
+            dataStart := add(data, 0x20)
+        }
+        uint256 farCallAbi =
+            (dataStart << 64) | (data.length << 96) | (uint256(uint32(gasleft())) << 192) | (1 << 248);
+        assembly {
+            success := call(to, callAddr, 0, 0, farCallAbi, 0, 0)
+        }
+    }
+}
+
+contract ZkAccount {
+    address constant BOOTLOADER = address(0x8001);
+    address constant NONCE_HOLDER = address(0x8003);
+    bytes32 constant SIGNATURE_HASH = keccak256("zk account signature");
+
+    uint256 public validations;
+    uint256 public executions;
+    bool public locked;
+
+    modifier onlyBootloader() {
+        require(msg.sender == BOOTLOADER, "only the bootloader can call the account");
+        _;
+    }
+
+    function lock() external {
+        locked = true;
+    }
+
+    function validateTransaction(bytes32, bytes32, Transaction calldata transaction)
+        external
+        payable
+        onlyBootloader
+        returns (bytes4 magic)
+    {
+        bool success =
+            SystemCall.call(NONCE_HOLDER, abi.encodeCall(INonceHolder.incrementMinNonceIfEquals, (transaction.nonce)));
+        require(success, "failed incrementing the nonce");
+        validations += 1;
+        bool signed = keccak256(transaction.signature) == SIGNATURE_HASH;
+        magic = locked || !signed ? bytes4(0) : IAccount.validateTransaction.selector;
+    }
+
+    function executeTransaction(bytes32, bytes32, Transaction calldata transaction)
+        external
+        payable
+        onlyBootloader
+    {
+        executions += 1;
+        (bool success, bytes memory data) = address(uint160(transaction.to)).call{value: transaction.value}(
+            transaction.data
+        );
+        if (!success) {
+            assembly {
+                revert(add(data, 0x20), mload(data))
+            }
+        }
+    }
+
+    function payForTransaction(bytes32, bytes32, Transaction calldata transaction) external payable onlyBootloader {
+        (bool success,) = BOOTLOADER.call{value: transaction.gasLimit * transaction.maxFeePerGas}("");
+        require(success, "failed paying the bootloader");
+    }
+
+    function prepareForPaymaster(bytes32, bytes32, Transaction calldata) external payable onlyBootloader {}
+
+    receive() external payable {}
+}
+
+contract ZkAccountCounter {
+    uint256 public number;
+    address public lastCaller;
+
+    function increment() public {
+        number += 1;
+        lastCaller = msg.sender;
+    }
+}
+
+contract ZkAccountTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    bytes constant SIGNATURE = "zk account signature";
+
+    ZkAccount account;
+    ZkAccountCounter counter;
+
+    function setUp() public {
+        vm.zkVm(true);
+        account = new ZkAccount();
+        counter = new ZkAccountCounter();
+        vm.deal(address(account), 10 ether);
+    }
+
+    function testZkAccountValidatesAndExecutes() public {
+        vm.zkRegisterAccount(address(account), SIGNATURE);
+
+        vm.prank(address(account));
+        counter.increment();
+
+        assertEq(counter.number(), 1);
+        assertEq(counter.lastCaller(), address(account));
+        assertEq(account.validations(), 1);
+        assertEq(account.executions(), 1);
+        assertLt(address(account).balance, 10 ether);
+    }
+
+    function testZkAccountDeploys() public {
+        vm.zkRegisterAccount(address(account), SIGNATURE);
+
+        vm.prank(address(account));
+        ZkAccountCounter deployed = new ZkAccountCounter();
+
+        assertEq(account.validations(), 1);
+        deployed.increment();
+        assertEq(deployed.number(), 1);
+    }
+
+    function testZkAccountValidationFails() public {
+        account.lock();
+        vm.zkRegisterAccount(address(account), SIGNATURE);
+
+        vm.expectRevert();
+        vm.prank(address(account));
+        counter.increment();
+    }
+
+    function testZkAccountRejectsInvalidSignature() public {
+        vm.zkRegisterAccount(address(account), "invalid signature");
+
+        vm.expectRevert();
+        vm.prank(address(account));
+        counter.increment();
+        assertEq(counter.number(), 0);
+    }
+
+    function testZkAccountRejectsDefaultSignature() public {
+        vm.zkRegisterAccount(address(account));
+
+        vm.expectRevert();
+        vm.prank(address(account));
+        counter.increment();
+    }
+
+    function testZkUnregisteredAccountUsesDefaultAccount() public {
+        vm.prank(address(account));
+        counter.increment();
+
+        assertEq(counter.lastCaller(), address(account));
+        assertEq(account.validations(), 0);
+        assertEq(account.executions(), 0);
+    }
+}